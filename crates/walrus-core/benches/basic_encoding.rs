@@ -9,13 +9,19 @@ use criterion::{AxisScale, BatchSize, BenchmarkId, Criterion, PlotConfiguration}
 use fastcrypto::hash::Blake2b256;
 use raptorq::SourceBlockEncodingPlan;
 use walrus_core::{
-    encoding::{Decoder as _, DecodingSymbol, Primary, RaptorQDecoder, RaptorQEncoder},
+    encoding::{
+        Decoder as _,
+        DecodingSymbol,
+        Primary,
+        RaptorQDecoder,
+        RaptorQEncoder,
+        ReedSolomonDecoder,
+        ReedSolomonEncoder,
+    },
     merkle::MerkleTree,
 };
 use walrus_test_utils::{random_data, random_subset};
 
-// TODO (WAL-610): Support both encoding types.
-
 const N_SHARDS: u16 = 1000;
 // Likely values for the number of source symbols for the primary and secondary encoding, which are
 // consistent with BFT.
@@ -121,6 +127,97 @@ fn basic_decoding(c: &mut Criterion) {
     group.finish();
 }
 
+fn reed_solomon_encoding(c: &mut Criterion) {
+    let mut group = c.benchmark_group("reed_solomon_encoding");
+    group.plot_config(PlotConfiguration::default().summary_scale(AxisScale::Logarithmic));
+
+    for symbol_count in SYMBOL_COUNTS {
+        for symbol_size in SYMBOL_SIZES {
+            let data_length = usize::from(symbol_size) * usize::from(symbol_count);
+            let data = random_data(data_length);
+            group.throughput(criterion::Throughput::Bytes(
+                u64::try_from(data_length).unwrap(),
+            ));
+
+            group.bench_with_input(
+                BenchmarkId::from_parameter(format!(
+                    "symbol_count={},symbol_size={}",
+                    symbol_count, symbol_size
+                )),
+                &(symbol_count, data),
+                |b, (symbol_count, data)| {
+                    b.iter(|| {
+                        let mut encoder = ReedSolomonEncoder::new(
+                            data,
+                            (*symbol_count).try_into().unwrap(),
+                            N_SHARDS.try_into().unwrap(),
+                        )
+                        .unwrap();
+                        let _encoded_symbols = encoder.encode_all();
+                    });
+                },
+            );
+        }
+    }
+
+    group.finish();
+}
+
+fn reed_solomon_decoding(c: &mut Criterion) {
+    let mut group = c.benchmark_group("reed_solomon_decoding");
+    group.plot_config(PlotConfiguration::default().summary_scale(AxisScale::Logarithmic));
+
+    for symbol_count in SYMBOL_COUNTS {
+        for symbol_size in SYMBOL_SIZES {
+            let data_length = usize::from(symbol_size) * usize::from(symbol_count);
+            let data = random_data(data_length);
+            group.throughput(criterion::Throughput::Bytes(
+                u64::try_from(data_length).unwrap(),
+            ));
+            let mut encoder = ReedSolomonEncoder::new(
+                &data,
+                symbol_count.try_into().unwrap(),
+                N_SHARDS.try_into().unwrap(),
+            )
+            .unwrap();
+            let symbols: Vec<_> = random_subset(
+                encoder
+                    .encode_all()
+                    .into_iter()
+                    .enumerate()
+                    .map(|(i, s)| DecodingSymbol::<Primary>::new(i as u16, s)),
+                usize::from(symbol_count),
+            )
+            .collect();
+            group.bench_with_input(
+                BenchmarkId::from_parameter(format!(
+                    "symbol_count={},symbol_size={}",
+                    symbol_count, symbol_size
+                )),
+                &(symbol_count, symbol_size, symbols),
+                |b, (symbol_count, symbol_size, symbols)| {
+                    b.iter_batched(
+                        || symbols.clone(),
+                        |symbols| {
+                            let mut decoder = ReedSolomonDecoder::new(
+                                (*symbol_count).try_into().unwrap(),
+                                N_SHARDS.try_into().unwrap(),
+                                (*symbol_size).try_into().unwrap(),
+                            );
+                            let decoded_data = &decoder.decode(symbols).unwrap();
+                            assert_eq!(data.len(), decoded_data.len());
+                            assert_eq!(&data, decoded_data);
+                        },
+                        BatchSize::SmallInput,
+                    );
+                },
+            );
+        }
+    }
+
+    group.finish();
+}
+
 fn flatten_faster(input: Vec<Vec<u8>>) -> Vec<u8> {
     assert!(!input.is_empty(), "input must not be empty");
     assert!(!input[0].is_empty(), "data must not be empty");
@@ -235,6 +332,8 @@ fn main() {
 
     basic_encoding(&mut criterion);
     basic_decoding(&mut criterion);
+    reed_solomon_encoding(&mut criterion);
+    reed_solomon_decoding(&mut criterion);
     flatten_symbols(&mut criterion);
     merkle_tree(&mut criterion);
 
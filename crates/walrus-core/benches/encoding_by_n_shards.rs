@@ -0,0 +1,105 @@
+// Copyright (c) Walrus Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Benchmarks for encoding, decoding, and hashing blobs for different numbers of shards and
+//! encoding types.
+
+use core::{num::NonZeroU16, time::Duration};
+
+use criterion::{AxisScale, BatchSize, BenchmarkId, Criterion, PlotConfiguration, Throughput};
+use walrus_core::{
+    encoding::{BlobIdHasher, EncodingConfig, EncodingConfigTrait as _, Primary},
+    EncodingType,
+};
+use walrus_test_utils::{random_data, random_subset};
+
+const N_SHARDS: [u16; 3] = [10, 100, 1000];
+
+const ENCODING_TYPES: [EncodingType; 2] = [EncodingType::RS2, EncodingType::RedStuffRaptorQ];
+
+const BLOB_SIZES: [(u64, &str); 3] = [(1 << 10, "1KiB"), (1 << 20, "1MiB"), (1 << 24, "16MiB")];
+
+fn blob_encoding_by_n_shards(c: &mut Criterion) {
+    for n_shards in N_SHARDS {
+        let encoding_config = EncodingConfig::new(NonZeroU16::new(n_shards).unwrap());
+
+        for encoding_type in ENCODING_TYPES {
+            let config = encoding_config.get_for_type(encoding_type);
+            let mut group = c.benchmark_group(format!("{encoding_type}/{n_shards}_shards"));
+            group.plot_config(PlotConfiguration::default().summary_scale(AxisScale::Logarithmic));
+
+            for (blob_size, size_str) in BLOB_SIZES {
+                let blob = random_data(blob_size.try_into().unwrap());
+                group.throughput(Throughput::Bytes(blob_size));
+
+                group.bench_with_input(
+                    BenchmarkId::new("encode_with_metadata", size_str),
+                    &blob,
+                    |b, blob| {
+                        b.iter(|| config.encode_with_metadata(blob).unwrap());
+                    },
+                );
+
+                group.bench_with_input(
+                    BenchmarkId::new("compute_metadata", size_str),
+                    &blob,
+                    |b, blob| {
+                        b.iter(|| config.compute_metadata(blob).unwrap());
+                    },
+                );
+
+                group.bench_with_input(
+                    BenchmarkId::new("blob_id_hasher", size_str),
+                    &blob,
+                    |b, blob| {
+                        b.iter(|| {
+                            let mut hasher = BlobIdHasher::new();
+                            hasher.update(blob);
+                            hasher.finalize(config.clone()).unwrap()
+                        });
+                    },
+                );
+
+                let (sliver_pairs, metadata) = config.encode_with_metadata(&blob).unwrap();
+                let slivers: Vec<_> = random_subset(
+                    sliver_pairs.into_iter().map(|pair| pair.primary),
+                    config.n_primary_source_symbols().get().into(),
+                )
+                .collect();
+
+                group.bench_with_input(
+                    BenchmarkId::new("decode_and_verify", size_str),
+                    &slivers,
+                    |b, slivers| {
+                        b.iter_batched(
+                            || slivers.clone(),
+                            |slivers| {
+                                let mut decoder =
+                                    config.get_blob_decoder::<Primary>(blob_size).unwrap();
+                                let (decoded_blob, _metadata) = decoder
+                                    .decode_and_verify(metadata.blob_id(), slivers)
+                                    .unwrap()
+                                    .unwrap();
+                                assert_eq!(blob, decoded_blob);
+                            },
+                            BatchSize::SmallInput,
+                        );
+                    },
+                );
+            }
+
+            group.finish();
+        }
+    }
+}
+
+fn main() {
+    let mut criterion = Criterion::default()
+        .configure_from_args()
+        .sample_size(10) // set sample size to the minimum to limit execution time
+        .warm_up_time(Duration::from_millis(10)); // warm up doesn't make much sense in this case
+
+    blob_encoding_by_n_shards(&mut criterion);
+
+    criterion.final_summary();
+}
@@ -0,0 +1,57 @@
+// Copyright (c) Walrus Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Parses and verifies blob metadata, both at once and while it is streamed in chunks.
+
+#![no_main]
+
+use arbitrary::Arbitrary;
+use libfuzzer_sys::fuzz_target;
+use walrus_core::{
+    metadata::{StreamingMetadataVerifier, UnverifiedBlobMetadataWithId},
+    BlobId,
+};
+use walrus_core_fuzz::fixture;
+
+#[derive(Debug, Arbitrary)]
+struct Input {
+    /// The BCS-encoded metadata, starting with the blob ID.
+    encoded: Vec<u8>,
+    /// The sizes of the chunks in which the metadata is streamed.
+    chunk_sizes: Vec<u8>,
+}
+
+fuzz_target!(|input: Input| {
+    let config = &fixture().config;
+    let Some(blob_id) = input.encoded.get(..BlobId::LENGTH) else {
+        return;
+    };
+    let blob_id = BlobId(
+        blob_id
+            .try_into()
+            .expect("the slice has the length of a blob ID"),
+    );
+
+    let verified = bcs::from_bytes::<UnverifiedBlobMetadataWithId>(&input.encoded)
+        .ok()
+        .and_then(|metadata| metadata.verify(config).ok());
+
+    // The blob ID expected by the streaming verifier is the one at the start of the metadata.
+    let mut verifier = StreamingMetadataVerifier::new(blob_id, config);
+    let mut remainder = input.encoded.as_slice();
+    let mut streamed = Ok(());
+    for &size in &input.chunk_sizes {
+        let (chunk, rest) = remainder.split_at(usize::from(size.max(1)).min(remainder.len()));
+        remainder = rest;
+        streamed = verifier.update(chunk);
+        if streamed.is_err() || remainder.is_empty() {
+            break;
+        }
+    }
+    let streamed = streamed.and_then(|()| {
+        verifier.update(remainder)?;
+        verifier.finish()
+    });
+
+    assert_eq!(streamed.ok(), verified);
+});
@@ -0,0 +1,80 @@
+// Copyright (c) Walrus Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Parses and verifies recovery symbols against the metadata of an encoded blob.
+
+#![no_main]
+
+use arbitrary::Arbitrary;
+use libfuzzer_sys::fuzz_target;
+use walrus_core::{
+    encoding::{GeneralRecoverySymbol, Primary, RecoverySymbol, SliverData},
+    merkle::MerkleProof,
+    SliverIndex,
+    SliverType,
+    DEFAULT_ENCODING,
+};
+use walrus_core_fuzz::{fixture, N_SHARDS};
+
+#[derive(Debug, Arbitrary)]
+struct Input {
+    /// The index of the sliver to be recovered.
+    target_index: u16,
+    /// Whether the sliver to be recovered is a primary sliver.
+    target_is_primary: bool,
+    /// The BCS-encoded recovery symbols.
+    encoded: Vec<u8>,
+}
+
+fuzz_target!(|input: Input| {
+    let fixture = fixture();
+    let metadata = fixture.metadata.metadata();
+    let target_index = SliverIndex(input.target_index);
+    let target_type = if input.target_is_primary {
+        SliverType::Primary
+    } else {
+        SliverType::Secondary
+    };
+
+    if let Ok(symbols) = bcs::from_bytes::<Vec<GeneralRecoverySymbol>>(&input.encoded) {
+        let results = GeneralRecoverySymbol::verify_batch(
+            &symbols,
+            metadata,
+            &fixture.config,
+            target_index,
+            target_type,
+        );
+        for (symbol, batch_result) in symbols.iter().zip(results) {
+            let result = symbol.verify(metadata, &fixture.config, target_index, target_type);
+            assert_eq!(result.is_ok(), batch_result.is_ok());
+            if result.is_ok() {
+                check_symbol_data(symbol);
+            }
+        }
+    }
+
+    if let Ok(symbol) = bcs::from_bytes::<RecoverySymbol<Primary, MerkleProof>>(&input.encoded) {
+        let _ = symbol.verify(metadata, &fixture.config, target_index);
+    }
+});
+
+/// Checks that a verified symbol is the symbol of the encoded blob with the same ID.
+fn check_symbol_data(symbol: &GeneralRecoverySymbol) {
+    let fixture = fixture();
+    let id = symbol.id();
+    assert!(id.primary_sliver_index().0 < N_SHARDS);
+    assert!(id.secondary_sliver_index().0 < N_SHARDS);
+    let primary: &SliverData<Primary> = fixture
+        .sliver_pairs
+        .iter()
+        .map(|pair| &pair.primary)
+        .find(|sliver| sliver.index == id.primary_sliver_index())
+        .expect("the fixture contains all primary slivers");
+    let expanded = primary
+        .recovery_symbols(&fixture.config.get_for_type(DEFAULT_ENCODING))
+        .expect("the slivers of the fixture can be expanded");
+    assert_eq!(
+        Some(symbol.data()),
+        expanded.get(id.secondary_sliver_index().as_usize())
+    );
+}
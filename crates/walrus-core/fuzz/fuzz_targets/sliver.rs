@@ -0,0 +1,53 @@
+// Copyright (c) Walrus Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Parses and verifies slivers against the metadata of an encoded blob.
+
+#![no_main]
+
+use std::num::NonZeroU16;
+
+use arbitrary::Arbitrary;
+use libfuzzer_sys::fuzz_target;
+use walrus_core::{
+    encoding::{EncodingAxis, Primary, Secondary, SliverData, SliverPair},
+    SliverPairIndex,
+};
+use walrus_core_fuzz::{fixture, N_SHARDS};
+
+#[derive(Debug, Arbitrary)]
+enum Input {
+    /// A BCS-encoded primary sliver.
+    Primary(Vec<u8>),
+    /// A BCS-encoded secondary sliver.
+    Secondary(Vec<u8>),
+}
+
+fuzz_target!(|input: Input| {
+    match input {
+        Input::Primary(encoded) => check_sliver::<Primary>(&encoded, |pair| &pair.primary),
+        Input::Secondary(encoded) => check_sliver::<Secondary>(&encoded, |pair| &pair.secondary),
+    }
+});
+
+/// Checks that a sliver passing the verification is the sliver of the encoded blob.
+fn check_sliver<T: EncodingAxis>(encoded: &[u8], expected: impl Fn(&SliverPair) -> &SliverData<T>) {
+    let fixture = fixture();
+    let Ok(sliver) = bcs::from_bytes::<SliverData<T>>(encoded) else {
+        return;
+    };
+    let _ = sliver.checksum();
+    if sliver
+        .verify(&fixture.config, fixture.metadata.metadata())
+        .is_err()
+    {
+        return;
+    }
+
+    let n_shards = NonZeroU16::new(N_SHARDS).expect("nonzero");
+    let SliverPairIndex(pair_index) = sliver.index.to_pair_index::<T>(n_shards);
+    assert_eq!(
+        &sliver,
+        expected(&fixture.sliver_pairs[usize::from(pair_index)])
+    );
+}
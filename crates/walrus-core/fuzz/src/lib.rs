@@ -0,0 +1,50 @@
+// Copyright (c) Walrus Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Shared fixtures of the fuzz targets of `walrus-core`.
+
+use std::{num::NonZeroU16, sync::OnceLock};
+
+use walrus_core::{
+    encoding::{EncodingConfig, SliverPair},
+    metadata::VerifiedBlobMetadataWithId,
+    DEFAULT_ENCODING,
+};
+
+/// The number of shards of the encoding config of the fixture.
+pub const N_SHARDS: u16 = 10;
+
+/// The length of the blob of the fixture.
+const BLOB_LENGTH: usize = 1000;
+
+/// An encoded blob against which the fuzzed inputs are verified.
+#[derive(Debug)]
+pub struct Fixture {
+    /// The encoding config.
+    pub config: EncodingConfig,
+    /// The unencoded blob.
+    pub blob: Vec<u8>,
+    /// The metadata of the blob.
+    pub metadata: VerifiedBlobMetadataWithId,
+    /// The sliver pairs of the blob, ordered by their index.
+    pub sliver_pairs: Vec<SliverPair>,
+}
+
+/// Returns the fixture, which is computed on the first call.
+pub fn fixture() -> &'static Fixture {
+    static FIXTURE: OnceLock<Fixture> = OnceLock::new();
+    FIXTURE.get_or_init(|| {
+        let config = EncodingConfig::new(NonZeroU16::new(N_SHARDS).expect("nonzero"));
+        let blob: Vec<u8> = (0..BLOB_LENGTH).map(|i| (i % 251) as u8).collect();
+        let (sliver_pairs, metadata) = config
+            .get_for_type(DEFAULT_ENCODING)
+            .encode_with_metadata(&blob)
+            .expect("the blob can be encoded");
+        Fixture {
+            config,
+            blob,
+            metadata,
+            sliver_pairs,
+        }
+    })
+}
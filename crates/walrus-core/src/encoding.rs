@@ -13,7 +13,14 @@ pub use basic_encoding::{
 };
 
 mod blob_encoding;
-pub use blob_encoding::{BlobDecoder, BlobDecoderEnum, BlobEncoder};
+pub use blob_encoding::{
+    BlobDecoder,
+    BlobDecoderEnum,
+    BlobEncoder,
+    BlobIdHasher,
+    ChunkedBlobEncoder,
+    SliverPairsWindow,
+};
 
 mod common;
 pub use common::{EncodingAxis, Primary, Secondary, MAX_SOURCE_SYMBOLS_PER_BLOCK, MAX_SYMBOL_SIZE};
@@ -27,12 +34,15 @@ pub use config::{
     max_sliver_size_for_n_secondary,
     max_sliver_size_for_n_shards,
     metadata_length_for_n_shards,
+    primary_sliver_size_for_n_shards,
     source_symbols_for_n_shards,
+    source_symbols_for_version,
     EncodingConfig,
     EncodingConfigEnum,
     EncodingConfigTrait,
     RaptorQEncodingConfig,
     ReedSolomonEncodingConfig,
+    ENCODING_VERSION,
 };
 
 mod errors;
@@ -40,7 +50,9 @@ pub use errors::{
     DataTooLargeError,
     DecodingVerificationError,
     EncodeError,
+    IncompleteSymbolsError,
     InvalidDataSizeError,
+    RangeDecodingError,
     RecoverySymbolError,
     SliverRecoveryError,
     SliverRecoveryOrVerificationError,
@@ -51,10 +63,19 @@ pub use errors::{
 };
 
 mod mapping;
-pub use mapping::{rotate_pairs, rotate_pairs_unchecked, SliverAssignmentError};
+pub use mapping::{
+    rotate_pairs,
+    rotate_pairs_unchecked,
+    rotation_offset,
+    shards_for_byte_range,
+    SliverAssignmentError,
+};
+
+mod range_decoding;
+pub use range_decoding::BlobRangeDecoder;
 
 mod slivers;
-pub use slivers::{PrimarySliver, SecondarySliver, SliverData, SliverPair};
+pub use slivers::{PrimarySliver, SecondarySliver, SliverChecksum, SliverData, SliverPair};
 
 mod symbols;
 pub use symbols::{
@@ -69,4 +90,12 @@ pub use symbols::{
     Symbols,
 };
 
+mod test_vectors;
+pub use test_vectors::{
+    blob_from_seed,
+    EncodingTestVector,
+    SliverPairTestVector,
+    TestVectorMismatchError,
+};
+
 mod utils;
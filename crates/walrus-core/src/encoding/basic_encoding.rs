@@ -10,7 +10,9 @@ pub mod raptorq;
 pub mod reed_solomon;
 
 /// Trait implemented for all basic (1D) decoders.
-pub trait Decoder {
+///
+/// Decoders are `Send`, so that the independent decoders of a blob can run on different threads.
+pub trait Decoder: Send {
     /// The type of the associated encoding configuration.
     type Config: EncodingConfigTrait;
 
@@ -1,6 +1,14 @@
 // Copyright (c) Walrus Foundation
 // SPDX-License-Identifier: Apache-2.0
 
+//! Reed-Solomon encoding based on the `reed-solomon-simd` crate.
+//!
+//! The encoding cannot be replaced by that of other erasure-coding libraries such as ISA-L. The
+//! repair symbols, and therefore the sliver hashes and blob IDs, depend on the field and the code
+//! construction: ISA-L's Reed-Solomon codes work over GF(2^8) and support at most 255 shards,
+//! whereas `reed-solomon-simd` uses codes over GF(2^16). Any other backend must produce exactly
+//! the symbols of `reed-solomon-simd`, which can be checked with the encoding test vectors.
+
 use alloc::vec::Vec;
 use core::{fmt, num::NonZeroU16};
 
@@ -182,7 +190,19 @@ impl Decoder for ReedSolomonDecoder {
         U: EncodingAxis,
     {
         let decoder = &mut self.decoder;
+        let expected_symbol_size: usize = self.symbol_size.get().into();
         for symbol in symbols.into_iter() {
+            let actual_symbol_size = symbol.len();
+            if actual_symbol_size != expected_symbol_size {
+                // Drop symbols of incorrect length and log a warning.
+                tracing::warn!(
+                    %symbol,
+                    expected_symbol_size,
+                    actual_symbol_size,
+                    "input to decoder has incorrect length",
+                );
+                continue;
+            }
             if symbol.index < self.n_source_symbols.get() {
                 self.source_symbols[usize::from(symbol.index)] = symbol.data.clone();
                 let _ = decoder.add_original_shard(symbol.index.into(), symbol.data);
@@ -320,4 +340,25 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn symbols_of_incorrect_length_are_ignored() -> Result {
+        let n_source_symbols = NonZeroU16::new(2).unwrap();
+        let n_shards = NonZeroU16::new(4).unwrap();
+        let data = [1, 2, 3, 4];
+        let mut encoder = ReedSolomonEncoder::new(&data, n_source_symbols, n_shards)?;
+        let mut symbols = encoder.encode_all().into_iter();
+        let mut decoder =
+            ReedSolomonDecoder::new(n_source_symbols, n_shards, encoder.symbol_size());
+
+        let first = DecodingSymbol::<Primary>::new(0, symbols.next().unwrap());
+        assert_eq!(decoder.decode([first]), None);
+
+        // A truncated copy of an already received symbol must not replace it.
+        let truncated = DecodingSymbol::<Primary>::new(0, vec![1]);
+        let second = DecodingSymbol::<Primary>::new(1, symbols.next().unwrap());
+        assert_eq!(decoder.decode([truncated, second]).unwrap(), data);
+
+        Ok(())
+    }
 }
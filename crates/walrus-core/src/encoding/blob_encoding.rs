@@ -2,9 +2,17 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use alloc::{vec, vec::Vec};
-use core::{cmp, marker::PhantomData, num::NonZeroU16, slice::Chunks};
+use core::{
+    cmp,
+    convert::Infallible,
+    fmt,
+    marker::PhantomData,
+    num::NonZeroU16,
+    ops::Range,
+    slice::Chunks,
+};
 
-use fastcrypto::hash::Blake2b256;
+use fastcrypto::hash::{Blake2b256, HashFunction as _};
 use tracing::{Level, Span};
 
 use super::{
@@ -23,7 +31,7 @@ use super::{
 };
 use crate::{
     encoding::config::EncodingConfigTrait as _,
-    merkle::{leaf_hash, MerkleTree},
+    merkle::{leaf_hash, leaf_hasher, MerkleTree, Node},
     metadata::{SliverPairMetadata, VerifiedBlobMetadataWithId},
     BlobId,
     SliverIndex,
@@ -194,6 +202,34 @@ impl<'a> BlobEncoder<'a> {
         self.symbol_size.get().into()
     }
 
+    fn window_symbol_size(symbol_bytes: &Range<usize>) -> NonZeroU16 {
+        NonZeroU16::new(
+            u16::try_from(symbol_bytes.len()).expect("windows are at most as large as a symbol"),
+        )
+        .expect("windows are not empty")
+    }
+
+    /// Returns the range of bytes of each symbol in the next window, along with the window read
+    /// from the blob, or `None` if all windows have already been encoded.
+    fn read_next_window<E>(
+        &self,
+        read_at: &mut impl FnMut(u64, &mut [u8]) -> Result<(), E>,
+    ) -> Result<Option<(Range<usize>, Vec<u8>)>, E> {
+        if self.is_finished() {
+            return Ok(None);
+        }
+        let _guard = self.span.enter();
+        let window_end = cmp::min(
+            self.next_window_start + self.window_size,
+            self.symbol_usize(),
+        );
+        let symbol_bytes = self.next_window_start..window_end;
+        tracing::debug!(?symbol_bytes, "encoding window of the blob");
+
+        let window = self.read_window(symbol_bytes.clone(), read_at)?;
+        Ok(Some((symbol_bytes, window)))
+    }
+
     /// Returns a reference to the symbol at the provided indices in the message matrix.
     ///
     /// The length of the returned slice can be lower than `self.symbol_size` if the blob needs to
@@ -264,6 +300,9 @@ struct ExpandedMessageMatrix<'a> {
 }
 
 impl<'a> ExpandedMessageMatrix<'a> {
+    /// The number of columns that are encoded together in [`Self::expand_all_columns`].
+    const COLUMNS_PER_BATCH: usize = 64;
+
     fn new(config: &'a EncodingConfigEnum<'a>, symbol_size: NonZeroU16, blob: &'a [u8]) -> Self {
         tracing::debug!("computing expanded message matrix");
         let matrix = vec![
@@ -294,39 +333,52 @@ impl<'a> ExpandedMessageMatrix<'a> {
         }
     }
 
+    #[cfg(test)]
     fn expanded_column_symbols(
         &'a self,
     ) -> impl Iterator<Item = impl ExactSizeIterator<Item = &'a [u8]> + 'a> {
-        (0..self.matrix.len()).map(move |col_index| {
-            self.matrix
-                .iter()
-                // Get the columns in reverse order `n_shards - col_index - 1`.
-                .map(move |row| {
-                    row[SliverPairIndex::try_from(col_index)
-                        .expect("size has already been checked")
-                        .to_sliver_index::<Secondary>(self.config.n_shards())
-                        .as_usize()]
-                    .as_ref()
-                })
-        })
+        (0..self.matrix.len()).map(move |col_index| self.expanded_column(col_index))
+    }
+
+    /// Returns the symbols of the secondary sliver with index `sliver_pair_index` in the expanded
+    /// matrix.
+    fn expanded_column(
+        &'a self,
+        sliver_pair_index: usize,
+    ) -> impl ExactSizeIterator<Item = &'a [u8]> + 'a {
+        // Get the columns in reverse order `n_shards - sliver_pair_index - 1`.
+        let col_index = SliverPairIndex::try_from(sliver_pair_index)
+            .expect("size has already been checked")
+            .to_sliver_index::<Secondary>(self.config.n_shards())
+            .as_usize();
+        self.matrix.iter().map(move |row| row[col_index].as_ref())
     }
 
     /// Expands all columns to completely fill the `n_shards * n_shards` expanded message matrix.
+    ///
+    /// The columns are encoded in batches of [`COLUMNS_PER_BATCH`][Self::COLUMNS_PER_BATCH]
+    /// columns, which bounds the memory needed for the repair symbols before they are copied into
+    /// the matrix.
     fn expand_all_columns(&mut self) {
-        for col_index in 0..self.config.n_shards().get().into() {
-            let mut column = Symbols::with_capacity(self.n_rows, self.symbol_size);
-            self.matrix.iter().take(self.n_rows).for_each(|row| {
-                let _ = column.extend(&row[col_index]);
+        let n_shards = self.config.n_shards_as_usize();
+        for batch_start in (0..n_shards).step_by(Self::COLUMNS_PER_BATCH) {
+            let batch_size = cmp::min(Self::COLUMNS_PER_BATCH, n_shards - batch_start);
+            let repair_columns = utils::map_indices(batch_size, |offset| {
+                let col_index = batch_start + offset;
+                let mut column = Symbols::with_capacity(self.n_rows, self.symbol_size);
+                self.matrix.iter().take(self.n_rows).for_each(|row| {
+                    let _ = column.extend(&row[col_index]);
+                });
+                self.config
+                    .encode_all_repair_symbols::<Primary>(column.data())
+                    .expect("size has already been checked")
             });
 
-            for (row_index, symbol) in self
-                .config
-                .encode_all_repair_symbols::<Primary>(column.data())
-                .expect("size has already been checked")
-                .into_iter()
-                .enumerate()
-            {
-                self.matrix[self.n_rows + row_index][col_index].copy_from_slice(&symbol);
+            for (offset, repair_symbols) in repair_columns.into_iter().enumerate() {
+                for (row_index, symbol) in repair_symbols.into_iter().enumerate() {
+                    self.matrix[self.n_rows + row_index][batch_start + offset]
+                        .copy_from_slice(&symbol);
+                }
             }
         }
     }
@@ -334,17 +386,18 @@ impl<'a> ExpandedMessageMatrix<'a> {
     /// Expands the first `source_symbols_primary` primary slivers (rows) to get all remaining
     /// secondary slivers.
     fn expand_rows_for_secondary(&mut self) {
-        for row in self.matrix.iter_mut().take(self.n_rows) {
-            for (col_index, symbol) in self
-                .config
-                .encode_all_repair_symbols::<Secondary>(&row[0..self.n_columns])
+        let config = self.config;
+        let n_columns = self.n_columns;
+        utils::for_each_indexed_mut(&mut self.matrix[..self.n_rows], |_, row| {
+            for (col_index, symbol) in config
+                .encode_all_repair_symbols::<Secondary>(&row[0..n_columns])
                 .expect("size has already been checked")
                 .into_iter()
                 .enumerate()
             {
-                row[self.n_columns + col_index].copy_from_slice(&symbol)
+                row[n_columns + col_index].copy_from_slice(&symbol)
             }
-        }
+        });
     }
 
     /// Computes the sliver pair metadata from the expanded message matrix.
@@ -352,32 +405,11 @@ impl<'a> ExpandedMessageMatrix<'a> {
         tracing::debug!("computing blob metadata and ID");
 
         let n_shards = self.config.n_shards_as_usize();
-        let mut leaf_hashes = Vec::with_capacity(n_shards * n_shards);
-        for row in 0..n_shards {
-            for col in 0..n_shards {
-                leaf_hashes.push(leaf_hash::<Blake2b256>(&self.matrix[row][col]));
-            }
-        }
+        let leaf_hashes = utils::map_indices(n_shards * n_shards, |index| {
+            leaf_hash::<Blake2b256>(&self.matrix[index / n_shards][index % n_shards])
+        });
 
-        let mut metadata = Vec::with_capacity(n_shards);
-        for sliver_index in 0..n_shards {
-            let primary_hash = MerkleTree::<Blake2b256>::build_from_leaf_hashes(
-                leaf_hashes[n_shards * sliver_index..n_shards * (sliver_index + 1)]
-                    .iter()
-                    .cloned(),
-            )
-            .root();
-            let secondary_hash = MerkleTree::<Blake2b256>::build_from_leaf_hashes(
-                (0..n_shards).map(|symbol_index| {
-                    leaf_hashes[n_shards * symbol_index + n_shards - 1 - sliver_index].clone()
-                }),
-            )
-            .root();
-            metadata.push(SliverPairMetadata {
-                primary_hash,
-                secondary_hash,
-            })
-        }
+        let metadata = sliver_pair_metadata(n_shards, &leaf_hashes);
 
         VerifiedBlobMetadataWithId::new_verified_from_metadata(
             metadata,
@@ -386,6 +418,15 @@ impl<'a> ExpandedMessageMatrix<'a> {
         )
     }
 
+    /// Updates the hash functions computing the leaf hashes of the symbols of the matrix with the
+    /// symbols, in row-major order.
+    fn update_leaf_hashers(&self, leaf_hashers: &mut [Blake2b256]) {
+        let n_shards = self.config.n_shards_as_usize();
+        utils::for_each_indexed_mut(leaf_hashers, |index, hasher| {
+            hasher.update(&self.matrix[index / n_shards][index % n_shards]);
+        });
+    }
+
     /// Writes the secondary metadata to the provided mutable slice.
     ///
     /// This is no longer used in the actual code and just kept for testing.
@@ -401,16 +442,16 @@ impl<'a> ExpandedMessageMatrix<'a> {
 
     /// Writes the secondary slivers to the provided mutable slice.
     fn write_secondary_slivers(&self, sliver_pairs: &mut [SliverPair]) {
-        sliver_pairs
-            .iter_mut()
-            .zip(self.expanded_column_symbols())
-            .for_each(|(sliver_pair, symbols)| {
-                for (target_slice, symbol) in
-                    sliver_pair.secondary.symbols.to_symbols_mut().zip(symbols)
-                {
-                    target_slice.copy_from_slice(symbol);
-                }
-            })
+        utils::for_each_indexed_mut(sliver_pairs, |index, sliver_pair| {
+            for (target_slice, symbol) in sliver_pair
+                .secondary
+                .symbols
+                .to_symbols_mut()
+                .zip(self.expanded_column(index))
+            {
+                target_slice.copy_from_slice(symbol);
+            }
+        })
     }
 
     /// Drops the part of the matrix that only contains recovery symbols.
@@ -450,6 +491,422 @@ impl<'a> ExpandedMessageMatrix<'a> {
     }
 }
 
+/// Computes the metadata of the sliver pairs from the leaf hashes of the symbols of the expanded
+/// message matrix, in row-major order.
+fn sliver_pair_metadata(n_shards: usize, leaf_hashes: &[Node]) -> Vec<SliverPairMetadata> {
+    utils::map_indices(n_shards, |sliver_index| {
+        let primary_hash = MerkleTree::<Blake2b256>::build_from_leaf_hashes(
+            leaf_hashes[n_shards * sliver_index..n_shards * (sliver_index + 1)]
+                .iter()
+                .cloned(),
+        )
+        .root();
+        let secondary_hash =
+            MerkleTree::<Blake2b256>::build_from_leaf_hashes((0..n_shards).map(|symbol_index| {
+                leaf_hashes[n_shards * symbol_index + n_shards - 1 - sliver_index].clone()
+            }))
+            .root();
+        SliverPairMetadata {
+            primary_hash,
+            secondary_hash,
+        }
+    })
+}
+
+/// Struct to encode a blob window by window, without holding the blob or its expansion in memory.
+///
+/// Each window comprises a range of bytes of all symbols of the message matrix. As the primary
+/// and secondary encodings operate independently on the bytes at each position of the symbols,
+/// expanding the message matrix restricted to these bytes yields the same bytes of the expanded
+/// symbols as expanding the complete matrix. The leaf hashes of the expanded symbols, and thereby
+/// the blob ID, are computed incrementally over the windows.
+///
+/// The memory required is dominated by the expansion of a single window, which is
+/// `n_shards * n_shards * window_size` bytes, and by the state of the `n_shards * n_shards` leaf
+/// hash functions.
+pub struct ChunkedBlobEncoder<'a> {
+    blob_size: u64,
+    /// The size of the encoded and decoded symbols.
+    symbol_size: NonZeroU16,
+    /// The number of bytes of each symbol that are encoded per window.
+    ///
+    /// Guaranteed to be non-zero and at most `symbol_size`.
+    window_size: usize,
+    /// The first byte of each symbol that is encoded in the next window.
+    next_window_start: usize,
+    /// The number of rows of the message matrix.
+    n_rows: usize,
+    /// The number of columns of the message matrix.
+    n_columns: usize,
+    /// The hash functions computing the leaf hashes of the symbols of the expanded message matrix,
+    /// in row-major order.
+    leaf_hashers: Vec<Blake2b256>,
+    /// Reference to the encoding configuration of this encoder.
+    config: EncodingConfigEnum<'a>,
+    /// A tracing span associated with this encoder.
+    span: Span,
+}
+
+impl fmt::Debug for ChunkedBlobEncoder<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ChunkedBlobEncoder")
+            .field("blob_size", &self.blob_size)
+            .field("symbol_size", &self.symbol_size)
+            .field("window_size", &self.window_size)
+            .field("next_window_start", &self.next_window_start)
+            .field("config", &self.config)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<'a> ChunkedBlobEncoder<'a> {
+    /// The alignment of the windows within the symbols.
+    ///
+    /// The Reed-Solomon encoding operates on blocks of 64 bytes of the symbols, so windows that
+    /// start at multiples of 64 bytes are encoded like the corresponding bytes of the complete
+    /// symbols.
+    pub const WINDOW_ALIGNMENT: u16 = 64;
+
+    /// The memory targeted for the expansion of a single window by
+    /// [`window_size_for_n_shards()`][Self::window_size_for_n_shards].
+    const TARGET_WINDOW_EXPANSION_BYTES: usize = 64 << 20;
+
+    /// Creates a new `ChunkedBlobEncoder` to encode a blob of `blob_size` bytes with the provided
+    /// configuration, encoding at most `window_size` bytes of each symbol at a time.
+    ///
+    /// The `window_size` is rounded up to a multiple of [`Self::WINDOW_ALIGNMENT`]. The windows
+    /// can then be encoded with [`encode_next_window()`][Self::encode_next_window].
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`DataTooLargeError`] if the blob is too large to be encoded.
+    pub fn new(
+        config: EncodingConfigEnum<'a>,
+        blob_size: u64,
+        window_size: NonZeroU16,
+    ) -> Result<Self, DataTooLargeError> {
+        tracing::debug!("creating new chunked blob encoder");
+        let symbol_size = utils::compute_symbol_size(
+            blob_size,
+            config.source_symbols_per_blob(),
+            config.encoding_type().required_alignment(),
+        )?;
+        let window_size = usize::from(window_size.get())
+            .next_multiple_of(Self::WINDOW_ALIGNMENT.into())
+            .min(symbol_size.get().into());
+        let n_shards = config.n_shards_as_usize();
+
+        Ok(Self {
+            blob_size,
+            symbol_size,
+            window_size,
+            next_window_start: 0,
+            n_rows: config.n_source_symbols::<Primary>().get().into(),
+            n_columns: config.n_source_symbols::<Secondary>().get().into(),
+            leaf_hashers: (0..n_shards * n_shards)
+                .map(|_| leaf_hasher::<Blake2b256>())
+                .collect(),
+            config,
+            span: tracing::span!(
+                Level::ERROR,
+                "ChunkedBlobEncoder",
+                blob_size,
+                symbol_size = symbol_size.get(),
+                window_size,
+            ),
+        })
+    }
+
+    /// Returns a window size for which the expansion of a window with `n_shards` shards requires
+    /// about 64 MiB of memory, independently of the size of the blob.
+    pub fn window_size_for_n_shards(n_shards: NonZeroU16) -> NonZeroU16 {
+        let n_shards = usize::from(n_shards.get());
+        let min_window_size = usize::from(Self::WINDOW_ALIGNMENT);
+        let window_size = (Self::TARGET_WINDOW_EXPANSION_BYTES / (n_shards * n_shards))
+            .clamp(min_window_size, u16::MAX.into());
+        NonZeroU16::new(u16::try_from(window_size).expect("the window size is clamped to a `u16`"))
+            .expect("the window size is at least the window alignment")
+    }
+
+    /// Returns the size of the symbols of the encoded blob.
+    pub fn symbol_size(&self) -> NonZeroU16 {
+        self.symbol_size
+    }
+
+    /// Returns true if all windows of the blob have been encoded.
+    pub fn is_finished(&self) -> bool {
+        self.next_window_start >= self.symbol_usize()
+    }
+
+    /// Encodes the next window of the blob to a [`SliverPairsWindow`].
+    ///
+    /// The blob is read through `read_at`, which must fill the provided buffer with the bytes of
+    /// the blob starting at the provided offset. Each window reads a range of bytes of each source
+    /// symbol.
+    ///
+    /// Returns `None` if all windows have already been encoded.
+    ///
+    /// # Errors
+    ///
+    /// Returns the errors returned by `read_at`.
+    pub fn encode_next_window<E>(
+        &mut self,
+        mut read_at: impl FnMut(u64, &mut [u8]) -> Result<(), E>,
+    ) -> Result<Option<SliverPairsWindow>, E> {
+        let Some((symbol_bytes, window)) = self.read_next_window(&mut read_at)? else {
+            return Ok(None);
+        };
+        let _guard = self.span.enter();
+        let window_symbol_size = Self::window_symbol_size(&symbol_bytes);
+
+        let mut expanded_matrix =
+            ExpandedMessageMatrix::new(&self.config, window_symbol_size, &window);
+        expanded_matrix.update_leaf_hashers(&mut self.leaf_hashers);
+        expanded_matrix.drop_recovery_symbols();
+
+        let mut sliver_pairs: Vec<_> = (0..self.config.n_shards().get())
+            .map(|i| SliverPair::new_empty(&self.config, window_symbol_size, SliverPairIndex(i)))
+            .collect();
+        expanded_matrix.write_secondary_slivers(&mut sliver_pairs);
+        expanded_matrix.write_primary_slivers(&mut sliver_pairs);
+
+        self.next_window_start = symbol_bytes.end;
+        Ok(Some(SliverPairsWindow {
+            symbol_bytes,
+            sliver_pairs,
+        }))
+    }
+
+    /// Hashes the next window of the blob, without computing its slivers.
+    ///
+    /// This is sufficient to compute the metadata of the blob with [`finish()`][Self::finish], and
+    /// avoids the work and memory of [`encode_next_window()`][Self::encode_next_window] to produce
+    /// the slivers. The blob is read through `read_at` as described there.
+    ///
+    /// Returns the range of bytes of each symbol that is contained in the window, or `None` if all
+    /// windows have already been encoded.
+    ///
+    /// # Errors
+    ///
+    /// Returns the errors returned by `read_at`.
+    pub fn hash_next_window<E>(
+        &mut self,
+        mut read_at: impl FnMut(u64, &mut [u8]) -> Result<(), E>,
+    ) -> Result<Option<Range<usize>>, E> {
+        let Some((symbol_bytes, window)) = self.read_next_window(&mut read_at)? else {
+            return Ok(None);
+        };
+        let _guard = self.span.enter();
+
+        let expanded_matrix = ExpandedMessageMatrix::new(
+            &self.config,
+            Self::window_symbol_size(&symbol_bytes),
+            &window,
+        );
+        expanded_matrix.update_leaf_hashers(&mut self.leaf_hashers);
+
+        self.next_window_start = symbol_bytes.end;
+        Ok(Some(symbol_bytes))
+    }
+
+    /// Hashes all remaining windows of the blob and returns its metadata.
+    ///
+    /// The blob is read through `read_at` as described in
+    /// [`encode_next_window()`][Self::encode_next_window]. After each window, `progress` is called
+    /// with the number of bytes of each symbol that have been hashed and the symbol size.
+    ///
+    /// # Errors
+    ///
+    /// Returns the errors returned by `read_at`.
+    pub fn compute_metadata<E>(
+        mut self,
+        mut read_at: impl FnMut(u64, &mut [u8]) -> Result<(), E>,
+        mut progress: impl FnMut(usize, usize),
+    ) -> Result<VerifiedBlobMetadataWithId, E> {
+        while let Some(symbol_bytes) = self.hash_next_window(&mut read_at)? {
+            progress(symbol_bytes.end, self.symbol_usize());
+        }
+        Ok(self.finish())
+    }
+
+    /// Returns the metadata of the encoded blob.
+    ///
+    /// # Panics
+    ///
+    /// Panics if not all windows of the blob have been encoded, see
+    /// [`is_finished()`][Self::is_finished].
+    pub fn finish(self) -> VerifiedBlobMetadataWithId {
+        assert!(
+            self.is_finished(),
+            "all windows must be encoded before computing the metadata"
+        );
+        let leaf_hashes: Vec<Node> = self
+            .leaf_hashers
+            .into_iter()
+            .map(|hash_fun| hash_fun.finalize().into())
+            .collect();
+        let metadata = sliver_pair_metadata(self.config.n_shards_as_usize(), &leaf_hashes);
+        tracing::debug!(parent: &self.span, "computed metadata of the blob");
+
+        VerifiedBlobMetadataWithId::new_verified_from_metadata(
+            metadata,
+            self.config.encoding_type(),
+            self.blob_size,
+        )
+    }
+
+    fn symbol_usize(&self) -> usize {
+        self.symbol_size.get().into()
+    }
+
+    /// Reads the bytes in `symbol_bytes` of all source symbols in row-major order, padded with
+    /// zeros beyond the end of the blob.
+    fn read_window<E>(
+        &self,
+        symbol_bytes: Range<usize>,
+        read_at: &mut impl FnMut(u64, &mut [u8]) -> Result<(), E>,
+    ) -> Result<Vec<u8>, E> {
+        let window_size = symbol_bytes.len();
+        let mut window = vec![0; self.n_rows * self.n_columns * window_size];
+        for (symbol_index, target) in window.chunks_exact_mut(window_size).enumerate() {
+            let offset = u64::try_from(symbol_index).expect("any `usize` fits into a `u64`")
+                * u64::from(self.symbol_size.get())
+                + u64::try_from(symbol_bytes.start).expect("any `usize` fits into a `u64`");
+            if offset >= self.blob_size {
+                // This and all following symbols only contain padding.
+                break;
+            }
+            let length = cmp::min(
+                window_size,
+                usize::try_from(self.blob_size - offset).unwrap_or(usize::MAX),
+            );
+            read_at(offset, &mut target[..length])?;
+        }
+        Ok(window)
+    }
+}
+
+/// A window of the sliver pairs of a blob, as produced by the [`ChunkedBlobEncoder`].
+///
+/// The slivers in the window contain the bytes in [`symbol_bytes`][Self::symbol_bytes] of each
+/// symbol of the complete slivers. The `i`-th symbol of a sliver in the window therefore belongs
+/// at offset `i * symbol_size + symbol_bytes.start` of the data of the complete sliver.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SliverPairsWindow {
+    /// The range of bytes of each symbol that is contained in the window.
+    pub symbol_bytes: Range<usize>,
+    /// The sliver pairs, with each symbol restricted to the bytes in `symbol_bytes`.
+    pub sliver_pairs: Vec<SliverPair>,
+}
+
+impl SliverPairsWindow {
+    /// Copies the window to the corresponding bytes of the complete `sliver_pairs`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the symbols of `sliver_pairs` do not contain the bytes in
+    /// [`symbol_bytes`][Self::symbol_bytes].
+    pub fn copy_to(&self, sliver_pairs: &mut [SliverPair]) {
+        for (window, sliver_pair) in self.sliver_pairs.iter().zip(sliver_pairs) {
+            for (source, target) in window
+                .primary
+                .symbols
+                .to_symbols()
+                .zip(sliver_pair.primary.symbols.to_symbols_mut())
+                .chain(
+                    window
+                        .secondary
+                        .symbols
+                        .to_symbols()
+                        .zip(sliver_pair.secondary.symbols.to_symbols_mut()),
+                )
+            {
+                target[self.symbol_bytes.clone()].copy_from_slice(source);
+            }
+        }
+    }
+}
+
+/// Struct to compute the metadata and ID of a blob whose data is provided in chunks.
+///
+/// As the blob ID depends on the encoding of the complete blob, the chunks are collected until the
+/// hasher is finalized. The blob is then hashed window by window with a [`ChunkedBlobEncoder`], so
+/// that, in contrast to [`BlobEncoder::compute_metadata`], the expanded blob is never held in
+/// memory.
+#[derive(Debug, Clone, Default)]
+pub struct BlobIdHasher {
+    blob: Vec<u8>,
+}
+
+impl BlobIdHasher {
+    /// Creates a new, empty `BlobIdHasher`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a new, empty `BlobIdHasher` with space for a blob of `blob_size` bytes.
+    pub fn with_capacity(blob_size: usize) -> Self {
+        Self {
+            blob: Vec::with_capacity(blob_size),
+        }
+    }
+
+    /// Appends the chunk to the data of the blob.
+    pub fn update(&mut self, chunk: &[u8]) {
+        self.blob.extend_from_slice(chunk);
+    }
+
+    /// Returns the number of bytes of the blob provided so far.
+    pub fn len(&self) -> usize {
+        self.blob.len()
+    }
+
+    /// Returns true if no bytes of the blob have been provided.
+    pub fn is_empty(&self) -> bool {
+        self.blob.is_empty()
+    }
+
+    /// Computes the metadata of the blob for the provided encoding configuration.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`DataTooLargeError`] if the blob is too large to be encoded.
+    pub fn finalize(
+        self,
+        config: EncodingConfigEnum<'_>,
+    ) -> Result<VerifiedBlobMetadataWithId, DataTooLargeError> {
+        self.finalize_with_progress(config, |_, _| ())
+    }
+
+    /// Computes the metadata of the blob for the provided encoding configuration, reporting the
+    /// progress of the computation.
+    ///
+    /// See [`ChunkedBlobEncoder::compute_metadata`] for the arguments of `progress`.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`DataTooLargeError`] if the blob is too large to be encoded.
+    pub fn finalize_with_progress(
+        self,
+        config: EncodingConfigEnum<'_>,
+        progress: impl FnMut(usize, usize),
+    ) -> Result<VerifiedBlobMetadataWithId, DataTooLargeError> {
+        let window_size = ChunkedBlobEncoder::window_size_for_n_shards(config.n_shards());
+        let encoder = ChunkedBlobEncoder::new(
+            config,
+            u64::try_from(self.blob.len()).map_err(|_| DataTooLargeError)?,
+            window_size,
+        )?;
+        let read_at = |offset: u64, buffer: &mut [u8]| {
+            let start = usize::try_from(offset).expect("the offset is within the blob");
+            buffer.copy_from_slice(&self.blob[start..start + buffer.len()]);
+            Ok::<_, Infallible>(())
+        };
+        let Ok(metadata) = encoder.compute_metadata(read_at, progress);
+        Ok(metadata)
+    }
+}
+
 /// A wrapper around the blob decoder for different encoding types.
 #[derive(Debug)]
 pub enum BlobDecoderEnum<'a, E: EncodingAxis> {
@@ -545,49 +1002,51 @@ impl<'a, D: Decoder, E: EncodingAxis> BlobDecoder<'a, D, E> {
     {
         let _guard = self.span.enter();
         tracing::debug!(axis = E::NAME, "starting to decode");
-        // Depending on the decoding axis, this represents the message matrix's columns (primary)
-        // or rows (secondary).
-        let mut columns_or_rows = Vec::with_capacity(self.decoders.len());
-        let mut decoding_successful = false;
-
-        for sliver in slivers {
-            let expected_len = self.decoders.len();
-            let expected_symbol_size = self.symbol_size;
-            if sliver.symbols.len() != expected_len
-                || sliver.symbols.symbol_size() != expected_symbol_size
-            {
-                // Drop slivers of incorrect length or incorrect symbol size and log a warning.
-                tracing::warn!(
-                    %sliver,
-                    expected_len,
-                    expected_symbol_size,
-                    "sliver has incorrect length or symbol size"
-                );
-                continue;
-            }
-            for (decoder, symbol) in self.decoders.iter_mut().zip(sliver.symbols.to_symbols()) {
-                if let Some(decoded_data) = decoder
-                    // NOTE: The encoding axis of the following symbol is irrelevant, but since we
-                    // are reconstructing from slivers of type `T`, it should be of type `T`.
-                    .decode([DecodingSymbol::<E>::new(sliver.index.0, symbol.into())])
-                {
-                    // If one decoding succeeds, all succeed as they have identical
-                    // encoding/decoding matrices.
-                    decoding_successful = true;
-                    columns_or_rows.push(decoded_data);
+        let expected_len = self.decoders.len();
+        let expected_symbol_size = self.symbol_size;
+        let slivers: Vec<_> = slivers
+            .into_iter()
+            .filter(|sliver| {
+                let is_valid = sliver.symbols.len() == expected_len
+                    && sliver.symbols.symbol_size() == expected_symbol_size;
+                if !is_valid {
+                    // Drop slivers of incorrect length or incorrect symbol size and log a warning.
+                    tracing::warn!(
+                        %sliver,
+                        expected_len,
+                        expected_symbol_size,
+                        "sliver has incorrect length or symbol size"
+                    );
                 }
-            }
-            // Stop decoding as soon as we are done.
-            if decoding_successful {
-                tracing::debug!("decoding finished successfully");
-                break;
-            }
-        }
+                is_valid
+            })
+            .collect();
+
+        // The decoders of the message matrix's columns (primary) or rows (secondary) are
+        // independent, so each of them is provided with its symbols of all slivers in parallel.
+        let mut decoders_and_results: Vec<_> = core::mem::take(&mut self.decoders)
+            .into_iter()
+            .map(|decoder| (decoder, None))
+            .collect();
+        utils::for_each_indexed_mut(&mut decoders_and_results, |index, (decoder, result)| {
+            *result = slivers.iter().find_map(|sliver| {
+                // NOTE: The encoding axis of the following symbol is irrelevant, but since we are
+                // reconstructing from slivers of type `T`, it should be of type `T`.
+                decoder.decode([DecodingSymbol::<E>::new(
+                    sliver.index.0,
+                    sliver.symbols[index].to_vec(),
+                )])
+            });
+        });
+        let (decoders, results): (Vec<_>, Vec<_>) = decoders_and_results.into_iter().unzip();
+        self.decoders = decoders;
 
-        if !decoding_successful {
+        // If one decoding succeeds, all succeed as they have identical encoding/decoding matrices.
+        let Some(columns_or_rows) = results.into_iter().collect::<Option<Vec<_>>>() else {
             tracing::debug!("decoding attempt unsuccessful");
             return None;
-        }
+        };
+        tracing::debug!("decoding finished successfully");
 
         let mut blob: Vec<_> = if E::IS_PRIMARY {
             // Primary decoding: transpose columns to get to the original blob.
@@ -864,6 +1323,137 @@ mod tests {
         assert!(unverified.verify(&config).is_ok());
     }
 
+    #[cfg(feature = "parallel")]
+    param_test! {
+        test_encode_with_metadata_is_independent_of_thread_count: [
+            raptorq: (EncodingType::RedStuffRaptorQ),
+            reed_solomon: (EncodingType::RS2),
+        ]
+    }
+    #[cfg(feature = "parallel")]
+    fn test_encode_with_metadata_is_independent_of_thread_count(encoding_type: EncodingType) {
+        let blob = random_data(27182);
+        let config = EncodingConfig::new(NonZeroU16::new(102).unwrap());
+        let config_enum = config.get_for_type(encoding_type);
+
+        let encode_with_threads = |n_threads| {
+            rayon::ThreadPoolBuilder::new()
+                .num_threads(n_threads)
+                .build()
+                .unwrap()
+                .install(|| config_enum.encode_with_metadata(&blob).unwrap())
+        };
+        assert_eq!(encode_with_threads(1), encode_with_threads(4));
+    }
+
+    #[cfg(feature = "parallel")]
+    param_test! {
+        test_decode_is_independent_of_thread_count: [
+            raptorq: (EncodingType::RedStuffRaptorQ),
+            reed_solomon: (EncodingType::RS2),
+        ]
+    }
+    #[cfg(feature = "parallel")]
+    fn test_decode_is_independent_of_thread_count(encoding_type: EncodingType) {
+        let blob = random_data(27182);
+        let config = EncodingConfig::new(NonZeroU16::new(102).unwrap());
+        let config_enum = config.get_for_type(encoding_type);
+        let (sliver_pairs, metadata) = config_enum.encode_with_metadata(&blob).unwrap();
+        let slivers: Vec<_> = sliver_pairs.into_iter().map(|pair| pair.primary).collect();
+
+        let decode_with_threads = |n_threads| {
+            rayon::ThreadPoolBuilder::new()
+                .num_threads(n_threads)
+                .build()
+                .unwrap()
+                .install(|| {
+                    config_enum
+                        .get_blob_decoder::<Primary>(blob.len() as u64)
+                        .unwrap()
+                        .decode_and_verify(metadata.blob_id(), slivers.clone())
+                        .unwrap()
+                        .expect("decoding succeeds with all slivers")
+                })
+        };
+        let (decoded_blob, decoded_metadata) = decode_with_threads(4);
+        assert_eq!(decoded_blob, blob);
+        assert_eq!(decoded_metadata, metadata);
+        assert_eq!(decode_with_threads(1).0, decoded_blob);
+    }
+
+    param_test! {
+        test_chunked_encoding_matches_encoding: [
+            raptorq_aligned_windows: (EncodingType::RedStuffRaptorQ, 64),
+            raptorq_unaligned_windows: (EncodingType::RedStuffRaptorQ, 100),
+            raptorq_single_window: (EncodingType::RedStuffRaptorQ, u16::MAX),
+            reed_solomon_aligned_windows: (EncodingType::RS2, 64),
+            reed_solomon_unaligned_windows: (EncodingType::RS2, 100),
+            reed_solomon_single_window: (EncodingType::RS2, u16::MAX),
+        ]
+    }
+    fn test_chunked_encoding_matches_encoding(encoding_type: EncodingType, window_size: u16) {
+        let blob = random_data(10_007);
+        let n_shards = 10;
+        let config = EncodingConfig::new(NonZeroU16::new(n_shards).unwrap());
+        let config_enum = config.get_for_type(encoding_type);
+        let (expected_sliver_pairs, expected_metadata) =
+            config_enum.encode_with_metadata(&blob).unwrap();
+
+        let mut encoder = ChunkedBlobEncoder::new(
+            config_enum.clone(),
+            blob.len().try_into().unwrap(),
+            NonZeroU16::new(window_size).unwrap(),
+        )
+        .unwrap();
+        let mut sliver_pairs: Vec<_> = (0..n_shards)
+            .map(|i| SliverPair::new_empty(&config_enum, encoder.symbol_size(), SliverPairIndex(i)))
+            .collect();
+        let read_at = |offset: u64, buffer: &mut [u8]| {
+            let offset = usize::try_from(offset).unwrap();
+            buffer.copy_from_slice(&blob[offset..offset + buffer.len()]);
+            Ok::<_, Infallible>(())
+        };
+        while let Some(window) = encoder.encode_next_window(read_at).unwrap() {
+            window.copy_to(&mut sliver_pairs);
+        }
+
+        assert_eq!(sliver_pairs, expected_sliver_pairs);
+        assert_eq!(encoder.finish(), expected_metadata);
+    }
+
+    param_test! {
+        test_blob_id_hasher_matches_encoding: [
+            raptorq_empty: (EncodingType::RedStuffRaptorQ, 0),
+            raptorq: (EncodingType::RedStuffRaptorQ, 100_003),
+            reed_solomon_empty: (EncodingType::RS2, 0),
+            reed_solomon: (EncodingType::RS2, 100_003),
+        ]
+    }
+    fn test_blob_id_hasher_matches_encoding(encoding_type: EncodingType, blob_size: usize) {
+        let blob = random_data(blob_size);
+        let config = EncodingConfig::new(NonZeroU16::new(10).unwrap());
+        let config_enum = config.get_for_type(encoding_type);
+
+        let mut hasher = BlobIdHasher::new();
+        for chunk in blob.chunks(4096) {
+            hasher.update(chunk);
+        }
+        assert_eq!(hasher.len(), blob_size);
+        let mut last_progress = None;
+        let metadata = hasher
+            .finalize_with_progress(config_enum.clone(), |hashed, total| {
+                last_progress = Some((hashed, total))
+            })
+            .unwrap();
+
+        assert_eq!(metadata, config_enum.compute_metadata(&blob).unwrap());
+        let symbol_size = config_enum
+            .symbol_size_for_blob_from_usize(blob_size)
+            .unwrap();
+        let symbol_size = usize::from(symbol_size.get());
+        assert_eq!(last_progress, Some((symbol_size, symbol_size)));
+    }
+
     #[test]
     fn test_encode_decode_and_verify() {
         let blob = random_data(16180);
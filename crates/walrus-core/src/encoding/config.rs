@@ -1,8 +1,12 @@
 // Copyright (c) Walrus Foundation
 // SPDX-License-Identifier: Apache-2.0
 
-use alloc::vec::Vec;
-use core::num::{NonZeroU16, NonZeroU32};
+use alloc::{collections::BTreeMap, sync::Arc, vec::Vec};
+use core::{
+    num::{NonZeroU16, NonZeroU32},
+    ops::Range,
+};
+use std::sync::{Mutex, PoisonError};
 
 use enum_dispatch::enum_dispatch;
 use raptorq::SourceBlockEncodingPlan;
@@ -16,10 +20,14 @@ use super::{
     BlobDecoder,
     BlobDecoderEnum,
     BlobEncoder,
+    BlobRangeDecoder,
+    ChunkedBlobEncoder,
     DataTooLargeError,
     DecodingSymbol,
     EncodeError,
     EncodingAxis,
+    Primary,
+    RangeDecodingError,
     ReedSolomonDecoder,
     ReedSolomonEncoder,
     SliverPair,
@@ -34,7 +42,13 @@ use crate::{bft, merkle::DIGEST_LEN, metadata::VerifiedBlobMetadataWithId, BlobI
 /// Trait for encoding configurations.
 ///
 /// This trait provides a common interface for encoding configurations for different types of
-/// encodings.
+/// encodings, and thereby abstracts over the encoding scheme used for a blob.
+///
+/// Each scheme is identified by an [`EncodingType`], which is stored in the blob metadata and is
+/// part of the input of the [`BlobId`]. Blobs encoded with different schemes therefore have
+/// different blob IDs, and the IDs of existing blobs are unaffected by adding a scheme. To add a
+/// scheme, add a variant to [`EncodingType`], implement this trait for its configuration, and add
+/// the configuration to [`EncodingConfigEnum`] and [`EncodingConfig::get_for_type`].
 #[enum_dispatch]
 pub trait EncodingConfigTrait {
     /// The encoding type associated with this encoding config.
@@ -204,6 +218,23 @@ pub trait EncodingConfigTrait {
             .ok_or(DataTooLargeError)
     }
 
+    /// Returns the indices of the primary source slivers that contain the bytes in `range` of a
+    /// blob of size `blob_size`.
+    ///
+    /// The primary source slivers are the rows of the message matrix and contain the bytes of the
+    /// blob in order, so a range of the blob can be read from them without decoding. Returns an
+    /// empty range if `range` is empty.
+    ///
+    /// Returns a [`DataTooLargeError`] `blob_size > self.max_blob_size()`.
+    #[inline]
+    fn primary_source_slivers_for_range(
+        &self,
+        blob_size: u64,
+        range: Range<u64>,
+    ) -> Result<Range<u16>, DataTooLargeError> {
+        source_slivers_for_range(self.sliver_size_for_blob::<Primary>(blob_size)?, range)
+    }
+
     /// Computes the length of a blob of given `unencoded_length`, once encoded.
     ///
     /// See [`encoded_blob_length_for_n_shards`] for additional documentation.
@@ -237,6 +268,10 @@ pub trait EncodingConfigTrait {
     }
 }
 
+/// The encoding configs created by [`EncodingConfig::new`], indexed by their numbers of shards.
+static CACHED_ENCODING_CONFIGS: Mutex<BTreeMap<NonZeroU16, EncodingConfig>> =
+    Mutex::new(BTreeMap::new());
+
 /// Configuration parameters for the encoding.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct EncodingConfig {
@@ -258,12 +293,22 @@ impl EncodingConfig {
     ///
     /// Panics if the number of shards causes the number of primary or secondary source symbols
     /// to be larger than [`MAX_SOURCE_SYMBOLS_PER_BLOCK`].
+    ///
+    /// The precomputed state of the config, such as the RaptorQ encoding plans, is generated once
+    /// per number of shards and cached for the lifetime of the process. Configs for the same
+    /// number of shards share this state, so that creating them repeatedly, for example for each
+    /// client or epoch, is cheap.
     pub fn new(n_shards: NonZeroU16) -> Self {
-        Self {
-            n_shards,
-            raptorq: RaptorQEncodingConfig::new(n_shards),
-            reed_solomon: ReedSolomonEncodingConfig::new(n_shards),
-        }
+        CACHED_ENCODING_CONFIGS
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .entry(n_shards)
+            .or_insert_with(|| Self {
+                n_shards,
+                raptorq: RaptorQEncodingConfig::new(n_shards),
+                reed_solomon: ReedSolomonEncodingConfig::new(n_shards),
+            })
+            .clone()
     }
 
     #[cfg(test)]
@@ -327,6 +372,17 @@ impl<'a> EncodingConfigEnum<'a> {
             }
         }
     }
+
+    /// Returns a decoder for the bytes in `range` of a blob of size `blob_size`.
+    ///
+    /// See [`BlobRangeDecoder`] for details.
+    pub fn get_range_decoder(
+        &self,
+        blob_size: u64,
+        range: Range<u64>,
+    ) -> Result<BlobRangeDecoder<'a>, RangeDecodingError> {
+        BlobRangeDecoder::new(self.clone(), blob_size, range)
+    }
 }
 
 /// Configuration of the RaptorQ encoding.
@@ -346,9 +402,9 @@ pub struct RaptorQEncodingConfig {
     /// The number of shards.
     pub(crate) n_shards: NonZeroU16,
     /// Encoding plan to speed up the primary encoding.
-    encoding_plan_primary: SourceBlockEncodingPlan,
+    encoding_plan_primary: Arc<SourceBlockEncodingPlan>,
     /// Encoding plan to speed up the secondary encoding.
-    encoding_plan_secondary: SourceBlockEncodingPlan,
+    encoding_plan_secondary: Arc<SourceBlockEncodingPlan>,
 }
 
 impl RaptorQEncodingConfig {
@@ -423,10 +479,12 @@ impl RaptorQEncodingConfig {
             source_symbols_primary,
             source_symbols_secondary,
             n_shards,
-            encoding_plan_primary: SourceBlockEncodingPlan::generate(source_symbols_primary.get()),
-            encoding_plan_secondary: SourceBlockEncodingPlan::generate(
+            encoding_plan_primary: Arc::new(SourceBlockEncodingPlan::generate(
+                source_symbols_primary.get(),
+            )),
+            encoding_plan_secondary: Arc::new(SourceBlockEncodingPlan::generate(
                 source_symbols_secondary.get(),
-            ),
+            )),
         }
     }
 
@@ -479,6 +537,16 @@ impl RaptorQEncodingConfig {
         BlobEncoder::new(self.into(), blob)
     }
 
+    /// Returns a [`ChunkedBlobEncoder`] for a blob of `blob_size` bytes, which encodes at most
+    /// `window_size` bytes of each symbol at a time.
+    pub fn get_chunked_blob_encoder(
+        &self,
+        blob_size: u64,
+        window_size: NonZeroU16,
+    ) -> Result<ChunkedBlobEncoder<'_>, DataTooLargeError> {
+        ChunkedBlobEncoder::new(self.into(), blob_size, window_size)
+    }
+
     pub(crate) fn get_decoder<E: EncodingAxis>(&self, symbol_size: NonZeroU16) -> RaptorQDecoder {
         RaptorQDecoder::new(self.n_source_symbols::<E>(), self.n_shards(), symbol_size)
     }
@@ -748,6 +816,16 @@ impl ReedSolomonEncodingConfig {
         BlobEncoder::new(self.into(), blob)
     }
 
+    /// Returns a [`ChunkedBlobEncoder`] for a blob of `blob_size` bytes, which encodes at most
+    /// `window_size` bytes of each symbol at a time.
+    pub fn get_chunked_blob_encoder(
+        &self,
+        blob_size: u64,
+        window_size: NonZeroU16,
+    ) -> Result<ChunkedBlobEncoder<'_>, DataTooLargeError> {
+        ChunkedBlobEncoder::new(self.into(), blob_size, window_size)
+    }
+
     /// Returns a [`BlobDecoder`] for the given `blob_size`.
     pub fn get_blob_decoder<E: EncodingAxis>(
         &self,
@@ -920,6 +998,36 @@ pub fn decoding_safety_limit(n_shards: NonZeroU16, encoding_type: EncodingType)
     }
 }
 
+/// The version of the derivation of the encoding parameters from the number of shards, which is
+/// implemented by [`source_symbols_for_n_shards`].
+///
+/// Blobs are encoded with the parameters of this version. The version of a blob is stored in the
+/// encoding parameters of its metadata, see [`EncodingParams`][crate::metadata::EncodingParams],
+/// or is version 1 if the metadata does not store them. Any change to the derivation must
+/// therefore increment this version, and the previous derivation must be kept in
+/// [`source_symbols_for_version`], so that the metadata of blobs encoded with previous versions
+/// can still be verified.
+pub const ENCODING_VERSION: u8 = 1;
+
+/// Computes the number of primary and secondary source symbols starting from the number of shards,
+/// with the given version of the derivation.
+///
+/// The metadata of a blob is verified against the parameters of the version with which the blob
+/// is encoded, independently of the version used by the encoding config of the verifier.
+///
+/// Returns `None` if the version is not supported, see [`ENCODING_VERSION`].
+#[inline]
+pub fn source_symbols_for_version(
+    version: u8,
+    n_shards: NonZeroU16,
+    encoding_type: EncodingType,
+) -> Option<(NonZeroU16, NonZeroU16)> {
+    match version {
+        1 => Some(source_symbols_for_n_shards(n_shards, encoding_type)),
+        _ => None,
+    }
+}
+
 /// Computes the number of primary and secondary source symbols starting from the number of shards.
 ///
 /// The computation is as follows:
@@ -1007,6 +1115,46 @@ fn source_symbols_per_blob_for_n_shards(
         .expect("product of two u16 always fits into a u32")
 }
 
+/// The size (in bytes) of a primary sliver of a blob of size `blob_size`, given the number of
+/// shards.
+///
+/// Returns a [`DataTooLargeError`] if the blob is too large to be encoded with `n_shards` shards.
+#[inline]
+pub fn primary_sliver_size_for_n_shards(
+    n_shards: NonZeroU16,
+    blob_size: u64,
+    encoding_type: EncodingType,
+) -> Result<NonZeroU32, DataTooLargeError> {
+    let (_, source_symbols_secondary) = source_symbols_for_n_shards(n_shards, encoding_type);
+    let symbol_size = utils::compute_symbol_size(
+        blob_size,
+        source_symbols_per_blob_for_n_shards(n_shards, encoding_type),
+        encoding_type.required_alignment(),
+    )?;
+    NonZeroU32::from(source_symbols_secondary)
+        .checked_mul(symbol_size.into())
+        .ok_or(DataTooLargeError)
+}
+
+/// Returns the indices of the primary source slivers of size `sliver_size` that contain the bytes
+/// in `range` of a blob.
+///
+/// Returns an empty range if `range` is empty.
+#[inline]
+pub(crate) fn source_slivers_for_range(
+    sliver_size: NonZeroU32,
+    range: Range<u64>,
+) -> Result<Range<u16>, DataTooLargeError> {
+    if range.is_empty() {
+        return Ok(0..0);
+    }
+    let sliver_size = u64::from(sliver_size.get());
+    let first = range.start / sliver_size;
+    let last = (range.end - 1) / sliver_size;
+    let to_index = |index: u64| u16::try_from(index).map_err(|_| DataTooLargeError);
+    Ok(to_index(first)?..to_index(last)? + 1)
+}
+
 /// Computes the length of a blob of given `unencoded_length` and `n_shards`, once encoded.
 ///
 /// The output length includes the metadata and the blob ID sizes. Returns `None` if the blob
@@ -1083,6 +1231,23 @@ mod tests {
         );
     }
 
+    param_test! {
+        test_primary_source_slivers_for_range: [
+            empty: (3..3, 0..0),
+            first_sliver: (0..5, 0..1),
+            across_slivers: (4..6, 0..2),
+            last_byte: (14..15, 2..3),
+            whole_blob: (0..15, 0..3),
+        ]
+    }
+    fn test_primary_source_slivers_for_range(range: Range<u64>, expected: Range<u16>) {
+        assert_eq!(
+            RaptorQEncodingConfig::new_for_test(3, 5, 10)
+                .primary_source_slivers_for_range(15, range),
+            Ok(expected)
+        );
+    }
+
     param_test! {
         test_encoded_size_raptor: [
             zero_small_committee: (0, 10, 10*((4+7) + 10*2*32 + 32)),
@@ -1168,6 +1333,42 @@ mod tests {
         assert_eq!(actual_secondary.get(), expected_secondary);
     }
 
+    #[test]
+    fn test_blob_id_depends_on_encoding_type() {
+        let blob = walrus_test_utils::random_data(1000);
+        let config = EncodingConfig::new(NonZeroU16::new(10).unwrap());
+
+        let compute_blob_id = |encoding_type| {
+            *config
+                .get_for_type(encoding_type)
+                .compute_metadata(&blob)
+                .unwrap()
+                .blob_id()
+        };
+        assert_ne!(
+            compute_blob_id(EncodingType::RedStuffRaptorQ),
+            compute_blob_id(EncodingType::RS2)
+        );
+    }
+
+    #[test]
+    fn test_new_shares_encoding_plans_for_the_same_number_of_shards() {
+        let config = EncodingConfig::new(NonZeroU16::new(13).unwrap());
+        let other_config = EncodingConfig::new(NonZeroU16::new(13).unwrap());
+        assert_eq!(config, other_config);
+        assert!(Arc::ptr_eq(
+            &config.raptorq.encoding_plan_primary,
+            &other_config.raptorq.encoding_plan_primary
+        ));
+        assert!(Arc::ptr_eq(
+            &config.raptorq.encoding_plan_secondary,
+            &other_config.raptorq.encoding_plan_secondary
+        ));
+
+        let different_config = EncodingConfig::new(NonZeroU16::new(14).unwrap());
+        assert_ne!(config.n_shards(), different_config.n_shards());
+    }
+
     param_test! {
         test_new_for_n_shards: [
             one: (1, 1, 1),
@@ -88,11 +88,31 @@ impl From<DataTooLargeError> for SliverRecoveryOrVerificationError {
     }
 }
 
+/// Error returned when decoding a range of a blob fails.
+#[derive(Debug, Error, PartialEq, Eq, Clone)]
+pub enum RangeDecodingError {
+    /// The blob is too large to be decoded.
+    #[error(transparent)]
+    DataTooLarge(#[from] DataTooLargeError),
+    /// The range is not within the blob.
+    #[error("the range is not within the blob")]
+    RangeOutOfBounds,
+    /// Not all slivers containing the range were provided.
+    #[error("not all slivers containing the range were provided")]
+    MissingSlivers,
+}
+
 /// Error returned when the size of input symbols does not match the size of existing symbols.
 #[derive(Debug, Error, PartialEq, Eq, Clone)]
 #[error("the size of the symbols provided does not match the size of the existing symbols")]
 pub struct WrongSymbolSizeError;
 
+/// Error returned when the data of [`Symbols`][super::Symbols] does not consist of complete
+/// symbols.
+#[derive(Debug, Error, PartialEq, Eq, Clone)]
+#[error("the data does not consist of complete symbols")]
+pub struct IncompleteSymbolsError;
+
 /// Error returned when the verification of a reconstructed blob fails. Verification failure occurs
 /// when the provided blob ID does not match the blob ID computed from the reconstructed blob.
 #[derive(Debug, Error, PartialEq, Eq, Clone)]
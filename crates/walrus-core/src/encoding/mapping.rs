@@ -3,11 +3,20 @@
 
 //! The mapping between the encoded sliver pairs and shards.
 
-use core::num::NonZeroU16;
+use alloc::vec::Vec;
+use core::{num::NonZeroU16, ops::Range};
 
 use thiserror::Error;
 
-use crate::{encoding::SliverPair, BlobId, ShardIndex, SliverPairIndex};
+use super::config::{primary_sliver_size_for_n_shards, source_slivers_for_range};
+use crate::{
+    encoding::{DataTooLargeError, EncodingAxis, SliverPair},
+    BlobId,
+    EncodingType,
+    ShardIndex,
+    SliverIndex,
+    SliverPairIndex,
+};
 
 /// Errors returned if the slice of sliver pairs has already been shuffled in a way that is
 /// inconsistent with the provided blob id.
@@ -92,26 +101,89 @@ impl SliverPairIndex {
     /// sliver corresponds, `blob_id`. The `blob_id` is interpreted as a big-endian unsigned
     /// integer, and then used to compute the offset for the sliver pair index.
     pub fn to_shard_index(&self, n_shards: NonZeroU16, blob_id: &BlobId) -> ShardIndex {
-        ((self.as_usize() + rotation_offset(n_shards, blob_id)) % usize::from(n_shards.get()))
+        let offset = usize::from(rotation_offset(n_shards, blob_id));
+        ((self.as_usize() + offset) % usize::from(n_shards.get()))
             .try_into()
             .expect("definitely fits into a u16 because `n_shards` is a u16")
     }
 }
 
+impl SliverIndex {
+    /// Returns the index of the shard on which the sliver of type `E` with this index is stored.
+    ///
+    /// This combines [`SliverIndex::to_pair_index`] and [`SliverPairIndex::to_shard_index`].
+    pub fn to_shard_index<E: EncodingAxis>(
+        &self,
+        n_shards: NonZeroU16,
+        blob_id: &BlobId,
+    ) -> ShardIndex {
+        self.to_pair_index::<E>(n_shards)
+            .to_shard_index(n_shards, blob_id)
+    }
+}
+
 impl ShardIndex {
     /// Returns the index of the sliver pair of this blob corresponding to this shard index.
     ///
     /// This is the reverse operation of [`SliverPairIndex::to_shard_index`].
     pub fn to_pair_index(&self, n_shards: NonZeroU16, blob_id: &BlobId) -> SliverPairIndex {
         let n_shards_usize = usize::from(n_shards.get());
-        ((n_shards_usize + self.as_usize() - rotation_offset(n_shards, blob_id)) % n_shards_usize)
+        let offset = usize::from(rotation_offset(n_shards, blob_id));
+        ((n_shards_usize + self.as_usize() - offset) % n_shards_usize)
             .try_into()
             .expect("definitely fits into a u16 because `n_shards` is a u16")
     }
+
+    /// Returns the index of the sliver of type `E` of this blob stored on this shard.
+    ///
+    /// This is the reverse operation of [`SliverIndex::to_shard_index`].
+    pub fn to_sliver_index<E: EncodingAxis>(
+        &self,
+        n_shards: NonZeroU16,
+        blob_id: &BlobId,
+    ) -> SliverIndex {
+        self.to_pair_index(n_shards, blob_id)
+            .to_sliver_index::<E>(n_shards)
+    }
 }
 
-fn rotation_offset(n_shards: NonZeroU16, blob_id: &BlobId) -> usize {
+/// Returns the offset by which the sliver pairs of the blob with ID `blob_id` are rotated across
+/// the `n_shards` shards, i.e., the index of the shard that stores the sliver pair with index 0.
+///
+/// The `blob_id` is interpreted as a big-endian unsigned integer, and the offset is its value
+/// modulo `n_shards`. The sliver pair with index `i` is stored on the shard with index
+/// `(i + offset) % n_shards`, see [`SliverPairIndex::to_shard_index`].
+pub fn rotation_offset(n_shards: NonZeroU16, blob_id: &BlobId) -> u16 {
     bytes_mod(blob_id.as_ref(), n_shards.get().into())
+        .try_into()
+        .expect("the result is smaller than `n_shards`, which is a u16")
+}
+
+/// Returns the sliver pairs whose primary slivers contain the bytes in `range` of the blob with ID
+/// `blob_id` and size `blob_size`, together with the shards on which they are stored.
+///
+/// The primary source slivers contain the bytes of the blob in order, so the returned pairs are
+/// consecutive and ordered by their index. This allows determining, e.g., which storage nodes
+/// need to be contacted to read the range without decoding the blob. Returns an empty vector if
+/// `range` is empty.
+///
+/// # Errors
+///
+/// Returns a [`DataTooLargeError`] if the blob is too large to be encoded with `n_shards` shards.
+pub fn shards_for_byte_range(
+    n_shards: NonZeroU16,
+    blob_id: &BlobId,
+    encoding_type: EncodingType,
+    blob_size: u64,
+    range: Range<u64>,
+) -> Result<Vec<(SliverPairIndex, ShardIndex)>, DataTooLargeError> {
+    let sliver_size = primary_sliver_size_for_n_shards(n_shards, blob_size, encoding_type)?;
+    Ok(source_slivers_for_range(sliver_size, range)?
+        .map(|index| {
+            let pair_index = SliverPairIndex(index);
+            (pair_index, pair_index.to_shard_index(n_shards, blob_id))
+        })
+        .collect())
 }
 
 /// Rotate the input `slice` in place, based on the rotation specified by the `rotation` byte array.
@@ -140,7 +212,17 @@ mod tests {
     use walrus_test_utils::param_test;
 
     use super::*;
-    use crate::{encoding::RaptorQEncodingConfig, test_utils};
+    use crate::{
+        encoding::{
+            EncodingConfig,
+            EncodingConfigTrait as _,
+            Primary,
+            RaptorQEncodingConfig,
+            Secondary,
+        },
+        test_utils,
+        DEFAULT_ENCODING,
+    };
 
     // Fixture
     fn sliver_pairs(num: u16) -> Vec<SliverPair> {
@@ -181,6 +263,56 @@ mod tests {
         );
     }
 
+    #[test]
+    fn shard_index_sliver_index_conversion_works() {
+        let n_shards = 13.try_into().unwrap();
+        let blob_id = &test_utils::blob_id_from_u64(27);
+        for index in 0..13 {
+            let shard_index = ShardIndex(index);
+            assert_eq!(
+                shard_index
+                    .to_sliver_index::<Primary>(n_shards, blob_id)
+                    .to_shard_index::<Primary>(n_shards, blob_id),
+                shard_index
+            );
+            assert_eq!(
+                shard_index
+                    .to_sliver_index::<Secondary>(n_shards, blob_id)
+                    .to_shard_index::<Secondary>(n_shards, blob_id),
+                shard_index
+            );
+        }
+    }
+
+    #[test]
+    fn shards_for_byte_range_matches_encoding_config() {
+        let n_shards = 13.try_into().unwrap();
+        let blob_id = test_utils::blob_id_from_u64(27);
+        let blob_size = 10_000;
+        let config = EncodingConfig::new(n_shards);
+        let config = config.get_for_type(DEFAULT_ENCODING);
+
+        for range in [0..0, 0..1, 0..blob_size, 1_000..1_001, 4_000..7_500] {
+            let expected: Vec<_> = config
+                .primary_source_slivers_for_range(blob_size, range.clone())
+                .unwrap()
+                .map(|index| {
+                    let pair_index = SliverPairIndex(index);
+                    (pair_index, pair_index.to_shard_index(n_shards, &blob_id))
+                })
+                .collect();
+            assert_eq!(
+                shards_for_byte_range(n_shards, &blob_id, DEFAULT_ENCODING, blob_size, range)
+                    .unwrap(),
+                expected
+            );
+        }
+        assert_eq!(
+            rotation_offset(n_shards, &blob_id),
+            SliverPairIndex(0).to_shard_index(n_shards, &blob_id).0
+        );
+    }
+
     #[test]
     fn test_rotate_pairs() {
         let mut pairs = sliver_pairs(7);
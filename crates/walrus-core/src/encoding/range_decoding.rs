@@ -0,0 +1,313 @@
+// Copyright (c) Walrus Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Decoding of byte ranges of a blob from the slivers that contain them.
+
+use alloc::{vec, vec::Vec};
+use core::{
+    num::{NonZeroU16, NonZeroU32},
+    ops::Range,
+};
+
+use super::{
+    config::source_slivers_for_range,
+    DataTooLargeError,
+    DecodingSymbol,
+    EncodingConfigEnum,
+    EncodingConfigTrait as _,
+    Primary,
+    RangeDecodingError,
+    Secondary,
+    SliverData,
+};
+use crate::SliverIndex;
+
+/// Decoder for a range of bytes of a blob, which only uses the slivers and symbols containing it.
+///
+/// The primary source slivers are the rows of the message matrix and contain the bytes of the blob
+/// in order. A range of the blob is therefore contained in the consecutive primary source slivers
+/// returned by [`Self::primary_sliver_indices`], and more precisely in the symbols of these
+/// slivers returned by [`Self::symbol_indices`]. Given these slivers, [`Self::decode`] extracts
+/// the range without any decoding. A primary sliver that cannot be retrieved can be recovered with
+/// [`Self::recover_primary_sliver`], which decodes only the corresponding row of the message matrix
+/// instead of the whole blob.
+#[derive(Debug, Clone)]
+pub struct BlobRangeDecoder<'a> {
+    config: EncodingConfigEnum<'a>,
+    range: Range<u64>,
+    symbol_size: NonZeroU16,
+    sliver_size: NonZeroU32,
+    sliver_indices: Range<u16>,
+}
+
+impl<'a> BlobRangeDecoder<'a> {
+    /// Creates a new decoder for the bytes in `range` of a blob of size `blob_size`.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`RangeDecodingError::RangeOutOfBounds`] if `range` is not within the blob, and a
+    /// [`RangeDecodingError::DataTooLarge`] if the blob is too large to be encoded with `config`.
+    pub fn new(
+        config: EncodingConfigEnum<'a>,
+        blob_size: u64,
+        range: Range<u64>,
+    ) -> Result<Self, RangeDecodingError> {
+        crate::ensure!(
+            range.start <= range.end && range.end <= blob_size,
+            RangeDecodingError::RangeOutOfBounds
+        );
+        let symbol_size = config.symbol_size_for_blob(blob_size)?;
+        let sliver_size = config.sliver_size_for_blob::<Primary>(blob_size)?;
+        let sliver_indices = source_slivers_for_range(sliver_size, range.clone())?;
+        Ok(Self {
+            config,
+            range,
+            symbol_size,
+            sliver_size,
+            sliver_indices,
+        })
+    }
+
+    /// Returns the range of the blob decoded by this decoder.
+    pub fn range(&self) -> &Range<u64> {
+        &self.range
+    }
+
+    /// Returns the indices of the primary source slivers that contain the range.
+    ///
+    /// Returns an empty range if the range is empty.
+    pub fn primary_sliver_indices(&self) -> Range<u16> {
+        self.sliver_indices.clone()
+    }
+
+    /// Returns the indices of the symbols of the primary sliver with index `sliver_index` that
+    /// contain bytes of the range.
+    ///
+    /// Returns an empty range if the sliver does not contain any byte of the range.
+    pub fn symbol_indices(&self, sliver_index: SliverIndex) -> Range<usize> {
+        let sliver_size = u64::from(self.sliver_size.get());
+        let sliver_start = u64::from(sliver_index.0) * sliver_size;
+        let sliver_end = sliver_start + sliver_size;
+        let start = self.range.start.clamp(sliver_start, sliver_end);
+        let end = self.range.end.clamp(sliver_start, sliver_end);
+        if start >= end {
+            return 0..0;
+        }
+        let symbol_size = u64::from(self.symbol_size.get());
+        let to_index = |offset: u64| {
+            usize::try_from(offset / symbol_size).expect("symbol indices within a sliver fit a u16")
+        };
+        to_index(start - sliver_start)..to_index(end - 1 - sliver_start) + 1
+    }
+
+    /// Recovers the primary source sliver with index `sliver_index` from the symbols at this index
+    /// of the provided secondary slivers.
+    ///
+    /// Only the row of the message matrix corresponding to the sliver is decoded. The secondary
+    /// slivers must have been verified against the blob metadata; slivers that do not have the
+    /// expected number of symbols or symbol size are ignored. The recovered sliver can be verified
+    /// with [`SliverData::verify`].
+    ///
+    /// Returns `None` if the sliver does not contain bytes of the range or if decoding fails, e.g.,
+    /// because not enough secondary slivers were provided.
+    pub fn recover_primary_sliver<'b>(
+        &self,
+        sliver_index: SliverIndex,
+        secondary_slivers: impl IntoIterator<Item = &'b SliverData<Secondary>>,
+    ) -> Option<SliverData<Primary>> {
+        if !self.sliver_indices.contains(&sliver_index.0) {
+            return None;
+        }
+        let n_symbols = usize::from(self.config.n_source_symbols::<Primary>().get());
+        let decoding_symbols = secondary_slivers
+            .into_iter()
+            .filter(|sliver| {
+                sliver.symbols.len() == n_symbols
+                    && sliver.symbols.symbol_size() == self.symbol_size
+            })
+            .map(|sliver| {
+                DecodingSymbol::<Primary>::new(
+                    sliver.index.0,
+                    sliver.symbols[sliver_index.as_usize()].to_vec(),
+                )
+            });
+        self.config
+            .decode_from_decoding_symbols(self.symbol_size, decoding_symbols)
+            .map(|data| SliverData::new(data, self.symbol_size, sliver_index))
+    }
+
+    /// Returns the bytes in the range, given the primary source slivers that contain it.
+    ///
+    /// The `primary_slivers` must include all slivers with indices in
+    /// [`Self::primary_sliver_indices`], in any order; other slivers are ignored. They can be
+    /// retrieved from the storage nodes or recovered with [`Self::recover_primary_sliver`].
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`RangeDecodingError::MissingSlivers`] if any of the slivers containing the range
+    /// is missing or does not have the expected size.
+    pub fn decode<'b>(
+        &self,
+        primary_slivers: impl IntoIterator<Item = &'b SliverData<Primary>>,
+    ) -> Result<Vec<u8>, RangeDecodingError> {
+        let sliver_size = usize::try_from(self.sliver_size.get())
+            .expect("we assume at least a 32-bit architecture");
+        let mut slivers = vec![None; self.sliver_indices.len()];
+        for sliver in primary_slivers {
+            let offset = sliver.index.0.checked_sub(self.sliver_indices.start);
+            if let Some(slot) = offset.and_then(|offset| slivers.get_mut(usize::from(offset))) {
+                if sliver.len() == sliver_size {
+                    *slot = Some(sliver);
+                }
+            }
+        }
+        let slivers: Vec<_> = slivers
+            .into_iter()
+            .collect::<Option<_>>()
+            .ok_or(RangeDecodingError::MissingSlivers)?;
+
+        // The slivers contain the blob's bytes in order, starting at the first sliver's offset.
+        let offset = u64::from(self.sliver_indices.start) * u64::from(self.sliver_size.get());
+        let to_usize = |index: u64| usize::try_from(index - offset).map_err(|_| DataTooLargeError);
+        let start = to_usize(self.range.start)?;
+        let end = to_usize(self.range.end)?;
+        Ok(slivers
+            .iter()
+            .flat_map(|sliver| sliver.symbols.data())
+            .skip(start)
+            .take(end - start)
+            .copied()
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use walrus_test_utils::{param_test, random_data};
+
+    use super::*;
+    use crate::{encoding::EncodingConfig, EncodingType};
+
+    const N_SHARDS: u16 = 10;
+    const BLOB_SIZE: usize = 1234;
+
+    fn encode(
+        config: &EncodingConfig,
+        encoding_type: EncodingType,
+        blob: &[u8],
+    ) -> (Vec<SliverData<Primary>>, Vec<SliverData<Secondary>>) {
+        config
+            .get_for_type(encoding_type)
+            .encode_with_metadata(blob)
+            .expect("the blob can be encoded")
+            .0
+            .into_iter()
+            .map(|pair| (pair.primary, pair.secondary))
+            .unzip()
+    }
+
+    param_test! {
+        test_decode_range_from_primary_slivers: [
+            rq_empty: (EncodingType::RedStuffRaptorQ, 10..10),
+            rq_single_byte: (EncodingType::RedStuffRaptorQ, 500..501),
+            rq_within_sliver: (EncodingType::RedStuffRaptorQ, 3..17),
+            rq_across_slivers: (EncodingType::RedStuffRaptorQ, 100..900),
+            rq_whole_blob: (EncodingType::RedStuffRaptorQ, 0..1234),
+            rs_empty: (EncodingType::RS2, 10..10),
+            rs_single_byte: (EncodingType::RS2, 500..501),
+            rs_within_sliver: (EncodingType::RS2, 3..17),
+            rs_across_slivers: (EncodingType::RS2, 100..900),
+            rs_whole_blob: (EncodingType::RS2, 0..1234),
+        ]
+    }
+    fn test_decode_range_from_primary_slivers(encoding_type: EncodingType, range: Range<u64>) {
+        let blob = random_data(BLOB_SIZE);
+        let config = EncodingConfig::new(NonZeroU16::new(N_SHARDS).unwrap());
+        let (primary_slivers, _) = encode(&config, encoding_type, &blob);
+        let decoder = BlobRangeDecoder::new(
+            config.get_for_type(encoding_type),
+            BLOB_SIZE as u64,
+            range.clone(),
+        )
+        .unwrap();
+
+        let covering_slivers: Vec<_> = decoder
+            .primary_sliver_indices()
+            .map(|index| &primary_slivers[usize::from(index)])
+            .collect();
+        let expected = &blob[range.start as usize..range.end as usize];
+        assert_eq!(
+            decoder
+                .decode(covering_slivers.iter().rev().copied())
+                .unwrap(),
+            expected
+        );
+        assert_eq!(decoder.decode(&primary_slivers).unwrap(), expected);
+
+        // The range is contained in the symbols returned for the covering slivers.
+        let symbol_bytes: Vec<u8> = decoder
+            .primary_sliver_indices()
+            .flat_map(|index| {
+                let sliver = &primary_slivers[usize::from(index)];
+                sliver.symbols[decoder.symbol_indices(SliverIndex(index))]
+                    .iter()
+                    .copied()
+            })
+            .collect();
+        if !expected.is_empty() {
+            assert!(symbol_bytes
+                .windows(expected.len())
+                .any(|window| window == expected));
+        }
+    }
+
+    param_test! {
+        test_recover_primary_sliver_from_secondary_slivers: [
+            raptorq: (EncodingType::RedStuffRaptorQ),
+            reed_solomon: (EncodingType::RS2),
+        ]
+    }
+    fn test_recover_primary_sliver_from_secondary_slivers(encoding_type: EncodingType) {
+        let blob = random_data(BLOB_SIZE);
+        let config = EncodingConfig::new(NonZeroU16::new(N_SHARDS).unwrap());
+        let config_enum = config.get_for_type(encoding_type);
+        let (primary_slivers, secondary_slivers) = encode(&config, encoding_type, &blob);
+        let decoder = BlobRangeDecoder::new(config_enum, BLOB_SIZE as u64, 200..700).unwrap();
+
+        let recovered_slivers: Vec<_> = decoder
+            .primary_sliver_indices()
+            .map(|index| {
+                let sliver = decoder
+                    .recover_primary_sliver(SliverIndex(index), &secondary_slivers)
+                    .expect("recovery succeeds with all secondary slivers");
+                assert_eq!(sliver, primary_slivers[usize::from(index)]);
+                sliver
+            })
+            .collect();
+        assert_eq!(decoder.decode(&recovered_slivers).unwrap(), &blob[200..700]);
+
+        // Slivers that do not contain the range are not recovered.
+        let outside_index = decoder.primary_sliver_indices().end;
+        assert!(decoder
+            .recover_primary_sliver(SliverIndex(outside_index), &secondary_slivers)
+            .is_none());
+    }
+
+    #[test]
+    fn test_range_decoding_errors() {
+        let blob = random_data(BLOB_SIZE);
+        let config = EncodingConfig::new(NonZeroU16::new(N_SHARDS).unwrap());
+        let config_enum = config.get_for_type(EncodingType::RS2);
+        let (primary_slivers, _) = encode(&config, EncodingType::RS2, &blob);
+
+        assert_eq!(
+            BlobRangeDecoder::new(config_enum.clone(), BLOB_SIZE as u64, 0..1235).unwrap_err(),
+            RangeDecodingError::RangeOutOfBounds
+        );
+        let decoder = BlobRangeDecoder::new(config_enum, BLOB_SIZE as u64, 100..900).unwrap();
+        assert_eq!(
+            decoder.decode(&primary_slivers[1..]).unwrap_err(),
+            RangeDecodingError::MissingSlivers
+        );
+    }
+}
@@ -3,9 +3,11 @@
 
 use alloc::vec::Vec;
 use core::{
-    fmt::Display,
+    fmt::{self, Display},
+    hash::Hasher as _,
     marker::PhantomData,
-    num::{NonZeroU16, NonZeroU32},
+    num::{NonZeroU16, NonZeroU32, ParseIntError},
+    str::FromStr,
 };
 
 use fastcrypto::hash::{Blake2b256, HashFunction};
@@ -30,7 +32,7 @@ use super::{
 use crate::{
     ensure,
     inconsistency::{InconsistencyProof, SliverOrInconsistencyProof},
-    merkle::{MerkleAuth, MerkleProof, MerkleTree, Node, DIGEST_LEN},
+    merkle::{leaf_hash, MerkleAuth, MerkleProof, MerkleTree, Node, DIGEST_LEN},
     metadata::{BlobMetadata, BlobMetadataApi as _, SliverPairMetadata},
     utils,
     SliverIndex,
@@ -55,6 +57,30 @@ pub struct SliverData<T: EncodingAxis> {
     _sliver_type: PhantomData<T>,
 }
 
+/// A checksum over the index, symbol size, and data of a sliver.
+///
+/// Unlike the sliver hashes in the blob metadata, the checksum does not authenticate the sliver.
+/// It is cheap to compute, and allows whoever computed it to detect accidental corruption of the
+/// sliver, such as bit rot on disk, without recomputing the sliver's Merkle tree.
+///
+/// The checksum is displayed and parsed as 16 hexadecimal digits.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct SliverChecksum(pub u64);
+
+impl Display for SliverChecksum {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:016x}", self.0)
+    }
+}
+
+impl FromStr for SliverChecksum {
+    type Err = ParseIntError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        u64::from_str_radix(s, 16).map(Self)
+    }
+}
+
 impl<T: EncodingAxis> SliverData<T> {
     /// Creates a new `Sliver` copying the provided slice of bytes.
     ///
@@ -132,6 +158,16 @@ impl<T: EncodingAxis> SliverData<T> {
         Ok(())
     }
 
+    /// Returns the [`SliverChecksum`] of the sliver, an xxHash64 of its index, symbol size, and
+    /// data.
+    pub fn checksum(&self) -> SliverChecksum {
+        let mut hasher = twox_hash::XxHash64::with_seed(0);
+        hasher.write(&self.index.get().to_le_bytes());
+        hasher.write(&self.symbols.symbol_size().get().to_le_bytes());
+        hasher.write(self.symbols.data());
+        SliverChecksum(hasher.finish())
+    }
+
     /// Returns true iff the sliver has the length expected based on the encoding configuration and
     /// blob size.
     fn has_correct_length(&self, config: &EncodingConfigEnum, blob_size: u64) -> bool {
@@ -356,7 +392,12 @@ impl<T: EncodingAxis> SliverData<T> {
         &self,
         config: &EncodingConfigEnum,
     ) -> Result<Node, RecoverySymbolError> {
-        Ok(MerkleTree::<U>::build(self.recovery_symbols(config)?.to_symbols()).root())
+        let recovery_symbols = self.recovery_symbols(config)?;
+        // The symbols are hashed in parallel if the `parallel` feature is enabled.
+        let leaf_hashes = super::utils::map_indices(recovery_symbols.len(), |index| {
+            leaf_hash::<U>(&recovery_symbols[index])
+        });
+        Ok(MerkleTree::<U>::build_from_leaf_hashes(leaf_hashes).root())
     }
 
     /// Returns the sliver size in bytes.
@@ -479,6 +520,8 @@ impl SliverPair {
 
 #[cfg(test)]
 mod tests {
+    use alloc::string::ToString;
+
     use fastcrypto::hash::Blake2b256;
     use walrus_test_utils::{param_test, random_subset, Result};
 
@@ -847,4 +890,30 @@ mod tests {
                 .verify_proof(&merkle_tree.root(), index.into()));
         }
     }
+
+    #[test]
+    fn checksum_detects_changes_to_the_sliver() {
+        let symbol_size = NonZeroU16::new(2).unwrap();
+        let sliver = SliverData::<Primary>::new([1, 2, 3, 4], symbol_size, SliverIndex(0));
+        assert_eq!(sliver.checksum(), sliver.clone().checksum());
+
+        let mut corrupted = sliver.clone();
+        corrupted.symbols[1][0] ^= 1;
+        assert_ne!(corrupted.checksum(), sliver.checksum());
+
+        let other_index = SliverData::<Primary>::new([1, 2, 3, 4], symbol_size, SliverIndex(1));
+        assert_ne!(other_index.checksum(), sliver.checksum());
+
+        let other_symbol_size =
+            SliverData::<Primary>::new([1, 2, 3, 4], NonZeroU16::new(1).unwrap(), SliverIndex(0));
+        assert_ne!(other_symbol_size.checksum(), sliver.checksum());
+    }
+
+    #[test]
+    fn checksum_roundtrips_through_its_string() {
+        let checksum = SliverChecksum(0xbeef);
+        assert_eq!(checksum.to_string(), "000000000000beef");
+        assert_eq!(checksum.to_string().parse(), Ok(checksum));
+        assert!("not hex".parse::<SliverChecksum>().is_err());
+    }
 }
@@ -0,0 +1,317 @@
+// Copyright (c) Walrus Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Deterministic test vectors for the encoding of blobs.
+//!
+//! A test vector contains a blob derived from a seed and the outputs of its encoding: the blob ID,
+//! the metadata, the slivers, and the recovery symbols for one of the slivers. The outputs are
+//! in the formats in which they are exchanged with storage nodes, so that alternative
+//! implementations of the encoding can check their compatibility with this one.
+
+use alloc::vec::Vec;
+use core::num::NonZeroU16;
+
+use fastcrypto::hash::{Blake2b256, HashFunction};
+use serde::{Deserialize, Serialize};
+use serde_with::{base64::Base64, serde_as, DisplayFromStr};
+
+use super::{
+    DataTooLargeError,
+    EncodingConfig,
+    EncodingConfigTrait as _,
+    GeneralRecoverySymbol,
+    Primary,
+};
+use crate::{metadata::BlobMetadataApi as _, BlobId, EncodingType, SliverPairIndex, SliverType};
+
+/// A deterministic test vector for the encoding of a blob.
+///
+/// All byte strings are serialized as base64. The metadata and the recovery symbols are
+/// BCS-encoded, as in the API of the storage nodes, while the slivers contain the concatenated
+/// data of their symbols.
+#[serde_as]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EncodingTestVector {
+    /// The seed from which the blob is derived, see [`blob_from_seed`].
+    pub seed: u64,
+    /// The number of shards for which the blob is encoded.
+    pub n_shards: NonZeroU16,
+    /// The encoding type with which the blob is encoded.
+    pub encoding_type: EncodingType,
+    /// The blob.
+    #[serde_as(as = "Base64")]
+    pub blob: Vec<u8>,
+    /// The ID of the blob.
+    #[serde_as(as = "DisplayFromStr")]
+    pub blob_id: BlobId,
+    /// The BCS-encoded metadata of the blob.
+    #[serde_as(as = "Base64")]
+    pub metadata: Vec<u8>,
+    /// The sliver pairs of the blob, in the order of their indices.
+    pub sliver_pairs: Vec<SliverPairTestVector>,
+    /// The index of the sliver pair whose primary sliver is recovered by the recovery symbols.
+    pub recovery_target: SliverPairIndex,
+    /// The BCS-encoded recovery symbols for the primary sliver of the target pair, one from the
+    /// secondary sliver of each sliver pair, in the order of the source sliver pairs.
+    #[serde_as(as = "Vec<Base64>")]
+    pub recovery_symbols: Vec<Vec<u8>>,
+}
+
+/// The data of the slivers of a sliver pair in an [`EncodingTestVector`].
+#[serde_as]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SliverPairTestVector {
+    /// The data of the primary sliver.
+    #[serde_as(as = "Base64")]
+    pub primary: Vec<u8>,
+    /// The data of the secondary sliver.
+    #[serde_as(as = "Base64")]
+    pub secondary: Vec<u8>,
+}
+
+/// The first part of an [`EncodingTestVector`] that differs from the output of this
+/// implementation.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum TestVectorMismatchError {
+    /// The blob cannot be encoded for the number of shards.
+    #[error("the blob is too large to be encoded")]
+    DataTooLarge(#[from] DataTooLargeError),
+    /// The blob is not the one derived from the seed.
+    #[error("the blob does not match the one derived from the seed")]
+    Blob,
+    /// The blob ID differs.
+    #[error("the blob ID does not match")]
+    BlobId,
+    /// The metadata differs.
+    #[error("the metadata does not match")]
+    Metadata,
+    /// The number of sliver pairs differs.
+    #[error("the number of sliver pairs does not match")]
+    SliverPairCount,
+    /// A sliver differs.
+    #[error("the {sliver_type} sliver of pair {pair_index} does not match")]
+    Sliver {
+        /// The index of the sliver pair.
+        pair_index: SliverPairIndex,
+        /// The type of the sliver.
+        sliver_type: SliverType,
+    },
+    /// The target of the recovery symbols differs.
+    #[error("the target of the recovery symbols does not match")]
+    RecoveryTarget,
+    /// The number of recovery symbols differs.
+    #[error("the number of recovery symbols does not match")]
+    RecoverySymbolCount,
+    /// A recovery symbol differs.
+    #[error("the recovery symbol from sliver pair {source_pair_index} does not match")]
+    RecoverySymbol {
+        /// The index of the sliver pair from which the recovery symbol is computed.
+        source_pair_index: SliverPairIndex,
+    },
+}
+
+impl EncodingTestVector {
+    /// Generates the test vector for the blob of `blob_length` bytes derived from the `seed`.
+    ///
+    /// The recovery symbols are computed for the primary sliver of the pair with index `seed`
+    /// modulo `n_shards`.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`DataTooLargeError`] if the blob is too large to be encoded for `n_shards`.
+    pub fn generate(
+        n_shards: NonZeroU16,
+        encoding_type: EncodingType,
+        seed: u64,
+        blob_length: usize,
+    ) -> Result<Self, DataTooLargeError> {
+        let blob = blob_from_seed(seed, blob_length);
+        let encoding_config = EncodingConfig::new(n_shards);
+        let config = encoding_config.get_for_type(encoding_type);
+        let (sliver_pairs, metadata) = config.encode_with_metadata(&blob)?;
+
+        let recovery_target = SliverPairIndex(
+            (seed % u64::from(n_shards.get()))
+                .try_into()
+                .expect("the remainder is smaller than the number of shards"),
+        );
+        let target_index = recovery_target.to_sliver_index::<Primary>(n_shards);
+        let recovery_symbols = sliver_pairs
+            .iter()
+            .map(|pair| {
+                let symbol = pair
+                    .secondary
+                    .recovery_symbol_for_sliver(recovery_target, &config)
+                    .expect("the slivers of an encoded blob can be re-encoded");
+                bcs::to_bytes(&GeneralRecoverySymbol::from_recovery_symbol(
+                    symbol,
+                    target_index,
+                ))
+                .expect("successful encoding")
+            })
+            .collect();
+
+        Ok(Self {
+            seed,
+            n_shards,
+            encoding_type: metadata.metadata().encoding_type(),
+            blob,
+            blob_id: *metadata.blob_id(),
+            metadata: bcs::to_bytes(metadata.metadata()).expect("successful encoding"),
+            sliver_pairs: sliver_pairs
+                .iter()
+                .map(|pair| SliverPairTestVector {
+                    primary: pair.primary.symbols.data().clone(),
+                    secondary: pair.secondary.symbols.data().clone(),
+                })
+                .collect(),
+            recovery_target,
+            recovery_symbols,
+        })
+    }
+
+    /// Checks that this implementation produces the outputs of the test vector.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`TestVectorMismatchError`] identifying the first output that differs.
+    pub fn verify(&self) -> Result<(), TestVectorMismatchError> {
+        let expected = Self::generate(
+            self.n_shards,
+            self.encoding_type,
+            self.seed,
+            self.blob.len(),
+        )?;
+
+        crate::ensure!(self.blob == expected.blob, TestVectorMismatchError::Blob);
+        crate::ensure!(
+            self.blob_id == expected.blob_id,
+            TestVectorMismatchError::BlobId
+        );
+        crate::ensure!(
+            self.metadata == expected.metadata,
+            TestVectorMismatchError::Metadata
+        );
+        crate::ensure!(
+            self.sliver_pairs.len() == expected.sliver_pairs.len(),
+            TestVectorMismatchError::SliverPairCount
+        );
+        for (index, (pair, expected_pair)) in self
+            .sliver_pairs
+            .iter()
+            .zip(&expected.sliver_pairs)
+            .enumerate()
+        {
+            let pair_index = SliverPairIndex(index.try_into().expect("at most `n_shards` pairs"));
+            crate::ensure!(
+                pair.primary == expected_pair.primary,
+                TestVectorMismatchError::Sliver {
+                    pair_index,
+                    sliver_type: SliverType::Primary,
+                }
+            );
+            crate::ensure!(
+                pair.secondary == expected_pair.secondary,
+                TestVectorMismatchError::Sliver {
+                    pair_index,
+                    sliver_type: SliverType::Secondary,
+                }
+            );
+        }
+        crate::ensure!(
+            self.recovery_target == expected.recovery_target,
+            TestVectorMismatchError::RecoveryTarget
+        );
+        crate::ensure!(
+            self.recovery_symbols.len() == expected.recovery_symbols.len(),
+            TestVectorMismatchError::RecoverySymbolCount
+        );
+        for (index, (symbol, expected_symbol)) in self
+            .recovery_symbols
+            .iter()
+            .zip(&expected.recovery_symbols)
+            .enumerate()
+        {
+            crate::ensure!(
+                symbol == expected_symbol,
+                TestVectorMismatchError::RecoverySymbol {
+                    source_pair_index: SliverPairIndex(
+                        index.try_into().expect("at most `n_shards` symbols")
+                    ),
+                }
+            );
+        }
+        Ok(())
+    }
+}
+
+/// Returns the blob of `length` bytes derived from the `seed`.
+///
+/// The blob is the concatenation of the Blake2b-256 hashes of the little-endian encodings of the
+/// seed and of a counter starting at 0, each as a `u64`, truncated to `length` bytes.
+pub fn blob_from_seed(seed: u64, length: usize) -> Vec<u8> {
+    let mut blob = Vec::with_capacity(length);
+    let mut counter = 0u64;
+    while blob.len() < length {
+        let mut hash_fun = Blake2b256::default();
+        hash_fun.update(seed.to_le_bytes());
+        hash_fun.update(counter.to_le_bytes());
+        let digest = hash_fun.finalize().digest;
+        let n_bytes = digest.len().min(length - blob.len());
+        blob.extend_from_slice(&digest[..n_bytes]);
+        counter += 1;
+    }
+    blob
+}
+
+#[cfg(test)]
+mod tests {
+    use walrus_test_utils::{param_test, Result as TestResult};
+
+    use super::*;
+
+    param_test! {
+        test_generated_test_vectors_verify -> TestResult: [
+            one_byte_rs2: (EncodingType::RS2, 1),
+            small_rs2: (EncodingType::RS2, 31),
+            large_rs2: (EncodingType::RS2, 10_000),
+            small_raptorq: (EncodingType::RedStuffRaptorQ, 31),
+        ]
+    }
+    fn test_generated_test_vectors_verify(
+        encoding_type: EncodingType,
+        blob_length: usize,
+    ) -> TestResult {
+        let n_shards = NonZeroU16::new(10).unwrap();
+        let test_vector = EncodingTestVector::generate(n_shards, encoding_type, 42, blob_length)?;
+        assert_eq!(test_vector.blob.len(), blob_length);
+        assert_eq!(test_vector.sliver_pairs.len(), 10);
+        assert_eq!(test_vector.recovery_symbols.len(), 10);
+        assert_eq!(test_vector.recovery_target, SliverPairIndex(2));
+        test_vector.verify()?;
+
+        let mut modified = test_vector.clone();
+        modified.sliver_pairs[3].secondary[0] ^= 1;
+        assert_eq!(
+            modified.verify(),
+            Err(TestVectorMismatchError::Sliver {
+                pair_index: SliverPairIndex(3),
+                sliver_type: SliverType::Secondary,
+            })
+        );
+
+        let mut modified = test_vector;
+        modified.seed += 1;
+        assert_eq!(modified.verify(), Err(TestVectorMismatchError::Blob));
+        Ok(())
+    }
+
+    #[test]
+    fn test_blob_from_seed_is_deterministic() {
+        assert_eq!(blob_from_seed(7, 100), blob_from_seed(7, 100));
+        assert_eq!(blob_from_seed(7, 100)[..40], blob_from_seed(7, 40)[..]);
+        assert_ne!(blob_from_seed(7, 100), blob_from_seed(8, 100));
+        assert!(blob_from_seed(7, 0).is_empty());
+    }
+}
@@ -62,6 +62,48 @@ pub fn packet_to_data(packet: EncodingPacket) -> Vec<u8> {
     packet.split().1
 }
 
+/// Computes `f` for all indices in `0..n` and collects the results in order.
+///
+/// The calls are distributed over the current rayon thread pool if the `parallel` feature is
+/// enabled.
+pub fn map_indices<T: Send>(n: usize, f: impl Fn(usize) -> T + Send + Sync) -> Vec<T> {
+    #[cfg(feature = "parallel")]
+    {
+        use rayon::iter::{IntoParallelIterator as _, ParallelIterator as _};
+        (0..n).into_par_iter().map(f).collect()
+    }
+    #[cfg(not(feature = "parallel"))]
+    {
+        (0..n).map(f).collect()
+    }
+}
+
+/// Calls `f` with the index and a mutable reference of each of the `items`.
+///
+/// The calls are distributed over the current rayon thread pool if the `parallel` feature is
+/// enabled.
+pub fn for_each_indexed_mut<T: Send>(items: &mut [T], f: impl Fn(usize, &mut T) + Send + Sync) {
+    #[cfg(feature = "parallel")]
+    {
+        use rayon::iter::{
+            IndexedParallelIterator as _,
+            IntoParallelRefMutIterator as _,
+            ParallelIterator as _,
+        };
+        items
+            .par_iter_mut()
+            .enumerate()
+            .for_each(|(index, item)| f(index, item));
+    }
+    #[cfg(not(feature = "parallel"))]
+    {
+        items
+            .iter_mut()
+            .enumerate()
+            .for_each(|(index, item)| f(index, item));
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use walrus_test_utils::param_test;
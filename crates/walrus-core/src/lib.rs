@@ -64,7 +64,9 @@ pub mod keys;
 pub mod merkle;
 pub mod messages;
 pub mod metadata;
+pub mod quilt;
 pub mod utils;
+pub mod verification;
 
 /// A public key for protocol messages.
 pub type PublicKey = BLS12381PublicKey;
@@ -258,6 +260,15 @@ impl SuiObjectId {
     pub const LENGTH: usize = 32;
 }
 
+/// Formats the object ID as a `0x`-prefixed hexadecimal string, like the `ObjectID` of the sui
+/// sdk.
+impl fmt::Display for SuiObjectId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("0x")?;
+        self.0.iter().try_for_each(|byte| write!(f, "{byte:02x}"))
+    }
+}
+
 #[cfg(feature = "sui-types")]
 impl From<ObjectID> for SuiObjectId {
     fn from(value: ObjectID) -> Self {
@@ -505,6 +516,11 @@ impl Sliver {
         by_axis::flat_map!(self.as_ref(), |x| x.is_empty())
     }
 
+    /// Returns the [`SliverChecksum`][encoding::SliverChecksum] of the sliver.
+    pub fn checksum(&self) -> encoding::SliverChecksum {
+        by_axis::flat_map!(self.as_ref(), |x| x.checksum())
+    }
+
     /// Checks that the provided sliver is authenticated by the metadata.
     ///
     /// The checks include verifying that the sliver has the correct length and symbol size, and
@@ -2,7 +2,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 //! Merkle tree implementation for Walrus.
-use alloc::{format, vec::Vec};
+use alloc::{collections::BTreeMap, format, vec::Vec};
 use core::{fmt::Debug, marker::PhantomData};
 
 use fastcrypto::hash::{Blake2b256, Digest, HashFunction};
@@ -17,6 +17,12 @@ const LEAF_PREFIX: [u8; 1] = [0];
 const INNER_PREFIX: [u8; 1] = [1];
 const EMPTY_NODE: [u8; DIGEST_LEN] = [0; DIGEST_LEN];
 
+/// The maximum length of the path of a [`MerkleProof`] that can be verified.
+///
+/// Proofs are received from untrusted parties, and longer paths would overflow the shifts of the
+/// leaf index. No tree can have this many levels, so such proofs are rejected as invalid.
+const MAX_PROOF_DEPTH: usize = usize::BITS as usize - 1;
+
 /// Returned if the specified index is out of bounds for a Merkle tree or proof.
 #[derive(Error, Debug, PartialEq, Eq)]
 #[error("index {0} is too large")]
@@ -74,6 +80,54 @@ pub trait MerkleAuth: Clone + alloc::fmt::Debug {
     ///
     /// Returns `None` if the provided index is too large.
     fn compute_root(&self, leaf: &[u8], leaf_index: usize) -> Option<Node>;
+
+    /// Verifies the proof against the root of the `authenticated` nodes, reusing and extending
+    /// the nodes authenticated by previously verified proofs.
+    ///
+    /// Returns the same result as [`verify_proof`][Self::verify_proof]. Implementations may stop
+    /// the verification as soon as they reach an authenticated node, so that proofs of several
+    /// leaves of the same tree share the computation of their common nodes. The provided
+    /// implementation verifies the proof independently.
+    fn verify_proof_with(
+        &self,
+        authenticated: &mut AuthenticatedNodes,
+        leaf: &[u8],
+        leaf_index: usize,
+    ) -> bool {
+        self.verify_proof(authenticated.root(), leaf, leaf_index)
+    }
+}
+
+/// The nodes of a Merkle tree that have been authenticated against its root by verified proofs.
+///
+/// A node is authenticated if the hashes on the path from the node to the root have been checked to
+/// result in the root. Each verified proof authenticates the nodes on its path and their siblings.
+/// The nodes must only be used to verify proofs of a single type with
+/// [`MerkleAuth::verify_proof_with`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuthenticatedNodes {
+    root: Node,
+    /// The length of the paths of the proofs that authenticated the nodes.
+    depth: Option<usize>,
+    /// The authenticated nodes, indexed by their level above the leaves and their index within
+    /// the level.
+    nodes: BTreeMap<(usize, usize), Node>,
+}
+
+impl AuthenticatedNodes {
+    /// Creates a new set of authenticated nodes, containing only the `root`.
+    pub fn new(root: Node) -> Self {
+        Self {
+            root,
+            depth: None,
+            nodes: BTreeMap::new(),
+        }
+    }
+
+    /// Returns the root of the tree.
+    pub fn root(&self) -> &Node {
+        &self.root
+    }
 }
 
 /// A proof that some data is at index `leaf_index` in a [`MerkleTree`].
@@ -123,7 +177,7 @@ where
     T: HashFunction<DIGEST_LEN>,
 {
     fn compute_root(&self, leaf: &[u8], leaf_index: usize) -> Option<Node> {
-        if leaf_index >> self.path.len() != 0 {
+        if self.path.len() > MAX_PROOF_DEPTH || leaf_index >> self.path.len() != 0 {
             return None;
         }
         let mut current_hash = leaf_hash::<T>(leaf);
@@ -142,6 +196,60 @@ where
         }
         Some(current_hash)
     }
+
+    fn verify_proof_with(
+        &self,
+        authenticated: &mut AuthenticatedNodes,
+        leaf: &[u8],
+        leaf_index: usize,
+    ) -> bool {
+        let depth = self.path.len();
+        if depth > MAX_PROOF_DEPTH || leaf_index >> depth != 0 {
+            return false;
+        }
+        if *authenticated.depth.get_or_insert(depth) != depth {
+            // The authenticated nodes are positioned in a tree of a different depth.
+            return self.verify_proof(&authenticated.root, leaf, leaf_index);
+        }
+
+        // The nodes on the path and their siblings, which are authenticated if the proof is valid.
+        let mut path_nodes = Vec::with_capacity(2 * depth);
+        let mut current_hash = leaf_hash::<T>(leaf);
+        let mut level_index = leaf_index;
+        for (level, sibling) in self.path.iter().enumerate() {
+            if let Some(authenticated_hash) = authenticated.nodes.get(&(level, level_index)) {
+                // The ancestors of an authenticated node and their siblings are authenticated, so
+                // the remainder of the path only needs to match them.
+                let remainder_matches =
+                    self.path[level..]
+                        .iter()
+                        .zip(level..)
+                        .all(|(sibling, level)| {
+                            let index = (leaf_index >> level) ^ 1;
+                            authenticated.nodes.get(&(level, index)) == Some(sibling)
+                        });
+                if *authenticated_hash != current_hash || !remainder_matches {
+                    return false;
+                }
+                authenticated.nodes.extend(path_nodes);
+                return true;
+            }
+            path_nodes.push(((level, level_index), current_hash.clone()));
+            path_nodes.push(((level, level_index ^ 1), sibling.clone()));
+            current_hash = if level_index % 2 == 0 {
+                inner_hash::<T>(&current_hash, sibling)
+            } else {
+                inner_hash::<T>(sibling, &current_hash)
+            };
+            level_index /= 2;
+        }
+
+        if current_hash != authenticated.root {
+            return false;
+        }
+        authenticated.nodes.extend(path_nodes);
+        true
+    }
 }
 
 impl<T> PartialEq for MerkleProof<T> {
@@ -278,17 +386,102 @@ where
     }
 }
 
+/// Computes the root of a [`MerkleTree`] over leaf hashes that are provided one at a time.
+///
+/// Only the roots of the complete subtrees over the leaves provided so far are stored, i.e., a
+/// number of nodes that is logarithmic in the number of leaves.
+pub(crate) struct MerkleRootBuilder<T = Blake2b256> {
+    _hash_type: PhantomData<T>,
+    // The roots of the complete subtrees together with their heights, in decreasing order of
+    // height.
+    subtrees: Vec<(Node, u32)>,
+}
+
+impl<T> core::fmt::Debug for MerkleRootBuilder<T> {
+    fn fmt(&self, fmt: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        fmt.debug_struct(&format!(
+            "MerkleRootBuilder<{}>",
+            core::any::type_name::<T>()
+        ))
+        .field("subtrees", &self.subtrees)
+        .finish()
+    }
+}
+
+impl<T> MerkleRootBuilder<T>
+where
+    T: HashFunction<DIGEST_LEN>,
+{
+    /// Creates a new builder without any leaves.
+    pub(crate) fn new() -> Self {
+        Self {
+            _hash_type: PhantomData,
+            subtrees: Vec::new(),
+        }
+    }
+
+    /// Adds the hash of the next leaf of the tree.
+    pub(crate) fn push_leaf_hash(&mut self, leaf_hash: Node) {
+        let mut node = leaf_hash;
+        let mut height = 0;
+        while self
+            .subtrees
+            .last()
+            .is_some_and(|(_, last_height)| *last_height == height)
+        {
+            let (left, _) = self.subtrees.pop().expect("the subtree exists");
+            node = inner_hash::<T>(&left, &node);
+            height += 1;
+        }
+        self.subtrees.push((node, height));
+    }
+
+    /// Returns the root of the Merkle tree over the leaves added so far.
+    ///
+    /// The result is the same as the root of the tree built with
+    /// [`MerkleTree::build_from_leaf_hashes`] from the same leaf hashes.
+    pub(crate) fn root(mut self) -> Node {
+        let Some((mut node, mut height)) = self.subtrees.pop() else {
+            return Node::Empty;
+        };
+        // Levels with an odd number of nodes are completed with an empty node, as in
+        // `MerkleTree::build_from_leaf_hashes`.
+        while let Some(&(_, left_height)) = self.subtrees.last() {
+            node = if left_height == height {
+                let (left, _) = self.subtrees.pop().expect("the subtree exists");
+                inner_hash::<T>(&left, &node)
+            } else {
+                inner_hash::<T>(&node, &Node::Empty)
+            };
+            height += 1;
+        }
+        node
+    }
+}
+
 /// Computes the hash of the provided input to be used as a leaf hash of a Merkle tree.
 pub(crate) fn leaf_hash<T>(input: &[u8]) -> Node
 where
     T: HashFunction<DIGEST_LEN>,
 {
-    let mut hash_fun = T::default();
-    hash_fun.update(LEAF_PREFIX);
+    let mut hash_fun = leaf_hasher::<T>();
     hash_fun.update(input);
     hash_fun.finalize().into()
 }
 
+/// Returns a hash function to compute the leaf hash of an input that is provided incrementally.
+///
+/// Updating the returned hash function with the complete input and finalizing it yields the same
+/// result as [`leaf_hash`].
+pub(crate) fn leaf_hasher<T>() -> T
+where
+    T: HashFunction<DIGEST_LEN>,
+{
+    let mut hash_fun = T::default();
+    hash_fun.update(LEAF_PREFIX);
+    hash_fun
+}
+
 fn inner_hash<T>(left: &Node, right: &Node) -> Node
 where
     T: HashFunction<DIGEST_LEN>,
@@ -375,6 +568,19 @@ mod test {
         }
     }
 
+    #[test]
+    fn test_merkle_path_too_long_is_rejected() {
+        let mt: MerkleTree = MerkleTree::build(TEST_INPUT);
+        let path: Vec<_> = core::iter::repeat_n(Node::Empty, MAX_PROOF_DEPTH + 1).collect();
+        let proof = MerkleProof::<Blake2b256>::new(&path);
+        assert!(!proof.verify_proof(&mt.root(), TEST_INPUT[0], 0));
+        assert!(!proof.verify_proof_with(
+            &mut AuthenticatedNodes::new(mt.root()),
+            TEST_INPUT[0],
+            0
+        ));
+    }
+
     #[test]
     fn test_merkle_path_verify() {
         for i in 0..TEST_INPUT.len() {
@@ -386,6 +592,59 @@ mod test {
         }
     }
 
+    #[test]
+    fn test_merkle_root_builder_matches_merkle_tree() {
+        for n_leaves in 0..=33 {
+            let leaf_hashes: Vec<_> = (0..n_leaves)
+                .map(|i: u32| leaf_hash::<Blake2b256>(&i.to_le_bytes()))
+                .collect();
+            let mut builder = MerkleRootBuilder::<Blake2b256>::new();
+            leaf_hashes
+                .iter()
+                .for_each(|leaf_hash| builder.push_leaf_hash(leaf_hash.clone()));
+
+            let mt: MerkleTree = MerkleTree::build_from_leaf_hashes(leaf_hashes);
+            assert_eq!(builder.root(), mt.root(), "n_leaves = {n_leaves}");
+        }
+    }
+
+    #[test]
+    fn test_merkle_path_verify_with_authenticated_nodes() {
+        for i in 0..TEST_INPUT.len() {
+            let mt: MerkleTree = MerkleTree::build(&TEST_INPUT[..i]);
+            let mut authenticated = AuthenticatedNodes::new(mt.root());
+            // Verify the proofs in an order in which consecutive proofs share few nodes.
+            for index in (0..i).step_by(2).chain((1..i).step_by(2)) {
+                let proof = mt.get_proof(index).unwrap();
+                assert!(!proof.verify_proof_with(&mut authenticated, b"wrong", index));
+                assert!(proof.verify_proof_with(&mut authenticated, TEST_INPUT[index], index));
+                assert!(!proof.verify_proof_with(&mut authenticated, TEST_INPUT[index], index ^ 1));
+            }
+        }
+    }
+
+    #[test]
+    fn test_merkle_path_verify_with_authenticated_nodes_rejects_forged_siblings() {
+        let mt: MerkleTree = MerkleTree::build(TEST_INPUT);
+        let mut authenticated = AuthenticatedNodes::new(mt.root());
+        assert!(mt
+            .get_proof(0)
+            .unwrap()
+            .verify_proof_with(&mut authenticated, TEST_INPUT[0], 0));
+
+        // Proofs with forged hashes must not be accepted, even if the leaf or its ancestors are
+        // authenticated.
+        for level in 0..3 {
+            let mut forged_proof = mt.get_proof(1).unwrap();
+            forged_proof.path[level] = Node::Digest([1; DIGEST_LEN]);
+            assert!(!forged_proof.verify_proof_with(&mut authenticated, TEST_INPUT[1], 1));
+        }
+        assert!(mt
+            .get_proof(1)
+            .unwrap()
+            .verify_proof_with(&mut authenticated, TEST_INPUT[1], 1));
+    }
+
     #[test]
     fn test_merkle_path_verify_fails_for_wrong_index() {
         for i in 0..TEST_INPUT.len() {
@@ -33,6 +33,14 @@ pub use invalid_blob_id::{InvalidBlobIdAttestation, InvalidBlobIdMsg};
 mod sync_shard;
 pub use sync_shard::{SignedSyncShardRequest, SyncShardMsg, SyncShardRequest, SyncShardResponse};
 
+mod epoch_accounting;
+pub use epoch_accounting::{
+    EpochAccountingMsg,
+    EpochAccountingReport,
+    ShardOccupancy,
+    SignedEpochAccountingReport,
+};
+
 mod certificate;
 pub use certificate::{CertificateError, ConfirmationCertificate, InvalidBlobCertificate};
 
@@ -183,6 +191,9 @@ wrapped_uint! {
         /// Intent type for invalid blob id messages.
         /// Note that this message is only used for communication between storage nodes.
         pub const SYNC_SHARD_MSG: Self = Self(3);
+        /// Intent type for epoch accounting reports.
+        /// Note that this message is only used for reports to the operator of a storage node.
+        pub const EPOCH_ACCOUNTING_MSG: Self = Self(4);
     }
 }
 
@@ -0,0 +1,125 @@
+// Copyright (c) Walrus Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use alloc::vec::Vec;
+
+use serde::{Deserialize, Serialize};
+
+use super::{Intent, InvalidIntent, MessageVerificationError, ProtocolMessage, SignedMessage};
+use crate::{messages::IntentType, Epoch, PublicKey, ShardIndex};
+
+/// The bytes stored by a storage node for one of its shards during an epoch.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ShardOccupancy {
+    /// The index of the shard.
+    pub shard: ShardIndex,
+    /// The number of sliver bytes stored for the shard during the epoch.
+    pub bytes_stored: u64,
+}
+
+/// The storage accounting of a storage node for a single epoch.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EpochAccountingReport {
+    /// The epoch that is accounted for.
+    pub epoch: Epoch,
+    /// The number of sliver bytes served to clients and other storage nodes during the epoch.
+    pub bytes_served: u64,
+    /// The number of sliver bytes stored during the epoch, across all shards.
+    pub bytes_stored: u64,
+    /// The occupancy of the shards owned by the node during the epoch, sorted by shard index.
+    pub shards: Vec<ShardOccupancy>,
+}
+
+/// A message containing the storage accounting of a storage node for an epoch.
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+#[serde(try_from = "ProtocolMessage<EpochAccountingReport>")]
+pub struct EpochAccountingMsg(pub(crate) ProtocolMessage<EpochAccountingReport>);
+
+impl EpochAccountingMsg {
+    const INTENT: Intent = Intent::storage(IntentType::EPOCH_ACCOUNTING_MSG);
+
+    /// Creates a new EpochAccountingMsg message generated in `epoch` for the provided report.
+    pub fn new(epoch: Epoch, report: EpochAccountingReport) -> Self {
+        Self(ProtocolMessage {
+            intent: Intent::storage(IntentType::EPOCH_ACCOUNTING_MSG),
+            epoch,
+            message_contents: report,
+        })
+    }
+}
+
+impl TryFrom<ProtocolMessage<EpochAccountingReport>> for EpochAccountingMsg {
+    type Error = InvalidIntent;
+    fn try_from(
+        protocol_message: ProtocolMessage<EpochAccountingReport>,
+    ) -> Result<Self, Self::Error> {
+        if protocol_message.intent == Self::INTENT {
+            Ok(Self(protocol_message))
+        } else {
+            Err(InvalidIntent {
+                expected: Self::INTENT,
+                actual: protocol_message.intent,
+            })
+        }
+    }
+}
+
+impl AsRef<ProtocolMessage<EpochAccountingReport>> for EpochAccountingMsg {
+    fn as_ref(&self) -> &ProtocolMessage<EpochAccountingReport> {
+        &self.0
+    }
+}
+
+/// A signed [`EpochAccountingMsg`] from a storage node.
+pub type SignedEpochAccountingReport = SignedMessage<EpochAccountingMsg>;
+
+impl SignedEpochAccountingReport {
+    /// Verifies the signature on the report under the specified public key and returns the
+    /// report.
+    pub fn verify(
+        &self,
+        public_key: &PublicKey,
+    ) -> Result<EpochAccountingReport, MessageVerificationError> {
+        self.verify_signature_and_get_message(public_key)
+            .map(|message| message.0.message_contents)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::messages::{IntentAppId, IntentVersion};
+
+    const EPOCH: Epoch = 21;
+
+    #[test]
+    fn msg_has_correct_header() {
+        let report = EpochAccountingReport {
+            epoch: EPOCH - 1,
+            bytes_served: 1,
+            bytes_stored: 2,
+            shards: alloc::vec![ShardOccupancy {
+                shard: ShardIndex(3),
+                bytes_stored: 2,
+            }],
+        };
+        let msg = EpochAccountingMsg::new(EPOCH, report.clone());
+        let encoded = bcs::to_bytes(&msg).expect("successful encoding");
+
+        assert_eq!(
+            encoded[..3],
+            [
+                IntentType::EPOCH_ACCOUNTING_MSG.0,
+                IntentVersion::default().0,
+                IntentAppId::STORAGE.0
+            ]
+        );
+        assert_eq!(encoded[3..7], EPOCH.to_le_bytes());
+        assert_eq!(
+            encoded[7..],
+            bcs::to_bytes(&report).expect("successful encoding")
+        );
+    }
+}
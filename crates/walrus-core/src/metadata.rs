@@ -3,7 +3,7 @@
 
 //! Metadata associated with a Blob and stored by storage nodes.
 
-use alloc::vec::Vec;
+use alloc::{borrow::Cow, vec::Vec};
 use core::num::NonZeroU16;
 
 use enum_dispatch::enum_dispatch;
@@ -13,13 +13,15 @@ use serde::{Deserialize, Serialize};
 use crate::{
     encoding::{
         encoded_blob_length_for_n_shards,
-        source_symbols_for_n_shards,
+        source_symbols_for_version,
         DataTooLargeError,
         EncodingAxis,
         EncodingConfig,
-        EncodingConfigTrait as _,
+        EncodingConfigTrait,
+        ENCODING_VERSION,
     },
-    merkle::{MerkleTree, Node as MerkleNode, DIGEST_LEN},
+    merkle::{leaf_hash, MerkleRootBuilder, MerkleTree, Node as MerkleNode, DIGEST_LEN},
+    verification,
     BlobId,
     EncodingType,
     SliverPairIndex,
@@ -44,6 +46,17 @@ pub enum VerificationError {
     /// available in the configuration provided.
     #[error("the unencoded blob length is too large for the given config")]
     UnencodedLengthTooLarge,
+    /// The encoded metadata is malformed, i.e., it cannot be decoded or contains trailing data.
+    #[error("the encoded metadata is malformed")]
+    MalformedMetadata,
+    /// The encoding parameters in the metadata were derived with an encoding version that is not
+    /// supported.
+    #[error("the metadata uses the unsupported encoding version {0}")]
+    UnsupportedEncodingVersion(u8),
+    /// The encoding parameters in the metadata do not match those derived with their encoding
+    /// version for the number of shards.
+    #[error("the encoding parameters in the metadata do not match their encoding version")]
+    EncodingParamsMismatch,
 }
 
 /// [`BlobMetadataWithId`] that has been verified with [`UnverifiedBlobMetadataWithId::verify`].
@@ -51,6 +64,8 @@ pub enum VerificationError {
 /// This ensures the following properties:
 /// - The unencoded length is nonzero and not larger than the maximum blob size.
 /// - The number of sliver hashes matches the number of slivers (twice the number of shards).
+/// - The encoding parameters, if stored in the metadata, have a supported encoding version and
+///   are those derived with this version for the number of shards of the encoding config.
 /// - The blob ID is correctly computed from the sliver hashes.
 pub type VerifiedBlobMetadataWithId = BlobMetadataWithId<true>;
 
@@ -77,7 +92,9 @@ impl<const V: bool> BlobMetadataWithId<V> {
 
     /// Creates a new verified metadata starting from the components of the metadata.
     ///
-    /// The verification is implicit as the blob ID is created directly from the metadata.
+    /// The verification is implicit as the blob ID is created directly from the metadata. The
+    /// metadata is created with [`BlobMetadata::new`], and therefore does not store the encoding
+    /// parameters.
     pub fn new_verified_from_metadata(
         sliver_pair_meta: Vec<SliverPairMetadata>,
         encoding: EncodingType,
@@ -125,7 +142,11 @@ impl VerifiedBlobMetadataWithId {
     /// matches that which was used to verify the metadata.
     pub fn is_encoding_config_applicable(&self, config: &EncodingConfig) -> bool {
         let encoding_type = self.metadata.encoding_type();
-        let (n_primary, n_secondary) = source_symbols_for_n_shards(self.n_shards(), encoding_type);
+        let (n_primary, n_secondary) = match self.metadata.encoding_params() {
+            Some(params) => params.source_symbols(),
+            None => BlobMetadataV1::derived_encoding_params(self.n_shards(), encoding_type)
+                .source_symbols(),
+        };
         let config = config.get_for_type(encoding_type);
 
         self.n_shards() == config.n_shards()
@@ -141,6 +162,26 @@ impl VerifiedBlobMetadataWithId {
         let n_hashes = self.metadata.hashes().len();
         NonZeroU16::new(n_hashes as u16).expect("verified metadata has a valid number of shards")
     }
+
+    /// Converts the metadata to [`BlobMetadata::V2`], which additionally stores the encoding
+    /// parameters of the blob.
+    ///
+    /// The blob ID is unchanged, as the encoding parameters are not part of its computation.
+    /// Storage nodes that do not know [`BlobMetadata::V2`] reject the converted metadata, so it
+    /// must only be sent to the nodes once all nodes of the committee advertise support for it.
+    ///
+    /// Metadata that already stores the encoding parameters is returned unchanged.
+    pub fn with_encoding_params(self) -> VerifiedBlobMetadataWithId {
+        if self.metadata.encoding_params().is_some() {
+            return self;
+        }
+        let encoding_params =
+            BlobMetadataV1::derived_encoding_params(self.n_shards(), self.metadata.encoding_type());
+        BlobMetadataWithId {
+            blob_id: self.blob_id,
+            metadata: self.metadata.with_encoding_params(encoding_params),
+        }
+    }
 }
 
 impl UnverifiedBlobMetadataWithId {
@@ -151,31 +192,25 @@ impl UnverifiedBlobMetadataWithId {
         self,
         config: &EncodingConfig,
     ) -> Result<VerifiedBlobMetadataWithId, VerificationError> {
-        let n_hashes = self.metadata().hashes().len();
-        let n_shards = config.n_shards.get().into();
-        crate::ensure!(
-            n_hashes == n_shards,
-            VerificationError::InvalidHashCount {
-                actual: n_hashes,
-                expected: n_shards,
-            }
-        );
+        verification::verify_hash_count(&self.metadata, config.n_shards)?;
+        let type_config = config.get_for_type(self.metadata.encoding_type());
+        if let Some(params) = self.metadata.encoding_params() {
+            params.verify_config(&type_config)?;
+        }
         crate::ensure!(
-            self.metadata.unencoded_length()
-                <= config
-                    .get_for_type(self.metadata.encoding_type())
-                    .max_blob_size(),
+            self.metadata.unencoded_length() <= type_config.max_blob_size(),
             VerificationError::UnencodedLengthTooLarge
         );
-        let computed_blob_id = BlobId::from_sliver_pair_metadata(&self.metadata);
-        crate::ensure!(
-            computed_blob_id == *self.blob_id(),
-            VerificationError::BlobIdMismatch
-        );
-        Ok(BlobMetadataWithId {
+        verification::verify_blob_id(&self.blob_id, &self.metadata)?;
+        Ok(self.into_verified_unchecked())
+    }
+
+    /// Converts the metadata into a verified one, without running the verification.
+    pub(crate) fn into_verified_unchecked(self) -> VerifiedBlobMetadataWithId {
+        BlobMetadataWithId {
             blob_id: self.blob_id,
             metadata: self.metadata,
-        })
+        }
     }
 }
 
@@ -215,6 +250,13 @@ pub trait BlobMetadataApi {
 
     /// Returns the hashes of the sliver pairs of the blob.
     fn hashes(&self) -> &Vec<SliverPairMetadata>;
+
+    /// Returns the encoding parameters stored in the metadata.
+    ///
+    /// Returns `None` for versions of the metadata that do not store them, for which the
+    /// parameters are derived from the number of shards with version
+    /// [`BlobMetadataV1::ENCODING_VERSION`] of the derivation.
+    fn encoding_params(&self) -> Option<&EncodingParams>;
 }
 
 /// Metadata about a blob.
@@ -223,11 +265,17 @@ pub trait BlobMetadataApi {
 pub enum BlobMetadata {
     /// Version 1 of the blob metadata.
     V1(BlobMetadataV1),
+    /// Version 2 of the blob metadata, which additionally stores the encoding parameters.
+    V2(BlobMetadataV2),
 }
 
 impl BlobMetadata {
     /// Creates a new [`BlobMetadata`] with the given encoding type, unencoded length, and sliver
     /// hashes.
+    ///
+    /// The metadata is created as [`BlobMetadata::V1`], which is understood by all storage nodes,
+    /// see [`VerifiedBlobMetadataWithId::with_encoding_params`] for the conversion to
+    /// [`BlobMetadata::V2`].
     pub fn new(
         encoding_type: EncodingType,
         unencoded_length: u64,
@@ -240,25 +288,160 @@ impl BlobMetadata {
         })
     }
 
+    /// Returns the metadata as [`BlobMetadata::V2`], storing the given encoding parameters.
+    ///
+    /// The encoding parameters of metadata that is already [`BlobMetadata::V2`] are replaced.
+    pub fn with_encoding_params(self, encoding_params: EncodingParams) -> BlobMetadata {
+        let (encoding_type, unencoded_length, hashes) = match self {
+            BlobMetadata::V1(inner) => (inner.encoding_type, inner.unencoded_length, inner.hashes),
+            BlobMetadata::V2(inner) => (inner.encoding_type, inner.unencoded_length, inner.hashes),
+        };
+        BlobMetadata::V2(BlobMetadataV2 {
+            encoding_type,
+            encoding_params,
+            unencoded_length,
+            hashes,
+        })
+    }
+
+    /// Returns the metadata as [`BlobMetadata::V1`], without the encoding parameters.
+    ///
+    /// Verified metadata only stores the encoding parameters derived from the number of shards,
+    /// see [`EncodingParams`], so no information is lost. Storage nodes store the metadata they
+    /// receive in this format, which can also be read by earlier versions of the nodes.
+    pub fn to_v1(&self) -> Cow<'_, BlobMetadata> {
+        match self {
+            BlobMetadata::V1(_) => Cow::Borrowed(self),
+            BlobMetadata::V2(inner) => Cow::Owned(BlobMetadata::new(
+                inner.encoding_type,
+                inner.unencoded_length,
+                inner.hashes.clone(),
+            )),
+        }
+    }
+
     /// Returns the encoding type of the blob.
     pub fn encoding_type(&self) -> EncodingType {
         match self {
             BlobMetadata::V1(inner) => inner.encoding_type,
+            BlobMetadata::V2(inner) => inner.encoding_type,
         }
     }
 
-    /// Returns a mutable reference to the inner [`BlobMetadataV1`].
+    /// Returns a mutable reference to the hashes of the sliver pairs.
     ///
     /// This is only available in tests.
     #[cfg(any(test, feature = "test-utils"))]
-    pub fn mut_inner(&mut self) -> &mut BlobMetadataV1 {
+    pub fn hashes_mut(&mut self) -> &mut Vec<SliverPairMetadata> {
         match self {
-            BlobMetadata::V1(inner) => inner,
+            BlobMetadata::V1(inner) => &mut inner.hashes,
+            BlobMetadata::V2(inner) => &mut inner.hashes,
         }
     }
+
+    /// Returns a mutable reference to the unencoded length of the blob.
+    ///
+    /// This is only available in tests.
+    #[cfg(any(test, feature = "test-utils"))]
+    pub fn unencoded_length_mut(&mut self) -> &mut u64 {
+        match self {
+            BlobMetadata::V1(inner) => &mut inner.unencoded_length,
+            BlobMetadata::V2(inner) => &mut inner.unencoded_length,
+        }
+    }
+}
+
+/// The parameters with which a blob is encoded.
+///
+/// The parameters are derived from the number of shards, with the version of the derivation given
+/// by `version`. When verifying the metadata, the parameters are checked against those derived
+/// with their version for the number of shards of the system, see [`Self::verify_derivation`], so
+/// that blobs encoded with any supported version are accepted. Whether a blob can then be decoded
+/// with a given encoding config is checked by
+/// [`VerifiedBlobMetadataWithId::is_encoding_config_applicable`].
+///
+/// The parameters are not bound to the blob ID, which is computed from the sliver hashes, the
+/// encoding type, and the unencoded length only. Whoever serves the metadata can therefore replace
+/// the parameters without changing the blob ID; the verification against the derivation limits
+/// this to swapping the version for another supported version.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EncodingParams {
+    /// The version of the derivation of the parameters from the number of shards.
+    pub version: u8,
+    /// The number of primary source symbols, i.e., the number of symbols of secondary slivers.
+    pub n_primary_source_symbols: NonZeroU16,
+    /// The number of secondary source symbols, i.e., the number of symbols of primary slivers.
+    pub n_secondary_source_symbols: NonZeroU16,
+}
+
+impl EncodingParams {
+    /// The length of the BCS-encoded parameters.
+    const ENCODED_LENGTH: usize = 1 + 2 * size_of::<u16>();
+
+    /// Returns the parameters of the encoding config, with the current [`ENCODING_VERSION`].
+    pub fn from_config<T: EncodingConfigTrait>(config: &T) -> Self {
+        Self {
+            version: ENCODING_VERSION,
+            n_primary_source_symbols: config.n_primary_source_symbols(),
+            n_secondary_source_symbols: config.n_secondary_source_symbols(),
+        }
+    }
+
+    /// Returns the numbers of primary and secondary source symbols.
+    pub fn source_symbols(&self) -> (NonZeroU16, NonZeroU16) {
+        (
+            self.n_primary_source_symbols,
+            self.n_secondary_source_symbols,
+        )
+    }
+
+    /// Checks that the encoding version of the parameters is supported and that the parameters
+    /// are those derived with this version for the number of shards and the encoding type of the
+    /// `config`.
+    ///
+    /// The parameters are not compared with those of the `config` itself, which uses the current
+    /// [`ENCODING_VERSION`], so that the metadata of blobs encoded with previous versions remains
+    /// valid.
+    pub fn verify_config<T: EncodingConfigTrait>(
+        &self,
+        config: &T,
+    ) -> Result<(), VerificationError> {
+        self.verify_derivation(config.n_shards(), config.encoding_type())
+    }
+
+    /// Checks that the parameters are those derived for `n_shards` with their encoding version.
+    pub fn verify_derivation(
+        &self,
+        n_shards: NonZeroU16,
+        encoding_type: EncodingType,
+    ) -> Result<(), VerificationError> {
+        let source_symbols = source_symbols_for_version(self.version, n_shards, encoding_type)
+            .ok_or(VerificationError::UnsupportedEncodingVersion(self.version))?;
+        crate::ensure!(
+            self.source_symbols() == source_symbols,
+            VerificationError::EncodingParamsMismatch
+        );
+        Ok(())
+    }
+
+    /// Decodes the BCS-encoded parameters.
+    fn decode(bytes: &[u8]) -> Result<Self, VerificationError> {
+        let decode_u16 = |offset: usize| {
+            NonZeroU16::new(u16::from_le_bytes([bytes[offset], bytes[offset + 1]]))
+                .ok_or(VerificationError::MalformedMetadata)
+        };
+        Ok(Self {
+            version: bytes[0],
+            n_primary_source_symbols: decode_u16(1)?,
+            n_secondary_source_symbols: decode_u16(3)?,
+        })
+    }
 }
 
 /// Metadata about a blob, without its corresponding [`BlobId`].
+///
+/// The blob is encoded with the parameters derived from the number of shards with version
+/// [`Self::ENCODING_VERSION`] of the derivation.
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct BlobMetadataV1 {
     /// The type of encoding used to erasure encode the blob.
@@ -269,6 +452,26 @@ pub struct BlobMetadataV1 {
     pub hashes: Vec<SliverPairMetadata>,
 }
 
+impl BlobMetadataV1 {
+    /// The encoding version of the blobs whose metadata does not store the encoding parameters.
+    pub const ENCODING_VERSION: u8 = 1;
+
+    /// Returns the encoding parameters of a blob with this version of the metadata.
+    fn derived_encoding_params(
+        n_shards: NonZeroU16,
+        encoding_type: EncodingType,
+    ) -> EncodingParams {
+        let (n_primary_source_symbols, n_secondary_source_symbols) =
+            source_symbols_for_version(Self::ENCODING_VERSION, n_shards, encoding_type)
+                .expect("the encoding version of the metadata is always supported");
+        EncodingParams {
+            version: Self::ENCODING_VERSION,
+            n_primary_source_symbols,
+            n_secondary_source_symbols,
+        }
+    }
+}
+
 impl BlobMetadataApi for BlobMetadataV1 {
     /// Return the hash of the sliver pair at the given index and type.
     fn get_sliver_hash(
@@ -276,22 +479,12 @@ impl BlobMetadataApi for BlobMetadataV1 {
         sliver_pair_index: SliverPairIndex,
         sliver_type: SliverType,
     ) -> Option<&MerkleNode> {
-        self.hashes.get(sliver_pair_index.as_usize()).map(
-            |sliver_pair_metadata| match sliver_type {
-                SliverType::Primary => &sliver_pair_metadata.primary_hash,
-                SliverType::Secondary => &sliver_pair_metadata.secondary_hash,
-            },
-        )
+        sliver_hash(&self.hashes, sliver_pair_index, sliver_type)
     }
 
     /// Returns the root hash of the Merkle tree over the sliver pairs.
     fn compute_root_hash(&self) -> MerkleNode {
-        MerkleTree::<Blake2b256>::build(
-            self.hashes
-                .iter()
-                .map(|h| h.pair_leaf_input::<Blake2b256>()),
-        )
-        .root()
+        root_hash(&self.hashes)
     }
 
     /// Returns the symbol size associated with the blob.
@@ -311,11 +504,7 @@ impl BlobMetadataApi for BlobMetadataV1 {
     /// Returns `None` if `hashes.len()` is not between `1` and `u16::MAX` or if the
     /// `unencoded_length` cannot be encoded
     fn encoded_size(&self) -> Option<u64> {
-        encoded_blob_length_for_n_shards(
-            NonZeroU16::new(self.hashes.len().try_into().ok()?)?,
-            self.unencoded_length,
-            self.encoding_type,
-        )
+        encoded_size(&self.hashes, self.unencoded_length, self.encoding_type)
     }
 
     fn encoding_type(&self) -> EncodingType {
@@ -329,6 +518,104 @@ impl BlobMetadataApi for BlobMetadataV1 {
     fn hashes(&self) -> &Vec<SliverPairMetadata> {
         &self.hashes
     }
+
+    fn encoding_params(&self) -> Option<&EncodingParams> {
+        None
+    }
+}
+
+/// Metadata about a blob, including the parameters with which it is encoded, without its
+/// corresponding [`BlobId`].
+///
+/// The encoding parameters are not part of the computation of the blob ID, and are therefore not
+/// authenticated by it. They are checked against the derivation for the number of shards when the
+/// metadata is verified, see [`EncodingParams`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BlobMetadataV2 {
+    /// The type of encoding used to erasure encode the blob.
+    pub encoding_type: EncodingType,
+    /// The parameters with which the blob is encoded.
+    pub encoding_params: EncodingParams,
+    /// The length of the unencoded blob.
+    pub unencoded_length: u64,
+    /// The hashes over the slivers of the blob.
+    pub hashes: Vec<SliverPairMetadata>,
+}
+
+impl BlobMetadataApi for BlobMetadataV2 {
+    fn get_sliver_hash(
+        &self,
+        sliver_pair_index: SliverPairIndex,
+        sliver_type: SliverType,
+    ) -> Option<&MerkleNode> {
+        sliver_hash(&self.hashes, sliver_pair_index, sliver_type)
+    }
+
+    fn compute_root_hash(&self) -> MerkleNode {
+        root_hash(&self.hashes)
+    }
+
+    /// Returns the symbol size associated with the blob.
+    ///
+    /// The encoding config is assumed to have the encoding parameters of the metadata, see
+    /// [`VerifiedBlobMetadataWithId::is_encoding_config_applicable`].
+    fn symbol_size(
+        &self,
+        encoding_config: &EncodingConfig,
+    ) -> Result<NonZeroU16, DataTooLargeError> {
+        encoding_config
+            .get_for_type(self.encoding_type)
+            .symbol_size_for_blob(self.unencoded_length)
+    }
+
+    fn encoded_size(&self) -> Option<u64> {
+        encoded_size(&self.hashes, self.unencoded_length, self.encoding_type)
+    }
+
+    fn encoding_type(&self) -> EncodingType {
+        self.encoding_type
+    }
+
+    fn unencoded_length(&self) -> u64 {
+        self.unencoded_length
+    }
+
+    fn hashes(&self) -> &Vec<SliverPairMetadata> {
+        &self.hashes
+    }
+
+    fn encoding_params(&self) -> Option<&EncodingParams> {
+        Some(&self.encoding_params)
+    }
+}
+
+fn sliver_hash(
+    hashes: &[SliverPairMetadata],
+    sliver_pair_index: SliverPairIndex,
+    sliver_type: SliverType,
+) -> Option<&MerkleNode> {
+    hashes
+        .get(sliver_pair_index.as_usize())
+        .map(|sliver_pair_metadata| match sliver_type {
+            SliverType::Primary => &sliver_pair_metadata.primary_hash,
+            SliverType::Secondary => &sliver_pair_metadata.secondary_hash,
+        })
+}
+
+fn root_hash(hashes: &[SliverPairMetadata]) -> MerkleNode {
+    MerkleTree::<Blake2b256>::build(hashes.iter().map(|h| h.pair_leaf_input::<Blake2b256>())).root()
+}
+
+fn encoded_size(
+    hashes: &[SliverPairMetadata],
+    unencoded_length: u64,
+    encoding_type: EncodingType,
+) -> Option<u64> {
+    encoded_blob_length_for_n_shards(
+        NonZeroU16::new(hashes.len().try_into().ok()?)?,
+        unencoded_length,
+        encoding_type,
+    )
 }
 
 /// Metadata about a sliver pair, i.e., the root hashes of the primary and secondary slivers.
@@ -369,6 +656,273 @@ impl SliverPairMetadata {
     }
 }
 
+/// Verifies the BCS-encoded [`UnverifiedBlobMetadataWithId`] of a blob while it is received.
+///
+/// The encoded metadata is provided in chunks of arbitrary sizes with [`Self::update`]. Only the
+/// decoded sliver-pair hashes and the roots of the complete subtrees of the Merkle tree over them
+/// are kept, so the memory used is independent of how the metadata is received. The blob ID, the
+/// encoding parameters, the unencoded length, and the number of hashes are checked as soon as
+/// they are received, and the input is rejected at the first malformed entry; the blob ID can only
+/// be checked against the hashes once all of them are received, in [`Self::finish`].
+///
+/// The checks performed are the same as those of [`UnverifiedBlobMetadataWithId::verify`].
+#[derive(Debug)]
+pub struct StreamingMetadataVerifier<'a> {
+    blob_id: BlobId,
+    config: &'a EncodingConfig,
+    state: StreamingState,
+    // The bytes received of the entry that is currently decoded.
+    pending: Vec<u8>,
+    encoding_type: Option<EncodingType>,
+    // Whether the version of the metadata stores the encoding parameters.
+    has_encoding_params: bool,
+    encoding_params: Option<EncodingParams>,
+    unencoded_length: u64,
+    primary_hash: Option<MerkleNode>,
+    hashes: Vec<SliverPairMetadata>,
+    root_builder: MerkleRootBuilder<Blake2b256>,
+}
+
+/// The entry of the encoded metadata expected next by the [`StreamingMetadataVerifier`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum StreamingState {
+    BlobId,
+    MetadataVersion,
+    EncodingType,
+    EncodingParams,
+    UnencodedLength,
+    HashCount,
+    PrimaryHash,
+    SecondaryHash,
+    Finished,
+}
+
+impl<'a> StreamingMetadataVerifier<'a> {
+    /// The maximum length of a ULEB128-encoded `u32` in BCS.
+    const MAX_ULEB128_LENGTH: usize = 5;
+
+    /// Creates a new verifier for the metadata of the blob with ID `blob_id`.
+    pub fn new(blob_id: BlobId, config: &'a EncodingConfig) -> Self {
+        Self {
+            blob_id,
+            config,
+            state: StreamingState::BlobId,
+            // The longest entries are the encoded Merkle nodes.
+            pending: Vec::with_capacity(1 + DIGEST_LEN),
+            encoding_type: None,
+            has_encoding_params: false,
+            encoding_params: None,
+            unencoded_length: 0,
+            primary_hash: None,
+            hashes: Vec::new(),
+            root_builder: MerkleRootBuilder::new(),
+        }
+    }
+
+    /// Processes the next chunk of the encoded metadata.
+    ///
+    /// Returns an error as soon as the metadata received so far is found to be invalid, after
+    /// which the verifier must not be used anymore.
+    pub fn update(&mut self, mut bytes: &[u8]) -> Result<(), VerificationError> {
+        while !bytes.is_empty() {
+            let (entry_bytes, remaining) = bytes.split_at(self.missing_bytes()?.min(bytes.len()));
+            self.pending.extend_from_slice(entry_bytes);
+            bytes = remaining;
+
+            if self.missing_bytes()? == 0 {
+                self.complete_entry()?;
+                self.pending.clear();
+            }
+        }
+        Ok(())
+    }
+
+    /// Completes the verification, returning the verified metadata.
+    ///
+    /// Fails if the metadata received is incomplete or if the blob ID does not match the hashes.
+    pub fn finish(self) -> Result<VerifiedBlobMetadataWithId, VerificationError> {
+        crate::ensure!(
+            self.state == StreamingState::Finished,
+            VerificationError::MalformedMetadata
+        );
+        let encoding_type = self
+            .encoding_type
+            .expect("the encoding type is decoded before the hashes");
+        let root = self.root_builder.root();
+        let computed_blob_id = BlobId::from_metadata(root, encoding_type, self.unencoded_length);
+        crate::ensure!(
+            computed_blob_id == self.blob_id,
+            VerificationError::BlobIdMismatch
+        );
+
+        let metadata = BlobMetadata::new(encoding_type, self.unencoded_length, self.hashes);
+        let metadata = match self.encoding_params {
+            Some(encoding_params) => metadata.with_encoding_params(encoding_params),
+            None => metadata,
+        };
+        Ok(BlobMetadataWithId::new_verified_unchecked(
+            self.blob_id,
+            metadata,
+        ))
+    }
+
+    /// Returns the number of bytes that are missing to complete the current entry.
+    fn missing_bytes(&self) -> Result<usize, VerificationError> {
+        let n_pending = self.pending.len();
+        let missing = match self.state {
+            StreamingState::BlobId => BlobId::LENGTH - n_pending,
+            StreamingState::EncodingParams => EncodingParams::ENCODED_LENGTH - n_pending,
+            StreamingState::UnencodedLength => size_of::<u64>() - n_pending,
+            StreamingState::MetadataVersion
+            | StreamingState::EncodingType
+            | StreamingState::HashCount => match self.pending.last() {
+                Some(byte) if byte & 0x80 == 0 => 0,
+                _ if n_pending >= Self::MAX_ULEB128_LENGTH => {
+                    return Err(VerificationError::MalformedMetadata)
+                }
+                _ => 1,
+            },
+            StreamingState::PrimaryHash | StreamingState::SecondaryHash => {
+                match self.pending.first() {
+                    None => 1,
+                    Some(0) => 0,
+                    Some(1) => 1 + DIGEST_LEN - n_pending,
+                    Some(_) => return Err(VerificationError::MalformedMetadata),
+                }
+            }
+            // The metadata is complete, any additional data is invalid.
+            StreamingState::Finished => return Err(VerificationError::MalformedMetadata),
+        };
+        Ok(missing)
+    }
+
+    /// Processes the current entry, whose bytes have all been received, and advances the state.
+    fn complete_entry(&mut self) -> Result<(), VerificationError> {
+        self.state = match self.state {
+            StreamingState::BlobId => {
+                crate::ensure!(
+                    self.pending == self.blob_id.as_ref(),
+                    VerificationError::BlobIdMismatch
+                );
+                StreamingState::MetadataVersion
+            }
+            StreamingState::MetadataVersion => {
+                // The variants `BlobMetadata::V1` and `BlobMetadata::V2`, the latter of which
+                // stores the encoding parameters.
+                self.has_encoding_params = match decode_uleb128(&self.pending)? {
+                    0 => false,
+                    1 => true,
+                    _ => return Err(VerificationError::MalformedMetadata),
+                };
+                StreamingState::EncodingType
+            }
+            StreamingState::EncodingType => {
+                let encoding_type = u8::try_from(decode_uleb128(&self.pending)?)
+                    .ok()
+                    .and_then(|value| EncodingType::try_from(value).ok())
+                    .ok_or(VerificationError::MalformedMetadata)?;
+                self.encoding_type = Some(encoding_type);
+                if self.has_encoding_params {
+                    StreamingState::EncodingParams
+                } else {
+                    StreamingState::UnencodedLength
+                }
+            }
+            StreamingState::EncodingParams => {
+                let encoding_params = EncodingParams::decode(&self.pending)?;
+                let encoding_type = self
+                    .encoding_type
+                    .expect("the encoding type is decoded before the encoding parameters");
+                encoding_params.verify_config(&self.config.get_for_type(encoding_type))?;
+                self.encoding_params = Some(encoding_params);
+                StreamingState::UnencodedLength
+            }
+            StreamingState::UnencodedLength => {
+                self.unencoded_length = u64::from_le_bytes(
+                    self.pending
+                        .as_slice()
+                        .try_into()
+                        .expect("the pending bytes have the length of a u64"),
+                );
+                let encoding_type = self
+                    .encoding_type
+                    .expect("the encoding type is decoded before the unencoded length");
+                crate::ensure!(
+                    self.unencoded_length
+                        <= self.config.get_for_type(encoding_type).max_blob_size(),
+                    VerificationError::UnencodedLengthTooLarge
+                );
+                StreamingState::HashCount
+            }
+            StreamingState::HashCount => {
+                let n_hashes = usize::try_from(decode_uleb128(&self.pending)?)
+                    .expect("u32 always fits into usize on supported platforms");
+                let n_shards = self.config.n_shards().get().into();
+                // The count is checked before allocating memory for the hashes.
+                crate::ensure!(
+                    n_hashes == n_shards,
+                    VerificationError::InvalidHashCount {
+                        actual: n_hashes,
+                        expected: n_shards,
+                    }
+                );
+                self.hashes = Vec::with_capacity(n_hashes);
+                StreamingState::PrimaryHash
+            }
+            StreamingState::PrimaryHash => {
+                self.primary_hash = Some(decode_node(&self.pending));
+                StreamingState::SecondaryHash
+            }
+            StreamingState::SecondaryHash => {
+                let sliver_pair_metadata = SliverPairMetadata {
+                    primary_hash: self
+                        .primary_hash
+                        .take()
+                        .expect("the primary hash is decoded before the secondary hash"),
+                    secondary_hash: decode_node(&self.pending),
+                };
+                self.root_builder.push_leaf_hash(leaf_hash::<Blake2b256>(
+                    &sliver_pair_metadata.pair_leaf_input::<Blake2b256>(),
+                ));
+                self.hashes.push(sliver_pair_metadata);
+                if self.hashes.len() == self.config.n_shards().get().into() {
+                    StreamingState::Finished
+                } else {
+                    StreamingState::PrimaryHash
+                }
+            }
+            StreamingState::Finished => unreachable!("no entry is decoded after the last one"),
+        };
+        Ok(())
+    }
+}
+
+/// Decodes a canonical ULEB128-encoded `u32`, as used by BCS for lengths and enum variants.
+fn decode_uleb128(bytes: &[u8]) -> Result<u32, VerificationError> {
+    // Non-canonical encodings have trailing zero bytes.
+    crate::ensure!(
+        bytes.len() == 1 || bytes.last() != Some(&0),
+        VerificationError::MalformedMetadata
+    );
+    let value = bytes.iter().enumerate().fold(0u64, |value, (i, byte)| {
+        value | (u64::from(byte & 0x7f) << (7 * i))
+    });
+    u32::try_from(value).map_err(|_| VerificationError::MalformedMetadata)
+}
+
+/// Decodes a BCS-encoded Merkle node, whose variant has already been checked to be valid.
+fn decode_node(bytes: &[u8]) -> MerkleNode {
+    match bytes {
+        [0] => MerkleNode::Empty,
+        [1, digest @ ..] => MerkleNode::Digest(
+            digest
+                .try_into()
+                .expect("the pending bytes contain a complete digest"),
+        ),
+        _ => unreachable!("the variant of the node has been checked"),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -420,14 +974,10 @@ mod tests {
         fn fails_for_hash_count_mismatch() {
             let mut metadata = test_utils::unverified_blob_metadata();
             let expected = metadata.metadata().hashes().len();
-            metadata
-                .metadata
-                .mut_inner()
-                .hashes
-                .push(SliverPairMetadata {
-                    primary_hash: MerkleNode::Digest([42u8; 32]),
-                    secondary_hash: MerkleNode::Digest([23u8; 32]),
-                });
+            metadata.metadata.hashes_mut().push(SliverPairMetadata {
+                primary_hash: MerkleNode::Digest([42u8; 32]),
+                secondary_hash: MerkleNode::Digest([23u8; 32]),
+            });
             let actual = metadata.metadata().hashes().len();
 
             let err = metadata
@@ -445,7 +995,7 @@ mod tests {
             let config = test_utils::encoding_config();
             let mut metadata = test_utils::unverified_blob_metadata();
             let encoding_type = metadata.metadata().encoding_type();
-            metadata.metadata.mut_inner().unencoded_length = u64::from(u16::MAX)
+            *metadata.metadata.unencoded_length_mut() = u64::from(u16::MAX)
                 * u64::from(
                     config
                         .get_for_type(encoding_type)
@@ -466,5 +1016,247 @@ mod tests {
 
             assert_eq!(err, VerificationError::UnencodedLengthTooLarge);
         }
+
+        #[test]
+        fn fails_for_encoding_params_mismatch() {
+            let mut metadata = with_encoding_params(test_utils::unverified_blob_metadata());
+            encoding_params_mut(&mut metadata).n_primary_source_symbols = NonZeroU16::MAX;
+
+            let err = metadata
+                .verify(&test_utils::encoding_config())
+                .expect_err("verification should fail");
+
+            assert_eq!(err, VerificationError::EncodingParamsMismatch);
+        }
+
+        #[test]
+        fn fails_for_unsupported_encoding_version() {
+            let mut metadata = with_encoding_params(test_utils::unverified_blob_metadata());
+            encoding_params_mut(&mut metadata).version = ENCODING_VERSION + 1;
+
+            let err = metadata
+                .verify(&test_utils::encoding_config())
+                .expect_err("verification should fail");
+
+            assert_eq!(
+                err,
+                VerificationError::UnsupportedEncodingVersion(ENCODING_VERSION + 1)
+            );
+        }
+
+        #[test]
+        fn succeeds_for_metadata_with_encoding_params() {
+            let metadata = with_encoding_params(test_utils::unverified_blob_metadata());
+            let verified = metadata
+                .clone()
+                .verify(&test_utils::encoding_config())
+                .expect("verification should succeed");
+
+            assert_eq!(verified.metadata(), metadata.metadata());
+        }
+
+        #[test]
+        fn new_metadata_does_not_store_encoding_params() {
+            let verified = test_utils::unverified_blob_metadata()
+                .verify(&test_utils::encoding_config())
+                .expect("verification should succeed");
+
+            assert!(matches!(verified.metadata(), BlobMetadata::V1(_)));
+            assert!(verified.is_encoding_config_applicable(&test_utils::encoding_config()));
+        }
+
+        #[test]
+        fn conversion_to_v2_keeps_the_blob_id() {
+            let config = test_utils::encoding_config();
+            let verified = test_utils::unverified_blob_metadata()
+                .verify(&config)
+                .expect("verification should succeed");
+
+            let converted = verified.clone().with_encoding_params();
+            assert_eq!(converted.blob_id(), verified.blob_id());
+            assert_eq!(
+                converted.metadata().encoding_params(),
+                Some(&EncodingParams::from_config(
+                    &config.get_for_type(verified.metadata().encoding_type())
+                ))
+            );
+            assert!(converted.is_encoding_config_applicable(&config));
+
+            let reverified = converted
+                .clone()
+                .into_unverified()
+                .verify(&config)
+                .expect("verification should succeed");
+            assert_eq!(reverified, converted);
+        }
+    }
+
+    fn encoding_params_mut(metadata: &mut UnverifiedBlobMetadataWithId) -> &mut EncodingParams {
+        match &mut metadata.metadata {
+            BlobMetadata::V2(inner) => &mut inner.encoding_params,
+            BlobMetadata::V1(_) => panic!("the metadata must store the encoding parameters"),
+        }
+    }
+
+    /// Converts the metadata to a [`BlobMetadataV2`], which has the same blob ID.
+    fn with_encoding_params(
+        metadata: UnverifiedBlobMetadataWithId,
+    ) -> UnverifiedBlobMetadataWithId {
+        let config = crate::test_utils::encoding_config();
+        let encoding_params =
+            EncodingParams::from_config(&config.get_for_type(metadata.metadata.encoding_type()));
+        UnverifiedBlobMetadataWithId::new(
+            metadata.blob_id,
+            metadata.metadata.with_encoding_params(encoding_params),
+        )
+    }
+
+    mod streaming_verification {
+        use super::*;
+        use crate::test_utils;
+
+        // The version, the encoding type, and the number of hashes are all less than 128 and
+        // therefore encoded in a single byte.
+        const HEADER_LENGTH: usize =
+            BlobId::LENGTH + 1 + 1 + EncodingParams::ENCODED_LENGTH + 8 + 1;
+
+        fn encoded_metadata() -> (BlobId, Vec<u8>) {
+            let metadata = with_encoding_params(test_utils::unverified_blob_metadata());
+            let encoded = bcs::to_bytes(&metadata).expect("metadata can be BCS encoded");
+            (*metadata.blob_id(), encoded)
+        }
+
+        fn verify_in_chunks(
+            blob_id: BlobId,
+            encoded: &[u8],
+            chunk_size: usize,
+        ) -> Result<VerifiedBlobMetadataWithId, VerificationError> {
+            let config = test_utils::encoding_config();
+            let mut verifier = StreamingMetadataVerifier::new(blob_id, &config);
+            for chunk in encoded.chunks(chunk_size) {
+                verifier.update(chunk)?;
+            }
+            verifier.finish()
+        }
+
+        #[test]
+        fn succeeds_for_any_chunk_size() {
+            let (blob_id, encoded) = encoded_metadata();
+            let expected = with_encoding_params(test_utils::unverified_blob_metadata())
+                .verify(&test_utils::encoding_config())
+                .expect("verification should succeed");
+
+            for chunk_size in [1, 2, 7, 33, 64, encoded.len()] {
+                let verified = verify_in_chunks(blob_id, &encoded, chunk_size)
+                    .expect("verification should succeed");
+                assert_eq!(verified, expected);
+            }
+        }
+
+        #[test]
+        fn rejects_incorrect_blob_id_before_the_hashes() {
+            let (_, encoded) = encoded_metadata();
+            let config = test_utils::encoding_config();
+            let mut verifier = StreamingMetadataVerifier::new(BlobId([7; 32]), &config);
+
+            let err = verifier
+                .update(&encoded[..BlobId::LENGTH])
+                .expect_err("verification should fail");
+            assert_eq!(err, VerificationError::BlobIdMismatch);
+        }
+
+        #[test]
+        fn rejects_hash_count_mismatch_before_the_hashes() {
+            let mut metadata = with_encoding_params(test_utils::unverified_blob_metadata());
+            let expected = metadata.metadata().hashes().len();
+            metadata
+                .metadata
+                .hashes_mut()
+                .push(SliverPairMetadata::new_empty());
+            let encoded = bcs::to_bytes(&metadata).expect("metadata can be BCS encoded");
+
+            // The header consists of the blob ID, the version, the encoding type, the encoding
+            // parameters, the unencoded length, and the number of hashes.
+            let header_length = HEADER_LENGTH;
+            let config = test_utils::encoding_config();
+            let mut verifier = StreamingMetadataVerifier::new(*metadata.blob_id(), &config);
+            let err = verifier
+                .update(&encoded[..header_length])
+                .expect_err("verification should fail");
+            assert_eq!(
+                err,
+                VerificationError::InvalidHashCount {
+                    actual: expected + 1,
+                    expected,
+                }
+            );
+        }
+
+        #[test]
+        fn rejects_modified_hash() {
+            let (blob_id, mut encoded) = encoded_metadata();
+            let last = encoded.len() - 1;
+            encoded[last] ^= 1;
+
+            assert_eq!(
+                verify_in_chunks(blob_id, &encoded, 16),
+                Err(VerificationError::BlobIdMismatch)
+            );
+        }
+
+        #[test]
+        fn rejects_invalid_node_variant() {
+            let (blob_id, mut encoded) = encoded_metadata();
+            let header_length = HEADER_LENGTH;
+            encoded[header_length] = 2;
+
+            let config = test_utils::encoding_config();
+            let mut verifier = StreamingMetadataVerifier::new(blob_id, &config);
+            let err = verifier
+                .update(&encoded[..=header_length])
+                .expect_err("verification should fail");
+            assert_eq!(err, VerificationError::MalformedMetadata);
+        }
+
+        #[test]
+        fn rejects_truncated_and_trailing_data() {
+            let (blob_id, mut encoded) = encoded_metadata();
+
+            assert_eq!(
+                verify_in_chunks(blob_id, &encoded[..encoded.len() - 1], 16),
+                Err(VerificationError::MalformedMetadata)
+            );
+
+            encoded.push(0);
+            assert_eq!(
+                verify_in_chunks(blob_id, &encoded, 16),
+                Err(VerificationError::MalformedMetadata)
+            );
+        }
+
+        #[test]
+        fn succeeds_for_metadata_without_encoding_params() {
+            let metadata = test_utils::unverified_blob_metadata();
+            let encoded = bcs::to_bytes(&metadata).expect("metadata can be BCS encoded");
+
+            let verified = verify_in_chunks(*metadata.blob_id(), &encoded, 16)
+                .expect("verification should succeed");
+            assert_eq!(verified.metadata(), metadata.metadata());
+        }
+
+        #[test]
+        fn rejects_encoding_params_mismatch_before_the_hashes() {
+            let mut metadata = with_encoding_params(test_utils::unverified_blob_metadata());
+            encoding_params_mut(&mut metadata).n_secondary_source_symbols = NonZeroU16::MAX;
+            let encoded = bcs::to_bytes(&metadata).expect("metadata can be BCS encoded");
+
+            let params_end = BlobId::LENGTH + 1 + 1 + EncodingParams::ENCODED_LENGTH;
+            let config = test_utils::encoding_config();
+            let mut verifier = StreamingMetadataVerifier::new(*metadata.blob_id(), &config);
+            let err = verifier
+                .update(&encoded[..params_end])
+                .expect_err("verification should fail");
+            assert_eq!(err, VerificationError::EncodingParamsMismatch);
+        }
     }
 }
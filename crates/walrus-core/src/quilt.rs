@@ -0,0 +1,439 @@
+// Copyright (c) Walrus Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Quilts, which pack many small files into a single blob.
+//!
+//! Each stored blob incurs costs that do not depend on its size, for its metadata and its on-chain
+//! object, which dominate the cost of storing small files. A quilt stores many files, its
+//! *entries*, in a single blob together with an index of their identifiers and positions. An entry
+//! is addressed by a [`QuiltEntryId`] and can be read with range reads of the header, the index,
+//! and the entry, without reading the whole quilt.
+//!
+//! A quilt consists of:
+//!
+//! 1. a header of [`QUILT_HEADER_LENGTH`] bytes, consisting of the magic bytes [`QUILT_MAGIC`], the
+//!    version of the format, and the length of the index in bytes as a little-endian `u32`;
+//! 2. the BCS-encoded [`QuiltIndex`];
+//! 3. the data of the entries, concatenated in the order of the index.
+
+use alloc::{collections::BTreeSet, string::String, vec::Vec};
+use core::{
+    fmt::{self, Display},
+    ops::Range,
+    str::FromStr,
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{ensure, BlobId};
+
+/// The magic bytes at the start of every quilt.
+pub const QUILT_MAGIC: [u8; 4] = *b"WQLT";
+
+/// The version of the quilt format written by [`QuiltBuilder`].
+pub const QUILT_VERSION: u8 = 1;
+
+/// The length of the header of a quilt in bytes.
+pub const QUILT_HEADER_LENGTH: usize = QUILT_MAGIC.len() + 1 + 4;
+
+/// Error returned when a quilt cannot be built or parsed.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum QuiltError {
+    /// The data does not start with the magic bytes of a quilt.
+    #[error("the data is not a quilt")]
+    NotAQuilt,
+    /// The version of the quilt format is not supported.
+    #[error("the quilt version {0} is not supported")]
+    UnsupportedVersion(u8),
+    /// The quilt ends before the end of its header, index, or entries.
+    #[error("the quilt is truncated")]
+    Truncated,
+    /// The index cannot be decoded or the entries are not laid out contiguously after it.
+    #[error("the quilt index is invalid")]
+    InvalidIndex,
+    /// The encoded index is longer than `u32::MAX` bytes.
+    #[error("the quilt index is too large")]
+    IndexTooLarge,
+    /// Two entries have the same identifier.
+    #[error("the identifier {0:?} is used by more than one entry")]
+    DuplicateIdentifier(String),
+    /// The quilt has no entry with the given position.
+    #[error("the quilt has no entry {0}")]
+    EntryNotFound(u32),
+    /// The quilt has no entry with the given identifier.
+    #[error("the quilt has no entry with identifier {0:?}")]
+    IdentifierNotFound(String),
+}
+
+/// The header at the start of a quilt.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct QuiltHeader {
+    /// The version of the quilt format.
+    pub version: u8,
+    /// The length of the encoded index in bytes.
+    pub index_length: u32,
+}
+
+impl QuiltHeader {
+    /// Parses the header from the first [`QUILT_HEADER_LENGTH`] bytes of a quilt.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, QuiltError> {
+        let header = bytes
+            .get(..QUILT_HEADER_LENGTH)
+            .ok_or(QuiltError::Truncated)?;
+        let (magic, rest) = header.split_at(QUILT_MAGIC.len());
+        ensure!(magic == QUILT_MAGIC, QuiltError::NotAQuilt);
+        let (&version, index_length) = rest.split_first().expect("the header has a version");
+        ensure!(
+            version == QUILT_VERSION,
+            QuiltError::UnsupportedVersion(version)
+        );
+        Ok(Self {
+            version,
+            index_length: u32::from_le_bytes(
+                index_length
+                    .try_into()
+                    .expect("the rest of the header is the index length"),
+            ),
+        })
+    }
+
+    /// Returns the encoding of the header.
+    pub fn to_bytes(&self) -> [u8; QUILT_HEADER_LENGTH] {
+        let mut bytes = [0; QUILT_HEADER_LENGTH];
+        bytes[..QUILT_MAGIC.len()].copy_from_slice(&QUILT_MAGIC);
+        bytes[QUILT_MAGIC.len()] = self.version;
+        bytes[QUILT_MAGIC.len() + 1..].copy_from_slice(&self.index_length.to_le_bytes());
+        bytes
+    }
+
+    /// Returns the range of bytes of the quilt that contain the encoded index.
+    pub fn index_range(&self) -> Range<u64> {
+        let start = QUILT_HEADER_LENGTH as u64;
+        start..start + u64::from(self.index_length)
+    }
+}
+
+/// An entry in the index of a quilt.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QuiltEntry {
+    /// The identifier of the entry, which is unique within the quilt, e.g., a file name.
+    pub identifier: String,
+    /// The offset of the data of the entry from the start of the quilt.
+    pub offset: u64,
+    /// The length of the data of the entry in bytes.
+    pub length: u64,
+}
+
+impl QuiltEntry {
+    /// Returns the range of bytes of the quilt that contain the data of the entry.
+    pub fn range(&self) -> Range<u64> {
+        self.offset..self.offset + self.length
+    }
+}
+
+/// The index of the entries of a quilt.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QuiltIndex {
+    entries: Vec<QuiltEntry>,
+}
+
+impl QuiltIndex {
+    /// Decodes the index from the bytes in the [`index_range`][QuiltHeader::index_range] of a quilt
+    /// with the given `header`.
+    ///
+    /// Checks that the identifiers are unique and that the entries are laid out contiguously
+    /// after the index.
+    pub fn from_bytes(header: &QuiltHeader, bytes: &[u8]) -> Result<Self, QuiltError> {
+        let index_range = header.index_range();
+        ensure!(
+            bytes.len() as u64 == index_range.end - index_range.start,
+            QuiltError::Truncated
+        );
+        let index: Self = bcs::from_bytes(bytes).map_err(|_| QuiltError::InvalidIndex)?;
+
+        let mut identifiers = BTreeSet::new();
+        let mut next_offset = index_range.end;
+        for entry in &index.entries {
+            ensure!(
+                identifiers.insert(entry.identifier.as_str()),
+                QuiltError::DuplicateIdentifier(entry.identifier.clone())
+            );
+            ensure!(entry.offset == next_offset, QuiltError::InvalidIndex);
+            next_offset = entry
+                .offset
+                .checked_add(entry.length)
+                .ok_or(QuiltError::InvalidIndex)?;
+        }
+        Ok(index)
+    }
+
+    /// Parses the header and the index of the `quilt`.
+    pub fn read(quilt: &[u8]) -> Result<Self, QuiltError> {
+        let header = QuiltHeader::from_bytes(quilt)?;
+        let index_range = header.index_range();
+        let index_bytes = quilt
+            .get(index_range.start as usize..index_range.end as usize)
+            .ok_or(QuiltError::Truncated)?;
+        let index = Self::from_bytes(&header, index_bytes)?;
+        ensure!(
+            index.end_offset().unwrap_or(index_range.end) == quilt.len() as u64,
+            QuiltError::Truncated
+        );
+        Ok(index)
+    }
+
+    /// Returns the entries of the quilt, in the order of their data.
+    pub fn entries(&self) -> &[QuiltEntry] {
+        &self.entries
+    }
+
+    /// Returns the number of entries in the quilt.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns true if the quilt has no entries.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Returns the entry at position `index` in the quilt.
+    pub fn get(&self, index: u32) -> Result<&QuiltEntry, QuiltError> {
+        usize::try_from(index)
+            .ok()
+            .and_then(|index| self.entries.get(index))
+            .ok_or(QuiltError::EntryNotFound(index))
+    }
+
+    /// Returns the position and the entry with the given `identifier`, if any.
+    pub fn find(&self, identifier: &str) -> Option<(u32, &QuiltEntry)> {
+        self.entries
+            .iter()
+            .position(|entry| entry.identifier == identifier)
+            .map(|index| {
+                (
+                    u32::try_from(index).expect("a quilt has at most `u32::MAX` entries"),
+                    &self.entries[index],
+                )
+            })
+    }
+
+    /// Returns the data of the entry at position `index` in the `quilt`.
+    pub fn entry_data<'a>(&self, quilt: &'a [u8], index: u32) -> Result<&'a [u8], QuiltError> {
+        let range = self.get(index)?.range();
+        quilt
+            .get(range.start as usize..range.end as usize)
+            .ok_or(QuiltError::Truncated)
+    }
+
+    /// Returns the offset of the end of the last entry, if any.
+    fn end_offset(&self) -> Option<u64> {
+        self.entries.last().map(|entry| entry.range().end)
+    }
+}
+
+/// Builds a quilt from its entries.
+#[derive(Debug, Clone, Default)]
+pub struct QuiltBuilder<'a> {
+    entries: Vec<(String, &'a [u8])>,
+    identifiers: BTreeSet<String>,
+}
+
+impl<'a> QuiltBuilder<'a> {
+    /// Creates a builder for a quilt without entries.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds an entry with the given `identifier` and `data` to the quilt.
+    ///
+    /// Returns an error if the quilt already contains an entry with the same identifier.
+    pub fn add_entry(
+        &mut self,
+        identifier: impl Into<String>,
+        data: &'a [u8],
+    ) -> Result<&mut Self, QuiltError> {
+        let identifier = identifier.into();
+        ensure!(
+            self.identifiers.insert(identifier.clone()),
+            QuiltError::DuplicateIdentifier(identifier)
+        );
+        self.entries.push((identifier, data));
+        Ok(self)
+    }
+
+    /// Builds the quilt, returning its bytes and its index.
+    ///
+    /// The entries are stored in the order in which they were added.
+    pub fn build(self) -> Result<(Vec<u8>, QuiltIndex), QuiltError> {
+        // The BCS encoding of the offsets has a fixed length, so the length of the index does not
+        // depend on the offsets.
+        let mut index = QuiltIndex {
+            entries: self
+                .entries
+                .iter()
+                .map(|(identifier, data)| QuiltEntry {
+                    identifier: identifier.clone(),
+                    offset: 0,
+                    length: data.len() as u64,
+                })
+                .collect(),
+        };
+        let index_length = bcs::serialized_size(&index).expect("the index can be encoded");
+        let header = QuiltHeader {
+            version: QUILT_VERSION,
+            index_length: index_length
+                .try_into()
+                .map_err(|_| QuiltError::IndexTooLarge)?,
+        };
+
+        let mut offset = header.index_range().end;
+        for entry in &mut index.entries {
+            entry.offset = offset;
+            offset += entry.length;
+        }
+        let index_bytes = bcs::to_bytes(&index).expect("the index can be encoded");
+        debug_assert_eq!(index_bytes.len(), index_length);
+
+        let mut quilt = Vec::with_capacity(
+            usize::try_from(offset).expect("the entries are in memory, so their length fits"),
+        );
+        quilt.extend_from_slice(&header.to_bytes());
+        quilt.extend_from_slice(&index_bytes);
+        for (_, data) in &self.entries {
+            quilt.extend_from_slice(data);
+        }
+        Ok((quilt, index))
+    }
+}
+
+/// The ID of an entry of a quilt.
+///
+/// The ID consists of the blob ID of the quilt and the position of the entry in the quilt's index,
+/// and is displayed as the two separated by a colon.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct QuiltEntryId {
+    /// The blob ID of the quilt.
+    pub quilt_id: BlobId,
+    /// The position of the entry in the index of the quilt.
+    pub index: u32,
+}
+
+impl Display for QuiltEntryId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.quilt_id, self.index)
+    }
+}
+
+/// Error returned when unable to parse a quilt entry ID.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("failed to parse a quilt entry ID")]
+pub struct QuiltEntryIdParseError;
+
+impl FromStr for QuiltEntryId {
+    type Err = QuiltEntryIdParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (quilt_id, index) = s.split_once(':').ok_or(QuiltEntryIdParseError)?;
+        Ok(Self {
+            quilt_id: quilt_id.parse().map_err(|_| QuiltEntryIdParseError)?,
+            index: index.parse().map_err(|_| QuiltEntryIdParseError)?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::{string::ToString, vec};
+
+    use walrus_test_utils::Result as TestResult;
+
+    use super::*;
+
+    #[test]
+    fn builds_and_reads_quilts() -> TestResult {
+        let data = [
+            b"hello".to_vec(),
+            vec![],
+            walrus_test_utils::random_data(1000),
+        ];
+        let mut builder = QuiltBuilder::new();
+        for (i, entry) in data.iter().enumerate() {
+            builder.add_entry(alloc::format!("file-{i}"), entry)?;
+        }
+        let (quilt, index) = builder.build()?;
+
+        assert_eq!(QuiltIndex::read(&quilt)?, index);
+        assert_eq!(index.len(), 3);
+        for (i, entry) in data.iter().enumerate() {
+            let position = u32::try_from(i)?;
+            assert_eq!(index.entry_data(&quilt, position)?, entry.as_slice());
+            assert_eq!(
+                index.find(&alloc::format!("file-{i}")).map(|(i, _)| i),
+                Some(position)
+            );
+        }
+        assert_eq!(index.get(3), Err(QuiltError::EntryNotFound(3)));
+        assert_eq!(
+            QuiltIndex::read(&quilt[..quilt.len() - 1]),
+            Err(QuiltError::Truncated)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn reads_entries_from_ranges_of_the_quilt() -> TestResult {
+        let mut builder = QuiltBuilder::new();
+        builder
+            .add_entry("a", b"first")?
+            .add_entry("b", b"second")?;
+        let (quilt, _) = builder.build()?;
+
+        let header = QuiltHeader::from_bytes(&quilt[..QUILT_HEADER_LENGTH])?;
+        let index_range = header.index_range();
+        let index = QuiltIndex::from_bytes(
+            &header,
+            &quilt[index_range.start as usize..index_range.end as usize],
+        )?;
+        let range = index.get(1)?.range();
+        assert_eq!(&quilt[range.start as usize..range.end as usize], b"second");
+        Ok(())
+    }
+
+    #[test]
+    fn rejects_invalid_quilts() -> TestResult {
+        let mut builder = QuiltBuilder::new();
+        builder.add_entry("a", b"data")?;
+        assert_eq!(
+            builder.add_entry("a", b"other").err(),
+            Some(QuiltError::DuplicateIdentifier("a".into()))
+        );
+        let (mut quilt, _) = builder.build()?;
+
+        assert_eq!(QuiltIndex::read(b"not a quilt"), Err(QuiltError::NotAQuilt));
+        assert_eq!(
+            QuiltIndex::read(&quilt[..QUILT_HEADER_LENGTH - 1]),
+            Err(QuiltError::Truncated)
+        );
+        quilt[QUILT_MAGIC.len()] = QUILT_VERSION + 1;
+        assert_eq!(
+            QuiltIndex::read(&quilt),
+            Err(QuiltError::UnsupportedVersion(QUILT_VERSION + 1))
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn quilt_entry_id_roundtrips_through_strings() -> TestResult {
+        let id = QuiltEntryId {
+            quilt_id: BlobId([7; BlobId::LENGTH]),
+            index: 42,
+        };
+        assert_eq!(id.to_string().parse::<QuiltEntryId>()?, id);
+        assert!("42".parse::<QuiltEntryId>().is_err());
+        assert!(alloc::format!("{}:x", id.quilt_id)
+            .parse::<QuiltEntryId>()
+            .is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,212 @@
+// Copyright (c) Walrus Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Verification of blob IDs, blob metadata, and Merkle proofs.
+//!
+//! The functions in this module do not require an
+//! [`EncodingConfig`][crate::encoding::EncodingConfig], the creation of which precomputes the
+//! state of the encoders. They only need the number of shards, which makes them cheap to use for
+//! verifying Walrus data in light clients.
+
+use core::num::NonZeroU16;
+
+use crate::{
+    encoding::max_blob_size_for_n_shards,
+    metadata::{BlobMetadataApi as _, UnverifiedBlobMetadataWithId, VerifiedBlobMetadataWithId},
+    BlobId,
+    BlobMetadata,
+    SliverPairIndex,
+    SliverType,
+};
+pub use crate::{
+    merkle::{MerkleAuth, MerkleProof, Node},
+    metadata::VerificationError,
+};
+
+/// Checks that `blob_id` is the blob ID computed from the sliver hashes, the encoding type, and
+/// the unencoded length in the `metadata`.
+pub fn verify_blob_id(blob_id: &BlobId, metadata: &BlobMetadata) -> Result<(), VerificationError> {
+    crate::ensure!(
+        BlobId::from_sliver_pair_metadata(metadata) == *blob_id,
+        VerificationError::BlobIdMismatch
+    );
+    Ok(())
+}
+
+/// Checks that the `metadata` contains the hashes of one sliver pair for each of the `n_shards`
+/// shards.
+pub fn verify_hash_count(
+    metadata: &BlobMetadata,
+    n_shards: NonZeroU16,
+) -> Result<(), VerificationError> {
+    let n_hashes = metadata.hashes().len();
+    let n_shards = n_shards.get().into();
+    crate::ensure!(
+        n_hashes == n_shards,
+        VerificationError::InvalidHashCount {
+            actual: n_hashes,
+            expected: n_shards,
+        }
+    );
+    Ok(())
+}
+
+/// Verifies the metadata of a blob stored on a system with `n_shards` shards.
+///
+/// Performs the same checks as [`UnverifiedBlobMetadataWithId::verify`] for an encoding config
+/// with the default numbers of source symbols for `n_shards`, without creating the config. The
+/// encoding parameters stored in the metadata, if any, are checked against those derived for
+/// `n_shards` with their encoding version.
+pub fn verify_metadata(
+    metadata: UnverifiedBlobMetadataWithId,
+    n_shards: NonZeroU16,
+) -> Result<VerifiedBlobMetadataWithId, VerificationError> {
+    let blob_metadata = metadata.metadata();
+    verify_hash_count(blob_metadata, n_shards)?;
+    if let Some(params) = blob_metadata.encoding_params() {
+        params.verify_derivation(n_shards, blob_metadata.encoding_type())?;
+    }
+    crate::ensure!(
+        blob_metadata.unencoded_length()
+            <= max_blob_size_for_n_shards(n_shards, blob_metadata.encoding_type()),
+        VerificationError::UnencodedLengthTooLarge
+    );
+    verify_blob_id(metadata.blob_id(), blob_metadata)?;
+    Ok(metadata.into_verified_unchecked())
+}
+
+/// Returns true if the `proof` authenticates `leaf` as the leaf with index `leaf_index` of the
+/// Merkle tree of the sliver with the given index and type, whose root is the sliver hash in the
+/// verified `metadata`.
+///
+/// Returns false if the metadata contains no hash for the sliver.
+pub fn verify_sliver_proof<M: MerkleAuth>(
+    metadata: &VerifiedBlobMetadataWithId,
+    sliver_pair_index: SliverPairIndex,
+    sliver_type: SliverType,
+    proof: &M,
+    leaf: &[u8],
+    leaf_index: usize,
+) -> bool {
+    metadata
+        .metadata()
+        .get_sliver_hash(sliver_pair_index, sliver_type)
+        .is_some_and(|root| proof.verify_proof(root, leaf, leaf_index))
+}
+
+#[cfg(test)]
+mod tests {
+    use walrus_test_utils::Result as TestResult;
+
+    use super::*;
+    use crate::{
+        encoding::{EncodingConfig, EncodingConfigTrait as _},
+        merkle::MerkleTree,
+        metadata::{BlobMetadataWithId, EncodingParams, SliverPairMetadata},
+        DefaultHashFunction,
+    };
+
+    #[test]
+    fn verifies_metadata_without_encoding_config() -> TestResult {
+        let n_shards = NonZeroU16::new(10).unwrap();
+        let config = EncodingConfig::new(n_shards);
+        let metadata = config
+            .get_for_type(crate::DEFAULT_ENCODING)
+            .compute_metadata(&walrus_test_utils::random_data(1000))?;
+
+        let verified = verify_metadata(metadata.clone().into_unverified(), n_shards)?;
+        assert_eq!(verified, metadata);
+        assert_eq!(
+            verify_metadata(
+                metadata.clone().into_unverified(),
+                NonZeroU16::new(11).unwrap()
+            ),
+            Err(VerificationError::InvalidHashCount {
+                actual: 10,
+                expected: 11
+            })
+        );
+
+        let other_blob_id = BlobId([7; BlobId::LENGTH]);
+        assert_eq!(
+            verify_metadata(
+                BlobMetadataWithId::new(other_blob_id, metadata.metadata().clone()),
+                n_shards
+            ),
+            Err(VerificationError::BlobIdMismatch)
+        );
+
+        let with_encoding_params = metadata.clone().with_encoding_params();
+        assert_eq!(
+            verify_metadata(with_encoding_params.clone().into_unverified(), n_shards)?,
+            with_encoding_params
+        );
+
+        let mut encoding_params =
+            EncodingParams::from_config(&config.get_for_type(crate::DEFAULT_ENCODING));
+        encoding_params.n_primary_source_symbols = NonZeroU16::MAX;
+        let blob_metadata = metadata
+            .metadata()
+            .clone()
+            .with_encoding_params(encoding_params);
+        assert_eq!(
+            verify_metadata(
+                BlobMetadataWithId::new(*metadata.blob_id(), blob_metadata),
+                n_shards
+            ),
+            Err(VerificationError::EncodingParamsMismatch)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn verifies_merkle_proofs_against_sliver_hashes() {
+        let leaves = [b"foo".as_slice(), b"bar", b"baz"];
+        let tree = MerkleTree::<DefaultHashFunction>::build(leaves);
+        let proof = tree.get_proof(1).unwrap();
+
+        let pair_metadata = SliverPairMetadata {
+            primary_hash: tree.root(),
+            secondary_hash: Node::Empty,
+        };
+        let metadata = BlobMetadataWithId::<false>::new_verified_from_metadata(
+            alloc::vec![pair_metadata],
+            crate::DEFAULT_ENCODING,
+            1,
+        );
+
+        let index = SliverPairIndex(0);
+        assert!(verify_sliver_proof(
+            &metadata,
+            index,
+            SliverType::Primary,
+            &proof,
+            b"bar",
+            1
+        ));
+        assert!(!verify_sliver_proof(
+            &metadata,
+            index,
+            SliverType::Primary,
+            &proof,
+            b"foo",
+            1
+        ));
+        assert!(!verify_sliver_proof(
+            &metadata,
+            index,
+            SliverType::Secondary,
+            &proof,
+            b"bar",
+            1
+        ));
+        assert!(!verify_sliver_proof(
+            &metadata,
+            SliverPairIndex(1),
+            SliverType::Primary,
+            &proof,
+            b"bar",
+            1
+        ));
+    }
+}
@@ -23,7 +23,7 @@ use sui_types::base_types::{SuiAddress, SUI_ADDRESS_LENGTH};
 use tempfile::TempDir;
 use tokio_stream::StreamExt;
 use walrus_core::{
-    encoding::{EncodingConfigTrait as _, Primary},
+    encoding::{source_symbols_for_n_shards, EncodingConfigTrait as _, Primary},
     merkle::Node,
     messages::BlobPersistenceType,
     metadata::VerifiedBlobMetadataWithId,
@@ -32,6 +32,7 @@ use walrus_core::{
     EpochCount,
     ShardIndex,
     SliverPairIndex,
+    SliverType,
     DEFAULT_ENCODING,
 };
 use walrus_proc_macros::walrus_simtest;
@@ -46,8 +47,9 @@ use walrus_sdk::{
             NoMetadataReceived,
             NoValidStatusReceived,
             NotEnoughConfirmations,
-            NotEnoughSlivers,
+            NotEnoughValidSlivers,
         },
+        SliverRetrievalDiagnosis,
     },
     store_when::StoreWhen,
 };
@@ -178,9 +180,9 @@ async_param_test! {
         all_shard_failures: (&[0, 1, 2, 3, 4], &[], &[NoValidStatusReceived]),
         f_plus_one_read_failures: (&[], &[0, 4], &[]),
         two_f_plus_one_read_failures: (
-            &[], &[1, 2, 4], &[NoMetadataReceived, NotEnoughSlivers]),
+            &[], &[1, 2, 4], &[NoMetadataReceived, not_enough_valid_primary_slivers()]),
         read_and_write_overlap_failures: (
-            &[4], &[2, 3], &[NoMetadataReceived, NotEnoughSlivers]),
+            &[4], &[2, 3], &[NoMetadataReceived, not_enough_valid_primary_slivers()]),
     ]
 }
 async fn test_store_and_read_blob_with_crash_failures(
@@ -290,7 +292,7 @@ async fn test_inconsistency(failed_nodes: &[usize]) -> TestResult {
     // depends on the blob id, we need to search for an invalid hash for which the modified shard
     // is not in the failure set.
     loop {
-        metadata.mut_inner().hashes[1].primary_hash = Node::Digest([i; 32]);
+        metadata.hashes_mut()[1].primary_hash = Node::Digest([i; 32]);
         let blob_id = BlobId::from_sliver_pair_metadata(&metadata);
         if !shards_of_failed_nodes.contains(
             &SliverPairIndex::new(1).to_shard_index(NonZeroU16::new(13).unwrap(), &blob_id),
@@ -369,6 +371,23 @@ async fn test_inconsistency(failed_nodes: &[usize]) -> TestResult {
     Ok(())
 }
 
+/// Returns the error of a read of a blob from the primary slivers of the default test cluster that
+/// fails because the nodes holding too many shards have crashed.
+fn not_enough_valid_primary_slivers() -> ClientErrorKind {
+    let n_shards = NonZeroU16::new(13).expect("the number of shards is non-zero");
+    let (n_required_slivers, _) = source_symbols_for_n_shards(n_shards, DEFAULT_ENCODING);
+    NotEnoughValidSlivers(Box::new(SliverRetrievalDiagnosis {
+        sliver_type: SliverType::Primary,
+        n_valid_slivers: 0,
+        n_required_slivers: n_required_slivers.get().into(),
+        n_failed_verification: 0,
+        nodes_failing_verification: vec![],
+        n_not_found: 0,
+        n_forbidden: 0,
+        n_other_failures: 0,
+    }))
+}
+
 fn error_kind_matches(actual: &ClientErrorKind, expected: &ClientErrorKind) -> bool {
     match (actual, expected) {
         (
@@ -376,6 +395,18 @@ fn error_kind_matches(actual: &ClientErrorKind, expected: &ClientErrorKind) -> b
             ClientErrorKind::NotEnoughConfirmations(exp_a, exp_b),
         ) => act_a == exp_a && act_b == exp_b,
         (ClientErrorKind::NotEnoughSlivers, ClientErrorKind::NotEnoughSlivers) => true,
+        // The numbers of valid, missing, and unreachable slivers depend on the timing of the
+        // responses, so only the remaining fields of the diagnosis are compared.
+        (
+            ClientErrorKind::NotEnoughValidSlivers(actual),
+            ClientErrorKind::NotEnoughValidSlivers(expected),
+        ) => {
+            actual.sliver_type == expected.sliver_type
+                && actual.n_required_slivers == expected.n_required_slivers
+                && actual.n_failed_verification == expected.n_failed_verification
+                && actual.nodes_failing_verification == expected.nodes_failing_verification
+                && actual.n_missing_slivers() > 0
+        }
         (ClientErrorKind::BlobIdDoesNotExist, ClientErrorKind::BlobIdDoesNotExist) => true,
         (ClientErrorKind::NoMetadataReceived, ClientErrorKind::NoMetadataReceived) => true,
         (ClientErrorKind::NoValidStatusReceived, ClientErrorKind::NoValidStatusReceived) => true,
@@ -609,6 +640,56 @@ pub async fn test_store_and_read_duplicate_blobs() -> TestResult {
     Ok(())
 }
 
+/// Tests that a quilt can be stored and that its entries can be read individually.
+#[ignore = "ignore E2E tests by default"]
+#[walrus_simtest]
+async fn test_store_quilt_and_read_entries() -> TestResult {
+    telemetry_subscribers::init_for_testing();
+
+    let (_sui_cluster_handle, _cluster, client) = test_cluster::default_setup().await?;
+    let client = client.as_ref();
+
+    let entries: Vec<_> = walrus_test_utils::random_data_list(1024, 4)
+        .into_iter()
+        .enumerate()
+        .map(|(i, data)| (format!("entry-{i}"), data))
+        .collect();
+
+    let store_result = client
+        .reserve_and_store_quilt_retry_committees(
+            &entries,
+            DEFAULT_ENCODING,
+            1,
+            StoreWhen::Always,
+            BlobPersistence::Permanent,
+            PostStoreAction::Keep,
+        )
+        .await?;
+    let quilt_id = store_result
+        .blob_store_result
+        .blob_id()
+        .expect("blob id should be present");
+    assert_eq!(store_result.entries.len(), entries.len());
+
+    let index = client.read_quilt_index(&quilt_id).await?;
+    assert_eq!(index.len(), entries.len());
+
+    for (entry_result, (identifier, data)) in store_result.entries.iter().zip(entries.iter()) {
+        assert_eq!(&entry_result.identifier, identifier);
+        let entry_id = entry_result.entry_id.expect("entry id should be present");
+        let (entry, entry_data) = client.read_quilt_entry(&entry_id).await?;
+        assert_eq!(&entry.identifier, identifier);
+        assert_eq!(&entry_data, data);
+
+        let (_, entry_data) = client
+            .read_quilt_entry_by_identifier(&quilt_id, identifier)
+            .await?;
+        assert_eq!(&entry_data, data);
+    }
+
+    Ok(())
+}
+
 /// Tests that blobs can be extended when possible.
 #[ignore = "ignore E2E tests by default"]
 #[walrus_simtest]
@@ -0,0 +1,127 @@
+// Copyright (c) Walrus Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+// C bindings to store and read blobs on Walrus.
+//
+// All functions except the release functions return a `WalrusErrorCode`. If a call fails, a
+// description of the error is available from `walrus_last_error_message` on the same thread.
+// Buffers and strings returned by the library must be released with `walrus_bytes_free` and
+// `walrus_string_free`, respectively.
+
+#ifndef WALRUS_H
+#define WALRUS_H
+
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+// The result of a call to the Walrus C API.
+typedef enum WalrusErrorCode {
+    // The call succeeded.
+    WALRUS_OK = 0,
+    // An argument is a null pointer, is not valid UTF-8, or is otherwise invalid.
+    WALRUS_INVALID_ARGUMENT = 1,
+    // The configuration, the wallet, or the client could not be loaded.
+    WALRUS_CONFIGURATION = 2,
+    // The blob does not exist on Walrus.
+    WALRUS_BLOB_NOT_FOUND = 3,
+    // The blob is blocked by the storage nodes.
+    WALRUS_BLOB_BLOCKED = 4,
+    // The wallet does not have enough WAL or SUI to pay for the operation.
+    WALRUS_INSUFFICIENT_FUNDS = 5,
+    // The call failed for a transient reason and may succeed when retried.
+    WALRUS_RETRIABLE = 6,
+    // The call failed for any other reason.
+    WALRUS_OTHER = 7,
+} WalrusErrorCode;
+
+// The kind of status of a blob on Walrus.
+typedef enum WalrusBlobStatusKind {
+    // The blob does not exist (anymore) within Walrus.
+    WALRUS_BLOB_STATUS_NONEXISTENT = 0,
+    // The blob ID has been marked as invalid.
+    WALRUS_BLOB_STATUS_INVALID = 1,
+    // The blob exists within Walrus in a permanent state.
+    WALRUS_BLOB_STATUS_PERMANENT = 2,
+    // The blob exists within Walrus, but only as deletable blobs.
+    WALRUS_BLOB_STATUS_DELETABLE = 3,
+} WalrusBlobStatusKind;
+
+// The status of a blob on Walrus.
+typedef struct WalrusBlobStatus {
+    // The kind of the status.
+    WalrusBlobStatusKind kind;
+    // Whether the blob is certified.
+    bool is_certified;
+    // The epoch at which the permanent blob expires (exclusive); 0 for the other kinds.
+    uint32_t end_epoch;
+    // The epoch in which the blob was first certified; only meaningful if `is_certified`.
+    uint32_t initial_certified_epoch;
+} WalrusBlobStatus;
+
+// An opaque handle to a Walrus client.
+typedef struct WalrusClient WalrusClient;
+
+// Creates a Walrus client from the configuration at `config_path` with the given `context`.
+//
+// If `config_path` is null, the configuration is loaded from the default locations; if `context`
+// is null, the default context is used. The handle must be released with `walrus_client_free`.
+WalrusErrorCode walrus_client_new(
+    const char *config_path,
+    const char *context,
+    WalrusClient **client_out
+);
+
+// Releases the client handle. Does nothing if `client` is null.
+void walrus_client_free(WalrusClient *client);
+
+// Stores `data_len` bytes at `data` as a blob for `epochs` epochs and returns its blob ID.
+//
+// The blob is stored only if it is not already stored for at least `epochs` epochs. The returned
+// blob ID must be released with `walrus_string_free`.
+WalrusErrorCode walrus_store_blob(
+    const WalrusClient *client,
+    const uint8_t *data,
+    size_t data_len,
+    uint32_t epochs,
+    bool deletable,
+    char **blob_id_out
+);
+
+// Reads the blob with the given blob ID.
+//
+// The returned buffer must be released with `walrus_bytes_free`.
+WalrusErrorCode walrus_read_blob(
+    const WalrusClient *client,
+    const char *blob_id,
+    uint8_t **data_out,
+    size_t *data_len_out
+);
+
+// Gets the status of the blob with the given blob ID.
+WalrusErrorCode walrus_blob_status(
+    const WalrusClient *client,
+    const char *blob_id,
+    WalrusBlobStatus *status_out
+);
+
+// Returns the message of the last error on the calling thread, or null if the last call succeeded.
+//
+// The string is valid until the next call on the same thread and must not be freed.
+const char *walrus_last_error_message(void);
+
+// Releases a buffer returned by `walrus_read_blob`. Does nothing if `data` is null.
+void walrus_bytes_free(uint8_t *data, size_t data_len);
+
+// Releases a string returned by the library. Does nothing if `string` is null.
+void walrus_string_free(char *string);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif // WALRUS_H
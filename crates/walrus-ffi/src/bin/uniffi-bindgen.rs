@@ -0,0 +1,8 @@
+// Copyright (c) Walrus Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Generates the Swift and Kotlin bindings of the Walrus mobile client.
+
+fn main() {
+    uniffi::uniffi_bindgen_main()
+}
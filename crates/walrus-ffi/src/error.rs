@@ -0,0 +1,125 @@
+// Copyright (c) Walrus Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! The error codes returned over the C ABI and the last error of the calling thread.
+
+use std::{
+    cell::RefCell,
+    ffi::{c_char, CString},
+    fmt::Display,
+    ptr,
+};
+
+use walrus_sdk::error::{ClientError, ClientErrorKind};
+
+/// The result of a call to the Walrus C API.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WalrusErrorCode {
+    /// The call succeeded.
+    Ok = 0,
+    /// An argument is a null pointer, is not valid UTF-8, or is otherwise invalid.
+    InvalidArgument = 1,
+    /// The configuration, the wallet, or the client could not be loaded.
+    Configuration = 2,
+    /// The blob does not exist on Walrus.
+    BlobNotFound = 3,
+    /// The blob is blocked by the storage nodes.
+    BlobBlocked = 4,
+    /// The wallet does not have enough WAL or SUI to pay for the operation.
+    InsufficientFunds = 5,
+    /// The call failed for a transient reason and may succeed when retried.
+    Retriable = 6,
+    /// The call failed for any other reason.
+    Other = 7,
+}
+
+/// An error raised by a call to the Walrus C API, before it is reported to the caller.
+#[derive(Debug)]
+pub(crate) struct FfiError {
+    code: WalrusErrorCode,
+    message: String,
+}
+
+impl FfiError {
+    /// Creates a new error with the provided code and message.
+    pub(crate) fn new(code: WalrusErrorCode, message: impl Display) -> Self {
+        Self {
+            code,
+            message: message.to_string(),
+        }
+    }
+
+    /// Creates a new error with code [`WalrusErrorCode::InvalidArgument`].
+    pub(crate) fn invalid_argument(message: impl Display) -> Self {
+        Self::new(WalrusErrorCode::InvalidArgument, message)
+    }
+
+    /// Creates a new error with code [`WalrusErrorCode::Configuration`].
+    pub(crate) fn configuration(error: impl Display) -> Self {
+        Self::new(WalrusErrorCode::Configuration, error)
+    }
+
+    /// Returns the code and the message of the error.
+    #[cfg(feature = "uniffi")]
+    pub(crate) fn into_parts(self) -> (WalrusErrorCode, String) {
+        (self.code, self.message)
+    }
+}
+
+impl From<ClientError> for FfiError {
+    fn from(error: ClientError) -> Self {
+        let code = match error.kind() {
+            ClientErrorKind::BlobIdDoesNotExist => WalrusErrorCode::BlobNotFound,
+            ClientErrorKind::BlobIdBlocked(_) => WalrusErrorCode::BlobBlocked,
+            _ if error.is_out_of_coin_error() => WalrusErrorCode::InsufficientFunds,
+            _ if error.is_retriable() => WalrusErrorCode::Retriable,
+            _ => WalrusErrorCode::Other,
+        };
+        Self::new(code, error)
+    }
+}
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+/// Runs `f` and returns its error code, recording the message of a failure as the last error of
+/// the calling thread.
+pub(crate) fn run(f: impl FnOnce() -> Result<(), FfiError>) -> WalrusErrorCode {
+    match f() {
+        Ok(()) => {
+            clear_last_error();
+            WalrusErrorCode::Ok
+        }
+        Err(error) => {
+            set_last_error(&error.message);
+            error.code
+        }
+    }
+}
+
+fn set_last_error(message: &str) {
+    // Interior null bytes cannot be represented in a C string, so they are dropped.
+    let message = CString::new(message.replace('\0', "")).expect("null bytes were removed");
+    LAST_ERROR.with(|last_error| *last_error.borrow_mut() = Some(message));
+}
+
+fn clear_last_error() {
+    LAST_ERROR.with(|last_error| *last_error.borrow_mut() = None);
+}
+
+/// Returns the message of the last error on the calling thread, or null if the last call on the
+/// thread succeeded.
+///
+/// The returned string is owned by the library and remains valid until the next call to the
+/// Walrus C API on the same thread; it must not be freed by the caller.
+#[no_mangle]
+pub extern "C" fn walrus_last_error_message() -> *const c_char {
+    LAST_ERROR.with(|last_error| {
+        last_error
+            .borrow()
+            .as_ref()
+            .map_or(ptr::null(), |message| message.as_ptr())
+    })
+}
@@ -0,0 +1,459 @@
+// Copyright (c) Walrus Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! C bindings to store and read blobs on Walrus.
+//!
+//! The bindings expose a blocking API over the C ABI, so that applications written in other
+//! languages (for example, C, C++, or Go through cgo) can embed Walrus storage. The C declarations
+//! are in `include/walrus.h`.
+//!
+//! A [`WalrusClient`] is an opaque handle created with [`walrus_client_new`] and released with
+//! [`walrus_client_free`]. Every call returns a [`WalrusErrorCode`]; if the call fails, a
+//! description of the error is available from [`walrus_last_error_message`]. Buffers and strings
+//! returned by the library are owned by the caller and must be released with
+//! [`walrus_bytes_free`] and [`walrus_string_free`], respectively.
+//!
+//! With the `uniffi` feature, the crate additionally exposes the `WalrusMobileClient` to Swift and
+//! Kotlin through UniFFI.
+
+use std::{
+    ffi::{c_char, CStr, CString},
+    ptr,
+};
+
+use tokio::runtime::{Builder, Runtime};
+use walrus_core::{encoding::Primary, BlobId, DEFAULT_ENCODING};
+use walrus_rest_client::api::BlobStatus;
+use walrus_sdk::{
+    client::{responses::BlobStoreResult, Client},
+    config::load_configuration,
+    store_when::StoreWhen,
+};
+use walrus_sui::client::{BlobPersistence, PostStoreAction, SuiContractClient};
+
+use self::error::{run, FfiError};
+pub use self::error::{walrus_last_error_message, WalrusErrorCode};
+#[cfg(feature = "uniffi")]
+pub use self::mobile::{WalrusError, WalrusMobileClient, WalrusStorageCost};
+
+mod error;
+#[cfg(feature = "uniffi")]
+mod mobile;
+
+#[cfg(feature = "uniffi")]
+uniffi::setup_scaffolding!();
+
+/// An opaque handle to a Walrus client and the runtime on which it runs.
+#[derive(Debug)]
+pub struct WalrusClient {
+    // The client is declared first so that it is dropped before the runtime running its tasks.
+    client: Client<SuiContractClient>,
+    runtime: Runtime,
+}
+
+impl WalrusClient {
+    /// Creates a client from the Walrus configuration at `config_path` and with the given
+    /// `context`, which signs transactions with the wallet set in the configuration.
+    fn new(config_path: Option<&str>, context: Option<&str>) -> Result<Self, FfiError> {
+        let runtime = Builder::new_multi_thread()
+            .enable_all()
+            .build()
+            .map_err(FfiError::configuration)?;
+        let client = runtime.block_on(async {
+            let config =
+                load_configuration(config_path, context).map_err(FfiError::configuration)?;
+            let sui_client = config
+                .new_contract_client_with_wallet_in_config(None)
+                .await
+                .map_err(FfiError::configuration)?;
+            Client::new_contract_client_with_refresher(config, sui_client)
+                .await
+                .map_err(FfiError::configuration)
+        })?;
+        Ok(Self { client, runtime })
+    }
+
+    /// Stores the blob for `epochs` epochs, unless it is already stored for long enough, and
+    /// returns its blob ID.
+    fn store_blob(&self, data: &[u8], epochs: u32, deletable: bool) -> Result<BlobId, FfiError> {
+        if epochs == 0 {
+            return Err(FfiError::invalid_argument("epochs must be greater than 0"));
+        }
+
+        let results =
+            self.runtime
+                .block_on(self.client.reserve_and_store_blobs_retry_committees(
+                    &[data],
+                    DEFAULT_ENCODING,
+                    epochs,
+                    StoreWhen::NotStored,
+                    BlobPersistence::from_deletable(deletable),
+                    PostStoreAction::Keep,
+                    None,
+                ))?;
+        let result = results
+            .into_iter()
+            .next()
+            .ok_or_else(|| FfiError::new(WalrusErrorCode::Other, "no store result returned"))?;
+        match result {
+            BlobStoreResult::Error { error_msg, .. } => {
+                Err(FfiError::new(WalrusErrorCode::Other, error_msg))
+            }
+            BlobStoreResult::MarkedInvalid { blob_id, .. } => Err(FfiError::new(
+                WalrusErrorCode::Other,
+                format!("the blob {blob_id} was marked as invalid"),
+            )),
+            result => Ok(result.blob_id().expect("stored blobs have a blob ID")),
+        }
+    }
+
+    /// Reads the blob with the given blob ID.
+    fn read_blob(&self, blob_id: &BlobId) -> Result<Vec<u8>, FfiError> {
+        Ok(self
+            .runtime
+            .block_on(self.client.read_blob::<Primary>(blob_id))?)
+    }
+
+    /// Gets the verified status of the blob with the given blob ID.
+    fn blob_status(&self, blob_id: &BlobId) -> Result<WalrusBlobStatus, FfiError> {
+        let status = self.runtime.block_on(
+            self.client
+                .get_blob_status_with_retries(blob_id, self.client.sui_client()),
+        )?;
+        Ok(status.into())
+    }
+}
+
+/// The kind of status of a blob on Walrus.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "uniffi", derive(uniffi::Enum))]
+pub enum WalrusBlobStatusKind {
+    /// The blob does not exist (anymore) within Walrus.
+    Nonexistent = 0,
+    /// The blob ID has been marked as invalid.
+    Invalid = 1,
+    /// The blob exists within Walrus in a permanent state.
+    Permanent = 2,
+    /// The blob exists within Walrus, but only as deletable blobs.
+    Deletable = 3,
+}
+
+/// The status of a blob on Walrus.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "uniffi", derive(uniffi::Record))]
+pub struct WalrusBlobStatus {
+    /// The kind of the status.
+    pub kind: WalrusBlobStatusKind,
+    /// Whether the blob is certified.
+    pub is_certified: bool,
+    /// The epoch at which the permanent blob expires (exclusive); 0 for the other kinds.
+    pub end_epoch: u32,
+    /// The epoch in which the blob was first certified; only meaningful if `is_certified`.
+    pub initial_certified_epoch: u32,
+}
+
+impl From<BlobStatus> for WalrusBlobStatus {
+    fn from(status: BlobStatus) -> Self {
+        let initial_certified_epoch = status.initial_certified_epoch();
+        let (kind, end_epoch) = match status {
+            BlobStatus::Nonexistent => (WalrusBlobStatusKind::Nonexistent, 0),
+            BlobStatus::Invalid { .. } => (WalrusBlobStatusKind::Invalid, 0),
+            BlobStatus::Permanent { end_epoch, .. } => (WalrusBlobStatusKind::Permanent, end_epoch),
+            BlobStatus::Deletable { .. } => (WalrusBlobStatusKind::Deletable, 0),
+        };
+        Self {
+            kind,
+            is_certified: initial_certified_epoch.is_some(),
+            end_epoch,
+            initial_certified_epoch: initial_certified_epoch.unwrap_or_default(),
+        }
+    }
+}
+
+/// Creates a Walrus client and writes its handle to `client_out`.
+///
+/// The client is configured from the Walrus configuration at `config_path` and with the given
+/// `context`, and signs transactions with the wallet set in the configuration. If `config_path`
+/// is null, the configuration is loaded from the default locations; if `context` is null, the
+/// default context is used.
+///
+/// # Safety
+///
+/// `config_path` and `context` must each be null or a valid null-terminated string, and
+/// `client_out` must be a valid pointer to write a client handle to. The handle must be released
+/// with [`walrus_client_free`].
+#[no_mangle]
+pub unsafe extern "C" fn walrus_client_new(
+    config_path: *const c_char,
+    context: *const c_char,
+    client_out: *mut *mut WalrusClient,
+) -> WalrusErrorCode {
+    run(|| {
+        let config_path = unsafe { optional_str(config_path, "config_path") }?;
+        let context = unsafe { optional_str(context, "context") }?;
+        if client_out.is_null() {
+            return Err(FfiError::invalid_argument("client_out must not be null"));
+        }
+
+        let client = Box::new(WalrusClient::new(config_path, context)?);
+        unsafe { client_out.write(Box::into_raw(client)) };
+        Ok(())
+    })
+}
+
+/// Releases the client handle. Does nothing if `client` is null.
+///
+/// # Safety
+///
+/// `client` must be null or a handle returned by [`walrus_client_new`] that has not been
+/// released yet.
+#[no_mangle]
+pub unsafe extern "C" fn walrus_client_free(client: *mut WalrusClient) {
+    if !client.is_null() {
+        drop(unsafe { Box::from_raw(client) });
+    }
+}
+
+/// Stores the `data_len` bytes at `data` as a blob for `epochs` epochs, and writes its blob ID to
+/// `blob_id_out`.
+///
+/// The blob is stored only if it is not already stored for at least `epochs` epochs. If
+/// `deletable` is true, the blob can be deleted before it expires. The blob object is kept in the
+/// wallet of the client.
+///
+/// # Safety
+///
+/// `client` must be a valid client handle, `data` must point to `data_len` readable bytes, and
+/// `blob_id_out` must be a valid pointer to write a string to. The returned string must be
+/// released with [`walrus_string_free`].
+#[no_mangle]
+pub unsafe extern "C" fn walrus_store_blob(
+    client: *const WalrusClient,
+    data: *const u8,
+    data_len: usize,
+    epochs: u32,
+    deletable: bool,
+    blob_id_out: *mut *mut c_char,
+) -> WalrusErrorCode {
+    run(|| {
+        let client = unsafe { client_ref(client) }?;
+        if data.is_null() && data_len > 0 {
+            return Err(FfiError::invalid_argument("data must not be null"));
+        }
+        if blob_id_out.is_null() {
+            return Err(FfiError::invalid_argument("blob_id_out must not be null"));
+        }
+        let data: &[u8] = if data_len == 0 {
+            &[]
+        } else {
+            unsafe { std::slice::from_raw_parts(data, data_len) }
+        };
+
+        let blob_id = client.store_blob(data, epochs, deletable)?;
+        let blob_id = CString::new(blob_id.to_string()).expect("blob IDs contain no null bytes");
+        unsafe { blob_id_out.write(blob_id.into_raw()) };
+        Ok(())
+    })
+}
+
+/// Reads the blob with the given blob ID and writes its data and length to `data_out` and
+/// `data_len_out`.
+///
+/// # Safety
+///
+/// `client` must be a valid client handle, `blob_id` must be a valid null-terminated string, and
+/// `data_out` and `data_len_out` must be valid pointers to write to. The returned buffer must be
+/// released with [`walrus_bytes_free`].
+#[no_mangle]
+pub unsafe extern "C" fn walrus_read_blob(
+    client: *const WalrusClient,
+    blob_id: *const c_char,
+    data_out: *mut *mut u8,
+    data_len_out: *mut usize,
+) -> WalrusErrorCode {
+    run(|| {
+        let client = unsafe { client_ref(client) }?;
+        let blob_id = unsafe { parse_blob_id(blob_id) }?;
+        if data_out.is_null() || data_len_out.is_null() {
+            return Err(FfiError::invalid_argument(
+                "data_out and data_len_out must not be null",
+            ));
+        }
+
+        let data = client.read_blob(&blob_id)?.into_boxed_slice();
+        unsafe {
+            data_len_out.write(data.len());
+            data_out.write(Box::into_raw(data).cast());
+        }
+        Ok(())
+    })
+}
+
+/// Gets the status of the blob with the given blob ID and writes it to `status_out`.
+///
+/// # Safety
+///
+/// `client` must be a valid client handle, `blob_id` must be a valid null-terminated string, and
+/// `status_out` must be a valid pointer to write a [`WalrusBlobStatus`] to.
+#[no_mangle]
+pub unsafe extern "C" fn walrus_blob_status(
+    client: *const WalrusClient,
+    blob_id: *const c_char,
+    status_out: *mut WalrusBlobStatus,
+) -> WalrusErrorCode {
+    run(|| {
+        let client = unsafe { client_ref(client) }?;
+        let blob_id = unsafe { parse_blob_id(blob_id) }?;
+        if status_out.is_null() {
+            return Err(FfiError::invalid_argument("status_out must not be null"));
+        }
+
+        unsafe { status_out.write(client.blob_status(&blob_id)?) };
+        Ok(())
+    })
+}
+
+/// Releases a buffer returned by [`walrus_read_blob`]. Does nothing if `data` is null.
+///
+/// # Safety
+///
+/// `data` must be null or a buffer returned by the library that has not been released yet, and
+/// `data_len` must be the length returned with it.
+#[no_mangle]
+pub unsafe extern "C" fn walrus_bytes_free(data: *mut u8, data_len: usize) {
+    if !data.is_null() {
+        drop(unsafe { Box::from_raw(ptr::slice_from_raw_parts_mut(data, data_len)) });
+    }
+}
+
+/// Releases a string returned by the library. Does nothing if `string` is null.
+///
+/// # Safety
+///
+/// `string` must be null or a string returned by the library that has not been released yet.
+#[no_mangle]
+pub unsafe extern "C" fn walrus_string_free(string: *mut c_char) {
+    if !string.is_null() {
+        drop(unsafe { CString::from_raw(string) });
+    }
+}
+
+/// Returns a reference to the client behind the handle.
+///
+/// # Safety
+///
+/// `client` must be null or a valid client handle.
+unsafe fn client_ref<'a>(client: *const WalrusClient) -> Result<&'a WalrusClient, FfiError> {
+    unsafe { client.as_ref() }.ok_or_else(|| FfiError::invalid_argument("client must not be null"))
+}
+
+/// Converts the C string to a string slice, returning `None` if it is null.
+///
+/// # Safety
+///
+/// `string` must be null or a valid null-terminated string.
+unsafe fn optional_str<'a>(string: *const c_char, name: &str) -> Result<Option<&'a str>, FfiError> {
+    if string.is_null() {
+        return Ok(None);
+    }
+    unsafe { CStr::from_ptr(string) }
+        .to_str()
+        .map(Some)
+        .map_err(|_| FfiError::invalid_argument(format!("{name} must be valid UTF-8")))
+}
+
+/// Parses the C string as a blob ID.
+///
+/// # Safety
+///
+/// `blob_id` must be null or a valid null-terminated string.
+unsafe fn parse_blob_id(blob_id: *const c_char) -> Result<BlobId, FfiError> {
+    let blob_id = unsafe { optional_str(blob_id, "blob_id") }?
+        .ok_or_else(|| FfiError::invalid_argument("blob_id must not be null"))?;
+    blob_id_from_str(blob_id)
+}
+
+/// Parses the string as a blob ID.
+fn blob_id_from_str(blob_id: &str) -> Result<BlobId, FfiError> {
+    blob_id
+        .parse()
+        .map_err(|error| FfiError::invalid_argument(format!("invalid blob ID: {error}")))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ffi::CStr;
+
+    use walrus_rest_client::api::DeletableCounts;
+
+    use super::*;
+
+    fn last_error_message() -> String {
+        let message = walrus_last_error_message();
+        assert!(!message.is_null());
+        unsafe { CStr::from_ptr(message) }
+            .to_string_lossy()
+            .into_owned()
+    }
+
+    #[test]
+    fn rejects_null_client() {
+        let blob_id = CString::new(BlobId([1; 32]).to_string()).unwrap();
+        let mut status = WalrusBlobStatus::from(BlobStatus::Nonexistent);
+
+        let code = unsafe { walrus_blob_status(ptr::null(), blob_id.as_ptr(), &mut status) };
+
+        assert_eq!(code, WalrusErrorCode::InvalidArgument);
+        assert_eq!(last_error_message(), "client must not be null");
+    }
+
+    #[test]
+    fn rejects_invalid_blob_id() {
+        let error = unsafe { parse_blob_id(c"not a blob ID".as_ptr()) }.unwrap_err();
+        assert_eq!(run(|| Err(error)), WalrusErrorCode::InvalidArgument);
+        assert!(last_error_message().starts_with("invalid blob ID"));
+    }
+
+    #[test]
+    fn parses_blob_id() {
+        let blob_id = BlobId([7; 32]);
+        let string = CString::new(blob_id.to_string()).unwrap();
+        assert_eq!(unsafe { parse_blob_id(string.as_ptr()) }.unwrap(), blob_id);
+    }
+
+    #[test]
+    fn clears_last_error_on_success() {
+        run(|| Err(FfiError::invalid_argument("error")));
+        assert_eq!(run(|| Ok(())), WalrusErrorCode::Ok);
+        assert!(walrus_last_error_message().is_null());
+    }
+
+    #[test]
+    fn converts_blob_status() {
+        let status = BlobStatus::Deletable {
+            initial_certified_epoch: Some(7),
+            deletable_counts: DeletableCounts::default(),
+        };
+        assert_eq!(
+            WalrusBlobStatus::from(status),
+            WalrusBlobStatus {
+                kind: WalrusBlobStatusKind::Deletable,
+                is_certified: true,
+                end_epoch: 0,
+                initial_certified_epoch: 7,
+            }
+        );
+    }
+
+    #[test]
+    fn round_trips_returned_buffers() {
+        let data = vec![1_u8, 2, 3].into_boxed_slice();
+        let len = data.len();
+        unsafe { walrus_bytes_free(Box::into_raw(data).cast(), len) };
+        unsafe { walrus_string_free(CString::new("blob").unwrap().into_raw()) };
+        unsafe { walrus_bytes_free(ptr::null_mut(), 0) };
+        unsafe { walrus_string_free(ptr::null_mut()) };
+    }
+}
@@ -0,0 +1,203 @@
+// Copyright (c) Walrus Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! UniFFI bindings for mobile applications.
+//!
+//! The bindings expose a trimmed, blocking client API to Swift and Kotlin. They are generated from
+//! the compiled library with the `uniffi-bindgen` binary of this crate, for example:
+//!
+//! ```sh
+//! cargo build -p walrus-ffi --release --features uniffi
+//! cargo run -p walrus-ffi --features uniffi-cli --bin uniffi-bindgen -- generate \
+//!     --library target/release/libwalrus_ffi.so --language kotlin --out-dir bindings
+//! ```
+
+use std::sync::Arc;
+
+use walrus_core::{encoding::EncodingConfigTrait as _, DEFAULT_ENCODING};
+
+use crate::{blob_id_from_str, error::FfiError, WalrusBlobStatus, WalrusClient, WalrusErrorCode};
+
+/// A Walrus client for mobile applications.
+#[derive(Debug, uniffi::Object)]
+pub struct WalrusMobileClient(WalrusClient);
+
+#[uniffi::export]
+impl WalrusMobileClient {
+    /// Creates a client from the Walrus configuration at `config_path` and with the given
+    /// `context`, which signs transactions with the wallet set in the configuration.
+    ///
+    /// If `config_path` is not provided, the configuration is loaded from the default locations;
+    /// if `context` is not provided, the default context is used.
+    #[uniffi::constructor]
+    pub fn new(
+        config_path: Option<String>,
+        context: Option<String>,
+    ) -> Result<Arc<Self>, WalrusError> {
+        let client = WalrusClient::new(config_path.as_deref(), context.as_deref())?;
+        Ok(Arc::new(Self(client)))
+    }
+
+    /// Stores the blob for `epochs` epochs and returns its blob ID.
+    ///
+    /// The blob is stored only if it is not already stored for at least `epochs` epochs. If
+    /// `deletable` is true, the blob can be deleted before it expires.
+    pub fn store_blob(
+        &self,
+        data: Vec<u8>,
+        epochs: u32,
+        deletable: bool,
+    ) -> Result<String, WalrusError> {
+        Ok(self.0.store_blob(&data, epochs, deletable)?.to_string())
+    }
+
+    /// Reads the blob with the given blob ID.
+    pub fn read_blob(&self, blob_id: String) -> Result<Vec<u8>, WalrusError> {
+        Ok(self.0.read_blob(&blob_id_from_str(&blob_id)?)?)
+    }
+
+    /// Gets the verified status of the blob with the given blob ID.
+    pub fn blob_status(&self, blob_id: String) -> Result<WalrusBlobStatus, WalrusError> {
+        Ok(self.0.blob_status(&blob_id_from_str(&blob_id)?)?)
+    }
+
+    /// Estimates the cost of storing a blob of `blob_size` bytes for `epochs` epochs.
+    pub fn estimate_storage_cost(
+        &self,
+        blob_size: u64,
+        epochs: u32,
+    ) -> Result<WalrusStorageCost, WalrusError> {
+        let encoded_size = self
+            .0
+            .client
+            .encoding_config()
+            .get_for_type(DEFAULT_ENCODING)
+            .encoded_blob_length(blob_size)
+            .ok_or_else(|| FfiError::invalid_argument("the blob size is too large"))?;
+        let price = self
+            .0
+            .runtime
+            .block_on(self.0.client.get_price_computation())
+            .map_err(FfiError::from)?;
+        Ok(WalrusStorageCost {
+            encoded_size,
+            storage_cost: price.storage_fee_for_encoded_length(encoded_size, epochs),
+            write_cost: price.write_fee_for_encoded_length(encoded_size),
+        })
+    }
+}
+
+/// The estimated cost of storing a blob, excluding gas.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, uniffi::Record)]
+pub struct WalrusStorageCost {
+    /// The size of the blob once encoded, which determines its cost.
+    pub encoded_size: u64,
+    /// The cost of the storage resource for the blob, in FROST.
+    pub storage_cost: u64,
+    /// The cost of writing the blob, in FROST.
+    pub write_cost: u64,
+}
+
+/// An error raised by the [`WalrusMobileClient`].
+#[derive(Debug, thiserror::Error, uniffi::Error)]
+pub enum WalrusError {
+    /// An argument is invalid.
+    #[error("{message}")]
+    InvalidArgument {
+        /// The description of the error.
+        message: String,
+    },
+    /// The configuration, the wallet, or the client could not be loaded.
+    #[error("{message}")]
+    Configuration {
+        /// The description of the error.
+        message: String,
+    },
+    /// The blob does not exist on Walrus.
+    #[error("{message}")]
+    BlobNotFound {
+        /// The description of the error.
+        message: String,
+    },
+    /// The blob is blocked by the storage nodes.
+    #[error("{message}")]
+    BlobBlocked {
+        /// The description of the error.
+        message: String,
+    },
+    /// The wallet does not have enough WAL or SUI to pay for the operation.
+    #[error("{message}")]
+    InsufficientFunds {
+        /// The description of the error.
+        message: String,
+    },
+    /// The call failed for a transient reason and may succeed when retried.
+    #[error("{message}")]
+    Retriable {
+        /// The description of the error.
+        message: String,
+    },
+    /// The call failed for any other reason.
+    #[error("{message}")]
+    Other {
+        /// The description of the error.
+        message: String,
+    },
+}
+
+impl From<FfiError> for WalrusError {
+    fn from(error: FfiError) -> Self {
+        let (code, message) = error.into_parts();
+        match code {
+            WalrusErrorCode::InvalidArgument => Self::InvalidArgument { message },
+            WalrusErrorCode::Configuration => Self::Configuration { message },
+            WalrusErrorCode::BlobNotFound => Self::BlobNotFound { message },
+            WalrusErrorCode::BlobBlocked => Self::BlobBlocked { message },
+            WalrusErrorCode::InsufficientFunds => Self::InsufficientFunds { message },
+            WalrusErrorCode::Retriable => Self::Retriable { message },
+            WalrusErrorCode::Ok | WalrusErrorCode::Other => Self::Other { message },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn converts_error_codes_to_errors() {
+        let cases = [
+            (WalrusErrorCode::InvalidArgument, "InvalidArgument"),
+            (WalrusErrorCode::Configuration, "Configuration"),
+            (WalrusErrorCode::BlobNotFound, "BlobNotFound"),
+            (WalrusErrorCode::BlobBlocked, "BlobBlocked"),
+            (WalrusErrorCode::InsufficientFunds, "InsufficientFunds"),
+            (WalrusErrorCode::Retriable, "Retriable"),
+            (WalrusErrorCode::Other, "Other"),
+            (WalrusErrorCode::Ok, "Other"),
+        ];
+        for (code, variant) in cases {
+            let error = WalrusError::from(FfiError::new(code, "message"));
+
+            assert!(
+                format!("{error:?}").starts_with(variant),
+                "{code:?} -> {error:?}"
+            );
+            assert_eq!(error.to_string(), "message");
+        }
+    }
+
+    #[test]
+    fn fails_to_create_client_without_configuration() {
+        let dir = tempfile::tempdir().unwrap();
+        let config_path = dir.path().join("client_config.yaml");
+
+        let error = WalrusMobileClient::new(Some(config_path.display().to_string()), None)
+            .expect_err("the configuration does not exist");
+
+        assert!(
+            matches!(error, WalrusError::Configuration { .. }),
+            "{error:?}"
+        );
+    }
+}
@@ -0,0 +1,77 @@
+// Copyright (c) Walrus Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Generates the mobile bindings from the compiled library, as documented in the crate.
+
+#![cfg(feature = "uniffi-cli")]
+
+use std::{
+    env::consts::{DLL_PREFIX, DLL_SUFFIX},
+    fs,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+/// Returns the path of the dynamic library built alongside the `uniffi-bindgen` binary.
+fn library_path() -> PathBuf {
+    Path::new(env!("CARGO_BIN_EXE_uniffi-bindgen"))
+        .with_file_name(format!("{DLL_PREFIX}walrus_ffi{DLL_SUFFIX}"))
+}
+
+/// Generates the bindings for `language` and returns the contents of the generated files.
+fn generate_bindings(language: &str) -> String {
+    let out_dir = tempfile::tempdir().unwrap();
+    let status = Command::new(env!("CARGO_BIN_EXE_uniffi-bindgen"))
+        .args([
+            "generate",
+            "--no-format",
+            "--language",
+            language,
+            "--library",
+        ])
+        .arg(library_path())
+        .arg("--out-dir")
+        .arg(out_dir.path())
+        .status()
+        .unwrap();
+    assert!(
+        status.success(),
+        "generating the {language} bindings failed"
+    );
+
+    let mut contents = String::new();
+    read_files(out_dir.path(), &mut contents);
+    contents
+}
+
+/// Appends the contents of the text files in `dir` and its subdirectories to `contents`.
+fn read_files(dir: &Path, contents: &mut String) {
+    for entry in fs::read_dir(dir).unwrap() {
+        let path = entry.unwrap().path();
+        if path.is_dir() {
+            read_files(&path, contents);
+        } else if let Ok(text) = fs::read_to_string(&path) {
+            contents.push_str(&text);
+        }
+    }
+}
+
+#[test]
+fn generates_kotlin_bindings() {
+    let bindings = generate_bindings("kotlin");
+
+    assert!(bindings.contains("class WalrusMobileClient"));
+    assert!(bindings.contains("fun `storeBlob`"));
+    assert!(bindings.contains("fun `readBlob`"));
+    assert!(bindings.contains("class WalrusException"));
+}
+
+#[test]
+fn generates_swift_bindings() {
+    let bindings = generate_bindings("swift");
+
+    assert!(bindings.contains("class WalrusMobileClient"));
+    assert!(bindings.contains("func storeBlob("));
+    assert!(bindings.contains("func readBlob("));
+    assert!(bindings.contains("struct WalrusStorageCost"));
+}
@@ -3,18 +3,22 @@
 
 //! API types.
 
-use std::cmp::{Ordering, Reverse};
+use std::{cmp::Ordering, time::Duration};
 
-use serde::{Deserialize, Serialize};
-use sui_types::event::EventID;
-use tokio::time::Duration;
-use utoipa::openapi::Ref;
-use walrus_core::{Epoch, PublicKey, ShardIndex};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde_with::{serde_as, DisplayFromStr};
+use walrus_core::{BlobId, Epoch, PublicKey, ShardIndex, SliverIndex, SliverType, SymbolId};
 
 use self::errors::Status;
+use crate::client::{RecoverySymbolsFilter, SymbolIdFilter};
 
+#[cfg(feature = "sui-types")]
+mod blob_status;
 pub mod errors;
 
+#[cfg(feature = "sui-types")]
+pub use self::blob_status::BlobStatus;
+
 /// Error message returned by the service.
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -32,75 +36,190 @@ pub enum ServiceResponse<T> {
     Error(Status),
 }
 
-/// Contains the certification status of a blob.
+/// Name of the HTTP header in which storage nodes advertise the [`ApiVersion`] they implement.
+pub const API_VERSION_HEADER: &str = "x-walrus-api-version";
+
+/// The version of the storage-node API.
 ///
-/// If the a permanent blob exists, it also contains its end epoch and the ID of the Sui event
-/// from which the latest status (registered or certified) resulted.
-#[derive(
-    Debug, Deserialize, Serialize, PartialEq, Eq, Clone, Copy, Default, Hash, utoipa::ToSchema,
-)]
-#[serde(rename_all = "camelCase", rename_all_fields = "camelCase")]
-pub enum BlobStatus {
-    /// The blob does not exist (anymore) within Walrus.
-    #[default]
-    Nonexistent,
-    /// The blob ID has been marked as invalid.
-    Invalid {
-        /// The ID of the Sui event in which the blob was marked as invalid.
-        #[schema(schema_with = event_id_schema)]
-        event: EventID,
-    },
-    /// The blob exists within Walrus in a permanent state.
-    Permanent {
-        /// The latest epoch at which the blob expires (non-inclusive).
-        #[schema(value_type = u64)]
-        end_epoch: Epoch,
-        /// Whether the blob is certified (true) or only registered (false).
-        is_certified: bool,
-        /// The ID of the Sui event that caused the status with the given `end_epoch`.
-        #[schema(schema_with = event_id_schema)]
-        status_event: EventID,
-        /// Counts of deletable `Blob` objects.
-        #[schema(inline)]
-        deletable_counts: DeletableCounts,
-        /// If the blob is certified, contains the epoch where it was initially certified.
-        initial_certified_epoch: Option<Epoch>,
-    },
-    /// The blob exists within Walrus; but there is no related permanent object, so it may be
-    /// deleted at any time.
-    Deletable {
-        /// If the blob is certified, contains the epoch where it was initially certified.
-        // INV: certified_epoch.is_some() == count_deletable_certified > 0
-        initial_certified_epoch: Option<Epoch>,
-        /// Counts of deletable `Blob` objects.
-        #[schema(inline)]
-        deletable_counts: DeletableCounts,
-    },
+/// The version is incremented whenever endpoints are added to the API, which allows clients to
+/// keep communicating with nodes that have not yet been upgraded while the committee upgrades
+/// gradually. Nodes advertise their version in the [`API_VERSION_HEADER`] of every response.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct ApiVersion(pub u32);
+
+impl ApiVersion {
+    /// The version assumed for nodes that do not advertise a version.
+    pub const BASELINE: Self = Self(1);
+    /// The version implemented by this crate.
+    pub const CURRENT: Self = Self(6);
+
+    /// Returns true if a node implementing this version supports the feature.
+    pub fn supports(self, feature: ApiFeature) -> bool {
+        self >= feature.introduced_in()
+    }
+
+    /// Returns the features supported by a node implementing this version.
+    pub fn features(self) -> Vec<ApiFeature> {
+        ApiFeature::ALL
+            .into_iter()
+            .filter(|feature| self.supports(*feature))
+            .collect()
+    }
 }
 
-fn event_id_schema() -> Ref {
-    Ref::new("#/components/schemas/EventID")
+impl Default for ApiVersion {
+    fn default() -> Self {
+        Self::BASELINE
+    }
+}
+
+impl std::fmt::Display for ApiVersion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl std::str::FromStr for ApiVersion {
+    type Err = std::num::ParseIntError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.trim().parse().map(Self)
+    }
 }
 
-impl BlobStatus {
-    /// Returns the certification epoch for the blob, or `None` if the blob is not certified.
-    pub fn initial_certified_epoch(&self) -> Option<Epoch> {
+/// Features of the storage-node API that are not supported by all [`ApiVersion`]s.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ApiFeature {
+    /// Retrieving multiple recovery symbols with a single request.
+    ListRecoverySymbols,
+    /// Retrieving the recovery symbols of multiple blobs with a single request.
+    BatchRecoverySymbols,
+    /// Listing the blobs stored for a shard.
+    ListStoredBlobs,
+    /// Reporting the capabilities of the node.
+    Capabilities,
+    /// Storing and serving [`BlobMetadata::V2`][walrus_core::metadata::BlobMetadata::V2], which
+    /// stores the encoding parameters of the blob.
+    ///
+    /// Clients must keep storing metadata without the encoding parameters until all nodes of the
+    /// committee support this feature.
+    MetadataV2,
+}
+
+impl ApiFeature {
+    /// All features, in the order in which they were introduced.
+    pub const ALL: [Self; 5] = [
+        Self::ListRecoverySymbols,
+        Self::BatchRecoverySymbols,
+        Self::ListStoredBlobs,
+        Self::Capabilities,
+        Self::MetadataV2,
+    ];
+
+    /// Returns the first API version supporting the feature.
+    pub fn introduced_in(self) -> ApiVersion {
         match self {
-            BlobStatus::Permanent {
-                initial_certified_epoch,
-                ..
-            }
-            | BlobStatus::Deletable {
-                initial_certified_epoch,
-                ..
-            } => *initial_certified_epoch,
-            _ => None,
+            ApiFeature::ListRecoverySymbols => ApiVersion(2),
+            ApiFeature::BatchRecoverySymbols => ApiVersion(3),
+            ApiFeature::ListStoredBlobs => ApiVersion(4),
+            ApiFeature::Capabilities => ApiVersion(5),
+            ApiFeature::MetadataV2 => ApiVersion(6),
+        }
+    }
+
+    /// Returns the name of the feature, as reported in the [`NodeCapabilities`].
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ApiFeature::ListRecoverySymbols => "listRecoverySymbols",
+            ApiFeature::BatchRecoverySymbols => "batchRecoverySymbols",
+            ApiFeature::ListStoredBlobs => "listStoredBlobs",
+            ApiFeature::Capabilities => "capabilities",
+            ApiFeature::MetadataV2 => "metadataV2",
+        }
+    }
+
+    /// Returns the feature with the given name, or `None` if the name is unknown.
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|feature| feature.as_str() == name)
+    }
+}
+
+impl Serialize for ApiFeature {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+/// Deserializes a list of feature names, ignoring the features unknown to this crate, which are
+/// reported by nodes implementing a newer version of the API.
+fn deserialize_known_features<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Vec<ApiFeature>, D::Error> {
+    Ok(Vec::<String>::deserialize(deserializer)?
+        .iter()
+        .filter_map(|name| ApiFeature::from_name(name))
+        .collect())
+}
+
+/// The recovery symbols of a single blob requested as part of a batch.
+///
+/// Contains the same information as a [`RecoverySymbolsFilter`] for the blob, in a form that can
+/// be sent in the BCS-encoded body of a request.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BatchedRecoverySymbolsRequest {
+    /// The ID of the blob.
+    pub blob_id: BlobId,
+    /// The symbols to return, or `None` to return all symbols held by the node that can be used
+    /// to recover the target sliver.
+    pub symbol_ids: Option<Vec<SymbolId>>,
+    /// The index of the sliver being recovered.
+    pub target_sliver: SliverIndex,
+    /// The type of the sliver being recovered.
+    pub target_type: SliverType,
+    /// The axis from which the proofs of the symbols must be constructed, if any.
+    pub proof_axis: Option<SliverType>,
+}
+
+impl BatchedRecoverySymbolsRequest {
+    /// Creates a request for the symbols matching `filter` for the recovery of the target sliver
+    /// of the blob.
+    pub fn new(
+        blob_id: BlobId,
+        filter: &RecoverySymbolsFilter,
+        target_sliver: SliverIndex,
+        target_type: SliverType,
+    ) -> Self {
+        let symbol_ids = match filter.id_filter() {
+            SymbolIdFilter::Ids(ids) => Some(ids.clone()),
+            SymbolIdFilter::Recovers { .. } => None,
+        };
+        Self {
+            blob_id,
+            symbol_ids,
+            target_sliver,
+            target_type,
+            proof_axis: filter.proof_axis(),
         }
     }
 
-    /// Returns true iff the blob is registered within Walrus.
-    pub fn is_registered(&self) -> bool {
-        matches!(self, Self::Deletable { .. } | Self::Permanent { .. })
+    /// Returns the filter for the requested symbols, or `None` if the list of requested symbol
+    /// IDs is empty.
+    pub fn filter(&self) -> Option<RecoverySymbolsFilter> {
+        let filter = match &self.symbol_ids {
+            Some(ids) => {
+                let mut ids = ids.clone();
+                ids.sort_unstable();
+                ids.dedup();
+                RecoverySymbolsFilter::ids(ids)?
+            }
+            None => RecoverySymbolsFilter::recovers(self.target_sliver, self.target_type),
+        };
+        Some(match self.proof_axis {
+            Some(axis) => filter.require_proof_from_axis(axis),
+            None => filter,
+        })
     }
 }
 
@@ -129,79 +248,6 @@ impl Ord for DeletableCounts {
     }
 }
 
-impl PartialOrd for BlobStatus {
-    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        Some(self.cmp(other))
-    }
-}
-
-impl Ord for BlobStatus {
-    fn cmp(&self, other: &Self) -> Ordering {
-        use BlobStatus::*;
-
-        match (self, other) {
-            (s, o) if s == o => Ordering::Equal,
-            // Nonexistent is the "smallest" status.
-            (Nonexistent, _) => Ordering::Less,
-            (_, Nonexistent) => Ordering::Greater,
-            // Invalid is the "largest" status.
-            (Invalid { .. }, Invalid { .. }) => Ordering::Equal,
-            (Invalid { .. }, _) => Ordering::Greater,
-            (_, Invalid { .. }) => Ordering::Less,
-            // Permanent is "larger" than Deletable.
-            (Permanent { .. }, Deletable { .. }) => Ordering::Greater,
-            (Deletable { .. }, Permanent { .. }) => Ordering::Less,
-            // For Deletable, first compare certified blobs, then all, then finally compare the
-            // initial certification epoch, preferring smaller values.
-            (
-                Deletable {
-                    initial_certified_epoch,
-                    deletable_counts,
-                },
-                Deletable {
-                    initial_certified_epoch: initial_certified_epoch_other,
-                    deletable_counts: deletable_counts_other,
-                },
-            ) => (deletable_counts, Reverse(initial_certified_epoch)).cmp(&(
-                deletable_counts_other,
-                Reverse(initial_certified_epoch_other),
-            )),
-            // For Permanent, compare status, end epochs, count of deletable blobs, and initial
-            // certification epoch (preferring smaller values), in this order.
-            (
-                Permanent {
-                    end_epoch,
-                    is_certified,
-                    deletable_counts,
-                    initial_certified_epoch,
-                    ..
-                },
-                Permanent {
-                    end_epoch: end_epoch_other,
-                    is_certified: is_certified_other,
-                    deletable_counts: deletable_counts_other,
-                    initial_certified_epoch: initial_certified_epoch_other,
-                    ..
-                },
-            ) => {
-                // Tuples are compared using lexicographic ordering.
-                (
-                    is_certified,
-                    end_epoch,
-                    deletable_counts,
-                    Reverse(initial_certified_epoch),
-                )
-                    .cmp(&(
-                        is_certified_other,
-                        end_epoch_other,
-                        deletable_counts_other,
-                        Reverse(initial_certified_epoch_other),
-                    ))
-            }
-        }
-    }
-}
-
 /// Contains the storage status of a sliver or metadata.
 #[derive(
     Debug, Deserialize, Serialize, PartialEq, Eq, Clone, Copy, Default, Hash, utoipa::ToSchema,
@@ -243,6 +289,126 @@ pub struct ServiceHealthInfo {
     pub latest_checkpoint_sequence_number: Option<u64>,
 }
 
+/// The state of the node's handover of shards during a committee transition.
+///
+/// Served by the admin API of the node.
+#[derive(Debug, Clone, Deserialize, Serialize, utoipa::ToSchema, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct EpochHandoverInfo {
+    /// The epoch to which the committee is transitioning or has last transitioned.
+    #[schema(value_type = u64)]
+    pub epoch: Epoch,
+    /// The phase of the handover.
+    pub phase: EpochHandoverPhase,
+    /// The shards handed over to other nodes in this transition.
+    ///
+    /// These shards are read only, but continue to be served by this node until the incoming
+    /// owners have confirmed that they are synced.
+    #[schema(value_type = Vec<u16>)]
+    pub outgoing_shards: Vec<ShardIndex>,
+    /// The shards received from other nodes in this transition.
+    #[schema(value_type = Vec<u16>)]
+    pub incoming_shards: Vec<ShardIndex>,
+}
+
+/// The phase of the node's handover of shards during a committee transition.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Deserialize, Serialize, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub enum EpochHandoverPhase {
+    /// The node is syncing the shards it received in the transition.
+    Syncing,
+    /// The node holds all of its shards and is waiting for the other members of the committee
+    /// to confirm that they are synced.
+    AwaitingConfirmation,
+    /// The committee confirmed the transition.
+    Confirmed,
+}
+
+/// An event about the data stored locally by a storage node.
+///
+/// These events are streamed by the node's event stream and are intended for monitoring and
+/// indexing; they are not part of the Walrus protocol.
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(
+    rename_all = "camelCase",
+    rename_all_fields = "camelCase",
+    tag = "type"
+)]
+pub enum NodeEvent {
+    /// The node stores the metadata and the slivers of all of its shards for the blob.
+    BlobStored {
+        /// The ID of the blob.
+        blob_id: BlobId,
+    },
+    /// The node observed the certification of the blob on chain.
+    BlobCertified {
+        /// The ID of the blob.
+        blob_id: BlobId,
+        /// The epoch in which the blob was certified.
+        epoch: Epoch,
+    },
+    /// The node started syncing a shard that it received in the transition to `epoch`.
+    ShardSyncStarted {
+        /// The shard being synced.
+        shard: ShardIndex,
+        /// The epoch to which the shard is synced.
+        epoch: Epoch,
+    },
+    /// The node synced a batch of slivers of a shard.
+    ShardSyncProgressed {
+        /// The shard being synced.
+        shard: ShardIndex,
+        /// The epoch to which the shard is synced.
+        epoch: Epoch,
+        /// The type of the synced slivers.
+        sliver_type: SliverType,
+        /// The number of slivers synced in the batch.
+        synced_slivers: usize,
+        /// The ID of the last blob whose sliver was synced.
+        last_synced_blob_id: BlobId,
+    },
+    /// The node finished syncing a shard.
+    ShardSyncCompleted {
+        /// The synced shard.
+        shard: ShardIndex,
+        /// The epoch to which the shard was synced.
+        epoch: Epoch,
+    },
+    /// The node stopped syncing a shard due to errors; the sync is retried after a restart.
+    ShardSyncFailed {
+        /// The shard that failed to sync.
+        shard: ShardIndex,
+        /// The epoch to which the shard was being synced.
+        epoch: Epoch,
+    },
+    /// The latest registration of the blob ended with the start of `epoch`.
+    BlobExpired {
+        /// The ID of the blob.
+        blob_id: BlobId,
+        /// The epoch at which the blob expired.
+        epoch: Epoch,
+    },
+    /// The node deleted the locally stored data of the blob.
+    BlobDataDeleted {
+        /// The ID of the blob.
+        blob_id: BlobId,
+        /// The reason for deleting the data.
+        reason: BlobDataDeletionReason,
+    },
+}
+
+/// The reason for which a storage node deleted the data of a blob.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum BlobDataDeletionReason {
+    /// The blob was marked as invalid on chain.
+    Invalid,
+    /// The blob is on the node's blocklist.
+    Blocked,
+    /// The blob expired and the node is configured to delete the data of expired blobs.
+    Expired,
+}
+
 /// The status of the shards for which the node is responsible.
 #[derive(Debug, Default, Clone, Deserialize, Serialize, utoipa::ToSchema)]
 #[serde(rename_all = "camelCase")]
@@ -301,6 +467,145 @@ pub struct ShardHealthInfo {
     pub status: ShardStatus,
 }
 
+/// The comparison of the shards in a node's storage with the shards assigned to it on chain.
+#[derive(Debug, Default, Clone, Deserialize, Serialize, utoipa::ToSchema, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct ShardOwnershipAudit {
+    /// The epoch of the committee against which the shards were compared.
+    #[schema(value_type = u64)]
+    pub epoch: Epoch,
+    /// The shards assigned to the node in the epoch.
+    #[schema(value_type = Vec<u16>)]
+    pub assigned: Vec<ShardIndex>,
+    /// The shards present in the node's storage, with their status.
+    pub present: Vec<ShardHealthInfo>,
+    /// The shards present in storage that are neither assigned to the node nor needed for an
+    /// ongoing epoch change, and can therefore be deleted.
+    #[schema(value_type = Vec<u16>)]
+    pub extraneous: Vec<ShardIndex>,
+    /// The assigned shards that are absent from storage or not yet fully synced.
+    #[schema(value_type = Vec<u16>)]
+    pub missing: Vec<ShardIndex>,
+}
+
+/// A page of the IDs of the blobs stored by a node for one of its shards.
+#[serde_as]
+#[derive(Debug, Default, Clone, Deserialize, Serialize, utoipa::ToSchema, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct StoredBlobsPage {
+    /// The IDs of the blobs in the page, in ascending order.
+    #[serde_as(as = "Vec<DisplayFromStr>")]
+    #[schema(value_type = Vec<String>)]
+    pub blob_ids: Vec<BlobId>,
+    /// The cursor from which to request the next page, or `None` if this is the last page.
+    #[serde_as(as = "Option<DisplayFromStr>")]
+    #[schema(value_type = Option<String>)]
+    pub next_cursor: Option<BlobId>,
+}
+
+/// The capabilities of a storage node, which allow clients to choose how to communicate with it.
+#[derive(Debug, Clone, Deserialize, Serialize, utoipa::ToSchema, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct NodeCapabilities {
+    /// The version of the API implemented by the node.
+    #[schema(value_type = u32)]
+    pub api_version: ApiVersion,
+    /// The features of the API supported by the node.
+    ///
+    /// Features unknown to this crate are omitted when deserializing.
+    #[serde(deserialize_with = "deserialize_known_features")]
+    #[schema(value_type = Vec<String>)]
+    pub features: Vec<ApiFeature>,
+    /// The maximum number of blobs whose recovery symbols can be requested in a single batch, or
+    /// `None` if unknown.
+    pub max_recovery_symbols_batch_size: Option<u32>,
+    /// The maximum number of blob IDs returned in a page of stored blobs, or `None` if unknown.
+    pub max_stored_blobs_page_size: Option<u32>,
+}
+
+impl NodeCapabilities {
+    /// Returns the capabilities of a node that implements the given version of the API, but does
+    /// not report its capabilities.
+    pub fn for_version(api_version: ApiVersion) -> Self {
+        Self {
+            api_version,
+            features: api_version.features(),
+            max_recovery_symbols_batch_size: None,
+            max_stored_blobs_page_size: None,
+        }
+    }
+
+    /// Returns true if the node supports the feature.
+    pub fn supports(&self, feature: ApiFeature) -> bool {
+        self.features.contains(&feature)
+    }
+}
+
+/// Name of the HTTP header in which storage nodes send the [`SliverChecksum`] of a sliver along
+/// with the sliver.
+///
+/// The checksum allows clients to detect slivers corrupted in transit before verifying them
+/// against the blob metadata; it does not authenticate the sliver.
+///
+/// [`SliverChecksum`]: walrus_core::encoding::SliverChecksum
+pub const SLIVER_CHECKSUM_HEADER: &str = "x-walrus-sliver-checksum";
+
+/// Name of the HTTP header with which clients attach an [`IdempotencyKey`] to write requests.
+pub const IDEMPOTENCY_KEY_HEADER: &str = "idempotency-key";
+
+/// A key identifying a write request across its retries.
+///
+/// A client attaches the same key to all attempts of a write, so that a node that has already
+/// processed the write, or is still processing it, does not process it again but returns the
+/// result of the first attempt. Keys consist of 1 to [`IdempotencyKey::MAX_LENGTH`] ASCII
+/// letters, digits, `-`, or `_`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct IdempotencyKey(String);
+
+impl IdempotencyKey {
+    /// The maximum length of a key.
+    pub const MAX_LENGTH: usize = 64;
+
+    /// Returns a new random key.
+    pub fn random() -> Self {
+        Self(format!("{:032x}", rand::random::<u128>()))
+    }
+
+    /// Returns the key as a string.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl std::fmt::Display for IdempotencyKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// The error returned when parsing an invalid [`IdempotencyKey`].
+#[derive(Debug, Clone, thiserror::Error)]
+#[error(
+    "an idempotency key must consist of 1 to {} ASCII letters, digits, '-', or '_'",
+    IdempotencyKey::MAX_LENGTH
+)]
+pub struct InvalidIdempotencyKey;
+
+impl std::str::FromStr for IdempotencyKey {
+    type Err = InvalidIdempotencyKey;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let is_valid = (1..=Self::MAX_LENGTH).contains(&s.len())
+            && s.bytes()
+                .all(|byte| byte.is_ascii_alphanumeric() || byte == b'-' || byte == b'_');
+        if is_valid {
+            Ok(Self(s.to_owned()))
+        } else {
+            Err(InvalidIdempotencyKey)
+        }
+    }
+}
+
 /// The current state of a shard on the storage node.
 #[derive(Debug, Copy, Clone, Eq, PartialEq, Deserialize, Serialize, utoipa::ToSchema)]
 #[serde(rename_all = "camelCase")]
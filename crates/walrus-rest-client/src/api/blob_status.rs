@@ -0,0 +1,160 @@
+// Copyright (c) Walrus Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! The status of a blob, which refers to the Sui events from which it resulted.
+//!
+//! Only available with the `sui-types` feature.
+
+use std::cmp::{Ordering, Reverse};
+
+use serde::{Deserialize, Serialize};
+use sui_types::event::EventID;
+use utoipa::openapi::Ref;
+use walrus_core::Epoch;
+
+use super::DeletableCounts;
+
+/// Contains the certification status of a blob.
+///
+/// If the a permanent blob exists, it also contains its end epoch and the ID of the Sui event
+/// from which the latest status (registered or certified) resulted.
+#[derive(
+    Debug, Deserialize, Serialize, PartialEq, Eq, Clone, Copy, Default, Hash, utoipa::ToSchema,
+)]
+#[serde(rename_all = "camelCase", rename_all_fields = "camelCase")]
+pub enum BlobStatus {
+    /// The blob does not exist (anymore) within Walrus.
+    #[default]
+    Nonexistent,
+    /// The blob ID has been marked as invalid.
+    Invalid {
+        /// The ID of the Sui event in which the blob was marked as invalid.
+        #[schema(schema_with = event_id_schema)]
+        event: EventID,
+    },
+    /// The blob exists within Walrus in a permanent state.
+    Permanent {
+        /// The latest epoch at which the blob expires (non-inclusive).
+        #[schema(value_type = u64)]
+        end_epoch: Epoch,
+        /// Whether the blob is certified (true) or only registered (false).
+        is_certified: bool,
+        /// The ID of the Sui event that caused the status with the given `end_epoch`.
+        #[schema(schema_with = event_id_schema)]
+        status_event: EventID,
+        /// Counts of deletable `Blob` objects.
+        #[schema(inline)]
+        deletable_counts: DeletableCounts,
+        /// If the blob is certified, contains the epoch where it was initially certified.
+        initial_certified_epoch: Option<Epoch>,
+    },
+    /// The blob exists within Walrus; but there is no related permanent object, so it may be
+    /// deleted at any time.
+    Deletable {
+        /// If the blob is certified, contains the epoch where it was initially certified.
+        // INV: certified_epoch.is_some() == count_deletable_certified > 0
+        initial_certified_epoch: Option<Epoch>,
+        /// Counts of deletable `Blob` objects.
+        #[schema(inline)]
+        deletable_counts: DeletableCounts,
+    },
+}
+
+fn event_id_schema() -> Ref {
+    Ref::new("#/components/schemas/EventID")
+}
+
+impl BlobStatus {
+    /// Returns the certification epoch for the blob, or `None` if the blob is not certified.
+    pub fn initial_certified_epoch(&self) -> Option<Epoch> {
+        match self {
+            BlobStatus::Permanent {
+                initial_certified_epoch,
+                ..
+            }
+            | BlobStatus::Deletable {
+                initial_certified_epoch,
+                ..
+            } => *initial_certified_epoch,
+            _ => None,
+        }
+    }
+
+    /// Returns true iff the blob is registered within Walrus.
+    pub fn is_registered(&self) -> bool {
+        matches!(self, Self::Deletable { .. } | Self::Permanent { .. })
+    }
+}
+
+impl PartialOrd for BlobStatus {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for BlobStatus {
+    fn cmp(&self, other: &Self) -> Ordering {
+        use BlobStatus::*;
+
+        match (self, other) {
+            (s, o) if s == o => Ordering::Equal,
+            // Nonexistent is the "smallest" status.
+            (Nonexistent, _) => Ordering::Less,
+            (_, Nonexistent) => Ordering::Greater,
+            // Invalid is the "largest" status.
+            (Invalid { .. }, Invalid { .. }) => Ordering::Equal,
+            (Invalid { .. }, _) => Ordering::Greater,
+            (_, Invalid { .. }) => Ordering::Less,
+            // Permanent is "larger" than Deletable.
+            (Permanent { .. }, Deletable { .. }) => Ordering::Greater,
+            (Deletable { .. }, Permanent { .. }) => Ordering::Less,
+            // For Deletable, first compare certified blobs, then all, then finally compare the
+            // initial certification epoch, preferring smaller values.
+            (
+                Deletable {
+                    initial_certified_epoch,
+                    deletable_counts,
+                },
+                Deletable {
+                    initial_certified_epoch: initial_certified_epoch_other,
+                    deletable_counts: deletable_counts_other,
+                },
+            ) => (deletable_counts, Reverse(initial_certified_epoch)).cmp(&(
+                deletable_counts_other,
+                Reverse(initial_certified_epoch_other),
+            )),
+            // For Permanent, compare status, end epochs, count of deletable blobs, and initial
+            // certification epoch (preferring smaller values), in this order.
+            (
+                Permanent {
+                    end_epoch,
+                    is_certified,
+                    deletable_counts,
+                    initial_certified_epoch,
+                    ..
+                },
+                Permanent {
+                    end_epoch: end_epoch_other,
+                    is_certified: is_certified_other,
+                    deletable_counts: deletable_counts_other,
+                    initial_certified_epoch: initial_certified_epoch_other,
+                    ..
+                },
+            ) => {
+                // Tuples are compared using lexicographic ordering.
+                (
+                    is_certified,
+                    end_epoch,
+                    deletable_counts,
+                    Reverse(initial_certified_epoch),
+                )
+                    .cmp(&(
+                        is_certified_other,
+                        end_epoch_other,
+                        deletable_counts_other,
+                        Reverse(initial_certified_epoch_other),
+                    ))
+            }
+        }
+    }
+}
@@ -367,6 +367,9 @@ status_codes![
     /// perhaps the entire file system is out of space.
     (ResourceExhausted, "RESOURCE_EXHAUSTED", HttpStatusCode::TOO_MANY_REQUESTS),
 
+    /// The request payload is larger than the server is willing to process.
+    (PayloadTooLarge, "PAYLOAD_TOO_LARGE", HttpStatusCode::PAYLOAD_TOO_LARGE),
+
     /// The service is currently unavailable.
     ///
     /// This is likely a temporary situation and may be corrected by retrying with a backoff.
@@ -3,16 +3,32 @@
 
 //! Client for interacting with the StorageNode API.
 
-use std::sync::Arc;
+use std::{
+    marker::PhantomData,
+    num::NonZeroUsize,
+    sync::{
+        atomic::{AtomicU32, Ordering},
+        Arc,
+    },
+};
 
+use bytes::Bytes;
 use fastcrypto::traits::{EncodeDecodeBase64, KeyPair};
-use futures::TryFutureExt as _;
+use futures::{stream, Stream, StreamExt as _, TryFutureExt as _, TryStreamExt as _};
 use middleware::{HttpClientMetrics, HttpMiddleware, UrlTemplate};
-use reqwest::{header::HeaderValue, Client as ReqwestClient, Method, Request, Response, Url};
+use reqwest::{
+    header::{HeaderMap, HeaderValue},
+    Client as ReqwestClient,
+    Method,
+    Request,
+    Response,
+    Url,
+};
 use serde::{de::DeserializeOwned, Serialize, Serializer};
-use sui_types::base_types::ObjectID;
 use tower::ServiceExt;
 use tracing::Level;
+#[cfg(not(target_arch = "wasm32"))]
+use walrus_core::NetworkPublicKey;
 use walrus_core::{
     encoding::{
         EncodingAxis,
@@ -21,8 +37,10 @@ use walrus_core::{
         Primary,
         RecoverySymbol,
         Secondary,
+        SliverChecksum,
         SliverData,
     },
+    ensure,
     inconsistency::InconsistencyProof,
     keys::ProtocolKeyPair,
     merkle::MerkleProof,
@@ -39,27 +57,56 @@ use walrus_core::{
     BlobId,
     Epoch,
     InconsistencyProof as InconsistencyProofEnum,
-    NetworkPublicKey,
     PublicKey,
     ShardIndex,
     Sliver,
     SliverIndex,
     SliverPairIndex,
     SliverType,
+    SuiObjectId,
     SymbolId,
 };
 
 use crate::{
-    api::{BlobStatus, ServiceHealthInfo, StoredOnNodeStatus},
-    error::{ClientBuildError, ListAndVerifyRecoverySymbolsError, NodeError},
-    node_response::NodeResponse,
+    api::{
+        ApiVersion,
+        BatchedRecoverySymbolsRequest,
+        IdempotencyKey,
+        NodeCapabilities,
+        ServiceHealthInfo,
+        StoredBlobsPage,
+        StoredOnNodeStatus,
+        API_VERSION_HEADER,
+        IDEMPOTENCY_KEY_HEADER,
+        SLIVER_CHECKSUM_HEADER,
+    },
+    error::{
+        ClientBuildError,
+        Kind,
+        ListAndVerifyRecoverySymbolsError,
+        NodeError,
+        RecoverySymbolCheckError,
+        SliverChecksumError,
+        StreamSliverError,
+    },
+    node_response::{is_content_type_octet_stream, NodeResponse},
 };
 
 mod builder;
 pub use builder::ClientBuilder;
 
+#[cfg(not(target_arch = "wasm32"))]
+pub use crate::tls::CertificatePin;
+
 mod middleware;
 
+mod observer;
+use observer::RequestObservers;
+pub use observer::{with_request_attempt, RequestInfo, RequestObserver, RequestOutcome};
+
+mod service;
+pub use service::{HttpLayer, HttpService};
+
 const METADATA_URL_TEMPLATE: &str = "/v1/blobs/:blob_id/metadata";
 const METADATA_STATUS_URL_TEMPLATE: &str = "/v1/blobs/:blob_id/metadata/status";
 const SLIVER_URL_TEMPLATE: &str = "/v1/blobs/:blob_id/slivers/:sliver_pair_index/:sliver_type";
@@ -72,10 +119,14 @@ const LEGACY_RECOVERY_URL_TEMPLATE: &str =
     "/v1/blobs/:blob_id/slivers/:sliver_pair_index/:sliver_type/:target_pair_index";
 const RECOVERY_SYMBOL_URL_TEMPLATE: &str = "/v1/blobs/:blob_id/recoverySymbols/:symbol_id";
 const LIST_RECOVERY_SYMBOLS_URL_TEMPLATE: &str = "/v1/blobs/:blob_id/recoverySymbols";
+const BATCH_RECOVERY_SYMBOLS_URL_TEMPLATE: &str = "/v1/recoverySymbols/batch";
 const INCONSISTENCY_PROOF_URL_TEMPLATE: &str = "/v1/blobs/:blob_id/inconsistencyProof/:sliver_type";
+#[cfg(feature = "sui-types")]
 const BLOB_STATUS_URL_TEMPLATE: &str = "/v1/blobs/:blob_id/status";
 const HEALTH_URL_TEMPLATE: &str = "/v1/health";
+const CAPABILITIES_URL_TEMPLATE: &str = "/v1/capabilities";
 const SYNC_SHARD_TEMPLATE: &str = "/v1/migrate/sync_shard";
+const STORED_BLOBS_URL_TEMPLATE: &str = "/v1/shards/:shard_index/blobs";
 
 #[derive(Debug, Clone)]
 struct UrlEndpoints(Url);
@@ -114,7 +165,7 @@ impl UrlEndpoints {
         match blob_persistence_type {
             BlobPersistenceType::Permanent => self.permanent_blob_confirmation(blob_id),
             BlobPersistenceType::Deletable { object_id } => {
-                self.deletable_blob_confirmation(blob_id, &object_id.into())
+                self.deletable_blob_confirmation(blob_id, object_id)
             }
         }
     }
@@ -129,7 +180,7 @@ impl UrlEndpoints {
     fn deletable_blob_confirmation(
         &self,
         blob_id: &BlobId,
-        object_id: &ObjectID,
+        object_id: &SuiObjectId,
     ) -> (Url, &'static str) {
         (
             self.blob_resource(blob_id, &format!("confirmation/deletable/{object_id}")),
@@ -137,6 +188,7 @@ impl UrlEndpoints {
         )
     }
 
+    #[cfg(feature = "sui-types")]
     fn blob_status(&self, blob_id: &BlobId) -> (Url, &'static str) {
         (
             self.blob_resource(blob_id, "status"),
@@ -210,6 +262,15 @@ impl UrlEndpoints {
         )
     }
 
+    fn batch_recovery_symbols(&self) -> (Url, &'static str) {
+        (
+            self.0
+                .join("/v1/recoverySymbols/batch")
+                .expect("this is a valid URL"),
+            BATCH_RECOVERY_SYMBOLS_URL_TEMPLATE,
+        )
+    }
+
     fn inconsistency_proof<A: EncodingAxis>(&self, blob_id: &BlobId) -> (Url, &'static str) {
         let sliver_type = SliverType::for_encoding::<A>();
         (
@@ -224,6 +285,15 @@ impl UrlEndpoints {
         (url, HEALTH_URL_TEMPLATE)
     }
 
+    fn capabilities(&self) -> (Url, &'static str) {
+        (
+            self.0
+                .join("/v1/capabilities")
+                .expect("this is a valid URL"),
+            CAPABILITIES_URL_TEMPLATE,
+        )
+    }
+
     fn sync_shard(&self) -> (Url, &'static str) {
         (
             self.0
@@ -232,6 +302,15 @@ impl UrlEndpoints {
             SYNC_SHARD_TEMPLATE,
         )
     }
+
+    fn stored_blobs(&self, shard_index: ShardIndex) -> (Url, &'static str) {
+        (
+            self.0
+                .join(&format!("/v1/shards/{shard_index}/blobs"))
+                .expect("this is a valid URL"),
+            STORED_BLOBS_URL_TEMPLATE,
+        )
+    }
 }
 
 /// Filter for [`Client::list_recovery_symbols()`] endpoint.
@@ -336,10 +415,53 @@ where
     serializer.collect_map(symbols.iter().map(|id| ("id", id)))
 }
 
+/// A recovery symbol returned by a storage node, along with the outcome of checking it against the
+/// request and the blob metadata.
+#[derive(Debug, Clone)]
+pub struct CheckedRecoverySymbol {
+    /// The symbol returned by the node.
+    pub symbol: GeneralRecoverySymbol,
+    /// `Ok(())` if the symbol was requested and is verified, the reason for rejecting it otherwise.
+    pub verification: Result<(), RecoverySymbolCheckError>,
+}
+
+impl CheckedRecoverySymbol {
+    /// Returns true if the symbol passed all checks.
+    pub fn is_verified(&self) -> bool {
+        self.verification.is_ok()
+    }
+}
+
+/// The serialized body of a request storing a sliver.
+///
+/// The sliver is serialized once into an exactly sized buffer, which is shared by all requests
+/// created from the body. Retries of a store therefore neither serialize nor copy the sliver
+/// again, and cloning the body is cheap.
+#[derive(Debug, Clone)]
+pub struct SliverBody<A> {
+    body: Bytes,
+    _sliver_type: PhantomData<fn() -> A>,
+}
+
+impl<A: EncodingAxis> SliverBody<A> {
+    /// Serializes the sliver into a request body.
+    pub fn new(sliver: &SliverData<A>) -> Self {
+        Self {
+            body: serialize_to_bytes(sliver),
+            _sliver_type: PhantomData,
+        }
+    }
+
+    /// Returns the serialized sliver.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.body
+    }
+}
+
 /// A client for communicating with a StorageNode.
 #[derive(Debug, Clone)]
 pub struct Client {
-    inner: HttpMiddleware<ReqwestClient>,
+    inner: HttpMiddleware<HttpService>,
     endpoints: UrlEndpoints,
 
     /// A clone of the client used to create requests via the reqwest::RequestBuilder.
@@ -347,6 +469,13 @@ pub struct Client {
     /// This is needed, because the reqwest builder wants the client for the ergonmics of being
     /// able to send the request directly from the builder.
     client_clone: ReqwestClient,
+
+    /// The API version advertised in the latest response of the node, or 0 if no response has
+    /// been received yet.
+    api_version: Arc<AtomicU32>,
+
+    /// The observers notified of every request sent to the node.
+    observers: RequestObservers,
 }
 
 impl Client {
@@ -363,6 +492,7 @@ impl Client {
     /// This method ensures that the storage node is authenticated: Only the storage node can
     /// establish the connection using the self-signed certificate corresponding to the provided
     /// identity and public key.
+    #[cfg(not(target_arch = "wasm32"))]
     pub fn for_storage_node(
         address: &str,
         public_key: &NetworkPublicKey,
@@ -372,9 +502,23 @@ impl Client {
             .build(address)
     }
 
-    /// Converts this to the inner client.
+    /// Returns the API version advertised by the storage node in its latest response.
+    ///
+    /// Returns `None` if no response has been received from the node yet. Nodes that do not
+    /// advertise a version are assumed to implement [`ApiVersion::BASELINE`].
+    pub fn api_version(&self) -> Option<ApiVersion> {
+        match self.api_version.load(Ordering::Relaxed) {
+            0 => None,
+            version => Some(ApiVersion(version)),
+        }
+    }
+
+    /// Converts this to the inner reqwest client.
+    ///
+    /// Requests sent directly with the returned client bypass any middleware or custom transport
+    /// configured on the [`ClientBuilder`].
     pub fn into_inner(self) -> ReqwestClient {
-        self.inner.into_inner()
+        self.client_clone
     }
 
     /// Requests the metadata for a blob ID from the node.
@@ -400,21 +544,31 @@ impl Client {
     }
 
     /// Get the metadata and verify it against the provided config.
+    ///
+    /// The metadata is verified while it is received, without buffering the response, and the
+    /// response is rejected as soon as it is found to be invalid.
     #[tracing::instrument(skip_all, fields(walrus.blob_id = %blob_id), err(level = Level::DEBUG))]
     pub async fn get_and_verify_metadata(
         &self,
         blob_id: &BlobId,
         encoding_config: &EncodingConfig,
     ) -> Result<VerifiedBlobMetadataWithId, NodeError> {
-        self.get_metadata(blob_id)
-            .await?
-            .verify(encoding_config)
-            .map_err(NodeError::other)
+        let (url, template) = self.endpoints.metadata(blob_id);
+        self.send_request(Request::new(Method::GET, url), template)
+            .and_then(|response| response.verified_metadata(blob_id, encoding_config))
+            .inspect_err(|error| tracing::trace!(?error))
+            .await
     }
 
     /// Requests the status of a blob ID from the node.
+    ///
+    /// Only available with the `sui-types` feature.
+    #[cfg(feature = "sui-types")]
     #[tracing::instrument(skip_all, fields(walrus.blob_id = %blob_id), err(level = Level::DEBUG))]
-    pub async fn get_blob_status(&self, blob_id: &BlobId) -> Result<BlobStatus, NodeError> {
+    pub async fn get_blob_status(
+        &self,
+        blob_id: &BlobId,
+    ) -> Result<crate::api::BlobStatus, NodeError> {
         let (url, template) = self.endpoints.blob_status(blob_id);
         self.send_and_parse_service_response(Request::new(Method::GET, url), template)
             .await
@@ -457,11 +611,15 @@ impl Client {
             .await?;
         let _ = confirmation
             .verify(public_key, epoch, *blob_id, blob_persistence_type)
-            .map_err(NodeError::other)?;
+            .map_err(NodeError::verification)?;
         Ok(confirmation)
     }
 
     /// Gets a primary or secondary sliver for the identified sliver pair.
+    ///
+    /// If the node sends the checksum of the sliver in the [`SLIVER_CHECKSUM_HEADER`], the
+    /// received sliver is checked against it. The sliver is not verified against the blob
+    /// metadata, see [`Self::get_and_verify_sliver`].
     #[tracing::instrument(
         skip_all,
         fields(
@@ -477,8 +635,20 @@ impl Client {
         sliver_pair_index: SliverPairIndex,
     ) -> Result<SliverData<A>, NodeError> {
         let (url, template) = self.endpoints.sliver::<A>(blob_id, sliver_pair_index);
-        self.send_and_parse_bcs_response(Request::new(Method::GET, url), template)
-            .await
+        let response = self
+            .send_request(Request::new(Method::GET, url), template)
+            .await?;
+        let expected_checksum = sliver_checksum(response.headers())?;
+        let sliver: SliverData<A> = response.bcs().await?;
+
+        if let Some(expected) = expected_checksum {
+            let actual = sliver.checksum();
+            ensure!(
+                actual == expected,
+                NodeError::verification(SliverChecksumError::Mismatch { expected, actual })
+            );
+        }
+        Ok(sliver)
     }
 
     /// Requests the status of a sliver from the node.
@@ -555,11 +725,120 @@ impl Client {
 
         sliver
             .verify(encoding_config, metadata.metadata())
-            .map_err(NodeError::other)?;
+            .map_err(NodeError::verification)?;
 
         Ok(sliver)
     }
 
+    /// Streams the identified recovery symbols, verifying them as they are received.
+    ///
+    /// The symbols are requested `symbols_per_request` at a time and each of them is verified
+    /// against the metadata as in [`Self::list_and_verify_recovery_symbols`], so that at most one
+    /// request's worth of symbols is buffered. The stream yields the verified symbols of each
+    /// request ordered by their IDs, and yields an error for requests for which no symbol could
+    /// be retrieved and verified.
+    ///
+    /// Requires the node to support
+    /// [`ApiFeature::ListRecoverySymbols`][crate::api::ApiFeature::ListRecoverySymbols].
+    pub fn stream_verified_recovery_symbols(
+        &self,
+        mut symbol_ids: Vec<SymbolId>,
+        metadata: Arc<VerifiedBlobMetadataWithId>,
+        encoding_config: Arc<EncodingConfig>,
+        target_index: SliverIndex,
+        target_type: SliverType,
+        symbols_per_request: NonZeroUsize,
+    ) -> impl Stream<Item = Result<GeneralRecoverySymbol, NodeError>> + Send + 'static {
+        symbol_ids.sort_unstable();
+        symbol_ids.dedup();
+        let client = self.clone();
+
+        stream::iter(chunked(symbol_ids, symbols_per_request))
+            .then(move |symbol_ids| {
+                let client = client.clone();
+                let metadata = metadata.clone();
+                let encoding_config = encoding_config.clone();
+                async move {
+                    let filter =
+                        RecoverySymbolsFilter::ids(symbol_ids).expect("chunks are non-empty");
+                    let mut symbols = client
+                        .list_and_verify_recovery_symbols(
+                            filter,
+                            metadata,
+                            encoding_config,
+                            target_index,
+                            target_type,
+                        )
+                        .await?;
+                    symbols.sort_by_key(GeneralRecoverySymbol::id);
+                    Ok::<_, NodeError>(stream::iter(symbols.into_iter().map(Ok)))
+                }
+            })
+            .try_flatten()
+    }
+
+    /// Streams the data of a primary or secondary sliver as it is received from the node.
+    ///
+    /// The sliver is requested with a single request, and the stream yields the data of the
+    /// sliver as it is stored by the node, in the chunks in which it is received, so that the
+    /// concatenation of its items is the sliver's data. Since the sliver is verified against a
+    /// Merkle root over its re-encoded symbols, it can only be verified once it is received
+    /// completely; the received bytes are therefore retained until the end of the response, at
+    /// which point the sliver is verified against the metadata and encoding config.
+    ///
+    /// If the sliver fails verification or the response is malformed, the stream yields an error
+    /// as its final item, and the data yielded before it must be discarded.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the provided encoding config is not applicable to the metadata, i.e., if
+    /// [`VerifiedBlobMetadataWithId::is_encoding_config_applicable`] returns false.
+    pub fn stream_verified_sliver<A: EncodingAxis>(
+        &self,
+        sliver_pair_index: SliverPairIndex,
+        metadata: Arc<VerifiedBlobMetadataWithId>,
+        encoding_config: Arc<EncodingConfig>,
+    ) -> impl Stream<Item = Result<Bytes, NodeError>> + Send + 'static {
+        assert!(
+            metadata.is_encoding_config_applicable(&encoding_config),
+            "encoding config is not applicable to the provided metadata and blob"
+        );
+        let client = self.clone();
+
+        let receiver = async move {
+            let (url, template) = client
+                .endpoints
+                .sliver::<A>(metadata.blob_id(), sliver_pair_index);
+            let response = client
+                .send_request(Request::new(Method::GET, url), template)
+                .await?;
+            ensure!(
+                is_content_type_octet_stream(response.headers()),
+                NodeError::from(Kind::InvalidContentType)
+            );
+            let expected_index = sliver_pair_index.to_sliver_index::<A>(encoding_config.n_shards());
+
+            Ok::<_, NodeError>(SliverReceiver::<A> {
+                response: Some(response),
+                expected_index,
+                metadata,
+                encoding_config,
+                received: vec![],
+                n_data_bytes_yielded: 0,
+                _sliver_type: PhantomData,
+            })
+        };
+
+        stream::once(receiver)
+            .map_ok(|receiver| {
+                stream::try_unfold(receiver, |mut receiver| async move {
+                    let data = receiver.next_data().await?;
+                    Ok(data.map(|data| (data, receiver)))
+                })
+            })
+            .try_flatten()
+    }
+
     /// Gets the recovery symbol for a primary or secondary sliver.
     ///
     /// The symbol is identified by the (A, sliver_pair_at_remote, intersecting_pair_index) tuple.
@@ -640,7 +919,7 @@ impl Client {
         target_index: SliverIndex,
         target_type: SliverType,
     ) -> Result<Vec<GeneralRecoverySymbol>, NodeError> {
-        let mut symbols = self
+        let symbols = self
             .list_recovery_symbols(metadata.blob_id(), &filter)
             .await?;
         tracing::trace!(
@@ -648,44 +927,199 @@ impl Client {
             "the server returned recovery symbols"
         );
 
-        tokio::task::spawn_blocking(move || {
-            let mut final_error =
-                NodeError::other(ListAndVerifyRecoverySymbolsError::EmptyResponse);
+        verify_recovery_symbols(
+            symbols,
+            filter,
+            metadata,
+            encoding_config,
+            target_index,
+            target_type,
+        )
+        .await
+    }
 
-            symbols.retain(|symbol| {
-                let _guard = tracing::info_span!(
-                    "list_and_verify_recovery_symbols__retain",
-                    walrus.symbol.id = %symbol.id()
-                )
-                .entered();
+    /// Gets the recovery symbols of multiple blobs with a single request.
+    ///
+    /// Returns a list of symbols for each of the requests, in the same order. The symbols are not
+    /// verified.
+    pub async fn list_recovery_symbols_batch(
+        &self,
+        requests: &[BatchedRecoverySymbolsRequest],
+    ) -> Result<Vec<Vec<GeneralRecoverySymbol>>, NodeError> {
+        let (url, template) = self.endpoints.batch_recovery_symbols();
+        let request = self.create_request_with_payload(Method::POST, url, &requests);
+        self.send_and_parse_bcs_response(request, template).await
+    }
 
-                if !filter.accepts(symbol) {
-                    tracing::warn!("server returned a symbol with an unrequested proof axis");
-                    return false;
-                }
+    /// Gets and verifies the recovery symbols of multiple blobs with a single request.
+    ///
+    /// Each request is accompanied by the metadata of its blob. Returns the verified symbols for
+    /// each of the requests, in the same order, or the error encountered when verifying them.
+    #[tracing::instrument(skip_all, fields(n_requests = requests.len()), err(level = Level::DEBUG))]
+    pub async fn list_and_verify_recovery_symbols_batch(
+        &self,
+        requests: Vec<(
+            BatchedRecoverySymbolsRequest,
+            Arc<VerifiedBlobMetadataWithId>,
+        )>,
+        encoding_config: Arc<EncodingConfig>,
+    ) -> Result<Vec<Result<Vec<GeneralRecoverySymbol>, NodeError>>, NodeError> {
+        let batched_requests: Vec<_> = requests
+            .iter()
+            .map(|(request, _)| request.clone())
+            .collect();
+        let responses = self.list_recovery_symbols_batch(&batched_requests).await?;
+        if responses.len() != requests.len() {
+            return Err(NodeError::other(
+                ListAndVerifyRecoverySymbolsError::BatchSizeMismatch {
+                    expected: requests.len(),
+                    actual: responses.len(),
+                },
+            ));
+        }
 
-                if let Err(error) = symbol.verify(
-                    metadata.metadata(),
-                    &encoding_config,
-                    target_index,
-                    target_type,
-                ) {
-                    tracing::warn!(?error, "recovery symbol verification failed");
-                    final_error = NodeError::other(error);
-                    return false;
+        let verified = requests
+            .into_iter()
+            .zip(responses)
+            .map(|((request, metadata), symbols)| {
+                let encoding_config = encoding_config.clone();
+                async move {
+                    let filter = request.filter().ok_or_else(|| {
+                        NodeError::other(ListAndVerifyRecoverySymbolsError::NoSymbolsRequested)
+                    })?;
+                    verify_recovery_symbols(
+                        symbols,
+                        filter,
+                        metadata,
+                        encoding_config,
+                        request.target_sliver,
+                        request.target_type,
+                    )
+                    .await
                 }
-
-                true
             });
+        Ok(futures::future::join_all(verified).await)
+    }
 
-            if symbols.is_empty() {
-                Err(final_error)
-            } else {
-                Ok(symbols)
-            }
-        })
+    /// Gets the recovery symbols of multiple blobs with a single request and checks each symbol.
+    ///
+    /// Unlike [`Self::list_and_verify_recovery_symbols_batch`], symbols failing verification are
+    /// not discarded: each of the returned symbols is accompanied by the outcome of its checks,
+    /// which allows identifying the symbols with which the node is inconsistent. Returns the
+    /// checked symbols for each of the requests, in the same order, or an error if the request
+    /// does not specify any symbols.
+    #[tracing::instrument(skip_all, fields(n_requests = requests.len()), err(level = Level::DEBUG))]
+    pub async fn list_and_check_recovery_symbols_batch(
+        &self,
+        requests: Vec<(
+            BatchedRecoverySymbolsRequest,
+            Arc<VerifiedBlobMetadataWithId>,
+        )>,
+        encoding_config: Arc<EncodingConfig>,
+    ) -> Result<Vec<Result<Vec<CheckedRecoverySymbol>, NodeError>>, NodeError> {
+        let batched_requests: Vec<_> = requests
+            .iter()
+            .map(|(request, _)| request.clone())
+            .collect();
+        let responses = self.list_recovery_symbols_batch(&batched_requests).await?;
+        if responses.len() != requests.len() {
+            return Err(NodeError::other(
+                ListAndVerifyRecoverySymbolsError::BatchSizeMismatch {
+                    expected: requests.len(),
+                    actual: responses.len(),
+                },
+            ));
+        }
+
+        let check_all = move || {
+            requests
+                .into_iter()
+                .zip(responses)
+                .map(|((request, metadata), symbols)| -> Result<_, NodeError> {
+                    let filter = request.filter().ok_or_else(|| {
+                        NodeError::other(ListAndVerifyRecoverySymbolsError::NoSymbolsRequested)
+                    })?;
+                    let verifications = check_recovery_symbols(
+                        &symbols,
+                        &filter,
+                        &metadata,
+                        &encoding_config,
+                        request.target_sliver,
+                        request.target_type,
+                    );
+                    Ok(symbols
+                        .into_iter()
+                        .zip(verifications)
+                        .map(|(symbol, verification)| CheckedRecoverySymbol {
+                            symbol,
+                            verification,
+                        })
+                        .collect::<Vec<_>>())
+                })
+                .collect::<Vec<_>>()
+        };
+
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            tokio::task::spawn_blocking(check_all).await.map_err(|_| {
+                NodeError::other(ListAndVerifyRecoverySymbolsError::BackgroundWorkerFailed)
+            })
+        }
+
+        #[cfg(target_arch = "wasm32")]
+        Ok(check_all())
+    }
+
+    /// Gets and verifies multiple recovery symbols, requesting each symbol individually.
+    ///
+    /// This is equivalent to [`Self::list_and_verify_recovery_symbols`] restricted to the
+    /// provided symbol IDs, but only uses endpoints that are supported by nodes which do not
+    /// support [`ApiFeature::ListRecoverySymbols`][crate::api::ApiFeature::ListRecoverySymbols].
+    #[tracing::instrument(
+        skip_all, fields(walrus.blob_id = %metadata.blob_id(),), err(level = Level::DEBUG)
+    )]
+    pub async fn get_and_verify_recovery_symbols(
+        &self,
+        symbol_ids: &[SymbolId],
+        filter: RecoverySymbolsFilter,
+        metadata: Arc<VerifiedBlobMetadataWithId>,
+        encoding_config: Arc<EncodingConfig>,
+        target_index: SliverIndex,
+        target_type: SliverType,
+    ) -> Result<Vec<GeneralRecoverySymbol>, NodeError> {
+        let results = futures::future::join_all(
+            symbol_ids
+                .iter()
+                .map(|symbol_id| self.get_recovery_symbol(metadata.blob_id(), *symbol_id)),
+        )
+        .await;
+
+        let mut final_error = None;
+        let symbols: Vec<_> = results
+            .into_iter()
+            .filter_map(|result| result.map_err(|error| final_error = Some(error)).ok())
+            .collect();
+        tracing::trace!(
+            n_symbols = symbols.len(),
+            n_requested = symbol_ids.len(),
+            "the server returned recovery symbols"
+        );
+
+        if symbols.is_empty() {
+            return Err(final_error.unwrap_or_else(|| {
+                NodeError::other(ListAndVerifyRecoverySymbolsError::EmptyResponse)
+            }));
+        }
+
+        verify_recovery_symbols(
+            symbols,
+            filter,
+            metadata,
+            encoding_config,
+            target_index,
+            target_type,
+        )
         .await
-        .map_err(|_| NodeError::other(ListAndVerifyRecoverySymbolsError::BackgroundWorkerFailed))?
     }
 
     /// Gets the recovery symbol for a primary or secondary sliver.
@@ -722,7 +1156,7 @@ impl Client {
                 encoding_config,
                 local_sliver_pair.to_sliver_index::<A>(encoding_config.n_shards()),
             )
-            .map_err(NodeError::other)?;
+            .map_err(NodeError::verification)?;
 
         Ok(symbol)
     }
@@ -734,9 +1168,34 @@ impl Client {
     pub async fn store_metadata(
         &self,
         metadata: &VerifiedBlobMetadataWithId,
+    ) -> Result<(), NodeError> {
+        self.store_metadata_inner(metadata, None).await
+    }
+
+    /// Stores the metadata on the node, attaching the idempotency key to the request.
+    ///
+    /// Retries of the same write should use the same key, so that the node does not process the
+    /// write again if an earlier attempt has reached it.
+    #[tracing::instrument(
+        skip_all, fields(walrus.blob_id = %metadata.blob_id()), err(level = Level::DEBUG)
+    )]
+    pub async fn store_metadata_with_idempotency_key(
+        &self,
+        metadata: &VerifiedBlobMetadataWithId,
+        idempotency_key: &IdempotencyKey,
+    ) -> Result<(), NodeError> {
+        self.store_metadata_inner(metadata, Some(idempotency_key))
+            .await
+    }
+
+    async fn store_metadata_inner(
+        &self,
+        metadata: &VerifiedBlobMetadataWithId,
+        idempotency_key: Option<&IdempotencyKey>,
     ) -> Result<(), NodeError> {
         let (url, template) = self.endpoints.metadata(metadata.blob_id());
-        let request = self.create_request_with_payload(Method::PUT, url, metadata.as_ref());
+        let mut request = self.create_request_with_payload(Method::PUT, url, metadata.as_ref());
+        set_idempotency_key(&mut request, idempotency_key);
         self.send_and_parse_service_response::<String>(request, template)
             .await?;
         Ok(())
@@ -757,10 +1216,73 @@ impl Client {
         blob_id: &BlobId,
         pair_index: SliverPairIndex,
         sliver: &SliverData<A>,
+    ) -> Result<(), NodeError> {
+        self.store_sliver_inner(blob_id, pair_index, &SliverBody::new(sliver), None)
+            .await
+    }
+
+    /// Stores a sliver on a node, attaching the idempotency key to the request.
+    ///
+    /// Retries of the same write should use the same key, so that the node does not process the
+    /// write again if an earlier attempt has reached it.
+    #[tracing::instrument(
+        skip_all,
+        fields(
+            walrus.blob_id = %blob_id,
+            walrus.sliver.pair_index = %pair_index,
+            walrus.sliver.type_ = %A::NAME,
+        ),
+        err(level = Level::DEBUG)
+    )]
+    pub async fn store_sliver_with_idempotency_key<A: EncodingAxis>(
+        &self,
+        blob_id: &BlobId,
+        pair_index: SliverPairIndex,
+        sliver: &SliverData<A>,
+        idempotency_key: &IdempotencyKey,
+    ) -> Result<(), NodeError> {
+        let body = SliverBody::new(sliver);
+        self.store_sliver_inner(blob_id, pair_index, &body, Some(idempotency_key))
+            .await
+    }
+
+    /// Stores an already serialized sliver on a node, attaching the idempotency key to the
+    /// request.
+    ///
+    /// In contrast to [`Self::store_sliver_with_idempotency_key`], the sliver is not serialized
+    /// again, so that retries of the write can share the same body.
+    #[tracing::instrument(
+        skip_all,
+        fields(
+            walrus.blob_id = %blob_id,
+            walrus.sliver.pair_index = %pair_index,
+            walrus.sliver.type_ = %A::NAME,
+        ),
+        err(level = Level::DEBUG)
+    )]
+    pub async fn store_sliver_body_with_idempotency_key<A: EncodingAxis>(
+        &self,
+        blob_id: &BlobId,
+        pair_index: SliverPairIndex,
+        body: &SliverBody<A>,
+        idempotency_key: &IdempotencyKey,
+    ) -> Result<(), NodeError> {
+        self.store_sliver_inner(blob_id, pair_index, body, Some(idempotency_key))
+            .await
+    }
+
+    async fn store_sliver_inner<A: EncodingAxis>(
+        &self,
+        blob_id: &BlobId,
+        pair_index: SliverPairIndex,
+        body: &SliverBody<A>,
+        idempotency_key: Option<&IdempotencyKey>,
     ) -> Result<(), NodeError> {
         tracing::trace!("starting to store sliver");
         let (url, template) = self.endpoints.sliver::<A>(blob_id, pair_index);
-        let request = self.create_request_with_payload(Method::PUT, url, &sliver);
+        let mut request = Request::new(Method::PUT, url);
+        *request.body_mut() = Some(body.body.clone().into());
+        set_idempotency_key(&mut request, idempotency_key);
         self.send_and_parse_service_response::<String>(request, template)
             .await?;
 
@@ -836,7 +1358,7 @@ impl Client {
             .await?;
         let _ = attestation
             .verify(public_key, epoch, blob_id)
-            .map_err(NodeError::other)?;
+            .map_err(NodeError::verification)?;
         Ok(attestation)
     }
 
@@ -851,6 +1373,53 @@ impl Client {
             .await
     }
 
+    /// Gets the capabilities of the storage node.
+    ///
+    /// Nodes that do not support
+    /// [`ApiFeature::Capabilities`][crate::api::ApiFeature::Capabilities] respond with a not-found
+    /// error, in which case the capabilities are derived from the API version advertised in the
+    /// response.
+    pub async fn get_capabilities(&self) -> Result<NodeCapabilities, NodeError> {
+        let (url, template) = self.endpoints.capabilities();
+        match self
+            .send_and_parse_service_response(Request::new(Method::GET, url), template)
+            .await
+        {
+            Err(error) if error.is_status_not_found() => Ok(NodeCapabilities::for_version(
+                self.api_version().unwrap_or(ApiVersion::BASELINE),
+            )),
+            result => result,
+        }
+    }
+
+    /// Lists the IDs of the blobs that the storage node stores for the shard.
+    ///
+    /// Returns a page of blob IDs in ascending order, starting after the blob ID `cursor` if
+    /// provided, or with the first stored blob otherwise. The next page can be requested with the
+    /// `next_cursor` of the returned page, which is `None` for the last page. The node limits the
+    /// size of the page to `limit`, if provided, and to its own maximum page size.
+    #[tracing::instrument(
+        skip_all, fields(walrus.shard_index = %shard_index), err(level = Level::DEBUG)
+    )]
+    pub async fn list_stored_blobs(
+        &self,
+        shard_index: ShardIndex,
+        cursor: Option<&BlobId>,
+        limit: Option<usize>,
+    ) -> Result<StoredBlobsPage, NodeError> {
+        let (url, template) = self.endpoints.stored_blobs(shard_index);
+
+        let request = self
+            .client_clone
+            .get(url)
+            .query(&[("cursor", cursor.map(BlobId::to_string))])
+            .query(&[("limit", limit)])
+            .build()
+            .expect("creating a URL from typed arguments should always succeed");
+        self.send_and_parse_service_response(request, template)
+            .await
+    }
+
     /// Syncs a shard from the storage node.
     #[tracing::instrument(
         skip_all,
@@ -899,18 +1468,35 @@ impl Client {
         request: Request,
         url_template: &'static str,
     ) -> Result<Response, NodeError> {
+        let observed_request = self.observers.start(&request, url_template);
         let output = self
             .inner
             .clone()
             .oneshot((request, UrlTemplate(url_template)))
             .await;
+        if let Some(observed_request) = observed_request {
+            observed_request.finish(output.as_ref().ok().map(Response::status));
+        }
 
         match output {
-            Ok(response) => response.response_error_for_status().await,
-            Err(err) => Err(NodeError::reqwest(err)),
+            Ok(response) => {
+                self.record_api_version(&response);
+                response.response_error_for_status().await
+            }
+            Err(err) => Err(NodeError::from_service_error(err)),
         }
     }
 
+    fn record_api_version(&self, response: &Response) {
+        let version = response
+            .headers()
+            .get(API_VERSION_HEADER)
+            .and_then(|value| value.to_str().ok()?.parse().ok())
+            .filter(|version| *version >= ApiVersion::BASELINE)
+            .unwrap_or(ApiVersion::BASELINE);
+        self.api_version.store(version.0, Ordering::Relaxed);
+    }
+
     async fn send_and_parse_bcs_response<T: DeserializeOwned>(
         &self,
         request: Request,
@@ -940,11 +1526,7 @@ impl Client {
         body: &T,
     ) -> Request {
         let mut request = Request::new(method, url);
-        *request.body_mut() = Some(
-            bcs::to_bytes(body)
-                .expect("type must be bcs encodable")
-                .into(),
-        );
+        *request.body_mut() = Some(serialize_to_bytes(body).into());
         request
     }
 
@@ -966,12 +1548,271 @@ impl Client {
     }
 }
 
+/// Serializes the value with BCS into a buffer of exactly its serialized size.
+///
+/// This avoids the repeated reallocation and copying of the buffer while serializing large values
+/// such as slivers, as well as the unused capacity of the resulting request body.
+fn serialize_to_bytes<T: Serialize + ?Sized>(value: &T) -> Bytes {
+    let size = bcs::serialized_size(value).expect("type must be bcs encodable");
+    let mut buffer = Vec::with_capacity(size);
+    bcs::serialize_into(&mut buffer, value).expect("type must be bcs encodable");
+    debug_assert_eq!(buffer.len(), buffer.capacity());
+    buffer.into()
+}
+
+/// Attaches the idempotency key, if any, to the request.
+fn set_idempotency_key(request: &mut Request, idempotency_key: Option<&IdempotencyKey>) {
+    if let Some(key) = idempotency_key {
+        let value =
+            HeaderValue::from_str(key.as_str()).expect("idempotency keys are valid header values");
+        request.headers_mut().insert(IDEMPOTENCY_KEY_HEADER, value);
+    }
+}
+
+/// Receives a BCS-encoded sliver in the body of a response, see
+/// [`Client::stream_verified_sliver`].
+struct SliverReceiver<A: EncodingAxis> {
+    /// The response, or `None` once its body has been read on WebAssembly.
+    response: Option<Response>,
+    expected_index: SliverIndex,
+    metadata: Arc<VerifiedBlobMetadataWithId>,
+    encoding_config: Arc<EncodingConfig>,
+    /// The bytes of the BCS-encoded sliver received so far.
+    received: Vec<u8>,
+    /// The number of bytes of the sliver's data that were already yielded.
+    n_data_bytes_yielded: usize,
+    _sliver_type: PhantomData<A>,
+}
+
+impl<A: EncodingAxis> SliverReceiver<A> {
+    /// Receives the body of the response until it contains more of the sliver's data, and
+    /// returns that data.
+    ///
+    /// Returns `None` once the body is received completely and the sliver is verified.
+    async fn next_data(&mut self) -> Result<Option<Bytes>, NodeError> {
+        while let Some(chunk) = self.next_chunk().await? {
+            self.received.extend_from_slice(&chunk);
+            if let Some(data) = self.unyielded_data() {
+                return Ok(Some(data));
+            }
+        }
+        self.verify()?;
+        Ok(None)
+    }
+
+    async fn next_chunk(&mut self) -> Result<Option<Bytes>, NodeError> {
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            let Some(response) = self.response.as_mut() else {
+                return Ok(None);
+            };
+            response.chunk().await.map_err(NodeError::reqwest)
+        }
+        // The body of a response cannot be read in chunks on WebAssembly.
+        #[cfg(target_arch = "wasm32")]
+        match self.response.take() {
+            Some(response) => Ok(Some(response.bytes().await.map_err(NodeError::reqwest)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Returns the bytes of the sliver's data received since the data was last yielded, if any.
+    fn unyielded_data(&mut self) -> Option<Bytes> {
+        // The data is preceded by its ULEB128-encoded length.
+        let (length_prefix_size, data_length) = decode_uleb128_length(&self.received)?;
+        let start = length_prefix_size + self.n_data_bytes_yielded;
+        let end = self.received.len().min(length_prefix_size + data_length);
+        if start >= end {
+            return None;
+        }
+        self.n_data_bytes_yielded = end - length_prefix_size;
+        Some(Bytes::copy_from_slice(&self.received[start..end]))
+    }
+
+    fn verify(&self) -> Result<(), NodeError> {
+        let sliver: SliverData<A> = bcs::from_bytes(&self.received).map_err(Kind::Bcs)?;
+        ensure!(
+            sliver.index == self.expected_index,
+            NodeError::verification(StreamSliverError::IndexMismatch {
+                expected: self.expected_index,
+                actual: sliver.index,
+            })
+        );
+        sliver
+            .verify(&self.encoding_config, self.metadata.metadata())
+            .map_err(NodeError::verification)
+    }
+}
+
+/// Returns the checksum that the node sent in the [`SLIVER_CHECKSUM_HEADER`] of a response, if
+/// any.
+fn sliver_checksum(headers: &HeaderMap) -> Result<Option<SliverChecksum>, NodeError> {
+    headers
+        .get(SLIVER_CHECKSUM_HEADER)
+        .map(|value| {
+            value
+                .to_str()
+                .ok()
+                .and_then(|value| value.parse().ok())
+                .ok_or_else(|| NodeError::verification(SliverChecksumError::InvalidHeader))
+        })
+        .transpose()
+}
+
+/// Decodes the ULEB128-encoded length at the start of `bytes`.
+///
+/// Returns the size of the encoding and the decoded length, or `None` if `bytes` does not start
+/// with a complete encoding of a `u32`.
+fn decode_uleb128_length(bytes: &[u8]) -> Option<(usize, usize)> {
+    const MAX_ULEB128_LENGTH: usize = 5;
+
+    let mut length = 0usize;
+    for (index, byte) in bytes.iter().take(MAX_ULEB128_LENGTH).enumerate() {
+        length |= usize::from(byte & 0x7f) << (7 * index);
+        if byte & 0x80 == 0 {
+            return Some((index + 1, length));
+        }
+    }
+    None
+}
+
+/// Splits the symbol IDs into chunks of at most `chunk_size` IDs.
+fn chunked(symbol_ids: Vec<SymbolId>, chunk_size: NonZeroUsize) -> Vec<Vec<SymbolId>> {
+    symbol_ids
+        .chunks(chunk_size.get())
+        .map(<[SymbolId]>::to_vec)
+        .collect()
+}
+
+/// Verifies the recovery symbols on a blocking thread, discarding those that are not accepted by
+/// the filter or that fail verification.
+///
+/// On `wasm32`, where there are no blocking threads, the symbols are verified in place.
+///
+/// Returns an error if none of the symbols are valid.
+async fn verify_recovery_symbols(
+    symbols: Vec<GeneralRecoverySymbol>,
+    filter: RecoverySymbolsFilter,
+    metadata: Arc<VerifiedBlobMetadataWithId>,
+    encoding_config: Arc<EncodingConfig>,
+    target_index: SliverIndex,
+    target_type: SliverType,
+) -> Result<Vec<GeneralRecoverySymbol>, NodeError> {
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        tokio::task::spawn_blocking(move || {
+            retain_verified_recovery_symbols(
+                symbols,
+                &filter,
+                &metadata,
+                &encoding_config,
+                target_index,
+                target_type,
+            )
+        })
+        .await
+        .map_err(|_| NodeError::other(ListAndVerifyRecoverySymbolsError::BackgroundWorkerFailed))?
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    retain_verified_recovery_symbols(
+        symbols,
+        &filter,
+        &metadata,
+        &encoding_config,
+        target_index,
+        target_type,
+    )
+}
+
+fn retain_verified_recovery_symbols(
+    mut symbols: Vec<GeneralRecoverySymbol>,
+    filter: &RecoverySymbolsFilter,
+    metadata: &VerifiedBlobMetadataWithId,
+    encoding_config: &EncodingConfig,
+    target_index: SliverIndex,
+    target_type: SliverType,
+) -> Result<Vec<GeneralRecoverySymbol>, NodeError> {
+    let mut final_error = NodeError::other(ListAndVerifyRecoverySymbolsError::EmptyResponse);
+    let mut verifications = check_recovery_symbols(
+        &symbols,
+        filter,
+        metadata,
+        encoding_config,
+        target_index,
+        target_type,
+    )
+    .into_iter();
+
+    symbols.retain(|symbol| {
+        let _guard = tracing::info_span!(
+            "list_and_verify_recovery_symbols__retain",
+            walrus.symbol.id = %symbol.id()
+        )
+        .entered();
+
+        match verifications
+            .next()
+            .expect("there is a verification result for each symbol")
+        {
+            Ok(()) => true,
+            Err(RecoverySymbolCheckError::NotRequested) => {
+                tracing::warn!("server returned a symbol with an unrequested proof axis");
+                false
+            }
+            Err(RecoverySymbolCheckError::Verification(error)) => {
+                tracing::warn!(?error, "recovery symbol verification failed");
+                final_error = NodeError::verification(error);
+                false
+            }
+        }
+    });
+
+    if symbols.is_empty() {
+        Err(final_error)
+    } else {
+        Ok(symbols)
+    }
+}
+
+/// Checks that the symbols are accepted by the filter and verifies them against the metadata.
+///
+/// The symbols are verified as a batch, sharing the verification of the Merkle proofs against the
+/// same sliver hash. Returns the results of the checks in the order of the symbols.
+fn check_recovery_symbols(
+    symbols: &[GeneralRecoverySymbol],
+    filter: &RecoverySymbolsFilter,
+    metadata: &VerifiedBlobMetadataWithId,
+    encoding_config: &EncodingConfig,
+    target_index: SliverIndex,
+    target_type: SliverType,
+) -> Vec<Result<(), RecoverySymbolCheckError>> {
+    let verifications = GeneralRecoverySymbol::verify_batch(
+        symbols,
+        metadata.metadata(),
+        encoding_config,
+        target_index,
+        target_type,
+    );
+    symbols
+        .iter()
+        .zip(verifications)
+        .map(|(symbol, verification)| {
+            if !filter.accepts(symbol) {
+                return Err(RecoverySymbolCheckError::NotRequested);
+            }
+            verification.map_err(RecoverySymbolCheckError::Verification)
+        })
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
-    use walrus_core::{encoding::Primary, test_utils, SuiObjectId};
+    use walrus_core::{encoding::Primary, test_utils};
     use walrus_test_utils::{param_test, Result as TestResult};
 
     use super::*;
+    use crate::error::NodeErrorKind;
 
     const BLOB_ID: BlobId = test_utils::blob_id_from_u64(99);
 
@@ -1037,6 +1878,33 @@ mod tests {
         assert_eq!(url.to_string(), "https://node.com/v1/migrate/sync_shard");
     }
 
+    #[test]
+    fn sliver_body_is_the_exactly_sized_bcs_encoding_of_the_sliver() {
+        let sliver = test_utils::primary_sliver();
+        let body = SliverBody::new(&sliver);
+
+        assert_eq!(body.as_bytes(), bcs::to_bytes(&sliver).unwrap());
+        assert_eq!(body.clone().as_bytes().as_ptr(), body.as_bytes().as_ptr());
+    }
+
+    #[test]
+    fn decodes_the_length_prefix_of_the_sliver_data() {
+        let sliver = test_utils::primary_sliver();
+        let encoded = bcs::to_bytes(&sliver).unwrap();
+        let data_length = sliver.symbols.data().len();
+        let (prefix_size, decoded_length) =
+            decode_uleb128_length(&encoded).expect("the prefix is complete");
+
+        assert_eq!(decoded_length, data_length);
+        assert_eq!(
+            &encoded[prefix_size..prefix_size + data_length],
+            sliver.symbols.data()
+        );
+        assert_eq!(decode_uleb128_length(&[0x80, 0x01]), Some((2, 128)));
+        assert_eq!(decode_uleb128_length(&[0x80]), None);
+        assert_eq!(decode_uleb128_length(&[]), None);
+    }
+
     param_test! {
         recovery_symbols_filter_to_query -> TestResult: [
             id_single: (
@@ -1080,4 +1948,89 @@ mod tests {
         );
         Ok(())
     }
+
+    #[tokio::test]
+    async fn requests_are_sent_through_layers_and_transport() {
+        let n_requests = Arc::new(AtomicU32::new(0));
+        let counter = n_requests.clone();
+        let layer = HttpLayer::new(tower::layer::layer_fn(move |service: HttpService| {
+            let counter = counter.clone();
+            service.map_request(move |request: Request| {
+                counter.fetch_add(1, Ordering::Relaxed);
+                request
+            })
+        }));
+        let transport = HttpService::new(tower::service_fn(|_: Request| async {
+            Err::<Response, _>(tower::BoxError::from("node is unreachable"))
+        }));
+
+        let client = Client::builder()
+            .authenticate_with_public_key(test_utils::network_key_pair().public().clone())
+            .tls_built_in_root_certs(false)
+            .layer(layer)
+            .transport(transport)
+            .build("node.com")
+            .expect("client can be built with a pinned key");
+        let error = client
+            .get_metadata(&BLOB_ID)
+            .await
+            .expect_err("the transport always fails");
+
+        assert!(!error.is_reqwest());
+        assert_eq!(error.to_string(), "node is unreachable");
+        assert_eq!(n_requests.load(Ordering::Relaxed), 1);
+    }
+
+    /// Returns a client whose requests are answered with the sliver and the checksum header.
+    fn client_serving_sliver(sliver: &SliverData<Primary>, checksum: Option<&str>) -> Client {
+        let body = bcs::to_bytes(sliver).expect("slivers can be BCS encoded");
+        let checksum = checksum.map(str::to_owned);
+        let transport = HttpService::new(tower::service_fn(move |_: Request| {
+            let mut response = http::Response::builder()
+                .header(
+                    reqwest::header::CONTENT_TYPE,
+                    mime::APPLICATION_OCTET_STREAM.as_ref(),
+                )
+                .body(body.clone())
+                .expect("the response is valid");
+            if let Some(checksum) = &checksum {
+                response.headers_mut().insert(
+                    SLIVER_CHECKSUM_HEADER,
+                    HeaderValue::from_str(checksum).expect("the checksum is a valid value"),
+                );
+            }
+            async move { Ok::<_, tower::BoxError>(Response::from(response)) }
+        }));
+
+        Client::builder()
+            .authenticate_with_public_key(test_utils::network_key_pair().public().clone())
+            .tls_built_in_root_certs(false)
+            .transport(transport)
+            .build("node.com")
+            .expect("client can be built with a pinned key")
+    }
+
+    #[tokio::test]
+    async fn checks_sliver_against_checksum_header() -> TestResult {
+        let sliver = test_utils::primary_sliver();
+        let checksum = sliver.checksum().to_string();
+
+        for header in [None, Some(checksum.as_str())] {
+            let received = client_serving_sliver(&sliver, header)
+                .get_sliver::<Primary>(&BLOB_ID, SliverPairIndex(0))
+                .await?;
+            assert_eq!(received, sliver);
+        }
+
+        let mut corrupted = sliver.clone();
+        corrupted.symbols.data_mut()[0] ^= 1;
+        for header in [checksum.as_str(), "not a checksum"] {
+            let error = client_serving_sliver(&corrupted, Some(header))
+                .get_sliver::<Primary>(&BLOB_ID, SliverPairIndex(0))
+                .await
+                .expect_err("the sliver does not match the checksum header");
+            assert_eq!(error.error_kind(), NodeErrorKind::VerificationFailed);
+        }
+        Ok(())
+    }
 }
@@ -1,31 +1,54 @@
 // Copyright (c) Walrus Foundation
 // SPDX-License-Identifier: Apache-2.0
-use std::{net::SocketAddr, sync::Arc, time::Duration};
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::Duration;
+use std::{net::SocketAddr, sync::Arc};
 
-use reqwest::{ClientBuilder as ReqwestClientBuilder, Url};
+use reqwest::{Client as ReqwestClient, ClientBuilder as ReqwestClientBuilder, Url};
+#[cfg(not(target_arch = "wasm32"))]
 use rustls::pki_types::CertificateDer;
+#[cfg(not(target_arch = "wasm32"))]
 use rustls_native_certs::CertificateResult;
+#[cfg(not(target_arch = "wasm32"))]
 use walrus_core::NetworkPublicKey;
 use walrus_utils::metrics::Registry;
 
-use super::{HttpClientMetrics, HttpMiddleware};
+use super::{
+    observer::{RequestObserver, RequestObservers},
+    service::{self, HttpLayer, HttpService},
+    HttpClientMetrics,
+    HttpMiddleware,
+};
+#[cfg(not(target_arch = "wasm32"))]
+use crate::tls::{CertificatePin, TlsCertificateVerifier};
 use crate::{
     client::{Client, UrlEndpoints},
     error::{BuildErrorKind, ClientBuildError},
-    tls::TlsCertificateVerifier,
 };
 
 /// A builder that can be used to construct a [`Client`].
 ///
 /// Can be created with [`Client::builder()`].
+///
+/// On `wasm32`, TLS and connection management are handled by the browser, so the options
+/// configuring them are not available.
 #[derive(Debug, Default)]
 pub struct ClientBuilder {
     inner: ReqwestClientBuilder,
+    #[cfg(not(target_arch = "wasm32"))]
     server_public_key: Option<NetworkPublicKey>,
+    #[cfg(not(target_arch = "wasm32"))]
     roots: Vec<CertificateDer<'static>>,
+    #[cfg(not(target_arch = "wasm32"))]
     no_built_in_root_certs: bool,
+    #[cfg(not(target_arch = "wasm32"))]
+    certificate_pins: Vec<CertificatePin>,
+    #[cfg(not(target_arch = "wasm32"))]
     connect_timeout: Option<Duration>,
     registry: Option<Registry>,
+    layers: Vec<HttpLayer>,
+    transport: Option<HttpService>,
+    observers: RequestObservers,
 }
 
 impl ClientBuilder {
@@ -39,6 +62,7 @@ impl ClientBuilder {
     /// The default of 5 seconds should allow for around 2-3 SYN attempts before failing.
     ///
     /// See RFC6298 for more information.
+    #[cfg(not(target_arch = "wasm32"))]
     const DEFAULT_CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
 
     /// Creates a new builder to construct a [`Client`].
@@ -59,6 +83,7 @@ impl ClientBuilder {
         }
     }
 
+    #[cfg(not(target_arch = "wasm32"))]
     /// Authenticate the server with the provided public key instead of the Web PKI.
     ///
     /// By default, to authenticate the connection to the storage node, the client verifies that the
@@ -71,6 +96,7 @@ impl ClientBuilder {
         self
     }
 
+    #[cfg(not(target_arch = "wasm32"))]
     /// Clears proxy settings in the client, and disables fetching proxy settings from the OS.
     ///
     /// On some systems, this can speed up the construction of the client.
@@ -79,6 +105,7 @@ impl ClientBuilder {
         self
     }
 
+    #[cfg(not(target_arch = "wasm32"))]
     /// Add a custom DER-encoded root certificate.
     ///
     /// It is the responsibility of the caller to check the certificate for validity.
@@ -88,6 +115,7 @@ impl ClientBuilder {
         self
     }
 
+    #[cfg(not(target_arch = "wasm32"))]
     /// Add a custom DER-encoded root certificate.
     ///
     /// It is the responsibility of the caller to check the certificate for validity.
@@ -96,6 +124,26 @@ impl ClientBuilder {
         self
     }
 
+    #[cfg(not(target_arch = "wasm32"))]
+    /// Require the certificate presented by the server to match the pin.
+    ///
+    /// If several pins are added, the certificate must match at least one of them. Pins are
+    /// checked in addition to, not instead of, the other verification of the certificate.
+    pub fn pin_certificate(mut self, pin: CertificatePin) -> Self {
+        self.certificate_pins.push(pin);
+        self
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    /// Require the certificate presented by the server to match at least one of the pins.
+    ///
+    /// See [`pin_certificate()`][Self::pin_certificate].
+    pub fn pin_certificates(mut self, pins: &[CertificatePin]) -> Self {
+        self.certificate_pins.extend(pins.iter().cloned());
+        self
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
     /// Controls the use of built-in/preloaded certificates during certificate validation.
     ///
     /// Defaults to true – built-in system certs will be used.
@@ -104,6 +152,7 @@ impl ClientBuilder {
         self
     }
 
+    #[cfg(not(target_arch = "wasm32"))]
     /// Set a timeout for only the connect phase of a Client.
     ///
     /// The default is 5 seconds.
@@ -118,6 +167,32 @@ impl ClientBuilder {
         self
     }
 
+    /// Wraps the service sending the HTTP requests in the provided layer.
+    ///
+    /// Layers are applied in the order in which they are added, so the layer added last is the
+    /// outermost one. All layers are below the tracing and metrics of the client itself.
+    pub fn layer(mut self, layer: HttpLayer) -> Self {
+        self.layers.push(layer);
+        self
+    }
+
+    /// Notifies the observer of every request sent by the client and of its outcome.
+    ///
+    /// Several observers can be added; they are notified in the order in which they were added.
+    pub fn observer(mut self, observer: Arc<dyn RequestObserver>) -> Self {
+        self.observers.push(observer);
+        self
+    }
+
+    /// Sends the HTTP requests over the provided service instead of the reqwest client.
+    ///
+    /// The TLS and connection options of the builder only apply to the reqwest client, and are
+    /// therefore ignored by a custom transport.
+    pub fn transport(mut self, transport: HttpService) -> Self {
+        self.transport = Some(transport);
+        self
+    }
+
     /// Convenience function to build the client where the server is identified by a [`SocketAddr`].
     ///
     /// Equivalent `self.build(&remote.to_string())`
@@ -131,11 +206,6 @@ impl ClientBuilder {
     /// Rustls TLS backend cannot be initialized, or the resolver cannot load the system
     /// configuration.
     pub fn build(mut self, address: &str) -> Result<Client, ClientBuildError> {
-        #[cfg(msim)]
-        {
-            self = self.no_proxy();
-        }
-
         let url = Url::parse(&format!("https://{address}"))
             .map_err(|_| BuildErrorKind::InvalidHostOrPort)?;
         // We extract the host from the URL, since the provided host string may have details like a
@@ -145,6 +215,32 @@ impl ClientBuilder {
             .ok_or(BuildErrorKind::InvalidHostOrPort)?
             .to_string();
         let endpoints = UrlEndpoints(url);
+        let registry = self.registry.take().unwrap_or_default();
+        let layers = std::mem::take(&mut self.layers);
+        let transport = self.transport.take();
+        let observers = std::mem::take(&mut self.observers);
+
+        let reqwest_client = self.build_reqwest_client(host)?;
+        let service = layers.iter().fold(
+            transport.unwrap_or_else(|| service::reqwest_service(reqwest_client.clone())),
+            |service, layer| layer.apply(service),
+        );
+
+        Ok(Client {
+            client_clone: reqwest_client,
+            inner: HttpMiddleware::new(service, HttpClientMetrics::new(&registry)),
+            endpoints,
+            api_version: Default::default(),
+            observers,
+        })
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn build_reqwest_client(mut self, host: String) -> Result<ReqwestClient, ClientBuildError> {
+        #[cfg(msim)]
+        {
+            self = self.no_proxy();
+        }
 
         if !self.no_built_in_root_certs {
             let CertificateResult { certs, errors, .. } = rustls_native_certs::load_native_certs();
@@ -166,15 +262,15 @@ impl ClientBuilder {
                 .map_err(BuildErrorKind::Tls)?
         } else {
             TlsCertificateVerifier::new(self.roots).map_err(BuildErrorKind::Tls)?
-        };
+        }
+        .with_pins(self.certificate_pins);
 
         let rustls_config = rustls::ClientConfig::builder()
             .dangerous()
             .with_custom_certificate_verifier(Arc::new(verifier))
             .with_no_client_auth();
 
-        let inner = self
-            .inner
+        self.inner
             .https_only(true)
             .http2_prior_knowledge()
             .http2_adaptive_window(true)
@@ -184,15 +280,11 @@ impl ClientBuilder {
                     .unwrap_or(Self::DEFAULT_CONNECT_TIMEOUT),
             )
             .build()
-            .map_err(ClientBuildError::reqwest)?;
+            .map_err(ClientBuildError::reqwest)
+    }
 
-        Ok(Client {
-            client_clone: inner.clone(),
-            inner: HttpMiddleware::new(
-                inner,
-                HttpClientMetrics::new(&self.registry.unwrap_or_default()),
-            ),
-            endpoints,
-        })
+    #[cfg(target_arch = "wasm32")]
+    fn build_reqwest_client(self, _host: String) -> Result<ReqwestClient, ClientBuildError> {
+        self.inner.build().map_err(ClientBuildError::reqwest)
     }
 }
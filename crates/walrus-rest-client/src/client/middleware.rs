@@ -16,7 +16,6 @@ use opentelemetry::propagation::Injector;
 use prometheus::{HistogramVec, IntGauge, IntGaugeVec};
 use reqwest::{
     header::{HeaderMap, HeaderName, HeaderValue},
-    Error,
     Method,
     Request,
     Response,
@@ -24,14 +23,17 @@ use reqwest::{
     Url,
     Version,
 };
+#[cfg(not(target_arch = "wasm32"))]
 use tokio::time::Instant;
-use tower::Service;
+use tower::{BoxError, Service};
 use tracing::{field, instrument::Instrumented, Instrument as _, Span};
 use tracing_opentelemetry::OpenTelemetrySpanExt as _;
 use walrus_utils::{
     http::{http_body, BodyVisitor, VisitBody},
     metrics::{self as metric_utils, OwnedGaugeGuard},
 };
+#[cfg(target_arch = "wasm32")]
+use web_time::Instant;
 
 use self::helpers::ErrorType;
 
@@ -175,14 +177,22 @@ impl HttpLabels {
     ///
     /// A value of `None` indicates that the request was aborted, and the added
     /// label values reflect that.
-    fn add_response_labels(&mut self, maybe_output: Option<Result<&Response, &reqwest::Error>>) {
+    fn add_response_labels(&mut self, maybe_output: Option<Result<&Response, &BoxError>>) {
+        // Errors that do not originate from reqwest are returned by custom middleware or
+        // transports.
+        let maybe_output = maybe_output
+            .map(|output| output.map_err(|error| error.downcast_ref::<reqwest::Error>()));
         self.http_response_status_code = maybe_output.and_then(|output| {
-            output.map_or_else(Error::status, |response| Some(response.status()))
+            output.map_or_else(
+                |error| error.and_then(reqwest::Error::status),
+                |response| Some(response.status()),
+            )
         });
         self.error_type =
             match maybe_output.map(|result| result.map(Response::error_for_status_ref)) {
                 Some(Ok(Ok(_))) => None,
-                Some(Ok(Err(ref err))) | Some(Err(&ref err)) => {
+                Some(Err(None)) => Some(ErrorType::Other("NodeError".into())),
+                Some(Ok(Err(ref err))) | Some(Err(Some(&ref err))) => {
                     if err.is_connect() {
                         Some(ErrorType::Connect)
                     } else if err.is_timeout() {
@@ -263,10 +273,6 @@ impl<S> HttpMiddleware<S> {
         Self { inner, metrics }
     }
 
-    pub(crate) fn into_inner(self) -> S {
-        self.inner
-    }
-
     fn propagate_trace(request: &mut Request, http_span: &Span) {
         // We use the global propagator as in the examples, since this allows a client using the
         // library to completely disable propagation for contextual information.
@@ -281,7 +287,7 @@ impl<S> HttpMiddleware<S> {
 
 impl<S> Service<RequestWithUrlTemplate> for HttpMiddleware<S>
 where
-    S: Service<Request, Error = Error, Response = Response>,
+    S: Service<Request, Error = BoxError, Response = Response>,
 {
     type Response = S::Response;
     type Error = S::Error;
@@ -311,7 +317,7 @@ pub(crate) struct HttpMiddlewareFuture<Fut> {
 
 impl<Fut> FusedFuture for HttpMiddlewareFuture<Fut>
 where
-    Fut: Future<Output = Result<Response, Error>>,
+    Fut: Future<Output = Result<Response, BoxError>>,
 {
     fn is_terminated(&self) -> bool {
         self.monitor.is_none()
@@ -329,7 +335,7 @@ impl<Fut> HttpMiddlewareFuture<Fut> {
 
 impl<Fut> Future for HttpMiddlewareFuture<Fut>
 where
-    Fut: Future<Output = Result<Response, Error>>,
+    Fut: Future<Output = Result<Response, BoxError>>,
 {
     type Output = Fut::Output;
 
@@ -417,14 +423,17 @@ impl RequestMonitor {
 
     /// Record metrics and tracing fields for the request on receiving a response,
     /// returning a monitor for the response portion of the request.
-    pub fn into_response_monitor(mut self, output: Result<&Response, &Error>) -> ResponseMonitor {
+    pub fn into_response_monitor(
+        mut self,
+        output: Result<&Response, &BoxError>,
+    ) -> ResponseMonitor {
         self.response_received_or_aborted(Some(output))
             .expect("monitor is returned when output is `Some`")
     }
 
     fn response_received_or_aborted(
         &mut self,
-        maybe_output: Option<Result<&Response, &Error>>,
+        maybe_output: Option<Result<&Response, &BoxError>>,
     ) -> Option<ResponseMonitor> {
         self.inner_mut().labels.add_response_labels(maybe_output);
         let response_available_at = Instant::now();
@@ -472,7 +481,7 @@ impl RequestMonitor {
         )
     }
 
-    fn populate_fields_from_response(&self, maybe_err: Option<&reqwest::Error>) {
+    fn populate_fields_from_response(&self, maybe_err: Option<&BoxError>) {
         let http_span = &self.inner().http_span;
         let labels = &self.inner().labels;
 
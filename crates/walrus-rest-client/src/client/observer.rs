@@ -0,0 +1,194 @@
+// Copyright (c) Walrus Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Hooks to observe the requests that the client sends to a storage node.
+
+use std::{fmt, future::Future, sync::Arc, time::Duration};
+
+use reqwest::{Method, Request, StatusCode, Url};
+#[cfg(not(target_arch = "wasm32"))]
+use tokio::time::Instant;
+#[cfg(target_arch = "wasm32")]
+use web_time::Instant;
+
+#[cfg(not(target_arch = "wasm32"))]
+tokio::task_local! {
+    static REQUEST_ATTEMPT: u32;
+}
+
+/// Runs `future`, attributing the requests that it sends to the given attempt of an operation.
+///
+/// Retry loops wrap each of their attempts in this function, so that [`RequestObserver`]s can
+/// distinguish retries from first attempts. Requests sent outside of such a future are reported as
+/// first attempts, as are all requests on `wasm32`.
+#[cfg(not(target_arch = "wasm32"))]
+pub async fn with_request_attempt<F: Future>(attempt: u32, future: F) -> F::Output {
+    REQUEST_ATTEMPT.scope(attempt, future).await
+}
+
+/// Runs `future`, attributing the requests that it sends to the given attempt of an operation.
+///
+/// On `wasm32`, all requests are reported as first attempts.
+#[cfg(target_arch = "wasm32")]
+pub async fn with_request_attempt<F: Future>(_attempt: u32, future: F) -> F::Output {
+    future.await
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn current_attempt() -> u32 {
+    REQUEST_ATTEMPT.try_with(|attempt| *attempt).unwrap_or(1)
+}
+
+#[cfg(target_arch = "wasm32")]
+fn current_attempt() -> u32 {
+    1
+}
+
+/// A request sent to a storage node, as reported to a [`RequestObserver`].
+#[derive(Debug, Clone)]
+pub struct RequestInfo {
+    /// The HTTP method of the request.
+    pub method: Method,
+    /// The URL of the request.
+    pub url: Url,
+    /// The template of the URL's path, such as `/v1/blobs/:blob_id/metadata`, which identifies
+    /// the endpoint without the identifiers of the blob or sliver.
+    pub url_template: &'static str,
+    /// The attempt of the operation to which the request belongs, starting from 1.
+    ///
+    /// See [`with_request_attempt`].
+    pub attempt: u32,
+}
+
+/// The outcome of a request sent to a storage node, as reported to a [`RequestObserver`].
+#[derive(Debug, Clone)]
+pub struct RequestOutcome {
+    /// The HTTP status of the response, or `None` if no response was received.
+    pub status: Option<StatusCode>,
+    /// The time from sending the request to receiving the headers of the response or an error.
+    ///
+    /// The time to receive the body of the response is not included, as the body may be streamed.
+    pub duration: Duration,
+}
+
+/// Observes the requests sent by a client, for example to record them in custom metrics or logs.
+///
+/// The hooks are called on the task sending the request and should therefore return quickly.
+pub trait RequestObserver: fmt::Debug + Send + Sync + 'static {
+    /// Called before the request is sent.
+    fn on_request(&self, request: &RequestInfo) {
+        let _ = request;
+    }
+
+    /// Called once the headers of the response, or an error, have been received.
+    fn on_response(&self, request: &RequestInfo, outcome: &RequestOutcome);
+}
+
+/// The observers registered with a client.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct RequestObservers(Vec<Arc<dyn RequestObserver>>);
+
+impl RequestObservers {
+    pub(crate) fn push(&mut self, observer: Arc<dyn RequestObserver>) {
+        self.0.push(observer);
+    }
+
+    /// Notifies the observers that the request is about to be sent.
+    ///
+    /// Returns `None` if there are no observers.
+    pub(crate) fn start(
+        &self,
+        request: &Request,
+        url_template: &'static str,
+    ) -> Option<ObservedRequest> {
+        if self.0.is_empty() {
+            return None;
+        }
+        let info = RequestInfo {
+            method: request.method().clone(),
+            url: request.url().clone(),
+            url_template,
+            attempt: current_attempt(),
+        };
+        for observer in &self.0 {
+            observer.on_request(&info);
+        }
+        Some(ObservedRequest {
+            observers: self.clone(),
+            info,
+            start: Instant::now(),
+        })
+    }
+}
+
+/// A request that has been reported to the observers, but whose outcome has not yet been.
+pub(crate) struct ObservedRequest {
+    observers: RequestObservers,
+    info: RequestInfo,
+    start: Instant,
+}
+
+impl ObservedRequest {
+    /// Notifies the observers of the outcome of the request.
+    pub(crate) fn finish(self, status: Option<StatusCode>) {
+        let outcome = RequestOutcome {
+            status,
+            duration: self.start.elapsed(),
+        };
+        for observer in &self.observers.0 {
+            observer.on_response(&self.info, &outcome);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use super::*;
+
+    #[derive(Debug, Default)]
+    struct RecordingObserver {
+        requests: Mutex<Vec<u32>>,
+        outcomes: Mutex<Vec<Option<StatusCode>>>,
+    }
+
+    impl RequestObserver for RecordingObserver {
+        fn on_request(&self, request: &RequestInfo) {
+            self.requests.lock().unwrap().push(request.attempt);
+        }
+
+        fn on_response(&self, _request: &RequestInfo, outcome: &RequestOutcome) {
+            self.outcomes.lock().unwrap().push(outcome.status);
+        }
+    }
+
+    #[tokio::test]
+    async fn reports_attempts_and_outcomes() {
+        let observer = Arc::new(RecordingObserver::default());
+        let mut observers = RequestObservers::default();
+        observers.push(observer.clone());
+        let request = Request::new(
+            Method::GET,
+            Url::parse("https://node.com/v1/health").unwrap(),
+        );
+
+        observers
+            .start(&request, "/v1/health")
+            .expect("there is an observer")
+            .finish(Some(StatusCode::OK));
+        with_request_attempt(3, async {
+            observers
+                .start(&request, "/v1/health")
+                .expect("there is an observer")
+                .finish(None);
+        })
+        .await;
+
+        assert_eq!(*observer.requests.lock().unwrap(), [1, 3]);
+        assert_eq!(
+            *observer.outcomes.lock().unwrap(),
+            [Some(StatusCode::OK), None]
+        );
+    }
+}
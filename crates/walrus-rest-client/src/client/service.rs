@@ -0,0 +1,55 @@
+// Copyright (c) Walrus Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! The tower service stack over which the [`Client`][super::Client] sends its requests.
+
+use std::{fmt, sync::Arc};
+
+use reqwest::{Client as ReqwestClient, Request, Response};
+use tower::{util::BoxCloneSyncService, BoxError, Layer, Service, ServiceExt as _};
+
+/// The type-erased service over which the [`Client`][super::Client] sends its HTTP requests.
+///
+/// By default, this is the [`reqwest::Client`] configured by the
+/// [`ClientBuilder`][super::ClientBuilder], which can be wrapped in middleware with
+/// [`HttpLayer`]s or replaced by a custom transport.
+pub type HttpService = BoxCloneSyncService<Request, Response, BoxError>;
+
+/// Returns the [`HttpService`] sending requests with the provided reqwest client.
+pub(crate) fn reqwest_service(client: ReqwestClient) -> HttpService {
+    HttpService::new(client.map_err(BoxError::from))
+}
+
+/// A tower layer that wraps the [`HttpService`] of a client in middleware.
+///
+/// The layer can be reused to construct several clients, for example, one per storage node. It
+/// can add retries, timeouts, authentication headers, or custom metrics, as long as the resulting
+/// service still sends [`reqwest::Request`]s and returns [`reqwest::Response`]s.
+#[derive(Clone)]
+pub struct HttpLayer(Arc<dyn Fn(HttpService) -> HttpService + Send + Sync>);
+
+impl HttpLayer {
+    /// Creates a new `HttpLayer` from the provided tower layer.
+    pub fn new<L>(layer: L) -> Self
+    where
+        L: Layer<HttpService> + Send + Sync + 'static,
+        L::Service:
+            Service<Request, Response = Response, Error = BoxError> + Clone + Send + Sync + 'static,
+        <L::Service as Service<Request>>::Future: Send + 'static,
+    {
+        Self(Arc::new(move |service| {
+            HttpService::new(layer.layer(service))
+        }))
+    }
+
+    /// Wraps the service in the layer.
+    pub(crate) fn apply(&self, service: HttpService) -> HttpService {
+        (self.0)(service)
+    }
+}
+
+impl fmt::Debug for HttpLayer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("HttpLayer").finish_non_exhaustive()
+    }
+}
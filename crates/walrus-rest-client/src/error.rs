@@ -4,13 +4,16 @@
 //! Errors that may be encountered while interacting with a storage node.
 
 use reqwest::StatusCode;
-use walrus_core::Epoch;
-
-use crate::{
-    api::errors::{Status, STORAGE_NODE_ERROR_DOMAIN},
-    tls::VerifierBuildError,
+use walrus_core::{
+    encoding::{SliverChecksum, SymbolVerificationError},
+    Epoch,
+    SliverIndex,
 };
 
+use crate::api::errors::{Status, StatusCode as ApiStatusCode, STORAGE_NODE_ERROR_DOMAIN};
+#[cfg(not(target_arch = "wasm32"))]
+use crate::tls::VerifierBuildError;
+
 /// Error raised during communication with a node.
 #[derive(Debug, thiserror::Error)]
 #[error(transparent)]
@@ -58,6 +61,13 @@ impl NodeError {
             .unwrap_or(false)
     }
 
+    /// Returns true if the error is due to the storage period of the blob having ended.
+    pub fn is_blob_expired(&self) -> bool {
+        self.status()
+            .map(|status| status.is_for_reason("BLOB_EXPIRED", STORAGE_NODE_ERROR_DOMAIN))
+            .unwrap_or(false)
+    }
+
     /// Returns true if the error is due to the shard not being assigned to the storage node.
     pub fn is_shard_not_assigned(&self) -> bool {
         // TODO(jsmith): use a constant shared between client and server.
@@ -96,6 +106,63 @@ impl NodeError {
         Some(StatusCode::INTERNAL_SERVER_ERROR) == self.http_status_code()
     }
 
+    /// Returns the [`NodeErrorKind`] classifying the error.
+    pub fn error_kind(&self) -> NodeErrorKind {
+        if self.is_shard_not_assigned() {
+            return NodeErrorKind::ShardNotOwned;
+        } else if self.is_blob_blocked() {
+            return NodeErrorKind::Blocked;
+        } else if self.is_blob_expired() {
+            return NodeErrorKind::Expired;
+        }
+
+        match &self.kind {
+            Kind::Verification(_) => return NodeErrorKind::VerificationFailed,
+            Kind::Bcs(_) | Kind::ErrorInNonErrorMessage(_) | Kind::InvalidContentType => {
+                return NodeErrorKind::InvalidResponse
+            }
+            Kind::Reqwest(error) if error.is_timeout() => return NodeErrorKind::Timeout,
+            Kind::Reqwest(error) if error.is_connect() => return NodeErrorKind::Connect,
+            _ => (),
+        }
+
+        if let Some(code) = self.status().map(Status::code) {
+            match code {
+                ApiStatusCode::InvalidArgument => return NodeErrorKind::InvalidRequest,
+                ApiStatusCode::FailedPrecondition => return NodeErrorKind::FailedPrecondition,
+                _ => (),
+            }
+        }
+
+        match self.http_status_code() {
+            Some(StatusCode::NOT_FOUND) => NodeErrorKind::NotFound,
+            Some(StatusCode::TOO_MANY_REQUESTS) => NodeErrorKind::RateLimited,
+            Some(StatusCode::REQUEST_TIMEOUT | StatusCode::GATEWAY_TIMEOUT) => {
+                NodeErrorKind::Timeout
+            }
+            Some(code) if code.is_server_error() => NodeErrorKind::Unavailable,
+            Some(_) => NodeErrorKind::Other,
+            // Other errors of the HTTP client without a status code are network errors, for
+            // example, the connection being reset while receiving the response.
+            None if self.is_reqwest() => NodeErrorKind::Unavailable,
+            None => NodeErrorKind::Other,
+        }
+    }
+
+    /// Returns true if the request may succeed when retried.
+    ///
+    /// See [`NodeErrorKind::is_retriable`].
+    pub fn is_retriable(&self) -> bool {
+        self.error_kind().is_retriable()
+    }
+
+    /// Returns true if the request fails again when retried against the same node.
+    ///
+    /// See [`NodeErrorKind::is_permanent`].
+    pub fn is_permanent(&self) -> bool {
+        self.error_kind().is_permanent()
+    }
+
     /// Wrap a standard error as a Node error.
     pub fn other<E>(err: E) -> Self
     where
@@ -104,10 +171,29 @@ impl NodeError {
         Kind::Other(err.into()).into()
     }
 
+    /// Wrap an error raised when verifying the response of the node.
+    pub fn verification<E>(err: E) -> Self
+    where
+        E: std::error::Error + Send + Sync + 'static,
+    {
+        Kind::Verification(err.into()).into()
+    }
+
     pub(crate) fn reqwest(err: reqwest::Error) -> Self {
         Kind::Reqwest(err).into()
     }
 
+    /// Converts an error returned by the HTTP service of the client.
+    ///
+    /// Errors that do not originate from reqwest were returned by a middleware or a custom
+    /// transport.
+    pub(crate) fn from_service_error(err: tower::BoxError) -> Self {
+        match err.downcast::<reqwest::Error>() {
+            Ok(err) => Self::reqwest(*err),
+            Err(err) => Kind::Other(err).into(),
+        }
+    }
+
     /// Returns the reason for the error, if any.
     pub fn service_error(&self) -> Option<ServiceError> {
         if let Kind::Status { ref status, .. } = self.kind {
@@ -148,9 +234,71 @@ pub(crate) enum Kind {
     #[error("invalid content type in response")]
     InvalidContentType,
     #[error(transparent)]
+    Verification(Box<dyn std::error::Error + Send + Sync>),
+    #[error(transparent)]
     Other(Box<dyn std::error::Error + Send + Sync>),
 }
 
+/// The classification of a [`NodeError`].
+///
+/// The kind indicates whether retrying a request against the same node can succeed. Errors
+/// that are neither retriable nor permanent, such as [`NodeErrorKind::NotFound`], depend on the
+/// state of the node, which may change over time.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum NodeErrorKind {
+    /// The request or the connection to the node timed out.
+    Timeout,
+    /// The connection to the node could not be established.
+    Connect,
+    /// The node rejected the request because the client exceeded its rate limits.
+    RateLimited,
+    /// The node is temporarily unavailable, failed internally, or the connection failed.
+    Unavailable,
+    /// The requested resource was not found on the node.
+    NotFound,
+    /// The shard is not owned by the node.
+    ShardNotOwned,
+    /// The response of the node failed verification.
+    VerificationFailed,
+    /// The blob is blocked by the node.
+    Blocked,
+    /// The storage period of the blob has ended.
+    Expired,
+    /// The request is invalid, regardless of the state of the node.
+    InvalidRequest,
+    /// The node is not in a state to perform the request, for example, because it has not yet
+    /// seen the registration of the blob.
+    FailedPrecondition,
+    /// The response of the node could not be parsed.
+    InvalidResponse,
+    /// Any other error.
+    Other,
+}
+
+impl NodeErrorKind {
+    /// Returns true if the request may succeed when retried after a backoff.
+    pub fn is_retriable(&self) -> bool {
+        matches!(
+            self,
+            Self::Timeout | Self::Connect | Self::RateLimited | Self::Unavailable
+        )
+    }
+
+    /// Returns true if the request fails again when retried against the same node.
+    pub fn is_permanent(&self) -> bool {
+        matches!(
+            self,
+            Self::ShardNotOwned
+                | Self::VerificationFailed
+                | Self::Blocked
+                | Self::Expired
+                | Self::InvalidRequest
+                | Self::InvalidResponse
+        )
+    }
+}
+
 /// An error returned when building the client with a
 /// [`ClientBuilder`][crate::client::ClientBuilder] has failed.
 #[derive(Debug, thiserror::Error)]
@@ -169,12 +317,14 @@ impl ClientBuildError {
 /// Errors returned during the communication with a storage node.
 #[derive(Debug, thiserror::Error)]
 pub(crate) enum BuildErrorKind {
+    #[cfg(not(target_arch = "wasm32"))]
     #[error("unable to secure the client with TLS: {0}")]
     Tls(#[from] VerifierBuildError),
     #[error("invalid storage node authority")]
     InvalidHostOrPort,
     #[error(transparent)]
     Reqwest(#[from] reqwest::Error),
+    #[cfg(not(target_arch = "wasm32"))]
     #[error("unable to load trusted certificates from the OS: {0:?}")]
     FailedToLoadCerts(Vec<rustls_native_certs::Error>),
 }
@@ -221,6 +371,17 @@ impl TryFrom<&Status> for ServiceError {
     }
 }
 
+/// The reason why a recovery symbol returned by a storage node was rejected.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum RecoverySymbolCheckError {
+    /// The symbol does not match the requested symbol IDs or proof axis.
+    #[error("the server returned a symbol that was not requested")]
+    NotRequested,
+    /// The symbol failed verification against the blob metadata.
+    #[error("the symbol failed verification: {0}")]
+    Verification(#[from] SymbolVerificationError),
+}
+
 /// Private errors for the `list_and_verify_recovery_symbols` endpoint that may lead to a
 /// `NodeError`.
 #[derive(Debug, Clone, thiserror::Error)]
@@ -229,4 +390,53 @@ pub(crate) enum ListAndVerifyRecoverySymbolsError {
     EmptyResponse,
     #[error("the background task verifying symbols failed")]
     BackgroundWorkerFailed,
+    #[error("the request does not specify any symbols")]
+    NoSymbolsRequested,
+    #[error("the server returned {actual} lists of symbols for {expected} requests")]
+    BatchSizeMismatch { expected: usize, actual: usize },
+}
+
+/// Private errors for checking a sliver against the checksum sent by the node.
+#[derive(Debug, Clone, thiserror::Error)]
+pub(crate) enum SliverChecksumError {
+    #[error("the sliver checksum sent by the node is not valid")]
+    InvalidHeader,
+    #[error("the sliver has the checksum {actual} instead of {expected} sent by the node")]
+    Mismatch {
+        expected: SliverChecksum,
+        actual: SliverChecksum,
+    },
+}
+
+/// Private errors for streaming a sliver that may lead to a `NodeError`.
+#[derive(Debug, Clone, thiserror::Error)]
+pub(crate) enum StreamSliverError {
+    #[error("the server returned the sliver with index {actual} instead of {expected}")]
+    IndexMismatch {
+        expected: SliverIndex,
+        actual: SliverIndex,
+    },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn verification_errors_are_permanent() {
+        let error = NodeError::verification(bcs::Error::Eof);
+
+        assert_eq!(error.error_kind(), NodeErrorKind::VerificationFailed);
+        assert!(error.is_permanent());
+        assert!(!error.is_retriable());
+    }
+
+    #[test]
+    fn other_errors_are_neither_retriable_nor_permanent() {
+        let error = NodeError::other(bcs::Error::Eof);
+
+        assert_eq!(error.error_kind(), NodeErrorKind::Other);
+        assert!(!error.is_permanent());
+        assert!(!error.is_retriable());
+    }
 }
@@ -2,6 +2,10 @@
 // SPDX-License-Identifier: Apache-2.0
 
 //! Code for interacting with the Walrus system.
+//!
+//! The client also compiles to `wasm32-unknown-unknown`, where requests are sent with the browser's
+//! `fetch` API. In that case, the browser is responsible for TLS, so storage nodes can only be
+//! reached if they present a certificate trusted by the browser.
 
 use std::fmt::Write;
 
@@ -12,7 +16,11 @@ pub mod api;
 pub mod client;
 pub mod error;
 
+#[cfg(all(not(target_arch = "wasm32"), any(test, feature = "test-utils")))]
+pub mod test_utils;
+
 mod node_response;
+#[cfg(not(target_arch = "wasm32"))]
 mod tls;
 
 /// Returns a string `<first-4-bytes-as-hex>.network.walrus.alt` corresponding to the public key.
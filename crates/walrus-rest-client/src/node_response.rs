@@ -6,7 +6,12 @@ use reqwest::{
     Response,
 };
 use serde::de::DeserializeOwned;
-use walrus_core::ensure;
+use walrus_core::{
+    encoding::EncodingConfig,
+    ensure,
+    metadata::{StreamingMetadataVerifier, VerifiedBlobMetadataWithId},
+    BlobId,
+};
 
 use crate::{
     api::ServiceResponse,
@@ -21,6 +26,16 @@ pub(crate) trait NodeResponse: Sized {
     /// Decode the body of the response using BCS encoding.
     async fn bcs<T: DeserializeOwned>(self) -> Result<T, NodeError>;
 
+    /// Decode and verify the BCS-encoded metadata of the blob with ID `blob_id` in the body of the
+    /// response, while it is received.
+    ///
+    /// See [`StreamingMetadataVerifier`] for the checks performed.
+    async fn verified_metadata(
+        self,
+        blob_id: &BlobId,
+        encoding_config: &EncodingConfig,
+    ) -> Result<VerifiedBlobMetadataWithId, NodeError>;
+
     /// Decode the body of the response as a [`ServiceResponse`].
     async fn service_response<T: DeserializeOwned>(self) -> Result<T, NodeError>;
 }
@@ -54,6 +69,33 @@ impl NodeResponse for Response {
         Ok(bcs::from_bytes(&body).map_err(Kind::Bcs)?)
     }
 
+    async fn verified_metadata(
+        self,
+        blob_id: &BlobId,
+        encoding_config: &EncodingConfig,
+    ) -> Result<VerifiedBlobMetadataWithId, NodeError> {
+        ensure!(
+            is_content_type_octet_stream(self.headers()),
+            NodeError::from(Kind::InvalidContentType)
+        );
+
+        let mut verifier = StreamingMetadataVerifier::new(*blob_id, encoding_config);
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            let mut response = self;
+            while let Some(chunk) = response.chunk().await.map_err(Kind::Reqwest)? {
+                verifier.update(&chunk).map_err(NodeError::verification)?;
+            }
+        }
+        // The body of a response cannot be read in chunks on WebAssembly.
+        #[cfg(target_arch = "wasm32")]
+        {
+            let body = self.bytes().await.map_err(Kind::Reqwest)?;
+            verifier.update(&body).map_err(NodeError::verification)?;
+        }
+        verifier.finish().map_err(NodeError::verification)
+    }
+
     async fn service_response<T: DeserializeOwned>(self) -> Result<T, NodeError> {
         let non_error_response = self.response_error_for_status().await?;
 
@@ -73,7 +115,7 @@ impl NodeResponse for Response {
     }
 }
 
-fn is_content_type_octet_stream(headers: &HeaderMap) -> bool {
+pub(crate) fn is_content_type_octet_stream(headers: &HeaderMap) -> bool {
     let Some(content_type) = headers.get(header::CONTENT_TYPE) else {
         // No media-type is often just bytes.
         return true;
@@ -0,0 +1,649 @@
+// Copyright (c) Walrus Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Test doubles for the storage-node API.
+//!
+//! The [`MockStorageNode`] is an in-memory storage node that serves the requests of a [`Client`]
+//! without a network. It can be programmed with latencies, failures, and corrupt responses, so
+//! that code built on the client can be tested without running storage nodes. The
+//! [`MockStorageNodes`] route the requests of clients to several such nodes by host, for example
+//! to mock the storage nodes of a committee.
+
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use reqwest::{header, Request, Response};
+use serde::Serialize;
+use sui_types::base_types::ObjectID;
+use tower::BoxError;
+use walrus_core::{
+    encoding::{PrimarySliver, SecondarySliver},
+    keys::ProtocolKeyPair,
+    messages::{BlobPersistenceType, Confirmation, StorageConfirmation},
+    metadata::{UnverifiedBlobMetadataWithId, VerifiedBlobMetadataWithId},
+    test_utils,
+    BlobId,
+    Epoch,
+    PublicKey,
+    Sliver,
+    SliverPairIndex,
+    SliverType,
+};
+
+use crate::{
+    api::{
+        errors::{ErrorInfo, Status, StatusCode, STORAGE_NODE_ERROR_DOMAIN},
+        ApiVersion,
+        BlobStatus,
+        ServiceResponse,
+        StoredOnNodeStatus,
+        API_VERSION_HEADER,
+        SLIVER_CHECKSUM_HEADER,
+    },
+    client::{Client, HttpLayer, HttpService},
+};
+
+/// A failure injected into the responses of a [`MockStorageNode`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MockFailure {
+    /// The request fails without a response, as if the node was unreachable.
+    Unreachable,
+    /// The node responds with an error with the given status code.
+    Status(StatusCode),
+}
+
+/// An in-memory storage node serving the storage-node API.
+///
+/// The node stores the metadata and slivers it receives, serves them back, and signs storage
+/// confirmations for the blobs whose metadata it stores. Clones of the node share its state, so
+/// the node can be programmed and inspected while clients send requests to it.
+///
+/// The node serves the metadata, sliver, blob-status, and confirmation endpoints; requests to
+/// other endpoints fail with [`StatusCode::NotFound`].
+#[derive(Debug, Clone)]
+pub struct MockStorageNode {
+    state: Arc<Mutex<MockState>>,
+}
+
+#[derive(Debug)]
+struct MockState {
+    protocol_key_pair: ProtocolKeyPair,
+    epoch: Epoch,
+    metadata: HashMap<BlobId, UnverifiedBlobMetadataWithId>,
+    slivers: HashMap<(BlobId, SliverPairIndex, SliverType), Sliver>,
+    blob_statuses: HashMap<BlobId, BlobStatus>,
+    latency: Duration,
+    failure: Option<MockFailure>,
+    next_failures: VecDeque<MockFailure>,
+    corrupt_responses: bool,
+    n_requests: usize,
+}
+
+impl MockStorageNode {
+    /// Creates a node in `epoch` that signs its confirmations with the provided key pair.
+    pub fn new(protocol_key_pair: ProtocolKeyPair, epoch: Epoch) -> Self {
+        Self {
+            state: Arc::new(Mutex::new(MockState {
+                protocol_key_pair,
+                epoch,
+                metadata: HashMap::new(),
+                slivers: HashMap::new(),
+                blob_statuses: HashMap::new(),
+                latency: Duration::ZERO,
+                failure: None,
+                next_failures: VecDeque::new(),
+                corrupt_responses: false,
+                n_requests: 0,
+            })),
+        }
+    }
+
+    /// Returns a client that sends its requests to this node.
+    pub fn client(&self) -> Client {
+        Client::builder()
+            .authenticate_with_public_key(test_utils::network_key_pair().public().clone())
+            .tls_built_in_root_certs(false)
+            .transport(self.service())
+            .build("mock.node.walrus.alt")
+            .expect("a client with a pinned key and a custom transport can always be built")
+    }
+
+    /// Returns the [`HttpService`] through which the node serves requests.
+    ///
+    /// The service can be used as the transport of clients built with a custom
+    /// [`ClientBuilder`][crate::client::ClientBuilder].
+    pub fn service(&self) -> HttpService {
+        let node = self.clone();
+        HttpService::new(tower::service_fn(move |request| {
+            let node = node.clone();
+            async move { node.handle(request).await }
+        }))
+    }
+
+    /// Returns the public key with which the node signs its confirmations.
+    pub fn public_key(&self) -> PublicKey {
+        self.lock().protocol_key_pair.public().clone()
+    }
+
+    /// Delays every response of the node by `latency`.
+    pub fn set_latency(&self, latency: Duration) {
+        self.lock().latency = latency;
+    }
+
+    /// Fails all requests with `failure` until it is reset with `None`.
+    pub fn set_failure(&self, failure: Option<MockFailure>) {
+        self.lock().failure = failure;
+    }
+
+    /// Fails the next `n` requests with `failure`, before any failure set with
+    /// [`Self::set_failure`].
+    pub fn fail_next_requests(&self, n: usize, failure: MockFailure) {
+        self.lock()
+            .next_failures
+            .extend(std::iter::repeat_n(failure, n));
+    }
+
+    /// Corrupts the metadata, slivers, and confirmations returned by the node, such that they fail
+    /// verification.
+    pub fn set_corrupt_responses(&self, corrupt: bool) {
+        self.lock().corrupt_responses = corrupt;
+    }
+
+    /// Sets the status that the node returns for the blob.
+    ///
+    /// Blobs without a status set are reported as [`BlobStatus::Nonexistent`].
+    pub fn set_blob_status(&self, blob_id: BlobId, status: BlobStatus) {
+        self.lock().blob_statuses.insert(blob_id, status);
+    }
+
+    /// Stores the metadata on the node, as if it had been sent by a client.
+    pub fn insert_metadata(&self, metadata: &VerifiedBlobMetadataWithId) {
+        self.lock()
+            .metadata
+            .insert(*metadata.blob_id(), metadata.clone().into_unverified());
+    }
+
+    /// Stores the sliver on the node, as if it had been sent by a client.
+    pub fn insert_sliver(&self, blob_id: BlobId, pair_index: SliverPairIndex, sliver: Sliver) {
+        let sliver_type = sliver.r#type();
+        self.lock()
+            .slivers
+            .insert((blob_id, pair_index, sliver_type), sliver);
+    }
+
+    /// Returns the metadata stored on the node for the blob, if any.
+    pub fn metadata(&self, blob_id: &BlobId) -> Option<UnverifiedBlobMetadataWithId> {
+        self.lock().metadata.get(blob_id).cloned()
+    }
+
+    /// Returns the sliver stored on the node, if any.
+    pub fn sliver(
+        &self,
+        blob_id: &BlobId,
+        pair_index: SliverPairIndex,
+        sliver_type: SliverType,
+    ) -> Option<Sliver> {
+        self.lock()
+            .slivers
+            .get(&(*blob_id, pair_index, sliver_type))
+            .cloned()
+    }
+
+    /// Returns the number of requests that the node has received.
+    pub fn n_requests(&self) -> usize {
+        self.lock().n_requests
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, MockState> {
+        self.state.lock().expect("the mutex is not poisoned")
+    }
+
+    async fn handle(&self, request: Request) -> Result<Response, BoxError> {
+        let (latency, failure) = {
+            let mut state = self.lock();
+            state.n_requests += 1;
+            let failure = state.next_failures.pop_front().or(state.failure);
+            (state.latency, failure)
+        };
+
+        if !latency.is_zero() {
+            tokio::time::sleep(latency).await;
+        }
+        match failure {
+            Some(MockFailure::Unreachable) => Err("the mock storage node is unreachable".into()),
+            Some(MockFailure::Status(code)) => Ok(error_response(
+                code,
+                "INJECTED_FAILURE",
+                "the mock storage node was programmed to fail",
+            )),
+            None => Ok(self.route(&request)),
+        }
+    }
+
+    fn route(&self, request: &Request) -> Response {
+        let segments: Vec<_> = request
+            .url()
+            .path_segments()
+            .map(Iterator::collect)
+            .unwrap_or_default();
+        let body = request
+            .body()
+            .and_then(|body| body.as_bytes())
+            .unwrap_or_default();
+
+        let ["v1", "blobs", blob_id, rest @ ..] = segments.as_slice() else {
+            return endpoint_not_supported();
+        };
+        let Ok(blob_id) = blob_id.parse::<BlobId>() else {
+            return invalid_argument("invalid blob ID");
+        };
+
+        match (request.method().as_str(), rest) {
+            ("GET", ["metadata"]) => self.get_metadata(&blob_id),
+            ("PUT", ["metadata"]) => self.put_metadata(&blob_id, body),
+            ("GET", ["metadata", "status"]) => {
+                json_response(stored_on_node(self.lock().metadata.contains_key(&blob_id)))
+            }
+            ("GET", ["status"]) => json_response(
+                self.lock()
+                    .blob_statuses
+                    .get(&blob_id)
+                    .cloned()
+                    .unwrap_or_default(),
+            ),
+            ("GET", ["confirmation", "permanent"]) => {
+                self.get_confirmation(&blob_id, BlobPersistenceType::Permanent)
+            }
+            ("GET", ["confirmation", "deletable", object_id]) => {
+                let Ok(object_id) = object_id.parse::<ObjectID>() else {
+                    return invalid_argument("invalid object ID");
+                };
+                let persistence_type = BlobPersistenceType::Deletable {
+                    object_id: object_id.into(),
+                };
+                self.get_confirmation(&blob_id, persistence_type)
+            }
+            (method, ["slivers", pair_index, sliver_type, rest @ ..]) => {
+                let (Ok(pair_index), Some(sliver_type)) =
+                    (pair_index.parse::<u16>(), parse_sliver_type(sliver_type))
+                else {
+                    return invalid_argument("invalid sliver pair index or sliver type");
+                };
+                let key = (blob_id, SliverPairIndex(pair_index), sliver_type);
+                match (method, rest) {
+                    ("GET", []) => self.get_sliver(&key),
+                    ("PUT", []) => self.put_sliver(key, body),
+                    ("GET", ["status"]) => {
+                        json_response(stored_on_node(self.lock().slivers.contains_key(&key)))
+                    }
+                    _ => endpoint_not_supported(),
+                }
+            }
+            _ => endpoint_not_supported(),
+        }
+    }
+
+    fn get_metadata(&self, blob_id: &BlobId) -> Response {
+        let state = self.lock();
+        let Some(metadata) = state.metadata.get(blob_id) else {
+            return not_found(
+                "METADATA_NOT_FOUND",
+                "the metadata is not stored on the node",
+            );
+        };
+        let mut bytes = bcs::to_bytes(metadata).expect("metadata can be BCS encoded");
+        if state.corrupt_responses {
+            // The encoding ends with the last hash of the metadata, which is thus modified.
+            *bytes.last_mut().expect("the encoding is not empty") ^= 0xff;
+        }
+        bcs_response(bytes)
+    }
+
+    fn put_metadata(&self, blob_id: &BlobId, body: &[u8]) -> Response {
+        let Ok(metadata) = bcs::from_bytes::<UnverifiedBlobMetadataWithId>(body) else {
+            return invalid_argument("the body is not valid metadata");
+        };
+        if metadata.blob_id() != blob_id {
+            return invalid_argument("the metadata is for a different blob ID");
+        }
+        self.lock().metadata.insert(*blob_id, metadata);
+        json_response("metadata successfully stored")
+    }
+
+    fn get_sliver(&self, key: &(BlobId, SliverPairIndex, SliverType)) -> Response {
+        let state = self.lock();
+        let Some(sliver) = state.slivers.get(key) else {
+            return not_found("SLIVER_NOT_FOUND", "the sliver is not stored on the node");
+        };
+        let mut sliver = sliver.clone();
+        if state.corrupt_responses {
+            match &mut sliver {
+                Sliver::Primary(sliver) => corrupt_symbols(sliver.symbols.data_mut()),
+                Sliver::Secondary(sliver) => corrupt_symbols(sliver.symbols.data_mut()),
+            }
+        }
+        let bytes = match &sliver {
+            Sliver::Primary(sliver) => bcs::to_bytes(sliver),
+            Sliver::Secondary(sliver) => bcs::to_bytes(sliver),
+        };
+        let mut response = bcs_response(bytes.expect("slivers can be BCS encoded"));
+        response.headers_mut().insert(
+            SLIVER_CHECKSUM_HEADER,
+            header::HeaderValue::from_str(&sliver.checksum().to_string())
+                .expect("checksums are valid header values"),
+        );
+        response
+    }
+
+    fn put_sliver(&self, key: (BlobId, SliverPairIndex, SliverType), body: &[u8]) -> Response {
+        let sliver = match key.2 {
+            SliverType::Primary => bcs::from_bytes::<PrimarySliver>(body).map(Sliver::Primary),
+            SliverType::Secondary => {
+                bcs::from_bytes::<SecondarySliver>(body).map(Sliver::Secondary)
+            }
+        };
+        let Ok(sliver) = sliver else {
+            return invalid_argument("the body is not a valid sliver");
+        };
+        self.lock().slivers.insert(key, sliver);
+        json_response("sliver stored successfully")
+    }
+
+    fn get_confirmation(
+        &self,
+        blob_id: &BlobId,
+        persistence_type: BlobPersistenceType,
+    ) -> Response {
+        let state = self.lock();
+        if !state.metadata.contains_key(blob_id) {
+            return not_found(
+                "METADATA_NOT_FOUND",
+                "the metadata is not stored on the node",
+            );
+        }
+        // A confirmation for another epoch fails verification.
+        let epoch = if state.corrupt_responses {
+            state.epoch + 1
+        } else {
+            state.epoch
+        };
+        let confirmation = Confirmation::new(epoch, *blob_id, persistence_type);
+        json_response(StorageConfirmation::Signed(
+            state.protocol_key_pair.sign_message(&confirmation),
+        ))
+    }
+}
+
+/// A set of [`MockStorageNode`]s, each serving the requests addressed to its host.
+///
+/// This allows mocking the storage nodes of a committee, each with its own protocol key and data,
+/// for clients that construct their own storage-node clients.
+#[derive(Debug, Clone, Default)]
+pub struct MockStorageNodes {
+    nodes: HashMap<String, MockStorageNode>,
+}
+
+impl MockStorageNodes {
+    /// Creates an empty set of nodes.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds the node serving the requests to `host`, replacing any node previously added for the
+    /// host.
+    ///
+    /// The host is the host name or IP address of the node's network address, without the port.
+    pub fn insert(&mut self, host: impl Into<String>, node: MockStorageNode) {
+        self.nodes.insert(host.into(), node);
+    }
+
+    /// Returns the node serving the requests to `host`, if any.
+    pub fn get(&self, host: &str) -> Option<&MockStorageNode> {
+        self.nodes.get(host)
+    }
+
+    /// Returns an [`HttpLayer`] that replaces the transport of a client with the nodes.
+    ///
+    /// Requests are served by the node added for the host of their URL, and fail without a
+    /// response, as if the node was unreachable, if there is no such node. The layer can be
+    /// passed, for example, to `walrus_sdk::client::Client::with_http_layer`.
+    pub fn layer(&self) -> HttpLayer {
+        let nodes = Arc::new(self.nodes.clone());
+        let service = HttpService::new(tower::service_fn(move |request: Request| {
+            let node = request
+                .url()
+                .host_str()
+                .and_then(|host| nodes.get(host))
+                .cloned();
+            async move {
+                let Some(node) = node else {
+                    return Err(format!("no mock storage node serves {}", request.url()).into());
+                };
+                node.handle(request).await
+            }
+        }));
+        HttpLayer::new(tower::layer::layer_fn(move |_transport| service.clone()))
+    }
+}
+
+fn corrupt_symbols(data: &mut [u8]) {
+    if let Some(byte) = data.first_mut() {
+        *byte ^= 0xff;
+    }
+}
+
+fn parse_sliver_type(sliver_type: &str) -> Option<SliverType> {
+    match sliver_type {
+        "primary" => Some(SliverType::Primary),
+        "secondary" => Some(SliverType::Secondary),
+        _ => None,
+    }
+}
+
+fn stored_on_node(is_stored: bool) -> StoredOnNodeStatus {
+    if is_stored {
+        StoredOnNodeStatus::Stored
+    } else {
+        StoredOnNodeStatus::Nonexistent
+    }
+}
+
+fn json_response<T: Serialize>(data: T) -> Response {
+    let body = ServiceResponse::Success { code: 200, data };
+    response(
+        http::StatusCode::OK,
+        mime::APPLICATION_JSON.as_ref(),
+        serde_json::to_vec(&body).expect("responses can be JSON encoded"),
+    )
+}
+
+fn bcs_response(bytes: Vec<u8>) -> Response {
+    response(
+        http::StatusCode::OK,
+        mime::APPLICATION_OCTET_STREAM.as_ref(),
+        bytes,
+    )
+}
+
+fn not_found(reason: &str, message: &str) -> Response {
+    error_response(StatusCode::NotFound, reason, message)
+}
+
+fn invalid_argument(message: &str) -> Response {
+    error_response(StatusCode::InvalidArgument, "INVALID_ARGUMENT", message)
+}
+
+fn endpoint_not_supported() -> Response {
+    not_found(
+        "ENDPOINT_NOT_SUPPORTED",
+        "the endpoint is not supported by the mock storage node",
+    )
+}
+
+fn error_response(code: StatusCode, reason: &str, message: &str) -> Response {
+    let status = Status::new(
+        code,
+        message.to_owned(),
+        ErrorInfo::new(reason.to_owned(), STORAGE_NODE_ERROR_DOMAIN.to_owned()),
+    );
+    response(
+        code.http_code(),
+        mime::APPLICATION_JSON.as_ref(),
+        serde_json::to_vec(&ServiceResponse::<()>::Error(status))
+            .expect("responses can be JSON encoded"),
+    )
+}
+
+fn response(status: http::StatusCode, content_type: &str, body: Vec<u8>) -> Response {
+    http::Response::builder()
+        .status(status)
+        .header(header::CONTENT_TYPE, content_type)
+        .header(API_VERSION_HEADER, ApiVersion::CURRENT.0.to_string())
+        .body(body)
+        .expect("the response is valid")
+        .into()
+}
+
+#[cfg(test)]
+mod tests {
+    use walrus_core::{
+        encoding::{EncodingConfig, EncodingConfigTrait as _, Primary, Secondary, SliverPair},
+        DEFAULT_ENCODING,
+    };
+    use walrus_test_utils::Result as TestResult;
+
+    use super::*;
+    use crate::error::NodeErrorKind;
+
+    fn encoded_blob() -> (EncodingConfig, VerifiedBlobMetadataWithId, Vec<SliverPair>) {
+        let config = test_utils::encoding_config();
+        let (pairs, metadata) = config
+            .get_for_type(DEFAULT_ENCODING)
+            .encode_with_metadata(b"mock storage node test blob")
+            .expect("the blob can be encoded");
+        (config, metadata, pairs)
+    }
+
+    #[tokio::test]
+    async fn stores_and_serves_metadata_slivers_and_confirmations() -> TestResult {
+        let (config, metadata, pairs) = encoded_blob();
+        let protocol_key_pair = test_utils::protocol_key_pair();
+        let public_key = protocol_key_pair.public().clone();
+        let node = MockStorageNode::new(protocol_key_pair, 1);
+        let client = node.client();
+
+        client.store_metadata(&metadata).await?;
+        client
+            .store_sliver(metadata.blob_id(), pairs[0].index(), &pairs[0].primary)
+            .await?;
+
+        assert_eq!(
+            client
+                .get_and_verify_metadata(metadata.blob_id(), &config)
+                .await?,
+            metadata
+        );
+        let sliver = client
+            .get_and_verify_sliver::<Primary>(pairs[0].index(), &metadata, &config)
+            .await?;
+        assert_eq!(sliver, pairs[0].primary);
+        client
+            .get_and_verify_confirmation(
+                metadata.blob_id(),
+                1,
+                &public_key,
+                BlobPersistenceType::Permanent,
+            )
+            .await?;
+        assert_eq!(node.n_requests(), 5);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn routes_requests_by_host() -> TestResult {
+        let (_, metadata, _) = encoded_blob();
+        let mut nodes = MockStorageNodes::new();
+        for host in ["node-0.walrus.alt", "node-1.walrus.alt"] {
+            nodes.insert(host, MockStorageNode::new(ProtocolKeyPair::generate(), 1));
+        }
+        let client_for = |address: &str| {
+            Client::builder()
+                .authenticate_with_public_key(test_utils::network_key_pair().public().clone())
+                .tls_built_in_root_certs(false)
+                .layer(nodes.layer())
+                .build(address)
+        };
+
+        client_for("node-1.walrus.alt:9185")?
+            .store_metadata(&metadata)
+            .await?;
+
+        let node_0 = nodes.get("node-0.walrus.alt").expect("the node was added");
+        let node_1 = nodes.get("node-1.walrus.alt").expect("the node was added");
+        assert!(node_0.metadata(metadata.blob_id()).is_none());
+        assert!(node_1.metadata(metadata.blob_id()).is_some());
+        assert_eq!((node_0.n_requests(), node_1.n_requests()), (0, 1));
+        client_for("node-2.walrus.alt:9185")?
+            .get_metadata(metadata.blob_id())
+            .await
+            .expect_err("no node serves the host");
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn injects_failures() -> TestResult {
+        let (config, metadata, _) = encoded_blob();
+        let node = MockStorageNode::new(test_utils::protocol_key_pair(), 1);
+        node.insert_metadata(&metadata);
+        let client = node.client();
+
+        node.fail_next_requests(1, MockFailure::Status(StatusCode::Unavailable));
+        let error = client
+            .get_metadata(metadata.blob_id())
+            .await
+            .expect_err("the first request fails");
+        assert_eq!(error.error_kind(), NodeErrorKind::Unavailable);
+
+        node.set_failure(Some(MockFailure::Unreachable));
+        client
+            .get_metadata(metadata.blob_id())
+            .await
+            .expect_err("the node is unreachable");
+
+        node.set_failure(None);
+        client
+            .get_and_verify_metadata(metadata.blob_id(), &config)
+            .await?;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn corrupt_responses_fail_verification() -> TestResult {
+        let (config, metadata, pairs) = encoded_blob();
+        let node = MockStorageNode::new(test_utils::protocol_key_pair(), 1);
+        node.insert_metadata(&metadata);
+        let pair_index = pairs[0].index();
+        node.insert_sliver(
+            *metadata.blob_id(),
+            pair_index,
+            Sliver::Secondary(pairs[0].secondary.clone()),
+        );
+        node.set_corrupt_responses(true);
+        let client = node.client();
+
+        let error = client
+            .get_and_verify_metadata(metadata.blob_id(), &config)
+            .await
+            .expect_err("the metadata is corrupt");
+        assert_eq!(error.error_kind(), NodeErrorKind::VerificationFailed);
+        let error = client
+            .get_and_verify_sliver::<Secondary>(pair_index, &metadata, &config)
+            .await
+            .expect_err("the sliver is corrupt");
+        assert_eq!(error.error_kind(), NodeErrorKind::VerificationFailed);
+        Ok(())
+    }
+}
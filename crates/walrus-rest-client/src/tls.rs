@@ -11,6 +11,7 @@ use std::{
     time::Duration,
 };
 
+use fastcrypto::hash::{HashFunction as _, Sha256};
 use p256::elliptic_curve::ALGORITHM_OID;
 use rustls::{
     client::{
@@ -21,6 +22,8 @@ use rustls::{
     DigitallySignedStruct,
     RootCertStore,
 };
+use serde::{Deserialize, Serialize};
+use serde_with::{base64::Base64, serde_as};
 use walrus_core::{self, NetworkPublicKey};
 use x509_cert::{
     certificate::{Certificate as X509Certificate, TbsCertificateInner},
@@ -142,10 +145,63 @@ pub(crate) enum VerifierBuildError {
     Builder(#[from] rustls::client::VerifierBuilderError),
 }
 
+/// A pin on the certificate presented by a server, specified by a SHA-256 digest.
+///
+/// Pins are checked in addition to the other verification of the certificate. When pins are
+/// configured, the certificate presented by the server must match at least one of them.
+#[serde_as]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CertificatePin {
+    /// The SHA-256 digest of the DER-encoded end-entity certificate.
+    CertificateSha256(#[serde_as(as = "Base64")] [u8; 32]),
+    /// The SHA-256 digest of the DER-encoded subject public key info of the end-entity
+    /// certificate.
+    ///
+    /// Unlike a pin on the certificate, this pin remains valid when the certificate is renewed
+    /// with the same key.
+    SpkiSha256(#[serde_as(as = "Base64")] [u8; 32]),
+}
+
+impl CertificatePin {
+    /// Returns a pin on the provided DER-encoded certificate.
+    pub fn for_certificate(certificate: &CertificateDer<'_>) -> Self {
+        Self::CertificateSha256(Sha256::digest(certificate.as_ref()).digest)
+    }
+
+    /// Returns a pin on the subject public key info of the provided DER-encoded certificate.
+    ///
+    /// Returns `None` if the certificate cannot be parsed.
+    pub fn for_public_key_of(certificate: &CertificateDer<'_>) -> Option<Self> {
+        spki_sha256(certificate).map(Self::SpkiSha256)
+    }
+
+    /// Returns true if the DER-encoded certificate matches the pin.
+    pub fn matches(&self, certificate: &CertificateDer<'_>) -> bool {
+        match self {
+            Self::CertificateSha256(digest) => {
+                Sha256::digest(certificate.as_ref()).digest == *digest
+            }
+            Self::SpkiSha256(digest) => spki_sha256(certificate).as_ref() == Some(digest),
+        }
+    }
+}
+
+fn spki_sha256(certificate: &CertificateDer<'_>) -> Option<[u8; 32]> {
+    let certificate = X509Certificate::from_der(certificate).ok()?;
+    let public_key_info = certificate
+        .tbs_certificate
+        .subject_public_key_info
+        .to_der()
+        .ok()?;
+    Some(Sha256::digest(public_key_info).digest)
+}
+
 #[derive(Debug)]
 pub(crate) struct TlsCertificateVerifier {
     inner: WebPkiServerVerifier,
     public_key: Option<NetworkPublicKey>,
+    pins: Vec<CertificatePin>,
 }
 
 impl TlsCertificateVerifier {
@@ -193,7 +249,21 @@ impl TlsCertificateVerifier {
         let inner: Arc<_> = WebPkiServerVerifier::builder(trust_root.into()).build()?;
         let inner = Arc::into_inner(inner).expect("uniquely owned arc");
 
-        Ok(Self { inner, public_key })
+        Ok(Self {
+            inner,
+            public_key,
+            pins: vec![],
+        })
+    }
+
+    /// Additionally requires the certificate presented by the server to match one of the pins.
+    pub(crate) fn with_pins(mut self, pins: Vec<CertificatePin>) -> Self {
+        self.pins = pins;
+        self
+    }
+
+    fn matches_pins(&self, end_entity: &CertificateDer<'_>) -> bool {
+        self.pins.is_empty() || self.pins.iter().any(|pin| pin.matches(end_entity))
     }
 
     fn has_pinned_public_key(&self, end_entity: &CertificateDer<'_>) -> bool {
@@ -234,13 +304,18 @@ impl ServerCertVerifier for TlsCertificateVerifier {
 
         // Check the public key only after a successful verification against the web PKI. At this
         // point, the only thing left to check is whether the public key is as expected.
-        if self.has_pinned_public_key(end_entity) {
-            Ok(verified)
-        } else {
+        if !self.has_pinned_public_key(end_entity) {
             Err(rustls::Error::General(
                 "the certificate presented by the server does not match the pinned public key"
                     .to_owned(),
             ))
+        } else if !self.matches_pins(end_entity) {
+            Err(rustls::Error::General(
+                "the certificate presented by the server does not match any certificate pin"
+                    .to_owned(),
+            ))
+        } else {
+            Ok(verified)
         }
     }
 
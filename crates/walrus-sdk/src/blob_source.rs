@@ -0,0 +1,130 @@
+// Copyright (c) Walrus Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Sources of the data of blobs to be stored.
+
+use std::{
+    fs::File,
+    io::{self, Read as _, Seek as _},
+    ops::Deref,
+    path::Path,
+};
+
+use memmap2::Mmap;
+
+/// The data of a blob to be stored.
+///
+/// Dereferences to the bytes of the blob, so that it can be passed to the store and encoding
+/// functions of the client. Files are memory-mapped where possible, which avoids reading them into
+/// memory before they are encoded.
+#[derive(Debug)]
+pub enum BlobSource {
+    /// The data of the blob in memory.
+    Bytes(Vec<u8>),
+    /// A memory-mapped private copy of the file containing the blob.
+    MappedFile(Mmap),
+}
+
+impl BlobSource {
+    /// Returns the source of a blob contained in the file at `path`.
+    ///
+    /// The blob is the content of the file at the time of the call: the file is copied to an
+    /// unnamed temporary file, which is memory-mapped, so that the blob is not affected if the
+    /// file is modified or truncated while the source is in use. The copy requires free space for
+    /// the file in the temporary directory, see [`std::env::temp_dir`]; if the file cannot be
+    /// copied or mapped, it is read into memory instead.
+    pub fn from_file(path: impl AsRef<Path>) -> io::Result<Self> {
+        let path = path.as_ref();
+        let mut file = File::open(path)?;
+
+        match map_private_copy(&mut file) {
+            Ok(mmap) => Ok(Self::MappedFile(mmap)),
+            Err(error) => {
+                tracing::debug!(
+                    ?error,
+                    path = %path.display(),
+                    "failed to memory-map a copy of the file; reading it instead"
+                );
+                file.rewind()?;
+                let mut data = Vec::new();
+                file.read_to_end(&mut data)?;
+                Ok(Self::Bytes(data))
+            }
+        }
+    }
+}
+
+/// Copies the file to an unnamed temporary file and memory-maps the copy.
+fn map_private_copy(file: &mut File) -> io::Result<Mmap> {
+    let mut copy = tempfile::tempfile()?;
+    io::copy(file, &mut copy)?;
+
+    // SAFETY: The map is read-only. The copy has no name in the file system, so it is only
+    // accessible through this handle, which is dropped without modifying the file after mapping
+    // it. The mapping remains valid after the handle is closed.
+    unsafe { Mmap::map(&copy) }
+}
+
+impl Deref for BlobSource {
+    type Target = [u8];
+
+    fn deref(&self) -> &Self::Target {
+        match self {
+            Self::Bytes(data) => data,
+            Self::MappedFile(mmap) => mmap,
+        }
+    }
+}
+
+impl AsRef<[u8]> for BlobSource {
+    fn as_ref(&self) -> &[u8] {
+        self
+    }
+}
+
+impl From<Vec<u8>> for BlobSource {
+    fn from(data: Vec<u8>) -> Self {
+        Self::Bytes(data)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::TempDir;
+    use walrus_test_utils::{param_test, Result as TestResult};
+
+    use super::*;
+
+    param_test! {
+        reads_blob_from_file -> TestResult: [
+            empty: (0),
+            non_empty: (31415),
+        ]
+    }
+    fn reads_blob_from_file(length: usize) -> TestResult {
+        let dir = TempDir::new()?;
+        let path = dir.path().join("blob");
+        let data = walrus_test_utils::random_data(length);
+        std::fs::write(&path, &data)?;
+
+        let source = BlobSource::from_file(&path)?;
+        assert_eq!(&*source, data.as_slice());
+
+        Ok(())
+    }
+
+    #[test]
+    fn is_not_affected_by_later_changes_of_the_file() -> TestResult {
+        let dir = TempDir::new()?;
+        let path = dir.path().join("blob");
+        let data = walrus_test_utils::random_data(4096);
+        std::fs::write(&path, &data)?;
+
+        let source = BlobSource::from_file(&path)?;
+        std::fs::write(&path, b"modified")?;
+        assert!(matches!(source, BlobSource::MappedFile(_)));
+        assert_eq!(&*source, data.as_slice());
+
+        Ok(())
+    }
+}
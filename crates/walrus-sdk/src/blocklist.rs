@@ -23,10 +23,15 @@ struct BlocklistInner(#[serde_as(as = "Vec<DisplayFromStr>")] pub Vec<BlobId>);
 
 /// A blocklist of blob IDs.
 ///
-/// Supports checking if a blob ID is blocked and inserting/removing blob IDs.
+/// Supports checking if a blob ID is blocked and inserting/removing blob IDs. In addition to the
+/// blob IDs in the file, the blocklist can contain blob IDs from a remote list, which are not
+/// persisted to the file.
+///
+/// Clones of the blocklist share the blocked blob IDs.
 #[derive(Debug, Default, Clone)]
 pub struct Blocklist {
     blocked_blobs: Arc<RwLock<HashSet<BlobId>>>,
+    remote_blobs: Arc<RwLock<HashSet<BlobId>>>,
     deny_list_path: PathBuf,
     shutdown: CancellationToken,
 }
@@ -44,6 +49,7 @@ impl Blocklist {
 
         let blocklist = Self {
             blocked_blobs: Arc::new(RwLock::new(HashSet::new())),
+            remote_blobs: Arc::new(RwLock::new(HashSet::new())),
             deny_list_path: path.clone(),
             shutdown: CancellationToken::new(),
         };
@@ -79,16 +85,39 @@ impl Blocklist {
     pub fn is_blocked(&self, blob_id: &BlobId) -> bool {
         let guard = self.blocked_blobs.read().expect("mutex poisoned");
         guard.contains(blob_id)
+            || self
+                .remote_blobs
+                .read()
+                .expect("mutex poisoned")
+                .contains(blob_id)
+    }
+
+    /// Returns the blob IDs that are currently blocked, including those from the remote list.
+    pub fn blocked_blob_ids(&self) -> HashSet<BlobId> {
+        let mut blob_ids = self.blocked_blobs.read().expect("mutex poisoned").clone();
+        blob_ids.extend(self.remote_blobs.read().expect("mutex poisoned").iter());
+        blob_ids
+    }
+
+    /// Replaces the blob IDs of the remote list.
+    ///
+    /// Returns the blob IDs that were added to and removed from the remote list.
+    pub fn replace_remote_blob_ids(&self, blob_ids: HashSet<BlobId>) -> (Vec<BlobId>, Vec<BlobId>) {
+        let mut guard = self.remote_blobs.write().expect("mutex poisoned");
+        let added = blob_ids.difference(&guard).copied().collect();
+        let removed = guard.difference(&blob_ids).copied().collect();
+        *guard = blob_ids;
+        (added, removed)
     }
 
     /// Adds a blob ID to the blocklist.
     ///
     /// Returns whether the ID was newly inserted.
     #[inline]
-    pub fn insert(&mut self, blob_id: BlobId) -> Result<bool> {
+    pub fn insert(&self, blob_id: BlobId) -> Result<bool> {
         let mut guard: std::sync::RwLockWriteGuard<'_, HashSet<BlobId>> =
             self.blocked_blobs.write().expect("mutex poisoned");
-        guard.insert(blob_id);
+        let inserted = guard.insert(blob_id);
         // Update yaml file to add this blob id
         let blobs = BlocklistInner(guard.iter().cloned().collect::<Vec<_>>());
         let mut file = std::fs::OpenOptions::new()
@@ -97,21 +126,21 @@ impl Blocklist {
             .create(true)
             .open(&self.deny_list_path)?;
         serde_yaml::to_writer(&mut file, &blobs)?;
-        Ok(true)
+        Ok(inserted)
     }
 
     /// Removes a blob ID from the blocklist.
     ///
     /// Returns whether the ID was previously blocked.
     #[inline]
-    pub fn remove(&mut self, blob_id: &BlobId) -> Result<bool> {
+    pub fn remove(&self, blob_id: &BlobId) -> Result<bool> {
         let mut guard: std::sync::RwLockWriteGuard<'_, HashSet<BlobId>> =
             self.blocked_blobs.write().expect("mutex poisoned");
-        guard.remove(blob_id);
+        let removed = guard.remove(blob_id);
         let blobs = BlocklistInner(guard.iter().cloned().collect::<Vec<_>>());
 
         if !self.deny_list_path.exists() {
-            return Ok(false);
+            return Ok(removed);
         };
 
         let mut file = std::fs::OpenOptions::new()
@@ -119,7 +148,7 @@ impl Blocklist {
             .truncate(true)
             .open(&self.deny_list_path)?;
         serde_yaml::to_writer(&mut file, &blobs)?;
-        Ok(true)
+        Ok(removed)
     }
 
     /// Loads the blocklist from the file at the given path.
@@ -6,22 +6,28 @@
 use std::{
     collections::HashMap,
     fmt::{Debug, Display},
+    ops::Range,
     path::PathBuf,
-    sync::Arc,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
     time::Instant,
 };
 
 use anyhow::anyhow;
+use bytes::Bytes;
+use chrono::{DateTime, Utc};
 pub use client_types::{WalrusStoreBlob, WalrusStoreBlobApi};
 pub use communication::NodeCommunicationFactory;
-use futures::{Future, FutureExt};
+use futures::{Future, FutureExt, Stream};
 use indicatif::{HumanDuration, MultiProgress};
 use metrics::ClientMetrics;
 use rand::{rngs::ThreadRng, RngCore as _};
-use rayon::{iter::IntoParallelIterator, prelude::*};
-use sui_types::base_types::ObjectID;
+use rayon::{iter::IntoParallelIterator, prelude::*, ThreadPool, ThreadPoolBuilder};
+use sui_types::base_types::{ObjectID, SuiAddress};
 use tokio::{sync::Semaphore, time::Duration};
-use tracing::{Instrument as _, Level};
+use tracing::{Instrument as _, Level, Span};
 use walrus_core::{
     bft,
     encoding::{
@@ -29,20 +35,44 @@ use walrus_core::{
         EncodingAxis,
         EncodingConfig,
         EncodingConfigTrait as _,
+        Primary,
         SliverData,
         SliverPair,
     },
     ensure,
     messages::{BlobPersistenceType, ConfirmationCertificate, SignedStorageConfirmation},
     metadata::{BlobMetadataApi as _, VerifiedBlobMetadataWithId},
+    quilt::{
+        QuiltBuilder,
+        QuiltEntry,
+        QuiltEntryId,
+        QuiltError,
+        QuiltHeader,
+        QuiltIndex,
+        QUILT_HEADER_LENGTH,
+    },
     BlobId,
     EncodingType,
     Epoch,
     EpochCount,
+    PublicKey,
     ShardIndex,
     Sliver,
+    SliverPairIndex,
+    SliverType,
+};
+pub use walrus_rest_client::client::{
+    HttpLayer,
+    HttpService,
+    RequestInfo,
+    RequestObserver,
+    RequestOutcome,
+};
+use walrus_rest_client::{
+    api::{BatchedRecoverySymbolsRequest, BlobStatus, NodeCapabilities, StoredBlobsPage},
+    client::RecoverySymbolsFilter,
+    error::NodeError,
 };
-use walrus_rest_client::{api::BlobStatus, error::NodeError};
 use walrus_sui::{
     client::{
         BlobPersistence,
@@ -52,22 +82,39 @@ use walrus_sui::{
         PostStoreAction,
         ReadClient,
         SuiContractClient,
+        SuiReadClient,
+        UnstakeResult,
+    },
+    types::{
+        move_structs::{BlobWithAttribute, EpochState},
+        Blob,
+        BlobEvent,
+        StakedWal,
+        StorageNode,
     },
-    types::{move_structs::BlobWithAttribute, Blob, BlobEvent, StakedWal},
 };
 use walrus_utils::{backoff::BackoffStrategy, metrics::Registry};
 
 use self::{
-    communication::NodeResult,
+    byte_range::{BlobRange, ByteRange},
+    communication::{NodeReadCommunication, NodeResult},
+    progress::{report_store_progress, StoreProgressEvent},
     refresh::{are_current_previous_different, CommitteesRefresherHandle, RequestKind},
     resource::{PriceComputation, RegisterBlobOp, ResourceManager, StoreOp},
-    responses::{BlobStoreResult, BlobStoreResultWithPath},
+    responses::{
+        BlobAvailability,
+        BlobStoreResult,
+        BlobStoreResultWithPath,
+        NodeRecoverySymbols,
+        QuiltEntryStoreResult,
+        QuiltStoreResult,
+    },
 };
 pub(crate) use crate::utils::{CompletedReasonWeight, WeightedFutures};
 use crate::{
     active_committees::ActiveCommittees,
     config::CommunicationLimits,
-    error::{ClientError, ClientErrorKind, ClientResult},
+    error::{ClientError, ClientErrorKind, ClientResult, SliverRetrievalDiagnosis},
     store_when::StoreWhen,
     utils::{styled_progress_bar, styled_spinner, WeightedResult},
 };
@@ -76,9 +123,11 @@ pub use crate::{
     config::{default_configuration_paths, ClientCommunicationConfig, ClientConfig},
 };
 
+pub mod byte_range;
 pub mod client_types;
 pub mod communication;
 pub mod metrics;
+pub mod progress;
 pub mod refresh;
 pub mod resource;
 pub mod responses;
@@ -93,6 +142,9 @@ pub struct Client<T> {
     // The `Arc` is used to share the encoding config with the `communication_factory` without
     // introducing lifetimes.
     encoding_config: Arc<EncodingConfig>,
+    /// The thread pool on which blobs are encoded and decoded, if the number of encoding threads is
+    /// configured; otherwise, the global rayon thread pool is used.
+    encoding_pool: Option<Arc<ThreadPool>>,
     blocklist: Option<Blocklist>,
     communication_factory: NodeCommunicationFactory,
 }
@@ -134,10 +186,23 @@ impl Client<()> {
             CommunicationLimits::new(&config.communication_config, encoding_config.n_shards());
 
         let encoding_config = Arc::new(encoding_config);
+        let encoding_pool = config
+            .communication_config
+            .encoding_threads
+            .map(|n_threads| {
+                ThreadPoolBuilder::new()
+                    .num_threads(n_threads)
+                    .thread_name(|index| format!("walrus-encoding-{index}"))
+                    .build()
+                    .map(Arc::new)
+            })
+            .transpose()
+            .map_err(ClientError::other)?;
 
         Ok(Self {
             sui_client: (),
             encoding_config: encoding_config.clone(),
+            encoding_pool,
             communication_limits,
             committees_handle,
             blocklist: None,
@@ -157,6 +222,7 @@ impl Client<()> {
             sui_client: _,
             committees_handle,
             encoding_config,
+            encoding_pool,
             communication_limits,
             blocklist,
             communication_factory: node_client_factory,
@@ -166,6 +232,7 @@ impl Client<()> {
             sui_client,
             committees_handle,
             encoding_config,
+            encoding_pool,
             communication_limits,
             blocklist,
             communication_factory: node_client_factory,
@@ -173,6 +240,18 @@ impl Client<()> {
     }
 }
 
+impl Client<SuiReadClient> {
+    /// Creates a new read-only client that reads from the Sui RPC nodes in
+    /// [`ClientConfig::rpc_urls`], and starts a committees refresher process in the background.
+    ///
+    /// The client does not load a Sui wallet, so a configuration created with
+    /// [`ClientConfig::new_read_only`] from the RPC URLs and the Walrus object IDs is sufficient.
+    pub async fn new_read_only(config: ClientConfig) -> ClientResult<Self> {
+        let sui_read_client = config.new_rpc_read_client().await?;
+        Self::new_read_client_with_refresher(config, sui_read_client).await
+    }
+}
+
 impl<T: ReadClient> Client<T> {
     /// Creates a new read client starting from a config file.
     pub async fn new_read_client(
@@ -254,6 +333,317 @@ impl<T: ReadClient> Client<T> {
         SliverData<U>: TryFrom<Sliver>,
     {
         tracing::debug!("starting to read blob");
+        let certified_epoch = self.read_epoch(blob_id, blob_status).await?;
+        self.read_metadata_and_slivers::<U>(certified_epoch, blob_id)
+            .await
+    }
+
+    /// Reads the bytes in `range` of the blob, retrying if the operation fails due to epoch
+    /// change.
+    pub async fn read_blob_range_retry_committees(
+        &self,
+        blob_id: &BlobId,
+        range: ByteRange,
+    ) -> ClientResult<BlobRange> {
+        self.retry_if_notified_epoch_change(|| self.read_blob_range(blob_id, range))
+            .await
+    }
+
+    /// Reads the bytes in `range` of the blob.
+    ///
+    /// The bytes are read directly from the primary source slivers that contain them, which are
+    /// verified against the blob's metadata. If any of these slivers cannot be retrieved, the blob
+    /// is reconstructed from all slivers instead.
+    #[tracing::instrument(level = Level::ERROR, skip_all, fields(%blob_id, ?range))]
+    pub async fn read_blob_range(
+        &self,
+        blob_id: &BlobId,
+        range: ByteRange,
+    ) -> ClientResult<BlobRange> {
+        tracing::debug!("starting to read blob range");
+        let certified_epoch = self.read_epoch(blob_id, None).await?;
+        let metadata = self.retrieve_metadata(certified_epoch, blob_id).await?;
+        self.read_range_with_metadata(certified_epoch, &metadata, range)
+            .await
+    }
+
+    /// Reads the bytes in `range` of the blob with the given verified metadata.
+    async fn read_range_with_metadata(
+        &self,
+        certified_epoch: Epoch,
+        metadata: &VerifiedBlobMetadataWithId,
+        range: ByteRange,
+    ) -> ClientResult<BlobRange> {
+        let blob_size = metadata.metadata().unencoded_length();
+        let range = range
+            .resolve(blob_size)
+            .ok_or(ClientErrorKind::RangeNotSatisfiable { blob_size })?;
+
+        let data = match self
+            .read_range_from_source_slivers(certified_epoch, metadata, range.clone())
+            .await
+        {
+            Ok(data) => data,
+            Err(error) => {
+                tracing::info!(
+                    %error,
+                    "reading the range from the source slivers failed; reconstructing the blob"
+                );
+                let blob = self
+                    .request_slivers_and_decode::<Primary>(certified_epoch, metadata)
+                    .await?;
+                blob[to_usize(range.start)?..to_usize(range.end)?].to_vec()
+            }
+        };
+
+        Ok(BlobRange {
+            data,
+            range,
+            blob_size,
+        })
+    }
+
+    /// Reads the index of the quilt stored in the blob with ID `quilt_id`.
+    ///
+    /// Only the header and the index of the quilt are read, with range reads of the blob.
+    #[tracing::instrument(level = Level::ERROR, skip_all, fields(%quilt_id))]
+    pub async fn read_quilt_index(&self, quilt_id: &BlobId) -> ClientResult<QuiltIndex> {
+        let certified_epoch = self.read_epoch(quilt_id, None).await?;
+        let metadata = self.retrieve_metadata(certified_epoch, quilt_id).await?;
+        self.read_quilt_index_with_metadata(certified_epoch, &metadata)
+            .await
+    }
+
+    /// Reads the entry of a quilt with the given ID, returning the entry and its data.
+    ///
+    /// Only the header and the index of the quilt and the data of the entry are read, with range
+    /// reads of the blob.
+    #[tracing::instrument(level = Level::ERROR, skip_all, fields(%entry_id))]
+    pub async fn read_quilt_entry(
+        &self,
+        entry_id: &QuiltEntryId,
+    ) -> ClientResult<(QuiltEntry, Vec<u8>)> {
+        self.read_quilt_entry_inner(&entry_id.quilt_id, |index| {
+            index.get(entry_id.index).cloned()
+        })
+        .await
+    }
+
+    /// Reads the entry with the given `identifier` of the quilt stored in the blob with ID
+    /// `quilt_id`, returning the entry and its data.
+    #[tracing::instrument(level = Level::ERROR, skip_all, fields(%quilt_id, identifier))]
+    pub async fn read_quilt_entry_by_identifier(
+        &self,
+        quilt_id: &BlobId,
+        identifier: &str,
+    ) -> ClientResult<(QuiltEntry, Vec<u8>)> {
+        self.read_quilt_entry_inner(quilt_id, |index| {
+            let (_, entry) = index
+                .find(identifier)
+                .ok_or_else(|| QuiltError::IdentifierNotFound(identifier.to_owned()))?;
+            Ok(entry.clone())
+        })
+        .await
+    }
+
+    async fn read_quilt_entry_inner(
+        &self,
+        quilt_id: &BlobId,
+        select_entry: impl FnOnce(&QuiltIndex) -> Result<QuiltEntry, QuiltError>,
+    ) -> ClientResult<(QuiltEntry, Vec<u8>)> {
+        let certified_epoch = self.read_epoch(quilt_id, None).await?;
+        let metadata = self.retrieve_metadata(certified_epoch, quilt_id).await?;
+        let index = self
+            .read_quilt_index_with_metadata(certified_epoch, &metadata)
+            .await?;
+        let entry = select_entry(&index).map_err(ClientErrorKind::InvalidQuilt)?;
+
+        let range = entry.range();
+        if range.is_empty() {
+            return Ok((entry, vec![]));
+        }
+        let data = self
+            .read_quilt_range(certified_epoch, &metadata, range)
+            .await?;
+        Ok((entry, data))
+    }
+
+    async fn read_quilt_index_with_metadata(
+        &self,
+        certified_epoch: Epoch,
+        metadata: &VerifiedBlobMetadataWithId,
+    ) -> ClientResult<QuiltIndex> {
+        let header_bytes = self
+            .read_quilt_range(certified_epoch, metadata, 0..QUILT_HEADER_LENGTH as u64)
+            .await?;
+        let header =
+            QuiltHeader::from_bytes(&header_bytes).map_err(ClientErrorKind::InvalidQuilt)?;
+        let index_range = header.index_range();
+        let index_bytes = if index_range.is_empty() {
+            vec![]
+        } else {
+            self.read_quilt_range(certified_epoch, metadata, index_range)
+                .await?
+        };
+        QuiltIndex::from_bytes(&header, &index_bytes)
+            .map_err(|error| ClientErrorKind::InvalidQuilt(error).into())
+    }
+
+    /// Reads the non-empty `range` of a quilt, failing if the quilt ends before its end.
+    async fn read_quilt_range(
+        &self,
+        certified_epoch: Epoch,
+        metadata: &VerifiedBlobMetadataWithId,
+        range: Range<u64>,
+    ) -> ClientResult<Vec<u8>> {
+        ensure!(
+            range.end <= metadata.metadata().unencoded_length(),
+            ClientErrorKind::InvalidQuilt(QuiltError::Truncated).into()
+        );
+        let byte_range = ByteRange::Bounded {
+            start: range.start,
+            end: range.end - 1,
+        };
+        Ok(self
+            .read_range_with_metadata(certified_epoch, metadata, byte_range)
+            .await?
+            .data)
+    }
+
+    /// Reads the bytes in `range` from the primary source slivers that contain them.
+    async fn read_range_from_source_slivers(
+        &self,
+        certified_epoch: Epoch,
+        metadata: &VerifiedBlobMetadataWithId,
+        range: Range<u64>,
+    ) -> ClientResult<Vec<u8>> {
+        let blob_size = metadata.metadata().unencoded_length();
+        let range_decoder = self
+            .encoding_config
+            .get_for_type(metadata.metadata().encoding_type())
+            .get_range_decoder(blob_size, range)
+            .map_err(ClientError::other)?;
+
+        let committees = self.get_committees().await?;
+        let comms = self
+            .communication_factory
+            .node_read_communications(&committees, certified_epoch)?;
+        let n_shards = self.encoding_config.n_shards();
+
+        let sliver_indices = range_decoder.primary_sliver_indices();
+        let slivers = futures::future::try_join_all(sliver_indices.map(|index| {
+            let shard_index = SliverPairIndex(index).to_shard_index(n_shards, metadata.blob_id());
+            let comm = comms
+                .iter()
+                .find(|comm| comm.node.shard_ids.contains(&shard_index));
+            async move {
+                let comm = comm.ok_or(ClientErrorKind::NotEnoughSlivers)?;
+                let NodeResult(_, _, _, result) = comm
+                    .retrieve_verified_sliver::<Primary>(metadata, shard_index)
+                    .instrument(comm.span.clone())
+                    .await;
+                result.map_err(ClientError::other)
+            }
+        }))
+        .await?;
+
+        range_decoder.decode(&slivers).map_err(ClientError::other)
+    }
+
+    /// Streams the verified bytes of the blob's sliver with the given sliver pair index.
+    ///
+    /// The bytes are yielded as they are received from the storage node storing the sliver. The
+    /// sliver can only be verified against the blob metadata once it is received completely, so
+    /// the stream ends with an error if verification fails, in which case the bytes yielded
+    /// before must be discarded.
+    ///
+    /// See [`walrus_rest_client::client::Client::stream_verified_sliver`].
+    pub async fn stream_sliver<A: EncodingAxis>(
+        &self,
+        blob_id: &BlobId,
+        sliver_pair_index: SliverPairIndex,
+    ) -> ClientResult<impl Stream<Item = Result<Bytes, NodeError>> + Send + 'static> {
+        let certified_epoch = self.read_epoch(blob_id, None).await?;
+        let metadata = self.retrieve_metadata(certified_epoch, blob_id).await?;
+
+        let committees = self.get_committees().await?;
+        let comms = self
+            .communication_factory
+            .node_read_communications(&committees, certified_epoch)?;
+        let shard_index =
+            sliver_pair_index.to_shard_index(self.encoding_config.n_shards(), blob_id);
+        let comm = comms
+            .iter()
+            .find(|comm| comm.node.shard_ids.contains(&shard_index))
+            .ok_or(ClientErrorKind::NotEnoughSlivers)?;
+
+        Ok(comm.client.stream_verified_sliver::<A>(
+            sliver_pair_index,
+            Arc::new(metadata),
+            self.encoding_config.clone(),
+        ))
+    }
+
+    /// Fetches the recovery symbols for the blob's target slivers from all storage nodes, with a
+    /// single request per node.
+    ///
+    /// For each of the sliver pairs in `target_pairs`, each node returns the symbols of all its
+    /// slivers of the orthogonal axis that can be used to recover the target sliver of axis `A`.
+    /// The symbols are returned along with the outcome of their verification against the blob
+    /// metadata, so that symbols failing verification can be identified rather than discarded.
+    /// The nodes must support batched requests for recovery symbols; for other nodes, the request
+    /// fails.
+    pub async fn get_recovery_symbols_batch<A: EncodingAxis>(
+        &self,
+        blob_id: &BlobId,
+        target_pairs: &[SliverPairIndex],
+    ) -> ClientResult<Vec<NodeRecoverySymbols>> {
+        let certified_epoch = self.read_epoch(blob_id, None).await?;
+        let metadata = Arc::new(self.retrieve_metadata(certified_epoch, blob_id).await?);
+
+        let committees = self.get_committees().await?;
+        let comms = self
+            .communication_factory
+            .node_read_communications(&committees, certified_epoch)?;
+        let n_shards = self.encoding_config.n_shards();
+        let requests: Vec<_> = target_pairs
+            .iter()
+            .map(|pair_index| {
+                let target_sliver = pair_index.to_sliver_index::<A>(n_shards);
+                let filter = RecoverySymbolsFilter::recovers(target_sliver, A::sliver_type());
+                let request = BatchedRecoverySymbolsRequest::new(
+                    *blob_id,
+                    &filter,
+                    target_sliver,
+                    A::sliver_type(),
+                );
+                (request, metadata.clone())
+            })
+            .collect();
+
+        Ok(futures::future::join_all(comms.iter().map(|comm| {
+            let requests = requests.clone();
+            async move {
+                let symbols = comm
+                    .client
+                    .list_and_check_recovery_symbols_batch(requests, self.encoding_config.clone())
+                    .await
+                    .and_then(|results| results.into_iter().collect());
+                NodeRecoverySymbols {
+                    node_public_key: comm.node.public_key.clone(),
+                    symbols,
+                }
+            }
+        }))
+        .await)
+    }
+
+    /// Returns the epoch whose committee serves reads of the blob.
+    async fn read_epoch(
+        &self,
+        blob_id: &BlobId,
+        blob_status: Option<BlobStatus>,
+    ) -> ClientResult<Epoch> {
         self.check_blob_id(blob_id)?;
         let committees = self.get_committees().await?;
 
@@ -283,8 +673,7 @@ impl<T: ReadClient> Client<T> {
             }));
         }
 
-        self.read_metadata_and_slivers::<U>(certified_epoch, blob_id)
-            .await
+        Ok(certified_epoch)
     }
 
     async fn read_metadata_and_slivers<U>(
@@ -387,6 +776,98 @@ impl<T: ReadClient> Client<T> {
             })
     }
 
+    /// Returns the estimated time at which the blob expires.
+    ///
+    /// Returns `None` if there is no permanent blob object for the blob ID, for example if the blob
+    /// is only stored as deletable blob, which can be deleted at any time.
+    pub async fn estimated_blob_expiry(
+        &self,
+        blob_id: &BlobId,
+    ) -> ClientResult<Option<DateTime<Utc>>> {
+        let BlobStatus::Permanent { end_epoch, .. } = self
+            .get_blob_status_with_retries(blob_id, &self.sui_client)
+            .await?
+        else {
+            return Ok(None);
+        };
+        self.estimated_start_of_epoch(end_epoch).await.map(Some)
+    }
+
+    /// Returns the size and availability of the blob, retrying if the operation fails due to
+    /// epoch change.
+    pub async fn blob_availability_retry_committees(
+        &self,
+        blob_id: &BlobId,
+    ) -> ClientResult<BlobAvailability> {
+        self.retry_if_notified_epoch_change(|| self.blob_availability(blob_id))
+            .await
+    }
+
+    /// Returns the size and availability of the blob, without reading its slivers.
+    ///
+    /// The size is read from the metadata of the blob, which is retrieved from the storage nodes,
+    /// and the certification epoch and the expiry are derived from the status of the blob.
+    #[tracing::instrument(level = Level::ERROR, skip_all, fields(%blob_id))]
+    pub async fn blob_availability(&self, blob_id: &BlobId) -> ClientResult<BlobAvailability> {
+        self.check_blob_id(blob_id)?;
+        let blob_status = self
+            .get_blob_status_with_retries(blob_id, &self.sui_client)
+            .await?;
+        let certified_epoch = blob_status
+            .initial_certified_epoch()
+            .ok_or_else(|| ClientError::from(ClientErrorKind::BlobIdDoesNotExist))?;
+        let read_epoch = self.read_epoch(blob_id, Some(blob_status)).await?;
+        let metadata = self.retrieve_metadata(read_epoch, blob_id).await?;
+        let estimated_expiry = match blob_status {
+            BlobStatus::Permanent { end_epoch, .. } => {
+                Some(self.estimated_start_of_epoch(end_epoch).await?)
+            }
+            _ => None,
+        };
+
+        Ok(BlobAvailability {
+            blob_size: metadata.metadata().unencoded_length(),
+            certified_epoch,
+            estimated_expiry,
+        })
+    }
+
+    /// Returns the estimated time at which the given epoch starts, which is in the past for past
+    /// epochs.
+    async fn estimated_start_of_epoch(&self, epoch: Epoch) -> ClientResult<DateTime<Utc>> {
+        let epoch_duration = self
+            .sui_client
+            .fixed_system_parameters()
+            .await?
+            .epoch_duration;
+        let estimated_start_of_current_epoch = match self.sui_client.epoch_state().await? {
+            EpochState::EpochChangeDone(epoch_start)
+            | EpochState::NextParamsSelected(epoch_start) => epoch_start,
+            EpochState::EpochChangeSync(_) => Utc::now(),
+        };
+        let current_epoch = self.get_committees().await?.epoch();
+
+        Ok(estimated_start_of_current_epoch + epoch_duration * epoch.saturating_sub(current_epoch))
+    }
+
+    /// Gets the price computation with the current prices and buyer subsidy rate.
+    ///
+    /// The prices are read from the system object at the time of the call, through the cache of
+    /// the Sui client if it is configured with one, so that cost estimates reflect the prices set
+    /// at an epoch change right away instead of after the next refresh of the committees.
+    pub async fn get_price_computation(&self) -> ClientResult<PriceComputation> {
+        let (storage_price, write_price) = self
+            .sui_client
+            .storage_and_write_price_per_unit_size()
+            .await?;
+        let buyer_subsidy_rate = self.sui_client.buyer_subsidy_rate().await?;
+        Ok(PriceComputation::new(
+            storage_price,
+            write_price,
+            buyer_subsidy_rate,
+        ))
+    }
+
     /// Executes the function while also awaiiting on the change notification.
     ///
     /// Returns a [`ClientErrorKind::CommitteeChangeNotified`] error if the client is notified that
@@ -483,10 +964,13 @@ impl Client<SuiContractClient> {
     /// Stores a list of blobs to Walrus, retrying if it fails because of epoch change.
     /// Similar to `[Client::reserve_and_store_blobs_retry_committees]`, except the result
     /// includes the corresponding path for blob.
+    ///
+    /// The blobs can be provided as [`BlobSource`s][crate::blob_source::BlobSource], to encode
+    /// memory-mapped copies of files without reading them into memory first.
     #[tracing::instrument(skip_all, fields(blob_id))]
-    pub async fn reserve_and_store_blobs_retry_committees_with_path(
+    pub async fn reserve_and_store_blobs_retry_committees_with_path<B: AsRef<[u8]>>(
         &self,
-        blobs_with_paths: &[(PathBuf, Vec<u8>)],
+        blobs_with_paths: &[(PathBuf, B)],
         encoding_type: EncodingType,
         epochs_ahead: EpochCount,
         store_when: StoreWhen,
@@ -496,13 +980,40 @@ impl Client<SuiContractClient> {
         // Not using Path as identifier because it's not unique.
         let blobs = blobs_with_paths
             .iter()
-            .map(|(_, blob)| blob.as_slice())
+            .map(|(_, blob)| blob.as_ref())
             .collect::<Vec<_>>();
         let blobs_with_identifiers =
             WalrusStoreBlob::<String>::default_unencoded_blobs_from_slice(&blobs);
 
         let encoded_blobs = self.encode_blobs(blobs_with_identifiers, encoding_type)?;
+        self.reserve_and_store_encoded_blobs_retry_committees_with_path(
+            blobs_with_paths,
+            encoded_blobs,
+            epochs_ahead,
+            store_when,
+            persistence,
+            post_store,
+        )
+        .await
+    }
 
+    /// Stores a list of blobs that are already encoded to Walrus, retrying if it fails because of
+    /// epoch change.
+    ///
+    /// The `encoded_blobs` must be the result of [`Client::encode_blobs`] for the blobs in
+    /// `blobs_with_paths`, created with
+    /// [`WalrusStoreBlob::default_unencoded_blobs_from_slice`]. This allows inspecting the
+    /// metadata of the blobs before storing them, without encoding them a second time.
+    #[tracing::instrument(skip_all, fields(blob_id))]
+    pub async fn reserve_and_store_encoded_blobs_retry_committees_with_path<B: AsRef<[u8]>>(
+        &self,
+        blobs_with_paths: &[(PathBuf, B)],
+        encoded_blobs: Vec<WalrusStoreBlob<'_, String>>,
+        epochs_ahead: EpochCount,
+        store_when: StoreWhen,
+        persistence: BlobPersistence,
+        post_store: PostStoreAction,
+    ) -> ClientResult<Vec<BlobStoreResultWithPath>> {
         let mut completed_blobs = self
             .retry_if_error_epoch_change(|| {
                 self.reserve_and_store_encoded_blobs(
@@ -538,6 +1049,60 @@ impl Client<SuiContractClient> {
         Ok(results)
     }
 
+    /// Packs the entries into a quilt and stores it as a single blob, retrying if it fails because
+    /// of epoch change.
+    ///
+    /// The entries are given as pairs of unique identifiers and data, and are stored in the given
+    /// order. Returns the result of storing the quilt together with the IDs of its entries, which
+    /// can be read individually with [`Client::read_quilt_entry`].
+    #[tracing::instrument(skip_all, fields(n_entries = entries.len()))]
+    pub async fn reserve_and_store_quilt_retry_committees<B: AsRef<[u8]>>(
+        &self,
+        entries: &[(String, B)],
+        encoding_type: EncodingType,
+        epochs_ahead: EpochCount,
+        store_when: StoreWhen,
+        persistence: BlobPersistence,
+        post_store: PostStoreAction,
+    ) -> ClientResult<QuiltStoreResult> {
+        let mut builder = QuiltBuilder::new();
+        for (identifier, data) in entries {
+            builder
+                .add_entry(identifier.clone(), data.as_ref())
+                .map_err(ClientErrorKind::InvalidQuilt)?;
+        }
+        let (quilt, index) = builder.build().map_err(ClientErrorKind::InvalidQuilt)?;
+
+        let blob_store_result = self
+            .reserve_and_store_blobs_retry_committees(
+                &[quilt.as_slice()],
+                encoding_type,
+                epochs_ahead,
+                store_when,
+                persistence,
+                post_store,
+                None,
+            )
+            .await?
+            .pop()
+            .ok_or_else(|| ClientError::store_blob_internal("the quilt was not stored".into()))?;
+
+        let quilt_id = blob_store_result.blob_id();
+        let entries = index
+            .entries()
+            .iter()
+            .zip(0..)
+            .map(|(entry, index)| QuiltEntryStoreResult {
+                identifier: entry.identifier.clone(),
+                entry_id: quilt_id.map(|quilt_id| QuiltEntryId { quilt_id, index }),
+            })
+            .collect();
+        Ok(QuiltStoreResult {
+            blob_store_result,
+            entries,
+        })
+    }
+
     /// Encodes the blob, reserves & registers the space on chain, and stores the slivers to the
     /// storage nodes. Finally, the function aggregates the storage confirmations and posts the
     /// [`ConfirmationCertificate`] on chain.
@@ -650,24 +1215,34 @@ impl Client<SuiContractClient> {
 
         let multi_pb = Arc::new(MultiProgress::new());
 
+        // The encoding runs on the rayon threads, on which the span of the store is not entered.
+        let span = Span::current();
         // Encode each blob into sliver pairs and metadata. Filters out failed blobs and continue.
-        let results = blobs_with_identifiers
-            .into_par_iter()
-            .map(|blob| {
-                let multi_pb_clone = multi_pb.clone();
-                let unencoded_blob = blob.get_blob();
-                let encode_result = self.encode_pairs_and_metadata(
-                    unencoded_blob,
-                    encoding_type,
-                    multi_pb_clone.as_ref(),
-                );
-                blob.with_encode_result(encode_result)
-            })
-            .collect::<Vec<_>>();
+        let results = self.install_encoding_pool(|| {
+            blobs_with_identifiers
+                .into_par_iter()
+                .map(|blob| {
+                    let _entered = span.enter();
+                    let multi_pb_clone = multi_pb.clone();
+                    let unencoded_blob = blob.get_blob();
+                    let encode_result = self.encode_pairs_and_metadata(
+                        unencoded_blob,
+                        encoding_type,
+                        multi_pb_clone.as_ref(),
+                    );
+                    blob.with_encode_result(encode_result)
+                })
+                .collect::<Vec<_>>()
+        });
 
         let mut final_results = Vec::with_capacity(results.len());
         for result in results {
             let cur = result?;
+            if let Some(metadata) = cur.get_metadata() {
+                report_store_progress(|| StoreProgressEvent::Encoded {
+                    blob_id: *metadata.blob_id(),
+                });
+            }
             final_results.push(cur);
         }
 
@@ -675,6 +1250,7 @@ impl Client<SuiContractClient> {
     }
 
     /// Encodes a blob into sliver pairs and metadata.
+    #[tracing::instrument(skip_all, fields(blob_size = blob.len()))]
     pub fn encode_pairs_and_metadata(
         &self,
         blob: &[u8],
@@ -687,9 +1263,11 @@ impl Client<SuiContractClient> {
         let encode_start_timer = Instant::now();
 
         let (pairs, metadata) = self
-            .encoding_config
-            .get_for_type(encoding_type)
-            .encode_with_metadata(blob)
+            .install_encoding_pool(|| {
+                self.encoding_config
+                    .get_for_type(encoding_type)
+                    .encode_with_metadata(blob)
+            })
             .map_err(ClientError::other)?;
 
         let duration = encode_start_timer.elapsed();
@@ -850,7 +1428,10 @@ impl Client<SuiContractClient> {
             })
             .collect();
 
-        // Certify all blobs on Sui.
+        // Certify and extend all blobs on Sui in one transaction. This cannot be combined with the
+        // registration: the storage nodes only accept the slivers of blobs whose registration they
+        // have seen on chain, and only sign the confirmations for the certificates once they
+        // store the slivers.
         let sui_cert_timer = Instant::now();
         let cert_and_extend_results = self
             .sui_client
@@ -861,6 +1442,16 @@ impl Client<SuiContractClient> {
                 blobs on Sui");
                 ClientError::from(ClientErrorKind::CertificationFailed(e))
             })?;
+        for (params, result) in cert_and_extend_params
+            .iter()
+            .zip(&cert_and_extend_results)
+            .filter(|(params, _)| params.certificate.is_some())
+        {
+            report_store_progress(|| StoreProgressEvent::Certified {
+                blob_id: params.blob.blob_id,
+                transaction_digest: result.transaction_digest,
+            });
+        }
         let sui_cert_timer_duration = sui_cert_timer.elapsed();
         tracing::info!(
             duration = ?sui_cert_timer_duration,
@@ -1128,6 +1719,34 @@ impl Client<SuiContractClient> {
         Ok(())
     }
 
+    /// Unstakes the staked WAL with the given object ID.
+    ///
+    /// See [`SuiContractClient::unstake`] for when the WAL is withdrawn directly and when its
+    /// withdrawal is only requested.
+    pub async fn unstake(&self, staked_wal_id: ObjectID) -> ClientResult<UnstakeResult> {
+        tracing::debug!(%staked_wal_id, "unstaking WAL");
+        Ok(self.sui_client.unstake(staked_wal_id).await?)
+    }
+
+    /// Returns the staked WAL owned by `owner`, along with the rewards in FROST earned by each of
+    /// them so far.
+    ///
+    /// If `owner` is `None`, the current wallet address is used.
+    pub async fn owned_staked_wal_with_rewards(
+        &self,
+        owner: Option<SuiAddress>,
+    ) -> ClientResult<Vec<(StakedWal, u64)>> {
+        let read_client = self.sui_client.read_client();
+        let staked_wal = self.sui_client.owned_staked_wal(owner).await?;
+        let rewards = futures::future::try_join_all(
+            staked_wal
+                .iter()
+                .map(|staked_wal| read_client.get_staking_rewards(staked_wal)),
+        )
+        .await?;
+        Ok(staked_wal.into_iter().zip(rewards).collect())
+    }
+
     /// Exchanges the provided amount of SUI (in MIST) for WAL using the specified exchange.
     pub async fn exchange_sui_for_wal(
         &self,
@@ -1161,6 +1780,46 @@ impl<T> Client<T> {
         self
     }
 
+    /// Sends all requests to the storage nodes through the provided tower layer.
+    ///
+    /// This allows injecting middleware, such as retries, authentication headers, or custom
+    /// metrics, below the client's own tracing and metrics. Layers are applied in the order in
+    /// which they are added, so the layer added last is the outermost one. The connections to the
+    /// storage nodes are no longer shared with clients created before adding the layer.
+    pub fn with_http_layer(mut self, layer: HttpLayer) -> Self {
+        self.communication_factory = self.communication_factory.with_http_layer(layer);
+        self
+    }
+
+    /// Notifies the provided observer of all requests to the storage nodes and of their outcomes.
+    ///
+    /// The observer receives the method, URL, and attempt number of each request, as well as the
+    /// HTTP status and the duration of its response, which allows plugging the client into custom
+    /// metrics or logging systems. As for [`Self::with_http_layer`], the connections to the
+    /// storage nodes are no longer shared with clients created before adding the observer.
+    pub fn with_request_observer(mut self, observer: Arc<dyn RequestObserver>) -> Self {
+        self.communication_factory = self.communication_factory.with_request_observer(observer);
+        self
+    }
+
+    /// Returns a client with the given Sui client, which shares the configuration, the committees
+    /// handle, the blocklist, and the connections to the storage nodes with `self`.
+    ///
+    /// This allows several clients in the same process, for example with different wallets, to use
+    /// the same connection pools instead of each connecting to the storage nodes separately.
+    pub fn clone_with_client<C>(&self, sui_client: C) -> Client<C> {
+        Client {
+            config: self.config.clone(),
+            sui_client,
+            communication_limits: self.communication_limits.clone(),
+            committees_handle: self.committees_handle.clone(),
+            encoding_config: self.encoding_config.clone(),
+            encoding_pool: self.encoding_pool.clone(),
+            blocklist: self.blocklist.clone(),
+            communication_factory: self.communication_factory.clone(),
+        }
+    }
+
     /// Stores the already-encoded metadata and sliver pairs for a blob into Walrus, by sending
     /// sliver pairs to at least 2f+1 shards.
     ///
@@ -1195,11 +1854,13 @@ impl<T> Client<T> {
             .communication_factory
             .node_write_communications(&committees, Arc::new(Semaphore::new(sliver_write_limit)))?;
 
+        let required_weight = bft::min_n_correct(committees.n_shards()).get().into();
         let progress_bar = {
-            let pb = styled_progress_bar(bft::min_n_correct(committees.n_shards()).get().into());
+            let pb = styled_progress_bar(required_weight);
             pb.set_message(format!("sending slivers ({})", metadata.blob_id()));
             multi_pb.add(pb)
         };
+        let confirmed_weight = Arc::new(AtomicUsize::new(0));
 
         let mut requests = WeightedFutures::new(comms.iter().map(|n| {
             n.store_metadata_and_pairs(
@@ -1211,10 +1872,25 @@ impl<T> Client<T> {
             )
             .inspect({
                 let value = progress_bar.clone();
+                let confirmed_weight = confirmed_weight.clone();
+                let blob_id = *metadata.blob_id();
                 move |result| {
                     if result.is_ok() && !value.is_finished() {
                         value.inc(result.1.try_into().expect("the weight fits a usize"))
                     }
+                    if result.is_ok() {
+                        let confirmed =
+                            confirmed_weight.fetch_add(result.1, Ordering::Relaxed) + result.1;
+                        report_store_progress(|| StoreProgressEvent::NodeConfirmation {
+                            blob_id,
+                            node_index: result.2,
+                            weight: result.1,
+                            confirmed_weight: confirmed,
+                            required_weight: required_weight
+                                .try_into()
+                                .expect("the number of shards fits a usize"),
+                        });
+                    }
                 }
             })
         }));
@@ -1451,8 +2127,14 @@ impl<T> Client<T> {
 
         progress_bar.finish_with_message("slivers received");
 
-        let mut n_not_found = 0; // Counts the number of "not found" status codes received.
-        let mut n_forbidden = 0; // Counts the number of "forbidden" status codes received.
+        let mut diagnosis = SliverRetrievalDiagnosis::new(
+            SliverType::for_encoding::<U>(),
+            self.encoding_config
+                .get_for_type(metadata.metadata().encoding_type())
+                .n_source_symbols::<U>()
+                .get()
+                .into(),
+        );
         let slivers = requests
             .take_results()
             .into_iter()
@@ -1460,16 +2142,14 @@ impl<T> Client<T> {
                 result
                     .map_err(|error| {
                         tracing::debug!(%node, %error, "retrieving sliver failed");
-                        if error.is_status_not_found() {
-                            n_not_found += 1;
-                        } else if error.is_blob_blocked() {
-                            n_forbidden += 1;
-                        }
+                        diagnosis.record_failure(node_public_key(&comms, node), &error);
                     })
                     .ok()
             })
             .collect::<Vec<_>>();
+        diagnosis.record_valid_slivers(slivers.len());
 
+        let (n_not_found, n_forbidden) = (diagnosis.n_not_found, diagnosis.n_forbidden);
         if committees.is_quorum(n_not_found + n_forbidden) {
             return if n_not_found > n_forbidden {
                 Err(ClientErrorKind::BlobIdDoesNotExist.into())
@@ -1478,8 +2158,9 @@ impl<T> Client<T> {
             };
         }
 
-        if let Some((blob, _meta)) = decoder
-            .decode_and_verify(metadata.blob_id(), slivers)
+        // The slivers are decoded in parallel on the encoding thread pool.
+        if let Some((blob, _meta)) = self
+            .install_encoding_pool(|| decoder.decode_and_verify(metadata.blob_id(), slivers))
             .map_err(ClientError::other)?
         {
             // We have enough to decode the blob.
@@ -1490,27 +2171,25 @@ impl<T> Client<T> {
             tracing::info!(
                 "blob decoding with initial set of slivers failed; requesting additional slivers"
             );
-            self.decode_sliver_by_sliver(
-                &mut requests,
-                &mut decoder,
-                metadata,
-                n_not_found,
-                n_forbidden,
-            )
-            .await
+            self.decode_sliver_by_sliver(&mut requests, &mut decoder, metadata, &comms, diagnosis)
+                .await
         }
     }
 
     /// Decodes the blob of given blob ID by requesting slivers and trying to decode at each new
     /// sliver it receives.
+    ///
+    /// If the slivers are exhausted before the blob is decoded, returns a [`ClientError`] of kind
+    /// [`ClientErrorKind::NotEnoughValidSlivers`] with the `diagnosis`, updated with the results
+    /// of the additional requests.
     #[tracing::instrument(level = Level::ERROR, skip_all)]
     async fn decode_sliver_by_sliver<'a, I, Fut, U>(
         &self,
         requests: &mut WeightedFutures<I, Fut, NodeResult<SliverData<U>, NodeError>>,
         decoder: &mut BlobDecoderEnum<'a, U>,
         metadata: &VerifiedBlobMetadataWithId,
-        mut n_not_found: usize,
-        mut n_forbidden: usize,
+        comms: &[NodeReadCommunication<'_>],
+        mut diagnosis: SliverRetrievalDiagnosis,
     ) -> ClientResult<Vec<u8>>
     where
         U: EncodingAxis,
@@ -1530,8 +2209,11 @@ impl<T> Client<T> {
         {
             match result {
                 Ok(sliver) => {
-                    let result = decoder
-                        .decode_and_verify(metadata.blob_id(), [sliver])
+                    diagnosis.record_valid_slivers(1);
+                    let result = self
+                        .install_encoding_pool(|| {
+                            decoder.decode_and_verify(metadata.blob_id(), [sliver])
+                        })
                         .map_err(ClientError::other)?;
                     if let Some((blob, _meta)) = result {
                         return Ok(blob);
@@ -1539,11 +2221,8 @@ impl<T> Client<T> {
                 }
                 Err(error) => {
                     tracing::debug!(%node, %error, "retrieving sliver failed");
-                    if error.is_status_not_found() {
-                        n_not_found += 1;
-                    } else if error.is_blob_blocked() {
-                        n_forbidden += 1;
-                    }
+                    diagnosis.record_failure(node_public_key(comms, node), &error);
+                    let (n_not_found, n_forbidden) = (diagnosis.n_not_found, diagnosis.n_forbidden);
                     if self
                         .get_committees()
                         .await?
@@ -1559,7 +2238,8 @@ impl<T> Client<T> {
             }
         }
         // We have exhausted all the slivers but were not able to reconstruct the blob.
-        Err(ClientErrorKind::NotEnoughSlivers.into())
+        tracing::debug!(%diagnosis, "not enough valid slivers to decode the blob");
+        Err(ClientErrorKind::NotEnoughValidSlivers(Box::new(diagnosis)).into())
     }
 
     /// Requests the metadata from storage nodes, and keeps the first reply that correctly verifies.
@@ -1761,7 +2441,7 @@ impl<T> Client<T> {
 
     /// Returns a [`ClientError`] with [`ClientErrorKind::BlobIdBlocked`] if the provided blob ID is
     /// contained in the blocklist.
-    fn check_blob_id(&self, blob_id: &BlobId) -> ClientResult<()> {
+    pub fn check_blob_id(&self, blob_id: &BlobId) -> ClientResult<()> {
         if let Some(blocklist) = &self.blocklist {
             if blocklist.is_blocked(blob_id) {
                 tracing::debug!(%blob_id, "encountered blocked blob ID");
@@ -1771,6 +2451,40 @@ impl<T> Client<T> {
         Ok(())
     }
 
+    /// Lists the IDs of the blobs that the storage node stores for the shard, one page at a time.
+    ///
+    /// Returns at most `limit` blob IDs in ascending order, starting after the blob ID `cursor` if
+    /// provided. The remaining blob IDs can be listed by passing the `next_cursor` of the returned
+    /// page as `cursor`, until it is `None`. The node may return fewer blob IDs than requested.
+    pub async fn list_stored_blobs(
+        &self,
+        node: &StorageNode,
+        shard_index: ShardIndex,
+        cursor: Option<&BlobId>,
+        limit: Option<usize>,
+    ) -> ClientResult<StoredBlobsPage> {
+        self.communication_factory
+            .create_client(node)
+            .map_err(ClientError::other)?
+            .list_stored_blobs(shard_index, cursor, limit)
+            .await
+            .map_err(ClientError::other)
+    }
+
+    /// Gets the capabilities of the storage node, such as the API version and the features that it
+    /// supports.
+    ///
+    /// For nodes that do not report their capabilities, these are derived from the API version
+    /// advertised by the node.
+    pub async fn capabilities(&self, node: &StorageNode) -> ClientResult<NodeCapabilities> {
+        self.communication_factory
+            .create_client(node)
+            .map_err(ClientError::other)?
+            .get_capabilities()
+            .await
+            .map_err(ClientError::other)
+    }
+
     /// Returns the shards of the given node in the write committee.
     #[cfg(any(test, feature = "test-utils"))]
     pub async fn shards_of(
@@ -1816,6 +2530,20 @@ impl<T> Client<T> {
         &self.encoding_config
     }
 
+    /// Runs `op` on the thread pool on which blobs are encoded and decoded.
+    ///
+    /// The encoding and decoding of a blob are parallelized over the threads of the pool on which
+    /// they run.
+    fn install_encoding_pool<R: Send>(&self, op: impl FnOnce() -> R + Send) -> R {
+        match &self.encoding_pool {
+            Some(pool) => {
+                let span = Span::current();
+                pool.install(|| span.in_scope(op))
+            }
+            None => op(),
+        }
+    }
+
     /// Returns the inner sui client.
     pub fn sui_client(&self) -> &T {
         &self.sui_client
@@ -1856,12 +2584,6 @@ impl<T> Client<T> {
         let (committees, _) = self.get_committees_and_price().await?;
         Ok(committees)
     }
-
-    /// Gets the current price computation from the cache.
-    pub async fn get_price_computation(&self) -> ClientResult<PriceComputation> {
-        let (_, price_computation) = self.get_committees_and_price().await?;
-        Ok(price_computation)
-    }
 }
 
 /// Verifies the [`BlobStatus`] using the on-chain event.
@@ -1913,3 +2635,206 @@ async fn verify_blob_status_event(
 
     Ok(())
 }
+
+/// Returns the public key of the node with the given index among the communications, if any.
+fn node_public_key<'a>(
+    comms: &'a [NodeReadCommunication<'_>],
+    node_index: usize,
+) -> Option<&'a PublicKey> {
+    comms
+        .iter()
+        .find(|comm| comm.node_index == node_index)
+        .map(|comm| &comm.node.public_key)
+}
+
+/// Converts a byte index within a blob to a `usize`.
+fn to_usize(index: u64) -> ClientResult<usize> {
+    usize::try_from(index).map_err(ClientError::other)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::num::NonZeroU16;
+
+    use tokio::sync::{mpsc, Notify};
+    use walrus_core::{
+        encoding::EncodingConfigTrait as _,
+        keys::{NetworkKeyPair, ProtocolKeyPair},
+        DEFAULT_ENCODING,
+    };
+    use walrus_rest_client::test_utils::{MockStorageNode, MockStorageNodes};
+    use walrus_sui::{
+        client::contract_config::ContractConfig,
+        types::{Committee, NetworkAddress},
+    };
+    use walrus_test_utils::Result as TestResult;
+
+    use super::*;
+
+    const EPOCH: Epoch = 1;
+    const N_NODES: u16 = 4;
+    const N_SHARDS: u16 = 10;
+
+    /// Creates a committee in [`EPOCH`] whose nodes are served by mock storage nodes holding the
+    /// nodes' protocol keys.
+    fn mock_committee() -> (Committee, MockStorageNodes) {
+        let mut nodes = MockStorageNodes::new();
+        let members = (0..N_NODES)
+            .map(|index| {
+                let host = format!("node-{index}.walrus.alt");
+                let node = MockStorageNode::new(ProtocolKeyPair::generate(), EPOCH);
+                let member = StorageNode {
+                    name: host.clone(),
+                    node_id: ObjectID::random(),
+                    network_address: NetworkAddress(format!("{host}:9185")),
+                    public_key: node.public_key(),
+                    next_epoch_public_key: None,
+                    network_public_key: NetworkKeyPair::generate().public().clone(),
+                    metadata: ObjectID::random(),
+                    shard_ids: (index..N_SHARDS)
+                        .step_by(N_NODES.into())
+                        .map(ShardIndex)
+                        .collect(),
+                };
+                nodes.insert(host, node);
+                member
+            })
+            .collect();
+        let n_shards = NonZeroU16::new(N_SHARDS).expect("the number of shards is non-zero");
+        let committee = Committee::new(members, EPOCH, n_shards).expect("the committee is valid");
+        (committee, nodes)
+    }
+
+    /// Returns a handle that serves the committees to the client, in place of a refresher.
+    fn serve_committees(committees: ActiveCommittees) -> CommitteesRefresherHandle {
+        let committees = Arc::new(committees);
+        let (req_tx, mut req_rx) = mpsc::channel(16);
+        tokio::spawn(async move {
+            while let Some(request) = req_rx.recv().await {
+                let reply = (committees.clone(), PriceComputation::new(1, 1, 0));
+                let _ = request.into_reply_channel().send(reply);
+            }
+        });
+        CommitteesRefresherHandle::new(Arc::new(Notify::new()), req_tx)
+    }
+
+    #[tokio::test]
+    async fn stores_and_reads_blob_with_mock_storage_nodes() -> TestResult {
+        let (committee, nodes) = mock_committee();
+        let config = ClientConfig::new_read_only(
+            ContractConfig::new(ObjectID::random(), ObjectID::random()),
+            vec![],
+        );
+        let client = Client::new(
+            config,
+            serve_committees(ActiveCommittees::new(committee, None)),
+        )
+        .await?
+        .with_http_layer(nodes.layer());
+
+        let blob = walrus_test_utils::random_data(1024);
+        let (pairs, metadata) = client
+            .encoding_config()
+            .get_for_type(DEFAULT_ENCODING)
+            .encode_with_metadata(&blob)?;
+        let certificate = client
+            .send_blob_data_and_get_certificate(
+                &metadata,
+                &pairs,
+                &BlobPersistenceType::Permanent,
+                &MultiProgress::new(),
+            )
+            .await?;
+        // A quorum of shards is held by at least three of the nodes, each of which signs its
+        // confirmation with its own key.
+        assert!(certificate.signers.len() >= 3);
+
+        let read_metadata = client.retrieve_metadata(EPOCH, metadata.blob_id()).await?;
+        assert_eq!(read_metadata, metadata);
+        let read_blob = client
+            .request_slivers_and_decode::<Primary>(EPOCH, &read_metadata)
+            .await?;
+        assert_eq!(read_blob, blob);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn diagnoses_slivers_failing_verification() -> TestResult {
+        let (committee, nodes) = mock_committee();
+        let config = ClientConfig::new_read_only(
+            ContractConfig::new(ObjectID::random(), ObjectID::random()),
+            vec![],
+        );
+        let client = Client::new(
+            config,
+            serve_committees(ActiveCommittees::new(committee, None)),
+        )
+        .await?
+        .with_http_layer(nodes.layer());
+
+        let blob = walrus_test_utils::random_data(1024);
+        let (pairs, metadata) = client
+            .encoding_config()
+            .get_for_type(DEFAULT_ENCODING)
+            .encode_with_metadata(&blob)?;
+        client
+            .send_blob_data_and_get_certificate(
+                &metadata,
+                &pairs,
+                &BlobPersistenceType::Permanent,
+                &MultiProgress::new(),
+            )
+            .await?;
+
+        // Only the two shards of the last node remain to return valid slivers.
+        let corrupt_nodes: Vec<_> = (0..N_NODES - 1)
+            .map(|index| {
+                let node = nodes
+                    .get(&format!("node-{index}.walrus.alt"))
+                    .expect("the node exists");
+                node.set_corrupt_responses(true);
+                node.public_key()
+            })
+            .collect();
+
+        let error = client
+            .request_slivers_and_decode::<Primary>(EPOCH, &metadata)
+            .await
+            .expect_err("the blob cannot be decoded from two valid slivers");
+        let ClientErrorKind::NotEnoughValidSlivers(diagnosis) = error.kind() else {
+            panic!("unexpected error: {error:?}");
+        };
+        let n_required_slivers = client
+            .encoding_config()
+            .get_for_type(DEFAULT_ENCODING)
+            .n_source_symbols::<Primary>()
+            .get();
+        assert_eq!(diagnosis.sliver_type, SliverType::Primary);
+        assert_eq!(diagnosis.n_valid_slivers, 2);
+        assert_eq!(
+            diagnosis.n_required_slivers,
+            usize::from(n_required_slivers)
+        );
+        assert_eq!(
+            diagnosis.n_missing_slivers(),
+            usize::from(n_required_slivers) - 2
+        );
+        assert_eq!(diagnosis.n_failed_verification, usize::from(N_SHARDS) - 2);
+        assert_eq!(
+            diagnosis.nodes_failing_verification.len(),
+            corrupt_nodes.len()
+        );
+        assert!(corrupt_nodes
+            .iter()
+            .all(|node| diagnosis.nodes_failing_verification.contains(node)));
+        assert_eq!(
+            (
+                diagnosis.n_not_found,
+                diagnosis.n_forbidden,
+                diagnosis.n_other_failures
+            ),
+            (0, 0, 0)
+        );
+        Ok(())
+    }
+}
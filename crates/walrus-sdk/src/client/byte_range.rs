@@ -0,0 +1,71 @@
+// Copyright (c) Walrus Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Ranges of bytes within a blob.
+
+use std::ops::Range;
+
+/// A range of bytes of a blob, which may be relative to the end of the blob.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ByteRange {
+    /// The bytes from `start` to `end`, both inclusive.
+    Bounded {
+        /// The index of the first byte.
+        start: u64,
+        /// The index of the last byte.
+        end: u64,
+    },
+    /// The bytes from the given index to the end of the blob.
+    From(u64),
+    /// The given number of bytes at the end of the blob.
+    Suffix(u64),
+}
+
+impl ByteRange {
+    /// Returns the indices of the bytes in the range for a blob of size `blob_size`, truncated to
+    /// the end of the blob.
+    ///
+    /// Returns `None` if the range does not contain any byte of the blob.
+    pub fn resolve(self, blob_size: u64) -> Option<Range<u64>> {
+        let range = match self {
+            Self::Bounded { start, end } => start..end.saturating_add(1).min(blob_size),
+            Self::From(start) => start..blob_size,
+            Self::Suffix(length) => blob_size.saturating_sub(length)..blob_size,
+        };
+        (!range.is_empty()).then_some(range)
+    }
+}
+
+/// The bytes of a blob in a range.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BlobRange {
+    /// The bytes of the blob in `range`.
+    pub data: Vec<u8>,
+    /// The indices of the bytes, which are within the blob.
+    pub range: Range<u64>,
+    /// The size of the whole blob.
+    pub blob_size: u64,
+}
+
+#[cfg(test)]
+mod tests {
+    use walrus_test_utils::param_test;
+
+    use super::*;
+
+    param_test! {
+        test_resolve: [
+            bounded: (ByteRange::Bounded { start: 2, end: 5 }, Some(2..6)),
+            bounded_past_the_end: (ByteRange::Bounded { start: 8, end: 20 }, Some(8..10)),
+            bounded_after_the_end: (ByteRange::Bounded { start: 10, end: 20 }, None),
+            from: (ByteRange::From(7), Some(7..10)),
+            from_the_end: (ByteRange::From(10), None),
+            suffix: (ByteRange::Suffix(3), Some(7..10)),
+            suffix_longer_than_the_blob: (ByteRange::Suffix(30), Some(0..10)),
+            empty_suffix: (ByteRange::Suffix(0), None),
+        ]
+    }
+    fn test_resolve(range: ByteRange, expected: Option<Range<u64>>) {
+        assert_eq!(range.resolve(10), expected);
+    }
+}
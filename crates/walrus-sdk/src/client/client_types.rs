@@ -1138,6 +1138,7 @@ impl<'a, T: Debug + Clone + Send + Sync> WalrusStoreBlobApi<'a, T> for Registere
         let blob_object = blob.clone();
         let new_state = self.complete_with(BlobStoreResult::NewlyCreated {
             cost: price_computation.operation_cost(&resource_operation),
+            subsidized_cost: price_computation.subsidized_operation_cost(&resource_operation),
             blob_object,
             resource_operation,
             // TODO: pass error back to the caller.
@@ -1374,6 +1375,7 @@ impl<'a, T: Debug + Clone + Send + Sync> WalrusStoreBlobApi<'a, T> for BlobWithC
             blob_object: blob.clone(),
             resource_operation: operation.clone(),
             cost: price_computation.operation_cost(operation),
+            subsidized_cost: price_computation.subsidized_operation_cost(operation),
             shared_blob_object: result.shared_blob_object(),
         };
 
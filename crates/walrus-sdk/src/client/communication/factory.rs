@@ -5,18 +5,24 @@
 
 use std::{
     collections::{hash_map::Entry, HashMap},
+    path::Path,
     sync::{Arc, Mutex},
 };
 
 use anyhow::anyhow;
 use rand::{seq::SliceRandom, thread_rng};
 use reqwest::Client as ReqwestClient;
-use rustls::pki_types::CertificateDer;
+use rustls::pki_types::{pem::PemObject as _, CertificateDer};
 use rustls_native_certs::CertificateResult;
 use tokio::sync::Semaphore;
 use walrus_core::{encoding::EncodingConfig, Epoch, NetworkPublicKey};
 use walrus_rest_client::{
-    client::{Client as StorageNodeClient, ClientBuilder as StorageNodeClientBuilder},
+    client::{
+        Client as StorageNodeClient,
+        ClientBuilder as StorageNodeClientBuilder,
+        HttpLayer,
+        RequestObserver,
+    },
     error::ClientBuildError,
 };
 use walrus_sui::types::{Committee, NetworkAddress, StorageNode};
@@ -35,8 +41,10 @@ pub struct NodeCommunicationFactory {
     config: ClientCommunicationConfig,
     encoding_config: Arc<EncodingConfig>,
     client_cache: Arc<Mutex<HashMap<(NetworkAddress, NetworkPublicKey), StorageNodeClient>>>,
-    native_certs: Vec<CertificateDer<'static>>,
+    root_certs: Vec<CertificateDer<'static>>,
     metrics_registry: Option<Registry>,
+    http_layers: Vec<HttpLayer>,
+    request_observers: Vec<Arc<dyn RequestObserver>>,
 }
 
 /// Factory to create the vectors of `NodeCommunication` objects.
@@ -47,7 +55,7 @@ impl NodeCommunicationFactory {
         encoding_config: Arc<EncodingConfig>,
         metrics_registry: Option<Registry>,
     ) -> ClientResult<Self> {
-        let native_certs = if !config.disable_native_certs {
+        let mut root_certs = if !config.disable_native_certs {
             let CertificateResult { certs, errors, .. } = rustls_native_certs::load_native_certs();
             if certs.is_empty() {
                 return Err(ClientError::from(ClientErrorKind::FailedToLoadCerts(
@@ -65,15 +73,42 @@ impl NodeCommunicationFactory {
         } else {
             vec![]
         };
+        for path in &config.extra_root_certificates {
+            root_certs.extend(load_root_certificates(path)?);
+        }
         Ok(Self {
             config,
             encoding_config,
             client_cache: Default::default(),
-            native_certs,
+            root_certs,
             metrics_registry,
+            http_layers: vec![],
+            request_observers: vec![],
         })
     }
 
+    /// Returns a factory whose storage-node clients send their requests through the provided
+    /// layer, in addition to the layers already added.
+    ///
+    /// The returned factory does not share its cached clients with `self`, since these do not use
+    /// the layer.
+    pub fn with_http_layer(mut self, layer: HttpLayer) -> Self {
+        self.http_layers.push(layer);
+        self.client_cache = Default::default();
+        self
+    }
+
+    /// Returns a factory whose storage-node clients notify the provided observer of their
+    /// requests, in addition to the observers already added.
+    ///
+    /// The returned factory does not share its cached clients with `self`, since these do not
+    /// notify the observer.
+    pub fn with_request_observer(mut self, observer: Arc<dyn RequestObserver>) -> Self {
+        self.request_observers.push(observer);
+        self.client_cache = Default::default();
+        self
+    }
+
     /// Returns a vector of [`NodeWriteCommunication`] objects representing nodes in random order.
     pub(crate) fn node_write_communications<'a>(
         &'a self,
@@ -219,10 +254,17 @@ impl NodeCommunicationFactory {
                 if let Some(registry) = self.metrics_registry.as_ref() {
                     builder = builder.metric_registry(registry.clone());
                 }
+                for layer in &self.http_layers {
+                    builder = builder.layer(layer.clone());
+                }
+                for observer in &self.request_observers {
+                    builder = builder.observer(observer.clone());
+                }
 
                 let client = builder
                     .authenticate_with_public_key(node.network_public_key.clone())
-                    .add_root_certificates(&self.native_certs)
+                    .add_root_certificates(&self.root_certs)
+                    .pin_certificates(self.config.certificate_pins_for(&node.network_public_key))
                     .tls_built_in_root_certs(false)
                     .build(&node.network_address.0)?;
                 Ok(vacant.insert(client).clone())
@@ -329,3 +371,23 @@ fn node_communications<'a, W>(
 
     Ok(comms)
 }
+
+/// Loads the certificates from the PEM file at `path`, failing if it contains none.
+fn load_root_certificates(path: &Path) -> ClientResult<Vec<CertificateDer<'static>>> {
+    let to_error = |reason: String| {
+        ClientError::from(ClientErrorKind::FailedToLoadRootCertificates {
+            path: path.to_owned(),
+            reason,
+        })
+    };
+    let pem = std::fs::read(path).map_err(|error| to_error(error.to_string()))?;
+    let certs = CertificateDer::pem_slice_iter(&pem)
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|error| to_error(error.to_string()))?;
+    if certs.is_empty() {
+        return Err(to_error(
+            "the file does not contain any certificate".to_owned(),
+        ));
+    }
+    Ok(certs)
+}
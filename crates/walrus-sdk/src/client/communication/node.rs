@@ -1,7 +1,10 @@
 // Copyright (c) Walrus Foundation
 // SPDX-License-Identifier: Apache-2.0
 
-use std::{num::NonZeroU16, sync::Arc};
+use std::{
+    num::NonZeroU16,
+    sync::{Arc, OnceLock},
+};
 
 use anyhow::Result;
 use futures::{future::Either, stream::FuturesUnordered, Future, StreamExt};
@@ -20,8 +23,8 @@ use walrus_core::{
     SliverPairIndex,
 };
 use walrus_rest_client::{
-    api::{BlobStatus, StoredOnNodeStatus},
-    client::Client as StorageNodeClient,
+    api::{BlobStatus, IdempotencyKey, StoredOnNodeStatus},
+    client::{with_request_attempt, Client as StorageNodeClient, SliverBody},
     error::NodeError,
 };
 use walrus_sui::types::StorageNode;
@@ -240,18 +243,26 @@ impl<W> NodeCommunication<'_, W> {
         epoch: Epoch,
         blob_persistence_type: &BlobPersistenceType,
     ) -> Result<SignedStorageConfirmation, NodeError> {
-        let confirmation = backoff::retry(self.backoff_strategy(), || {
-            self.client.get_confirmation(blob_id, blob_persistence_type)
-        })
+        let mut attempt = 0;
+        let confirmation = backoff::retry_while(
+            self.backoff_strategy(),
+            || {
+                attempt += 1;
+                with_request_attempt(
+                    attempt,
+                    self.client.get_confirmation(blob_id, blob_persistence_type),
+                )
+            },
+            should_retry,
+        )
         .await
-        .map_err(|error| {
+        .inspect_err(|error| {
             tracing::warn!(?error, "could not retrieve confirmation after retrying");
-            NodeError::other(error)
         })?;
 
         let _ = confirmation
             .verify(self.public_key(), epoch, *blob_id, *blob_persistence_type)
-            .map_err(NodeError::other)?;
+            .map_err(NodeError::verification)?;
 
         Ok(confirmation)
     }
@@ -354,8 +365,13 @@ impl NodeWriteCommunication<'_> {
                 tracing::debug!("the metadata is already stored on the node");
             }
             StoredOnNodeStatus::Nonexistent => {
-                self.retry_with_limits_and_backoff(|| self.client.store_metadata(metadata))
-                    .await?;
+                // All attempts share the key, so that the node processes the write at most once.
+                let idempotency_key = IdempotencyKey::random();
+                self.retry_with_limits_and_backoff(|| {
+                    self.client
+                        .store_metadata_with_idempotency_key(metadata, &idempotency_key)
+                })
+                .await?;
             }
         }
         Ok(metadata_status)
@@ -468,20 +484,33 @@ impl NodeWriteCommunication<'_> {
         self.store_sliver(blob_id, sliver, pair_index).await
     }
 
-    /// Stores a sliver on a node.
+    /// Stores a sliver on a node, retrying with the same idempotency key.
+    ///
+    /// The write permits are acquired once for all attempts. The sliver is serialized when the
+    /// first attempt starts, after the permits are acquired, and the serialized body is shared by
+    /// all retries.
     async fn store_sliver<A: EncodingAxis>(
         &self,
         blob_id: &BlobId,
         sliver: &SliverData<A>,
         pair_index: SliverPairIndex,
     ) -> Result<(), SliverStoreError> {
-        self.retry_with_limits_and_backoff(|| self.client.store_sliver(blob_id, pair_index, sliver))
-            .await
-            .map_err(|error| SliverStoreError {
+        let idempotency_key = IdempotencyKey::random();
+        let body = OnceLock::new();
+        self.retry_with_limits_and_backoff(|| {
+            self.client.store_sliver_body_with_idempotency_key(
+                blob_id,
                 pair_index,
-                sliver_type: A::sliver_type(),
-                error,
-            })
+                body.get_or_init(|| SliverBody::new(sliver)),
+                &idempotency_key,
+            )
+        })
+        .await
+        .map_err(|error| SliverStoreError {
+            pair_index,
+            sliver_type: A::sliver_type(),
+            error,
+        })
     }
 
     /// Requests the status for sliver after retrying.
@@ -501,22 +530,35 @@ impl NodeWriteCommunication<'_> {
         })
     }
 
-    async fn retry_with_limits_and_backoff<F, Fut, T, E>(&self, f: F) -> Result<T, E>
+    async fn retry_with_limits_and_backoff<F, Fut, T>(&self, mut f: F) -> Result<T, NodeError>
     where
         F: FnMut() -> Fut,
-        Fut: Future<Output = Result<T, E>>,
+        Fut: Future<Output = Result<T, NodeError>>,
     {
+        let mut attempt = 0;
+        let next_attempt = || {
+            attempt += 1;
+            with_request_attempt(attempt, f())
+        };
         batch_limit(
             self.sliver_write_limit.clone(),
             batch_limit(
                 self.node_write_limit.clone(),
-                backoff::retry(self.backoff_strategy(), f),
+                backoff::retry_while(self.backoff_strategy(), next_attempt, should_retry),
             ),
         )
         .await
     }
 }
 
+/// Requests are retried unless the error is permanent.
+///
+/// Errors that are neither retriable nor permanent are retried, since they may depend on the
+/// state of the node; for example, the node may not yet have seen the registration of the blob.
+fn should_retry(error: &NodeError) -> bool {
+    !error.is_permanent()
+}
+
 async fn batch_limit<F>(permits: Arc<Semaphore>, f: F) -> F::Output
 where
     F: Future + Sized,
@@ -0,0 +1,101 @@
+// Copyright (c) Walrus Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Reporting of the progress of store operations.
+//!
+//! The events of a store operation are sent to the channel passed to [`with_store_progress`], for
+//! the store operations performed while the future passed to it is running. This allows callers,
+//! such as the publisher, to follow the progress of a store operation without changing the
+//! signatures of the store functions.
+
+use std::future::Future;
+
+use serde::Serialize;
+use serde_with::{serde_as, DisplayFromStr};
+use sui_types::digests::TransactionDigest;
+use tokio::sync::mpsc::UnboundedSender;
+use walrus_core::BlobId;
+
+/// An event in the progress of storing a blob.
+#[serde_as]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(
+    tag = "event",
+    rename_all = "camelCase",
+    rename_all_fields = "camelCase"
+)]
+pub enum StoreProgressEvent {
+    /// The blob was encoded into slivers.
+    Encoded {
+        /// The ID of the blob.
+        #[serde_as(as = "DisplayFromStr")]
+        blob_id: BlobId,
+    },
+    /// A storage node confirmed that it stored the slivers of its shards.
+    NodeConfirmation {
+        /// The ID of the blob.
+        #[serde_as(as = "DisplayFromStr")]
+        blob_id: BlobId,
+        /// The index of the node in the committee.
+        node_index: usize,
+        /// The number of shards of the node.
+        weight: usize,
+        /// The number of shards whose slivers have been confirmed so far.
+        confirmed_weight: usize,
+        /// The number of shards whose slivers need to be confirmed for the blob to be certified.
+        required_weight: usize,
+    },
+    /// The blob was certified on Sui.
+    Certified {
+        /// The ID of the blob.
+        #[serde_as(as = "DisplayFromStr")]
+        blob_id: BlobId,
+        /// The digest of the transaction that certified the blob.
+        #[serde_as(as = "DisplayFromStr")]
+        transaction_digest: TransactionDigest,
+    },
+}
+
+tokio::task_local! {
+    static STORE_PROGRESS: UnboundedSender<StoreProgressEvent>;
+}
+
+/// Runs the future, sending the progress events of the store operations it performs to `sender`.
+///
+/// Events are only reported for the work done on the task running the future, which includes all
+/// steps of the store functions of the [`Client`][crate::client::Client].
+pub async fn with_store_progress<F: Future>(
+    sender: UnboundedSender<StoreProgressEvent>,
+    future: F,
+) -> F::Output {
+    STORE_PROGRESS.scope(sender, future).await
+}
+
+/// Reports the event created by `event`, if the progress of the current task is followed.
+pub(crate) fn report_store_progress(event: impl FnOnce() -> StoreProgressEvent) {
+    let _ = STORE_PROGRESS.try_with(|sender| {
+        // The receiver may have stopped following the progress, which does not affect the store.
+        let _ = sender.send(event());
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio::sync::mpsc;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn reports_events_only_within_scope() {
+        let (sender, mut receiver) = mpsc::unbounded_channel();
+        let event = StoreProgressEvent::Encoded {
+            blob_id: BlobId([7; 32]),
+        };
+
+        report_store_progress(|| event.clone());
+        with_store_progress(sender, async { report_store_progress(|| event.clone()) }).await;
+
+        assert_eq!(receiver.recv().await, Some(event));
+        assert_eq!(receiver.recv().await, None);
+    }
+}
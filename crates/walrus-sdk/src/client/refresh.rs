@@ -179,6 +179,8 @@ impl<T: ReadClient> CommitteesRefresher<T> {
     /// This function does _not_ update the last refresh time.
     async fn refresh(&mut self) -> Result<()> {
         tracing::debug!("getting the latest active committee and price computation from chain");
+        // Bypass the cache of the read client, and refill it with the latest data.
+        self.sui_client.clear_cache();
         let (committees, price_computation, epoch_state) =
             Self::get_latest(&self.sui_client).await?;
 
@@ -210,7 +212,9 @@ impl<T: ReadClient> CommitteesRefresher<T> {
 
         let (storage_price, write_price) =
             sui_client.storage_and_write_price_per_unit_size().await?;
-        let price_computation = PriceComputation::new(storage_price, write_price);
+        let buyer_subsidy_rate = sui_client.buyer_subsidy_rate().await?;
+        let price_computation =
+            PriceComputation::new(storage_price, write_price, buyer_subsidy_rate);
         Ok((committees, price_computation, epoch_state))
     }
 
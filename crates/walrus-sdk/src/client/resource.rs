@@ -30,43 +30,82 @@ use crate::{
     store_when::StoreWhen,
 };
 
+// Keep in sync with `MAX_SUBSIDY_RATE` in `subsidies.move`.
+/// The subsidy rate corresponding to a subsidy of 100%, i.e., rates are in basis points.
+const MAX_SUBSIDY_RATE: u16 = 10_000;
+
 /// Struct to compute the cost of operations with blob and storage resources.
 #[derive(Debug, Clone)]
 pub struct PriceComputation {
     storage_price_per_unit_size: u64,
     write_price_per_unit_size: u64,
+    buyer_subsidy_rate: u16,
 }
 
 impl PriceComputation {
-    pub(crate) fn new(storage_price_per_unit_size: u64, write_price_per_unit_size: u64) -> Self {
+    pub(crate) fn new(
+        storage_price_per_unit_size: u64,
+        write_price_per_unit_size: u64,
+        buyer_subsidy_rate: u16,
+    ) -> Self {
         Self {
             storage_price_per_unit_size,
             write_price_per_unit_size,
+            buyer_subsidy_rate: buyer_subsidy_rate.min(MAX_SUBSIDY_RATE),
         }
     }
 
     /// Computes the cost of the operation.
     pub fn operation_cost(&self, operation: &RegisterBlobOp) -> u64 {
+        let (storage_fee, write_fee) = self.operation_fees(operation);
+        storage_fee + write_fee
+    }
+
+    /// Computes the cost of the operation after the buyer subsidy is deducted.
+    ///
+    /// Returns `None` if the operation is not subsidized. The subsidies contract only subsidizes the
+    /// storage fee, not the write fee, and the subsidy may be lower if the subsidy pool is nearly
+    /// exhausted when the operation is executed.
+    pub fn subsidized_operation_cost(&self, operation: &RegisterBlobOp) -> Option<u64> {
+        let (storage_fee, write_fee) = self.operation_fees(operation);
+        let subsidy = u64::try_from(
+            u128::from(storage_fee) * u128::from(self.buyer_subsidy_rate)
+                / u128::from(MAX_SUBSIDY_RATE),
+        )
+        .expect("the subsidy is at most the storage fee");
+        (subsidy > 0).then(|| storage_fee - subsidy + write_fee)
+    }
+
+    /// Returns the rate in basis points at which the storage fee is subsidized.
+    pub fn buyer_subsidy_rate(&self) -> u16 {
+        self.buyer_subsidy_rate
+    }
+
+    /// Returns the storage fee and the write fee of the operation.
+    fn operation_fees(&self, operation: &RegisterBlobOp) -> (u64, u64) {
         match operation {
             RegisterBlobOp::RegisterFromScratch {
                 encoded_length,
                 epochs_ahead,
-            } => {
-                self.storage_fee_for_encoded_length(*encoded_length, *epochs_ahead)
-                    + self.write_fee_for_encoded_length(*encoded_length)
-            }
+            } => (
+                self.storage_fee_for_encoded_length(*encoded_length, *epochs_ahead),
+                self.write_fee_for_encoded_length(*encoded_length),
+            ),
             RegisterBlobOp::ReuseStorage { encoded_length } => {
-                self.write_fee_for_encoded_length(*encoded_length)
+                (0, self.write_fee_for_encoded_length(*encoded_length))
             }
             RegisterBlobOp::ReuseAndExtend {
                 encoded_length,
                 epochs_extended,
-            } => self.storage_fee_for_encoded_length(*encoded_length, *epochs_extended),
-            RegisterBlobOp::ReuseAndExtendNonCertified {
+            }
+            | RegisterBlobOp::ReuseAndExtendNonCertified {
                 encoded_length,
                 epochs_extended,
-            } => self.storage_fee_for_encoded_length(*encoded_length, *epochs_extended),
-            _ => 0, // No cost for reusing registration or no-op.
+            } => (
+                self.storage_fee_for_encoded_length(*encoded_length, *epochs_extended),
+                0,
+            ),
+            _ => (0, 0), // No cost for reusing registration or no-op.
         }
     }
 
@@ -550,33 +589,39 @@ impl<'a> ResourceManager<'a> {
             };
         }
 
-        // Register all in reused_metadata_with_storage in one ptb.
+        // Register all in reused_metadata_with_storage, and reserve space and register all in
+        // new_metadata_list, in one ptb.
         tracing::debug!(
-            num_blobs=%reused_metadata_with_storage.len(),
-            "registering blobs with its storage resources"
+            num_blobs_with_storage = reused_metadata_with_storage.len(),
+            num_new_blobs = new_metadata_list.len(),
+            "registering blobs with their storage resources, and creating new ones for blobs that \
+            are not already registered or whose lifetime is too short"
         );
-        let blobs = self
+        let (reused_storage_blobs, new_blobs) = self
             .sui_client
-            .register_blobs(reused_metadata_with_storage, persistence)
-            .await?;
-        results.extend(blobs.into_iter().zip(reused_encoded_lengths.iter()).map(
-            |(blob, &encoded_length)| (blob, RegisterBlobOp::ReuseStorage { encoded_length }),
-        ));
-
-        // Reserve space and register all in new_metadata_list in one ptb.
-        tracing::debug!(
-            num_blobs = ?new_metadata_list.len(),
-            "blobs are not already registered or their lifetime is too short; creating new ones"
-        );
-        results.extend(
-            self.reserve_and_register_blob_op(
-                &new_encoded_lengths,
+            .register_blobs_with_owned_and_new_storage(
+                reused_metadata_with_storage,
                 epochs_ahead,
-                &new_metadata_list,
+                new_metadata_list
+                    .iter()
+                    .map(|m| (*m).try_into())
+                    .collect::<Result<Vec<_>, _>>()?,
                 persistence,
             )
-            .await?,
+            .await?;
+        results.extend(
+            reused_storage_blobs
+                .into_iter()
+                .zip(reused_encoded_lengths.iter())
+                .map(|(blob, &encoded_length)| {
+                    (blob, RegisterBlobOp::ReuseStorage { encoded_length })
+                }),
         );
+        results.extend(Self::register_from_scratch_ops(
+            new_blobs,
+            &new_encoded_lengths,
+            epochs_ahead,
+        ));
 
         results.extend(extended_blobs);
         results.extend(extended_blobs_noncertified);
@@ -606,6 +651,19 @@ impl<'a> ResourceManager<'a> {
                 persistence,
             )
             .await?;
+        Ok(Self::register_from_scratch_ops(
+            blobs,
+            encoded_lengths,
+            epochs_ahead,
+        ))
+    }
+
+    /// Pairs the blobs registered with newly purchased storage with their register operations.
+    fn register_from_scratch_ops(
+        blobs: Vec<Blob>,
+        encoded_lengths: &[u64],
+        epochs_ahead: EpochCount,
+    ) -> Vec<(Blob, RegisterBlobOp)> {
         debug_assert_eq!(
             blobs.len(),
             encoded_lengths.len(),
@@ -614,7 +672,7 @@ impl<'a> ResourceManager<'a> {
             blobs.len(),
             encoded_lengths.len()
         );
-        Ok(blobs
+        blobs
             .into_iter()
             .zip(encoded_lengths.iter())
             .map(|(blob, &encoded_length)| {
@@ -627,7 +685,7 @@ impl<'a> ResourceManager<'a> {
                     },
                 )
             })
-            .collect())
+            .collect()
     }
 
     /// Finds a blob object with the given `blob_id` owned by the active wallet.
@@ -654,3 +712,38 @@ impl<'a> ResourceManager<'a> {
             .cloned())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn subsidy_applies_only_to_the_storage_fee() {
+        let price_computation = PriceComputation::new(1_000, 500, 2_500);
+        let encoded_length = 64 * 1024 * 1024;
+        let storage_fee = price_computation.storage_fee_for_encoded_length(encoded_length, 4);
+        let write_fee = price_computation.write_fee_for_encoded_length(encoded_length);
+
+        let register = RegisterBlobOp::RegisterFromScratch {
+            encoded_length,
+            epochs_ahead: 4,
+        };
+        assert_eq!(
+            price_computation.operation_cost(&register),
+            storage_fee + write_fee
+        );
+        assert_eq!(
+            price_computation.subsidized_operation_cost(&register),
+            Some(storage_fee - storage_fee / 4 + write_fee)
+        );
+        assert_eq!(
+            price_computation
+                .subsidized_operation_cost(&RegisterBlobOp::ReuseStorage { encoded_length }),
+            None
+        );
+        assert_eq!(
+            PriceComputation::new(1_000, 500, 0).subsidized_operation_cost(&register),
+            None
+        );
+    }
+}
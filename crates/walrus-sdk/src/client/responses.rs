@@ -5,15 +5,38 @@
 
 use std::{fmt::Display, path::PathBuf};
 
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use serde_with::{serde_as, DisplayFromStr};
 use sui_types::{base_types::ObjectID, event::EventID};
 use utoipa::ToSchema;
-use walrus_core::{BlobId, Epoch};
+use walrus_core::{quilt::QuiltEntryId, BlobId, Epoch, PublicKey};
+use walrus_rest_client::{client::CheckedRecoverySymbol, error::NodeError};
 use walrus_sui::{types::move_structs::Blob, EventIdSchema, ObjectIdSchema};
 
 use super::resource::RegisterBlobOp;
 
+/// The size and availability of a blob, which are known without reading its slivers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BlobAvailability {
+    /// The size of the unencoded blob in bytes.
+    pub blob_size: u64,
+    /// The epoch in which the blob was first certified.
+    pub certified_epoch: Epoch,
+    /// The estimated time at which the blob expires, if it is stored permanently.
+    pub estimated_expiry: Option<DateTime<Utc>>,
+}
+
+/// The recovery symbols returned by a storage node for a batch of target slivers.
+#[derive(Debug)]
+pub struct NodeRecoverySymbols {
+    /// The public key of the storage node.
+    pub node_public_key: PublicKey,
+    /// For each of the target slivers, in the requested order, the symbols returned by the node
+    /// along with the outcome of their verification; or the error returned for the request.
+    pub symbols: Result<Vec<Vec<CheckedRecoverySymbol>>, NodeError>,
+}
+
 /// Either an event ID or an object ID.
 #[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 #[serde(rename_all = "camelCase")]
@@ -83,6 +106,9 @@ pub enum BlobStoreResult {
         resource_operation: RegisterBlobOp,
         /// The storage cost, excluding gas.
         cost: u64,
+        /// The storage cost after the buyer subsidy, excluding gas, if the storage was subsidized.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        subsidized_cost: Option<u64>,
         /// The shared blob object ID if created.
         #[serde_as(as = "Option<DisplayFromStr>")]
         #[serde(skip_serializing_if = "Option::is_none")]
@@ -140,3 +166,25 @@ impl BlobStoreResult {
         matches!(self, Self::MarkedInvalid { .. } | Self::Error { .. })
     }
 }
+
+/// The result of storing a quilt, which packs many entries into a single blob.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QuiltStoreResult {
+    /// The result of storing the quilt as a blob.
+    pub blob_store_result: BlobStoreResult,
+    /// The entries of the quilt, in the order of its index.
+    pub entries: Vec<QuiltEntryStoreResult>,
+}
+
+/// An entry of a stored quilt.
+#[serde_as]
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QuiltEntryStoreResult {
+    /// The identifier of the entry.
+    pub identifier: String,
+    /// The ID of the entry, if the blob ID of the quilt is known.
+    #[serde_as(as = "Option<DisplayFromStr>")]
+    pub entry_id: Option<QuiltEntryId>,
+}
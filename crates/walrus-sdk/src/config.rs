@@ -5,6 +5,7 @@
 use std::{
     collections::HashMap,
     path::{Path, PathBuf},
+    sync::Arc,
 };
 
 use anyhow::{anyhow, bail, Context, Result};
@@ -16,6 +17,12 @@ use walrus_sui::{
     client::{
         contract_config::ContractConfig,
         retry_client::RetriableSuiClient,
+        GasEscalationConfig,
+        GasSponsorConfig,
+        OfflineSigner,
+        OfflineSignerConfig,
+        SigningServiceConfig,
+        SigningServiceSigner,
         SuiClientError,
         SuiContractClient,
         SuiReadClient,
@@ -26,12 +33,14 @@ use walrus_utils::{backoff::ExponentialBackoffConfig, config::path_or_defaults_i
 
 mod committees_refresh_config;
 mod communication_config;
+mod network_profile;
 mod reqwest_config;
 mod sliver_write_extra_time;
 
 pub use self::{
     committees_refresh_config::CommitteesRefreshConfig,
-    communication_config::{ClientCommunicationConfig, CommunicationLimits},
+    communication_config::{ClientCommunicationConfig, CommunicationLimits, NodeCertificatePins},
+    network_profile::NetworkProfile,
     reqwest_config::RequestRateConfig,
 };
 
@@ -87,15 +96,57 @@ pub struct ClientConfig {
     /// Path to the wallet configuration.
     #[serde(default)]
     pub wallet_config: Option<WalletConfig>,
+    /// The URLs of the Sui RPC nodes to use.
+    ///
+    /// If set, the client uses the healthy RPC nodes in order of their latency, and fails over to
+    /// the next one on errors or high latency. Otherwise, the RPC node of the wallet is used.
+    #[serde(default)]
+    pub rpc_urls: Vec<String>,
     /// Configuration for the client's network communication.
     #[serde(default)]
     pub communication_config: ClientCommunicationConfig,
     /// The configuration of the committee refresh from chain.
     #[serde(default)]
     pub refresh_config: CommitteesRefreshConfig,
+    /// The sponsor that pays for the gas of the transactions of the client, if any.
+    #[serde(default)]
+    pub gas_sponsor: Option<GasSponsorConfig>,
+    /// The escalation of the gas price and budget of transactions that fail due to gas.
+    #[serde(default)]
+    pub gas_escalation: GasEscalationConfig,
+    /// The remote signing service that signs the transactions of the client, if the keys of the
+    /// wallet are not available in its keystore.
+    #[serde(default)]
+    pub signing_service: Option<SigningServiceConfig>,
+    /// The offline signing of the transactions of the client, for example by the signers of a Sui
+    /// multisig address.
+    ///
+    /// Cannot be combined with a [`Self::signing_service`].
+    #[serde(default)]
+    pub offline_signer: Option<OfflineSignerConfig>,
 }
 
 impl ClientConfig {
+    /// Creates a configuration for the Walrus objects in `contract_config` that reads from the Sui
+    /// RPC nodes at `rpc_urls`, without a wallet.
+    ///
+    /// The configuration is sufficient to read blobs with a [`SuiReadClient`] created with
+    /// [`Self::new_rpc_read_client`]. All other options are set to their defaults.
+    pub fn new_read_only(contract_config: ContractConfig, rpc_urls: Vec<String>) -> Self {
+        Self {
+            contract_config,
+            exchange_objects: vec![],
+            wallet_config: None,
+            rpc_urls,
+            communication_config: Default::default(),
+            refresh_config: Default::default(),
+            gas_sponsor: None,
+            gas_escalation: Default::default(),
+            signing_service: None,
+            offline_signer: None,
+        }
+    }
+
     /// Loads the Walrus client configuration from the given path along with a context. If the file
     /// is a multi-config file, the context argument can be used to override the default context.
     pub fn load_from_multi_config(
@@ -137,26 +188,74 @@ impl ClientConfig {
     }
 
     /// Creates a [`SuiReadClient`] based on the configuration.
+    ///
+    /// The read client caches the Walrus objects read from chain as configured in the
+    /// [`CommitteesRefreshConfig::read_cache_ttl`].
     pub async fn new_read_client(
         &self,
         sui_client: RetriableSuiClient,
     ) -> Result<SuiReadClient, SuiClientError> {
-        SuiReadClient::new(sui_client, &self.contract_config).await
+        Ok(SuiReadClient::new(sui_client, &self.contract_config)
+            .await?
+            .with_cache_ttl(self.refresh_config.read_cache_ttl))
+    }
+
+    /// Creates a [`SuiReadClient`] that reads from the configured [`Self::rpc_urls`].
+    ///
+    /// Unlike [`Self::new_contract_client`], this does not load the Sui wallet, so it works in
+    /// environments without a wallet configuration. Returns an error if no RPC URLs are configured
+    /// or none of the RPC nodes is reachable.
+    pub async fn new_rpc_read_client(&self) -> Result<SuiReadClient, SuiClientError> {
+        self.new_read_client(self.new_rpc_client().await?).await
     }
 
     /// Creates a [`SuiContractClient`] based on the configuration.
+    ///
+    /// If [`Self::rpc_urls`] are configured, they are used instead of the RPC node of the wallet.
+    /// If a gas sponsor is configured, the gas of the transactions of the client is paid by the
+    /// sponsor. Transactions that fail due to gas are resubmitted as configured in
+    /// [`Self::gas_escalation`]. If a signing service or an offline signer is configured, it signs
+    /// the transactions instead of the keystore of the wallet.
     pub async fn new_contract_client(
         &self,
         wallet_context: WalletContext,
         gas_budget: Option<u64>,
     ) -> Result<SuiContractClient, SuiClientError> {
-        SuiContractClient::new(
-            wallet_context,
-            &self.contract_config,
-            self.backoff_config().clone(),
-            gas_budget,
-        )
-        .await
+        let sui_client = if self.rpc_urls.is_empty() {
+            RetriableSuiClient::new_from_wallet(&wallet_context, self.backoff_config().clone())
+                .await?
+        } else {
+            self.new_rpc_client().await?
+        };
+        let read_client = Arc::new(self.new_read_client(sui_client).await?);
+        let contract_client =
+            SuiContractClient::new_with_read_client(wallet_context, gas_budget, read_client)?
+                .with_gas_escalation(self.gas_escalation.clone());
+        let contract_client = match (&self.signing_service, &self.offline_signer) {
+            (Some(_), Some(_)) => {
+                return Err(SuiClientError::Internal(anyhow!(
+                    "a signing service and an offline signer cannot be configured at the same time"
+                )));
+            }
+            (Some(signing_service), None) => contract_client
+                .with_signer(Arc::new(SigningServiceSigner::new(signing_service.clone()))),
+            (None, Some(offline_signer)) => {
+                contract_client.with_signer(Arc::new(OfflineSigner::new(offline_signer.clone())))
+            }
+            (None, None) => contract_client,
+        };
+        match &self.gas_sponsor {
+            Some(gas_sponsor) => contract_client.with_gas_sponsor(gas_sponsor),
+            None => Ok(contract_client),
+        }
+    }
+
+    /// Creates a [`RetriableSuiClient`] that fails over between the configured [`Self::rpc_urls`],
+    /// after checking their health.
+    ///
+    /// Returns an error if no RPC URLs are configured or none of the RPC nodes is reachable.
+    pub async fn new_rpc_client(&self) -> Result<RetriableSuiClient, SuiClientError> {
+        RetriableSuiClient::new_for_rpc_urls(&self.rpc_urls, self.backoff_config().clone()).await
     }
 
     /// Creates a [`SuiContractClient`] with a wallet configured in the client config.
@@ -197,9 +296,12 @@ pub enum MultiClientConfig {
 
 #[cfg(test)]
 mod tests {
-    use indoc::indoc;
+    use fastcrypto::traits::EncodeDecodeBase64 as _;
+    use indoc::{formatdoc, indoc};
     use rand::{rngs::StdRng, SeedableRng as _};
     use tempfile::TempDir;
+    use walrus_core::keys::NetworkKeyPair;
+    use walrus_rest_client::client::CertificatePin;
     use walrus_sui::client::contract_config::ContractConfig;
     use walrus_test_utils::Result as TestResult;
 
@@ -226,8 +328,13 @@ mod tests {
                 ObjectID::random_from_rng(&mut rng),
             ],
             wallet_config: None,
+            rpc_urls: vec![],
             communication_config: Default::default(),
             refresh_config: Default::default(),
+            gas_sponsor: None,
+            gas_escalation: Default::default(),
+            signing_service: None,
+            offline_signer: None,
         };
 
         walrus_test_utils::overwrite_file_and_fail_if_not_equal(
@@ -251,6 +358,88 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn parses_rpc_urls_config_file() -> TestResult {
+        let yaml = indoc! {"
+            system_object: 0xa2637d13d171b278eadfa8a3fbe8379b5e471e1f3739092e5243da17fc8090eb
+            staking_object: 0xca7cf321e47a1fc9bfd032abc31b253f5063521fd5b4c431f2cdd3fee1b4ec00
+            rpc_urls:
+                - https://fullnode.testnet.sui.io:443
+                - http://localhost:9000
+        "};
+
+        let config: ClientConfig = serde_yaml::from_str(yaml)?;
+        assert_eq!(
+            config.rpc_urls,
+            vec![
+                "https://fullnode.testnet.sui.io:443",
+                "http://localhost:9000"
+            ]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn parses_certificate_pins_config_file() -> TestResult {
+        let public_key = NetworkKeyPair::generate().public().clone();
+        let yaml = formatdoc! {"
+            system_object: 0xa2637d13d171b278eadfa8a3fbe8379b5e471e1f3739092e5243da17fc8090eb
+            staking_object: 0xca7cf321e47a1fc9bfd032abc31b253f5063521fd5b4c431f2cdd3fee1b4ec00
+            communication_config:
+                extra_root_certificates:
+                    - /etc/walrus/private-ca.pem
+                certificate_pins:
+                    - public_key: {}
+                      pins:
+                        - spki_sha256: BwcHBwcHBwcHBwcHBwcHBwcHBwcHBwcHBwcHBwcHBwc=
+            ",
+            public_key.encode_base64()
+        };
+
+        let config: ClientConfig = serde_yaml::from_str(&yaml)?;
+        let communication_config = config.communication_config;
+        assert_eq!(
+            communication_config.extra_root_certificates,
+            vec![PathBuf::from("/etc/walrus/private-ca.pem")]
+        );
+        assert_eq!(
+            communication_config.certificate_pins_for(&public_key),
+            [CertificatePin::SpkiSha256([7; 32])]
+        );
+        assert!(communication_config
+            .certificate_pins_for(NetworkKeyPair::generate().public())
+            .is_empty());
+
+        Ok(())
+    }
+
+    #[test]
+    fn parses_offline_signer_config_file() -> TestResult {
+        let yaml = indoc! {"
+            system_object: 0xa2637d13d171b278eadfa8a3fbe8379b5e471e1f3739092e5243da17fc8090eb
+            staking_object: 0xca7cf321e47a1fc9bfd032abc31b253f5063521fd5b4c431f2cdd3fee1b4ec00
+            offline_signer:
+                address: 0xca7cf321e47a1fc9bfd032abc31b253f5063521fd5b4c431f2cdd3fee1b4ec00
+                exchange_dir: /tmp/unsigned-transactions
+        "};
+
+        let config: ClientConfig = serde_yaml::from_str(yaml)?;
+        let offline_signer = config
+            .offline_signer
+            .expect("the offline signer is configured");
+        assert_eq!(
+            offline_signer.exchange_dir,
+            PathBuf::from("/tmp/unsigned-transactions")
+        );
+        assert_eq!(
+            offline_signer.signature_timeout,
+            std::time::Duration::from_secs(3600)
+        );
+
+        Ok(())
+    }
+
     #[test]
     fn parses_no_exchange_object_config_file() -> TestResult {
         let yaml = indoc! {"
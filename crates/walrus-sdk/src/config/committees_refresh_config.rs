@@ -38,6 +38,14 @@ pub struct CommitteesRefreshConfig {
     pub epoch_change_distance_threshold: Duration,
     /// The size of the refresher channel.
     pub refresher_channel_size: usize,
+    /// The duration for which the Sui read client caches the system and staking objects and the
+    /// committees, from which the prices, the epoch, and the committees are derived.
+    ///
+    /// The cache is cleared whenever the committees are refreshed. Setting this to 0 disables the
+    /// cache.
+    #[serde(rename = "read_cache_ttl_secs")]
+    #[serde_as(as = "DurationSeconds")]
+    pub read_cache_ttl: Duration,
 }
 
 impl CommitteesRefreshConfig {
@@ -82,6 +90,7 @@ impl Default for CommitteesRefreshConfig {
             min_auto_refresh_interval: default::MIN_AUTO_REFRESH_INTERVAL,
             epoch_change_distance_threshold: default::EPOCH_CHANGE_DISTANCE_THRS,
             refresher_channel_size: default::REFRESHER_CHANNEL_SIZE,
+            read_cache_ttl: default::READ_CACHE_TTL,
         }
     }
 }
@@ -94,4 +103,5 @@ mod default {
     pub(crate) const MIN_AUTO_REFRESH_INTERVAL: Duration = Duration::from_secs(5);
     pub(crate) const EPOCH_CHANGE_DISTANCE_THRS: Duration = Duration::from_secs(300);
     pub const REFRESHER_CHANNEL_SIZE: usize = 100;
+    pub(crate) const READ_CACHE_TTL: Duration = Duration::from_secs(10);
 }
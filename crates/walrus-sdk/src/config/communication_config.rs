@@ -3,6 +3,7 @@
 
 use std::{
     num::{NonZeroU16, NonZeroUsize},
+    path::PathBuf,
     time::Duration,
 };
 
@@ -11,7 +12,9 @@ use serde_with::{serde_as, DurationMilliSeconds};
 use walrus_core::{
     encoding::{EncodingConfig, EncodingConfigTrait as _, Primary},
     EncodingType,
+    NetworkPublicKey,
 };
+use walrus_rest_client::client::CertificatePin;
 use walrus_utils::backoff::ExponentialBackoffConfig;
 
 use crate::config::{
@@ -46,6 +49,11 @@ pub struct ClientCommunicationConfig {
     pub disable_proxy: bool,
     /// Disable the use of operating system certificates for authenticating the communication.
     pub disable_native_certs: bool,
+    /// Paths to PEM files with additional root certificates that are trusted for authenticating
+    /// the communication, for example the certificate authority of a private deployment.
+    pub extra_root_certificates: Vec<PathBuf>,
+    /// Pins on the certificates presented by specific storage nodes.
+    pub certificate_pins: Vec<NodeCertificatePins>,
     /// The extra time allowed for sliver writes.
     pub sliver_write_extra_time: SliverWriteExtraTime,
     /// The delay for which the client waits before storing data to ensure that storage nodes have
@@ -55,6 +63,11 @@ pub struct ClientCommunicationConfig {
     pub registration_delay: Duration,
     /// The maximum total blob size allowed to store if multiple blobs are uploaded.
     pub max_total_blob_size: usize,
+    /// The number of threads used to encode and decode blobs.
+    ///
+    /// If `None`, the blobs are encoded and decoded on the global thread pool, which has one thread
+    /// per CPU.
+    pub encoding_threads: Option<usize>,
     /// The configuration for the backoff after committee change is detected.
     pub committee_change_backoff: ExponentialBackoffConfig,
 }
@@ -63,6 +76,8 @@ impl Default for ClientCommunicationConfig {
     fn default() -> Self {
         Self {
             disable_native_certs: false,
+            extra_root_certificates: Default::default(),
+            certificate_pins: Default::default(),
             max_concurrent_writes: Default::default(),
             max_concurrent_sliver_reads: Default::default(),
             max_concurrent_metadata_reads:
@@ -75,6 +90,7 @@ impl Default for ClientCommunicationConfig {
             sliver_write_extra_time: Default::default(),
             registration_delay: Duration::from_millis(200),
             max_total_blob_size: 1024 * 1024 * 1024, // 1GiB
+            encoding_threads: Default::default(),
             committee_change_backoff: ExponentialBackoffConfig::new(
                 Duration::from_secs(1),
                 Duration::from_secs(5),
@@ -85,6 +101,15 @@ impl Default for ClientCommunicationConfig {
 }
 
 impl ClientCommunicationConfig {
+    /// Returns the certificate pins configured for the storage node with the given public key.
+    pub fn certificate_pins_for(&self, public_key: &NetworkPublicKey) -> &[CertificatePin] {
+        self.certificate_pins
+            .iter()
+            .find(|node_pins| &node_pins.public_key == public_key)
+            .map(|node_pins| node_pins.pins.as_slice())
+            .unwrap_or_default()
+    }
+
     /// Provides a config with lower number of retries to speed up integration testing.
     #[cfg(any(test, feature = "test-utils"))]
     pub fn default_for_test() -> Self {
@@ -119,6 +144,15 @@ impl ClientCommunicationConfig {
     }
 }
 
+/// The certificate pins of a storage node.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct NodeCertificatePins {
+    /// The network public key of the storage node.
+    pub public_key: NetworkPublicKey,
+    /// The pins, at least one of which must match the certificate presented by the node.
+    pub pins: Vec<CertificatePin>,
+}
+
 /// Communication limits in the client.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct CommunicationLimits {
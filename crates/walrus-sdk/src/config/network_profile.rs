@@ -0,0 +1,192 @@
+// Copyright (c) Walrus Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Built-in client configurations of the Walrus networks.
+
+use std::{fmt, path::PathBuf, str::FromStr};
+
+use anyhow::{anyhow, Context as _};
+use serde::{Deserialize, Serialize};
+use sui_types::base_types::ObjectID;
+use walrus_sui::{
+    client::contract_config::ContractConfig,
+    config::WalletConfig,
+    utils::SuiNetwork,
+};
+
+use super::ClientConfig;
+
+// Keep the object IDs in sync with `setup/client_config.yaml`.
+const MAINNET_SYSTEM_OBJECT: &str =
+    "0x2134d52768ea07e8c43570ef975eb3e4c27a39fa6396bef985b5abc58d03ddd2";
+const MAINNET_STAKING_OBJECT: &str =
+    "0x10b9d30c28448939ce6c4d6c6e0ffce4a7f8a4ada8248bdad09ef8b70e4a3904";
+const MAINNET_SUBSIDIES_OBJECT: &str =
+    "0xb606eb177899edc2130c93bf65985af7ec959a2755dc126c953755e59324209e";
+const TESTNET_SYSTEM_OBJECT: &str =
+    "0x6c2547cbbc38025cf3adac45f63cb0a8d12ecf777cdc75a4971612bf97fdf6af";
+const TESTNET_STAKING_OBJECT: &str =
+    "0xbe46180321c30aab2f8b3501e24048377287fa708018a5b7c2792b35fe339ee3";
+const TESTNET_SUBSIDIES_OBJECT: &str =
+    "0xda799d85db0429765c8291c594d334349ef5bc09220e79ad397b30106161a0af";
+const TESTNET_EXCHANGE_OBJECTS: [&str; 4] = [
+    "0xf4d164ea2def5fe07dc573992a029e010dba09b1a8dcbc44c5c2e79567f39073",
+    "0x19825121c52080bb1073662231cfea5c0e4d905fd13e95f21e9a018f2ef41862",
+    "0x83b454e524c71f30803f4d6c302a86fb6a39e96cdfb873c2d1e93bc1c26a3bc5",
+    "0x8d63209cf8589ce7aef8f262437163c67577ed09f3e636a9d8e0813843fb8bf1",
+];
+/// The client configuration written by `scripts/local-testbed.sh`.
+const LOCALNET_CLIENT_CONFIG: &str = "./working_dir/client_config.yaml";
+/// The default path of the Sui wallet configuration.
+const DEFAULT_WALLET_CONFIG: &str = "~/.sui/sui_config/client.yaml";
+
+/// A Walrus network with a built-in client configuration.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum NetworkProfile {
+    /// Walrus Mainnet.
+    Mainnet,
+    /// Walrus Testnet.
+    Testnet,
+    /// A local Walrus network started with `scripts/local-testbed.sh`.
+    Localnet,
+}
+
+impl NetworkProfile {
+    /// Returns the client configuration of the network.
+    ///
+    /// The configuration uses the public RPC node of the Sui network, and the Sui wallet in the
+    /// default location with the environment of the network as active environment. The objects of
+    /// a local network differ between deployments, so the configuration of [`Self::Localnet`] is
+    /// read from the client configuration written by the local testbed.
+    pub fn client_config(self) -> anyhow::Result<ClientConfig> {
+        let (contract_config, exchange_objects) = match self {
+            Self::Mainnet => (
+                ContractConfig::new_with_subsidies(
+                    object_id(MAINNET_SYSTEM_OBJECT),
+                    object_id(MAINNET_STAKING_OBJECT),
+                    Some(object_id(MAINNET_SUBSIDIES_OBJECT)),
+                ),
+                vec![],
+            ),
+            Self::Testnet => (
+                ContractConfig::new_with_subsidies(
+                    object_id(TESTNET_SYSTEM_OBJECT),
+                    object_id(TESTNET_STAKING_OBJECT),
+                    Some(object_id(TESTNET_SUBSIDIES_OBJECT)),
+                ),
+                TESTNET_EXCHANGE_OBJECTS
+                    .into_iter()
+                    .map(object_id)
+                    .collect(),
+            ),
+            Self::Localnet => return self.localnet_client_config(),
+        };
+        Ok(ClientConfig {
+            contract_config,
+            exchange_objects,
+            wallet_config: Some(self.wallet_config()),
+            rpc_urls: vec![self.rpc_url()],
+            communication_config: Default::default(),
+            refresh_config: Default::default(),
+            gas_sponsor: None,
+            gas_escalation: Default::default(),
+            signing_service: None,
+            offline_signer: None,
+        })
+    }
+
+    fn localnet_client_config(self) -> anyhow::Result<ClientConfig> {
+        let (mut config, _) = ClientConfig::load_from_multi_config(LOCALNET_CLIENT_CONFIG, None)
+            .with_context(|| {
+                format!(
+                    "the localnet profile requires the client configuration of a local testbed \
+                    in '{LOCALNET_CLIENT_CONFIG}'; start one with `scripts/local-testbed.sh`"
+                )
+            })?;
+        if config.rpc_urls.is_empty() {
+            config.rpc_urls = vec![self.rpc_url()];
+        }
+        Ok(config)
+    }
+
+    /// Returns the Sui network on which the Walrus network runs.
+    pub fn sui_network(self) -> SuiNetwork {
+        match self {
+            Self::Mainnet => SuiNetwork::Mainnet,
+            Self::Testnet => SuiNetwork::Testnet,
+            Self::Localnet => SuiNetwork::Localnet,
+        }
+    }
+
+    fn rpc_url(self) -> String {
+        self.sui_network().env().rpc
+    }
+
+    fn wallet_config(self) -> WalletConfig {
+        let path = match home::home_dir() {
+            Some(home_dir) => home_dir.join(".sui").join("sui_config").join("client.yaml"),
+            None => PathBuf::from(DEFAULT_WALLET_CONFIG),
+        };
+        WalletConfig::PathWithOverride {
+            path,
+            active_env: Some(self.to_string()),
+            active_address: None,
+        }
+    }
+}
+
+impl fmt::Display for NetworkProfile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Self::Mainnet => "mainnet",
+            Self::Testnet => "testnet",
+            Self::Localnet => "localnet",
+        };
+        write!(f, "{name}")
+    }
+}
+
+impl FromStr for NetworkProfile {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "mainnet" => Ok(Self::Mainnet),
+            "testnet" => Ok(Self::Testnet),
+            "localnet" => Ok(Self::Localnet),
+            _ => Err(anyhow!(
+                "unknown network '{s}'; the built-in networks are 'mainnet', 'testnet', and \
+                'localnet'"
+            )),
+        }
+    }
+}
+
+fn object_id(hex: &str) -> ObjectID {
+    hex.parse()
+        .expect("the object IDs of the built-in networks are valid")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builds_the_configurations_of_the_public_networks() -> anyhow::Result<()> {
+        for network in [NetworkProfile::Mainnet, NetworkProfile::Testnet] {
+            let config = network.client_config()?;
+            assert_eq!(network.to_string().parse::<NetworkProfile>()?, network);
+            assert_eq!(config.rpc_urls, vec![network.sui_network().env().rpc]);
+        }
+        assert_eq!(
+            NetworkProfile::Testnet
+                .client_config()?
+                .exchange_objects
+                .len(),
+            TESTNET_EXCHANGE_OBJECTS.len()
+        );
+        assert!("devnet".parse::<NetworkProfile>().is_err());
+        Ok(())
+    }
+}
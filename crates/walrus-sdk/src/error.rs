@@ -3,8 +3,18 @@
 
 //! The errors for the storage client and the communication with storage nodes.
 
-use walrus_core::{BlobId, EncodingType, Epoch, SliverPairIndex, SliverType};
-use walrus_rest_client::error::{ClientBuildError, NodeError};
+use std::{fmt, path::PathBuf};
+
+use walrus_core::{
+    quilt::QuiltError,
+    BlobId,
+    EncodingType,
+    Epoch,
+    PublicKey,
+    SliverPairIndex,
+    SliverType,
+};
+use walrus_rest_client::error::{ClientBuildError, NodeError, NodeErrorKind};
 use walrus_sui::client::{SuiClientError, MIN_STAKING_THRESHOLD};
 
 /// Storing the metadata and the set of sliver pairs onto the storage node, and retrieving the
@@ -22,6 +32,26 @@ pub enum StoreError {
     Confirmation(NodeError),
 }
 
+impl StoreError {
+    /// Returns the error raised by the node.
+    pub fn node_error(&self) -> &NodeError {
+        match self {
+            Self::Metadata(error) | Self::Confirmation(error) => error,
+            Self::SliverStore(SliverStoreError { error, .. }) => error,
+        }
+    }
+
+    /// Returns true if storing on the node may succeed when retried.
+    pub fn is_retriable(&self) -> bool {
+        self.node_error().is_retriable()
+    }
+
+    /// Returns true if storing on the node fails again when retried.
+    pub fn is_permanent(&self) -> bool {
+        self.node_error().is_permanent()
+    }
+}
+
 /// The JWT secret could not be decoded from the provided string.
 #[derive(Debug, thiserror::Error, PartialEq)]
 #[error("the JWT secret could not be decoded from the provided string")]
@@ -39,6 +69,95 @@ pub struct SliverStoreError {
     pub error: NodeError,
 }
 
+/// The slivers received while reading a blob, and why they did not suffice to decode it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SliverRetrievalDiagnosis {
+    /// The type of the slivers from which the blob is decoded.
+    pub sliver_type: SliverType,
+    /// The number of slivers that were received and verified.
+    pub n_valid_slivers: usize,
+    /// The number of slivers required to decode the blob.
+    pub n_required_slivers: usize,
+    /// The number of slivers that failed verification against the blob metadata.
+    pub n_failed_verification: usize,
+    /// The nodes that sent at least one sliver that failed verification.
+    pub nodes_failing_verification: Vec<PublicKey>,
+    /// The number of slivers the nodes reported as not found.
+    pub n_not_found: usize,
+    /// The number of slivers the nodes refused to return because the blob is blocked.
+    pub n_forbidden: usize,
+    /// The number of slivers that could not be retrieved for other reasons, such as network
+    /// errors or unavailable nodes.
+    pub n_other_failures: usize,
+}
+
+impl SliverRetrievalDiagnosis {
+    /// Creates an empty diagnosis for decoding from `n_required_slivers` slivers of the type.
+    pub(crate) fn new(sliver_type: SliverType, n_required_slivers: usize) -> Self {
+        Self {
+            sliver_type,
+            n_valid_slivers: 0,
+            n_required_slivers,
+            n_failed_verification: 0,
+            nodes_failing_verification: vec![],
+            n_not_found: 0,
+            n_forbidden: 0,
+            n_other_failures: 0,
+        }
+    }
+
+    /// Returns the number of valid slivers that are still needed to decode the blob.
+    ///
+    /// Decoding may require slivers in addition to these, as the decoding of RaptorQ-encoded blobs
+    /// succeeds with high probability but not with certainty from the required number of slivers.
+    pub fn n_missing_slivers(&self) -> usize {
+        self.n_required_slivers.saturating_sub(self.n_valid_slivers)
+    }
+
+    /// Records the receipt of `n_slivers` valid slivers.
+    pub(crate) fn record_valid_slivers(&mut self, n_slivers: usize) {
+        self.n_valid_slivers += n_slivers;
+    }
+
+    /// Records the failure of retrieving a sliver from the node with the public key.
+    pub(crate) fn record_failure(&mut self, node: Option<&PublicKey>, error: &NodeError) {
+        if error.is_status_not_found() {
+            self.n_not_found += 1;
+        } else if error.is_blob_blocked() {
+            self.n_forbidden += 1;
+        } else if error.error_kind() == NodeErrorKind::VerificationFailed {
+            self.n_failed_verification += 1;
+            if let Some(node) = node {
+                if !self.nodes_failing_verification.contains(node) {
+                    self.nodes_failing_verification.push(node.clone());
+                }
+            }
+        } else {
+            self.n_other_failures += 1;
+        }
+    }
+}
+
+impl fmt::Display for SliverRetrievalDiagnosis {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "received {} of the {} valid {} slivers required to decode the blob ({} missing); \
+            {} slivers from {} nodes failed verification, {} were not found, {} were blocked, and \
+            {} could not be retrieved otherwise",
+            self.n_valid_slivers,
+            self.n_required_slivers,
+            self.sliver_type,
+            self.n_missing_slivers(),
+            self.n_failed_verification,
+            self.nodes_failing_verification.len(),
+            self.n_not_found,
+            self.n_forbidden,
+            self.n_other_failures,
+        )
+    }
+}
+
 /// A helper type for the client to handle errors.
 pub type ClientResult<T> = Result<T, ClientError>;
 
@@ -87,6 +206,40 @@ impl ClientError {
         matches!(&self.kind, ClientErrorKind::NoValidStatusReceived)
     }
 
+    /// Returns `true` if the operation may succeed when retried.
+    ///
+    /// This is the case for errors caused by the network, by unavailable storage nodes, or by an
+    /// epoch change.
+    pub fn is_retriable(&self) -> bool {
+        self.may_be_caused_by_epoch_change()
+            || matches!(
+                &self.kind,
+                ClientErrorKind::NoValidStatusReceived | ClientErrorKind::AllConnectionsFailed(_)
+            )
+    }
+
+    /// Returns `true` if the operation fails again when retried with the same inputs.
+    ///
+    /// Errors that are neither retriable nor permanent, such as [`ClientErrorKind::Other`], are
+    /// of an unknown cause.
+    pub fn is_permanent(&self) -> bool {
+        matches!(
+            &self.kind,
+            ClientErrorKind::BlobIdDoesNotExist
+                | ClientErrorKind::InvalidConfig
+                | ClientErrorKind::BlobIdBlocked(_)
+                | ClientErrorKind::NoCompatiblePaymentCoin
+                | ClientErrorKind::NoCompatibleGasCoins(_)
+                | ClientErrorKind::UnsupportedEncodingType(_)
+                | ClientErrorKind::StakeBelowThreshold(_)
+                | ClientErrorKind::FailedToLoadCerts(_)
+                | ClientErrorKind::FailedToLoadRootCertificates { .. }
+                | ClientErrorKind::StoreBlobInternal(_)
+                | ClientErrorKind::RangeNotSatisfiable { .. }
+                | ClientErrorKind::InvalidQuilt(_)
+        )
+    }
+
     /// Returns `true` if the error may have been caused by epoch change.
     pub fn may_be_caused_by_epoch_change(&self) -> bool {
         matches!(
@@ -101,6 +254,7 @@ impl ClientError {
                 | ClientErrorKind::NoMetadataReceived
                 // Cannot get slivers because we are behind by several epochs.
                 | ClientErrorKind::NotEnoughSlivers
+                | ClientErrorKind::NotEnoughValidSlivers(_)
                 // The client was notified that the committee has changed.
                 | ClientErrorKind::CommitteeChangeNotified
         )
@@ -136,6 +290,12 @@ pub enum ClientErrorKind {
     /// The client could not retrieve enough slivers to reconstruct the blob.
     #[error("could not retrieve enough slivers to reconstruct the blob")]
     NotEnoughSlivers,
+    /// The client could not decode the blob from the slivers it retrieved.
+    ///
+    /// The diagnosis describes how many slivers were received, how many more are needed, and why
+    /// the others could not be used.
+    #[error("could not retrieve enough valid slivers to reconstruct the blob: {0}")]
+    NotEnoughValidSlivers(Box<SliverRetrievalDiagnosis>),
     /// The blob ID is not certified on Walrus.
     ///
     /// This is deduced because either:
@@ -194,10 +354,27 @@ pub enum ClientErrorKind {
     /// Unable to load trusted certificates from the OS.
     #[error("unable to load trusted certificates from the OS: {0:?}")]
     FailedToLoadCerts(Vec<rustls_native_certs::Error>),
+    /// Unable to load the additional root certificates configured for the client.
+    #[error("unable to load root certificates from '{}': {reason}", path.display())]
+    FailedToLoadRootCertificates {
+        /// The path of the file containing the certificates.
+        path: PathBuf,
+        /// The reason for the failure.
+        reason: String,
+    },
     /// A failure internal to the node.
     #[error("client internal error: {0}")]
     Other(Box<dyn std::error::Error + Send + Sync + 'static>),
     /// An internal error occurred while storing a blob, usually indicating a bug.
     #[error("store blob internal error: {0}")]
     StoreBlobInternal(String),
+    /// The requested range does not contain any byte of the blob.
+    #[error("the requested range is not satisfiable for a blob of {blob_size} bytes")]
+    RangeNotSatisfiable {
+        /// The size of the blob.
+        blob_size: u64,
+    },
+    /// The blob is not a valid quilt, or the quilt does not contain the requested entry.
+    #[error("invalid quilt: {0}")]
+    InvalidQuilt(QuiltError),
 }
@@ -2,20 +2,38 @@
 // SPDX-License-Identifier: Apache-2.0
 
 //! The Walrus Rust SDK.
+//!
+//! The client that stores and reads blobs requires the default `native` feature. Without it, the
+//! crate compiles to `wasm32-unknown-unknown`, and browsers can use the [`read`] module to fetch
+//! slivers from the storage nodes and verify and decode blobs without trusting an aggregator.
 
+#[cfg(feature = "native")]
 pub mod active_committees;
+#[cfg(feature = "native")]
+pub mod blob_source;
+#[cfg(feature = "native")]
 pub mod blocklist;
+#[cfg(feature = "native")]
 pub mod client;
+#[cfg(feature = "native")]
 pub mod config;
+#[cfg(feature = "native")]
 pub mod error;
+pub mod read;
 pub mod store_when;
 /// Utilities for the Walrus SDK.
+#[cfg(feature = "native")]
 pub mod utils;
 
+#[cfg(feature = "native")]
 pub use sui_types::event::EventID;
+pub use walrus_core;
+pub use walrus_rest_client;
+#[cfg(feature = "native")]
 pub use walrus_sui as sui;
 
 /// Format the event ID as the transaction digest and the sequence number.
+#[cfg(feature = "native")]
 pub fn format_event_id(event_id: &EventID) -> String {
     format!("(tx: {}, seq: {})", event_id.tx_digest, event_id.event_seq)
 }
@@ -0,0 +1,212 @@
+// Copyright (c) Walrus Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Reading blobs directly from the storage nodes.
+//!
+//! Unlike the [`Client`][crate::client::Client], the functions in this module do not read the
+//! committee from Sui, and are therefore also available without the `native` feature, for example
+//! in browsers. The caller provides the clients of the storage nodes together with the shards that
+//! the nodes hold, which must be obtained from a trusted source. The metadata and the slivers
+//! received from the nodes are verified, so that the nodes themselves need not be trusted.
+
+use futures::{stream, StreamExt as _};
+use walrus_core::{
+    encoding::{
+        DataTooLargeError,
+        DecodingVerificationError,
+        EncodingConfig,
+        EncodingConfigTrait as _,
+        Primary,
+    },
+    metadata::{BlobMetadataApi as _, VerifiedBlobMetadataWithId},
+    BlobId,
+    ShardIndex,
+};
+use walrus_rest_client::client::Client;
+
+/// The maximum number of slivers that are requested concurrently.
+const MAX_CONCURRENT_SLIVER_READS: usize = 32;
+
+/// A storage node and the shards that it holds.
+#[derive(Debug, Clone)]
+pub struct NodeShards {
+    /// The client with which the storage node is reached.
+    pub client: Client,
+    /// The shards held by the storage node.
+    pub shards: Vec<ShardIndex>,
+}
+
+/// Error returned when reading a blob from the storage nodes fails.
+#[derive(Debug, thiserror::Error)]
+pub enum ReadError {
+    /// None of the storage nodes returned valid metadata for the blob.
+    #[error("none of the storage nodes returned valid metadata for the blob")]
+    NoValidMetadata,
+    /// The storage nodes returned too few valid slivers to decode the blob.
+    #[error("the storage nodes returned too few valid slivers to decode the blob")]
+    NotEnoughValidSlivers,
+    /// The blob is too large for the encoding config.
+    #[error(transparent)]
+    DataTooLarge(#[from] DataTooLargeError),
+    /// The decoded blob does not match the blob ID.
+    #[error(transparent)]
+    DecodingVerification(#[from] DecodingVerificationError),
+}
+
+/// Reads the blob with ID `blob_id` from the storage `nodes`.
+///
+/// The metadata is requested from one node after the other, until a node returns metadata that
+/// can be verified with the `encoding_config`. The primary slivers are then requested from the
+/// nodes holding them, and the blob is decoded as soon as enough valid slivers are received.
+pub async fn read_blob(
+    nodes: &[NodeShards],
+    encoding_config: &EncodingConfig,
+    blob_id: &BlobId,
+) -> Result<Vec<u8>, ReadError> {
+    let metadata = read_metadata(nodes, encoding_config, blob_id).await?;
+    let config = encoding_config.get_for_type(metadata.metadata().encoding_type());
+    let mut decoder = config.get_blob_decoder::<Primary>(metadata.metadata().unencoded_length())?;
+    let n_required_slivers = usize::from(config.n_source_symbols::<Primary>().get());
+
+    let mut requests = stream::iter(
+        nodes
+            .iter()
+            .flat_map(|node| node.shards.iter().map(move |shard| (&node.client, *shard))),
+    )
+    .map(|(client, shard)| {
+        let pair_index = shard.to_pair_index(encoding_config.n_shards(), blob_id);
+        client.get_and_verify_sliver::<Primary>(pair_index, &metadata, encoding_config)
+    })
+    .buffer_unordered(MAX_CONCURRENT_SLIVER_READS);
+
+    let mut n_valid_slivers = 0;
+    let mut pending_slivers = Vec::with_capacity(n_required_slivers);
+    while let Some(result) = requests.next().await {
+        match result {
+            Ok(sliver) => {
+                n_valid_slivers += 1;
+                pending_slivers.push(sliver);
+            }
+            Err(error) => {
+                tracing::debug!(%error, "retrieving sliver failed");
+                continue;
+            }
+        }
+        if n_valid_slivers < n_required_slivers {
+            continue;
+        }
+        if let Some((blob, _)) = decoder.decode_and_verify(blob_id, pending_slivers.drain(..))? {
+            return Ok(blob);
+        }
+    }
+    Err(ReadError::NotEnoughValidSlivers)
+}
+
+/// Returns the first metadata of the blob returned by the storage nodes that is valid and
+/// applicable to the `encoding_config`.
+async fn read_metadata(
+    nodes: &[NodeShards],
+    encoding_config: &EncodingConfig,
+    blob_id: &BlobId,
+) -> Result<VerifiedBlobMetadataWithId, ReadError> {
+    for node in nodes {
+        match node
+            .client
+            .get_and_verify_metadata(blob_id, encoding_config)
+            .await
+        {
+            Ok(metadata) if metadata.is_encoding_config_applicable(encoding_config) => {
+                return Ok(metadata);
+            }
+            Ok(_) => tracing::debug!("the metadata was encoded with other parameters"),
+            Err(error) => tracing::debug!(%error, "retrieving metadata failed"),
+        }
+    }
+    Err(ReadError::NoValidMetadata)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::num::NonZeroU16;
+
+    use walrus_core::{keys::ProtocolKeyPair, Sliver, DEFAULT_ENCODING};
+    use walrus_rest_client::test_utils::MockStorageNode;
+    use walrus_test_utils::Result as TestResult;
+
+    use super::*;
+
+    const N_SHARDS: u16 = 10;
+    const N_NODES: u16 = 4;
+
+    /// Returns mock storage nodes, which hold the shards in turn, storing the blob.
+    fn nodes_storing_blob(
+        encoding_config: &EncodingConfig,
+        blob: &[u8],
+    ) -> TestResult<(BlobId, Vec<(MockStorageNode, NodeShards)>)> {
+        let (pairs, metadata) = encoding_config
+            .get_for_type(DEFAULT_ENCODING)
+            .encode_with_metadata(blob)?;
+        let blob_id = *metadata.blob_id();
+        let n_shards = encoding_config.n_shards();
+
+        let nodes = (0..N_NODES)
+            .map(|index| {
+                let node = MockStorageNode::new(ProtocolKeyPair::generate(), 0);
+                let shards: Vec<_> = (index..N_SHARDS)
+                    .step_by(N_NODES.into())
+                    .map(ShardIndex)
+                    .collect();
+                node.insert_metadata(&metadata);
+                for shard in &shards {
+                    let pair_index = shard.to_pair_index(n_shards, &blob_id);
+                    let pair = &pairs[pair_index.as_usize()];
+                    node.insert_sliver(blob_id, pair_index, Sliver::Primary(pair.primary.clone()));
+                }
+                let node_shards = NodeShards {
+                    client: node.client(),
+                    shards,
+                };
+                (node, node_shards)
+            })
+            .collect();
+        Ok((blob_id, nodes))
+    }
+
+    #[tokio::test]
+    async fn reads_blob_from_storage_nodes() -> TestResult {
+        let encoding_config = EncodingConfig::new(NonZeroU16::new(N_SHARDS).unwrap());
+        let blob = walrus_test_utils::random_data(1024);
+        let (blob_id, nodes) = nodes_storing_blob(&encoding_config, &blob)?;
+        let nodes: Vec<_> = nodes
+            .into_iter()
+            .map(|(_, node_shards)| node_shards)
+            .collect();
+
+        let read_blob = read_blob(&nodes, &encoding_config, &blob_id).await?;
+
+        assert_eq!(read_blob, blob);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn fails_if_too_few_nodes_return_valid_slivers() -> TestResult {
+        let encoding_config = EncodingConfig::new(NonZeroU16::new(N_SHARDS).unwrap());
+        let blob = walrus_test_utils::random_data(1024);
+        let (blob_id, nodes) = nodes_storing_blob(&encoding_config, &blob)?;
+        // The metadata is read from the first node, which is the only one returning valid data.
+        for (node, _) in &nodes[1..] {
+            node.set_corrupt_responses(true);
+        }
+        let nodes: Vec<_> = nodes
+            .into_iter()
+            .map(|(_, node_shards)| node_shards)
+            .collect();
+
+        let error = read_blob(&nodes, &encoding_config, &blob_id)
+            .await
+            .expect_err("the blob cannot be decoded from the slivers of a single node");
+
+        assert!(matches!(error, ReadError::NotEnoughValidSlivers));
+        Ok(())
+    }
+}
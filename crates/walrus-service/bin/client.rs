@@ -42,8 +42,10 @@ fn client() -> Result<()> {
     let runner = ClientCommandRunner::new(
         &app.config,
         app.context.as_deref(),
+        app.network,
         &app.wallet,
         app.gas_budget,
+        app.sponsor,
         app.json,
     );
 
@@ -57,8 +59,10 @@ fn client() -> Result<()> {
         }
         Commands::Daemon(command) => {
             let metrics_address = command.get_metrics_address();
+            let export_spans = command.exports_spans();
 
-            let runtime = MetricsAndLoggingRuntime::start(metrics_address)?;
+            let runtime =
+                MetricsAndLoggingRuntime::start_with_tracing(metrics_address, export_spans)?;
             utils::export_build_info(&runtime.registry, VERSION);
 
             tracing::debug!(%metrics_address, "started metrics and logging on separate runtime");
@@ -0,0 +1,56 @@
+// Copyright (c) Walrus Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Walrus blob lifecycle indexer entry point.
+
+use std::path::PathBuf;
+
+use clap::Parser;
+use walrus_service::{
+    common::utils::MetricsAndLoggingRuntime,
+    indexer::{run_indexer_database_migrations, start_indexer, IndexerConfig, VERSION},
+    utils::load_from_yaml,
+};
+
+/// Index the lifecycle of Walrus blobs into a PostgreSQL database.
+#[derive(Parser)]
+#[command(
+    name = env!("CARGO_BIN_NAME"),
+    version = VERSION,
+    rename_all = "kebab-case",
+)]
+#[derive(Debug)]
+struct Args {
+    #[arg(long, short, help = "Specify the config file path to use")]
+    config: PathBuf,
+    #[arg(
+        long,
+        short,
+        help = "Override the metrics address to use (ie: 127.0.0.1:10186)"
+    )]
+    metrics_address: Option<std::net::SocketAddr>,
+}
+
+fn main() {
+    let args = Args::parse();
+    let mut config: IndexerConfig = load_from_yaml(&args.config).expect("loading config from yaml");
+    if let Some(metrics_address) = args.metrics_address {
+        config.metrics_address = metrics_address;
+    }
+
+    let rt = tokio::runtime::Runtime::new().expect("creating tokio runtime");
+    let _guard = rt.enter();
+
+    let metrics_runtime = MetricsAndLoggingRuntime::new(config.metrics_address, None)
+        .expect("starting metrics runtime");
+
+    // Run migrations before starting the indexer.
+    run_indexer_database_migrations(&config).expect("running the database migrations");
+
+    let result = rt.block_on(start_indexer(config, &metrics_runtime));
+    if let Err(error) = result {
+        tracing::error!(?error, "the indexer encountered an error");
+    }
+    tracing::error!("the indexer exited prematurely");
+    std::process::exit(1);
+}
@@ -27,6 +27,7 @@ use tokio::{
 use tokio_util::sync::CancellationToken;
 use walrus_core::{
     keys::{NetworkKeyPair, ProtocolKeyPair},
+    BlobId,
     Epoch,
 };
 use walrus_service::{
@@ -35,11 +36,13 @@ use walrus_service::{
         config::{self, defaults::REST_API_PORT, StorageNodeConfig},
         dbtool::DbToolCommands,
         events::event_processor_runtime::EventProcessorRuntime,
+        metadata_backfill::MetadataBackfillOptions,
         server::{RestApiConfig, RestApiServer},
         system_events::EventManager,
         ConfigLoader,
         StorageNode,
         StorageNodeConfigLoader,
+        BLOCKLIST_AUDIT_TARGET,
     },
     utils::{
         self,
@@ -104,6 +107,55 @@ enum Commands {
         ignore_sync_failures: bool,
     },
 
+    /// Validate a node configuration file before starting the node.
+    ///
+    /// Checks that the configured paths exist and are writable, that the keys can be loaded, that
+    /// the configured socket addresses can be bound, and that the node information registered
+    /// on chain matches the configuration.
+    CheckConfig {
+        /// Path to the Walrus node configuration file.
+        #[arg(long)]
+        config_path: PathBuf,
+        /// Skip the checks against the node information registered on chain.
+        #[arg(long)]
+        skip_on_chain_checks: bool,
+    },
+
+    /// Preview the shards the node gains and loses in the next epoch.
+    ///
+    /// Prints the shards assigned to the node by the committee selected for the next epoch, and an
+    /// estimate of the data the node needs to sync for the gained shards. This allows provisioning
+    /// disk space and bandwidth ahead of the epoch change.
+    ShardPreview {
+        /// Path to the Walrus node configuration file.
+        #[arg(long)]
+        config_path: PathBuf,
+        /// Print the preview as JSON.
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Restore missing or corrupted blob metadata in the node's database.
+    ///
+    /// Checks the metadata of all certified blobs, and reconstructs the damaged metadata from the
+    /// primary slivers stored by the node, or retrieves it from other storage nodes if the node
+    /// does not store enough slivers. This repairs databases damaged by partial deletions without
+    /// resyncing the shards. The node must be stopped while the command runs.
+    BackfillMetadata {
+        /// Path to the Walrus node configuration file.
+        #[arg(long)]
+        config_path: PathBuf,
+        /// Only report the blobs with missing or corrupted metadata, without restoring it.
+        #[arg(long)]
+        dry_run: bool,
+        /// Do not retrieve metadata from other storage nodes.
+        #[arg(long)]
+        local_only: bool,
+        /// Print the report as JSON.
+        #[arg(long)]
+        json: bool,
+    },
+
     /// Generate a new key for use with the Walrus protocol, and writes it to a file.
     KeyGen {
         /// Path to the file at which the key will be created [default: ./<KEY_TYPE>.key].
@@ -140,6 +192,18 @@ enum Commands {
         force: bool,
     },
 
+    /// Manage the blocklist of blob IDs whose data the node does not serve.
+    ///
+    /// Changes are written to the file configured as `blocklist_path` and are picked up by a
+    /// running node within a minute. Additions and removals are recorded in the audit log.
+    Blocklist {
+        /// Path to the Walrus node configuration file.
+        #[arg(long)]
+        config_path: PathBuf,
+        #[command(subcommand)]
+        command: BlocklistCommands,
+    },
+
     /// Database inspection and maintenance tools.
     /// Hidden command for emergency use only.
     #[command(hide = true)]
@@ -154,6 +218,29 @@ enum Commands {
     Catchup(CatchupArgs),
 }
 
+#[derive(Subcommand, Debug, Clone)]
+#[command(rename_all = "kebab-case")]
+enum BlocklistCommands {
+    /// Add a blob ID to the blocklist.
+    Add {
+        /// The ID of the blob to block.
+        blob_id: BlobId,
+        /// The reason for blocking the blob, which is recorded in the audit log.
+        #[arg(long)]
+        reason: Option<String>,
+    },
+    /// Remove a blob ID from the blocklist.
+    Remove {
+        /// The ID of the blob to unblock.
+        blob_id: BlobId,
+        /// The reason for unblocking the blob, which is recorded in the audit log.
+        #[arg(long)]
+        reason: Option<String>,
+    },
+    /// List the blocked blob IDs.
+    List,
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
 enum KeyType {
     /// A protocol key used to sign Walrus protocol messages.
@@ -421,6 +508,29 @@ fn main() -> anyhow::Result<()> {
             }
         },
 
+        Commands::CheckConfig {
+            config_path,
+            skip_on_chain_checks,
+        } => commands::check_config(&config_path, skip_on_chain_checks)?,
+
+        Commands::ShardPreview { config_path, json } => {
+            commands::shard_preview(&config_path, json)?
+        }
+
+        Commands::BackfillMetadata {
+            config_path,
+            dry_run,
+            local_only,
+            json,
+        } => commands::backfill_metadata(
+            &config_path,
+            MetadataBackfillOptions {
+                dry_run,
+                local_only,
+            },
+            json,
+        )?,
+
         Commands::KeyGen {
             out,
             key_type,
@@ -444,6 +554,11 @@ fn main() -> anyhow::Result<()> {
             commands::generate_config(path_args, config_args, force)?;
         }
 
+        Commands::Blocklist {
+            config_path,
+            command,
+        } => commands::blocklist(&config_path, command)?,
+
         Commands::DbTool { command } => command.execute()?,
 
         Commands::Catchup(catchup_args) => commands::catchup(catchup_args)?,
@@ -465,7 +580,9 @@ mod commands {
     use walrus_core::{
         ensure,
         keys::{SupportedKeyPair, TaggedKeyPair},
+        ShardIndex,
     };
+    use walrus_sdk::blocklist::Blocklist;
     use walrus_service::{
         node::{
             config::TlsConfig,
@@ -473,6 +590,7 @@ mod commands {
                 event_processor::{EventProcessor, EventProcessorRuntimeConfig, SystemConfig},
                 EventProcessorConfig,
             },
+            metadata_backfill,
             DatabaseConfig,
         },
         utils,
@@ -485,7 +603,11 @@ mod commands {
             SuiReadClient,
         },
         config::{load_wallet_context_from_path, WalletConfig},
-        types::move_structs::NodeMetadata,
+        types::{
+            move_structs::{NodeMetadata, StorageNode as StorageNodeInfo},
+            NetworkAddress,
+            StorageNodeCap,
+        },
     };
     use walrus_utils::{backoff::ExponentialBackoffConfig, metrics::Registry};
 
@@ -1088,6 +1210,419 @@ mod commands {
         Ok(())
     }
 
+    /// Validates the node configuration at `config_path` and prints the result of each check.
+    ///
+    /// Returns an error if any of the checks fails.
+    #[tokio::main]
+    pub(crate) async fn check_config(
+        config_path: &Path,
+        skip_on_chain_checks: bool,
+    ) -> anyhow::Result<()> {
+        let config: StorageNodeConfig = load_from_yaml(config_path).with_context(|| {
+            format!(
+                "unable to parse the node configuration at '{}'",
+                config_path.display()
+            )
+        })?;
+        println!(
+            "Checking the node configuration at '{}':",
+            config_path.display()
+        );
+
+        let mut report = ConfigCheckReport::default();
+        check_local_config(&config, &mut report);
+        if skip_on_chain_checks {
+            println!("Skipping the checks against the on-chain node information.");
+        } else {
+            check_on_chain_node_info(&config, &mut report).await;
+        }
+
+        report.into_result()
+    }
+
+    #[tokio::main]
+    pub(crate) async fn shard_preview(config_path: &Path, json: bool) -> anyhow::Result<()> {
+        let config: StorageNodeConfig = load_from_yaml(config_path).with_context(|| {
+            format!(
+                "unable to parse the node configuration at '{}'",
+                config_path.display()
+            )
+        })?;
+        let (read_client, node_capability) = get_node_capability(&config).await?;
+        let preview =
+            utils::ShardAssignmentPreview::fetch(&read_client, node_capability.node_id).await?;
+
+        if json {
+            println!("{}", serde_json::to_string_pretty(&preview)?);
+            return Ok(());
+        }
+
+        let display_shards = |shards: &[ShardIndex]| {
+            if shards.is_empty() {
+                "none".to_owned()
+            } else {
+                shards
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            }
+        };
+        println!(
+            "Shard assignment of node {} in epoch {}:",
+            preview.node_id, preview.next_epoch
+        );
+        println!(
+            "  current shards ({}): {}",
+            preview.current_shards.len(),
+            display_shards(&preview.current_shards)
+        );
+        println!(
+            "  next shards ({}): {}",
+            preview.next_shards.len(),
+            display_shards(&preview.next_shards)
+        );
+        println!(
+            "  gained shards ({}): {}",
+            preview.gained_shards.len(),
+            display_shards(&preview.gained_shards)
+        );
+        println!(
+            "  lost shards ({}): {}",
+            preview.lost_shards.len(),
+            display_shards(&preview.lost_shards)
+        );
+        println!(
+            "Estimated data to sync: {} bytes ({} bytes per shard); this is an upper bound based \
+            on the storage capacity reserved in the system.",
+            preview.estimated_bytes_to_sync(),
+            preview.estimated_bytes_per_shard,
+        );
+        Ok(())
+    }
+
+    #[tokio::main]
+    pub(crate) async fn backfill_metadata(
+        config_path: &Path,
+        options: MetadataBackfillOptions,
+        json: bool,
+    ) -> anyhow::Result<()> {
+        let mut config: StorageNodeConfig = load_from_yaml(config_path).with_context(|| {
+            format!(
+                "unable to parse the node configuration at '{}'",
+                config_path.display()
+            )
+        })?;
+        config.load_keys()?;
+
+        let report = metadata_backfill::backfill_metadata(&config, options).await?;
+
+        if json {
+            println!("{}", serde_json::to_string_pretty(&report)?);
+            return Ok(());
+        }
+        println!("Metadata backfill: {report}.");
+        if options.dry_run {
+            for blob_id in &report.damaged {
+                println!("  missing or corrupted: {blob_id}");
+            }
+        }
+        for blob_id in &report.unrecoverable {
+            println!("  unrecoverable: {blob_id}");
+        }
+        Ok(())
+    }
+
+    pub(crate) fn blocklist(config_path: &Path, command: BlocklistCommands) -> anyhow::Result<()> {
+        let config: StorageNodeConfig = load_from_yaml(config_path).with_context(|| {
+            format!(
+                "unable to parse the node configuration at '{}'",
+                config_path.display()
+            )
+        })?;
+        let blocklist_path = config
+            .blocklist_path
+            .context("the configuration does not specify a `blocklist_path`")?;
+        let blocklist = Blocklist::new(&Some(blocklist_path.clone()))?;
+
+        match command {
+            BlocklistCommands::Add { blob_id, reason } => {
+                blocklist.insert(blob_id)?;
+                tracing::info!(
+                    target: BLOCKLIST_AUDIT_TARGET,
+                    walrus.blob_id = %blob_id,
+                    reason = reason.as_deref(),
+                    "added blob to the blocklist"
+                );
+                println!(
+                    "Added blob {blob_id} to the blocklist at '{}'.",
+                    blocklist_path.display()
+                );
+                if config.delete_blocked_blob_data {
+                    println!("The node deletes the data it stores for the blob.");
+                }
+            }
+            BlocklistCommands::Remove { blob_id, reason } => {
+                blocklist.remove(&blob_id)?;
+                tracing::info!(
+                    target: BLOCKLIST_AUDIT_TARGET,
+                    walrus.blob_id = %blob_id,
+                    reason = reason.as_deref(),
+                    "removed blob from the blocklist"
+                );
+                println!(
+                    "Removed blob {blob_id} from the blocklist at '{}'.",
+                    blocklist_path.display()
+                );
+            }
+            BlocklistCommands::List => {
+                let mut blob_ids: Vec<_> = blocklist
+                    .blocked_blob_ids()
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect();
+                blob_ids.sort();
+                for blob_id in blob_ids {
+                    println!("{blob_id}");
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Collects and prints the outcomes of the individual configuration checks.
+    #[derive(Debug, Default)]
+    pub(super) struct ConfigCheckReport {
+        n_failed: usize,
+    }
+
+    impl ConfigCheckReport {
+        pub(super) fn record(&mut self, description: impl Display, result: anyhow::Result<()>) {
+            match result {
+                Ok(()) => println!("  [ OK ] {description}"),
+                Err(error) => {
+                    self.n_failed += 1;
+                    println!("  [FAIL] {description}: {error:#}");
+                }
+            }
+        }
+
+        pub(super) fn into_result(self) -> anyhow::Result<()> {
+            ensure!(
+                self.n_failed == 0,
+                "{} configuration check(s) failed; fix the issues above before starting the node",
+                self.n_failed
+            );
+            println!("All configuration checks passed.");
+            Ok(())
+        }
+    }
+
+    /// Runs the checks that do not require access to the Sui network.
+    pub(super) fn check_local_config(config: &StorageNodeConfig, report: &mut ConfigCheckReport) {
+        report.record(
+            format_args!(
+                "storage path '{}' is a writable directory",
+                config.storage_path.display()
+            ),
+            check_directory_is_writable(&config.storage_path),
+        );
+        if let Some(blocklist_path) = config.blocklist_path.as_ref() {
+            report.record(
+                format_args!("blocklist at '{}' can be loaded", blocklist_path.display()),
+                Blocklist::new(&config.blocklist_path).map(|_| ()),
+            );
+        }
+        report.record(
+            "protocol key pair can be loaded",
+            config.protocol_key_pair.load_transient().map(|_| ()),
+        );
+        if let Some(next_protocol_key_pair) = config.next_protocol_key_pair.as_ref() {
+            report.record(
+                "next protocol key pair can be loaded",
+                next_protocol_key_pair.load_transient().map(|_| ()),
+            );
+        }
+        report.record(
+            "network key pair can be loaded",
+            config.network_key_pair.load_transient().map(|_| ()),
+        );
+        if let Some(certificate_path) = config.tls.certificate_path.as_ref() {
+            report.record(
+                format_args!(
+                    "TLS certificate at '{}' is readable",
+                    certificate_path.display()
+                ),
+                fs::read(certificate_path)
+                    .map(|_| ())
+                    .context("unable to read the certificate file"),
+            );
+        }
+        report.record(
+            "public host does not contain a port",
+            if config.public_host.contains(':') {
+                Err(anyhow::anyhow!(
+                    "'{}' contains ':'; specify the port with the `public_port` parameter",
+                    config.public_host
+                ))
+            } else {
+                Ok(())
+            },
+        );
+        report.record(
+            format_args!("REST API address {} can be bound", config.rest_api_address),
+            check_address_is_bindable(config.rest_api_address),
+        );
+        report.record(
+            format_args!("metrics address {} can be bound", config.metrics_address),
+            check_address_is_bindable(config.metrics_address),
+        );
+    }
+
+    /// Checks that `path` is a writable directory, or that it can be created.
+    fn check_directory_is_writable(path: &Path) -> anyhow::Result<()> {
+        // The node creates the directory on startup, so check the closest existing ancestor.
+        let Some(existing_dir) = path.ancestors().find(|ancestor| ancestor.exists()) else {
+            bail!("neither the directory nor any of its parents exist");
+        };
+        ensure!(
+            existing_dir.is_dir(),
+            "'{}' exists but is not a directory",
+            existing_dir.display()
+        );
+
+        let probe_path = existing_dir.join(".walrus-node-check-config");
+        fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&probe_path)
+            .and_then(|mut file| file.write_all(b"probe"))
+            .with_context(|| format!("'{}' is not writable", existing_dir.display()))?;
+        fs::remove_file(&probe_path).with_context(|| {
+            format!("unable to remove the probe file '{}'", probe_path.display())
+        })?;
+
+        Ok(())
+    }
+
+    /// Checks that a TCP listener can be bound to `address`.
+    fn check_address_is_bindable(address: SocketAddr) -> anyhow::Result<()> {
+        std::net::TcpListener::bind(address)
+            .map(|_| ())
+            .context("unable to bind the address; is another process (or the node) using it?")
+    }
+
+    /// Checks the configuration against the node information registered on chain.
+    async fn check_on_chain_node_info(config: &StorageNodeConfig, report: &mut ConfigCheckReport) {
+        let node_info = match get_registered_node_info(config).await {
+            Ok(node_info) => {
+                report.record("registered node information can be retrieved", Ok(()));
+                node_info
+            }
+            Err(error) => {
+                report.record("registered node information can be retrieved", Err(error));
+                return;
+            }
+        };
+
+        if let Ok(protocol_key_pair) = config.protocol_key_pair.load_transient() {
+            let local_public_key = protocol_key_pair.public();
+            let matches_on_chain = &node_info.public_key == local_public_key
+                || node_info.next_epoch_public_key.as_ref() == Some(local_public_key);
+            report.record(
+                "protocol public key matches the registered public key",
+                if matches_on_chain {
+                    Ok(())
+                } else {
+                    Err(anyhow::anyhow!(
+                        "the configured key has public key {local_public_key}, but the registered \
+                        public key is {}",
+                        node_info.public_key
+                    ))
+                },
+            );
+        }
+        if let Ok(network_key_pair) = config.network_key_pair.load_transient() {
+            let local_network_key = network_key_pair.public();
+            report.record(
+                "network public key matches the registered network public key",
+                if &node_info.network_public_key == local_network_key {
+                    Ok(())
+                } else {
+                    Err(anyhow::anyhow!(
+                        "the registered network public key differs from the configured one; the \
+                        node updates it on chain on startup if the config synchronizer is enabled"
+                    ))
+                },
+            );
+        }
+
+        let local_address =
+            NetworkAddress(format!("{}:{}", config.public_host, config.public_port));
+        report.record(
+            "public address matches the registered network address",
+            if node_info.network_address == local_address {
+                Ok(())
+            } else {
+                Err(anyhow::anyhow!(
+                    "the configured address is '{}', but the registered address is '{}'; the \
+                    node updates it on chain on startup if the config synchronizer is enabled",
+                    local_address.0,
+                    node_info.network_address.0
+                ))
+            },
+        );
+    }
+
+    /// Retrieves the node information registered on chain for the node's capability object.
+    async fn get_registered_node_info(
+        config: &StorageNodeConfig,
+    ) -> anyhow::Result<StorageNodeInfo> {
+        let (read_client, node_capability) = get_node_capability(config).await?;
+        let staking_pool = read_client
+            .get_staking_pool(node_capability.node_id)
+            .await
+            .context("unable to retrieve the node's staking pool")?;
+        Ok(staking_pool.node_info)
+    }
+
+    /// Connects to Sui and retrieves the node's capability object, either from the ID in the
+    /// configuration or as the one owned by the node's wallet.
+    async fn get_node_capability(
+        config: &StorageNodeConfig,
+    ) -> anyhow::Result<(SuiReadClient, StorageNodeCap)> {
+        let sui_config = config
+            .sui
+            .as_ref()
+            .context("the configuration does not contain a `sui` section")?;
+        let read_client = sui_config
+            .new_read_client()
+            .await
+            .context("unable to connect to the Sui RPC node")?;
+
+        let node_capability: StorageNodeCap = if let Some(cap_id) = config.storage_node_cap {
+            read_client
+                .sui_client()
+                .get_sui_object(cap_id)
+                .await
+                .with_context(|| format!("unable to retrieve the capability object {cap_id}"))?
+        } else {
+            let address = WalletConfig::load_wallet_context(Some(&sui_config.wallet_config))
+                .and_then(|mut wallet| wallet.active_address())
+                .context("unable to determine the address of the node's wallet")?;
+            read_client
+                .get_address_capability_object(address)
+                .await?
+                .with_context(|| {
+                    format!(
+                        "no storage-node capability object is owned by {address}; register the \
+                        node with `walrus-node register` or set `storage_node_cap`"
+                    )
+                })?
+        };
+        Ok((read_client, node_capability))
+    }
+
     /// Creates a new file at the given path. If force is true, overwrites any existing file.
     /// Otherwise, fails if the file already exists.
     fn create_file(path: &Path, force: bool) -> Result<File, std::io::Error> {
@@ -1235,6 +1770,87 @@ mod tests {
 
     use super::*;
 
+    fn config_for_local_checks(dir: &Path) -> StorageNodeConfig {
+        StorageNodeConfig {
+            storage_path: dir.join("db"),
+            protocol_key_pair: PathOrInPlace::from_path(dir.join("protocol.key")),
+            network_key_pair: PathOrInPlace::from_path(dir.join("network.key")),
+            rest_api_address: (Ipv4Addr::LOCALHOST, 0).into(),
+            metrics_address: (Ipv4Addr::LOCALHOST, 0).into(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn check_local_config_succeeds_for_valid_config() -> Result<()> {
+        let dir = TempDir::new()?;
+        let config = config_for_local_checks(dir.path());
+        commands::keygen(
+            &dir.path().join("protocol.key"),
+            KeyType::Protocol,
+            false,
+            KeyFormat::Tagged,
+        )?;
+        commands::keygen(
+            &dir.path().join("network.key"),
+            KeyType::Network,
+            false,
+            KeyFormat::Pkcs8,
+        )?;
+
+        let mut report = commands::ConfigCheckReport::default();
+        commands::check_local_config(&config, &mut report);
+        report.into_result()?;
+
+        assert!(
+            !dir.path().join("db").exists(),
+            "checks must not create the storage directory"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn check_local_config_fails_for_missing_keys() -> Result<()> {
+        let dir = TempDir::new()?;
+        let config = config_for_local_checks(dir.path());
+
+        let mut report = commands::ConfigCheckReport::default();
+        commands::check_local_config(&config, &mut report);
+        report
+            .into_result()
+            .expect_err("the key files do not exist");
+
+        Ok(())
+    }
+
+    #[test]
+    fn check_local_config_fails_for_storage_path_that_is_a_file() -> Result<()> {
+        let dir = TempDir::new()?;
+        let mut config = config_for_local_checks(dir.path());
+        commands::keygen(
+            &dir.path().join("protocol.key"),
+            KeyType::Protocol,
+            false,
+            KeyFormat::Tagged,
+        )?;
+        commands::keygen(
+            &dir.path().join("network.key"),
+            KeyType::Network,
+            false,
+            KeyFormat::Pkcs8,
+        )?;
+        config.storage_path = dir.path().join("protocol.key");
+
+        let mut report = commands::ConfigCheckReport::default();
+        commands::check_local_config(&config, &mut report);
+        report
+            .into_result()
+            .expect_err("the storage path is not a directory");
+
+        Ok(())
+    }
+
     #[test]
     fn generate_key_pair_saves_base64_key_to_file() -> Result<()> {
         let dir = TempDir::new()?;
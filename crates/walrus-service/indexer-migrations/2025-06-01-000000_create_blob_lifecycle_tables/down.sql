@@ -0,0 +1,4 @@
+DROP TABLE blob_object;
+DROP TABLE blob_lifecycle_event;
+DROP TABLE epoch_start;
+DROP TABLE indexer_cursor;
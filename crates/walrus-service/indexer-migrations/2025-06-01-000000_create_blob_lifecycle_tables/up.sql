@@ -0,0 +1,81 @@
+-- Initialize the tables of the blob lifecycle indexer.
+
+CREATE TABLE blob_object (
+    -- The ID of the Sui blob object.
+    object_id        BYTEA   NOT NULL,
+    -- The Walrus blob ID.
+    blob_id          BYTEA   NOT NULL,
+    -- The unencoded size of the blob in bytes.
+    size             BIGINT  NOT NULL,
+    -- The erasure coding type of the blob.
+    encoding_type    TEXT    NOT NULL,
+    -- Whether the blob object is deletable.
+    deletable        BOOLEAN NOT NULL,
+    -- The epoch in which the blob object was registered.
+    registered_epoch BIGINT  NOT NULL,
+    -- The epoch in which the blob object was first certified, if any.
+    certified_epoch  BIGINT      NULL,
+    -- The end epoch (exclusive) of the blob object, including extensions.
+    end_epoch        BIGINT  NOT NULL,
+    -- The current status of the blob object.
+    status           TEXT    NOT NULL,
+    -- When was the row last updated?
+    updated_at       TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (object_id),
+    CONSTRAINT valid_blob_object_status
+    CHECK (status IN ('registered', 'certified', 'invalid', 'deleted', 'expired')),
+    CONSTRAINT valid_blob_object_blob_id
+    CHECK (LENGTH(blob_id) = 32)
+);
+
+CREATE INDEX blob_object_blob_id ON blob_object (blob_id);
+
+CREATE INDEX blob_object_expiry
+    ON blob_object (end_epoch)
+    WHERE status IN ('registered', 'certified');
+
+CREATE TABLE blob_lifecycle_event (
+    -- Index since first Walrus event.
+    element_index              BIGINT NOT NULL,
+    -- Sequence number of the checkpoint.
+    checkpoint_sequence_number BIGINT NOT NULL,
+    -- Digest of the transaction.
+    transaction_digest         BYTEA  NOT NULL,
+    -- Index of the event within the transaction.
+    event_seq                  BIGINT NOT NULL,
+    -- The kind of the event: 'registered', 'certified', 'extended', 'deleted', 'invalid', or
+    -- 'deny_list_deleted'.
+    kind                       TEXT   NOT NULL,
+    -- The Walrus blob ID.
+    blob_id                    BYTEA  NOT NULL,
+    -- The ID of the Sui blob object, unless the event refers to all blob objects of the blob ID.
+    object_id                  BYTEA      NULL,
+    -- The epoch in which the event was emitted.
+    epoch                      BIGINT NOT NULL,
+    -- The end epoch (exclusive) of the blob object after the event, if any.
+    end_epoch                  BIGINT     NULL,
+    -- When was the event recorded?
+    created_at                 TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (element_index)
+);
+
+CREATE INDEX blob_lifecycle_event_blob_id ON blob_lifecycle_event (blob_id);
+
+CREATE TABLE epoch_start (
+    -- The epoch number.
+    epoch      BIGINT NOT NULL,
+    -- When the start of the epoch was noticed.
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (epoch)
+);
+
+CREATE TABLE indexer_cursor (
+    -- Single-row table.
+    id                 BOOLEAN NOT NULL DEFAULT TRUE,
+    -- The index of the next element of the event stream to process.
+    next_element_index BIGINT  NOT NULL,
+    -- When was the cursor last updated?
+    updated_at         TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (id),
+    CONSTRAINT single_row CHECK (id)
+);
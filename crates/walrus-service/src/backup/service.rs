@@ -601,8 +601,13 @@ async fn backup_fetcher(
         contract_config: backup_config.sui.contract_config.clone(),
         exchange_objects: vec![],
         wallet_config: None,
+        rpc_urls: vec![],
         communication_config: ClientCommunicationConfig::default(),
         refresh_config: Default::default(),
+        gas_sponsor: None,
+        gas_escalation: Default::default(),
+        signing_service: None,
+        offline_signer: None,
     };
 
     let read_client =
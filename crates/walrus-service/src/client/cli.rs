@@ -5,7 +5,6 @@
 
 use std::{
     fmt::{self, Display},
-    fs,
     path::{Path, PathBuf},
     str::FromStr,
 };
@@ -17,6 +16,7 @@ use serde::{Deserialize, Serialize};
 use sui_sdk::wallet_context::WalletContext;
 use walrus_core::BlobId;
 use walrus_sdk::{
+    blob_source::BlobSource,
     blocklist::Blocklist,
     client::Client,
     config::ClientConfig,
@@ -37,6 +37,7 @@ pub use args::{
     NodeSelection,
     NodeSortBy,
     PublisherArgs,
+    S3Args,
     SortBy,
 };
 pub use cli_output::CliOutput;
@@ -49,8 +50,9 @@ pub const DEFAULT_RPC_URL: &str = TESTNET_RPC;
 
 /// Creates a [`Client`] based on the provided [`ClientConfig`] with read-only access to Sui.
 ///
-/// The RPC URL is set based on the `rpc_url` parameter (if `Some`), the `wallet` (if `Ok`) or the
-/// default [`DEFAULT_RPC_URL`] if `allow_fallback_to_default` is true.
+/// The RPC URL is set based on the `rpc_url` parameter (if `Some`), the RPC URLs in the `config`
+/// (if any), the `wallet` (if `Ok`) or the default [`DEFAULT_RPC_URL`] if
+/// `allow_fallback_to_default` is true.
 pub async fn get_read_client(
     config: ClientConfig,
     rpc_url: Option<String>,
@@ -104,8 +106,10 @@ pub async fn get_contract_client(
 
 /// Creates a [`SuiReadClient`] from the provided RPC URL or wallet.
 ///
-/// The RPC URL is set based on the `rpc_url` parameter (if `Some`), the `wallet` (if `Ok`) or the
-/// default [`DEFAULT_RPC_URL`] if `allow_fallback_to_default` is true.
+/// The RPC URL is set based on the `rpc_url` parameter (if `Some`), the RPC URLs in the `config`
+/// (if any), the `wallet` (if `Ok`) or the default [`DEFAULT_RPC_URL`] if
+/// `allow_fallback_to_default` is true. If multiple RPC URLs are configured, the healthy RPC nodes
+/// are used in order of their latency, failing over to the next one on errors or high latency.
 // NB: When making changes to the logic, make sure to update the docstring of `get_read_client` and
 // the argument docs in `crates/walrus-service/bin/client.rs`.
 pub async fn get_sui_read_client_from_rpc_node_or_wallet(
@@ -127,6 +131,16 @@ pub async fn get_sui_read_client_from_rpc_node_or_wallet(
                 .await
                 .context(format!("cannot connect to Sui RPC node at {url}"))
         }
+        None if !config.rpc_urls.is_empty() => {
+            tracing::info!(
+                rpc_urls = ?config.rpc_urls,
+                "using RPC URLs set in client configuration"
+            );
+            config.new_rpc_client().await.context(format!(
+                "cannot connect to any of the Sui RPC nodes at {:?}",
+                config.rpc_urls
+            ))
+        }
         None => match wallet {
             Ok(wallet) => {
                 tracing::info!("using RPC URL set in wallet configuration");
@@ -388,8 +402,10 @@ fn thousands_separator_float(num: f64, digits: u32) -> String {
 }
 
 /// Reads a blob from the filesystem or returns a helpful error message.
-pub fn read_blob_from_file(path: impl AsRef<Path>) -> anyhow::Result<Vec<u8>> {
-    fs::read(&path).context(format!(
+///
+/// A copy of the file is memory-mapped if possible, see [`BlobSource::from_file`].
+pub fn read_blob_from_file(path: impl AsRef<Path>) -> anyhow::Result<BlobSource> {
+    BlobSource::from_file(&path).context(format!(
         "unable to read blob from '{}'",
         path.as_ref().display()
     ))
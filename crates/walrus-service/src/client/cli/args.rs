@@ -6,7 +6,7 @@
 use std::{
     net::SocketAddr,
     num::{NonZeroU16, NonZeroU32},
-    path::PathBuf,
+    path::{Path, PathBuf},
     time::{Duration, SystemTime},
 };
 
@@ -24,14 +24,36 @@ use walrus_core::{
     Epoch,
     EpochCount,
 };
+use walrus_sdk::{blocklist::Blocklist, config::NetworkProfile};
 use walrus_sui::{
-    client::{ExpirySelectionPolicy, ReadClient, SuiContractClient},
+    client::{ExpirySelectionPolicy, GasSponsorConfig, ReadClient, SuiContractClient},
     types::{move_structs::Authorized, StorageNode},
     utils::SuiNetwork,
 };
 
 use super::{parse_blob_id, read_blob_from_file, BlobIdDecimal, HumanReadableBytes};
-use crate::client::{config::AuthConfig, daemon::CacheConfig};
+use crate::{
+    client::{
+        config::{AuthConfig, CorsConfig, RateLimitConfig, ResponseHeadersConfig},
+        daemon::{
+            auth::ApiKeys,
+            AcmeConfig,
+            AdminTokenDigest,
+            BlobCacheConfig,
+            CacheConfig,
+            CompressionConfig,
+            DiskCacheConfig,
+            PublishJobsConfig,
+            ReadSourcesConfig,
+            RemoteBlocklistConfig,
+            ResumableUploadsConfig,
+            SignedUrls,
+            TlsConfig,
+            UrlSigningKey,
+        },
+    },
+    utils::load_from_yaml,
+};
 
 /// The command-line arguments for the Walrus client.
 #[derive(Parser, Debug, Clone, Deserialize)]
@@ -61,6 +83,15 @@ pub struct App {
     #[arg(long, global = true)]
     #[serde(default)]
     pub context: Option<String>,
+    /// The Walrus network whose built-in configuration is used instead of a configuration file.
+    ///
+    /// One of `mainnet`, `testnet`, or `localnet`. The built-in configurations contain the RPC URL
+    /// and the object IDs of the network, and use the Sui wallet in `~/.sui/sui_config/` with the
+    /// network as active environment. For `localnet`, the object IDs are read from the client
+    /// configuration of the local testbed in `./working_dir/`.
+    #[arg(long, global = true, conflicts_with_all = ["config", "context"])]
+    #[serde(default)]
+    pub network: Option<NetworkProfile>,
     /// The path to the Sui wallet configuration file.
     ///
     /// The wallet configuration is taken from the following locations:
@@ -81,9 +112,19 @@ pub struct App {
     pub wallet: Option<PathBuf>,
     /// The gas budget for transactions.
     ///
-    /// If not specified, the gas budget is estimated automatically.
+    /// If not specified, the gas budget is estimated automatically. If specified, transactions
+    /// that fail due to gas are not resubmitted with an increased gas price and budget.
     #[arg(long, global = true)]
     pub gas_budget: Option<u64>,
+    /// The sponsor that pays for the gas of transactions, overriding the sponsor in the Walrus
+    /// configuration.
+    ///
+    /// Either the URL of a gas station implementing the API of the Sui gas pool, or the path to a
+    /// file containing the Bech32-encoded private key of the sponsor. Gas stations that require an
+    /// authentication token must be set in the configuration.
+    #[arg(long, global = true, value_parser = parse_gas_sponsor)]
+    #[serde(default)]
+    pub sponsor: Option<GasSponsorConfig>,
     /// Write output as JSON.
     ///
     /// This is always done in JSON mode.
@@ -119,6 +160,23 @@ impl App {
     }
 }
 
+/// Parses the sponsor of the gas of transactions from the URL of a gas station or the path to a
+/// key file.
+fn parse_gas_sponsor(input: &str) -> Result<GasSponsorConfig> {
+    if input.starts_with("http://") || input.starts_with("https://") {
+        Ok(GasSponsorConfig::Endpoint {
+            url: input
+                .parse()
+                .with_context(|| format!("invalid URL of the gas station: {input}"))?,
+            auth_token: None,
+        })
+    } else {
+        Ok(GasSponsorConfig::Key {
+            key_file: input.into(),
+        })
+    }
+}
+
 /// Top level enum to separate the daemon and CLI commands.
 #[derive(Subcommand, Debug, Clone, Deserialize, PartialEq, Eq)]
 #[command(rename_all = "kebab-case")]
@@ -210,6 +268,11 @@ pub enum CliCommands {
         #[arg(long)]
         #[serde(default)]
         dry_run: bool,
+        /// Preview the costs of registering the blobs, obtained with a dry run of the transaction
+        /// on chain, and ask for confirmation before storing them.
+        #[arg(long, conflicts_with = "dry_run")]
+        #[serde(default)]
+        preview: bool,
         /// Do not check for the blob status before storing it.
         ///
         /// This will create a new blob even if the blob is already certified for a sufficient
@@ -307,6 +370,34 @@ pub enum CliCommands {
         #[command(subcommand)]
         command: Option<InfoCommands>,
     },
+    /// Print the current epoch, its start, and the estimated time at which it ends.
+    ///
+    /// With `--wait-for`, the command waits until the given epoch has started before printing the
+    /// epoch, which allows scripts to coordinate with epoch changes.
+    Epoch {
+        /// The URL of the Sui RPC node to use.
+        #[command(flatten)]
+        #[serde(flatten)]
+        rpc_arg: RpcArg,
+        /// Wait until the current epoch is at least this epoch.
+        #[arg(long)]
+        #[serde(default)]
+        wait_for: Option<Epoch>,
+    },
+    /// Print the storage and write prices of the most recent epochs.
+    ///
+    /// The prices are read from the state of the system at the start of every epoch, which full
+    /// nodes may prune; the history may therefore be shorter than requested.
+    PriceHistory {
+        /// The URL of the Sui RPC node to use.
+        #[command(flatten)]
+        #[serde(flatten)]
+        rpc_arg: RpcArg,
+        /// The maximum number of epochs for which to print the prices.
+        #[arg(long, default_value_t = default::price_history_epochs())]
+        #[serde(default = "default::price_history_epochs")]
+        epochs: usize,
+    },
     /// Print health information for one or multiple storage nodes.
     ///
     /// Only one of `--node_ids`, `--node_urls`, `--committee`, and `--active_set` can be specified.
@@ -354,12 +445,66 @@ pub enum CliCommands {
         #[serde_as(as = "DisplayFromStr")]
         blob_id_decimal: BlobIdDecimal,
     },
+    /// Sign a URL of a daemon with its URL signing key.
+    ///
+    /// The signed URL authorizes requests with the given method to the URL until it expires,
+    /// without an API key or JWT. All query parameters of the URL, such as the number of epochs of
+    /// a store request, are signed and cannot be changed by the holder of the URL. URLs for methods
+    /// other than `GET` and `HEAD` can only be used once, and require `--max-size`.
+    SignUrl {
+        /// The URL to sign.
+        url: String,
+        /// Path to the file containing the URL signing key of the daemon.
+        #[arg(long)]
+        #[serde(deserialize_with = "walrus_utils::config::resolve_home_dir")]
+        key_file: PathBuf,
+        /// The HTTP method of the requests authorized by the URL, such as `GET` or `PUT`.
+        #[arg(long, default_value = "GET")]
+        #[serde(default = "default::signed_url_method")]
+        method: String,
+        /// The duration for which the URL is valid.
+        #[arg(long, value_parser = humantime::parse_duration, default_value = "1h")]
+        #[serde(default = "default::signed_url_validity")]
+        valid_for: Duration,
+        /// The maximum size in bytes of the body of the requests, such as the size of the blob to
+        /// store.
+        #[arg(long)]
+        #[serde(default)]
+        max_size: Option<u64>,
+        /// The identity of the API key on whose behalf the URL is signed.
+        ///
+        /// The permissions, daily quota, and concurrent uploads of the API key apply to requests
+        /// with the URL. Required to store blobs with a publisher configured with API keys.
+        #[arg(long)]
+        #[serde(default)]
+        identity: Option<String>,
+    },
     /// List all registered blobs for the current wallet.
     ListBlobs {
         #[arg(long)]
         #[serde(default)]
         /// The output list of blobs will include expired blobs.
         include_expired: bool,
+        /// Also show the attributes of the blobs.
+        ///
+        /// Reading the attributes requires an additional request per blob object.
+        #[arg(long)]
+        #[serde(default)]
+        with_attributes: bool,
+    },
+    /// List the unused storage resources owned by the wallet.
+    ///
+    /// With `--size` and `--epochs`, the command additionally suggests which of the storage
+    /// resources can be used, possibly after fusing them, to store a blob of the given size.
+    Storage {
+        /// The unencoded size in bytes of a blob to be stored.
+        #[arg(long, requires = "epochs")]
+        #[serde(default)]
+        size: Option<u64>,
+        /// The number of epochs for which the blob is to be stored.
+        #[arg(long, requires = "size")]
+        #[serde(default)]
+        epochs: Option<EpochCount>,
     },
     /// Delete a blob from Walrus.
     ///
@@ -400,6 +545,25 @@ pub enum CliCommands {
         #[serde(default = "default::staking_amounts_frost")]
         amounts: Vec<u64>,
     },
+    /// Unstake staked WAL.
+    ///
+    /// The WAL is withdrawn directly if possible, for example if the stake is not active yet.
+    /// Otherwise, the withdrawal is requested, and the WAL can be withdrawn by running this
+    /// command again once the withdraw epoch is reached.
+    Unstake {
+        /// The object IDs of the staked WAL to unstake.
+        #[arg(index = 1, required = true, num_args = 1..)]
+        staked_wal_ids: Vec<ObjectID>,
+    },
+    /// Show the staked WAL of the wallet, along with the rewards earned so far.
+    StakeStatus {
+        /// The address whose staked WAL is shown.
+        ///
+        /// If not specified, the active address of the wallet is used.
+        #[arg(long)]
+        #[serde(default)]
+        owner: Option<SuiAddress>,
+    },
     /// Generates a new Sui wallet.
     GenerateSuiWallet {
         /// The path where the wallet configuration will be stored.
@@ -441,7 +605,16 @@ pub enum CliCommands {
         #[serde(default = "default::exchange_amount_mist")]
         /// The amount of MIST to exchange for WAL/FROST.
         amount: u64,
+        #[arg(long, conflicts_with = "amount")]
+        #[serde(default)]
+        /// The WAL balance in FROST that the wallet should reach.
+        ///
+        /// If set, only as much SUI is exchanged as needed to reach this balance, and nothing is
+        /// exchanged if the wallet already holds enough WAL.
+        min_balance: Option<u64>,
     },
+    /// Show the SUI and WAL balances of the wallet.
+    Balance,
     /// Burns one or more owned Blob object on Sui.
     ///
     /// This command burns the Blob objects with the given object IDs. The Blob objects must be
@@ -474,6 +647,12 @@ pub enum CliCommands {
         #[arg(long)]
         amount: u64,
     },
+    /// Get the funds of a shared blob and the number of epochs for which they can extend it.
+    GetSharedBlobFunding {
+        /// The object ID of the shared blob.
+        #[arg(index = 1)]
+        shared_blob_obj_id: ObjectID,
+    },
     /// Extend an owned or shared blob.
     Extend {
         /// The object ID of the blob to extend.
@@ -550,6 +729,26 @@ pub enum CliCommands {
         #[command(subcommand)]
         command: NodeAdminCommands,
     },
+    /// Generate or verify deterministic test vectors for the encoding of blobs.
+    ///
+    /// The test vectors contain blobs derived from seeds together with their blob IDs, metadata,
+    /// slivers, and recovery symbols, and allow alternative implementations of the encoding to
+    /// check their compatibility with this one.
+    #[command(hide = true)]
+    EncodingTestVectors {
+        /// The test-vector command to run.
+        #[command(subcommand)]
+        command: TestVectorCommands,
+    },
+    /// Measure the performance of operations on this machine.
+    ///
+    /// The results help to size the hardware of clients, publishers, and storage nodes that
+    /// encode or decode large blobs.
+    Bench {
+        /// The benchmark to run.
+        #[command(subcommand)]
+        command: BenchCommands,
+    },
 }
 
 /// Subcommands for the `info` command.
@@ -581,6 +780,81 @@ pub enum InfoCommands {
         #[serde(flatten)]
         sort: SortBy<NodeSortBy>,
     },
+    /// Print the shards a storage node gains and loses in the next epoch.
+    ///
+    /// This also estimates the amount of data the node needs to sync for the gained shards. The
+    /// preview is only available once the committee for the next epoch has been selected.
+    ShardPreview {
+        /// The ID of the storage node.
+        #[arg(long)]
+        node_id: ObjectID,
+    },
+}
+
+/// Subcommands for the `encoding-test-vectors` command.
+#[derive(Subcommand, Debug, Clone, Deserialize, PartialEq, Eq)]
+#[command(rename_all = "kebab-case")]
+#[serde(rename_all = "camelCase", rename_all_fields = "camelCase")]
+pub enum TestVectorCommands {
+    /// Generate test vectors and write them as a JSON array.
+    ///
+    /// One test vector is generated for each combination of seed and blob length.
+    Generate {
+        /// The number of shards for which the blobs are encoded.
+        #[arg(long)]
+        n_shards: NonZeroU16,
+        /// The seeds from which the blobs are derived.
+        #[arg(long, num_args = 1.., default_values_t = [0])]
+        #[serde(default = "default::test_vector_seeds")]
+        seeds: Vec<u64>,
+        /// The lengths of the blobs in bytes.
+        #[arg(long, num_args = 1.., required = true)]
+        blob_lengths: Vec<usize>,
+        /// The encoding type with which the blobs are encoded.
+        #[arg(long)]
+        #[serde(default)]
+        encoding_type: Option<EncodingType>,
+        /// The file to which the test vectors are written.
+        ///
+        /// If unset, prints the test vectors to stdout.
+        #[arg(long)]
+        #[serde(default)]
+        out: Option<PathBuf>,
+    },
+    /// Verify that this implementation produces the outputs of the test vectors in a file.
+    Verify {
+        /// The file containing the JSON array of test vectors.
+        #[serde(deserialize_with = "walrus_utils::config::resolve_home_dir")]
+        file: PathBuf,
+    },
+}
+
+/// Subcommands for the `bench` command.
+#[derive(Subcommand, Debug, Clone, Deserialize, PartialEq, Eq)]
+#[command(rename_all = "kebab-case")]
+#[serde(rename_all = "camelCase", rename_all_fields = "camelCase")]
+pub enum BenchCommands {
+    /// Measure the throughput of encoding, computing the blob ID of, and decoding random blobs.
+    ///
+    /// Decoding uses the minimum number of primary slivers required to reconstruct the blob.
+    Encode {
+        /// The number of shards for which the blobs are encoded.
+        #[arg(long, default_value_t = default::bench_n_shards())]
+        #[serde(default = "default::bench_n_shards")]
+        n_shards: NonZeroU16,
+        /// The sizes of the blobs in bytes.
+        #[arg(long, num_args = 1.., default_values_t = default::bench_blob_sizes())]
+        #[serde(default = "default::bench_blob_sizes")]
+        blob_sizes: Vec<u64>,
+        /// The encoding type with which the blobs are encoded.
+        #[arg(long)]
+        #[serde(default)]
+        encoding_type: Option<EncodingType>,
+        /// The number of times each operation is repeated; the mean duration is reported.
+        #[arg(long, default_value_t = default::bench_iterations())]
+        #[serde(default = "default::bench_iterations")]
+        iterations: NonZeroU32,
+    },
 }
 
 /// Subcommands for the `node-admin` command.
@@ -684,6 +958,10 @@ pub enum DaemonCommands {
         #[serde(flatten, default)]
         /// The aggregator args.
         aggregator_args: AggregatorArgs,
+        #[command(flatten)]
+        #[serde(flatten)]
+        /// The args for the S3-compatible API.
+        s3_args: S3Args,
     },
 }
 
@@ -696,6 +974,16 @@ impl DaemonCommands {
             DaemonCommands::Daemon { args, .. } => args.daemon_args.metrics_address,
         }
     }
+
+    /// Returns whether the command exports spans to an OpenTelemetry collector.
+    pub fn exports_spans(&self) -> bool {
+        let daemon_args = match &self {
+            DaemonCommands::Publisher { args } => &args.daemon_args,
+            DaemonCommands::Aggregator { daemon_args, .. } => daemon_args,
+            DaemonCommands::Daemon { args, .. } => &args.daemon_args,
+        };
+        daemon_args.tracing_args.export_spans
+    }
 }
 
 /// The arguments for the aggregator service.
@@ -711,6 +999,191 @@ pub struct AggregatorArgs {
     #[arg(long, num_args = 1.., default_values_t = default::allowed_headers())]
     #[serde(default = "default::allowed_headers")]
     pub(crate) allowed_headers: Vec<String>,
+    /// Path to a YAML file configuring the headers of responses with blob data.
+    ///
+    /// The file can disable the detection of the content type from the first bytes of blobs, and
+    /// set a default `Content-Type`, a `Content-Disposition`, and a `Cache-Control` header for
+    /// each route.
+    #[arg(long)]
+    #[serde(
+        default,
+        deserialize_with = "walrus_utils::config::resolve_home_dir_option"
+    )]
+    pub(crate) response_headers_config: Option<PathBuf>,
+    /// Serves static websites from manifest blobs below `/site/<manifest blob ID>/`.
+    ///
+    /// Website files are sandboxed with a `Content-Security-Policy` header: their scripts run, but
+    /// in an opaque origin, so they cannot access the storage or cookies of the aggregator's
+    /// origin or of other websites.
+    #[arg(long)]
+    #[serde(default)]
+    pub(crate) serve_sites: bool,
+    /// The maximum total size in MiB of the blobs cached in memory.
+    ///
+    /// Reconstructed blobs are cached until they expire, but at most for the time set with
+    /// `--blob-cache-ttl`. A size of 0 disables the in-memory cache.
+    #[arg(long = "blob-cache-size", default_value_t = default::blob_cache_size_mib())]
+    #[serde(default = "default::blob_cache_size_mib")]
+    pub(crate) blob_cache_size_mib: u64,
+    /// The maximum size in MiB of a blob that is cached.
+    #[arg(
+        long = "max-cached-blob-size",
+        default_value_t = default::max_cached_blob_size_mib()
+    )]
+    #[serde(default = "default::max_cached_blob_size_mib")]
+    pub(crate) max_cached_blob_size_mib: u64,
+    /// The maximum time for which a blob is cached.
+    #[arg(long, value_parser = humantime::parse_duration, default_value = "1h")]
+    #[serde(default = "default::blob_cache_ttl")]
+    pub(crate) blob_cache_ttl: Duration,
+    /// A directory in which blobs are additionally cached on disk.
+    ///
+    /// Blobs cached in the directory by a previous run are removed when the aggregator starts.
+    #[arg(long)]
+    #[serde(
+        default,
+        deserialize_with = "walrus_utils::config::resolve_home_dir_option"
+    )]
+    pub(crate) blob_cache_dir: Option<PathBuf>,
+    /// The maximum total size in MiB of the blobs cached on disk.
+    #[arg(
+        long = "blob-cache-disk-size",
+        default_value_t = default::blob_cache_disk_size_mib()
+    )]
+    #[serde(default = "default::blob_cache_disk_size_mib")]
+    pub(crate) blob_cache_disk_size_mib: u64,
+    /// Path to a file containing the administration token of the blob cache.
+    ///
+    /// If set, blobs can be pinned in memory with requests to `/v1/cache/pinned` that present the
+    /// token as bearer token. Pinned blobs are not evicted from the cache, and are refreshed
+    /// periodically until they are unpinned.
+    #[arg(long)]
+    #[serde(
+        default,
+        deserialize_with = "walrus_utils::config::resolve_home_dir_option"
+    )]
+    pub(crate) blob_cache_admin_token_file: Option<PathBuf>,
+    /// The maximum total size in MiB of the blobs pinned in the cache.
+    #[arg(long = "pinned-blobs-size", default_value_t = default::pinned_blobs_size_mib())]
+    #[serde(default = "default::pinned_blobs_size_mib")]
+    pub(crate) pinned_blobs_size_mib: u64,
+    /// Disables the compression of responses.
+    ///
+    /// By default, responses are compressed with zstd, Brotli, or gzip if the client accepts it in
+    /// the `Accept-Encoding` header and the response is larger than `--compression-min-size`.
+    #[arg(long)]
+    #[serde(default)]
+    pub(crate) disable_compression: bool,
+    /// The minimum size in bytes of the responses that are compressed.
+    #[arg(long, default_value_t = default::compression_min_size())]
+    #[serde(default = "default::compression_min_size")]
+    pub(crate) compression_min_size: u16,
+    /// The URL of a peer aggregator from which blobs are read if reading them from the storage
+    /// nodes fails; can be repeated to fall back to several peers in order.
+    ///
+    /// Sources that fail repeatedly are tried last for some time. If all sources fail, only cached
+    /// blobs are served until a source can be tried again. The whole blob is always read from a
+    /// peer and verified against its blob ID. Peers must not in turn fall back to this aggregator.
+    #[arg(long = "peer-aggregator")]
+    #[serde(default)]
+    pub(crate) peer_aggregators: Vec<String>,
+    /// The timeout of reads from peer aggregators.
+    #[arg(long, value_parser = humantime::parse_duration, default_value = "1min")]
+    #[serde(default = "default::peer_read_timeout")]
+    pub(crate) peer_read_timeout: Duration,
+}
+
+impl AggregatorArgs {
+    /// Loads the configuration of the response headers, or returns the default configuration if
+    /// no file is specified.
+    pub(crate) fn load_response_headers_config(&self) -> Result<ResponseHeadersConfig> {
+        let Some(path) = self.response_headers_config.as_ref() else {
+            return Ok(ResponseHeadersConfig::default());
+        };
+        let config = load_from_yaml(path).with_context(|| {
+            format!(
+                "failed to load the response headers config from {}",
+                path.display()
+            )
+        })?;
+        tracing::info!(?config, "response headers config applied");
+        Ok(config)
+    }
+
+    /// Returns the configuration of the cache of blobs.
+    pub(crate) fn blob_cache_config(&self) -> BlobCacheConfig {
+        BlobCacheConfig {
+            memory_capacity: self.blob_cache_size_mib << 20,
+            max_blob_size: self.max_cached_blob_size_mib << 20,
+            max_ttl: self.blob_cache_ttl,
+            disk: self
+                .blob_cache_dir
+                .clone()
+                .map(|directory| DiskCacheConfig {
+                    directory,
+                    capacity: self.blob_cache_disk_size_mib << 20,
+                }),
+            max_pinned_size: if self.blob_cache_admin_token_file.is_some() {
+                self.pinned_blobs_size_mib << 20
+            } else {
+                0
+            },
+        }
+    }
+
+    /// Returns the configuration of the compression of responses, or `None` if it is disabled.
+    pub(crate) fn compression_config(&self) -> Option<CompressionConfig> {
+        (!self.disable_compression).then_some(CompressionConfig {
+            min_size: self.compression_min_size,
+        })
+    }
+
+    /// Returns the configuration of the sources from which blobs are read.
+    pub(crate) fn read_sources_config(&self) -> Result<ReadSourcesConfig> {
+        let peer_aggregators = self
+            .peer_aggregators
+            .iter()
+            .map(|url| {
+                url.parse()
+                    .with_context(|| format!("invalid URL of a peer aggregator: {url}"))
+            })
+            .collect::<Result<_>>()?;
+        Ok(ReadSourcesConfig {
+            peer_aggregators,
+            peer_timeout: self.peer_read_timeout,
+        })
+    }
+
+    /// Loads the administration token of the blob cache, if a file is specified, and returns its
+    /// digest.
+    pub(crate) fn load_blob_cache_admin_token(&self) -> Result<Option<AdminTokenDigest>> {
+        self.blob_cache_admin_token_file
+            .as_deref()
+            .map(|path| load_admin_token(path, "blob cache"))
+            .transpose()
+    }
+}
+
+/// Reads the administration token of the `purpose` from the file at `path` and returns its digest.
+fn load_admin_token(path: &Path, purpose: &str) -> Result<AdminTokenDigest> {
+    let token = std::fs::read_to_string(path).with_context(|| {
+        format!(
+            "failed to read the {purpose} admin token from {}",
+            path.display()
+        )
+    })?;
+    let token = token.trim();
+    ensure!(!token.is_empty(), "the {purpose} admin token is empty");
+    Ok(AdminTokenDigest::new(token))
+}
+
+/// Reads the URL signing key from the file at `path`.
+pub(crate) fn load_url_signing_key(path: &Path) -> Result<UrlSigningKey> {
+    let key = std::fs::read_to_string(path)
+        .with_context(|| format!("failed to read the URL signing key from {}", path.display()))?;
+    let key = key.trim();
+    ensure!(!key.is_empty(), "the URL signing key is empty");
+    Ok(UrlSigningKey::new(key.as_bytes()))
 }
 
 /// The arguments for the publisher service.
@@ -822,6 +1295,65 @@ pub struct PublisherArgs {
     #[arg(long)]
     #[serde(default)]
     pub jwt_verify_upload: bool,
+    /// Path to a YAML file with the API keys accepted by the publisher.
+    ///
+    /// Clients present an API key as bearer token in the `Authorization` header. Each key belongs
+    /// to an identity, to which requests are attributed in the logs, and can limit the number of
+    /// epochs (`max_epochs`) and the size in bytes (`max_size`) of the blobs stored with it, the
+    /// number of bytes stored with it per UTC day (`daily_quota`), and the number of its uploads in
+    /// progress at the same time (`max_concurrent_uploads`). Unless JWT authentication is also
+    /// configured, requests without a valid API key are rejected.
+    #[arg(long)]
+    #[serde(
+        default,
+        deserialize_with = "walrus_utils::config::resolve_home_dir_option"
+    )]
+    pub api_keys_config: Option<PathBuf>,
+    /// The directory in which the publisher keeps the data of resumable uploads until they are
+    /// complete.
+    ///
+    /// If set, the publisher accepts resumable uploads with the tus protocol at `/v1/uploads`, and
+    /// stores the blob of an upload once all its data has been received. Uploads in progress are
+    /// lost when the publisher restarts.
+    #[arg(long)]
+    #[serde(
+        default,
+        deserialize_with = "walrus_utils::config::resolve_home_dir_option"
+    )]
+    pub resumable_uploads_dir: Option<PathBuf>,
+    /// The maximum size of a resumable upload in MiB.
+    ///
+    /// Resumable uploads are further limited to the maximum body size of store requests.
+    #[arg(
+        long = "max-resumable-upload-size",
+        default_value_t = default::max_resumable_upload_size_mib()
+    )]
+    #[serde(default = "default::max_resumable_upload_size_mib")]
+    pub max_resumable_upload_size_mib: u64,
+    /// The time after its creation at which a resumable upload expires and its data is removed.
+    #[arg(long, value_parser = humantime::parse_duration, default_value = "1day")]
+    #[serde(default = "default::resumable_upload_expiry")]
+    pub resumable_upload_expiry: Duration,
+    /// The directory in which the publisher keeps the blobs and the status of publish jobs.
+    ///
+    /// If set, store requests with the `Prefer: respond-async` header are answered immediately with
+    /// the ID of a job, which stores the blob in the background; its status and result are polled
+    /// at `/v1/jobs/<job-id>`. Jobs that are queued or running when the publisher stops are resumed
+    /// when it restarts.
+    #[arg(long)]
+    #[serde(
+        default,
+        deserialize_with = "walrus_utils::config::resolve_home_dir_option"
+    )]
+    pub publish_jobs_dir: Option<PathBuf>,
+    /// The maximum number of publish jobs that store their blobs concurrently.
+    #[arg(long, default_value_t = default::max_concurrent_publish_jobs())]
+    #[serde(default = "default::max_concurrent_publish_jobs")]
+    pub max_concurrent_publish_jobs: usize,
+    /// The time after a publish job finished for which its status is kept.
+    #[arg(long, value_parser = humantime::parse_duration, default_value = "1day")]
+    #[serde(default = "default::publish_job_retention")]
+    pub publish_job_retention: Duration,
     #[command(flatten)]
     #[serde(flatten)]
     /// The configuration for the JWT duplicate suppression cache.
@@ -853,12 +1385,16 @@ impl PublisherArgs {
     }
 
     pub(crate) fn generate_auth_config(&self) -> Result<Option<AuthConfig>> {
-        if self.jwt_decode_secret.is_some() || self.jwt_expiring_sec > 0 || self.jwt_verify_upload {
+        let jwt_enabled =
+            self.jwt_decode_secret.is_some() || self.jwt_expiring_sec > 0 || self.jwt_verify_upload;
+        if jwt_enabled || self.api_keys_config.is_some() {
             let mut auth_config = AuthConfig {
                 expiring_sec: self.jwt_expiring_sec,
                 verify_upload: self.jwt_verify_upload,
                 algorithm: self.jwt_algorithm,
                 replay_suppression_config: self.replay_suppression_config.clone(),
+                api_keys: self.load_api_keys()?,
+                api_keys_only: !jwt_enabled,
                 ..Default::default()
             };
 
@@ -873,6 +1409,38 @@ impl PublisherArgs {
             Ok(None)
         }
     }
+
+    /// Returns the configuration of resumable uploads, if they are enabled.
+    pub(crate) fn resumable_uploads_config(&self) -> Option<ResumableUploadsConfig> {
+        self.resumable_uploads_dir
+            .as_ref()
+            .map(|directory| ResumableUploadsConfig {
+                directory: directory.clone(),
+                max_upload_size: self.max_resumable_upload_size_mib << 20,
+                expiry: self.resumable_upload_expiry,
+            })
+    }
+
+    /// Returns the configuration of publish jobs, if they are enabled.
+    pub(crate) fn publish_jobs_config(&self) -> Option<PublishJobsConfig> {
+        self.publish_jobs_dir
+            .as_ref()
+            .map(|directory| PublishJobsConfig {
+                directory: directory.clone(),
+                max_concurrent_jobs: self.max_concurrent_publish_jobs,
+                retention: self.publish_job_retention,
+            })
+    }
+
+    /// Loads the API keys from the configured file, if any.
+    fn load_api_keys(&self) -> Result<ApiKeys> {
+        let Some(path) = self.api_keys_config.as_ref() else {
+            return Ok(ApiKeys::default());
+        };
+        load_from_yaml(path)
+            .and_then(ApiKeys::new)
+            .with_context(|| format!("failed to load the API keys from {}", path.display()))
+    }
 }
 
 /// The URL of the Sui RPC node to use.
@@ -881,8 +1449,9 @@ impl PublisherArgs {
 pub struct RpcArg {
     /// The URL of the Sui RPC node to use.
     ///
-    /// If unset, the wallet configuration is applied (if set), or the fullnode at
-    /// `fullnode.testnet.sui.io:443` is used.
+    /// If unset, the RPC URLs in the Walrus configuration are used (if set), the wallet
+    /// configuration is applied (if set), or the fullnode at `fullnode.testnet.sui.io:443` is
+    /// used.
     // NB: Keep this in sync with `crate::cli`.
     #[arg(long)]
     #[serde(default)]
@@ -907,6 +1476,275 @@ pub struct DaemonArgs {
         deserialize_with = "walrus_utils::config::resolve_home_dir_option"
     )]
     pub(crate) blocklist: Option<PathBuf>,
+    /// URL of a remote list (in YAML syntax) of blob IDs, which are blocked in addition to those
+    /// in the blocklist file.
+    #[arg(long)]
+    #[serde(default)]
+    pub(crate) remote_blocklist_url: Option<String>,
+    /// The interval at which the remote list of blocked blob IDs is fetched.
+    #[arg(long, value_parser = humantime::parse_duration, default_value = "5min")]
+    #[serde(default = "default::remote_blocklist_refresh_interval")]
+    pub(crate) remote_blocklist_refresh_interval: Duration,
+    /// Path to a file containing the administration token of the moderation endpoints.
+    ///
+    /// If set, the blob IDs in the blocklist file can be listed, added, and removed through the
+    /// endpoints below `/v1/moderation/blocked`, which require the token as bearer token.
+    #[arg(long, requires = "blocklist")]
+    #[serde(
+        default,
+        deserialize_with = "walrus_utils::config::resolve_home_dir_option"
+    )]
+    pub(crate) moderation_admin_token_file: Option<PathBuf>,
+    /// Path to a YAML file configuring the CORS headers of the daemon.
+    ///
+    /// The file can restrict the origins (`allowed_origins`), methods (`allowed_methods`), and
+    /// headers (`allowed_headers`) of requests from browsers, and set the time in seconds for which
+    /// browsers cache preflight responses (`max_age`). By default, requests from any origin are
+    /// allowed.
+    #[arg(long)]
+    #[serde(
+        default,
+        deserialize_with = "walrus_utils::config::resolve_home_dir_option"
+    )]
+    pub(crate) cors_config: Option<PathBuf>,
+    /// Path to a YAML file configuring the rate limits per client of the routes of the daemon.
+    ///
+    /// For each route, such as `/v1/blobs/{blob_id}`, the file can limit the requests
+    /// (`requests_per_second`, `request_burst`) and the bytes in request and response bodies
+    /// (`bytes_per_second`, `byte_burst`). Clients are identified by their API key or IP address;
+    /// behind a reverse proxy, set `client_ip_header` to the header with the client address. By
+    /// default, requests are not rate limited.
+    #[arg(long)]
+    #[serde(
+        default,
+        deserialize_with = "walrus_utils::config::resolve_home_dir_option"
+    )]
+    pub(crate) rate_limit_config: Option<PathBuf>,
+    /// Path to a file containing the secret key with which URLs to the daemon are signed.
+    ///
+    /// If set, a URL signed with the key, for example with `walrus sign-url`, authorizes the
+    /// requests to the URL until it expires, without an API key or JWT.
+    #[arg(long)]
+    #[serde(
+        default,
+        deserialize_with = "walrus_utils::config::resolve_home_dir_option"
+    )]
+    pub(crate) url_signing_key_file: Option<PathBuf>,
+    /// Only serve blobs and websites through URLs signed with the URL signing key.
+    #[arg(long, requires = "url_signing_key_file")]
+    #[serde(default)]
+    pub(crate) require_signed_reads: bool,
+    #[command(flatten)]
+    #[serde(flatten)]
+    /// The configuration for serving the daemon over HTTPS.
+    pub(crate) tls_args: TlsArgs,
+    #[command(flatten)]
+    #[serde(flatten)]
+    /// The configuration for exporting the spans of the daemon.
+    pub(crate) tracing_args: TracingArgs,
+}
+
+impl DaemonArgs {
+    /// Loads the CORS configuration, or returns the default configuration if no file is
+    /// specified.
+    pub(crate) fn load_cors_config(&self) -> Result<CorsConfig> {
+        let Some(path) = self.cors_config.as_ref() else {
+            return Ok(CorsConfig::default());
+        };
+        load_from_yaml(path)
+            .with_context(|| format!("failed to load the CORS config from {}", path.display()))
+    }
+
+    /// Loads the rate-limit configuration, or returns an empty configuration, which does not limit
+    /// any route, if no file is specified.
+    pub(crate) fn load_rate_limit_config(&self) -> Result<RateLimitConfig> {
+        let Some(path) = self.rate_limit_config.as_ref() else {
+            return Ok(RateLimitConfig::default());
+        };
+        let config: RateLimitConfig = load_from_yaml(path).with_context(|| {
+            format!(
+                "failed to load the rate-limit config from {}",
+                path.display()
+            )
+        })?;
+        for (route, limits) in &config.routes {
+            ensure!(
+                limits
+                    .requests_per_second
+                    .is_none_or(|rate| rate.is_finite() && rate > 0.0)
+                    && limits.bytes_per_second != Some(0),
+                "the rate limits of {route} must be positive"
+            );
+        }
+        Ok(config)
+    }
+
+    /// Loads the URL signing key, if a file is specified, and returns the configuration of the
+    /// signed URLs accepted by the daemon.
+    pub(crate) fn load_signed_urls(&self) -> Result<Option<SignedUrls>> {
+        ensure!(
+            !self.require_signed_reads || self.url_signing_key_file.is_some(),
+            "requiring signed reads requires a URL signing key file"
+        );
+        self.url_signing_key_file
+            .as_deref()
+            .map(|path| {
+                Ok(SignedUrls {
+                    key: load_url_signing_key(path)?,
+                    require_for_reads: self.require_signed_reads,
+                })
+            })
+            .transpose()
+    }
+
+    /// Loads the blocklist file, if one is specified, and returns the blocklist.
+    pub(crate) fn load_blocklist(&self) -> Result<Blocklist> {
+        ensure!(
+            self.moderation_admin_token_file.is_none() || self.blocklist.is_some(),
+            "the moderation endpoints require a blocklist file"
+        );
+        Blocklist::new(&self.blocklist)
+    }
+
+    /// Returns the configuration of the remote list of blocked blob IDs, if a URL is specified.
+    pub(crate) fn remote_blocklist_config(&self) -> Result<Option<RemoteBlocklistConfig>> {
+        let Some(url) = self.remote_blocklist_url.as_ref() else {
+            return Ok(None);
+        };
+        Ok(Some(RemoteBlocklistConfig {
+            url: url
+                .parse()
+                .with_context(|| format!("invalid URL of the remote blocklist: {url}"))?,
+            refresh_interval: self.remote_blocklist_refresh_interval,
+        }))
+    }
+
+    /// Loads the administration token of the moderation endpoints, if a file is specified, and
+    /// returns its digest.
+    pub(crate) fn load_moderation_admin_token(&self) -> Result<Option<AdminTokenDigest>> {
+        self.moderation_admin_token_file
+            .as_deref()
+            .map(|path| load_admin_token(path, "moderation"))
+            .transpose()
+    }
+}
+
+/// The arguments for serving the daemon over HTTPS.
+///
+/// The certificate is either read from PEM files or provisioned with ACME; if neither is
+/// configured, the daemon serves plain HTTP.
+#[derive(Default, Debug, Clone, Args, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct TlsArgs {
+    /// Path to a PEM file with the TLS certificate chain of the daemon.
+    ///
+    /// The certificate and key are reloaded every hour, so that renewed certificates are used
+    /// without restarting the daemon.
+    #[arg(long, requires = "tls_key", conflicts_with = "acme_domains")]
+    #[serde(
+        default,
+        deserialize_with = "walrus_utils::config::resolve_home_dir_option"
+    )]
+    pub(crate) tls_certificate: Option<PathBuf>,
+    /// Path to a PEM file with the private key of the TLS certificate of the daemon.
+    #[arg(long, requires = "tls_certificate")]
+    #[serde(
+        default,
+        deserialize_with = "walrus_utils::config::resolve_home_dir_option"
+    )]
+    pub(crate) tls_key: Option<PathBuf>,
+    /// A domain for which to provision a TLS certificate with ACME; can be repeated.
+    ///
+    /// If set, the daemon obtains and renews a certificate from Let's Encrypt using the TLS-ALPN-01
+    /// challenge, which requires the daemon to be reachable on port 443 at the domains.
+    #[arg(long = "acme-domain")]
+    #[serde(default)]
+    pub(crate) acme_domains: Vec<String>,
+    /// An email address to register as contact of the ACME account; can be repeated.
+    #[arg(long = "acme-contact")]
+    #[serde(default)]
+    pub(crate) acme_contacts: Vec<String>,
+    /// The directory in which to cache the ACME account key and certificates.
+    ///
+    /// Without a cache, a new certificate is requested whenever the daemon starts, which can
+    /// quickly exceed the rate limits of Let's Encrypt.
+    #[arg(long)]
+    #[serde(
+        default,
+        deserialize_with = "walrus_utils::config::resolve_home_dir_option"
+    )]
+    pub(crate) acme_cache_dir: Option<PathBuf>,
+    /// Use the staging environment of Let's Encrypt, whose certificates are not trusted by
+    /// browsers but which has higher rate limits.
+    #[arg(long)]
+    #[serde(default)]
+    pub(crate) acme_staging: bool,
+}
+
+impl TlsArgs {
+    /// Returns the TLS configuration, or `None` if the daemon serves plain HTTP.
+    pub(crate) fn tls_config(&self) -> Result<Option<TlsConfig>> {
+        match (&self.tls_certificate, &self.tls_key) {
+            (Some(_), Some(_)) if !self.acme_domains.is_empty() => Err(anyhow!(
+                "a TLS certificate cannot be configured together with ACME domains"
+            )),
+            (Some(certificate), Some(key)) => Ok(Some(TlsConfig::Pem {
+                certificate: certificate.clone(),
+                key: key.clone(),
+            })),
+            (None, None) if self.acme_domains.is_empty() => Ok(None),
+            (None, None) => Ok(Some(TlsConfig::Acme(AcmeConfig {
+                domains: self.acme_domains.clone(),
+                contacts: self.acme_contacts.clone(),
+                cache_dir: self.acme_cache_dir.clone(),
+                staging: self.acme_staging,
+            }))),
+            _ => Err(anyhow!(
+                "both the TLS certificate and its key must be configured"
+            )),
+        }
+    }
+}
+
+/// The arguments for exporting the spans of the daemon to an OpenTelemetry collector.
+#[derive(Debug, Clone, Default, Args, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct TracingArgs {
+    /// Export spans to an OpenTelemetry collector with OTLP.
+    ///
+    /// The endpoint of the collector, the filter selecting the exported spans, and the service
+    /// name are taken from the `OTLP_ENDPOINT`, `TRACE_FILTER`, and `OTEL_SERVICE_NAME` environment
+    /// variables. The spans of a request continue the W3C trace context (`traceparent` header) of
+    /// the request, if any, and the trace context is propagated to the storage nodes, so that a
+    /// trace covers a store or read operation from the HTTP request down to the individual node
+    /// requests.
+    #[arg(long)]
+    #[serde(default)]
+    pub(crate) export_spans: bool,
+}
+
+/// The arguments for the S3-compatible API of the daemon.
+#[derive(Debug, Clone, Args, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct S3Args {
+    /// Path to the file with the index of the objects stored through the S3-compatible API.
+    ///
+    /// If set, the daemon serves a subset of the S3 API (`PutObject`, `GetObject`, `HeadObject`,
+    /// `DeleteObject`, and `ListObjects`) below `/s3`, which S3 tools can use with path-style
+    /// addressing. The file is created if it does not exist. Requests that store or delete objects
+    /// must be signed with an API key of the publisher, using its identity as access key ID; they
+    /// are rejected if no API keys are configured.
+    #[arg(long)]
+    #[serde(
+        default,
+        deserialize_with = "walrus_utils::config::resolve_home_dir_option"
+    )]
+    pub(crate) s3_index: Option<PathBuf>,
+    /// The number of epochs ahead for which objects stored through the S3-compatible API are
+    /// stored.
+    #[arg(long, default_value_t = default::s3_epochs())]
+    #[serde(default = "default::s3_epochs")]
+    pub(crate) s3_epochs: EpochCount,
 }
 
 #[serde_as]
@@ -1285,8 +2123,13 @@ impl EpochArg {
 }
 
 pub(crate) mod default {
-    use std::{net::SocketAddr, time::Duration};
+    use std::{
+        net::SocketAddr,
+        num::{NonZeroU16, NonZeroU32},
+        time::Duration,
+    };
 
+    use walrus_core::EpochCount;
     use walrus_sui::utils::SuiNetwork;
 
     pub(crate) fn max_body_size_kib() -> usize {
@@ -1297,6 +2140,30 @@ pub(crate) mod default {
         8
     }
 
+    pub(crate) fn s3_epochs() -> EpochCount {
+        1
+    }
+
+    pub(crate) fn price_history_epochs() -> usize {
+        10
+    }
+
+    pub(crate) fn test_vector_seeds() -> Vec<u64> {
+        vec![0]
+    }
+
+    pub(crate) fn bench_n_shards() -> NonZeroU16 {
+        NonZeroU16::new(1000).expect("1000 is non-zero")
+    }
+
+    pub(crate) fn bench_blob_sizes() -> Vec<u64> {
+        vec![1 << 20, 1 << 24, 1 << 28]
+    }
+
+    pub(crate) fn bench_iterations() -> NonZeroU32 {
+        NonZeroU32::new(3).expect("3 is non-zero")
+    }
+
     pub(crate) fn max_request_buffer_size() -> usize {
         // 1x the number of concurrent requests by default means that we start rejecting requests
         // rather soon to avoid overloading the publisher.
@@ -1328,6 +2195,54 @@ pub(crate) mod default {
         Duration::from_secs(1)
     }
 
+    pub(crate) fn max_resumable_upload_size_mib() -> u64 {
+        4096
+    }
+
+    pub(crate) fn resumable_upload_expiry() -> Duration {
+        Duration::from_secs(24 * 60 * 60)
+    }
+
+    pub(crate) fn max_concurrent_publish_jobs() -> usize {
+        4
+    }
+
+    pub(crate) fn publish_job_retention() -> Duration {
+        Duration::from_secs(24 * 60 * 60)
+    }
+
+    pub(crate) fn blob_cache_size_mib() -> u64 {
+        256
+    }
+
+    pub(crate) fn max_cached_blob_size_mib() -> u64 {
+        16
+    }
+
+    pub(crate) fn blob_cache_ttl() -> Duration {
+        Duration::from_secs(60 * 60)
+    }
+
+    pub(crate) fn blob_cache_disk_size_mib() -> u64 {
+        4096
+    }
+
+    pub(crate) fn pinned_blobs_size_mib() -> u64 {
+        1024
+    }
+
+    pub(crate) fn compression_min_size() -> u16 {
+        1024
+    }
+
+    pub(crate) fn peer_read_timeout() -> Duration {
+        Duration::from_secs(60)
+    }
+
+    pub(crate) fn remote_blocklist_refresh_interval() -> Duration {
+        Duration::from_secs(5 * 60)
+    }
+
     pub(crate) fn status_timeout() -> Duration {
         Duration::from_secs(10)
     }
@@ -1360,6 +2275,14 @@ pub(crate) mod default {
         Duration::from_secs(60)
     }
 
+    pub(crate) fn signed_url_method() -> String {
+        "GET".to_owned()
+    }
+
+    pub(crate) fn signed_url_validity() -> Duration {
+        Duration::from_secs(60 * 60)
+    }
+
     pub(crate) fn allowed_headers() -> Vec<String> {
         vec![
             "content-type".to_string(),
@@ -1409,6 +2332,7 @@ mod tests {
                 end_epoch: None,
             },
             dry_run: false,
+            preview: false,
             force: false,
             ignore_resources: false,
             deletable: false,
@@ -1435,6 +2359,15 @@ mod tests {
                     bind_address: SocketAddr::from_str("127.0.0.1:12345").unwrap(),
                     metrics_address: default::metrics_address(),
                     blocklist: None,
+                    remote_blocklist_url: None,
+                    remote_blocklist_refresh_interval: default::remote_blocklist_refresh_interval(),
+                    moderation_admin_token_file: None,
+                    cors_config: None,
+                    rate_limit_config: None,
+                    url_signing_key_file: None,
+                    require_signed_reads: false,
+                    tls_args: TlsArgs::default(),
+                    tracing_args: TracingArgs::default(),
                 },
                 max_body_size_kib: default::max_body_size_kib(),
                 max_request_buffer_size: default::max_request_buffer_size(),
@@ -1450,10 +2383,34 @@ mod tests {
                 jwt_algorithm: None,
                 jwt_expiring_sec: 0,
                 jwt_verify_upload: false,
+                api_keys_config: None,
+                resumable_uploads_dir: None,
+                max_resumable_upload_size_mib: default::max_resumable_upload_size_mib(),
+                resumable_upload_expiry: default::resumable_upload_expiry(),
+                publish_jobs_dir: None,
+                max_concurrent_publish_jobs: default::max_concurrent_publish_jobs(),
+                publish_job_retention: default::publish_job_retention(),
                 replay_suppression_config: Default::default(),
             },
             aggregator_args: AggregatorArgs {
                 allowed_headers: default::allowed_headers(),
+                response_headers_config: None,
+                serve_sites: false,
+                blob_cache_size_mib: default::blob_cache_size_mib(),
+                max_cached_blob_size_mib: default::max_cached_blob_size_mib(),
+                blob_cache_ttl: default::blob_cache_ttl(),
+                blob_cache_dir: None,
+                blob_cache_disk_size_mib: default::blob_cache_disk_size_mib(),
+                blob_cache_admin_token_file: None,
+                pinned_blobs_size_mib: default::pinned_blobs_size_mib(),
+                disable_compression: false,
+                compression_min_size: default::compression_min_size(),
+                peer_aggregators: vec![],
+                peer_read_timeout: default::peer_read_timeout(),
+            },
+            s3_args: S3Args {
+                s3_index: None,
+                s3_epochs: default::s3_epochs(),
             },
         })
     }
@@ -1474,8 +2431,10 @@ mod tests {
         let mut app = App {
             config: None,
             context: None,
+            network: None,
             wallet: None,
             gas_budget: None,
+            sponsor: None,
             json: false,
             command: Commands::Json {
                 command_string: Some(json.to_string()),
@@ -1485,6 +2444,41 @@ mod tests {
         assert_eq!(app.command, command);
         Ok(())
     }
+
+    fn tls_args(certificate: bool, key: bool, acme: bool) -> TlsArgs {
+        TlsArgs {
+            tls_certificate: certificate.then(|| PathBuf::from("cert.pem")),
+            tls_key: key.then(|| PathBuf::from("key.pem")),
+            acme_domains: if acme {
+                vec!["example.com".to_owned()]
+            } else {
+                vec![]
+            },
+            ..Default::default()
+        }
+    }
+
+    param_test! {
+        test_tls_config: [
+            plain_http: (tls_args(false, false, false), Some(false)),
+            pem: (tls_args(true, true, false), Some(true)),
+            acme: (tls_args(false, false, true), Some(true)),
+            missing_key: (tls_args(true, false, false), None),
+            missing_certificate: (tls_args(false, true, false), None),
+            pem_and_acme: (tls_args(true, true, true), None),
+        ]
+    }
+    /// Tests that the TLS configuration is only valid with a complete source of certificates.
+    ///
+    /// `expected` is `None` if the configuration is invalid, and otherwise whether TLS is enabled.
+    fn test_tls_config(args: TlsArgs, expected: Option<bool>) {
+        let tls_config = args.tls_config();
+        assert_eq!(
+            tls_config.as_ref().ok().map(Option::is_some),
+            expected,
+            "{tls_config:?}"
+        );
+    }
 }
 
 /// Specifies whether the user has granted the confirmation for the action, or if it is required.
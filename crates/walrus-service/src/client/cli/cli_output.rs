@@ -4,6 +4,7 @@
 use std::{io::stdout, num::NonZeroU16, path::PathBuf};
 
 use anyhow::Result;
+use chrono::{DateTime, Utc};
 use colored::Colorize;
 use indoc::printdoc;
 use itertools::Itertools as _;
@@ -18,47 +19,62 @@ use walrus_sdk::{
     },
     format_event_id,
 };
-use walrus_sui::types::Blob;
-
-use crate::client::{
-    cli::{
-        error,
-        success,
-        thousands_separator,
-        warning,
-        HumanReadableBytes,
-        HumanReadableFrost,
-        HumanReadableMist,
-        WalrusColors,
-    },
-    responses::{
-        BlobIdConversionOutput,
-        BlobIdOutput,
-        BlobStatusOutput,
-        DeleteOutput,
-        DryRunOutput,
-        EncodingDependentPriceInfo,
-        EpochTimeOrMessage,
-        ExampleBlobInfo,
-        ExchangeOutput,
-        ExtendBlobOutput,
-        FundSharedBlobOutput,
-        GetBlobAttributeOutput,
-        InfoBftOutput,
-        InfoCommitteeOutput,
-        InfoEpochOutput,
-        InfoOutput,
-        InfoPriceOutput,
-        InfoSizeOutput,
-        InfoStorageOutput,
-        NodeHealthOutput,
-        ReadOutput,
-        ServiceHealthInfoOutput,
-        ShareBlobOutput,
-        StakeOutput,
-        StorageNodeInfo,
-        WalletOutput,
+use walrus_sui::types::{move_structs::BlobWithAttribute, Blob};
+
+use crate::{
+    client::{
+        cli::{
+            error,
+            success,
+            thousands_separator,
+            warning,
+            HumanReadableBytes,
+            HumanReadableFrost,
+            HumanReadableMist,
+            WalrusColors,
+        },
+        responses::{
+            BalanceOutput,
+            BlobIdConversionOutput,
+            BlobIdOutput,
+            BlobStatusOutput,
+            DeleteOutput,
+            DryRunOutput,
+            EncodingBenchmarkOutput,
+            EncodingDependentPriceInfo,
+            EpochOutput,
+            EpochPricesOutput,
+            EpochTimeOrMessage,
+            ExampleBlobInfo,
+            ExchangeOutput,
+            ExtendBlobOutput,
+            FundSharedBlobOutput,
+            GetBlobAttributeOutput,
+            InfoBftOutput,
+            InfoCommitteeOutput,
+            InfoEpochOutput,
+            InfoOutput,
+            InfoPriceOutput,
+            InfoSizeOutput,
+            InfoStorageOutput,
+            NodeHealthOutput,
+            ReadOutput,
+            ServiceHealthInfoOutput,
+            ShareBlobOutput,
+            SharedBlobFundingOutput,
+            SignedUrlOutput,
+            StakeOutput,
+            StakeStatusOutput,
+            StakedWalWithRewards,
+            StorageNodeInfo,
+            StorageResourcesOutput,
+            StorageReuse,
+            TestVectorVerificationOutput,
+            UnstakeOutput,
+            WalletOutput,
+        },
     },
+    utils::ShardAssignmentPreview,
 };
 
 /// Trait to differentiate output depending on the output mode.
@@ -84,6 +100,7 @@ impl CliOutput for Vec<BlobStoreResultWithPath> {
 
         let mut total_encoded_size = 0;
         let mut total_cost = 0;
+        let mut total_subsidized_cost = 0;
         let mut reuse_and_extend_count = 0;
         let mut newly_certified = 0;
 
@@ -91,11 +108,13 @@ impl CliOutput for Vec<BlobStoreResultWithPath> {
             if let BlobStoreResult::NewlyCreated {
                 resource_operation,
                 cost,
+                subsidized_cost,
                 ..
             } = &res.blob_store_result
             {
                 total_encoded_size += resource_operation.encoded_length();
                 total_cost += cost;
+                total_subsidized_cost += subsidized_cost.unwrap_or(*cost);
                 match resource_operation {
                     RegisterBlobOp::ReuseAndExtend { .. } => {
                         reuse_and_extend_count += 1;
@@ -130,7 +149,10 @@ impl CliOutput for Vec<BlobStoreResultWithPath> {
                 "Total encoded size: {}",
                 HumanReadableBytes(total_encoded_size)
             );
-            println!("Total cost: {}", HumanReadableFrost::from(total_cost));
+            println!(
+                "Total cost: {}",
+                format_cost(total_cost, Some(total_subsidized_cost))
+            );
         } else {
             println!(
                 "{}",
@@ -170,6 +192,7 @@ impl CliOutput for BlobStoreResultWithPath {
                 blob_object,
                 resource_operation,
                 cost,
+                subsidized_cost,
                 shared_blob_object,
             } => {
                 let operation_str = match resource_operation {
@@ -208,7 +231,7 @@ impl CliOutput for BlobStoreResultWithPath {
                     blob_object.id,
                     HumanReadableBytes(blob_object.size),
                     HumanReadableBytes(resource_operation.encoded_length()),
-                    HumanReadableFrost::from(*cost),
+                    format_cost(*cost, *subsidized_cost),
                     operation_str,
                     blob_object.storage.end_epoch,
                     shared_blob_object
@@ -375,6 +398,74 @@ impl CliOutput for BlobIdConversionOutput {
     }
 }
 
+impl CliOutput for TestVectorVerificationOutput {
+    fn print_cli_output(&self) {
+        if self.failures.is_empty() {
+            println!("{} Verified {} test vectors", success(), self.n_verified);
+            return;
+        }
+        println!(
+            "{} {} of {} test vectors failed verification:",
+            error(),
+            self.failures.len(),
+            self.n_verified + self.failures.len()
+        );
+        for failure in &self.failures {
+            println!(
+                "  test vector {} (seed {}, {} bytes): {}",
+                failure.index, failure.seed, failure.blob_length, failure.error
+            );
+        }
+    }
+}
+
+impl CliOutput for EncodingBenchmarkOutput {
+    fn print_cli_output(&self) {
+        println!(
+            "\n{}\nEncoding type: {}, number of shards: {}, mean of {} iterations\n",
+            "Encoding benchmark".bold().walrus_purple(),
+            self.encoding_type,
+            self.n_shards,
+            self.iterations,
+        );
+        let mut table = Table::new();
+        table.set_format(default_table_format());
+        table.set_titles(row![
+            b->"Blob size",
+            bc->"Encode",
+            bc->"Compute blob ID",
+            bc->"Decode",
+        ]);
+        for result in &self.results {
+            let format_duration = |duration: std::time::Duration| {
+                format!(
+                    "{:.1} ms ({}/s)",
+                    duration.as_secs_f64() * 1000.0,
+                    HumanReadableBytes(result.bytes_per_second(duration))
+                )
+            };
+            table.add_row(row![
+                r->HumanReadableBytes(result.blob_size),
+                r->format_duration(result.encode),
+                r->format_duration(result.compute_metadata),
+                r->format_duration(result.decode),
+            ]);
+        }
+        table.printstd();
+    }
+}
+
+impl CliOutput for SignedUrlOutput {
+    fn print_cli_output(&self) {
+        println!(
+            "{} Signed URL (expires at {}):\n{}",
+            success(),
+            self.expires_at,
+            self.url
+        );
+    }
+}
+
 impl CliOutput for InfoOutput {
     fn print_cli_output(&self) {
         let Self {
@@ -625,6 +716,49 @@ impl CliOutput for InfoBftOutput {
     }
 }
 
+impl CliOutput for ShardAssignmentPreview {
+    fn print_cli_output(&self) {
+        let Self {
+            node_id,
+            next_epoch,
+            current_shards,
+            next_shards,
+            gained_shards,
+            lost_shards,
+            estimated_bytes_per_shard,
+        } = self;
+
+        printdoc!(
+            "
+
+            {heading}
+            Storage node ID: {node_id}
+            Shards in the current epoch: {n_current}
+            Shards in epoch {next_epoch}: {n_next}
+
+            Gained shards ({n_gained}):
+            {gained}
+
+            Lost shards ({n_lost}):
+            {lost}
+
+            Estimated data per shard: {per_shard}
+            Estimated data to sync: {to_sync}
+            (Estimates are based on the reserved storage capacity and are upper bounds.)
+            ",
+            heading = "Next-epoch shard assignment".bold().walrus_teal(),
+            n_current = current_shards.len(),
+            n_next = next_shards.len(),
+            n_gained = gained_shards.len(),
+            gained = DisplayShardList(gained_shards),
+            n_lost = lost_shards.len(),
+            lost = DisplayShardList(lost_shards),
+            per_shard = HumanReadableBytes(*estimated_bytes_per_shard),
+            to_sync = HumanReadableBytes(self.estimated_bytes_to_sync()),
+        );
+    }
+}
+
 fn print_storage_node_table(n_shards: &NonZeroU16, storage_nodes: &[StorageNodeInfo]) {
     let mut table = Table::new();
     table.set_format(default_table_format());
@@ -705,6 +839,103 @@ impl CliOutput for Vec<Blob> {
     }
 }
 
+impl CliOutput for Vec<BlobWithAttribute> {
+    fn print_cli_output(&self) {
+        let mut table = Table::new();
+        table.set_format(default_table_format());
+        table.set_titles(row![
+            b->"Blob ID",
+            bc->"Unencoded size",
+            bc->"Certified?",
+            bc->"Deletable?",
+            bc->"Exp. epoch",
+            b->"Object ID",
+            b->"Attributes",
+        ]);
+
+        for BlobWithAttribute { blob, attribute } in self {
+            let attributes = attribute
+                .iter()
+                .flat_map(|attribute| attribute.iter())
+                .map(|(key, value)| format!("{key}: {value}"))
+                .join("\n");
+            table.add_row(row![
+                blob.blob_id,
+                c->HumanReadableBytes(blob.size),
+                c->blob.certified_epoch.is_some(),
+                c->blob.deletable,
+                c->blob.storage.end_epoch,
+                blob.id,
+                attributes,
+            ]);
+        }
+        table.printstd();
+    }
+}
+
+impl CliOutput for StorageResourcesOutput {
+    fn print_cli_output(&self) {
+        if self.resources.is_empty() {
+            println!("The wallet does not own any unused storage resources.");
+        } else {
+            let mut table = Table::new();
+            table.set_format(default_table_format());
+            table.set_titles(row![
+                b->"Object ID",
+                bc->"Size",
+                bc->"Start epoch",
+                bc->"End epoch",
+            ]);
+            for storage in &self.resources {
+                table.add_row(row![
+                    storage.id,
+                    c->HumanReadableBytes(storage.storage_size),
+                    c->storage.start_epoch,
+                    c->storage.end_epoch,
+                ]);
+            }
+            table.printstd();
+            println!(
+                "Total size: {} (current epoch: {})",
+                HumanReadableBytes(self.total_size),
+                self.current_epoch
+            );
+        }
+
+        let Some(suggestion) = &self.suggestion else {
+            return;
+        };
+        let store = format!(
+            "a blob with an encoded size of {} until epoch {}",
+            HumanReadableBytes(suggestion.encoded_size),
+            suggestion.end_epoch
+        );
+        match &suggestion.reuse {
+            StorageReuse::Reuse(storage_id) => {
+                println!(
+                    "{} The storage resource {storage_id} can store {store}.",
+                    success()
+                )
+            }
+            StorageReuse::FuseAmount(storage_ids) => println!(
+                "{} Fused by amount, the storage resources {} can store {store}.",
+                success(),
+                storage_ids.iter().join(", ")
+            ),
+            StorageReuse::FusePeriods(storage_ids) => println!(
+                "{} Fused by period, the storage resources {} can store {store}.",
+                success(),
+                storage_ids.iter().join(", ")
+            ),
+            StorageReuse::None => println!(
+                "{} None of the storage resources can store {store}; new storage needs to be \
+                purchased.",
+                warning()
+            ),
+        }
+    }
+}
+
 impl CliOutput for DeleteOutput {
     fn print_cli_output(&self) {
         let identity = self.blob_identity.to_string();
@@ -782,6 +1013,18 @@ fn deletable_counts_summary(counts: &DeletableCounts) -> String {
     )
 }
 
+/// Formats the storage cost, showing the full price next to the subsidized one, if lower.
+fn format_cost(cost: u64, subsidized_cost: Option<u64>) -> String {
+    match subsidized_cost.filter(|subsidized_cost| *subsidized_cost < cost) {
+        Some(subsidized_cost) => format!(
+            "{} (subsidized; full price: {})",
+            HumanReadableFrost::from(subsidized_cost),
+            HumanReadableFrost::from(cost)
+        ),
+        None => HumanReadableFrost::from(cost).to_string(),
+    }
+}
+
 impl CliOutput for StakeOutput {
     fn print_cli_output(&self) {
         let Some(first_wal) = self.staked_wal.first() else {
@@ -801,6 +1044,77 @@ impl CliOutput for StakeOutput {
     }
 }
 
+impl CliOutput for UnstakeOutput {
+    fn print_cli_output(&self) {
+        for unstaked in &self.unstaked {
+            match unstaked.withdraw_epoch {
+                None => println!(
+                    "{} Withdrew the staked WAL {} to the wallet.",
+                    success(),
+                    unstaked.staked_wal_id
+                ),
+                Some(withdraw_epoch) => println!(
+                    "{} Requested the withdrawal of the staked WAL {}; run `walrus unstake` again \
+                    to withdraw it from epoch {} on.",
+                    success(),
+                    unstaked.staked_wal_id,
+                    withdraw_epoch
+                ),
+            }
+        }
+    }
+}
+
+impl CliOutput for StakeStatusOutput {
+    fn print_cli_output(&self) {
+        if self.staked_wal.is_empty() {
+            println!("{} No staked WAL found.", warning());
+            return;
+        }
+        println!(
+            "{} (current epoch: {})",
+            "Staked WAL".bold().walrus_purple(),
+            self.current_epoch
+        );
+        let mut table = Table::new();
+        table.set_format(default_table_format());
+        table.set_titles(row![
+            b->"Object ID",
+            b->"Node ID",
+            b->"Principal",
+            b->"Rewards",
+            b->"Activation epoch",
+            b->"State",
+        ]);
+        for StakedWalWithRewards {
+            staked_wal,
+            rewards,
+        } in &self.staked_wal
+        {
+            table.add_row(row![
+                staked_wal.id,
+                staked_wal.node_id,
+                r->HumanReadableFrost::from(staked_wal.principal),
+                r->HumanReadableFrost::from(*rewards),
+                r->staked_wal.activation_epoch,
+                staked_wal.state,
+            ]);
+        }
+        table.printstd();
+        let total_principal: u64 = self
+            .staked_wal
+            .iter()
+            .map(|staked| staked.staked_wal.principal)
+            .sum();
+        let total_rewards: u64 = self.staked_wal.iter().map(|staked| staked.rewards).sum();
+        println!(
+            "Total: {} staked, {} rewards",
+            HumanReadableFrost::from(total_principal),
+            HumanReadableFrost::from(total_rewards)
+        );
+    }
+}
+
 impl CliOutput for WalletOutput {
     fn print_cli_output(&self) {
         println!(
@@ -813,6 +1127,13 @@ impl CliOutput for WalletOutput {
 
 impl CliOutput for ExchangeOutput {
     fn print_cli_output(&self) {
+        if self.amount_sui == 0 {
+            println!(
+                "{} The WAL balance is sufficient; no SUI was exchanged.",
+                success()
+            );
+            return;
+        }
         println!(
             "{} Exchanged {} for WAL.",
             success(),
@@ -821,6 +1142,23 @@ impl CliOutput for ExchangeOutput {
     }
 }
 
+impl CliOutput for BalanceOutput {
+    fn print_cli_output(&self) {
+        printdoc! {"
+
+            {heading}
+            Address: {address}
+            SUI: {sui_balance}
+            WAL: {wal_balance}
+            ",
+            heading = "Balance".bold().walrus_purple(),
+            address = self.address,
+            sui_balance = HumanReadableMist::from(self.sui_balance),
+            wal_balance = HumanReadableFrost::from(self.wal_balance),
+        }
+    }
+}
+
 impl CliOutput for ShareBlobOutput {
     fn print_cli_output(&self) {
         println!(
@@ -846,6 +1184,86 @@ impl CliOutput for FundSharedBlobOutput {
     }
 }
 
+impl CliOutput for SharedBlobFundingOutput {
+    fn print_cli_output(&self) {
+        printdoc! {"
+
+            {heading}
+            Shared blob object ID: {shared_blob_object_id}
+            Blob ID: {blob_id}
+            Funds: {funds}
+            Cost per epoch: {cost_per_epoch}
+            Expiry epoch: {end_epoch} (current epoch: {current_epoch})
+            Funded epochs: {funded_epochs}
+            Remaining epochs (including funded epochs): {remaining_epochs}
+            ",
+            heading = "Shared blob funding".bold().walrus_purple(),
+            shared_blob_object_id = self.shared_blob_object_id,
+            blob_id = self.blob_id,
+            funds = HumanReadableFrost::from(self.funds),
+            cost_per_epoch = HumanReadableFrost::from(self.cost_per_epoch),
+            end_epoch = self.end_epoch,
+            current_epoch = self.current_epoch,
+            funded_epochs = self.funded_epochs,
+            remaining_epochs = self.remaining_epochs,
+        }
+    }
+}
+
+impl CliOutput for EpochOutput {
+    fn print_cli_output(&self) {
+        let format_time = |time: Option<DateTime<Utc>>| match time {
+            Some(time) => time.to_string(),
+            None => "unknown (epoch change in progress)".to_owned(),
+        };
+        printdoc! {"
+
+            {heading}
+            Current epoch: {epoch}
+            Start time: {start}
+            Estimated end time: {estimated_end}
+            Epoch duration: {epoch_duration}
+            ",
+            heading = "Epoch".bold().walrus_teal(),
+            epoch = self.epoch,
+            start = format_time(self.start),
+            estimated_end = format_time(self.estimated_end),
+            epoch_duration = humantime::format_duration(self.epoch_duration),
+        }
+    }
+}
+
+impl CliOutput for Vec<EpochPricesOutput> {
+    fn print_cli_output(&self) {
+        if self.is_empty() {
+            println!(
+                "{} No epoch changes found; the full node may have pruned them.",
+                warning()
+            );
+            return;
+        }
+        println!(
+            "\n{}\n(Conversion rate: 1 WAL = 1,000,000,000 FROST)",
+            "Storage prices per epoch".bold().walrus_teal()
+        );
+        let mut table = Table::new();
+        table.set_format(default_table_format());
+        table.set_titles(row![
+            bc->"Epoch",
+            br->"Price per encoded storage unit",
+            br->"Additional price for each write",
+        ]);
+        for prices in self {
+            table.add_row(row![
+                c->prices.epoch,
+                r->HumanReadableFrost::from(prices.storage_price_per_unit_size),
+                r->HumanReadableFrost::from(prices.write_price_per_unit_size),
+            ]);
+        }
+        table.printstd();
+    }
+}
+
 impl CliOutput for ExtendBlobOutput {
     fn print_cli_output(&self) {
         println!(
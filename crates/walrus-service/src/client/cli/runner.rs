@@ -4,47 +4,62 @@
 //! Helper struct to run the Walrus client binary commands.
 
 use std::{
+    convert::Infallible,
     io::Write,
     iter,
-    num::NonZeroU16,
+    num::{NonZeroU16, NonZeroU32},
     path::{Path, PathBuf},
     sync::Arc,
-    time::Duration,
+    time::{Duration, Instant},
 };
 
 use anyhow::{Context, Result};
 use chrono::{DateTime, Utc};
 use indicatif::MultiProgress;
 use itertools::Itertools as _;
-use rand::seq::SliceRandom;
+use rand::{seq::SliceRandom, RngCore as _};
+use reqwest::{Method, Url};
 use sui_config::{sui_config_dir, SUI_CLIENT_CONFIG};
-use sui_sdk::wallet_context::WalletContext;
-use sui_types::base_types::ObjectID;
+use sui_sdk::{rpc_types::ObjectChange, wallet_context::WalletContext};
+use sui_types::base_types::{ObjectID, SuiAddress};
 use walrus_core::{
     encoding::{
         encoded_blob_length_for_n_shards,
+        ChunkedBlobEncoder,
         EncodingConfig,
         EncodingConfigTrait as _,
+        EncodingTestVector,
         Primary,
     },
     ensure,
     metadata::BlobMetadataApi as _,
     BlobId,
     EncodingType,
+    Epoch,
     EpochCount,
     DEFAULT_ENCODING,
     SUPPORTED_ENCODING_TYPES,
 };
 use walrus_rest_client::api::BlobStatus;
 use walrus_sdk::{
-    client::{resource::RegisterBlobOp, Client, NodeCommunicationFactory},
-    config::load_configuration,
+    blob_source::BlobSource,
+    client::{
+        resource::RegisterBlobOp,
+        Client,
+        NodeCommunicationFactory,
+        WalrusStoreBlob,
+        WalrusStoreBlobApi as _,
+    },
+    config::{load_configuration, NetworkProfile},
     error::ClientErrorKind,
     store_when::StoreWhen,
     sui::{
         client::{
+            BlobObjectMetadata,
             BlobPersistence,
+            CoinType,
             ExpirySelectionPolicy,
+            GasSponsorConfig,
             PostStoreAction,
             ReadClient,
             SuiContractClient,
@@ -53,12 +68,14 @@ use walrus_sdk::{
         types::move_structs::{Authorized, BlobAttribute, EpochState},
         utils::SuiNetwork,
     },
-    utils::styled_spinner,
+    utils::{styled_progress_bar, styled_spinner},
 };
 use walrus_utils::metrics::Registry;
 
 use super::args::{
+    load_url_signing_key,
     AggregatorArgs,
+    BenchCommands,
     BlobIdentifiers,
     BlobIdentity,
     BurnSelection,
@@ -73,7 +90,9 @@ use super::args::{
     NodeSelection,
     PublisherArgs,
     RpcArg,
+    S3Args,
     SortBy,
+    TestVectorCommands,
     UserConfirmation,
 };
 use crate::{
@@ -87,16 +106,30 @@ use crate::{
             warning,
             BlobIdDecimal,
             CliOutput,
+            HumanReadableBytes,
             HumanReadableFrost,
             HumanReadableMist,
         },
+        daemon::{
+            BlobCache,
+            MeteredClient,
+            MultiSourceClient,
+            PublishJobs,
+            ResumableUploads,
+            S3Index,
+        },
         multiplexer::ClientMultiplexer,
         responses::{
+            BalanceOutput,
             BlobIdConversionOutput,
             BlobIdOutput,
             BlobStatusOutput,
             DeleteOutput,
             DryRunOutput,
+            EncodingBenchmarkOutput,
+            EncodingBenchmarkResult,
+            EpochOutput,
+            EpochPricesOutput,
             ExchangeOutput,
             ExtendBlobOutput,
             FundSharedBlobOutput,
@@ -111,13 +144,22 @@ use crate::{
             ReadOutput,
             ServiceHealthInfoOutput,
             ShareBlobOutput,
+            SharedBlobFundingOutput,
+            SignedUrlOutput,
             StakeOutput,
+            StakeStatusOutput,
+            StakedWalWithRewards,
+            StorageResourcesOutput,
+            TestVectorFailure,
+            TestVectorVerificationOutput,
+            UnstakeOutput,
+            UnstakedWal,
             WalletOutput,
         },
         ClientConfig,
         ClientDaemon,
     },
-    utils::{self, generate_sui_wallet, MetricsAndLoggingRuntime},
+    utils::{self, generate_sui_wallet, MetricsAndLoggingRuntime, ShardAssignmentPreview},
 };
 
 /// A helper struct to run commands for the Walrus client.
@@ -137,14 +179,31 @@ pub struct ClientCommandRunner {
 
 impl ClientCommandRunner {
     /// Creates a new client runner, loading the configuration and wallet context.
+    ///
+    /// If a `network` is specified, its built-in configuration is used instead of a configuration
+    /// file.
     pub fn new(
         config: &Option<PathBuf>,
         context: Option<&str>,
+        network: Option<NetworkProfile>,
         wallet_override: &Option<PathBuf>,
         gas_budget: Option<u64>,
+        sponsor: Option<GasSponsorConfig>,
         json: bool,
     ) -> Self {
-        let config = load_configuration(config.as_ref(), context);
+        let config = match network {
+            Some(network) => {
+                tracing::info!(%network, "using the built-in Walrus configuration of the network");
+                network.client_config()
+            }
+            None => load_configuration(config.as_ref(), context),
+        };
+        let config = config.map(|mut config| {
+            if sponsor.is_some() {
+                config.gas_sponsor = sponsor;
+            }
+            config
+        });
         let wallet_config = wallet_override
             .as_ref()
             .map(WalletConfig::from_path)
@@ -179,6 +238,7 @@ impl ClientCommandRunner {
                 files,
                 epoch_arg,
                 dry_run,
+                preview,
                 force,
                 ignore_resources,
                 deletable,
@@ -189,6 +249,7 @@ impl ClientCommandRunner {
                     files,
                     epoch_arg,
                     dry_run,
+                    preview,
                     StoreWhen::from_flags(force, ignore_resources),
                     BlobPersistence::from_deletable(deletable),
                     PostStoreAction::from_share(share),
@@ -212,6 +273,16 @@ impl ClientCommandRunner {
                 command,
             } => self.info(rpc_url, command).await,
 
+            CliCommands::Epoch {
+                rpc_arg: RpcArg { rpc_url },
+                wait_for,
+            } => self.epoch(rpc_url, wait_for).await,
+
+            CliCommands::PriceHistory {
+                rpc_arg: RpcArg { rpc_url },
+                epochs,
+            } => self.price_history(rpc_url, epochs).await,
+
             CliCommands::Health {
                 node_selection,
                 detail,
@@ -228,7 +299,28 @@ impl ClientCommandRunner {
 
             CliCommands::ConvertBlobId { blob_id_decimal } => self.convert_blob_id(blob_id_decimal),
 
-            CliCommands::ListBlobs { include_expired } => self.list_blobs(include_expired).await,
+            CliCommands::SignUrl {
+                url,
+                key_file,
+                method,
+                valid_for,
+                max_size,
+                identity,
+            } => self.sign_url(
+                &url,
+                &key_file,
+                &method,
+                valid_for,
+                max_size,
+                identity.as_deref(),
+            ),
+
+            CliCommands::ListBlobs {
+                include_expired,
+                with_attributes,
+            } => self.list_blobs(include_expired, with_attributes).await,
+
+            CliCommands::Storage { size, epochs } => self.storage(size, epochs).await,
 
             CliCommands::Delete {
                 target,
@@ -244,6 +336,10 @@ impl ClientCommandRunner {
                 self.stake_with_node_pools(node_ids, amounts).await
             }
 
+            CliCommands::Unstake { staked_wal_ids } => self.unstake(staked_wal_ids).await,
+
+            CliCommands::StakeStatus { owner } => self.stake_status(owner).await,
+
             CliCommands::GenerateSuiWallet {
                 path,
                 sui_network,
@@ -277,7 +373,13 @@ impl ClientCommandRunner {
             CliCommands::GetWal {
                 exchange_id,
                 amount,
-            } => self.exchange_sui_for_wal(exchange_id, amount).await,
+                min_balance,
+            } => {
+                self.exchange_sui_for_wal(exchange_id, amount, min_balance)
+                    .await
+            }
+
+            CliCommands::Balance => self.balance().await,
 
             CliCommands::BurnBlobs {
                 burn_selection,
@@ -303,6 +405,22 @@ impl ClientCommandRunner {
                 FundSharedBlobOutput { amount }.print_output(self.json)
             }
 
+            CliCommands::GetSharedBlobFunding { shared_blob_obj_id } => {
+                let sui_read_client = get_sui_read_client_from_rpc_node_or_wallet(
+                    &self.config?,
+                    None,
+                    self.wallet,
+                    !self.wallet_set_explicitly,
+                )
+                .await?;
+                SharedBlobFundingOutput::from(
+                    sui_read_client
+                        .get_shared_blob_funding(shared_blob_obj_id)
+                        .await?,
+                )
+                .print_output(self.json)
+            }
+
             CliCommands::Extend {
                 blob_obj_id,
                 shared,
@@ -425,6 +543,10 @@ impl ClientCommandRunner {
             CliCommands::NodeAdmin { node_id, command } => {
                 self.run_admin_command(node_id, command).await
             }
+
+            CliCommands::EncodingTestVectors { command } => self.encoding_test_vectors(command),
+
+            CliCommands::Bench { command } => self.bench(command),
         }
     }
 
@@ -461,8 +583,9 @@ impl ClientCommandRunner {
             DaemonCommands::Daemon {
                 args,
                 aggregator_args,
+                s3_args,
             } => {
-                self.daemon(&metrics_runtime.registry, args, aggregator_args)
+                self.daemon(&metrics_runtime.registry, args, aggregator_args, s3_args)
                     .await
             }
         }
@@ -524,6 +647,7 @@ impl ClientCommandRunner {
         files: Vec<PathBuf>,
         epoch_arg: EpochArg,
         dry_run: bool,
+        preview: bool,
         store_when: StoreWhen,
         persistence: BlobPersistence,
         post_store: PostStoreAction,
@@ -552,17 +676,37 @@ impl ClientCommandRunner {
             return Self::store_dry_run(client, files, encoding_type, epochs_ahead, self.json)
                 .await;
         }
+        if preview && self.json {
+            anyhow::bail!(
+                "the cost preview requires a confirmation and is not available in JSON mode"
+            );
+        }
 
         tracing::info!("storing {} files as blobs on Walrus", files.len());
         let start_timer = std::time::Instant::now();
         let blobs = files
             .into_iter()
             .map(|file| read_blob_from_file(&file).map(|blob| (file, blob)))
-            .collect::<Result<Vec<(PathBuf, Vec<u8>)>>>()?;
+            .collect::<Result<Vec<(PathBuf, BlobSource)>>>()?;
+        let blob_slices = blobs
+            .iter()
+            .map(|(_, blob)| blob.as_ref())
+            .collect::<Vec<_>>();
+        let encoded_blobs = client.encode_blobs(
+            WalrusStoreBlob::<String>::default_unencoded_blobs_from_slice(&blob_slices),
+            encoding_type,
+        )?;
+        if preview
+            && !Self::confirm_store_preview(&client, &encoded_blobs, epochs_ahead, persistence)
+                .await?
+        {
+            println!("{} Aborting. No blobs were stored.", success());
+            return Ok(());
+        }
         let results = client
-            .reserve_and_store_blobs_retry_committees_with_path(
+            .reserve_and_store_encoded_blobs_retry_committees_with_path(
                 &blobs,
-                encoding_type,
+                encoded_blobs,
                 epochs_ahead,
                 store_when,
                 persistence,
@@ -593,6 +737,46 @@ impl ClientCommandRunner {
         results.print_output(self.json)
     }
 
+    /// Previews the costs of registering the encoded blobs with a dry run of the transaction, and
+    /// asks the user for confirmation to store them.
+    async fn confirm_store_preview(
+        client: &Client<SuiContractClient>,
+        encoded_blobs: &[WalrusStoreBlob<'_, String>],
+        epochs_ahead: EpochCount,
+        persistence: BlobPersistence,
+    ) -> Result<bool> {
+        let blob_metadata_list = encoded_blobs
+            .iter()
+            .filter_map(|blob| blob.get_metadata())
+            .map(BlobObjectMetadata::try_from)
+            .collect::<Result<Vec<_>, _>>()?;
+        let n_blobs = blob_metadata_list.len();
+        let preview = client
+            .sui_client()
+            .preview_reserve_and_register_blobs(epochs_ahead, blob_metadata_list, persistence)
+            .await?;
+
+        println!(
+            "{} Registering {} blob(s) from scratch for {} epoch(s) is expected to cost:\n\
+            Storage: {}\n\
+            Gas: {}\n\
+            Objects created: {}\n\
+            Certifying the blobs after the upload requires another transaction. Storage that is \
+            already owned by the wallet may be reused, lowering the actual costs.",
+            warning(),
+            n_blobs,
+            epochs_ahead,
+            HumanReadableFrost::from(preview.wal_cost),
+            HumanReadableMist::from(preview.gas_cost),
+            preview
+                .object_changes
+                .iter()
+                .filter(|change| matches!(change, ObjectChange::Created { .. }))
+                .count(),
+        );
+        ask_for_confirmation()
+    }
+
     async fn store_dry_run(
         client: Client<SuiContractClient>,
         files: Vec<PathBuf>,
@@ -746,9 +930,59 @@ impl ClientCommandRunner {
             Some(InfoCommands::Bft) => InfoBftOutput::get_bft_info(&sui_read_client)
                 .await?
                 .print_output(self.json),
+            Some(InfoCommands::ShardPreview { node_id }) => {
+                ShardAssignmentPreview::fetch(&sui_read_client, node_id)
+                    .await?
+                    .print_output(self.json)
+            }
         }
     }
 
+    pub(crate) async fn epoch(
+        self,
+        rpc_url: Option<String>,
+        wait_for: Option<Epoch>,
+    ) -> Result<()> {
+        let config = self.config?;
+        let sui_read_client = get_sui_read_client_from_rpc_node_or_wallet(
+            &config,
+            rpc_url,
+            self.wallet,
+            !self.wallet_set_explicitly,
+        )
+        .await?;
+
+        let epoch_state = match wait_for {
+            Some(epoch) => {
+                if !self.json {
+                    println!("Waiting for epoch {epoch} to start...");
+                }
+                sui_read_client.wait_for_epoch(epoch).await?
+            }
+            None => sui_read_client.current_epoch_state().await?,
+        };
+        EpochOutput::from(epoch_state).print_output(self.json)
+    }
+
+    pub(crate) async fn price_history(self, rpc_url: Option<String>, epochs: usize) -> Result<()> {
+        let config = self.config?;
+        let sui_read_client = get_sui_read_client_from_rpc_node_or_wallet(
+            &config,
+            rpc_url,
+            self.wallet,
+            !self.wallet_set_explicitly,
+        )
+        .await?;
+
+        sui_read_client
+            .price_history(epochs)
+            .await?
+            .into_iter()
+            .map(EpochPricesOutput::from)
+            .collect::<Vec<_>>()
+            .print_output(self.json)
+    }
+
     pub(crate) async fn health(
         self,
         rpc_url: Option<String>,
@@ -828,43 +1062,107 @@ impl ClientCommandRunner {
             };
 
         tracing::debug!(%n_shards, "encoding the blob");
-        let spinner = styled_spinner();
-        spinner.set_message("computing the blob ID");
-        let metadata = EncodingConfig::new(n_shards)
-            .get_for_type(encoding_type)
-            .compute_metadata(&read_blob_from_file(&file)?)?;
-        spinner.finish_with_message(format!("blob ID computed: {}", metadata.blob_id()));
+        let blob = read_blob_from_file(&file)?;
+        let blob_size = blob.len() as u64;
+        let encoding_config = EncodingConfig::new(n_shards);
+        // Hash the blob window by window, so that the memory required does not grow with the size
+        // of the blob.
+        let encoder = ChunkedBlobEncoder::new(
+            encoding_config.get_for_type(encoding_type),
+            blob_size,
+            ChunkedBlobEncoder::window_size_for_n_shards(n_shards),
+        )?;
+        let progress_bar = styled_progress_bar(blob_size);
+        progress_bar.set_message("computing the blob ID");
+        let read_at = |offset: u64, buffer: &mut [u8]| {
+            let start = usize::try_from(offset).expect("the offset is within the blob");
+            buffer.copy_from_slice(&blob[start..start + buffer.len()]);
+            Ok::<_, Infallible>(())
+        };
+        let Ok(metadata) = encoder.compute_metadata(read_at, |hashed, symbol_size| {
+            progress_bar.set_position(blob_size * hashed as u64 / symbol_size as u64)
+        });
+        progress_bar.finish_with_message(format!("blob ID computed: {}", metadata.blob_id()));
 
         BlobIdOutput::new(&file, &metadata).print_output(self.json)
     }
 
-    pub(crate) async fn list_blobs(self, include_expired: bool) -> Result<()> {
+    pub(crate) async fn list_blobs(
+        self,
+        include_expired: bool,
+        with_attributes: bool,
+    ) -> Result<()> {
         let config = self.config?;
         let contract_client = config
             .new_contract_client(self.wallet?, self.gas_budget)
             .await?;
-        let blobs = contract_client
-            .owned_blobs(
-                None,
-                ExpirySelectionPolicy::from_include_expired_flag(include_expired),
-            )
+        let selection_policy = ExpirySelectionPolicy::from_include_expired_flag(include_expired);
+        if with_attributes {
+            contract_client
+                .owned_blobs_with_attributes(None, selection_policy)
+                .await?
+                .print_output(self.json)
+        } else {
+            contract_client
+                .owned_blobs(None, selection_policy)
+                .await?
+                .print_output(self.json)
+        }
+    }
+
+    pub(crate) async fn storage(self, size: Option<u64>, epochs: Option<EpochCount>) -> Result<()> {
+        let config = self.config?;
+        let contract_client = config
+            .new_contract_client(self.wallet?, self.gas_budget)
+            .await?;
+        let current_epoch = contract_client.current_epoch().await?;
+        let upcoming_store = match (size, epochs) {
+            (Some(size), Some(epochs)) => {
+                let n_shards = contract_client.fixed_system_parameters().await?.n_shards;
+                let encoded_size =
+                    encoded_blob_length_for_n_shards(n_shards, size, DEFAULT_ENCODING)
+                        .context("the blob size is too large to be stored")?;
+                Some((encoded_size, current_epoch + epochs))
+            }
+            _ => None,
+        };
+        let resources = contract_client
+            .owned_storage(ExpirySelectionPolicy::Valid)
             .await?;
-        blobs.print_output(self.json)
+        StorageResourcesOutput::new(resources, current_epoch, upcoming_store)
+            .print_output(self.json)
     }
 
     pub(crate) async fn publisher(self, registry: &Registry, args: PublisherArgs) -> Result<()> {
         args.print_debug_message("attempting to run the Walrus publisher");
+        let blocklist = args.daemon_args.load_blocklist()?;
+        let remote_blocklist = args.daemon_args.remote_blocklist_config()?;
+        let moderation_token = args.daemon_args.load_moderation_admin_token()?;
         let client = ClientMultiplexer::new(
             self.wallet?,
             &self.config?,
             self.gas_budget,
             registry,
             &args,
+            blocklist.clone(),
         )
         .await?;
+        let client = MeteredClient::new(client, registry);
         let auth_config = args.generate_auth_config()?;
-
-        ClientDaemon::new_publisher(
+        let cors_config = args.daemon_args.load_cors_config()?;
+        let rate_limit_config = args.daemon_args.load_rate_limit_config()?;
+        let signed_urls = args.daemon_args.load_signed_urls()?;
+        let tls_config = args.daemon_args.tls_args.tls_config()?;
+        let resumable_uploads = args
+            .resumable_uploads_config()
+            .map(ResumableUploads::new)
+            .transpose()?;
+        let publish_jobs = args
+            .publish_jobs_config()
+            .map(PublishJobs::new)
+            .transpose()?;
+
+        let mut publisher = ClientDaemon::new_publisher(
             client,
             auth_config,
             args.daemon_args.bind_address,
@@ -873,8 +1171,23 @@ impl ClientCommandRunner {
             args.max_request_buffer_size,
             args.max_concurrent_requests,
         )
-        .run()
-        .await?;
+        .with_cors(&cors_config)?
+        .with_rate_limits(rate_limit_config)
+        .with_signed_urls(signed_urls)
+        .with_tls(tls_config);
+        if let Some(resumable_uploads) = resumable_uploads {
+            publisher = publisher.with_resumable_uploads(resumable_uploads);
+        }
+        if let Some(publish_jobs) = publish_jobs {
+            publisher = publisher.with_publish_jobs(publish_jobs);
+        }
+        if let Some(remote_blocklist) = remote_blocklist {
+            publisher = publisher.with_remote_blocklist(blocklist.clone(), remote_blocklist);
+        }
+        if let Some(admin_token) = moderation_token {
+            publisher = publisher.with_moderation(blocklist, admin_token);
+        }
+        publisher.run().await?;
         Ok(())
     }
 
@@ -886,22 +1199,60 @@ impl ClientCommandRunner {
         aggregator_args: AggregatorArgs,
     ) -> Result<()> {
         tracing::debug!(?rpc_url, "attempting to run the Walrus aggregator");
+        let response_headers = aggregator_args.load_response_headers_config()?;
+        let cors_config = daemon_args.load_cors_config()?;
+        let rate_limit_config = daemon_args.load_rate_limit_config()?;
+        let signed_urls = daemon_args.load_signed_urls()?;
+        let tls_config = daemon_args.tls_args.tls_config()?;
+        let admin_token = aggregator_args.load_blob_cache_admin_token()?;
+        let compression = aggregator_args.compression_config();
+        let read_sources = aggregator_args.read_sources_config()?;
+        let blocklist = daemon_args.load_blocklist()?;
+        let remote_blocklist = daemon_args.remote_blocklist_config()?;
+        let moderation_token = daemon_args.load_moderation_admin_token()?;
         let client = get_read_client(
             self.config?,
             rpc_url,
             self.wallet,
             !self.wallet_set_explicitly,
-            &daemon_args.blocklist,
+            &None,
         )
-        .await?;
-        ClientDaemon::new_aggregator(
+        .await?
+        .with_blocklist(blocklist.clone());
+        let encoding_config = client.encoding_config().clone();
+        let client = BlobCache::new(
+            MultiSourceClient::new(
+                MeteredClient::new(client, registry),
+                read_sources,
+                encoding_config,
+                registry,
+            )?,
+            aggregator_args.blob_cache_config(),
+            registry,
+        )?;
+        let mut daemon = ClientDaemon::new_aggregator(
             client,
             daemon_args.bind_address,
             registry,
             aggregator_args.allowed_headers,
+            response_headers,
+            aggregator_args.serve_sites,
         )
-        .run()
-        .await?;
+        .with_cors(&cors_config)?
+        .with_rate_limits(rate_limit_config)
+        .with_signed_urls(signed_urls)
+        .with_tls(tls_config)
+        .with_compression(compression);
+        if let Some(admin_token) = admin_token {
+            daemon = daemon.with_blob_pinning(admin_token);
+        }
+        if let Some(remote_blocklist) = remote_blocklist {
+            daemon = daemon.with_remote_blocklist(blocklist.clone(), remote_blocklist);
+        }
+        if let Some(admin_token) = moderation_token {
+            daemon = daemon.with_moderation(blocklist, admin_token);
+        }
+        daemon.run().await?;
         Ok(())
     }
 
@@ -910,20 +1261,87 @@ impl ClientCommandRunner {
         registry: &Registry,
         args: PublisherArgs,
         aggregator_args: AggregatorArgs,
+        s3_args: S3Args,
     ) -> Result<()> {
         args.print_debug_message("attempting to run the Walrus daemon");
         let auth_config = args.generate_auth_config()?;
-
-        let client = get_contract_client(
-            self.config?,
-            self.wallet,
-            self.gas_budget,
-            &args.daemon_args.blocklist,
+        let response_headers = aggregator_args.load_response_headers_config()?;
+        let admin_token = aggregator_args.load_blob_cache_admin_token()?;
+        let compression = aggregator_args.compression_config();
+        let read_sources = aggregator_args.read_sources_config()?;
+        let cors_config = args.daemon_args.load_cors_config()?;
+        let rate_limit_config = args.daemon_args.load_rate_limit_config()?;
+        let signed_urls = args.daemon_args.load_signed_urls()?;
+        let tls_config = args.daemon_args.tls_args.tls_config()?;
+        let s3_index = s3_args.s3_index.map(S3Index::open).transpose()?;
+        let blocklist = args.daemon_args.load_blocklist()?;
+        let remote_blocklist = args.daemon_args.remote_blocklist_config()?;
+        let moderation_token = args.daemon_args.load_moderation_admin_token()?;
+        let resumable_uploads = args
+            .resumable_uploads_config()
+            .map(ResumableUploads::new)
+            .transpose()?;
+        let publish_jobs = args
+            .publish_jobs_config()
+            .map(PublishJobs::new)
+            .transpose()?;
+
+        // The aggregator and the publisher share the client, and thereby the committees and the
+        // connections to the storage nodes, as well as the blob cache, which also caches the
+        // blobs stored through the publisher. As before, blobs are stored with the main wallet;
+        // the sub-wallets are only used by the standalone publisher.
+        tracing::info!("the daemon stores blobs with the main wallet, not with sub-wallets");
+        let client = get_contract_client(self.config?, self.wallet, self.gas_budget, &None)
+            .await?
+            .with_blocklist(blocklist.clone());
+        let encoding_config = client.encoding_config().clone();
+        let client = BlobCache::new(
+            MultiSourceClient::new(
+                MeteredClient::new(client, registry),
+                read_sources,
+                encoding_config,
+                registry,
+            )?,
+            aggregator_args.blob_cache_config(),
+            registry,
+        )?;
+        let mut daemon = ClientDaemon::new_daemon(
+            client,
+            auth_config.clone(),
+            registry,
+            &args,
+            &aggregator_args,
+            response_headers,
         )
-        .await?;
-        ClientDaemon::new_daemon(client, auth_config, registry, &args, &aggregator_args)
-            .run()
-            .await?;
+        .with_cors(&cors_config)?
+        .with_rate_limits(rate_limit_config)
+        .with_signed_urls(signed_urls)
+        .with_tls(tls_config)
+        .with_compression(compression);
+        if let Some(s3_index) = s3_index {
+            daemon = daemon.with_s3(
+                s3_index,
+                s3_args.s3_epochs,
+                auth_config.as_ref(),
+                args.max_body_size(),
+            );
+        }
+        if let Some(resumable_uploads) = resumable_uploads {
+            daemon = daemon.with_resumable_uploads(resumable_uploads);
+        }
+        if let Some(publish_jobs) = publish_jobs {
+            daemon = daemon.with_publish_jobs(publish_jobs);
+        }
+        if let Some(admin_token) = admin_token {
+            daemon = daemon.with_blob_pinning(admin_token);
+        }
+        if let Some(remote_blocklist) = remote_blocklist {
+            daemon = daemon.with_remote_blocklist(blocklist.clone(), remote_blocklist);
+        }
+        if let Some(admin_token) = moderation_token {
+            daemon = daemon.with_moderation(blocklist, admin_token);
+        }
+        daemon.run().await?;
         Ok(())
     }
 
@@ -931,6 +1349,32 @@ impl ClientCommandRunner {
         BlobIdConversionOutput::from(blob_id_decimal).print_output(self.json)
     }
 
+    pub(crate) fn sign_url(
+        self,
+        url: &str,
+        key_file: &Path,
+        method: &str,
+        valid_for: Duration,
+        max_size: Option<u64>,
+        identity: Option<&str>,
+    ) -> Result<()> {
+        let key = load_url_signing_key(key_file)?;
+        let url = Url::parse(url).with_context(|| format!("invalid URL: {url}"))?;
+        let method = Method::from_bytes(method.to_uppercase().as_bytes())
+            .with_context(|| format!("invalid HTTP method: {method}"))?;
+        let expires_at = Utc::now()
+            + chrono::Duration::from_std(valid_for)
+                .context("the validity of the URL is too long")?;
+        let url = key
+            .sign_url(&method, url, expires_at, max_size, identity)
+            .context("failed to sign the URL")?;
+        SignedUrlOutput {
+            url: url.into(),
+            expires_at,
+        }
+        .print_output(self.json)
+    }
+
     pub(crate) async fn delete(
         self,
         target: BlobIdentifiers,
@@ -1038,6 +1482,35 @@ impl ClientCommandRunner {
         StakeOutput { staked_wal }.print_output(self.json)
     }
 
+    pub(crate) async fn unstake(self, staked_wal_ids: Vec<ObjectID>) -> Result<()> {
+        let client = get_contract_client(self.config?, self.wallet, self.gas_budget, &None).await?;
+        let mut unstaked = Vec::with_capacity(staked_wal_ids.len());
+        for staked_wal_id in staked_wal_ids {
+            let result = client.unstake(staked_wal_id).await?;
+            unstaked.push(UnstakedWal::new(staked_wal_id, result));
+        }
+        UnstakeOutput { unstaked }.print_output(self.json)
+    }
+
+    pub(crate) async fn stake_status(self, owner: Option<SuiAddress>) -> Result<()> {
+        let client = get_contract_client(self.config?, self.wallet, self.gas_budget, &None).await?;
+        let current_epoch = client.sui_client().current_epoch().await?;
+        let staked_wal = client
+            .owned_staked_wal_with_rewards(owner)
+            .await?
+            .into_iter()
+            .map(|(staked_wal, rewards)| StakedWalWithRewards {
+                staked_wal,
+                rewards,
+            })
+            .collect();
+        StakeStatusOutput {
+            current_epoch,
+            staked_wal,
+        }
+        .print_output(self.json)
+    }
+
     pub(crate) async fn generate_sui_wallet(
         self,
         path: &Path,
@@ -1054,6 +1527,7 @@ impl ClientCommandRunner {
         self,
         exchange_id: Option<ObjectID>,
         amount: u64,
+        min_balance: Option<u64>,
     ) -> Result<()> {
         let config = self.config?;
         let exchange_id = exchange_id
@@ -1069,14 +1543,39 @@ impl ClientCommandRunner {
                 Note that this command is only available on Testnet.",
             )?;
         let client = get_contract_client(config, self.wallet, self.gas_budget, &None).await?;
-        tracing::info!(
-            "exchanging {} for WAL using exchange object {exchange_id}",
-            HumanReadableMist::from(amount)
-        );
-        client.exchange_sui_for_wal(exchange_id, amount).await?;
+        let amount = if let Some(min_balance) = min_balance {
+            tracing::info!(
+                "exchanging SUI for WAL up to a balance of {} using exchange object {exchange_id}",
+                HumanReadableFrost::from(min_balance)
+            );
+            client
+                .sui_client()
+                .exchange_sui_for_missing_wal(exchange_id, min_balance)
+                .await?
+        } else {
+            tracing::info!(
+                "exchanging {} for WAL using exchange object {exchange_id}",
+                HumanReadableMist::from(amount)
+            );
+            client.exchange_sui_for_wal(exchange_id, amount).await?;
+            amount
+        };
         ExchangeOutput { amount_sui: amount }.print_output(self.json)
     }
 
+    pub(crate) async fn balance(self) -> Result<()> {
+        let sui_client = self
+            .config?
+            .new_contract_client(self.wallet?, self.gas_budget)
+            .await?;
+        BalanceOutput {
+            address: sui_client.address(),
+            sui_balance: sui_client.balance(CoinType::Sui).await?,
+            wal_balance: sui_client.balance(CoinType::Wal).await?,
+        }
+        .print_output(self.json)
+    }
+
     pub(crate) async fn burn_blobs(
         self,
         burn_selection: BurnSelection,
@@ -1179,6 +1678,171 @@ impl ClientCommandRunner {
         }
         Ok(())
     }
+
+    pub(crate) fn encoding_test_vectors(self, command: TestVectorCommands) -> Result<()> {
+        match command {
+            TestVectorCommands::Generate {
+                n_shards,
+                seeds,
+                blob_lengths,
+                encoding_type,
+                out,
+            } => {
+                let encoding_type = encoding_type.unwrap_or(DEFAULT_ENCODING);
+                let test_vectors = seeds
+                    .iter()
+                    .cartesian_product(&blob_lengths)
+                    .map(|(&seed, &blob_length)| {
+                        EncodingTestVector::generate(n_shards, encoding_type, seed, blob_length)
+                            .with_context(|| format!("cannot encode a blob of {blob_length} bytes"))
+                    })
+                    .collect::<Result<Vec<_>>>()?;
+
+                if let Some(out) = out {
+                    let file = std::fs::File::create(&out)
+                        .with_context(|| format!("cannot create the file {}", out.display()))?;
+                    serde_json::to_writer_pretty(std::io::BufWriter::new(file), &test_vectors)?;
+                    if !self.json {
+                        println!(
+                            "{} Wrote {} test vectors to {}",
+                            success(),
+                            test_vectors.len(),
+                            out.display()
+                        );
+                    }
+                } else {
+                    serde_json::to_writer_pretty(std::io::stdout(), &test_vectors)?;
+                    println!();
+                }
+                Ok(())
+            }
+            TestVectorCommands::Verify { file } => {
+                let test_vectors: Vec<EncodingTestVector> = serde_json::from_slice(
+                    &std::fs::read(&file)
+                        .with_context(|| format!("cannot read the file {}", file.display()))?,
+                )?;
+                let failures = test_vectors
+                    .iter()
+                    .enumerate()
+                    .filter_map(|(index, test_vector)| {
+                        test_vector.verify().err().map(|error| TestVectorFailure {
+                            index,
+                            seed: test_vector.seed,
+                            blob_length: test_vector.blob.len(),
+                            error: error.to_string(),
+                        })
+                    })
+                    .collect::<Vec<_>>();
+                let output = TestVectorVerificationOutput {
+                    n_verified: test_vectors.len() - failures.len(),
+                    failures,
+                };
+                output.print_output(self.json)?;
+                ensure!(
+                    output.failures.is_empty(),
+                    "{} test vectors failed verification",
+                    output.failures.len()
+                );
+                Ok(())
+            }
+        }
+    }
+
+    pub(crate) fn bench(self, command: BenchCommands) -> Result<()> {
+        match command {
+            BenchCommands::Encode {
+                n_shards,
+                blob_sizes,
+                encoding_type,
+                iterations,
+            } => {
+                let encoding_type = encoding_type.unwrap_or(DEFAULT_ENCODING);
+                let encoding_config = EncodingConfig::new(n_shards);
+                let config = encoding_config.get_for_type(encoding_type);
+                let n_source_slivers = config.n_primary_source_symbols().get().into();
+
+                let mut results = Vec::with_capacity(blob_sizes.len());
+                for blob_size in blob_sizes {
+                    let spinner = styled_spinner();
+                    spinner.set_message(format!(
+                        "benchmarking a blob of {}",
+                        HumanReadableBytes(blob_size)
+                    ));
+                    let mut blob = vec![0; usize::try_from(blob_size)?];
+                    rand::thread_rng().fill_bytes(&mut blob);
+
+                    let (encode, (sliver_pairs, metadata)) = mean_duration(
+                        iterations,
+                        || (),
+                        |()| Ok(config.encode_with_metadata(&blob)?),
+                    )
+                    .with_context(|| format!("cannot encode a blob of {blob_size} bytes"))?;
+                    let (compute_metadata, _) =
+                        mean_duration(iterations, || (), |()| Ok(config.compute_metadata(&blob)?))?;
+
+                    // Decoding from the first primary slivers, which only contain source symbols,
+                    // is the fastest way to reconstruct the blob.
+                    let source_slivers: Vec<_> = sliver_pairs
+                        .into_iter()
+                        .map(|pair| pair.primary)
+                        .take(n_source_slivers)
+                        .collect();
+                    let (decode, decoded_blob) = mean_duration(
+                        iterations,
+                        || source_slivers.clone(),
+                        |slivers| {
+                            let mut decoder = config.get_blob_decoder::<Primary>(blob_size)?;
+                            let (decoded_blob, _) = decoder
+                                .decode_and_verify(metadata.blob_id(), slivers)?
+                                .context("not enough slivers to decode the blob")?;
+                            Ok(decoded_blob)
+                        },
+                    )?;
+                    ensure!(
+                        decoded_blob == blob,
+                        "the decoded blob differs from the original"
+                    );
+
+                    spinner.finish_and_clear();
+                    results.push(EncodingBenchmarkResult {
+                        blob_size,
+                        encode,
+                        compute_metadata,
+                        decode,
+                    });
+                }
+
+                EncodingBenchmarkOutput {
+                    n_shards,
+                    encoding_type,
+                    iterations: iterations.get(),
+                    results,
+                }
+                .print_output(self.json)
+            }
+        }
+    }
+}
+
+/// Runs `operation` `iterations` times on inputs created by `setup`, and returns the mean duration
+/// of the operation, excluding the setup, together with the output of the last run.
+fn mean_duration<I, T>(
+    iterations: NonZeroU32,
+    mut setup: impl FnMut() -> I,
+    mut operation: impl FnMut(I) -> Result<T>,
+) -> Result<(Duration, T)> {
+    let mut total = Duration::ZERO;
+    let mut output = None;
+    for _ in 0..iterations.get() {
+        let input = setup();
+        let start = Instant::now();
+        output = Some(operation(input)?);
+        total += start.elapsed();
+    }
+    Ok((
+        total / iterations.get(),
+        output.expect("there is at least one iteration"),
+    ))
 }
 
 async fn delete_blob(
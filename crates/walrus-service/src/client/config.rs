@@ -1,13 +1,17 @@
 // Copyright (c) Walrus Foundation
 // SPDX-License-Identifier: Apache-2.0
 
-use std::fmt;
+use std::{collections::HashMap, fmt, time::Duration};
 
+use axum::http::{HeaderName, HeaderValue};
 use fastcrypto::encoding::{Encoding as _, Hex};
 use jsonwebtoken::{Algorithm, DecodingKey};
+use serde::Deserialize;
+use serde_with::{serde_as, DurationSeconds, TryFromInto};
+use walrus_core::EpochCount;
 use walrus_sdk::error::JwtDecodeError;
 
-use super::daemon::CacheConfig;
+use super::daemon::{auth::ApiKeys, CacheConfig};
 
 /// Configuration for the JWT authentication on the publisher.
 #[derive(Default, Clone)]
@@ -28,6 +32,10 @@ pub struct AuthConfig {
     pub(crate) verify_upload: bool,
     /// The configuration for the replay suppression cache.
     pub(crate) replay_suppression_config: CacheConfig,
+    /// The API keys accepted by the publisher, in addition to JWTs.
+    pub(crate) api_keys: ApiKeys,
+    /// If set, only requests with one of the API keys are accepted, and JWTs are rejected.
+    pub(crate) api_keys_only: bool,
 }
 
 impl fmt::Debug for AuthConfig {
@@ -36,6 +44,8 @@ impl fmt::Debug for AuthConfig {
             .field("algorithm", &self.algorithm)
             .field("expiring_sec", &self.expiring_sec)
             .field("verify_upload", &self.verify_upload)
+            .field("api_keys", &self.api_keys.len())
+            .field("api_keys_only", &self.api_keys_only)
             .finish()
     }
 }
@@ -80,6 +90,173 @@ impl AuthConfig {
     }
 }
 
+/// Configuration of the API keys with which clients authenticate to the publisher.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub(crate) struct ApiKeysConfig {
+    /// The API keys and the permissions of their holders.
+    pub keys: Vec<ApiKeyConfig>,
+}
+
+/// An API key and the permissions of its holder.
+#[derive(Clone, Deserialize)]
+pub(crate) struct ApiKeyConfig {
+    /// The identity of the holder of the key, to which requests are attributed in the logs.
+    pub identity: String,
+    /// The API key, which the client presents as bearer token.
+    pub key: String,
+    /// The uploads the holder of the key is allowed to make.
+    #[serde(flatten)]
+    pub permissions: UploadPermissions,
+}
+
+impl fmt::Debug for ApiKeyConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ApiKeyConfig")
+            .field("identity", &self.identity)
+            .field("permissions", &self.permissions)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Limits on the blobs an authenticated client can store through the publisher.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+pub(crate) struct UploadPermissions {
+    /// The maximum number of epochs for which a blob can be stored.
+    #[serde(default)]
+    pub max_epochs: Option<EpochCount>,
+    /// The maximum size of a blob, in bytes.
+    #[serde(default)]
+    pub max_size: Option<u64>,
+    /// The maximum number of bytes that can be stored per day (UTC).
+    #[serde(default)]
+    pub daily_quota: Option<u64>,
+    /// The maximum number of uploads that can be in progress at the same time.
+    #[serde(default)]
+    pub max_concurrent_uploads: Option<usize>,
+}
+
+/// Configuration of the headers the aggregator adds to responses with blob data.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct ResponseHeadersConfig {
+    /// Whether to detect the content type of a blob from its first bytes, if no content type is
+    /// requested.
+    pub(crate) sniff_content_type: bool,
+    /// The headers for the routes serving blob data, keyed by the route, such as
+    /// `/v1/blobs/{blob_id}`.
+    pub(crate) routes: HashMap<String, RouteResponseHeaders>,
+}
+
+impl Default for ResponseHeadersConfig {
+    fn default() -> Self {
+        Self {
+            sniff_content_type: true,
+            routes: HashMap::new(),
+        }
+    }
+}
+
+impl ResponseHeadersConfig {
+    /// Returns the headers configured for the route.
+    pub(crate) fn route(&self, route: &str) -> Option<&RouteResponseHeaders> {
+        self.routes.get(route)
+    }
+}
+
+/// The headers added to responses with blob data on a route.
+#[serde_as]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub(crate) struct RouteResponseHeaders {
+    /// The `Content-Type` of blobs whose content type is neither requested nor detected.
+    #[serde_as(as = "Option<TryFromInto<String>>")]
+    pub default_content_type: Option<HeaderValue>,
+    /// The `Content-Disposition` header, for example `inline` or `attachment`.
+    #[serde_as(as = "Option<TryFromInto<String>>")]
+    pub content_disposition: Option<HeaderValue>,
+    /// The `Cache-Control` header, which replaces the default caching policy of the aggregator.
+    #[serde_as(as = "Option<TryFromInto<String>>")]
+    pub cache_control: Option<HeaderValue>,
+}
+
+/// Configuration of the rate limits of the daemon.
+///
+/// Clients are identified by their API key, if they present one of the API keys of the publisher,
+/// and otherwise by their IP address.
+#[serde_as]
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct RateLimitConfig {
+    /// The rate limits per client of the routes, keyed by the route, such as
+    /// `/v1/blobs/{blob_id}`.
+    ///
+    /// Routes without rate limits are not limited.
+    pub(crate) routes: HashMap<String, RouteRateLimits>,
+    /// The header in which a reverse proxy in front of the daemon passes the IP address of the
+    /// client, such as `X-Forwarded-For`.
+    ///
+    /// The last address in the header, which is the one added by the proxy, is used. If not set,
+    /// clients are identified by the address of the peer of the connection.
+    #[serde_as(as = "Option<TryFromInto<String>>")]
+    pub(crate) client_ip_header: Option<HeaderName>,
+}
+
+impl RateLimitConfig {
+    /// Returns `true` if no route is rate limited.
+    pub(crate) fn is_empty(&self) -> bool {
+        self.routes.is_empty()
+    }
+}
+
+/// The rate limits per client of a route.
+///
+/// The limits are enforced with token buckets, which allow a client that made no requests for a
+/// while to make a burst of requests.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[serde(default)]
+pub(crate) struct RouteRateLimits {
+    /// The number of requests per second.
+    pub requests_per_second: Option<f64>,
+    /// The number of requests that can be made at once; defaults to the number of requests per
+    /// second, but at least 1.
+    pub request_burst: Option<u32>,
+    /// The number of bytes per second, counting the bodies of both requests and responses.
+    pub bytes_per_second: Option<u64>,
+    /// The number of bytes that can be transferred at once; defaults to the number of bytes per
+    /// second.
+    pub byte_burst: Option<u64>,
+}
+
+/// Configuration of the CORS headers of the daemon, which allow web pages on other origins to read
+/// and store blobs directly from the browser.
+///
+/// Each of the lists can contain `*` to allow any value.
+#[serde_as]
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct CorsConfig {
+    /// The origins allowed to make requests, such as `https://example.com`.
+    pub(crate) allowed_origins: Vec<String>,
+    /// The methods allowed in requests.
+    pub(crate) allowed_methods: Vec<String>,
+    /// The headers allowed in requests.
+    pub(crate) allowed_headers: Vec<String>,
+    /// The time in seconds for which browsers can cache the response to a preflight request.
+    #[serde_as(as = "DurationSeconds")]
+    pub(crate) max_age: Duration,
+}
+
+impl Default for CorsConfig {
+    fn default() -> Self {
+        Self {
+            allowed_origins: vec!["*".to_owned()],
+            allowed_methods: vec!["*".to_owned()],
+            allowed_headers: vec!["*".to_owned()],
+            max_age: Duration::from_secs(86400),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use walrus_test_utils::{param_test, Result as TestResult};
@@ -98,4 +275,72 @@ mod tests {
         assert_eq!(bytes, output);
         Ok(())
     }
+
+    #[test]
+    fn parses_response_headers_config() -> TestResult {
+        let config: ResponseHeadersConfig = serde_yaml::from_str(
+            r#"
+            sniff_content_type: false
+            routes:
+              /v1/blobs/{blob_id}:
+                default_content_type: application/octet-stream
+                cache_control: no-store
+            "#,
+        )?;
+
+        assert!(!config.sniff_content_type);
+        let route = config
+            .route("/v1/blobs/{blob_id}")
+            .expect("route is configured");
+        assert_eq!(
+            route.default_content_type,
+            Some(HeaderValue::from_static("application/octet-stream"))
+        );
+        assert_eq!(
+            route.cache_control,
+            Some(HeaderValue::from_static("no-store"))
+        );
+        assert_eq!(route.content_disposition, None);
+        Ok(())
+    }
+
+    #[test]
+    fn rejects_invalid_header_values() {
+        let result: Result<ResponseHeadersConfig, _> = serde_yaml::from_str(
+            r#"
+            routes:
+              /v1/blobs/{blob_id}:
+                content_disposition: "inline\n"
+            "#,
+        );
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn parses_rate_limit_config() -> TestResult {
+        let config: RateLimitConfig = serde_yaml::from_str(
+            r#"
+            client_ip_header: X-Forwarded-For
+            routes:
+              /v1/blobs:
+                requests_per_second: 0.5
+                request_burst: 5
+                bytes_per_second: 1048576
+            "#,
+        )?;
+        assert_eq!(
+            config.client_ip_header,
+            Some(HeaderName::from_static("x-forwarded-for"))
+        );
+        assert_eq!(
+            config.routes["/v1/blobs"],
+            RouteRateLimits {
+                requests_per_second: Some(0.5),
+                request_burst: Some(5),
+                bytes_per_second: Some(1 << 20),
+                byte_burst: None,
+            }
+        );
+        Ok(())
+    }
 }
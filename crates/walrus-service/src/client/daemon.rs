@@ -13,13 +13,17 @@ use axum::{
     response::{IntoResponse, Response},
     routing::{get, put},
     BoxError,
+    Extension,
     Router,
 };
 use axum_extra::{
     headers::{authorization::Bearer, Authorization},
     TypedHeader,
 };
+use cache::CacheError;
+use chrono::{DateTime, Utc};
 use openapi::{AggregatorApiDoc, DaemonApiDoc, PublisherApiDoc};
+use progress::{UploadProgressRegistry, BLOB_PROGRESS_ENDPOINT};
 use reqwest::StatusCode;
 pub use routes::PublisherQuery;
 use routes::{
@@ -29,19 +33,27 @@ use routes::{
     BLOB_PUT_ENDPOINT,
     STATUS_ENDPOINT,
 };
+use signed_url::{SignedUrlError, SignedUrlGrant};
 use sui_types::base_types::ObjectID;
+use tokio::sync::Semaphore;
 use tower::{
     buffer::BufferLayer,
-    limit::ConcurrencyLimitLayer,
+    limit::GlobalConcurrencyLimitLayer,
     load_shed::{error::Overloaded, LoadShedLayer},
     ServiceBuilder,
 };
-use tower_http::trace::TraceLayer;
+use tower_http::{cors::CorsLayer, trace::TraceLayer};
+use tracing::Instrument as _;
 use utoipa::OpenApi;
 use utoipa_redoc::{Redoc, Servable};
 use walrus_core::{encoding::Primary, BlobId, EncodingType, EpochCount, DEFAULT_ENCODING};
 use walrus_sdk::{
-    client::{responses::BlobStoreResult, Client},
+    blocklist::Blocklist,
+    client::{
+        byte_range::{BlobRange, ByteRange},
+        responses::{BlobAvailability, BlobStoreResult},
+        Client,
+    },
     error::ClientResult,
     store_when::StoreWhen,
 };
@@ -54,17 +66,62 @@ use walrus_utils::metrics::Registry;
 use crate::{
     client::{
         cli::{AggregatorArgs, PublisherArgs},
-        config::AuthConfig,
-        daemon::auth::verify_jwt_claim,
+        config::{AuthConfig, CorsConfig, RateLimitConfig, ResponseHeadersConfig},
+        daemon::auth::{verify_jwt_claim, ApiKeyHolder, PublisherAuthError},
+    },
+    common::{
+        api::RestApiError as _,
+        telemetry::{metrics_middleware, MakeHttpSpan, MetricsMiddlewareState},
     },
-    common::telemetry::{metrics_middleware, MakeHttpSpan, MetricsMiddlewareState},
 };
 
 pub mod auth;
+mod blob_cache;
+pub(crate) use blob_cache::{BlobCache, BlobCacheConfig, DiskCacheConfig};
 pub(crate) mod cache;
 pub(crate) use cache::{CacheConfig, CacheHandle};
+mod compression;
+pub(crate) use compression::CompressionConfig;
+mod content_type;
+mod jobs;
+pub(crate) use jobs::{PublishJobs, PublishJobsConfig};
+mod metrics;
+pub(crate) use metrics::MeteredClient;
+mod moderation;
+pub(crate) use moderation::RemoteBlocklistConfig;
 mod openapi;
+mod pin;
+pub(crate) use pin::AdminTokenDigest;
+mod progress;
+pub(crate) use progress::UploadProgressHandle;
+mod rate_limit;
 mod routes;
+mod s3;
+pub(crate) use s3::S3Index;
+mod signed_url;
+pub(crate) use signed_url::{SignedUrls, UrlSigningKey};
+mod site;
+mod sources;
+pub(crate) use sources::{MultiSourceClient, ReadSourcesConfig};
+#[cfg(test)]
+mod test_utils;
+mod tls;
+pub(crate) use tls::{AcmeConfig, TlsConfig};
+mod tus;
+pub(crate) use tus::{ResumableUploads, ResumableUploadsConfig};
+
+/// The state of the authentication of store requests: the configuration and the cache of used
+/// JWTs.
+type PublisherAuthState = (Arc<AuthConfig>, Arc<CacheHandle<String>>);
+
+/// The limits that the publisher applies to all requests that store blobs.
+#[derive(Debug, Clone)]
+struct StoreLimits {
+    /// The maximum size of a blob, in bytes.
+    max_blob_size: usize,
+    /// The permits for storing blobs concurrently, shared by all endpoints that store blobs.
+    permits: Arc<Semaphore>,
+}
 
 pub trait WalrusReadClient {
     fn read_blob(
@@ -72,10 +129,32 @@ pub trait WalrusReadClient {
         blob_id: &BlobId,
     ) -> impl std::future::Future<Output = ClientResult<Vec<u8>>> + Send;
 
+    /// Reads the bytes of the blob in the given range.
+    fn read_blob_range(
+        &self,
+        blob_id: &BlobId,
+        range: ByteRange,
+    ) -> impl std::future::Future<Output = ClientResult<BlobRange>> + Send;
+
     fn get_blob_by_object_id(
         &self,
         blob_object_id: &ObjectID,
     ) -> impl std::future::Future<Output = ClientResult<BlobWithAttribute>> + Send;
+
+    /// Returns an error if the blob ID is blocked.
+    fn check_blob_id(&self, blob_id: &BlobId) -> ClientResult<()>;
+
+    /// Returns the estimated time at which the blob expires, if it is stored permanently.
+    fn estimated_blob_expiry(
+        &self,
+        blob_id: &BlobId,
+    ) -> impl std::future::Future<Output = ClientResult<Option<DateTime<Utc>>>> + Send;
+
+    /// Returns the size and availability of the blob, without reconstructing it.
+    fn blob_availability(
+        &self,
+        blob_id: &BlobId,
+    ) -> impl std::future::Future<Output = ClientResult<BlobAvailability>> + Send;
 }
 
 /// Trait representing a client that can write blobs to Walrus.
@@ -100,12 +179,28 @@ impl<T: ReadClient> WalrusReadClient for Client<T> {
         self.read_blob_retry_committees::<Primary>(blob_id).await
     }
 
+    async fn read_blob_range(&self, blob_id: &BlobId, range: ByteRange) -> ClientResult<BlobRange> {
+        self.read_blob_range_retry_committees(blob_id, range).await
+    }
+
     async fn get_blob_by_object_id(
         &self,
         blob_object_id: &ObjectID,
     ) -> ClientResult<BlobWithAttribute> {
         self.get_blob_by_object_id(blob_object_id).await
     }
+
+    fn check_blob_id(&self, blob_id: &BlobId) -> ClientResult<()> {
+        self.check_blob_id(blob_id)
+    }
+
+    async fn estimated_blob_expiry(&self, blob_id: &BlobId) -> ClientResult<Option<DateTime<Utc>>> {
+        self.estimated_blob_expiry(blob_id).await
+    }
+
+    async fn blob_availability(&self, blob_id: &BlobId) -> ClientResult<BlobAvailability> {
+        self.blob_availability_retry_committees(blob_id).await
+    }
 }
 
 impl WalrusWriteClient for Client<SuiContractClient> {
@@ -154,6 +249,13 @@ pub struct ClientDaemon<T> {
     metrics: MetricsMiddlewareState,
     router: Router<Arc<T>>,
     allowed_headers: Arc<HashSet<String>>,
+    cors_layer: CorsLayer,
+    publisher_auth: Option<PublisherAuthState>,
+    store_limits: Option<StoreLimits>,
+    tls_config: Option<TlsConfig>,
+    compression: Option<CompressionConfig>,
+    rate_limits: Option<RateLimitConfig>,
+    signed_urls: Option<Arc<SignedUrls>>,
 }
 
 impl<T: WalrusReadClient + Send + Sync + 'static> ClientDaemon<T> {
@@ -163,9 +265,14 @@ impl<T: WalrusReadClient + Send + Sync + 'static> ClientDaemon<T> {
         network_address: SocketAddr,
         registry: &Registry,
         allowed_headers: Vec<String>,
+        response_headers: ResponseHeadersConfig,
+        serve_sites: bool,
     ) -> Self {
-        Self::new::<AggregatorApiDoc>(client, network_address, registry)
-            .with_aggregator(allowed_headers)
+        Self::new::<AggregatorApiDoc>(client, network_address, registry).with_aggregator(
+            allowed_headers,
+            response_headers,
+            serve_sites,
+        )
     }
 
     /// Creates a new [`ClientDaemon`], which serves requests at the provided `network_address` and
@@ -182,29 +289,157 @@ impl<T: WalrusReadClient + Send + Sync + 'static> ClientDaemon<T> {
                 .merge(Redoc::with_url(routes::API_DOCS, A::openapi()))
                 .route(STATUS_ENDPOINT, get(routes::status)),
             allowed_headers: Arc::new(HashSet::new()),
+            cors_layer: daemon_cors_layer(&CorsConfig::default())
+                .expect("the default CORS configuration is valid"),
+            publisher_auth: None,
+            store_limits: None,
+            tls_config: None,
+            compression: None,
+            rate_limits: None,
+            signed_urls: None,
         }
     }
 
-    /// Specifies that the daemon should expose the aggregator interface (read blobs).
-    fn with_aggregator(mut self, allowed_headers: Vec<String>) -> Self {
+    /// Configures the CORS headers with which the daemon responds to requests from browsers.
+    ///
+    /// By default, requests from any origin are allowed.
+    pub fn with_cors(mut self, cors_config: &CorsConfig) -> anyhow::Result<Self> {
+        self.cors_layer = daemon_cors_layer(cors_config)?;
+        tracing::info!(?cors_config, "CORS config applied");
+        Ok(self)
+    }
+
+    /// Configures the daemon to serve requests over HTTPS with the certificate from `tls_config`.
+    ///
+    /// By default, requests are served over plain HTTP.
+    pub(crate) fn with_tls(mut self, tls_config: Option<TlsConfig>) -> Self {
+        tracing::info!(?tls_config, "TLS config applied");
+        self.tls_config = tls_config;
+        self
+    }
+
+    /// Configures the daemon to compress its responses if the client accepts a compressed encoding.
+    ///
+    /// By default, responses are not compressed.
+    pub(crate) fn with_compression(mut self, compression: Option<CompressionConfig>) -> Self {
+        tracing::info!(?compression, "response compression config applied");
+        self.compression = compression;
+        self
+    }
+
+    /// Configures the rate limits per client of the routes of the daemon.
+    ///
+    /// Clients that present one of the API keys of the publisher are identified by the key, so the
+    /// limits apply to the publisher configured when the daemon is run. By default, requests are
+    /// not rate limited.
+    pub(crate) fn with_rate_limits(mut self, rate_limits: RateLimitConfig) -> Self {
+        tracing::info!(?rate_limits, "rate limits applied");
+        self.rate_limits = (!rate_limits.is_empty()).then_some(rate_limits);
+        self
+    }
+
+    /// Configures the daemon to accept URLs signed with the URL signing key of `signed_urls`.
+    ///
+    /// A valid signed URL authorizes a single store request in place of an API key or JWT, subject
+    /// to the limits of the API key on whose behalf it is signed, see [`auth_layer`]. If
+    /// `signed_urls` requires it, blobs and websites can only be read through signed URLs. By
+    /// default, signed URLs are not accepted.
+    pub(crate) fn with_signed_urls(mut self, signed_urls: Option<SignedUrls>) -> Self {
+        if let Some(signed_urls) = &signed_urls {
+            tracing::info!(
+                require_for_reads = signed_urls.require_for_reads,
+                "accepting signed URLs"
+            );
+        }
+        self.signed_urls = signed_urls.map(Arc::new);
+        self
+    }
+
+    /// Specifies that the daemon should periodically fetch the remote list of blocked blob IDs of
+    /// the `blocklist`, which must be the blocklist of the client of the daemon.
+    pub(crate) fn with_remote_blocklist(
+        self,
+        blocklist: Blocklist,
+        config: RemoteBlocklistConfig,
+    ) -> Self {
+        tracing::info!(?config, "refreshing the remote blocklist");
+        tokio::spawn(moderation::refresh_remote_blocklist(blocklist, config));
+        self
+    }
+
+    /// Specifies that the daemon should expose the endpoints to moderate the blobs on the
+    /// `blocklist`, which require the administration token with the given digest.
+    ///
+    /// The `blocklist` must be the blocklist of the client of the daemon.
+    pub(crate) fn with_moderation(
+        mut self,
+        blocklist: Blocklist,
+        admin_token: AdminTokenDigest,
+    ) -> Self {
+        tracing::info!("exposing the endpoints to moderate blobs");
+        let admin_auth = middleware::from_fn_with_state(admin_token, pin::admin_auth_layer);
+        self.router = self
+            .router
+            .route(
+                moderation::BLOCKED_BLOBS_ENDPOINT,
+                get(moderation::list_blocked_blobs)
+                    .with_state(blocklist.clone())
+                    .route_layer(admin_auth.clone()),
+            )
+            .route(
+                moderation::BLOCKED_BLOB_ENDPOINT,
+                put(moderation::block_blob)
+                    .delete(moderation::unblock_blob)
+                    .with_state(blocklist)
+                    .route_layer(admin_auth),
+            );
+        self
+    }
+
+    /// Specifies that the daemon should expose the aggregator interface (read blobs and, if
+    /// `serve_sites` is set, serve websites from manifest blobs).
+    fn with_aggregator(
+        mut self,
+        allowed_headers: Vec<String>,
+        response_headers: ResponseHeadersConfig,
+        serve_sites: bool,
+    ) -> Self {
         self.with_allowed_headers(allowed_headers);
         tracing::info!("Aggregator allowed headers: {:?}", self.allowed_headers);
+        let response_headers = Extension(Arc::new(response_headers));
         self.router = self
             .router
-            .route(BLOB_GET_ENDPOINT, get(routes::get_blob))
+            .route(
+                BLOB_GET_ENDPOINT,
+                get(routes::get_blob)
+                    .head(routes::head_blob)
+                    .layer(response_headers.clone()),
+            )
             .route(
                 BLOB_OBJECT_GET_ENDPOINT,
                 get(routes::get_blob_by_object_id)
-                    .with_state((self.client.clone(), self.allowed_headers.clone())),
+                    .with_state((self.client.clone(), self.allowed_headers.clone()))
+                    .layer(response_headers.clone()),
             );
+        if serve_sites {
+            tracing::info!("the aggregator serves websites from manifest blobs");
+            self.router = self
+                .router
+                .route(site::SITE_ROOT_ENDPOINT, get(site::redirect_to_site_index))
+                .route(
+                    site::SITE_INDEX_ENDPOINT,
+                    get(site::get_site_index).layer(response_headers.clone()),
+                )
+                .route(
+                    site::SITE_FILE_ENDPOINT,
+                    get(site::get_site_file).layer(response_headers),
+                );
+        }
         self
     }
 
     /// Runs the daemon.
     pub async fn run(self) -> Result<(), std::io::Error> {
-        let listener = tokio::net::TcpListener::bind(self.network_address).await?;
-        tracing::info!(address = %self.network_address, "the client daemon is starting");
-
         let request_layers = ServiceBuilder::new()
             .layer(middleware::from_fn_with_state(
                 self.metrics.clone(),
@@ -215,11 +450,43 @@ impl<T: WalrusReadClient + Send + Sync + 'static> ClientDaemon<T> {
                     .make_span_with(MakeHttpSpan::new())
                     .on_response(MakeHttpSpan::new()),
             )
-            .layer(daemon_cors_layer());
+            .layer(self.cors_layer);
+        let mut app = self.router.with_state(self.client);
+        if let Some(signed_urls) = self.signed_urls {
+            app = app.layer(middleware::from_fn_with_state(
+                signed_urls,
+                signed_url::signed_url_layer,
+            ));
+        }
+        if let Some(rate_limits) = self.rate_limits {
+            let api_keys = self
+                .publisher_auth
+                .map(|(auth_config, _)| auth_config.api_keys.clone())
+                .unwrap_or_default();
+            let rate_limiter = Arc::new(rate_limit::RateLimiter::new(rate_limits, api_keys));
+            tokio::spawn(rate_limit::RateLimiter::prune_idle_clients(Arc::downgrade(
+                &rate_limiter,
+            )));
+            app = app.layer(middleware::from_fn_with_state(
+                rate_limiter,
+                rate_limit::rate_limit_layer,
+            ));
+        }
+        if let Some(compression) = self.compression {
+            app = compression::compress_responses(app, compression);
+        }
+        let app = app.layer(request_layers);
 
+        if let Some(tls_config) = self.tls_config {
+            tracing::info!(address = %self.network_address, "the client daemon is starting with TLS");
+            return tls::serve(self.network_address, tls_config, app).await;
+        }
+
+        let listener = tokio::net::TcpListener::bind(self.network_address).await?;
+        tracing::info!(address = %self.network_address, "the client daemon is starting");
         axum::serve(
             listener,
-            self.router.with_state(self.client).layer(request_layers),
+            app.into_make_service_with_connect_info::<SocketAddr>(),
         )
         .with_graceful_shutdown(async {
             let _ = tokio::signal::ctrl_c().await;
@@ -228,6 +495,33 @@ impl<T: WalrusReadClient + Send + Sync + 'static> ClientDaemon<T> {
     }
 }
 
+impl<T: WalrusReadClient + Send + Sync + 'static> ClientDaemon<BlobCache<T>> {
+    /// Specifies that the daemon should expose the endpoints to pin blobs in its cache, which
+    /// require the administration token with the given digest.
+    ///
+    /// The pinned blobs are refreshed in the background while the daemon runs.
+    pub(crate) fn with_blob_pinning(mut self, admin_token: AdminTokenDigest) -> Self {
+        tracing::info!("exposing the endpoints to pin blobs in the cache");
+        tokio::spawn(BlobCache::refresh_pinned_blobs(Arc::downgrade(
+            &self.client,
+        )));
+        let admin_auth = middleware::from_fn_with_state(admin_token, pin::admin_auth_layer);
+        self.router = self
+            .router
+            .route(
+                pin::PINNED_BLOBS_ENDPOINT,
+                get(pin::list_pinned_blobs).route_layer(admin_auth.clone()),
+            )
+            .route(
+                pin::PINNED_BLOB_ENDPOINT,
+                put(pin::pin_blob)
+                    .delete(pin::unpin_blob)
+                    .route_layer(admin_auth),
+            );
+        self
+    }
+}
+
 impl<T: WalrusWriteClient + Send + Sync + 'static> ClientDaemon<T> {
     /// Constructs a new [`ClientDaemon`] with publisher functionality.
     pub fn new_publisher(
@@ -254,17 +548,83 @@ impl<T: WalrusWriteClient + Send + Sync + 'static> ClientDaemon<T> {
         registry: &Registry,
         publisher_args: &PublisherArgs,
         aggregator_args: &AggregatorArgs,
+        response_headers: ResponseHeadersConfig,
     ) -> Self {
         Self::new::<DaemonApiDoc>(client, publisher_args.daemon_args.bind_address, registry)
-            .with_aggregator(aggregator_args.allowed_headers.clone())
+            .with_aggregator(
+                aggregator_args.allowed_headers.clone(),
+                response_headers,
+                aggregator_args.serve_sites,
+            )
             .with_publisher(
                 auth_config,
-                publisher_args.max_body_size_kib,
+                publisher_args.max_body_size(),
                 publisher_args.max_request_buffer_size,
                 publisher_args.max_concurrent_requests,
             )
     }
 
+    /// Specifies that the daemon should expose the S3-compatible API below [`s3::S3_ENDPOINT`],
+    /// with the objects in `index` and new objects stored for `epochs` epochs.
+    ///
+    /// Requests modifying the index need to be signed with one of the API keys of the publisher;
+    /// if the publisher is not configured with API keys, such requests are rejected.
+    pub(crate) fn with_s3(
+        mut self,
+        index: S3Index,
+        epochs: EpochCount,
+        auth_config: Option<&AuthConfig>,
+        max_body_limit: usize,
+    ) -> Self {
+        tracing::info!(%epochs, "exposing the S3-compatible API");
+        let state = s3::S3State::new(
+            self.client.clone(),
+            index,
+            epochs,
+            auth_config.map(|auth_config| auth_config.api_keys.clone()),
+        );
+        self.router = self
+            .router
+            .nest(s3::S3_ENDPOINT, s3::router(state, max_body_limit));
+        self
+    }
+
+    /// Specifies that the daemon should accept resumable uploads with the tus protocol at
+    /// [`tus::UPLOADS_ENDPOINT`], and store their blobs once they are complete.
+    ///
+    /// Must be called after the publisher interface has been configured, so that uploads are
+    /// authenticated in the same way as store requests and are subject to the same limits.
+    pub(crate) fn with_resumable_uploads(mut self, uploads: ResumableUploads) -> Self {
+        tracing::info!("accepting resumable uploads");
+        let store_limits = self
+            .store_limits
+            .clone()
+            .expect("the publisher interface must be configured before resumable uploads");
+        self.router = self.router.merge(tus::router(
+            self.client.clone(),
+            uploads,
+            self.publisher_auth.clone(),
+            store_limits,
+        ));
+        self
+    }
+
+    /// Specifies that the daemon should store the blobs of store requests that prefer an
+    /// asynchronous response with publish jobs, whose status is exposed at [`jobs::JOB_ENDPOINT`].
+    ///
+    /// Must be called after the publisher interface has been configured. The jobs are run in the
+    /// background while the daemon runs.
+    pub(crate) fn with_publish_jobs(mut self, publish_jobs: PublishJobs) -> Self {
+        tracing::info!("accepting asynchronous store requests");
+        let publish_jobs = Arc::new(publish_jobs);
+        tokio::spawn(publish_jobs.clone().run(self.client.clone()));
+        self.router = self.router.layer(Extension(publish_jobs.clone())).route(
+            jobs::JOB_ENDPOINT,
+            get(jobs::get_job_status).with_state(publish_jobs),
+        );
+        self
+    }
+
     /// Specifies that the daemon should expose the publisher interface (store blobs).
     fn with_publisher(
         mut self,
@@ -280,33 +640,54 @@ impl<T: WalrusWriteClient + Send + Sync + 'static> ClientDaemon<T> {
             "configuring the publisher endpoint",
         );
 
+        let upload_progress = UploadProgressRegistry::default();
+        let progress_layer =
+            axum::middleware::from_fn_with_state(upload_progress.clone(), progress::progress_layer);
+        let store_limits = StoreLimits {
+            max_blob_size: max_body_limit,
+            permits: Arc::new(Semaphore::new(max_concurrent_requests)),
+        };
         let base_layers = ServiceBuilder::new()
             .layer(HandleErrorLayer::new(handle_publisher_error))
             .layer(LoadShedLayer::new())
             .layer(BufferLayer::new(max_request_buffer_size))
-            .layer(ConcurrencyLimitLayer::new(max_concurrent_requests))
+            .layer(GlobalConcurrencyLimitLayer::with_semaphore(
+                store_limits.permits.clone(),
+            ))
             .layer(DefaultBodyLimit::max(max_body_limit));
+        self.store_limits = Some(store_limits);
 
         if let Some(auth_config) = auth_config {
             // Create and run the cache to track the used JWT tokens.
             let replay_suppression_cache = auth_config.replay_suppression_config.build_and_run();
+            let publisher_auth = (Arc::new(auth_config), Arc::new(replay_suppression_cache));
             self.router = self.router.route(
                 BLOB_PUT_ENDPOINT,
                 put(routes::put_blob).route_layer(
                     ServiceBuilder::new()
                         .layer(axum::middleware::from_fn_with_state(
-                            (Arc::new(auth_config), Arc::new(replay_suppression_cache)),
+                            publisher_auth.clone(),
                             auth_layer,
                         ))
+                        .layer(progress_layer)
                         .layer(base_layers),
                 ),
             );
+            self.publisher_auth = Some(publisher_auth);
         } else {
             self.router = self.router.route(
                 BLOB_PUT_ENDPOINT,
-                put(routes::put_blob).route_layer(base_layers),
+                put(routes::put_blob).route_layer(
+                    ServiceBuilder::new()
+                        .layer(progress_layer)
+                        .layer(base_layers),
+                ),
             );
         }
+        self.router = self.router.route(
+            BLOB_PROGRESS_ENDPOINT,
+            get(progress::stream_progress).with_state(upload_progress),
+        );
         self
     }
 }
@@ -317,11 +698,25 @@ impl<T> ClientDaemon<T> {
     }
 }
 
+/// Authenticates requests with an API key, a JWT, or a signed URL, and attributes the remainder
+/// of the request to the authenticated identity.
+///
+/// Signed URLs are verified beforehand by [`signed_url::signed_url_layer`], which adds the granted
+/// access to the extensions of the request. The nonce of a signed URL is recorded in the replay
+/// suppression cache, so that the URL can only be used once. If the publisher is configured with
+/// API keys, a signed URL must name the identity of one of them, and the request is then treated as
+/// a request with that API key.
+///
+/// The holder of a valid API key is added to the extensions of the request, so that the size of
+/// the blob can be checked against the holder's permissions and charged to its daily quota once it
+/// has been received. The upload counts towards the holder's concurrent uploads until the request
+/// completes.
 pub(crate) async fn auth_layer(
-    State((auth_config, token_cache)): State<(Arc<AuthConfig>, Arc<CacheHandle<String>>)>,
+    State((auth_config, token_cache)): State<PublisherAuthState>,
     query: Query<PublisherQuery>,
-    TypedHeader(bearer_header): TypedHeader<Authorization<Bearer>>,
-    request: Request,
+    bearer_header: Option<TypedHeader<Authorization<Bearer>>>,
+    signed_url: Option<Extension<SignedUrlGrant>>,
+    mut request: Request,
     next: Next,
 ) -> Response {
     // Get a hint on the body size if possible.
@@ -329,20 +724,99 @@ pub(crate) async fn auth_layer(
     // It is fine to use this imprecise hint, because we will check again the size when storing to
     // Walrus.
     tracing::debug!(query = ?query.0, "authenticating a request to store a blob");
+    let body_size_hint = request.body().size_hint();
 
-    if let Err(resp) = verify_jwt_claim(
-        query,
-        bearer_header,
-        &auth_config,
-        token_cache.as_ref(),
-        request.body().size_hint(),
-    )
-    .await
-    {
-        resp
+    let api_key_holder = if let Some(Extension(grant)) = signed_url {
+        match authorize_signed_url(&grant, &auth_config, &token_cache).await {
+            Ok(Some(holder)) => holder,
+            Ok(None) => return run_authenticated(next, request, "signed URL").await,
+            Err(response) => return response,
+        }
     } else {
-        next.run(request).await
+        let Some(TypedHeader(bearer_header)) = bearer_header else {
+            return PublisherAuthError::MissingCredentials.to_response();
+        };
+        match auth_config.api_keys.holder(bearer_header.token().trim()) {
+            Some(holder) => holder.clone(),
+            None if auth_config.api_keys_only => {
+                tracing::debug!("rejecting a request with an invalid API key");
+                return PublisherAuthError::InvalidApiKey.to_response();
+            }
+            None => {
+                return match verify_jwt_claim(
+                    query,
+                    bearer_header,
+                    &auth_config,
+                    token_cache.as_ref(),
+                    body_size_hint,
+                )
+                .await
+                {
+                    Ok(claim) => run_authenticated(next, request, claim.identity()).await,
+                    Err(response) => response,
+                };
+            }
+        }
+    };
+
+    if let Err(error) = api_key_holder.check_upload(&query.0, body_size_hint) {
+        return error.to_response();
+    }
+    // The permit is held until the request completes.
+    let _upload_permit = match api_key_holder.start_upload() {
+        Ok(permit) => permit,
+        Err(error) => return error.to_response(),
+    };
+    let identity = api_key_holder.identity.clone();
+    request.extensions_mut().insert(api_key_holder);
+    run_authenticated(next, request, &identity).await
+}
+
+/// Checks that a signed URL authorizes a store request, and returns the holder of the API key on
+/// whose behalf the URL is signed, if the publisher is configured with API keys.
+///
+/// The nonce of the URL is recorded in the replay suppression cache until the URL expires.
+async fn authorize_signed_url(
+    grant: &SignedUrlGrant,
+    auth_config: &AuthConfig,
+    token_cache: &CacheHandle<String>,
+) -> Result<Option<Arc<ApiKeyHolder>>, Response> {
+    let holder = match &grant.identity {
+        Some(identity) => auth_config
+            .api_keys
+            .key_of_identity(identity)
+            .map(|(_, holder)| holder.clone()),
+        None => None,
+    };
+    if holder.is_none() && (grant.identity.is_some() || !auth_config.api_keys.is_empty()) {
+        tracing::debug!(
+            identity = ?grant.identity,
+            "rejecting a signed URL without a known identity"
+        );
+        return Err(SignedUrlError::UnknownIdentity.to_response());
+    }
+
+    if let Some(nonce) = &grant.nonce {
+        let result = token_cache
+            .insert_if_not_present(format!("signed-url:{nonce}"), grant.expires_at)
+            .await;
+        match result {
+            Ok(()) => (),
+            Err(CacheError::KeyAlreadyPresent(_)) => {
+                return Err(SignedUrlError::AlreadyUsed.to_response());
+            }
+            Err(error) => return Err(PublisherAuthError::from(error).to_response()),
+        }
     }
+    Ok(holder)
+}
+
+/// Runs the authenticated request within a span with the identity of the requester.
+async fn run_authenticated(next: Next, request: Request, identity: &str) -> Response {
+    tracing::info!(%identity, "authenticated a request to store a blob");
+    next.run(request)
+        .instrument(tracing::error_span!("authenticated_request", %identity))
+        .await
 }
 
 async fn handle_publisher_error(error: BoxError) -> Response {
@@ -1,9 +1,15 @@
 // Copyright (c) Walrus Foundation
 // SPDX-License-Identifier: Apache-2.0
 
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+use anyhow::{bail, ensure};
 use axum::{body::Body, extract::Query, http::Response};
 use axum_extra::headers::{authorization::Bearer, Authorization};
-use chrono::DateTime;
+use chrono::{DateTime, NaiveDate, Utc};
 use jsonwebtoken::{
     decode,
     errors::{Error as JwtError, ErrorKind as JwtErrorKind},
@@ -11,12 +17,17 @@ use jsonwebtoken::{
     Validation,
 };
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 use sui_types::base_types::SuiAddress;
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
 use walrus_core::EpochCount;
 use walrus_proc_macros::RestApiError;
 
 use super::{cache::CacheHandle, routes::PublisherQuery};
-use crate::{client::config::AuthConfig, common::api::RestApiError};
+use crate::{
+    client::config::{ApiKeyConfig, ApiKeysConfig, AuthConfig, UploadPermissions},
+    common::api::RestApiError,
+};
 
 pub const PUBLISHER_AUTH_DOMAIN: &str = "auth.publisher.walrus.space";
 
@@ -37,6 +48,12 @@ pub struct Claim {
     /// [rfc7519s4.1.7]: https://datatracker.ietf.org/doc/html/rfc7519#section-4.1.7
     pub jti: String,
 
+    /// The subject of the token, to which requests are attributed in the logs.
+    ///
+    /// If absent, requests are attributed to the JWT ID.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sub: Option<String>,
+
     /// The owner address of the sui blob object.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub send_object_to: Option<SuiAddress>,
@@ -116,6 +133,11 @@ impl Claim {
         Ok(claim)
     }
 
+    /// Returns the identity to which requests authenticated with the claim are attributed.
+    pub fn identity(&self) -> &str {
+        self.sub.as_deref().unwrap_or(&self.jti)
+    }
+
     /// Checks that the query matches the claim.
     pub fn check_valid_upload(
         &self,
@@ -259,13 +281,14 @@ impl Claim {
     }
 }
 
+/// Verifies the JWT in the bearer header and returns its claim.
 pub async fn verify_jwt_claim(
     query: Query<PublisherQuery>,
     bearer: Authorization<Bearer>,
     auth_config: &AuthConfig,
     token_cache: &CacheHandle<String>,
     body_size_hint: http_body::SizeHint,
-) -> Result<(), Response<Body>> {
+) -> Result<Claim, Response<Body>> {
     let mut validation = if auth_config.decoding_key.is_some() {
         auth_config
             .algorithm
@@ -317,17 +340,256 @@ pub async fn verify_jwt_claim(
                     });
                 Err(error.to_response())
             } else {
-                Ok(())
+                Ok(claim)
             }
         }
         Err(code) => Err(code.to_response()),
     }
 }
 
+/// The API keys accepted by the publisher, and their holders.
+///
+/// The keys are indexed by their SHA-256 digest, so that looking up a key does not reveal its
+/// bytes through timing. The keys are additionally indexed by the identity of their holders, for
+/// requests that are signed with the key instead of presenting it, such as S3 requests.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct ApiKeys {
+    holders: HashMap<[u8; 32], Arc<ApiKeyHolder>>,
+    keys_by_identity: HashMap<String, (String, Arc<ApiKeyHolder>)>,
+}
+
+impl ApiKeys {
+    /// Creates the set of API keys from the configuration.
+    ///
+    /// Returns an error if a key is empty or used by more than one identity, or if an identity
+    /// has more than one key.
+    pub(crate) fn new(config: ApiKeysConfig) -> anyhow::Result<Self> {
+        let mut holders = HashMap::new();
+        let mut keys_by_identity = HashMap::new();
+        for ApiKeyConfig {
+            identity,
+            key,
+            permissions,
+        } in config.keys
+        {
+            ensure!(!key.is_empty(), "the API key of '{identity}' is empty");
+            let holder = Arc::new(ApiKeyHolder::new(identity, permissions));
+            if let Some(other) = holders.insert(api_key_digest(&key), holder.clone()) {
+                bail!(
+                    "the same API key is used by '{}' and '{}'",
+                    other.identity,
+                    holder.identity
+                );
+            }
+            ensure!(
+                keys_by_identity
+                    .insert(holder.identity.clone(), (key, holder.clone()))
+                    .is_none(),
+                "'{}' has more than one API key",
+                holder.identity
+            );
+        }
+        Ok(Self {
+            holders,
+            keys_by_identity,
+        })
+    }
+
+    /// Returns the holder of the API key, if the key is valid.
+    pub(crate) fn holder(&self, key: &str) -> Option<&Arc<ApiKeyHolder>> {
+        self.holders.get(&api_key_digest(key))
+    }
+
+    /// Returns the API key of the holder with the identity, and the holder, if there is such a
+    /// holder.
+    pub(crate) fn key_of_identity(&self, identity: &str) -> Option<(&str, &Arc<ApiKeyHolder>)> {
+        self.keys_by_identity
+            .get(identity)
+            .map(|(key, holder)| (key.as_str(), holder))
+    }
+
+    /// Returns the number of API keys.
+    pub(crate) fn len(&self) -> usize {
+        self.holders.len()
+    }
+
+    /// Returns true if there are no API keys.
+    pub(crate) fn is_empty(&self) -> bool {
+        self.holders.is_empty()
+    }
+}
+
+fn api_key_digest(key: &str) -> [u8; 32] {
+    Sha256::digest(key.as_bytes()).into()
+}
+
+/// The holder of an API key, together with its usage of the publisher.
+#[derive(Debug)]
+pub(crate) struct ApiKeyHolder {
+    /// The identity of the holder, to which requests are attributed in the logs.
+    pub identity: String,
+    /// The uploads the holder is allowed to make.
+    pub permissions: UploadPermissions,
+    /// The bytes stored by the holder on the current day.
+    daily_usage: Mutex<DailyUsage>,
+    /// The permits for uploads in progress, if their number is limited.
+    upload_permits: Option<Arc<Semaphore>>,
+}
+
+/// The number of bytes stored on a day.
+#[derive(Debug, Clone, Copy, Default)]
+struct DailyUsage {
+    day: NaiveDate,
+    bytes: u64,
+}
+
+impl ApiKeyHolder {
+    fn new(identity: String, permissions: UploadPermissions) -> Self {
+        Self {
+            identity,
+            permissions,
+            daily_usage: Mutex::default(),
+            upload_permits: permissions
+                .max_concurrent_uploads
+                .map(|max_uploads| Arc::new(Semaphore::new(max_uploads))),
+        }
+    }
+
+    /// Reserves one of the uploads the holder is allowed to have in progress at the same time.
+    ///
+    /// The upload is counted as in progress until the returned permit is dropped.
+    pub(crate) fn start_upload(&self) -> Result<Option<OwnedSemaphorePermit>, PublisherAuthError> {
+        let Some(upload_permits) = &self.upload_permits else {
+            return Ok(None);
+        };
+        upload_permits
+            .clone()
+            .try_acquire_owned()
+            .map(Some)
+            .map_err(|_| {
+                tracing::debug!(
+                    identity = self.identity,
+                    "too many concurrent uploads for the API key"
+                );
+                PublisherAuthError::TooManyConcurrentUploads(
+                    self.permissions.max_concurrent_uploads.unwrap_or_default(),
+                )
+            })
+    }
+
+    /// Checks that the holder is allowed to make the upload requested in the query.
+    ///
+    /// The size of the blob is checked against the lower bound of the body size, and needs to be
+    /// checked again with [`Self::check_size`] once the body has been received.
+    pub(crate) fn check_upload(
+        &self,
+        query: &PublisherQuery,
+        body_size_hint: http_body::SizeHint,
+    ) -> Result<(), PublisherAuthError> {
+        if let Some(max_epochs) = self.permissions.max_epochs {
+            if query.epochs > max_epochs {
+                tracing::debug!(
+                    identity = self.identity,
+                    max_epochs,
+                    query_epochs = query.epochs,
+                    "upload with more epochs than allowed for the API key"
+                );
+                return Err(PublisherAuthError::InvalidEpochs);
+            }
+        }
+        let size = body_size_hint.lower();
+        self.check_size(size)?;
+        self.check_quota(size, Utc::now().date_naive())
+    }
+
+    /// Checks that the holder is allowed to store a blob of the given size.
+    pub(crate) fn check_size(&self, size: u64) -> Result<(), PublisherAuthError> {
+        match self.permissions.max_size {
+            Some(max_size) if size > max_size => {
+                tracing::debug!(
+                    identity = self.identity,
+                    max_size,
+                    size,
+                    "upload with a larger size than allowed for the API key"
+                );
+                Err(PublisherAuthError::BlobTooLarge(max_size))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Charges a blob of the given size to the daily quota of the holder.
+    ///
+    /// Returns an error, without charging the blob, if the quota would be exceeded. The blob can
+    /// be refunded with [`Self::refund_quota`] if it is not stored.
+    pub(crate) fn charge_quota(&self, size: u64) -> Result<(), PublisherAuthError> {
+        self.charge_quota_on(size, Utc::now().date_naive())
+    }
+
+    /// Refunds a blob previously charged with [`Self::charge_quota`].
+    pub(crate) fn refund_quota(&self, size: u64) {
+        let today = Utc::now().date_naive();
+        let mut usage = self
+            .daily_usage
+            .lock()
+            .expect("mutex should not be poisoned");
+        // Blobs charged on a previous day no longer count towards the quota.
+        if usage.day == today {
+            usage.bytes = usage.bytes.saturating_sub(size);
+        }
+    }
+
+    fn check_quota(&self, size: u64, today: NaiveDate) -> Result<(), PublisherAuthError> {
+        let usage = *self
+            .daily_usage
+            .lock()
+            .expect("mutex should not be poisoned");
+        let used = if usage.day == today { usage.bytes } else { 0 };
+        self.ensure_within_quota(used, size)
+    }
+
+    fn charge_quota_on(&self, size: u64, today: NaiveDate) -> Result<(), PublisherAuthError> {
+        let mut usage = self
+            .daily_usage
+            .lock()
+            .expect("mutex should not be poisoned");
+        if usage.day != today {
+            *usage = DailyUsage {
+                day: today,
+                bytes: 0,
+            };
+        }
+        self.ensure_within_quota(usage.bytes, size)?;
+        usage.bytes += size;
+        Ok(())
+    }
+
+    fn ensure_within_quota(&self, used: u64, size: u64) -> Result<(), PublisherAuthError> {
+        match self.permissions.daily_quota {
+            Some(quota) if used.saturating_add(size) > quota => {
+                tracing::debug!(
+                    identity = self.identity,
+                    quota,
+                    used,
+                    size,
+                    "upload exceeding the daily quota of the API key"
+                );
+                Err(PublisherAuthError::DailyQuotaExceeded(quota))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
 /// Type representing the possible errors that can occur during the authentication process.
 #[derive(Debug, thiserror::Error, RestApiError)]
 #[rest_api_error(domain = PUBLISHER_AUTH_DOMAIN)]
 pub enum PublisherAuthError {
+    /// The request presents neither a bearer token nor a signed URL.
+    #[error("the request presents neither a bearer token nor a signed URL")]
+    #[rest_api_error(reason = "MISSING_CREDENTIALS", status = ApiStatusCode::InvalidArgument)]
+    MissingCredentials,
+
     /// The expiration in the query does not match the token.
     #[error("the expiration in the query does not match the token")]
     #[rest_api_error(reason = "INVALID_EXPIRATION", status = ApiStatusCode::FailedPrecondition)]
@@ -373,6 +635,29 @@ pub enum PublisherAuthError {
     #[rest_api_error(reason = "TOKEN_ALREADY_USED", status = ApiStatusCode::ResourceExhausted)]
     TokenAlreadyUsed,
 
+    /// The bearer token is not one of the API keys accepted by the publisher.
+    #[error("the API key is invalid")]
+    #[rest_api_error(reason = "INVALID_API_KEY", status = ApiStatusCode::Unauthenticated)]
+    InvalidApiKey,
+
+    /// The blob is larger than allowed for the API key.
+    #[error("the blob is larger than the maximum of {0} bytes allowed for the API key")]
+    #[rest_api_error(reason = "BLOB_TOO_LARGE", status = ApiStatusCode::PayloadTooLarge)]
+    BlobTooLarge(u64),
+
+    /// Storing the blob would exceed the daily quota of the API key.
+    #[error("storing the blob would exceed the daily quota of {0} bytes of the API key")]
+    #[rest_api_error(reason = "DAILY_QUOTA_EXCEEDED", status = ApiStatusCode::ResourceExhausted)]
+    DailyQuotaExceeded(u64),
+
+    /// The API key has reached its maximum number of uploads in progress.
+    #[error("the API key has reached its maximum of {0} concurrent uploads")]
+    #[rest_api_error(
+        reason = "TOO_MANY_CONCURRENT_UPLOADS",
+        status = ApiStatusCode::ResourceExhausted
+    )]
+    TooManyConcurrentUploads(usize),
+
     /// One of the timestamps in the JWT token is invalid.
     #[error("one of the timestamps in the JWT token is invalid")]
     #[rest_api_error(reason = "INVALID_TIMESTAMP", status = ApiStatusCode::FailedPrecondition)]
@@ -389,8 +674,8 @@ mod tests {
     use std::sync::Arc;
 
     use axum::{
-        http::{Request, StatusCode},
-        routing::get,
+        http::{Method, Request, StatusCode},
+        routing::{get, put},
         Router,
     };
     use jsonwebtoken::{encode, Algorithm, EncodingKey, Header};
@@ -402,7 +687,11 @@ mod tests {
     use super::*;
     use crate::client::{
         config::AuthConfig,
-        daemon::{auth_layer, cache::CacheConfig},
+        daemon::{
+            auth_layer,
+            cache::CacheConfig,
+            signed_url::{signed_url_layer, SignedUrls, UrlSigningKey},
+        },
     };
 
     // Fixtures and helpers for tests.
@@ -969,4 +1258,241 @@ mod tests {
 
         execute_requests(&router, requests).await;
     }
+
+    fn api_keys_for_tests() -> ApiKeys {
+        ApiKeys::new(ApiKeysConfig {
+            keys: vec![
+                ApiKeyConfig {
+                    identity: "limited".to_string(),
+                    key: "limited-key".to_string(),
+                    permissions: UploadPermissions {
+                        max_epochs: Some(5),
+                        max_size: Some(10),
+                        ..Default::default()
+                    },
+                },
+                ApiKeyConfig {
+                    identity: "unlimited".to_string(),
+                    key: "unlimited-key".to_string(),
+                    permissions: UploadPermissions::default(),
+                },
+            ],
+        })
+        .expect("the keys are valid")
+    }
+
+    #[tokio::test]
+    async fn api_keys_limit_uploads() {
+        let auth_config = AuthConfig {
+            api_keys: api_keys_for_tests(),
+            api_keys_only: true,
+            ..Default::default()
+        };
+        let token_cache = CacheConfig::default().build_and_run();
+        let publisher_layers = ServiceBuilder::new().layer(axum::middleware::from_fn_with_state(
+            (Arc::new(auth_config), Arc::new(token_cache)),
+            auth_layer,
+        ));
+
+        let router =
+            Router::new().route("/v1/blobs", get(|| async {}).route_layer(publisher_layers));
+
+        let limited_key = || correct_auth_header("limited-key".to_string());
+        let requests = vec![
+            (
+                RequestHeadersAndData::new(
+                    "/v1/blobs?epochs=5",
+                    limited_key(),
+                    Some(Body::from(vec![42; 10])),
+                ),
+                StatusCode::OK,
+            ),
+            (
+                // Too many epochs.
+                RequestHeadersAndData::new("/v1/blobs?epochs=6", limited_key(), None),
+                StatusCode::BAD_REQUEST,
+            ),
+            (
+                // Too large body.
+                RequestHeadersAndData::new(
+                    "/v1/blobs?epochs=1",
+                    limited_key(),
+                    Some(Body::from(vec![42; 11])),
+                ),
+                StatusCode::PAYLOAD_TOO_LARGE,
+            ),
+            (
+                RequestHeadersAndData::new(
+                    "/v1/blobs?epochs=100",
+                    correct_auth_header("unlimited-key".to_string()),
+                    Some(Body::from(vec![42; 100])),
+                ),
+                StatusCode::OK,
+            ),
+            (
+                // Unknown key, which is not accepted as JWT either.
+                RequestHeadersAndData::new(
+                    "/v1/blobs?epochs=1",
+                    correct_auth_header("other-key".to_string()),
+                    None,
+                ),
+                StatusCode::UNAUTHORIZED,
+            ),
+        ];
+
+        execute_requests(&router, requests).await;
+    }
+
+    #[tokio::test]
+    async fn signed_urls_are_single_use_and_limited_by_their_identity() {
+        let auth_config = AuthConfig {
+            api_keys: api_keys_for_tests(),
+            api_keys_only: true,
+            ..Default::default()
+        };
+        let token_cache = CacheConfig::default().build_and_run();
+        let publisher_layers = ServiceBuilder::new().layer(axum::middleware::from_fn_with_state(
+            (Arc::new(auth_config), Arc::new(token_cache)),
+            auth_layer,
+        ));
+        let key = UrlSigningKey::new(b"a secret key");
+        let signed_urls = Arc::new(SignedUrls {
+            key: key.clone(),
+            require_for_reads: false,
+        });
+        let router = Router::new()
+            .route("/v1/blobs", put(|| async {}).route_layer(publisher_layers))
+            .layer(axum::middleware::from_fn_with_state(
+                signed_urls,
+                signed_url_layer,
+            ));
+
+        let sign = |query: &str, identity: Option<&str>| {
+            let url = reqwest::Url::parse(&format!("http://localhost/v1/blobs?{query}")).unwrap();
+            let expires_at = Utc::now() + chrono::TimeDelta::hours(1);
+            let url = key
+                .sign_url(&Method::PUT, url, expires_at, Some(100), identity)
+                .unwrap();
+            format!("{}?{}", url.path(), url.query().unwrap_or_default())
+        };
+        let store =
+            |uri: &str, size: usize| Request::put(uri).body(Body::from(vec![42; size])).unwrap();
+
+        let uri = sign("epochs=5", Some("limited"));
+        let used_uri = uri.clone();
+        for (request, expected_status) in [
+            (store(&uri, 10), StatusCode::OK),
+            // The URL can only be used once.
+            (store(&used_uri, 10), StatusCode::FORBIDDEN),
+            // The permissions of the identity apply.
+            (
+                store(&sign("epochs=6", Some("limited")), 10),
+                StatusCode::BAD_REQUEST,
+            ),
+            (
+                store(&sign("epochs=1", Some("limited")), 11),
+                StatusCode::PAYLOAD_TOO_LARGE,
+            ),
+            // The size allowed by the URL applies.
+            (
+                store(&sign("epochs=1", Some("unlimited")), 101),
+                StatusCode::PAYLOAD_TOO_LARGE,
+            ),
+            (
+                store(&sign("epochs=1", Some("unlimited")), 100),
+                StatusCode::OK,
+            ),
+            // The URL must be signed on behalf of a known identity.
+            (store(&sign("epochs=1", None), 10), StatusCode::FORBIDDEN),
+            (
+                store(&sign("epochs=1", Some("unknown")), 10),
+                StatusCode::FORBIDDEN,
+            ),
+        ] {
+            let response = router.clone().oneshot(request).await.unwrap();
+            assert_eq!(response.status(), expected_status);
+        }
+    }
+
+    #[test]
+    fn rejects_api_keys_shared_by_identities() {
+        let key = |identity: &str| ApiKeyConfig {
+            identity: identity.to_string(),
+            key: "shared-key".to_string(),
+            permissions: UploadPermissions::default(),
+        };
+
+        let result = ApiKeys::new(ApiKeysConfig {
+            keys: vec![key("first"), key("second")],
+        });
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn rejects_identities_with_several_api_keys() {
+        let key = |key: &str| ApiKeyConfig {
+            identity: "tenant".to_string(),
+            key: key.to_string(),
+            permissions: UploadPermissions::default(),
+        };
+
+        let result = ApiKeys::new(ApiKeysConfig {
+            keys: vec![key("first-key"), key("second-key")],
+        });
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn daily_quota_is_reset_each_day() {
+        let holder = ApiKeyHolder::new(
+            "tenant".to_string(),
+            UploadPermissions {
+                daily_quota: Some(100),
+                ..Default::default()
+            },
+        );
+        let day = |day| NaiveDate::from_ymd_opt(2025, 1, day).expect("the date is valid");
+
+        holder
+            .charge_quota_on(60, day(1))
+            .expect("within the quota");
+        assert!(matches!(
+            holder.charge_quota_on(41, day(1)),
+            Err(PublisherAuthError::DailyQuotaExceeded(100))
+        ));
+        assert!(holder.check_quota(41, day(1)).is_err());
+        holder
+            .charge_quota_on(40, day(1))
+            .expect("within the quota");
+
+        assert!(holder.check_quota(100, day(2)).is_ok());
+        holder
+            .charge_quota_on(100, day(2))
+            .expect("the quota is reset");
+    }
+
+    #[test]
+    fn limits_concurrent_uploads() {
+        let holder = ApiKeyHolder::new(
+            "tenant".to_string(),
+            UploadPermissions {
+                max_concurrent_uploads: Some(2),
+                ..Default::default()
+            },
+        );
+
+        let first = holder.start_upload().expect("within the limit");
+        let _second = holder.start_upload().expect("within the limit");
+        assert!(matches!(
+            holder.start_upload(),
+            Err(PublisherAuthError::TooManyConcurrentUploads(2))
+        ));
+
+        drop(first);
+        holder
+            .start_upload()
+            .expect("a finished upload releases its permit");
+    }
 }
@@ -0,0 +1,859 @@
+// Copyright (c) Walrus Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! A cache of the blobs reconstructed by the aggregator.
+//!
+//! Blobs are cached in memory and, optionally, in a directory on disk. Both tiers evict the least
+//! recently used blobs when they exceed their size limit. Each blob is cached until the estimated
+//! end of its storage period, but at most for a configured time, so that blobs that are deleted or
+//! expire are not served for long from the cache.
+//!
+//! Operators can additionally pin blobs in memory, for example the assets of a website. Pinned
+//! blobs are not evicted, and are refreshed periodically, so that they are unpinned once they are
+//! deleted, expire, or are blocked.
+
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex, Weak},
+    time::{Duration, Instant},
+};
+
+use anyhow::Context as _;
+use axum::body::Bytes;
+use chrono::{DateTime, Utc};
+use moka::{future::Cache, notification::RemovalCause, policy::EvictionPolicy, Expiry};
+use prometheus::{IntCounter, IntCounterVec, IntGaugeVec};
+use serde::Serialize;
+use serde_with::{serde_as, DisplayFromStr};
+use sui_types::base_types::ObjectID;
+use walrus_core::{BlobId, EncodingType, EpochCount};
+use walrus_sdk::{
+    client::{
+        byte_range::{BlobRange, ByteRange},
+        responses::{BlobAvailability, BlobStoreResult},
+    },
+    error::{ClientErrorKind, ClientResult},
+    store_when::StoreWhen,
+};
+use walrus_sui::{
+    client::{BlobPersistence, PostStoreAction},
+    types::move_structs::BlobWithAttribute,
+};
+use walrus_utils::metrics::Registry;
+
+use super::{pin::PinBlobError, WalrusReadClient, WalrusWriteClient};
+
+const MEMORY_TIER: &str = "memory";
+const DISK_TIER: &str = "disk";
+const PINNED_TIER: &str = "pinned";
+
+/// The interval at which pinned blobs are checked for being due for a refresh.
+const PINNED_REFRESH_INTERVAL: Duration = Duration::from_secs(60);
+
+walrus_utils::metrics::define_metric_set! {
+    #[namespace = "walrus_aggregator_blob_cache"]
+    /// Metrics of the aggregator's blob cache.
+    struct BlobCacheMetrics {
+        #[help = "The number of reads of blobs served from the cache, by cache tier."]
+        hits_total: IntCounterVec["tier"],
+
+        #[help = "The number of reads of blobs that were not found in the cache."]
+        misses_total: IntCounter[],
+
+        #[help = "The number of blobs removed from the cache, by cache tier and cause."]
+        evictions_total: IntCounterVec["tier", "cause"],
+
+        #[help = "The total size in bytes of the blobs in the cache, by cache tier."]
+        size_bytes: IntGaugeVec["tier"],
+    }
+}
+
+/// The configuration of the aggregator's blob cache.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct BlobCacheConfig {
+    /// The maximum total size in bytes of the blobs cached in memory.
+    ///
+    /// The in-memory cache is disabled if the size is zero.
+    pub memory_capacity: u64,
+    /// The maximum size in bytes of a blob that is cached.
+    pub max_blob_size: u64,
+    /// The maximum time for which a blob is cached.
+    pub max_ttl: Duration,
+    /// The configuration of the on-disk cache, if blobs are also cached on disk.
+    pub disk: Option<DiskCacheConfig>,
+    /// The maximum total size in bytes of the blobs pinned in memory.
+    ///
+    /// Blobs cannot be pinned if the size is zero.
+    pub max_pinned_size: u64,
+}
+
+/// The configuration of the on-disk tier of the blob cache.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct DiskCacheConfig {
+    /// The directory in which the blobs are stored.
+    pub directory: PathBuf,
+    /// The maximum total size in bytes of the blobs cached on disk.
+    pub capacity: u64,
+}
+
+/// A blob in the in-memory cache.
+#[derive(Debug, Clone)]
+struct CachedBlob {
+    data: Bytes,
+    expires_at: Instant,
+}
+
+/// A blob in the on-disk cache, whose data is stored in a file named after the blob ID.
+#[derive(Debug, Clone)]
+struct CachedFile {
+    size: u64,
+    expires_at: Instant,
+}
+
+/// A blob pinned in memory, which is kept until it is unpinned.
+#[derive(Debug, Clone)]
+struct PinnedBlob {
+    data: Bytes,
+    estimated_expiry: Option<DateTime<Utc>>,
+    refresh_at: Instant,
+}
+
+/// Information about a pinned blob.
+#[serde_as]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct PinnedBlobInfo {
+    /// The ID of the blob.
+    #[serde_as(as = "DisplayFromStr")]
+    pub blob_id: BlobId,
+    /// The size of the blob in bytes.
+    pub size: u64,
+    /// The estimated expiry of the blob, if it is stored permanently.
+    pub estimated_expiry: Option<DateTime<Utc>>,
+}
+
+impl PinnedBlobInfo {
+    fn new(blob_id: BlobId, blob: &PinnedBlob) -> Self {
+        Self {
+            blob_id,
+            size: blob.data.len() as u64,
+            estimated_expiry: blob.estimated_expiry,
+        }
+    }
+}
+
+/// Expires the entries of the caches at their expiry instant.
+#[derive(Debug)]
+struct ExpireAt;
+
+impl Expiry<BlobId, CachedBlob> for ExpireAt {
+    fn expire_after_create(
+        &self,
+        _blob_id: &BlobId,
+        value: &CachedBlob,
+        created_at: Instant,
+    ) -> Option<Duration> {
+        Some(value.expires_at.saturating_duration_since(created_at))
+    }
+}
+
+impl Expiry<BlobId, CachedFile> for ExpireAt {
+    fn expire_after_create(
+        &self,
+        _blob_id: &BlobId,
+        value: &CachedFile,
+        created_at: Instant,
+    ) -> Option<Duration> {
+        Some(value.expires_at.saturating_duration_since(created_at))
+    }
+}
+
+/// A [`WalrusReadClient`] that caches the blobs read or stored through the wrapped client.
+///
+/// Reads of byte ranges are served from the cache if the blob is cached, but do not populate the
+/// cache. The blocklist of the wrapped client is checked before every read, so that blobs that
+/// are blocked after they have been cached are no longer served.
+#[derive(Debug)]
+pub(crate) struct BlobCache<T> {
+    client: T,
+    memory: Option<Cache<BlobId, CachedBlob>>,
+    disk: Option<DiskCache>,
+    pinned: Mutex<HashMap<BlobId, PinnedBlob>>,
+    max_blob_size: u64,
+    max_pinned_size: u64,
+    max_ttl: Duration,
+    metrics: BlobCacheMetrics,
+}
+
+impl<T: WalrusReadClient + Send + Sync> BlobCache<T> {
+    /// Creates a new cache for the blobs read through `client`.
+    ///
+    /// Any blobs cached on disk by a previous run are removed from the cache directory.
+    pub(crate) fn new(
+        client: T,
+        config: BlobCacheConfig,
+        registry: &Registry,
+    ) -> anyhow::Result<Self> {
+        let metrics = BlobCacheMetrics::new(registry);
+
+        let memory = (config.memory_capacity > 0).then(|| {
+            let evictions = metrics.evictions_total.clone();
+            Cache::builder()
+                .name("aggregator_blob_cache")
+                .eviction_policy(EvictionPolicy::lru())
+                .max_capacity(config.memory_capacity)
+                .weigher(|_, blob: &CachedBlob| weight(blob.data.len() as u64))
+                .expire_after(ExpireAt)
+                .eviction_listener(move |_, _, cause| {
+                    if let Some(cause) = eviction_cause(cause) {
+                        walrus_utils::with_label!(evictions, MEMORY_TIER, cause).inc();
+                    }
+                })
+                .build()
+        });
+        let disk = config
+            .disk
+            .map(|config| DiskCache::new(config, &metrics))
+            .transpose()?;
+
+        tracing::info!(
+            memory_capacity = config.memory_capacity,
+            disk_capacity = disk.as_ref().map(|disk| disk.capacity),
+            max_blob_size = config.max_blob_size,
+            max_pinned_size = config.max_pinned_size,
+            max_ttl = ?config.max_ttl,
+            "configured the blob cache"
+        );
+
+        Ok(Self {
+            client,
+            memory,
+            disk,
+            pinned: Mutex::new(HashMap::new()),
+            max_blob_size: config.max_blob_size,
+            max_pinned_size: config.max_pinned_size,
+            max_ttl: config.max_ttl,
+            metrics,
+        })
+    }
+
+    fn is_enabled(&self) -> bool {
+        self.memory.is_some() || self.disk.is_some() || self.max_pinned_size > 0
+    }
+
+    /// Returns the blob from the cache, if it is pinned or cached in either tier.
+    async fn get(&self, blob_id: &BlobId) -> Option<Bytes> {
+        let pinned = self
+            .lock_pinned()
+            .get(blob_id)
+            .map(|blob| blob.data.clone());
+        if let Some(data) = pinned {
+            walrus_utils::with_label!(self.metrics.hits_total, PINNED_TIER).inc();
+            return Some(data);
+        }
+        if let Some(memory) = &self.memory {
+            if let Some(blob) = memory.get(blob_id).await {
+                walrus_utils::with_label!(self.metrics.hits_total, MEMORY_TIER).inc();
+                return Some(blob.data);
+            }
+        }
+        if let Some(disk) = &self.disk {
+            if let Some(blob) = disk.get(blob_id).await {
+                walrus_utils::with_label!(self.metrics.hits_total, DISK_TIER).inc();
+                // Move blobs that are read from disk back into memory.
+                self.insert_in_memory(*blob_id, blob.clone()).await;
+                return Some(blob.data);
+            }
+        }
+        self.metrics.misses_total.inc();
+        None
+    }
+
+    /// Inserts the blob into both tiers of the cache, if it is small enough and does not expire
+    /// before `expiry`.
+    async fn insert(&self, blob_id: &BlobId, data: Bytes, expiry: Option<DateTime<Utc>>) {
+        if data.len() as u64 > self.max_blob_size {
+            tracing::debug!(size = data.len(), "the blob is too large to be cached");
+            return;
+        }
+        let ttl = self.ttl(expiry);
+        if ttl.is_zero() {
+            tracing::debug!(?expiry, "the blob expires too soon to be cached");
+            return;
+        }
+
+        let blob = CachedBlob {
+            data,
+            expires_at: Instant::now() + ttl,
+        };
+        if let Some(disk) = &self.disk {
+            disk.insert(*blob_id, &blob).await;
+        }
+        self.insert_in_memory(*blob_id, blob).await;
+    }
+
+    async fn insert_in_memory(&self, blob_id: BlobId, blob: CachedBlob) {
+        if let Some(memory) = &self.memory {
+            memory.insert(blob_id, blob).await;
+            walrus_utils::with_label!(self.metrics.size_bytes, MEMORY_TIER)
+                .set(i64::try_from(memory.weighted_size()).unwrap_or(i64::MAX));
+        }
+    }
+
+    /// Returns the estimated expiry of the blob, or `None` if it cannot be determined.
+    async fn expiry(&self, blob_id: &BlobId) -> ClientResult<Option<DateTime<Utc>>> {
+        self.client.estimated_blob_expiry(blob_id).await
+    }
+
+    /// Returns the time for which a blob with the given expiry is cached.
+    fn ttl(&self, expiry: Option<DateTime<Utc>>) -> Duration {
+        expiry.map_or(self.max_ttl, |expiry| {
+            (expiry - Utc::now())
+                .to_std()
+                .unwrap_or_default()
+                .min(self.max_ttl)
+        })
+    }
+
+    /// Reads the blob, if it is not already cached, and pins it in memory.
+    ///
+    /// Pinning a blob that is already pinned refreshes it. Returns an error if the blob cannot be
+    /// read, or if pinning it would exceed the maximum total size of the pinned blobs.
+    pub(crate) async fn pin(&self, blob_id: &BlobId) -> Result<PinnedBlobInfo, PinBlobError> {
+        self.client.check_blob_id(blob_id)?;
+        let (data, estimated_expiry) = tokio::join!(
+            async {
+                match self.get(blob_id).await {
+                    Some(data) => Ok(data),
+                    None => self.client.read_blob(blob_id).await.map(Bytes::from),
+                }
+            },
+            self.expiry(blob_id)
+        );
+        let estimated_expiry = estimated_expiry?;
+        let blob = PinnedBlob {
+            data: data?,
+            estimated_expiry,
+            refresh_at: Instant::now() + self.ttl(estimated_expiry),
+        };
+
+        let mut pinned = self.lock_pinned();
+        let pinned_size: u64 = pinned
+            .iter()
+            .filter(|(id, _)| *id != blob_id)
+            .map(|(_, blob)| blob.data.len() as u64)
+            .sum();
+        let available = self.max_pinned_size.saturating_sub(pinned_size);
+        if blob.data.len() as u64 > available {
+            return Err(PinBlobError::InsufficientCapacity(available));
+        }
+        let info = PinnedBlobInfo::new(*blob_id, &blob);
+        pinned.insert(*blob_id, blob);
+        self.update_pinned_size(&pinned);
+
+        tracing::info!(%blob_id, size = info.size, "pinned a blob in the cache");
+        Ok(info)
+    }
+
+    /// Unpins the blob, and returns whether it was pinned.
+    pub(crate) fn unpin(&self, blob_id: &BlobId) -> bool {
+        let mut pinned = self.lock_pinned();
+        let was_pinned = pinned.remove(blob_id).is_some();
+        self.update_pinned_size(&pinned);
+        if was_pinned {
+            tracing::info!(%blob_id, "unpinned a blob from the cache");
+        }
+        was_pinned
+    }
+
+    /// Returns information about the pinned blobs, ordered by blob ID.
+    pub(crate) fn pinned_blobs(&self) -> Vec<PinnedBlobInfo> {
+        let mut blobs: Vec<_> = self
+            .lock_pinned()
+            .iter()
+            .map(|(blob_id, blob)| PinnedBlobInfo::new(*blob_id, blob))
+            .collect();
+        blobs.sort_by_key(|info| info.blob_id);
+        blobs
+    }
+
+    /// Periodically refreshes the pinned blobs, until the cache is dropped.
+    ///
+    /// Pinned blobs are refreshed as often as cached blobs expire, that is, at their estimated
+    /// expiry but at least after the maximum time for which blobs are cached.
+    pub(crate) async fn refresh_pinned_blobs(cache: Weak<Self>) {
+        let mut interval = tokio::time::interval(PINNED_REFRESH_INTERVAL);
+        loop {
+            interval.tick().await;
+            let Some(cache) = cache.upgrade() else {
+                return;
+            };
+            let now = Instant::now();
+            let due: Vec<_> = cache
+                .lock_pinned()
+                .iter()
+                .filter(|(_, blob)| blob.refresh_at <= now)
+                .map(|(blob_id, _)| *blob_id)
+                .collect();
+            for blob_id in due {
+                cache.refresh_pinned_blob(&blob_id).await;
+            }
+        }
+    }
+
+    /// Checks that the pinned blob is still available and updates its expiry, or unpins it if it
+    /// no longer exists or is blocked.
+    ///
+    /// The content of a blob never changes, so only its first byte is read to check that it is
+    /// still available.
+    async fn refresh_pinned_blob(&self, blob_id: &BlobId) {
+        let available = async {
+            self.client.check_blob_id(blob_id)?;
+            match self
+                .client
+                .read_blob_range(blob_id, ByteRange::Bounded { start: 0, end: 0 })
+                .await
+            {
+                Err(error)
+                    if !matches!(error.kind(), ClientErrorKind::RangeNotSatisfiable { .. }) =>
+                {
+                    Err(error)
+                }
+                // An empty blob has no first byte to read, but is available.
+                _ => Ok(()),
+            }
+        };
+        let (available, estimated_expiry) = tokio::join!(available, self.expiry(blob_id));
+
+        match available.and(estimated_expiry) {
+            Ok(estimated_expiry) => {
+                if let Some(blob) = self.lock_pinned().get_mut(blob_id) {
+                    blob.estimated_expiry = estimated_expiry;
+                    blob.refresh_at = Instant::now() + self.ttl(estimated_expiry);
+                }
+                tracing::debug!(%blob_id, ?estimated_expiry, "refreshed a pinned blob");
+            }
+            Err(error)
+                if matches!(
+                    error.kind(),
+                    ClientErrorKind::BlobIdDoesNotExist | ClientErrorKind::BlobIdBlocked(_)
+                ) =>
+            {
+                tracing::warn!(?error, %blob_id, "the pinned blob is no longer available");
+                self.unpin(blob_id);
+            }
+            Err(error) => {
+                tracing::warn!(?error, %blob_id, "failed to refresh a pinned blob; retrying later");
+            }
+        }
+    }
+
+    fn lock_pinned(&self) -> std::sync::MutexGuard<'_, HashMap<BlobId, PinnedBlob>> {
+        self.pinned
+            .lock()
+            .expect("the lock is never held across a panic")
+    }
+
+    fn update_pinned_size(&self, pinned: &HashMap<BlobId, PinnedBlob>) {
+        let size: usize = pinned.values().map(|blob| blob.data.len()).sum();
+        walrus_utils::with_label!(self.metrics.size_bytes, PINNED_TIER)
+            .set(i64::try_from(size).unwrap_or(i64::MAX));
+    }
+}
+
+impl<T: WalrusReadClient + Send + Sync> WalrusReadClient for BlobCache<T> {
+    async fn read_blob(&self, blob_id: &BlobId) -> ClientResult<Vec<u8>> {
+        if !self.is_enabled() {
+            return self.client.read_blob(blob_id).await;
+        }
+
+        self.client.check_blob_id(blob_id)?;
+        if let Some(data) = self.get(blob_id).await {
+            return Ok(data.to_vec());
+        }
+
+        // Determine the expiry of the blob while it is being reconstructed.
+        let (blob, expiry) = tokio::join!(self.client.read_blob(blob_id), self.expiry(blob_id));
+        let blob = blob?;
+        match expiry {
+            Ok(expiry) => {
+                self.insert(blob_id, Bytes::from(blob.clone()), expiry)
+                    .await
+            }
+            Err(error) => {
+                tracing::debug!(
+                    ?error,
+                    "failed to get the expiry of the blob; not caching it"
+                )
+            }
+        }
+        Ok(blob)
+    }
+
+    async fn read_blob_range(&self, blob_id: &BlobId, range: ByteRange) -> ClientResult<BlobRange> {
+        if !self.is_enabled() {
+            return self.client.read_blob_range(blob_id, range).await;
+        }
+
+        self.client.check_blob_id(blob_id)?;
+        let Some(data) = self.get(blob_id).await else {
+            return self.client.read_blob_range(blob_id, range).await;
+        };
+
+        let blob_size = data.len() as u64;
+        let range = range
+            .resolve(blob_size)
+            .ok_or(ClientErrorKind::RangeNotSatisfiable { blob_size })?;
+        let slice = usize::try_from(range.start).expect("the range is within the blob")
+            ..usize::try_from(range.end).expect("the range is within the blob");
+        Ok(BlobRange {
+            data: data.slice(slice).to_vec(),
+            range,
+            blob_size,
+        })
+    }
+
+    async fn get_blob_by_object_id(
+        &self,
+        blob_object_id: &ObjectID,
+    ) -> ClientResult<BlobWithAttribute> {
+        self.client.get_blob_by_object_id(blob_object_id).await
+    }
+
+    fn check_blob_id(&self, blob_id: &BlobId) -> ClientResult<()> {
+        self.client.check_blob_id(blob_id)
+    }
+
+    async fn estimated_blob_expiry(&self, blob_id: &BlobId) -> ClientResult<Option<DateTime<Utc>>> {
+        self.client.estimated_blob_expiry(blob_id).await
+    }
+
+    async fn blob_availability(&self, blob_id: &BlobId) -> ClientResult<BlobAvailability> {
+        self.client.blob_availability(blob_id).await
+    }
+}
+
+impl<T: WalrusWriteClient + Send + Sync> WalrusWriteClient for BlobCache<T> {
+    async fn write_blob(
+        &self,
+        blob: &[u8],
+        encoding_type: Option<EncodingType>,
+        epochs_ahead: EpochCount,
+        store_when: StoreWhen,
+        persistence: BlobPersistence,
+        post_store: PostStoreAction,
+    ) -> ClientResult<BlobStoreResult> {
+        let result = self
+            .client
+            .write_blob(
+                blob,
+                encoding_type,
+                epochs_ahead,
+                store_when,
+                persistence,
+                post_store,
+            )
+            .await?;
+        // Cache the stored blob, so that it is served without being reconstructed if it is read
+        // shortly after it is published.
+        if let Some(blob_id) = result.blob_id().filter(|_| !result.is_not_stored()) {
+            if self.is_enabled() {
+                match self.expiry(&blob_id).await {
+                    Ok(expiry) => {
+                        self.insert(&blob_id, Bytes::copy_from_slice(blob), expiry)
+                            .await
+                    }
+                    Err(error) => {
+                        tracing::debug!(
+                            ?error,
+                            "failed to get the expiry of the stored blob; not caching it"
+                        )
+                    }
+                }
+            }
+        }
+        Ok(result)
+    }
+
+    fn default_post_store_action(&self) -> PostStoreAction {
+        self.client.default_post_store_action()
+    }
+}
+
+/// The on-disk tier of the blob cache.
+///
+/// The index of the cached files is kept in memory, and files are removed from the directory when
+/// they are evicted from the index.
+#[derive(Debug)]
+struct DiskCache {
+    directory: Arc<PathBuf>,
+    capacity: u64,
+    index: Cache<BlobId, CachedFile>,
+    metrics: BlobCacheMetrics,
+}
+
+impl DiskCache {
+    fn new(config: DiskCacheConfig, metrics: &BlobCacheMetrics) -> anyhow::Result<Self> {
+        std::fs::create_dir_all(&config.directory).with_context(|| {
+            format!(
+                "failed to create the blob cache directory {}",
+                config.directory.display()
+            )
+        })?;
+        remove_cached_files(&config.directory)?;
+
+        let directory = Arc::new(config.directory);
+        let evictions = metrics.evictions_total.clone();
+        let index = Cache::builder()
+            .name("aggregator_blob_disk_cache")
+            .eviction_policy(EvictionPolicy::lru())
+            .max_capacity(config.capacity)
+            .weigher(|_, file: &CachedFile| weight(file.size))
+            .expire_after(ExpireAt)
+            .eviction_listener({
+                let directory = directory.clone();
+                move |blob_id: Arc<BlobId>, _, cause| {
+                    // The file of a replaced entry has been overwritten by the new entry.
+                    if matches!(cause, RemovalCause::Replaced) {
+                        return;
+                    }
+                    if let Err(error) = std::fs::remove_file(file_path(&directory, &blob_id)) {
+                        tracing::warn!(?error, %blob_id, "failed to remove a cached blob file");
+                    }
+                    if let Some(cause) = eviction_cause(cause) {
+                        walrus_utils::with_label!(evictions, DISK_TIER, cause).inc();
+                    }
+                }
+            })
+            .build();
+
+        Ok(Self {
+            directory,
+            capacity: config.capacity,
+            index,
+            metrics: metrics.clone(),
+        })
+    }
+
+    async fn get(&self, blob_id: &BlobId) -> Option<CachedBlob> {
+        let file = self.index.get(blob_id).await?;
+        let data = tokio::fs::read(file_path(&self.directory, blob_id))
+            .await
+            .inspect_err(|error| {
+                tracing::debug!(?error, %blob_id, "failed to read a cached blob file");
+            })
+            .ok()
+            // The size differs if the file is being overwritten concurrently.
+            .filter(|data| data.len() as u64 == file.size);
+
+        let Some(data) = data else {
+            self.index.invalidate(blob_id).await;
+            return None;
+        };
+        Some(CachedBlob {
+            data: Bytes::from(data),
+            expires_at: file.expires_at,
+        })
+    }
+
+    async fn insert(&self, blob_id: BlobId, blob: &CachedBlob) {
+        if let Err(error) = tokio::fs::write(file_path(&self.directory, &blob_id), &blob.data).await
+        {
+            tracing::warn!(?error, %blob_id, "failed to write a blob to the cache directory");
+            return;
+        }
+        let file = CachedFile {
+            size: blob.data.len() as u64,
+            expires_at: blob.expires_at,
+        };
+        self.index.insert(blob_id, file).await;
+        walrus_utils::with_label!(self.metrics.size_bytes, DISK_TIER)
+            .set(i64::try_from(self.index.weighted_size()).unwrap_or(i64::MAX));
+    }
+}
+
+fn file_path(directory: &Path, blob_id: &BlobId) -> PathBuf {
+    directory.join(blob_id.to_string())
+}
+
+/// Removes the files of cached blobs from the directory.
+///
+/// Only files whose names are blob IDs are removed, so that other files in the directory are
+/// left untouched.
+fn remove_cached_files(directory: &Path) -> anyhow::Result<()> {
+    for entry in std::fs::read_dir(directory)? {
+        let entry = entry?;
+        let is_cached_blob = entry
+            .file_name()
+            .to_str()
+            .is_some_and(|name| name.parse::<BlobId>().is_ok());
+        if is_cached_blob && entry.file_type()?.is_file() {
+            std::fs::remove_file(entry.path())?;
+        }
+    }
+    Ok(())
+}
+
+/// Returns the weight of a blob of the given size in the caches, which is its size in bytes.
+fn weight(size: u64) -> u32 {
+    u32::try_from(size).unwrap_or(u32::MAX)
+}
+
+/// Returns the label of the cause of an eviction, or `None` if the entry was replaced.
+fn eviction_cause(cause: RemovalCause) -> Option<&'static str> {
+    match cause {
+        RemovalCause::Expired => Some("expired"),
+        RemovalCause::Explicit => Some("invalidated"),
+        RemovalCause::Size => Some("size"),
+        RemovalCause::Replaced => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::daemon::test_utils::FakeClient;
+
+    const BLOB: &[u8] = b"the data of a blob";
+
+    fn config(disk: Option<DiskCacheConfig>) -> BlobCacheConfig {
+        BlobCacheConfig {
+            memory_capacity: 1 << 20,
+            max_blob_size: 1 << 10,
+            max_ttl: Duration::from_secs(60),
+            disk,
+            max_pinned_size: 0,
+        }
+    }
+
+    #[tokio::test]
+    async fn serves_blobs_and_ranges_from_memory() -> anyhow::Result<()> {
+        let cache = BlobCache::new(
+            FakeClient::serving(BLOB),
+            config(None),
+            &Registry::default(),
+        )?;
+        let blob_id = BlobId([1; 32]);
+
+        assert_eq!(cache.read_blob(&blob_id).await?, BLOB);
+        assert_eq!(cache.read_blob(&blob_id).await?, BLOB);
+        let range = cache
+            .read_blob_range(&blob_id, ByteRange::Bounded { start: 4, end: 7 })
+            .await?;
+
+        assert_eq!(range.data, b"data");
+        assert_eq!(range.blob_size, BLOB.len() as u64);
+        assert_eq!(cache.client.reads(), 1);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn serves_blobs_from_disk_after_eviction_from_memory() -> anyhow::Result<()> {
+        let directory = tempfile::tempdir()?;
+        let disk = DiskCacheConfig {
+            directory: directory.path().to_owned(),
+            capacity: 1 << 20,
+        };
+        let cache = BlobCache::new(
+            FakeClient::serving(BLOB),
+            config(Some(disk)),
+            &Registry::default(),
+        )?;
+        let blob_id = BlobId([2; 32]);
+
+        assert_eq!(cache.read_blob(&blob_id).await?, BLOB);
+        cache
+            .memory
+            .as_ref()
+            .expect("the memory cache is enabled")
+            .invalidate(&blob_id)
+            .await;
+        assert_eq!(cache.read_blob(&blob_id).await?, BLOB);
+
+        assert_eq!(cache.client.reads(), 1);
+        assert!(file_path(directory.path(), &blob_id).exists());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn does_not_serve_blocked_blobs_from_the_cache() -> anyhow::Result<()> {
+        let blob_id = BlobId([3; 32]);
+        let mut cache = BlobCache::new(
+            FakeClient::serving(BLOB),
+            config(None),
+            &Registry::default(),
+        )?;
+        cache.read_blob(&blob_id).await?;
+
+        cache.client.blocked = Some(blob_id);
+        let error = cache
+            .read_blob(&blob_id)
+            .await
+            .expect_err("the blob is blocked");
+
+        assert!(matches!(error.kind(), ClientErrorKind::BlobIdBlocked(_)));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn serves_stored_blobs_from_the_cache() -> anyhow::Result<()> {
+        let cache = BlobCache::new(
+            FakeClient::serving(BLOB),
+            config(None),
+            &Registry::default(),
+        )?;
+
+        let result = cache
+            .write_blob(
+                BLOB,
+                None,
+                1,
+                StoreWhen::NotStored,
+                BlobPersistence::Permanent,
+                PostStoreAction::Keep,
+            )
+            .await?;
+        let blob_id = result.blob_id().expect("the blob is stored");
+
+        assert_eq!(cache.read_blob(&blob_id).await?, BLOB);
+        assert_eq!(cache.client.reads(), 0);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn serves_pinned_blobs_up_to_the_pinned_capacity() -> anyhow::Result<()> {
+        let cache = BlobCache::new(
+            FakeClient::serving(BLOB),
+            BlobCacheConfig {
+                memory_capacity: 0,
+                max_pinned_size: 2 * BLOB.len() as u64,
+                ..config(None)
+            },
+            &Registry::default(),
+        )?;
+        let blob_ids = [BlobId([4; 32]), BlobId([5; 32]), BlobId([6; 32])];
+
+        cache.pin(&blob_ids[0]).await?;
+        cache.pin(&blob_ids[1]).await?;
+        let error = cache
+            .pin(&blob_ids[2])
+            .await
+            .expect_err("the capacity for pinned blobs is exhausted");
+        assert!(matches!(error, PinBlobError::InsufficientCapacity(0)));
+
+        assert_eq!(cache.read_blob(&blob_ids[0]).await?, BLOB);
+        assert_eq!(cache.client.reads(), 2);
+
+        assert!(cache.unpin(&blob_ids[0]));
+        assert!(!cache.unpin(&blob_ids[0]));
+        cache.pin(&blob_ids[2]).await?;
+        let pinned: Vec<_> = cache
+            .pinned_blobs()
+            .into_iter()
+            .map(|info| info.blob_id)
+            .collect();
+        assert_eq!(pinned, [blob_ids[1], blob_ids[2]]);
+        Ok(())
+    }
+}
@@ -0,0 +1,168 @@
+// Copyright (c) Walrus Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Compression of the responses of the daemon.
+//!
+//! Responses are compressed with zstd, Brotli, or gzip, as negotiated with the `Accept-Encoding`
+//! header of the request, if they are large enough for compression to be worthwhile. Content types
+//! that are usually compressed already, such as images and videos, are not compressed again, and
+//! neither are responses with a byte range of a blob.
+
+use axum::{
+    http::{
+        self,
+        header::{CONTENT_ENCODING, CONTENT_TYPE, ETAG},
+        HeaderValue,
+    },
+    middleware,
+    response::Response,
+    Router,
+};
+use tower_http::compression::{
+    predicate::{NotForContentType, Predicate, SizeAbove},
+    CompressionLayer,
+};
+
+/// The content types that are not compressed, in addition to images, gRPC, and server-sent events.
+const PRECOMPRESSED_CONTENT_TYPES: [&str; 7] = [
+    "audio/",
+    "video/",
+    "font/woff",
+    "application/gzip",
+    "application/zip",
+    "application/zstd",
+    "application/x-7z-compressed",
+];
+
+/// The configuration of the compression of responses.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct CompressionConfig {
+    /// The minimum size in bytes of the responses that are compressed.
+    pub min_size: u16,
+}
+
+/// Compresses the responses of the `router` if the client accepts a compressed encoding.
+pub(super) fn compress_responses(router: Router, config: CompressionConfig) -> Router {
+    router
+        .layer(CompressionLayer::new().compress_when(ShouldCompress(config)))
+        .layer(middleware::map_response(weaken_etag_if_encoded))
+}
+
+/// Decides whether a response is compressed.
+///
+/// Responses without a body, such as the responses to `HEAD` requests, are not compressed, even if
+/// their `Content-Length` header announces a large representation.
+#[derive(Debug, Clone, Copy)]
+struct ShouldCompress(CompressionConfig);
+
+impl Predicate for ShouldCompress {
+    fn should_compress<B>(&self, response: &http::Response<B>) -> bool
+    where
+        B: http_body::Body,
+    {
+        let is_precompressed = response
+            .headers()
+            .get(CONTENT_TYPE)
+            .and_then(|content_type| content_type.to_str().ok())
+            .is_some_and(|content_type| {
+                PRECOMPRESSED_CONTENT_TYPES
+                    .iter()
+                    .any(|precompressed| content_type.starts_with(precompressed))
+            });
+        !is_precompressed
+            && !response.body().is_end_stream()
+            && SizeAbove::new(self.0.min_size)
+                .and(NotForContentType::GRPC)
+                .and(NotForContentType::IMAGES)
+                .and(NotForContentType::SSE)
+                .should_compress(response)
+    }
+}
+
+/// Marks the entity tag of encoded responses as weak.
+///
+/// The entity tag of a blob identifies its unencoded content, so the encoded content is only
+/// equivalent to it, and not identical byte for byte as required by a strong entity tag.
+async fn weaken_etag_if_encoded(mut response: Response) -> Response {
+    if !response.headers().contains_key(CONTENT_ENCODING) {
+        return response;
+    }
+    if let Some(etag) = response.headers().get(ETAG) {
+        if !etag.as_bytes().starts_with(b"W/") {
+            let weak_etag = HeaderValue::from_bytes(&[b"W/", etag.as_bytes()].concat())
+                .expect("prefixing a valid header value keeps it valid");
+            response.headers_mut().insert(ETAG, weak_etag);
+        }
+    }
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{
+        body::Body,
+        http::{
+            header::{ACCEPT_ENCODING, CONTENT_LENGTH},
+            Request,
+        },
+        routing::get,
+    };
+    use tower::ServiceExt as _;
+
+    use super::*;
+
+    const TEXT: &str = "the text of a blob that compresses well ";
+
+    fn router() -> Router {
+        let blob = |content_type: &'static str| {
+            get(move || async move {
+                (
+                    [(CONTENT_TYPE, content_type), (ETAG, "\"blob-id\"")],
+                    TEXT.repeat(100),
+                )
+            })
+        };
+        let router = Router::new()
+            .route("/text", blob("text/html"))
+            .route("/video", blob("video/mp4"))
+            .route("/small", get(|| async { TEXT }))
+            .route(
+                "/head",
+                get(|| async { ([(CONTENT_LENGTH, "4000"), (CONTENT_TYPE, "text/html")], ()) }),
+            );
+        compress_responses(router, CompressionConfig { min_size: 1024 })
+    }
+
+    async fn get_response(path: &str, accept_encoding: &str) -> Response {
+        router()
+            .oneshot(
+                Request::builder()
+                    .uri(path)
+                    .header(ACCEPT_ENCODING, accept_encoding)
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn compresses_large_text_responses() {
+        for encoding in ["zstd", "br", "gzip"] {
+            let response = get_response("/text", encoding).await;
+            assert_eq!(response.headers()[CONTENT_ENCODING], encoding);
+            assert_eq!(response.headers()[ETAG], "W/\"blob-id\"");
+        }
+    }
+
+    #[tokio::test]
+    async fn does_not_compress_small_or_precompressed_responses() {
+        for path in ["/small", "/video", "/head"] {
+            let response = get_response(path, "gzip").await;
+            assert!(!response.headers().contains_key(CONTENT_ENCODING), "{path}");
+        }
+        let response = get_response("/text", "identity").await;
+        assert!(!response.headers().contains_key(CONTENT_ENCODING));
+        assert_eq!(response.headers()[ETAG], "\"blob-id\"");
+    }
+}
@@ -0,0 +1,189 @@
+// Copyright (c) Walrus Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Detection of the content type of blobs from their first bytes or their file names.
+//!
+//! Only formats with an unambiguous signature are detected from the data. Formats that browsers
+//! execute or render as documents, such as HTML or SVG, are deliberately not detected, so that a
+//! blob is only served with such a content type if it is requested explicitly, or if it is a file
+//! of a website whose manifest names it with the corresponding extension. Responses with such
+//! content types are additionally sandboxed, see [`is_active_content_type`].
+
+use axum::http::HeaderValue;
+
+/// The signatures at the start of the detected formats.
+const SIGNATURES: &[(&[u8], &str)] = &[
+    (b"\x89PNG\r\n\x1a\n", "image/png"),
+    (b"\xff\xd8\xff", "image/jpeg"),
+    (b"GIF87a", "image/gif"),
+    (b"GIF89a", "image/gif"),
+    (b"%PDF-", "application/pdf"),
+    (b"PK\x03\x04", "application/zip"),
+    (b"\x1f\x8b", "application/gzip"),
+    (b"\x1a\x45\xdf\xa3", "video/webm"),
+    (b"OggS", "application/ogg"),
+    (b"fLaC", "audio/flac"),
+    (b"ID3", "audio/mpeg"),
+    (b"\0asm", "application/wasm"),
+    (b"wOFF", "font/woff"),
+    (b"wOF2", "font/woff2"),
+];
+
+/// Returns the content type of the data, detected from its first bytes.
+pub(super) fn sniff_content_type(data: &[u8]) -> Option<&'static str> {
+    if let Some((_, content_type)) = SIGNATURES
+        .iter()
+        .find(|(signature, _)| data.starts_with(signature))
+    {
+        return Some(*content_type);
+    }
+
+    if data.starts_with(b"RIFF") {
+        return match data.get(8..12)? {
+            b"WEBP" => Some("image/webp"),
+            b"WAVE" => Some("audio/wav"),
+            _ => None,
+        };
+    }
+
+    // ISO base media files start with a box of type `ftyp` followed by the major brand.
+    if data.get(4..8)? == b"ftyp" {
+        return match data.get(8..12)? {
+            b"avif" | b"avis" => Some("image/avif"),
+            b"heic" | b"heix" => Some("image/heic"),
+            b"qt  " => Some("video/quicktime"),
+            b"M4A " => Some("audio/mp4"),
+            _ => Some("video/mp4"),
+        };
+    }
+
+    None
+}
+
+/// The content types of the file extensions of the files of websites.
+const EXTENSIONS: &[(&str, &str)] = &[
+    ("html", "text/html; charset=utf-8"),
+    ("htm", "text/html; charset=utf-8"),
+    ("css", "text/css; charset=utf-8"),
+    ("js", "text/javascript; charset=utf-8"),
+    ("mjs", "text/javascript; charset=utf-8"),
+    ("json", "application/json"),
+    ("map", "application/json"),
+    ("webmanifest", "application/manifest+json"),
+    ("txt", "text/plain; charset=utf-8"),
+    ("md", "text/markdown; charset=utf-8"),
+    ("xml", "application/xml"),
+    ("svg", "image/svg+xml"),
+    ("png", "image/png"),
+    ("jpg", "image/jpeg"),
+    ("jpeg", "image/jpeg"),
+    ("gif", "image/gif"),
+    ("webp", "image/webp"),
+    ("avif", "image/avif"),
+    ("ico", "image/x-icon"),
+    ("woff", "font/woff"),
+    ("woff2", "font/woff2"),
+    ("ttf", "font/ttf"),
+    ("otf", "font/otf"),
+    ("wasm", "application/wasm"),
+    ("pdf", "application/pdf"),
+    ("mp4", "video/mp4"),
+    ("webm", "video/webm"),
+    ("mp3", "audio/mpeg"),
+    ("wav", "audio/wav"),
+    ("ogg", "audio/ogg"),
+];
+
+/// Returns the content type of a file, determined from the extension of its path.
+pub(super) fn content_type_from_extension(path: &str) -> Option<&'static str> {
+    let file_name = path.rsplit('/').next()?;
+    let (_, extension) = file_name.rsplit_once('.')?;
+    EXTENSIONS
+        .iter()
+        .find(|(known, _)| known.eq_ignore_ascii_case(extension))
+        .map(|(_, content_type)| *content_type)
+}
+
+/// Returns true if browsers may render data of the content type as a document or execute it.
+///
+/// These are the HTML, XML (including SVG), and script types. Data served with such a content type
+/// could run scripts in the origin of the aggregator, so it must not be served inline without
+/// restrictions. Content types that cannot be parsed are also considered active.
+pub(super) fn is_active_content_type(content_type: &HeaderValue) -> bool {
+    const ACTIVE_KINDS: [&str; 3] = ["html", "xml", "script"];
+
+    let Ok(content_type) = content_type.to_str() else {
+        return true;
+    };
+    let essence = content_type
+        .split(';')
+        .next()
+        .unwrap_or_default()
+        .trim()
+        .to_ascii_lowercase();
+    !essence.contains('/') || ACTIVE_KINDS.iter().any(|kind| essence.contains(kind))
+}
+
+#[cfg(test)]
+mod tests {
+    use walrus_test_utils::param_test;
+
+    use super::*;
+
+    param_test! {
+        test_sniff_content_type: [
+            png: (b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR", Some("image/png")),
+            jpeg: (b"\xff\xd8\xff\xe0\0\x10JFIF", Some("image/jpeg")),
+            pdf: (b"%PDF-1.7\n", Some("application/pdf")),
+            webp: (b"RIFF\x24\0\0\0WEBPVP8 ", Some("image/webp")),
+            wav: (b"RIFF\x24\0\0\0WAVEfmt ", Some("audio/wav")),
+            unknown_riff: (b"RIFF\x24\0\0\0AVI LIST", None),
+            mp4: (b"\0\0\0\x20ftypisom\0\0\x02\0", Some("video/mp4")),
+            quicktime: (b"\0\0\0\x14ftypqt  \0\0\0\0", Some("video/quicktime")),
+            avif: (b"\0\0\0\x1cftypavif\0\0\0\0", Some("image/avif")),
+            html: (b"<!DOCTYPE html><html>", None),
+            svg: (b"<svg xmlns=\"http://www.w3.org/2000/svg\">", None),
+            text: (b"hello, world", None),
+            empty: (b"", None),
+        ]
+    }
+    fn test_sniff_content_type(data: &[u8], expected: Option<&str>) {
+        assert_eq!(sniff_content_type(data), expected);
+    }
+
+    param_test! {
+        test_content_type_from_extension: [
+            html: ("index.html", Some("text/html; charset=utf-8")),
+            nested: ("assets/app.JS", Some("text/javascript; charset=utf-8")),
+            svg: ("images/logo.svg", Some("image/svg+xml")),
+            unknown: ("archive.tar.xz", None),
+            no_extension: ("LICENSE", None),
+            dotted_directory: ("v1.2/README", None),
+        ]
+    }
+    fn test_content_type_from_extension(path: &str, expected: Option<&str>) {
+        assert_eq!(content_type_from_extension(path), expected);
+    }
+
+    param_test! {
+        test_is_active_content_type: [
+            html: ("text/html", true),
+            html_with_parameters: ("Text/HTML; charset=utf-8", true),
+            xhtml: ("application/xhtml+xml", true),
+            svg: ("image/svg+xml", true),
+            xml: ("text/xml", true),
+            javascript: ("application/javascript", true),
+            invalid: ("html", true),
+            png: ("image/png", false),
+            plain_text: ("text/plain; charset=utf-8", false),
+            octet_stream: ("application/octet-stream", false),
+            json: ("application/json", false),
+        ]
+    }
+    fn test_is_active_content_type(content_type: &'static str, expected: bool) {
+        assert_eq!(
+            is_active_content_type(&HeaderValue::from_static(content_type)),
+            expected
+        );
+    }
+}
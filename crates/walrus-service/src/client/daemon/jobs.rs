@@ -0,0 +1,608 @@
+// Copyright (c) Walrus Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Asynchronous publish jobs.
+//!
+//! A store request with the `Prefer: respond-async` header is answered with `202 Accepted` as soon
+//! as the blob has been written to the job directory, with the ID of a job that stores the blob in
+//! the background. Clients poll [`JOB_ENDPOINT`] for the status of the job and, once it has
+//! completed, the ID of the blob and the result of the store operation.
+//!
+//! Jobs are persisted in the job directory, so that the jobs that were queued or running when the
+//! publisher stopped are run again when it restarts. The status of a finished job is kept for the
+//! configured retention period.
+
+use std::{
+    collections::HashMap,
+    path::{Path as FsPath, PathBuf},
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use anyhow::Context as _;
+use axum::{
+    extract::{Path, State},
+    http::{header, HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_with::{serde_as, DisplayFromStr};
+use tokio::sync::{Notify, Semaphore};
+use tracing::Level;
+use walrus_core::BlobId;
+use walrus_proc_macros::RestApiError;
+use walrus_rest_client::api::errors::DAEMON_ERROR_DOMAIN as ERROR_DOMAIN;
+use walrus_sdk::{client::responses::BlobStoreResult, store_when::StoreWhen};
+use walrus_sui::client::{BlobPersistence, PostStoreAction};
+
+use super::{
+    auth::ApiKeyHolder,
+    routes::{PublisherQuery, StoreBlobError},
+    WalrusWriteClient,
+};
+use crate::common::api::RestApiError;
+
+/// The endpoint at which the status of a publish job is polled.
+pub const JOB_ENDPOINT: &str = "/v1/jobs/{job_id}";
+/// The endpoint below which the publish jobs are located.
+const JOBS_ENDPOINT: &str = "/v1/jobs";
+
+/// The preference with which clients request an asynchronous store.
+const RESPOND_ASYNC: &str = "respond-async";
+const PREFERENCE_APPLIED: &str = "preference-applied";
+/// The extension of the files with the records of the jobs.
+const RECORD_EXTENSION: &str = "json";
+
+/// The configuration of asynchronous publish jobs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct PublishJobsConfig {
+    /// The directory in which the blobs and the records of the jobs are stored.
+    pub directory: PathBuf,
+    /// The maximum number of jobs that store their blobs concurrently.
+    pub max_concurrent_jobs: usize,
+    /// The time after it finished for which the status of a job is kept.
+    pub retention: Duration,
+}
+
+/// The status of a publish job, as returned to clients.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct JobStatus {
+    job_id: String,
+    #[serde(flatten)]
+    state: JobState,
+    created_at: DateTime<Utc>,
+    updated_at: DateTime<Utc>,
+}
+
+/// The state of a publish job.
+#[serde_as]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(
+    tag = "status",
+    rename_all = "camelCase",
+    rename_all_fields = "camelCase"
+)]
+enum JobState {
+    /// The job waits for a worker to store its blob.
+    Queued,
+    /// The blob of the job is being stored.
+    Running,
+    /// The blob was stored.
+    Completed {
+        #[serde_as(as = "DisplayFromStr")]
+        blob_id: BlobId,
+        /// The result of the store operation, as returned by synchronous store requests.
+        result: serde_json::Value,
+    },
+    /// Storing the blob failed.
+    Failed { error: String },
+}
+
+impl JobState {
+    fn is_finished(&self) -> bool {
+        matches!(self, Self::Completed { .. } | Self::Failed { .. })
+    }
+}
+
+/// The record of a job, which is persisted in the job directory.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct JobRecord {
+    status: JobStatus,
+    query: PublisherQuery,
+}
+
+#[derive(Debug)]
+struct JobEntry {
+    record: JobRecord,
+    /// The holder of the API key with which the job was submitted, whose quota is refunded if the
+    /// job fails; not retained across restarts.
+    api_key_holder: Option<Arc<ApiKeyHolder>>,
+}
+
+/// The publish jobs of the publisher.
+#[derive(Debug)]
+pub(crate) struct PublishJobs {
+    config: PublishJobsConfig,
+    jobs: Mutex<HashMap<String, JobEntry>>,
+    /// Notified whenever a job is queued.
+    job_queued: Notify,
+}
+
+impl PublishJobs {
+    /// Creates the publish jobs, and queues the jobs that were queued or running when the
+    /// publisher stopped.
+    pub(crate) fn new(config: PublishJobsConfig) -> anyhow::Result<Self> {
+        std::fs::create_dir_all(&config.directory).with_context(|| {
+            format!(
+                "failed to create the directory for publish jobs at {}",
+                config.directory.display()
+            )
+        })?;
+        let jobs = load_jobs(&config.directory).with_context(|| {
+            format!(
+                "failed to load the publish jobs from {}",
+                config.directory.display()
+            )
+        })?;
+        let queued = jobs
+            .values()
+            .filter(|entry| entry.record.status.state == JobState::Queued)
+            .count();
+        if queued > 0 {
+            tracing::info!(queued, "resuming the publish jobs of a previous run");
+        }
+        Ok(Self {
+            config,
+            jobs: Mutex::new(jobs),
+            job_queued: Notify::new(),
+        })
+    }
+
+    /// Writes the blob to the job directory and queues a job to store it.
+    #[tracing::instrument(level = Level::ERROR, skip_all)]
+    pub(super) async fn submit(
+        &self,
+        query: PublisherQuery,
+        blob: &[u8],
+        api_key_holder: Option<Arc<ApiKeyHolder>>,
+    ) -> Result<JobStatus, PublishJobError> {
+        self.remove_expired().await;
+        let job_id = format!("{:032x}", rand::random::<u128>());
+        tokio::fs::write(self.data_path(&job_id), blob)
+            .await
+            .context("failed to write the blob of the job")?;
+
+        let now = Utc::now();
+        let record = JobRecord {
+            status: JobStatus {
+                job_id: job_id.clone(),
+                state: JobState::Queued,
+                created_at: now,
+                updated_at: now,
+            },
+            query,
+        };
+        if let Err(error) = self.persist(&record).await {
+            self.remove_file(&self.data_path(&job_id)).await;
+            return Err(error.into());
+        }
+        self.lock_jobs().insert(
+            job_id.clone(),
+            JobEntry {
+                record: record.clone(),
+                api_key_holder,
+            },
+        );
+        self.job_queued.notify_one();
+        tracing::debug!(job_id, blob_size = blob.len(), "queued a publish job");
+        Ok(record.status)
+    }
+
+    /// Returns the status of the job.
+    fn status(&self, job_id: &str) -> Result<JobStatus, PublishJobError> {
+        self.lock_jobs()
+            .get(job_id)
+            .filter(|entry| !self.is_expired(&entry.record.status))
+            .map(|entry| entry.record.status.clone())
+            .ok_or(PublishJobError::JobNotFound)
+    }
+
+    /// Runs the queued jobs with the client, until the daemon stops.
+    pub(super) async fn run<T>(self: Arc<Self>, client: Arc<T>)
+    where
+        T: WalrusWriteClient + Send + Sync + 'static,
+    {
+        let permits = Arc::new(Semaphore::new(self.config.max_concurrent_jobs.max(1)));
+        loop {
+            let permit = permits
+                .clone()
+                .acquire_owned()
+                .await
+                .expect("the semaphore is never closed");
+            let (job_id, query) = loop {
+                if let Some(job) = self.start_next_job().await {
+                    break job;
+                }
+                self.job_queued.notified().await;
+            };
+            let jobs = self.clone();
+            let client = client.clone();
+            tokio::spawn(async move {
+                jobs.run_job(client.as_ref(), &job_id, query).await;
+                drop(permit);
+            });
+        }
+    }
+
+    /// Marks the oldest queued job as running, and returns its ID and query.
+    async fn start_next_job(&self) -> Option<(String, PublisherQuery)> {
+        let record = {
+            let mut jobs = self.lock_jobs();
+            let entry = jobs
+                .values_mut()
+                .filter(|entry| entry.record.status.state == JobState::Queued)
+                .min_by_key(|entry| entry.record.status.created_at)?;
+            entry.record.status.state = JobState::Running;
+            entry.record.status.updated_at = Utc::now();
+            entry.record.clone()
+        };
+        if let Err(error) = self.persist(&record).await {
+            // The job is still run, but is run again if the publisher restarts before it finishes.
+            tracing::warn!(
+                ?error,
+                job_id = record.status.job_id,
+                "failed to persist a job"
+            );
+        }
+        Some((record.status.job_id, record.query))
+    }
+
+    /// Stores the blob of the job, and records the outcome.
+    #[tracing::instrument(level = Level::ERROR, skip_all, fields(%job_id))]
+    async fn run_job<T: WalrusWriteClient>(&self, client: &T, job_id: &str, query: PublisherQuery) {
+        tracing::debug!("starting a publish job");
+        let (state, blob_size) = match self.store_blob(client, job_id, query).await {
+            Ok(result) => (result, 0),
+            Err((error, blob_size)) => (JobState::Failed { error }, blob_size),
+        };
+
+        let record = {
+            let mut jobs = self.lock_jobs();
+            let Some(entry) = jobs.get_mut(job_id) else {
+                return;
+            };
+            if let (JobState::Failed { .. }, Some(holder)) = (&state, &entry.api_key_holder) {
+                holder.refund_quota(blob_size);
+            }
+            entry.record.status.state = state;
+            entry.record.status.updated_at = Utc::now();
+            entry.api_key_holder = None;
+            entry.record.clone()
+        };
+        tracing::info!(status = ?record.status.state, "finished a publish job");
+        if let Err(error) = self.persist(&record).await {
+            tracing::warn!(?error, "failed to persist a finished job");
+        }
+        self.remove_file(&self.data_path(job_id)).await;
+    }
+
+    /// Stores the blob of the job, and returns the completed state of the job, or the error and
+    /// the size of the blob if storing it failed.
+    async fn store_blob<T: WalrusWriteClient>(
+        &self,
+        client: &T,
+        job_id: &str,
+        query: PublisherQuery,
+    ) -> Result<JobState, (String, u64)> {
+        let blob = tokio::fs::read(self.data_path(job_id))
+            .await
+            .map_err(|error| (format!("failed to read the blob of the job: {error}"), 0))?;
+        let blob_size = blob.len() as u64;
+        let PublisherQuery {
+            encoding_type,
+            epochs,
+            deletable,
+            send_object_to,
+        } = query;
+        let post_store_action = match send_object_to {
+            Some(address) => PostStoreAction::TransferTo(address),
+            None => client.default_post_store_action(),
+        };
+
+        let result = client
+            .write_blob(
+                &blob,
+                encoding_type,
+                epochs,
+                StoreWhen::NotStoredIgnoreResources,
+                BlobPersistence::from_deletable(deletable),
+                post_store_action,
+            )
+            .await
+            .map_err(|error| {
+                tracing::error!(?error, "error storing the blob of a publish job");
+                (StoreBlobError::from(error).to_string(), blob_size)
+            })?;
+        match (&result, result.blob_id()) {
+            (BlobStoreResult::MarkedInvalid { .. }, _) => Err((
+                "the blob was marked invalid, which is likely a system error, please report it"
+                    .to_owned(),
+                blob_size,
+            )),
+            (BlobStoreResult::Error { error_msg, .. }, _) => Err((error_msg.clone(), blob_size)),
+            (_, Some(blob_id)) => Ok(JobState::Completed {
+                blob_id,
+                result: serde_json::to_value(&result)
+                    .expect("the result of a store operation can be serialized"),
+            }),
+            (_, None) => Err((
+                "the store operation returned no blob ID".to_owned(),
+                blob_size,
+            )),
+        }
+    }
+
+    /// Removes the records of the finished jobs whose retention period has elapsed.
+    async fn remove_expired(&self) {
+        let expired: Vec<_> = {
+            let mut jobs = self.lock_jobs();
+            let expired = jobs
+                .iter()
+                .filter(|(_, entry)| self.is_expired(&entry.record.status))
+                .map(|(job_id, _)| job_id.clone())
+                .collect();
+            jobs.retain(|_, entry| !self.is_expired(&entry.record.status));
+            expired
+        };
+        for job_id in expired {
+            tracing::debug!(job_id, "removing an expired publish job");
+            self.remove_file(&self.record_path(&job_id)).await;
+        }
+    }
+
+    fn is_expired(&self, status: &JobStatus) -> bool {
+        status.state.is_finished()
+            && chrono::Duration::from_std(self.config.retention)
+                .is_ok_and(|retention| status.updated_at + retention <= Utc::now())
+    }
+
+    /// Writes the record of the job to its file, replacing the previous record atomically.
+    async fn persist(&self, record: &JobRecord) -> anyhow::Result<()> {
+        let path = self.record_path(&record.status.job_id);
+        let temporary_path = path.with_extension("tmp");
+        let serialized = serde_json::to_vec(record).expect("the record of a job can be serialized");
+        tokio::fs::write(&temporary_path, serialized)
+            .await
+            .context("failed to write the record of the job")?;
+        tokio::fs::rename(&temporary_path, &path)
+            .await
+            .context("failed to replace the record of the job")
+    }
+
+    fn lock_jobs(&self) -> std::sync::MutexGuard<'_, HashMap<String, JobEntry>> {
+        self.jobs.lock().expect("mutex should not be poisoned")
+    }
+
+    fn data_path(&self, job_id: &str) -> PathBuf {
+        self.config.directory.join(job_id)
+    }
+
+    fn record_path(&self, job_id: &str) -> PathBuf {
+        self.data_path(job_id).with_extension(RECORD_EXTENSION)
+    }
+
+    async fn remove_file(&self, path: &FsPath) {
+        match tokio::fs::remove_file(path).await {
+            Ok(()) => (),
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => (),
+            Err(error) => tracing::warn!(?error, ?path, "failed to remove a file of a job"),
+        }
+    }
+}
+
+/// Returns whether the name is the identifier of a job, i.e., 32 lowercase hexadecimal digits.
+fn is_job_id(name: &str) -> bool {
+    name.len() == 32
+        && name
+            .bytes()
+            .all(|byte| byte.is_ascii_digit() || (b'a'..=b'f').contains(&byte))
+}
+
+/// Loads the records of the jobs from the directory.
+///
+/// Jobs that were running are queued again, and jobs whose blob is missing are marked as failed.
+/// The blobs of jobs without a record, which were not accepted, are removed.
+fn load_jobs(directory: &FsPath) -> anyhow::Result<HashMap<String, JobEntry>> {
+    let mut jobs = HashMap::new();
+    let mut blobs = Vec::new();
+    for entry in std::fs::read_dir(directory)? {
+        let path = entry?.path();
+        let Some(job_id) = path
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .filter(|stem| is_job_id(stem))
+        else {
+            continue;
+        };
+        match path.extension().and_then(|extension| extension.to_str()) {
+            None => blobs.push(job_id.to_owned()),
+            Some(RECORD_EXTENSION) => {
+                let mut record: JobRecord = serde_json::from_slice(&std::fs::read(&path)?)
+                    .with_context(|| format!("invalid record of a job at {}", path.display()))?;
+                let state = &mut record.status.state;
+                if *state == JobState::Running {
+                    *state = JobState::Queued;
+                }
+                if *state == JobState::Queued && !directory.join(job_id).exists() {
+                    *state = JobState::Failed {
+                        error: "the blob of the job was lost".to_owned(),
+                    };
+                }
+                jobs.insert(
+                    job_id.to_owned(),
+                    JobEntry {
+                        record,
+                        api_key_holder: None,
+                    },
+                );
+            }
+            Some(_) => (),
+        }
+    }
+    for job_id in blobs {
+        let is_pending = jobs
+            .get(&job_id)
+            .is_some_and(|entry| !entry.record.status.state.is_finished());
+        if !is_pending {
+            std::fs::remove_file(directory.join(job_id))?;
+        }
+    }
+    Ok(jobs)
+}
+
+/// Returns whether the `Prefer` headers of the request contain the `respond-async` preference.
+pub(super) fn prefers_async(headers: &HeaderMap) -> bool {
+    headers
+        .get_all("prefer")
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .any(|preference| {
+            preference
+                .split(';')
+                .next()
+                .is_some_and(|token| token.trim().eq_ignore_ascii_case(RESPOND_ASYNC))
+        })
+}
+
+/// Returns the response to a request whose blob is stored by the job.
+pub(super) fn job_accepted(status: JobStatus) -> Response {
+    let location = format!("{JOBS_ENDPOINT}/{}", status.job_id);
+    (
+        StatusCode::ACCEPTED,
+        [
+            (header::LOCATION.as_str(), location),
+            (PREFERENCE_APPLIED, RESPOND_ASYNC.to_owned()),
+        ],
+        Json(status),
+    )
+        .into_response()
+}
+
+/// Returns the status of the publish job.
+pub(super) async fn get_job_status(
+    State(jobs): State<Arc<PublishJobs>>,
+    Path(job_id): Path<String>,
+) -> Result<Response, PublishJobError> {
+    let status = jobs.status(&job_id)?;
+    Ok(([(header::CACHE_CONTROL, "no-store")], Json(status)).into_response())
+}
+
+#[derive(Debug, thiserror::Error, RestApiError)]
+#[rest_api_error(domain = ERROR_DOMAIN)]
+pub(crate) enum PublishJobError {
+    /// The job does not exist, or its status is no longer retained.
+    #[error("the publish job does not exist")]
+    #[rest_api_error(reason = "JOB_NOT_FOUND", status = ApiStatusCode::NotFound)]
+    JobNotFound,
+
+    #[error(transparent)]
+    #[rest_api_error(delegate)]
+    Store(#[from] StoreBlobError),
+}
+
+impl From<anyhow::Error> for PublishJobError {
+    fn from(error: anyhow::Error) -> Self {
+        Self::Store(StoreBlobError::Internal(error))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::http::HeaderValue;
+    use walrus_test_utils::param_test;
+
+    use super::*;
+
+    fn config(directory: &FsPath) -> PublishJobsConfig {
+        PublishJobsConfig {
+            directory: directory.to_owned(),
+            max_concurrent_jobs: 1,
+            retention: Duration::from_secs(60),
+        }
+    }
+
+    fn query() -> PublisherQuery {
+        serde_json::from_str(r#"{"epochs": 3}"#).expect("valid query")
+    }
+
+    #[tokio::test]
+    async fn resumes_jobs_after_restart() -> anyhow::Result<()> {
+        let directory = tempfile::tempdir()?;
+        let jobs = PublishJobs::new(config(directory.path()))?;
+        let first = jobs.submit(query(), b"blob", None).await?;
+        let second = jobs.submit(query(), b"blob", None).await?;
+        let (running, _) = jobs.start_next_job().await.expect("a job is queued");
+        assert_eq!(jobs.status(&running)?.state, JobState::Running);
+        let orphan = format!("{:032x}", 7);
+        std::fs::write(directory.path().join(&orphan), b"orphan")?;
+        drop(jobs);
+
+        let jobs = PublishJobs::new(config(directory.path()))?;
+        for job_id in [&first.job_id, &second.job_id] {
+            assert_eq!(jobs.status(job_id)?.state, JobState::Queued);
+        }
+        let (_, query) = jobs.start_next_job().await.expect("a job is queued");
+        assert_eq!(query.epochs, 3);
+        assert!(!directory.path().join(orphan).exists());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn expires_finished_jobs() -> anyhow::Result<()> {
+        let directory = tempfile::tempdir()?;
+        let jobs = PublishJobs::new(PublishJobsConfig {
+            retention: Duration::ZERO,
+            ..config(directory.path())
+        })?;
+        let status = jobs.submit(query(), b"blob", None).await?;
+        jobs.lock_jobs()
+            .get_mut(&status.job_id)
+            .expect("the job exists")
+            .record
+            .status
+            .state = JobState::Failed {
+            error: "failed".to_owned(),
+        };
+
+        assert!(matches!(
+            jobs.status(&status.job_id),
+            Err(PublishJobError::JobNotFound)
+        ));
+        jobs.remove_expired().await;
+        assert!(!jobs.record_path(&status.job_id).exists());
+        Ok(())
+    }
+
+    param_test! {
+        test_prefers_async: [
+            respond_async: (&["respond-async"], true),
+            case_insensitive: (&["Respond-Async"], true),
+            among_others: (&["return=minimal, respond-async; wait=10"], true),
+            separate_headers: (&["return=minimal", "respond-async"], true),
+            other: (&["return=minimal"], false),
+            none: (&[], false),
+        ]
+    }
+    fn test_prefers_async(preferences: &[&'static str], expected: bool) {
+        let mut headers = HeaderMap::new();
+        for preference in preferences {
+            headers.append("prefer", HeaderValue::from_static(preference));
+        }
+        assert_eq!(prefers_async(&headers), expected);
+    }
+}
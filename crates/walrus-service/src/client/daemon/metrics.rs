@@ -0,0 +1,179 @@
+// Copyright (c) Walrus Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Metrics of the reads and stores that the daemon performs on Walrus.
+//!
+//! The requests to the daemon are recorded by the HTTP metrics middleware, and the reads served
+//! from the blob cache by the metrics of the cache. The metrics here cover the work done by the
+//! wrapped client: the latency of reading and storing blobs, the amount of data read and stored,
+//! and the storage cost paid for newly stored blobs.
+
+use std::time::Instant;
+
+use chrono::{DateTime, Utc};
+use prometheus::{HistogramVec, IntCounter, IntCounterVec};
+use sui_types::base_types::ObjectID;
+use walrus_core::{BlobId, EncodingType, EpochCount};
+use walrus_sdk::{
+    client::{
+        byte_range::{BlobRange, ByteRange},
+        responses::{BlobAvailability, BlobStoreResult},
+    },
+    error::ClientResult,
+    store_when::StoreWhen,
+};
+use walrus_sui::{
+    client::{BlobPersistence, PostStoreAction},
+    types::move_structs::BlobWithAttribute,
+};
+use walrus_utils::metrics::Registry;
+
+use super::{WalrusReadClient, WalrusWriteClient};
+
+const READ_BLOB: &str = "blob";
+const READ_RANGE: &str = "range";
+const SUCCESS: &str = "success";
+const ERROR: &str = "error";
+
+walrus_utils::metrics::define_metric_set! {
+    #[namespace = "walrus_daemon"]
+    /// Metrics of the reads and stores performed by the daemon.
+    struct DaemonClientMetrics {
+        #[help = "The time in seconds taken to read blobs or byte ranges from Walrus, by the kind \
+        of read and its outcome."]
+        read_duration_seconds: HistogramVec{
+            labels: ["kind", "outcome"],
+            buckets: vec![0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0, 60.0, 120.0],
+        },
+
+        #[help = "The number of bytes read from Walrus, by the kind of read."]
+        read_bytes_total: IntCounterVec["kind"],
+
+        #[help = "The time in seconds taken to store blobs on Walrus, by the result of the store \
+        operation."]
+        store_duration_seconds: HistogramVec{
+            labels: ["result"],
+            buckets: vec![0.5, 1.0, 2.5, 5.0, 10.0, 30.0, 60.0, 120.0, 300.0, 600.0],
+        },
+
+        #[help = "The size in bytes of the blobs stored on Walrus, by the result of the store \
+        operation."]
+        stored_blob_size_bytes: HistogramVec{
+            labels: ["result"],
+            buckets: vec![1e3, 1e4, 1e5, 1e6, 1e7, 1e8, 1e9, 1e10],
+        },
+
+        #[help = "The total storage cost in FROST paid for newly created blobs, excluding gas."]
+        storage_cost_frost_total: IntCounter[],
+    }
+}
+
+/// A client that records the latency and size of the reads and stores performed through the
+/// wrapped client.
+#[derive(Debug)]
+pub(crate) struct MeteredClient<T> {
+    client: T,
+    metrics: DaemonClientMetrics,
+}
+
+impl<T> MeteredClient<T> {
+    /// Wraps the client, registering the metrics with the `registry`.
+    pub(crate) fn new(client: T, registry: &Registry) -> Self {
+        Self {
+            client,
+            metrics: DaemonClientMetrics::new(registry),
+        }
+    }
+
+    fn observe_read(&self, kind: &str, started_at: Instant, size: Option<usize>) {
+        let outcome = if size.is_some() { SUCCESS } else { ERROR };
+        walrus_utils::with_label!(self.metrics.read_duration_seconds, kind, outcome)
+            .observe(started_at.elapsed().as_secs_f64());
+        if let Some(size) = size {
+            walrus_utils::with_label!(self.metrics.read_bytes_total, kind).inc_by(size as u64);
+        }
+    }
+}
+
+impl<T: WalrusReadClient + Send + Sync> WalrusReadClient for MeteredClient<T> {
+    async fn read_blob(&self, blob_id: &BlobId) -> ClientResult<Vec<u8>> {
+        let started_at = Instant::now();
+        let result = self.client.read_blob(blob_id).await;
+        self.observe_read(READ_BLOB, started_at, result.as_ref().ok().map(Vec::len));
+        result
+    }
+
+    async fn read_blob_range(&self, blob_id: &BlobId, range: ByteRange) -> ClientResult<BlobRange> {
+        let started_at = Instant::now();
+        let result = self.client.read_blob_range(blob_id, range).await;
+        self.observe_read(
+            READ_RANGE,
+            started_at,
+            result.as_ref().ok().map(|range| range.data.len()),
+        );
+        result
+    }
+
+    async fn get_blob_by_object_id(
+        &self,
+        blob_object_id: &ObjectID,
+    ) -> ClientResult<BlobWithAttribute> {
+        self.client.get_blob_by_object_id(blob_object_id).await
+    }
+
+    fn check_blob_id(&self, blob_id: &BlobId) -> ClientResult<()> {
+        self.client.check_blob_id(blob_id)
+    }
+
+    async fn estimated_blob_expiry(&self, blob_id: &BlobId) -> ClientResult<Option<DateTime<Utc>>> {
+        self.client.estimated_blob_expiry(blob_id).await
+    }
+
+    async fn blob_availability(&self, blob_id: &BlobId) -> ClientResult<BlobAvailability> {
+        self.client.blob_availability(blob_id).await
+    }
+}
+
+impl<T: WalrusWriteClient + Send + Sync> WalrusWriteClient for MeteredClient<T> {
+    async fn write_blob(
+        &self,
+        blob: &[u8],
+        encoding_type: Option<EncodingType>,
+        epochs_ahead: EpochCount,
+        store_when: StoreWhen,
+        persistence: BlobPersistence,
+        post_store: PostStoreAction,
+    ) -> ClientResult<BlobStoreResult> {
+        let started_at = Instant::now();
+        let result = self
+            .client
+            .write_blob(
+                blob,
+                encoding_type,
+                epochs_ahead,
+                store_when,
+                persistence,
+                post_store,
+            )
+            .await;
+
+        let label = match &result {
+            Ok(BlobStoreResult::NewlyCreated { cost, .. }) => {
+                self.metrics.storage_cost_frost_total.inc_by(*cost);
+                "newly_created"
+            }
+            Ok(BlobStoreResult::AlreadyCertified { .. }) => "already_certified",
+            Ok(BlobStoreResult::MarkedInvalid { .. }) => "marked_invalid",
+            Ok(BlobStoreResult::Error { .. }) | Err(_) => ERROR,
+        };
+        walrus_utils::with_label!(self.metrics.store_duration_seconds, label)
+            .observe(started_at.elapsed().as_secs_f64());
+        walrus_utils::with_label!(self.metrics.stored_blob_size_bytes, label)
+            .observe(blob.len() as f64);
+        result
+    }
+
+    fn default_post_store_action(&self) -> PostStoreAction {
+        self.client.default_post_store_action()
+    }
+}
@@ -0,0 +1,255 @@
+// Copyright (c) Walrus Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Moderation of the blobs served and stored by the daemon.
+//!
+//! Blobs on the blocklist of the daemon are neither served nor stored, and requests for them are
+//! answered with `451 Unavailable For Legal Reasons`. Blob IDs are blocked through the blocklist
+//! file, through the moderation endpoints, which persist their changes to the file and require the
+//! administration token of the daemon as bearer token, or through a remote list that is fetched
+//! periodically. Changes to the blocklist are logged with the target [`BLOCKLIST_AUDIT_TARGET`],
+//! so that they can be routed to a separate audit log.
+
+use std::{collections::HashSet, time::Duration};
+
+use anyhow::Context as _;
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    Json,
+};
+use reqwest::Url;
+use serde::Deserialize;
+use walrus_core::BlobId;
+use walrus_proc_macros::RestApiError;
+use walrus_rest_client::api::errors::DAEMON_ERROR_DOMAIN as ERROR_DOMAIN;
+use walrus_sdk::blocklist::Blocklist;
+
+use crate::common::api::{BlobIdString, RestApiError};
+
+/// The path to list the blocked blobs.
+pub const BLOCKED_BLOBS_ENDPOINT: &str = "/v1/moderation/blocked";
+/// The path to block or unblock the blob with the given blob ID.
+pub const BLOCKED_BLOB_ENDPOINT: &str = "/v1/moderation/blocked/{blob_id}";
+/// The tracing target of the audit log entries for the blocklist.
+pub const BLOCKLIST_AUDIT_TARGET: &str = "walrus::blocklist_audit";
+
+/// The configuration of the remote list of blocked blob IDs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct RemoteBlocklistConfig {
+    /// The URL from which the list, in the YAML syntax of the blocklist file, is fetched.
+    pub url: Url,
+    /// The interval at which the list is fetched.
+    pub refresh_interval: Duration,
+}
+
+/// Periodically replaces the blob IDs of the remote list of the `blocklist` with the list at the
+/// configured URL.
+///
+/// If the list cannot be fetched or parsed, the previous list is kept.
+pub(super) async fn refresh_remote_blocklist(blocklist: Blocklist, config: RemoteBlocklistConfig) {
+    let http_client = reqwest::Client::builder()
+        .timeout(config.refresh_interval)
+        .build()
+        .expect("the HTTP client can be built without custom TLS settings");
+    let mut interval = tokio::time::interval(config.refresh_interval);
+    loop {
+        interval.tick().await;
+        let blob_ids = match fetch_remote_blocklist(&http_client, &config.url).await {
+            Ok(blob_ids) => blob_ids,
+            Err(error) => {
+                tracing::warn!(
+                    ?error,
+                    url = %config.url,
+                    "failed to fetch the remote blocklist, keeping the previous list"
+                );
+                continue;
+            }
+        };
+        let (added, removed) = blocklist.replace_remote_blob_ids(blob_ids);
+        for blob_id in added {
+            tracing::info!(
+                target: BLOCKLIST_AUDIT_TARGET,
+                walrus.blob_id = %blob_id,
+                url = %config.url,
+                "added blob to the blocklist from the remote list"
+            );
+        }
+        for blob_id in removed {
+            tracing::info!(
+                target: BLOCKLIST_AUDIT_TARGET,
+                walrus.blob_id = %blob_id,
+                url = %config.url,
+                "removed blob from the blocklist from the remote list"
+            );
+        }
+    }
+}
+
+async fn fetch_remote_blocklist(
+    http_client: &reqwest::Client,
+    url: &Url,
+) -> anyhow::Result<HashSet<BlobId>> {
+    let list = http_client
+        .get(url.clone())
+        .send()
+        .await?
+        .error_for_status()?
+        .text()
+        .await?;
+    parse_blob_ids(&list)
+}
+
+/// Parses a list of blob IDs in the YAML syntax of the blocklist file.
+fn parse_blob_ids(list: &str) -> anyhow::Result<HashSet<BlobId>> {
+    if list.trim().is_empty() {
+        return Ok(HashSet::new());
+    }
+    let blob_ids: Vec<String> =
+        serde_yaml::from_str(list).context("the remote blocklist is not a list of blob IDs")?;
+    blob_ids
+        .iter()
+        .map(|blob_id| {
+            blob_id
+                .parse()
+                .with_context(|| format!("invalid blob ID in the remote blocklist: {blob_id}"))
+        })
+        .collect()
+}
+
+/// The query parameters of the moderation endpoints.
+#[derive(Debug, Deserialize)]
+pub(super) struct ModerationQuery {
+    /// The reason for the change of the blocklist, which is recorded in the audit log.
+    #[serde(default)]
+    reason: Option<String>,
+}
+
+/// Adds the blob to the blocklist file.
+#[tracing::instrument(skip_all, fields(%blob_id))]
+pub(super) async fn block_blob(
+    State(blocklist): State<Blocklist>,
+    Path(BlobIdString(blob_id)): Path<BlobIdString>,
+    Query(ModerationQuery { reason }): Query<ModerationQuery>,
+) -> Result<StatusCode, ModerationError> {
+    if blocklist.insert(blob_id)? {
+        tracing::info!(
+            target: BLOCKLIST_AUDIT_TARGET,
+            walrus.blob_id = %blob_id,
+            reason = reason.as_deref(),
+            "added blob to the blocklist through the moderation API"
+        );
+    }
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Removes the blob from the blocklist file.
+///
+/// A blob that is also on the remote list remains blocked.
+#[tracing::instrument(skip_all, fields(%blob_id))]
+pub(super) async fn unblock_blob(
+    State(blocklist): State<Blocklist>,
+    Path(BlobIdString(blob_id)): Path<BlobIdString>,
+    Query(ModerationQuery { reason }): Query<ModerationQuery>,
+) -> Result<StatusCode, ModerationError> {
+    if !blocklist.remove(&blob_id)? {
+        return Err(ModerationError::NotBlocked);
+    }
+    tracing::info!(
+        target: BLOCKLIST_AUDIT_TARGET,
+        walrus.blob_id = %blob_id,
+        reason = reason.as_deref(),
+        "removed blob from the blocklist through the moderation API"
+    );
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Lists the blocked blobs, from both the blocklist file and the remote list.
+pub(super) async fn list_blocked_blobs(
+    State(blocklist): State<Blocklist>,
+) -> Json<Vec<BlobIdString>> {
+    let mut blob_ids: Vec<_> = blocklist.blocked_blob_ids().into_iter().collect();
+    blob_ids.sort();
+    Json(blob_ids.into_iter().map(BlobIdString).collect())
+}
+
+#[derive(Debug, thiserror::Error, RestApiError)]
+#[rest_api_error(domain = ERROR_DOMAIN)]
+pub(crate) enum ModerationError {
+    /// The blob is not on the blocklist file.
+    #[error("the blob is not on the blocklist file")]
+    #[rest_api_error(reason = "BLOB_NOT_BLOCKED", status = ApiStatusCode::NotFound)]
+    NotBlocked,
+
+    #[error(transparent)]
+    #[rest_api_error(delegate)]
+    Internal(#[from] anyhow::Error),
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{
+        body::Body,
+        http::{self, Method},
+        routing::{get, put},
+        Router,
+    };
+    use tower::ServiceExt as _;
+    use walrus_test_utils::Result as TestResult;
+
+    use super::*;
+
+    const BLOB_ID: &str = "E7_nNXvFU_3qZVu3OH1yycRG7LZlyn1-UxEDCDDqGGU";
+
+    #[test]
+    fn parses_remote_blocklists() -> TestResult {
+        let blob_id: BlobId = BLOB_ID.parse()?;
+        assert_eq!(parse_blob_ids(&format!("- {BLOB_ID}\n"))?, [blob_id].into());
+        assert_eq!(
+            parse_blob_ids(&format!("[\"{BLOB_ID}\"]"))?,
+            [blob_id].into()
+        );
+        assert!(parse_blob_ids(" \n")?.is_empty());
+        assert!(parse_blob_ids("- not-a-blob-id\n").is_err());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn blocks_and_unblocks_blobs() -> TestResult {
+        let directory = tempfile::tempdir()?;
+        let path = directory.path().join("blocklist.yaml");
+        let blocklist = Blocklist::new(&Some(path.clone()))?;
+        let router = Router::new()
+            .route(BLOCKED_BLOBS_ENDPOINT, get(list_blocked_blobs))
+            .route(BLOCKED_BLOB_ENDPOINT, put(block_blob).delete(unblock_blob))
+            .with_state(blocklist.clone());
+        let request = |method: Method, uri: String| {
+            router.clone().oneshot(
+                http::Request::builder()
+                    .method(method)
+                    .uri(uri)
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+        };
+        let blob_uri = format!("{BLOCKED_BLOBS_ENDPOINT}/{BLOB_ID}?reason=takedown");
+        let blob_id: BlobId = BLOB_ID.parse()?;
+
+        let response = request(Method::PUT, blob_uri.clone()).await?;
+        assert_eq!(response.status(), StatusCode::NO_CONTENT);
+        assert!(blocklist.is_blocked(&blob_id));
+        assert!(Blocklist::new(&Some(path.clone()))?.is_blocked(&blob_id));
+
+        let response = request(Method::GET, BLOCKED_BLOBS_ENDPOINT.to_owned()).await?;
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await?;
+        assert_eq!(serde_json::from_slice::<Vec<String>>(&body)?, [BLOB_ID]);
+
+        let response = request(Method::DELETE, blob_uri.clone()).await?;
+        assert_eq!(response.status(), StatusCode::NO_CONTENT);
+        assert!(!blocklist.is_blocked(&blob_id));
+
+        let response = request(Method::DELETE, blob_uri).await?;
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        Ok(())
+    }
+}
@@ -21,7 +21,7 @@ use crate::common::api::Binary;
 #[derive(OpenApi)]
 #[openapi(
     info(title = "Walrus Aggregator"),
-    paths(routes::get_blob, routes::get_blob_by_object_id),
+    paths(routes::get_blob, routes::head_blob, routes::get_blob_by_object_id),
     components(schemas(BlobId, Status,))
 )]
 pub(super) struct AggregatorApiDoc;
@@ -50,7 +50,12 @@ pub(super) struct PublisherApiDoc;
 #[derive(OpenApi)]
 #[openapi(
     info(title = "Walrus Daemon"),
-    paths(routes::get_blob, routes::put_blob, routes::get_blob_by_object_id),
+    paths(
+        routes::get_blob,
+        routes::head_blob,
+        routes::put_blob,
+        routes::get_blob_by_object_id
+    ),
     components(schemas(
         Blob,
         BlobId,
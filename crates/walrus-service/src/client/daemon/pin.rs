@@ -0,0 +1,166 @@
+// Copyright (c) Walrus Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Endpoints to pin blobs in the cache of the aggregator.
+//!
+//! Pinning a blob prefetches it into memory, where it is kept until it is unpinned, so that known
+//! hot blobs, such as the assets of a website, are always served with low latency. The endpoints
+//! require the administration token of the aggregator as bearer token.
+
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, Request, State},
+    http::StatusCode,
+    middleware::Next,
+    response::{IntoResponse, Response},
+    Json,
+};
+use axum_extra::{
+    headers::{authorization::Bearer, Authorization},
+    TypedHeader,
+};
+use sha2::{Digest, Sha256};
+use walrus_proc_macros::RestApiError;
+use walrus_rest_client::api::errors::DAEMON_ERROR_DOMAIN as ERROR_DOMAIN;
+use walrus_sdk::error::ClientError;
+
+use super::{
+    blob_cache::{BlobCache, PinnedBlobInfo},
+    routes::GetBlobError,
+    WalrusReadClient,
+};
+use crate::common::api::{BlobIdString, RestApiError};
+
+/// The path to list the pinned blobs.
+pub const PINNED_BLOBS_ENDPOINT: &str = "/v1/cache/pinned";
+/// The path to pin or unpin the blob with the given blob ID.
+pub const PINNED_BLOB_ENDPOINT: &str = "/v1/cache/pinned/{blob_id}";
+
+/// The SHA-256 digest of the administration token.
+///
+/// Tokens are compared by their digests, so that the comparison does not reveal the bytes of the
+/// token through timing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct AdminTokenDigest([u8; 32]);
+
+impl AdminTokenDigest {
+    pub(crate) fn new(token: &str) -> Self {
+        Self(Sha256::digest(token.as_bytes()).into())
+    }
+}
+
+/// Rejects requests that do not present the administration token as bearer token.
+pub(super) async fn admin_auth_layer(
+    State(expected): State<AdminTokenDigest>,
+    bearer_header: Option<TypedHeader<Authorization<Bearer>>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let is_authorized = bearer_header.is_some_and(|TypedHeader(header)| {
+        AdminTokenDigest::new(header.token().trim()) == expected
+    });
+    if !is_authorized {
+        return PinBlobError::InvalidToken.into_response();
+    }
+    next.run(request).await
+}
+
+/// Prefetches the blob and pins it in the cache.
+///
+/// Pinning a blob that is already pinned refreshes it.
+#[tracing::instrument(skip_all, fields(%blob_id))]
+pub(super) async fn pin_blob<T: WalrusReadClient + Send + Sync>(
+    State(cache): State<Arc<BlobCache<T>>>,
+    Path(BlobIdString(blob_id)): Path<BlobIdString>,
+) -> Result<Json<PinnedBlobInfo>, PinBlobError> {
+    cache.pin(&blob_id).await.map(Json).inspect_err(|error| {
+        if let PinBlobError::Read(GetBlobError::Internal(error)) = error {
+            tracing::error!(?error, "error pinning blob");
+        }
+    })
+}
+
+/// Unpins the blob from the cache.
+#[tracing::instrument(skip_all, fields(%blob_id))]
+pub(super) async fn unpin_blob<T: WalrusReadClient + Send + Sync>(
+    State(cache): State<Arc<BlobCache<T>>>,
+    Path(BlobIdString(blob_id)): Path<BlobIdString>,
+) -> Result<StatusCode, PinBlobError> {
+    if cache.unpin(&blob_id) {
+        Ok(StatusCode::NO_CONTENT)
+    } else {
+        Err(PinBlobError::NotPinned)
+    }
+}
+
+/// Lists the pinned blobs.
+pub(super) async fn list_pinned_blobs<T: WalrusReadClient + Send + Sync>(
+    State(cache): State<Arc<BlobCache<T>>>,
+) -> Json<Vec<PinnedBlobInfo>> {
+    Json(cache.pinned_blobs())
+}
+
+#[derive(Debug, thiserror::Error, RestApiError)]
+#[rest_api_error(domain = ERROR_DOMAIN)]
+pub(crate) enum PinBlobError {
+    /// The request does not present the administration token of the aggregator.
+    #[error("the administration token is missing or invalid")]
+    #[rest_api_error(reason = "INVALID_ADMIN_TOKEN", status = ApiStatusCode::Unauthenticated)]
+    InvalidToken,
+
+    /// The blob is not pinned.
+    #[error("the blob is not pinned")]
+    #[rest_api_error(reason = "BLOB_NOT_PINNED", status = ApiStatusCode::NotFound)]
+    NotPinned,
+
+    /// Pinning the blob would exceed the maximum total size of the pinned blobs.
+    #[error("the blob is larger than the {0} bytes available for pinned blobs")]
+    #[rest_api_error(reason = "INSUFFICIENT_CAPACITY", status = ApiStatusCode::ResourceExhausted)]
+    InsufficientCapacity(u64),
+
+    #[error(transparent)]
+    #[rest_api_error(delegate)]
+    Read(#[from] GetBlobError),
+}
+
+impl From<ClientError> for PinBlobError {
+    fn from(error: ClientError) -> Self {
+        GetBlobError::from(error).into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{body::Body, http, middleware, routing::get, Router};
+    use tower::ServiceExt as _;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn requires_the_admin_token() {
+        let router = Router::new()
+            .route(PINNED_BLOBS_ENDPOINT, get(|| async { "pinned" }))
+            .layer(middleware::from_fn_with_state(
+                AdminTokenDigest::new("secret"),
+                admin_auth_layer,
+            ));
+
+        for (authorization, expected) in [
+            (None, StatusCode::UNAUTHORIZED),
+            (Some("Bearer other"), StatusCode::UNAUTHORIZED),
+            (Some("Bearer secret"), StatusCode::OK),
+        ] {
+            let mut request = http::Request::builder().uri(PINNED_BLOBS_ENDPOINT);
+            if let Some(authorization) = authorization {
+                request = request.header("authorization", authorization);
+            }
+            let response = router
+                .clone()
+                .oneshot(request.body(Body::empty()).unwrap())
+                .await
+                .unwrap();
+            assert_eq!(response.status(), expected, "{authorization:?}");
+        }
+    }
+}
@@ -0,0 +1,464 @@
+// Copyright (c) Walrus Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Streaming the progress of store requests to the clients that sent them.
+//!
+//! A client chooses a random progress ID, subscribes to the server-sent events at
+//! [`BLOB_PROGRESS_ENDPOINT`], and sends its store request with the same ID in the `progress_id`
+//! query parameter. The subscriber then receives the number of bytes received by the publisher,
+//! the encoding of the blob, the confirmations of the storage nodes, and the digest of the
+//! certification transaction, followed by either the result of the store or its failure.
+//!
+//! Progress is only followed while the store request is in progress, and for at most
+//! [`MAX_FOLLOWED_UPLOADS`] requests at a time. Subscribers that connect after the upload started
+//! receive the events emitted so far; subscriptions to uploads that have not started yet or have
+//! already finished are rejected.
+
+use std::{
+    collections::HashMap,
+    future::Future,
+    sync::{Arc, Mutex},
+};
+
+use axum::{
+    body::{Body, Bytes},
+    extract::{Path, Query, Request, State},
+    http::StatusCode,
+    middleware::Next,
+    response::{
+        sse::{Event, KeepAlive, Sse},
+        IntoResponse,
+        Response,
+    },
+};
+use futures::{stream, StreamExt as _};
+use serde::{Deserialize, Serialize};
+use tokio::sync::{
+    broadcast::{self, error::RecvError},
+    mpsc,
+};
+use walrus_sdk::{
+    client::{
+        progress::{with_store_progress, StoreProgressEvent},
+        responses::BlobStoreResult,
+    },
+    error::ClientResult,
+};
+use walrus_utils::http::{http_body::Frame, BodyVisitor, VisitBody};
+
+/// The path to subscribe to the progress events of the store request with the given progress ID.
+pub const BLOB_PROGRESS_ENDPOINT: &str = "/v1/blobs/progress/{progress_id}";
+
+/// The minimum and maximum length of a progress ID.
+const PROGRESS_ID_LENGTH: std::ops::RangeInclusive<usize> = 16..=128;
+/// The number of bytes received between two consecutive reports of the received bytes.
+const RECEIVED_REPORT_INTERVAL_BYTES: u64 = 1024 * 1024;
+/// The number of events buffered for subscribers that are slow to consume them.
+const EVENT_CHANNEL_CAPACITY: usize = 1024;
+/// The maximum number of store requests whose progress is followed at the same time.
+const MAX_FOLLOWED_UPLOADS: usize = 1024;
+
+/// The query parameter that associates a store request with a progress ID.
+#[derive(Debug, Deserialize)]
+pub(super) struct ProgressQuery {
+    #[serde(default)]
+    progress_id: Option<String>,
+}
+
+/// An event in the progress of a store request.
+#[derive(Debug, Clone, Serialize)]
+#[serde(untagged)]
+enum ProgressEvent {
+    /// The publisher received the given number of bytes of the blob so far.
+    Received { bytes: u64 },
+    /// An event of the store operation.
+    Store(StoreProgressEvent),
+    /// The store request completed with the given result.
+    Completed { result: BlobStoreResult },
+    /// The store request failed with the given status, which is unknown if the request was
+    /// aborted.
+    Failed { status: Option<u16> },
+}
+
+impl ProgressEvent {
+    fn name(&self) -> &'static str {
+        match self {
+            Self::Received { .. } => "received",
+            Self::Store(StoreProgressEvent::Encoded { .. }) => "encoded",
+            Self::Store(StoreProgressEvent::NodeConfirmation { .. }) => "nodeConfirmation",
+            Self::Store(StoreProgressEvent::Certified { .. }) => "certified",
+            Self::Completed { .. } => "completed",
+            Self::Failed { .. } => "failed",
+        }
+    }
+
+    fn is_terminal(&self) -> bool {
+        matches!(self, Self::Completed { .. } | Self::Failed { .. })
+    }
+
+    fn to_sse_event(&self) -> Result<Event, axum::Error> {
+        Event::default().event(self.name()).json_data(self)
+    }
+}
+
+/// The events of an upload in progress and the channel on which new events are sent to its
+/// subscribers.
+#[derive(Debug)]
+struct UploadProgress {
+    history: Vec<ProgressEvent>,
+    sender: broadcast::Sender<ProgressEvent>,
+}
+
+/// The progress of the store requests in progress that are followed by their clients, by
+/// progress ID.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct UploadProgressRegistry {
+    uploads: Arc<Mutex<HashMap<String, UploadProgress>>>,
+}
+
+impl UploadProgressRegistry {
+    /// Starts following the progress of an upload.
+    ///
+    /// Returns `None` if an upload with the same ID is in progress, or if the maximum number of
+    /// uploads is already followed.
+    fn start(&self, progress_id: &str) -> Option<UploadProgressHandle> {
+        let mut uploads = self.lock();
+        if uploads.contains_key(progress_id) || uploads.len() >= MAX_FOLLOWED_UPLOADS {
+            return None;
+        }
+
+        let sender = broadcast::channel(EVENT_CHANNEL_CAPACITY).0;
+        uploads.insert(
+            progress_id.to_owned(),
+            UploadProgress {
+                history: vec![],
+                sender: sender.clone(),
+            },
+        );
+        Some(UploadProgressHandle {
+            registry: self.clone(),
+            progress_id: progress_id.to_owned(),
+            sender,
+        })
+    }
+
+    /// Returns the events of the upload so far and a receiver for the ones that follow, or `None`
+    /// if no upload with the ID is in progress.
+    fn subscribe(
+        &self,
+        progress_id: &str,
+    ) -> Option<(Vec<ProgressEvent>, broadcast::Receiver<ProgressEvent>)> {
+        let uploads = self.lock();
+        let upload = uploads.get(progress_id)?;
+        Some((upload.history.clone(), upload.sender.subscribe()))
+    }
+
+    /// Sends the event to the subscribers of the upload, and stops following the upload if the
+    /// event is terminal.
+    fn report(&self, handle: &UploadProgressHandle, event: ProgressEvent) {
+        let mut uploads = self.lock();
+        // The upload may have finished, and another one may have started with the same ID.
+        let Some(upload) = uploads
+            .get_mut(&handle.progress_id)
+            .filter(|upload| upload.sender.same_channel(&handle.sender))
+        else {
+            return;
+        };
+
+        if event.is_terminal() {
+            uploads.remove(&handle.progress_id);
+        } else {
+            // Only the latest number of received bytes is relevant to late subscribers.
+            if let ProgressEvent::Received { .. } = event {
+                upload
+                    .history
+                    .retain(|event| !matches!(event, ProgressEvent::Received { .. }));
+            }
+            upload.history.push(event.clone());
+        }
+        // There may be no subscribers, which is not an error.
+        let _ = handle.sender.send(event);
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<String, UploadProgress>> {
+        self.uploads
+            .lock()
+            .expect("the lock is never held across a panic")
+    }
+}
+
+/// A handle to report the progress of a store request.
+#[derive(Debug, Clone)]
+pub(crate) struct UploadProgressHandle {
+    registry: UploadProgressRegistry,
+    progress_id: String,
+    sender: broadcast::Sender<ProgressEvent>,
+}
+
+impl UploadProgressHandle {
+    fn report(&self, event: ProgressEvent) {
+        self.registry.report(self, event);
+    }
+
+    /// Runs the store operation, reporting its progress and, if it succeeds, its result.
+    pub(super) async fn follow<F>(&self, store: F) -> ClientResult<BlobStoreResult>
+    where
+        F: Future<Output = ClientResult<BlobStoreResult>>,
+    {
+        let (sender, mut receiver) = mpsc::unbounded_channel();
+        let forward_events = async {
+            // Ends once the store completes, which drops the sender.
+            while let Some(event) = receiver.recv().await {
+                self.report(ProgressEvent::Store(event));
+            }
+        };
+        let (result, ()) = tokio::join!(with_store_progress(sender, store), forward_events);
+
+        if let Ok(
+            stored @ (BlobStoreResult::NewlyCreated { .. }
+            | BlobStoreResult::AlreadyCertified { .. }),
+        ) = &result
+        {
+            self.report(ProgressEvent::Completed {
+                result: stored.clone(),
+            });
+        }
+        result
+    }
+}
+
+/// Reports the upload as failed when dropped, unless it has already finished.
+///
+/// This covers requests that are aborted, for example, because the client disconnected.
+#[derive(Debug)]
+struct FailOnDrop(UploadProgressHandle);
+
+impl Drop for FailOnDrop {
+    fn drop(&mut self) {
+        self.0.report(ProgressEvent::Failed { status: None });
+    }
+}
+
+/// Reports the number of bytes of the blob received so far.
+#[derive(Debug)]
+struct ReceivedBytesReporter {
+    handle: UploadProgressHandle,
+    received: u64,
+    reported: u64,
+}
+
+impl ReceivedBytesReporter {
+    fn report(&mut self) {
+        self.reported = self.received;
+        self.handle.report(ProgressEvent::Received {
+            bytes: self.received,
+        });
+    }
+}
+
+impl BodyVisitor<Bytes, axum::Error> for ReceivedBytesReporter {
+    fn frame_polled(&mut self, maybe_result: Option<Result<&Frame<Bytes>, &axum::Error>>) {
+        match maybe_result {
+            Some(Ok(frame)) => {
+                if let Some(data) = frame.data_ref() {
+                    self.received += u64::try_from(data.len()).expect("chunk length fits a u64");
+                    if self.received - self.reported >= RECEIVED_REPORT_INTERVAL_BYTES {
+                        self.report();
+                    }
+                }
+            }
+            None if self.received > self.reported => self.report(),
+            _ => (),
+        }
+    }
+}
+
+fn is_valid_progress_id(progress_id: &str) -> bool {
+    PROGRESS_ID_LENGTH.contains(&progress_id.len())
+        && progress_id
+            .bytes()
+            .all(|byte| byte.is_ascii_alphanumeric() || byte == b'-' || byte == b'_')
+}
+
+fn invalid_progress_id() -> Response {
+    (
+        StatusCode::BAD_REQUEST,
+        format!(
+            "the progress ID must consist of {} to {} ASCII letters, digits, '-', or '_'",
+            PROGRESS_ID_LENGTH.start(),
+            PROGRESS_ID_LENGTH.end()
+        ),
+    )
+        .into_response()
+}
+
+/// Follows the progress of store requests that specify a progress ID.
+///
+/// The handle to report the progress is added to the extensions of the request, so that the
+/// handler can report the progress of the store operation.
+pub(super) async fn progress_layer(
+    State(registry): State<UploadProgressRegistry>,
+    Query(ProgressQuery { progress_id }): Query<ProgressQuery>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let Some(progress_id) = progress_id else {
+        return next.run(request).await;
+    };
+    if !is_valid_progress_id(&progress_id) {
+        return invalid_progress_id();
+    }
+    let Some(handle) = registry.start(&progress_id) else {
+        tracing::debug!(
+            %progress_id,
+            "an upload with the same progress ID is in progress or too many uploads are followed; \
+            not following the progress",
+        );
+        return next.run(request).await;
+    };
+
+    let guard = FailOnDrop(handle.clone());
+    let mut request = request.map(|body| {
+        Body::new(VisitBody::new(
+            body,
+            ReceivedBytesReporter {
+                handle: handle.clone(),
+                received: 0,
+                reported: 0,
+            },
+        ))
+    });
+    request.extensions_mut().insert(handle);
+
+    let response = next.run(request).await;
+    guard.0.report(ProgressEvent::Failed {
+        status: Some(response.status().as_u16()),
+    });
+    response
+}
+
+/// Streams the progress events of the store request with the given progress ID.
+///
+/// The events emitted before the subscription are sent first, and the stream ends after the
+/// store request completed or failed. Responds with `404 Not Found` if no store request with the
+/// progress ID is in progress.
+pub(super) async fn stream_progress(
+    State(registry): State<UploadProgressRegistry>,
+    Path(progress_id): Path<String>,
+) -> Response {
+    if !is_valid_progress_id(&progress_id) {
+        return invalid_progress_id();
+    }
+
+    let Some((history, receiver)) = registry.subscribe(&progress_id) else {
+        return (
+            StatusCode::NOT_FOUND,
+            "no store request with the progress ID is in progress",
+        )
+            .into_response();
+    };
+    let live_events = stream::unfold((receiver, false), |(mut receiver, finished)| async move {
+        if finished {
+            return None;
+        }
+        loop {
+            match receiver.recv().await {
+                Ok(event) => {
+                    let finished = event.is_terminal();
+                    return Some((event, (receiver, finished)));
+                }
+                // Missed confirmations are superseded by the later ones.
+                Err(RecvError::Lagged(_)) => continue,
+                Err(RecvError::Closed) => return None,
+            }
+        }
+    });
+    let events = stream::iter(history)
+        .chain(live_events)
+        .map(|event| event.to_sse_event());
+
+    Sse::new(events)
+        .keep_alive(KeepAlive::default())
+        .into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use walrus_core::BlobId;
+
+    use super::*;
+
+    const PROGRESS_ID: &str = "0123456789abcdef";
+
+    #[test]
+    fn validates_progress_ids() {
+        assert!(is_valid_progress_id(PROGRESS_ID));
+        assert!(is_valid_progress_id(&"a-_".repeat(40)));
+        assert!(!is_valid_progress_id("too-short"));
+        assert!(!is_valid_progress_id(&"a".repeat(129)));
+        assert!(!is_valid_progress_id("0123456789abcdef/"));
+    }
+
+    #[tokio::test]
+    async fn late_subscribers_receive_past_events() {
+        let registry = UploadProgressRegistry::default();
+        assert!(registry.subscribe(PROGRESS_ID).is_none());
+        let handle = registry
+            .start(PROGRESS_ID)
+            .expect("the upload was not started");
+        assert!(registry.start(PROGRESS_ID).is_none());
+
+        handle.report(ProgressEvent::Received { bytes: 1 });
+        handle.report(ProgressEvent::Received { bytes: 2 });
+        handle.report(ProgressEvent::Store(StoreProgressEvent::Encoded {
+            blob_id: BlobId([1; 32]),
+        }));
+        let (history, mut receiver) = registry
+            .subscribe(PROGRESS_ID)
+            .expect("the upload is in progress");
+        handle.report(ProgressEvent::Failed { status: Some(500) });
+        handle.report(ProgressEvent::Received { bytes: 3 });
+
+        let names: Vec<_> = history.iter().map(ProgressEvent::name).collect();
+        assert_eq!(names, ["received", "encoded"]);
+        assert!(matches!(history[0], ProgressEvent::Received { bytes: 2 }));
+        assert_eq!(receiver.recv().await.unwrap().name(), "failed");
+        assert!(receiver.try_recv().is_err());
+        assert!(registry.subscribe(PROGRESS_ID).is_none());
+    }
+
+    #[test]
+    fn finished_uploads_are_evicted() {
+        let registry = UploadProgressRegistry::default();
+        let first = registry
+            .start(PROGRESS_ID)
+            .expect("the upload was not started");
+        first.report(ProgressEvent::Failed { status: None });
+        assert!(registry.lock().is_empty());
+
+        // The events of the finished upload do not reach an upload with the same ID.
+        let second = registry
+            .start(PROGRESS_ID)
+            .expect("the previous upload finished");
+        first.report(ProgressEvent::Failed { status: None });
+        assert!(registry.subscribe(PROGRESS_ID).is_some());
+        second.report(ProgressEvent::Failed { status: None });
+        assert!(registry.lock().is_empty());
+    }
+
+    #[test]
+    fn limits_the_number_of_followed_uploads() {
+        let registry = UploadProgressRegistry::default();
+        let handles: Vec<_> = (0..MAX_FOLLOWED_UPLOADS)
+            .map(|index| {
+                registry
+                    .start(&format!("{PROGRESS_ID}{index}"))
+                    .expect("the limit is not reached")
+            })
+            .collect();
+        assert!(registry.start(PROGRESS_ID).is_none());
+
+        handles[0].report(ProgressEvent::Failed { status: None });
+        assert!(registry.start(PROGRESS_ID).is_some());
+    }
+}
@@ -0,0 +1,519 @@
+// Copyright (c) Walrus Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Rate limiting of the requests to the daemon.
+//!
+//! The requests to each configured route are limited per client, which is identified by its API
+//! key, if the request presents one of the API keys of the publisher, or otherwise by its IP
+//! address. Both the number of requests and the number of bytes in the bodies of the requests and
+//! responses are limited with token buckets, which allow bursts up to their capacity. Responses on
+//! routes with a request limit carry the `RateLimit-Limit`, `RateLimit-Remaining`, and
+//! `RateLimit-Reset` headers, and rejected requests are answered with `429 Too Many Requests` and
+//! a `Retry-After` header.
+
+use std::{
+    collections::HashMap,
+    net::{IpAddr, SocketAddr},
+    sync::{Arc, Mutex, Weak},
+    time::Duration,
+};
+
+use axum::{
+    body::HttpBody as _,
+    extract::{ConnectInfo, MatchedPath, Request, State},
+    http::{
+        header::{AUTHORIZATION, CONTENT_LENGTH, RETRY_AFTER},
+        HeaderMap,
+        HeaderName,
+        HeaderValue,
+    },
+    middleware::Next,
+    response::Response,
+};
+use http_body::SizeHint;
+use tokio::time::Instant;
+use walrus_proc_macros::RestApiError;
+use walrus_rest_client::api::errors::DAEMON_ERROR_DOMAIN as ERROR_DOMAIN;
+
+use super::auth::ApiKeys;
+use crate::{
+    client::config::{RateLimitConfig, RouteRateLimits},
+    common::api::RestApiError,
+};
+
+/// The maximum number of requests per second of a client, or the capacity of its bucket.
+const RATE_LIMIT_LIMIT: &str = "ratelimit-limit";
+/// The number of requests the client can still make at once.
+const RATE_LIMIT_REMAINING: &str = "ratelimit-remaining";
+/// The number of seconds until the client can make the maximum number of requests again.
+const RATE_LIMIT_RESET: &str = "ratelimit-reset";
+/// The rate-limit headers exposed to browsers.
+pub(super) const EXPOSED_HEADERS: [&str; 4] = [
+    RATE_LIMIT_LIMIT,
+    RATE_LIMIT_REMAINING,
+    RATE_LIMIT_RESET,
+    "retry-after",
+];
+/// The interval at which the buckets of idle clients are removed.
+const PRUNE_INTERVAL: Duration = Duration::from_secs(60);
+
+/// A rate limit, enforced with a token bucket.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Limit {
+    /// The number of tokens added to the bucket per second.
+    rate: f64,
+    /// The maximum number of tokens in the bucket.
+    capacity: f64,
+}
+
+/// The limits of a route.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct RouteLimits {
+    requests: Option<Limit>,
+    bytes: Option<Limit>,
+}
+
+impl From<RouteRateLimits> for RouteLimits {
+    fn from(limits: RouteRateLimits) -> Self {
+        Self {
+            requests: limits.requests_per_second.map(|rate| Limit {
+                rate,
+                capacity: limits.request_burst.map_or(rate, f64::from).max(1.0),
+            }),
+            bytes: limits.bytes_per_second.map(|rate| Limit {
+                rate: rate as f64,
+                capacity: limits.byte_burst.unwrap_or(rate) as f64,
+            }),
+        }
+    }
+}
+
+/// A token bucket.
+#[derive(Debug, Clone, Copy)]
+struct TokenBucket {
+    /// The number of tokens, which is negative if more bytes were transferred than allowed.
+    tokens: f64,
+    updated_at: Instant,
+}
+
+impl TokenBucket {
+    /// Creates a full bucket.
+    fn full(limit: Option<Limit>, now: Instant) -> Self {
+        Self {
+            tokens: limit.map_or(0.0, |limit| limit.capacity),
+            updated_at: now,
+        }
+    }
+
+    /// Adds the tokens for the time elapsed since the last update, up to the capacity.
+    fn refill(&mut self, limit: Limit, now: Instant) {
+        let elapsed = now.saturating_duration_since(self.updated_at).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * limit.rate).min(limit.capacity);
+        self.updated_at = now;
+    }
+
+    /// Returns the time until the bucket holds the given number of tokens.
+    fn time_until(&self, limit: Limit, tokens: f64) -> Duration {
+        Duration::from_secs_f64(((tokens - self.tokens) / limit.rate).max(0.0))
+    }
+
+    /// Returns `true` if the bucket is full at `now`.
+    fn is_full(&self, limit: Option<Limit>, now: Instant) -> bool {
+        let Some(limit) = limit else {
+            return true;
+        };
+        let mut bucket = *self;
+        bucket.refill(limit, now);
+        bucket.tokens >= limit.capacity
+    }
+}
+
+/// The token buckets of a client on a route.
+#[derive(Debug, Clone, Copy)]
+struct ClientBuckets {
+    limits: RouteLimits,
+    requests: TokenBucket,
+    bytes: TokenBucket,
+}
+
+impl ClientBuckets {
+    fn new(limits: RouteLimits, now: Instant) -> Self {
+        Self {
+            limits,
+            requests: TokenBucket::full(limits.requests, now),
+            bytes: TokenBucket::full(limits.bytes, now),
+        }
+    }
+
+    /// Admits a request with a body of `request_bytes` bytes, or returns the time after which the
+    /// client can retry.
+    ///
+    /// Returns the state of the request limit, if there is one.
+    fn admit(
+        &mut self,
+        request_bytes: u64,
+        now: Instant,
+    ) -> Result<Option<RateLimitStatus>, Duration> {
+        if let Some(limit) = self.limits.bytes {
+            self.bytes.refill(limit, now);
+            // Responses are charged after the fact, so the bucket can be in debt.
+            if self.bytes.tokens <= 0.0 {
+                return Err(self.bytes.time_until(limit, 1.0));
+            }
+        }
+        let status = match self.limits.requests {
+            Some(limit) => {
+                self.requests.refill(limit, now);
+                if self.requests.tokens < 1.0 {
+                    return Err(self.requests.time_until(limit, 1.0));
+                }
+                self.requests.tokens -= 1.0;
+                Some(RateLimitStatus {
+                    limit: limit.capacity,
+                    remaining: self.requests.tokens,
+                    reset: self.requests.time_until(limit, limit.capacity),
+                })
+            }
+            None => None,
+        };
+        self.charge_bytes(request_bytes);
+        Ok(status)
+    }
+
+    /// Charges the transferred bytes to the byte bucket.
+    fn charge_bytes(&mut self, bytes: u64) {
+        if self.limits.bytes.is_some() {
+            self.bytes.tokens -= bytes as f64;
+        }
+    }
+
+    /// Returns `true` if both buckets are full, so that they can be dropped without affecting the
+    /// client.
+    fn is_idle(&self, now: Instant) -> bool {
+        self.requests.is_full(self.limits.requests, now)
+            && self.bytes.is_full(self.limits.bytes, now)
+    }
+}
+
+/// The state of the request limit of a client after a request.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct RateLimitStatus {
+    limit: f64,
+    remaining: f64,
+    reset: Duration,
+}
+
+impl RateLimitStatus {
+    fn insert_headers(&self, headers: &mut HeaderMap) {
+        headers.insert(
+            RATE_LIMIT_LIMIT,
+            HeaderValue::from(self.limit.floor() as u64),
+        );
+        headers.insert(
+            RATE_LIMIT_REMAINING,
+            HeaderValue::from(self.remaining.floor() as u64),
+        );
+        headers.insert(RATE_LIMIT_RESET, HeaderValue::from(seconds(self.reset)));
+    }
+}
+
+/// Rounds the duration up to whole seconds.
+fn seconds(duration: Duration) -> u64 {
+    duration.as_secs_f64().ceil() as u64
+}
+
+/// The identity of a client for rate limiting.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum ClientId {
+    /// The identity of the holder of an API key.
+    ApiKey(String),
+    /// The IP address of the client.
+    Ip(IpAddr),
+}
+
+/// The rate limiter of the daemon.
+#[derive(Debug)]
+pub(crate) struct RateLimiter {
+    routes: HashMap<String, RouteLimits>,
+    client_ip_header: Option<HeaderName>,
+    api_keys: ApiKeys,
+    buckets: Mutex<HashMap<(String, ClientId), ClientBuckets>>,
+}
+
+impl RateLimiter {
+    /// Creates a rate limiter with the configured limits, which identifies clients with the given
+    /// API keys by the identity of the key holder.
+    pub(crate) fn new(config: RateLimitConfig, api_keys: ApiKeys) -> Self {
+        Self {
+            routes: config
+                .routes
+                .into_iter()
+                .map(|(route, limits)| (route, limits.into()))
+                .collect(),
+            client_ip_header: config.client_ip_header,
+            api_keys,
+            buckets: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Identifies the client that sent the request.
+    ///
+    /// Returns `None` if the request carries neither a valid API key nor the IP address of the
+    /// client.
+    fn client_id(&self, request: &Request) -> Option<ClientId> {
+        let api_key_holder = request
+            .headers()
+            .get(AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "))
+            .and_then(|token| self.api_keys.holder(token.trim()));
+        if let Some(holder) = api_key_holder {
+            return Some(ClientId::ApiKey(holder.identity.clone()));
+        }
+        let forwarded_ip = self.client_ip_header.as_ref().and_then(|header| {
+            request
+                .headers()
+                .get_all(header)
+                .iter()
+                .next_back()
+                .and_then(|value| value.to_str().ok())
+                .and_then(|value| value.rsplit(',').next())
+                .and_then(|address| address.trim().parse().ok())
+        });
+        forwarded_ip
+            .or_else(|| {
+                request
+                    .extensions()
+                    .get::<ConnectInfo<SocketAddr>>()
+                    .map(|ConnectInfo(address)| address.ip())
+            })
+            .map(ClientId::Ip)
+    }
+
+    fn admit(
+        &self,
+        key: &(String, ClientId),
+        limits: RouteLimits,
+        request_bytes: u64,
+    ) -> Result<Option<RateLimitStatus>, Duration> {
+        let now = Instant::now();
+        self.buckets
+            .lock()
+            .expect("mutex should not be poisoned")
+            .entry(key.clone())
+            .or_insert_with(|| ClientBuckets::new(limits, now))
+            .admit(request_bytes, now)
+    }
+
+    fn charge_bytes(&self, key: &(String, ClientId), bytes: u64) {
+        if let Some(buckets) = self
+            .buckets
+            .lock()
+            .expect("mutex should not be poisoned")
+            .get_mut(key)
+        {
+            buckets.charge_bytes(bytes);
+        }
+    }
+
+    /// Periodically removes the buckets of clients that have been idle long enough for their
+    /// buckets to be full again, until the rate limiter is dropped.
+    pub(super) async fn prune_idle_clients(rate_limiter: Weak<Self>) {
+        let mut interval = tokio::time::interval(PRUNE_INTERVAL);
+        loop {
+            interval.tick().await;
+            let Some(rate_limiter) = rate_limiter.upgrade() else {
+                return;
+            };
+            let now = Instant::now();
+            rate_limiter
+                .buckets
+                .lock()
+                .expect("mutex should not be poisoned")
+                .retain(|_, buckets| !buckets.is_idle(now));
+        }
+    }
+}
+
+/// Enforces the rate limits of the route of the request.
+pub(super) async fn rate_limit_layer(
+    State(rate_limiter): State<Arc<RateLimiter>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let Some((route, limits)) = request
+        .extensions()
+        .get::<MatchedPath>()
+        .and_then(|route| rate_limiter.routes.get_key_value(route.as_str()))
+    else {
+        return next.run(request).await;
+    };
+    let Some(client_id) = rate_limiter.client_id(&request) else {
+        tracing::debug!("not rate limiting a request from an unknown client");
+        return next.run(request).await;
+    };
+    let key = (route.clone(), client_id);
+    let request_bytes = body_size(request.headers(), request.body().size_hint());
+    let status = match rate_limiter.admit(&key, *limits, request_bytes) {
+        Ok(status) => status,
+        Err(retry_after) => {
+            tracing::debug!(?key, ?retry_after, "rejecting a rate-limited request");
+            let retry_after = seconds(retry_after).max(1);
+            let mut response = RateLimitError::TooManyRequests(retry_after).to_response();
+            response
+                .headers_mut()
+                .insert(RETRY_AFTER, HeaderValue::from(retry_after));
+            return response;
+        }
+    };
+
+    let mut response = next.run(request).await;
+    let response_bytes = body_size(response.headers(), response.body().size_hint());
+    rate_limiter.charge_bytes(&key, response_bytes);
+    if let Some(status) = status {
+        status.insert_headers(response.headers_mut());
+    }
+    response
+}
+
+/// Returns the size of a body, as announced in the `Content-Length` header or known from the body
+/// itself, or 0 if it is unknown.
+fn body_size(headers: &HeaderMap, size_hint: SizeHint) -> u64 {
+    headers
+        .get(CONTENT_LENGTH)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.parse().ok())
+        .or_else(|| size_hint.exact())
+        .unwrap_or(0)
+}
+
+#[derive(Debug, thiserror::Error, RestApiError)]
+#[rest_api_error(domain = ERROR_DOMAIN)]
+pub(crate) enum RateLimitError {
+    /// The client exceeded the rate limit of the route.
+    #[error("the rate limit was exceeded, retry after {0} seconds")]
+    #[rest_api_error(reason = "RATE_LIMITED", status = ApiStatusCode::ResourceExhausted)]
+    TooManyRequests(u64),
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{
+        body::Body,
+        http::{self, StatusCode},
+        middleware,
+        routing::get,
+        Router,
+    };
+    use tower::ServiceExt as _;
+
+    use super::*;
+
+    fn limits(requests_per_second: Option<f64>, bytes_per_second: Option<u64>) -> RouteLimits {
+        RouteRateLimits {
+            requests_per_second,
+            request_burst: Some(2),
+            bytes_per_second,
+            byte_burst: None,
+        }
+        .into()
+    }
+
+    #[test]
+    fn limits_requests_with_bursts() {
+        let now = Instant::now();
+        let mut buckets = ClientBuckets::new(limits(Some(0.5), None), now);
+
+        let status = buckets.admit(0, now).expect("within the burst");
+        assert_eq!(status.map(|status| status.remaining), Some(1.0));
+        let status = buckets.admit(0, now).expect("within the burst");
+        assert_eq!(
+            status.map(|status| status.reset),
+            Some(Duration::from_secs(4))
+        );
+        assert_eq!(buckets.admit(0, now), Err(Duration::from_secs(2)));
+
+        let later = now + Duration::from_secs(2);
+        assert!(buckets.admit(0, later).is_ok());
+        assert!(!buckets.is_idle(later));
+        assert!(buckets.is_idle(later + Duration::from_secs(4)));
+    }
+
+    #[test]
+    fn limits_bytes_after_the_fact() {
+        let now = Instant::now();
+        let mut buckets = ClientBuckets::new(limits(None, Some(1000)), now);
+
+        assert_eq!(buckets.admit(600, now), Ok(None));
+        buckets.charge_bytes(1400);
+        assert_eq!(buckets.admit(0, now), Err(Duration::from_secs_f64(1.001)));
+        assert!(buckets.admit(0, now + Duration::from_secs(2)).is_ok());
+    }
+
+    fn router(client_ip_header: Option<&'static str>) -> Router {
+        let config = RateLimitConfig {
+            routes: [(
+                "/limited/{id}".to_owned(),
+                RouteRateLimits {
+                    requests_per_second: Some(1.0),
+                    ..Default::default()
+                },
+            )]
+            .into(),
+            client_ip_header: client_ip_header.map(HeaderName::from_static),
+        };
+        let rate_limiter = Arc::new(RateLimiter::new(config, ApiKeys::default()));
+        Router::new()
+            .route("/limited/{id}", get(|| async { "limited" }))
+            .route("/unlimited", get(|| async { "unlimited" }))
+            .layer(middleware::from_fn_with_state(
+                rate_limiter,
+                rate_limit_layer,
+            ))
+    }
+
+    async fn get_response(router: &Router, uri: &str, forwarded_for: &str) -> Response {
+        let mut request = http::Request::builder()
+            .uri(uri)
+            .header("x-forwarded-for", forwarded_for)
+            .body(Body::empty())
+            .unwrap();
+        request
+            .extensions_mut()
+            .insert(ConnectInfo(SocketAddr::from(([10, 0, 0, 1], 1234))));
+        router.clone().oneshot(request).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn rejects_requests_above_the_limit_per_client() {
+        let router = router(Some("x-forwarded-for"));
+
+        let response = get_response(&router, "/limited/1", "1.1.1.1, 2.2.2.2").await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()[RATE_LIMIT_LIMIT], "1");
+        assert_eq!(response.headers()[RATE_LIMIT_REMAINING], "0");
+        assert_eq!(response.headers()[RATE_LIMIT_RESET], "1");
+
+        // The limit applies to the route, not to the path.
+        let response = get_response(&router, "/limited/2", "3.3.3.3, 2.2.2.2").await;
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(response.headers()[RETRY_AFTER], "1");
+
+        let response = get_response(&router, "/limited/1", "2.2.2.2, 3.3.3.3").await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let response = get_response(&router, "/unlimited", "2.2.2.2").await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert!(!response.headers().contains_key(RATE_LIMIT_LIMIT));
+    }
+
+    #[tokio::test]
+    async fn identifies_clients_by_peer_address_without_proxy_header() {
+        let router = router(None);
+
+        let response = get_response(&router, "/limited/1", "1.1.1.1").await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let response = get_response(&router, "/limited/1", "2.2.2.2").await;
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+    }
+}
@@ -1,32 +1,56 @@
 // Copyright (c) Walrus Foundation
 // SPDX-License-Identifier: Apache-2.0
 
-use std::{collections::HashSet, str::FromStr, sync::Arc, time::Duration};
+use std::{collections::HashSet, fmt::Display, str::FromStr, sync::Arc};
 
-use anyhow::anyhow;
+use anyhow::{anyhow, Context as _};
 use axum::{
     body::Bytes,
     extract::{Path, Query, State},
-    http::{HeaderMap, HeaderName, HeaderValue, StatusCode},
+    http::{HeaderMap, HeaderName, HeaderValue, Method, StatusCode},
     response::{IntoResponse, Response},
+    Extension,
     Json,
 };
 use axum_extra::{
-    headers::{authorization::Bearer, Authorization},
+    headers::{
+        authorization::Bearer,
+        Authorization,
+        ETag,
+        HeaderMapExt as _,
+        IfModifiedSince,
+        IfNoneMatch,
+    },
     TypedHeader,
 };
+use chrono::SecondsFormat;
 use jsonwebtoken::{DecodingKey, Validation};
-use reqwest::header::{CACHE_CONTROL, CONTENT_TYPE, ETAG, X_CONTENT_TYPE_OPTIONS};
-use serde::Deserialize;
+use reqwest::header::{
+    ACCEPT_RANGES,
+    CACHE_CONTROL,
+    CONTENT_DISPOSITION,
+    CONTENT_LENGTH,
+    CONTENT_RANGE,
+    CONTENT_SECURITY_POLICY,
+    CONTENT_TYPE,
+    ETAG,
+    IF_RANGE,
+    RANGE,
+    X_CONTENT_TYPE_OPTIONS,
+};
+use serde::{Deserialize, Serialize};
 use sui_types::base_types::{ObjectID, SuiAddress};
-use tower_http::cors::{Any, CorsLayer};
+use tower_http::cors::{AllowHeaders, AllowMethods, AllowOrigin, CorsLayer, ExposeHeaders};
 use tracing::Level;
 use utoipa::IntoParams;
 use walrus_core::{BlobId, EncodingType, EpochCount};
 use walrus_proc_macros::RestApiError;
 use walrus_rest_client::api::errors::DAEMON_ERROR_DOMAIN as ERROR_DOMAIN;
 use walrus_sdk::{
-    client::responses::BlobStoreResult,
+    client::{
+        byte_range::{BlobRange, ByteRange},
+        responses::{BlobAvailability, BlobStoreResult},
+    },
     error::{ClientError, ClientErrorKind},
     store_when::StoreWhen,
 };
@@ -37,11 +61,18 @@ use walrus_sui::{
     SuiAddressSchema,
 };
 
-use super::{WalrusReadClient, WalrusWriteClient};
+use super::{content_type, jobs, rate_limit, tus, WalrusReadClient, WalrusWriteClient};
 use crate::{
-    client::daemon::{
-        auth::{Claim, PublisherAuthError},
-        PostStoreAction,
+    client::{
+        config::{CorsConfig, ResponseHeadersConfig, RouteResponseHeaders},
+        daemon::{
+            auth::{ApiKeyHolder, Claim, PublisherAuthError},
+            signed_url::SignedUrlGrant,
+            sources::SourcesUnavailable,
+            PostStoreAction,
+            PublishJobs,
+            UploadProgressHandle,
+        },
     },
     common::api::{Binary, BlobIdString, RestApiError},
 };
@@ -56,69 +87,416 @@ pub const BLOB_GET_ENDPOINT: &str = "/v1/blobs/{blob_id}";
 pub const BLOB_OBJECT_GET_ENDPOINT: &str = "/v1/blobs/by-object-id/{blob_object_id}";
 /// The path to store a blob.
 pub const BLOB_PUT_ENDPOINT: &str = "/v1/blobs";
+/// The header with the epoch in which a blob was first certified.
+pub(super) const CERTIFIED_EPOCH_HEADER: &str = "x-walrus-certified-epoch";
+/// The header with the estimated time at which a permanent blob expires, in RFC 3339 format.
+pub(super) const ESTIMATED_EXPIRY_HEADER: &str = "x-walrus-estimated-expiry";
 
 /// Retrieve a Walrus blob.
 ///
 /// Reconstructs the blob identified by the provided blob ID from Walrus and return it binary data.
+/// If the request has a `Range` header with a single byte range, only the requested bytes are
+/// returned, which are read from the slivers that contain them without reconstructing the blob.
 #[tracing::instrument(level = Level::ERROR, skip_all, fields(%blob_id))]
 #[utoipa::path(
     get,
     path = BLOB_GET_ENDPOINT,
-    params(("blob_id" = BlobId,)),
+    params(("blob_id" = BlobId,), BlobQuery),
     responses(
         (status = 200, description = "The blob was reconstructed successfully", body = [u8]),
+        (status = 206, description = "The requested range of the blob was read successfully",
+        body = [u8]),
+        (status = 416, description = "The requested range is not satisfiable"),
         GetBlobError,
     ),
 )]
 pub(super) async fn get_blob<T: WalrusReadClient>(
     request_headers: HeaderMap,
     State(client): State<Arc<T>>,
+    Extension(config): Extension<Arc<ResponseHeadersConfig>>,
+    Query(query): Query<BlobQuery>,
+    Path(BlobIdString(blob_id)): Path<BlobIdString>,
+) -> Response {
+    match BlobResponseHeaders::new(&request_headers, query, &config, BLOB_GET_ENDPOINT) {
+        Ok(response_headers) => read_blob(client.as_ref(), &blob_id, &response_headers).await,
+        Err(error) => error.to_response(),
+    }
+}
+
+/// Retrieve the size and availability of a Walrus blob.
+///
+/// Returns the headers of the blob without reconstructing it, as only its metadata is retrieved
+/// from the storage nodes. In addition to the `Content-Length` and the `ETag` of the blob, the
+/// response has the `X-Walrus-Certified-Epoch` header with the epoch in which the blob was first
+/// certified and, if the blob is stored permanently, the `X-Walrus-Estimated-Expiry` header with
+/// the estimated time at which it expires, in RFC 3339 format.
+#[tracing::instrument(level = Level::ERROR, skip_all, fields(%blob_id))]
+#[utoipa::path(
+    head,
+    path = BLOB_GET_ENDPOINT,
+    params(("blob_id" = BlobId,), BlobQuery),
+    responses(
+        (status = 200, description = "The blob is available"),
+        GetBlobError,
+    ),
+)]
+pub(super) async fn head_blob<T: WalrusReadClient>(
+    request_headers: HeaderMap,
+    State(client): State<Arc<T>>,
+    Extension(config): Extension<Arc<ResponseHeadersConfig>>,
+    Query(query): Query<BlobQuery>,
     Path(BlobIdString(blob_id)): Path<BlobIdString>,
 ) -> Response {
+    let response_headers =
+        match BlobResponseHeaders::new(&request_headers, query, &config, BLOB_GET_ENDPOINT) {
+            Ok(response_headers) => response_headers,
+            Err(error) => return error.to_response(),
+        };
+    if is_not_modified(&request_headers, &blob_id) {
+        return not_modified(client.as_ref(), &blob_id, &response_headers).await;
+    }
+
+    tracing::debug!("starting to read the availability of the blob");
+    match client.blob_availability(&blob_id).await {
+        Ok(BlobAvailability {
+            blob_size,
+            certified_epoch,
+            estimated_expiry,
+        }) => {
+            let mut headers = HeaderMap::new();
+            response_headers.populate(&mut headers, &blob_id, None);
+            headers.insert(CONTENT_LENGTH, HeaderValue::from(blob_size));
+            headers.insert(CERTIFIED_EPOCH_HEADER, HeaderValue::from(certified_epoch));
+            if let Some(expiry) = estimated_expiry {
+                headers.insert(
+                    ESTIMATED_EXPIRY_HEADER,
+                    HeaderValue::from_str(&expiry.to_rfc3339_opts(SecondsFormat::Secs, true))
+                        .expect("the timestamp only contains visible ASCII characters"),
+                );
+            }
+            (StatusCode::OK, headers).into_response()
+        }
+        Err(error) => get_blob_error_response(error, &blob_id),
+    }
+}
+
+/// The query parameters for reading a blob.
+#[derive(Debug, Default, Deserialize, IntoParams)]
+pub(super) struct BlobQuery {
+    /// The content type with which the blob is returned, overriding the detected content type.
+    /// HTML, XML, and script types are returned as sandboxed attachments.
+    #[serde(default, rename = "type")]
+    #[param(rename = "type")]
+    pub content_type: Option<String>,
+}
+
+/// The sandbox of content served inline, such as the files of websites.
+///
+/// Scripts may run, but without `allow-same-origin` the document is placed in an opaque origin, so
+/// it cannot access the cookies and storage of the aggregator's origin, nor those of other
+/// documents served by the aggregator.
+pub(super) const SANDBOX_ALLOWING_SCRIPTS: &str =
+    "sandbox allow-scripts allow-forms allow-popups allow-modals";
+
+/// The headers of a response with the data of a blob.
+pub(super) struct BlobResponseHeaders<'a> {
+    request_headers: &'a HeaderMap,
+    requested_content_type: Option<HeaderValue>,
+    config: &'a ResponseHeadersConfig,
+    route: Option<&'a RouteResponseHeaders>,
+    inline_active_content: bool,
+}
+
+impl<'a> BlobResponseHeaders<'a> {
+    pub(super) fn new(
+        request_headers: &'a HeaderMap,
+        query: BlobQuery,
+        config: &'a ResponseHeadersConfig,
+        route: &str,
+    ) -> Result<Self, GetBlobError> {
+        let requested_content_type = query
+            .content_type
+            .map(|content_type| {
+                HeaderValue::try_from(content_type)
+                    .ok()
+                    .filter(|value| value.to_str().is_ok_and(|value| value.contains('/')))
+                    .ok_or(GetBlobError::InvalidContentType)
+            })
+            .transpose()?;
+
+        Ok(Self {
+            request_headers,
+            requested_content_type,
+            config,
+            route: config.route(route),
+            inline_active_content: false,
+        })
+    }
+
+    /// Serves content types that browsers render as documents or execute inline instead of as
+    /// attachments. Such responses are still sandboxed, but may run scripts in an opaque origin,
+    /// see [`SANDBOX_ALLOWING_SCRIPTS`].
+    pub(super) fn with_inline_active_content(mut self) -> Self {
+        self.inline_active_content = true;
+        self
+    }
+
+    /// Inserts the headers into the response.
+    ///
+    /// `data` is the beginning of the blob, if the response starts at the beginning of the blob,
+    /// and is used to detect the content type.
+    fn populate(&self, headers: &mut HeaderMap, blob_id: &BlobId, data: Option<&[u8]>) {
+        // Prevent the browser from trying to guess the MIME type to avoid dangerous inferences.
+        headers.insert(X_CONTENT_TYPE_OPTIONS, HeaderValue::from_static("nosniff"));
+        self.populate_cache_headers(headers, blob_id);
+        // Advertise that ranges of the blob can be requested, for example to seek within media.
+        headers.insert(ACCEPT_RANGES, HeaderValue::from_static("bytes"));
+        if let Some(content_disposition) = self
+            .route
+            .and_then(|route| route.content_disposition.clone())
+        {
+            headers.insert(CONTENT_DISPOSITION, content_disposition);
+        }
+        if let Some(content_type) = self.content_type(data) {
+            // Content that the browser renders as a document or executes, such as HTML requested
+            // with `?type=text/html`, must not run scripts in the origin of the aggregator. It is
+            // therefore sandboxed and, unless configured otherwise, downloaded as an attachment.
+            if content_type::is_active_content_type(&content_type) {
+                if self.inline_active_content {
+                    headers.insert(
+                        CONTENT_SECURITY_POLICY,
+                        HeaderValue::from_static(SANDBOX_ALLOWING_SCRIPTS),
+                    );
+                } else {
+                    headers.insert(CONTENT_SECURITY_POLICY, HeaderValue::from_static("sandbox"));
+                    headers.insert(CONTENT_DISPOSITION, HeaderValue::from_static("attachment"));
+                }
+            }
+            headers.insert(CONTENT_TYPE, content_type);
+        }
+    }
+
+    /// Inserts the headers that help caches distribute Walrus blobs into the response.
+    ///
+    /// These are also the headers of responses to conditional requests that are not modified.
+    fn populate_cache_headers(&self, headers: &mut HeaderMap, blob_id: &BlobId) {
+        //
+        // Cache for 1 day, and allow refreshig on the client side. Refreshes use the ETag to
+        // check if the content has changed. This allows invalidated blobs to be removed from
+        // caches. `stale-while-revalidate` allows stale content to be served for 1 hour while
+        // the browser tries to validate it (async revalidation).
+        let cache_control = self
+            .route
+            .and_then(|route| route.cache_control.clone())
+            .unwrap_or(HeaderValue::from_static(
+                "public, max-age=86400, stale-while-revalidate=3600",
+            ));
+        headers.insert(CACHE_CONTROL, cache_control);
+        // The `ETag` is the blob ID itself, which is a strong validator as the content of a blob
+        // ID never changes.
+        headers.insert(
+            ETAG,
+            HeaderValue::from_str(&blob_etag(blob_id))
+                .expect("the blob ID string only contains visible ASCII characters"),
+        );
+    }
+
+    /// Returns the content type of the blob.
+    ///
+    /// The content type requested in the query takes precedence over the content type of the
+    /// request, which takes precedence over the content type detected from the data and the
+    /// default content type of the route.
+    fn content_type(&self, data: Option<&[u8]>) -> Option<HeaderValue> {
+        if let Some(content_type) = &self.requested_content_type {
+            tracing::debug!(?content_type, "using the requested content type");
+            return Some(content_type.clone());
+        }
+        // Mirror the content type.
+        if let Some(content_type) = self.request_headers.get(CONTENT_TYPE) {
+            tracing::debug!(?content_type, "mirroring the request's content type");
+            return Some(content_type.clone());
+        }
+        if let Some(content_type) = data
+            .filter(|_| self.config.sniff_content_type)
+            .and_then(content_type::sniff_content_type)
+        {
+            tracing::debug!(
+                content_type,
+                "using the content type detected from the data"
+            );
+            return Some(HeaderValue::from_static(content_type));
+        }
+        self.route
+            .and_then(|route| route.default_content_type.clone())
+    }
+}
+
+/// Reads the blob, or the range of it requested in the `Range` header.
+pub(super) async fn read_blob<T: WalrusReadClient>(
+    client: &T,
+    blob_id: &BlobId,
+    response_headers: &BlobResponseHeaders<'_>,
+) -> Response {
+    if is_not_modified(response_headers.request_headers, blob_id) {
+        return not_modified(client, blob_id, response_headers).await;
+    }
+    if let Some(range) = requested_range(response_headers.request_headers, blob_id) {
+        return read_blob_range(client, blob_id, range, response_headers).await;
+    }
+
     tracing::debug!("starting to read blob");
-    match client.read_blob(&blob_id).await {
+    match client.read_blob(blob_id).await {
         Ok(blob) => {
             tracing::debug!("successfully retrieved blob");
-            let mut response = (StatusCode::OK, blob).into_response();
-            let headers = response.headers_mut();
-            // Prevent the browser from trying to guess the MIME type to avoid dangerous inferences.
-            headers.insert(X_CONTENT_TYPE_OPTIONS, HeaderValue::from_static("nosniff"));
-            // Insert headers that help caches distribute Walrus blobs.
-            //
-            // Cache for 1 day, and allow refreshig on the client side. Refreshes use the ETag to
-            // check if the content has changed. This allows invalidated blobs to be removed from
-            // caches. `stale-while-revalidate` allows stale content to be served for 1 hour while
-            // the browser tries to validate it (async revalidation).
-            headers.insert(
-                CACHE_CONTROL,
-                HeaderValue::from_static("public, max-age=86400, stale-while-revalidate=3600"),
-            );
-            // The `ETag` is the blob ID itself.
+            let mut headers = HeaderMap::new();
+            response_headers.populate(&mut headers, blob_id, Some(&blob));
+            (StatusCode::OK, headers, blob).into_response()
+        }
+        Err(error) => get_blob_error_response(error, blob_id),
+    }
+}
+
+/// Reads the bytes of the blob in `range` and returns them as partial content.
+async fn read_blob_range<T: WalrusReadClient>(
+    client: &T,
+    blob_id: &BlobId,
+    range: ByteRange,
+    response_headers: &BlobResponseHeaders<'_>,
+) -> Response {
+    tracing::debug!(?range, "starting to read blob range");
+    match client.read_blob_range(blob_id, range).await {
+        Ok(BlobRange {
+            data,
+            range,
+            blob_size,
+        }) => {
+            tracing::debug!("successfully retrieved blob range");
+            let mut headers = HeaderMap::new();
+            let start_of_blob = (range.start == 0).then_some(data.as_slice());
+            response_headers.populate(&mut headers, blob_id, start_of_blob);
             headers.insert(
-                ETAG,
-                HeaderValue::from_str(&blob_id.to_string())
-                    .expect("the blob ID string only contains visible ASCII characters"),
+                CONTENT_RANGE,
+                HeaderValue::from_str(&format!(
+                    "bytes {}-{}/{blob_size}",
+                    range.start,
+                    range.end - 1
+                ))
+                .expect("the content range only contains visible ASCII characters"),
             );
-            // Mirror the content type.
-            if let Some(content_type) = request_headers.get(CONTENT_TYPE) {
-                tracing::debug!(?content_type, "mirroring the request's content type");
-                headers.insert(CONTENT_TYPE, content_type.clone());
-            }
-            response
+            (StatusCode::PARTIAL_CONTENT, headers, data).into_response()
         }
         Err(error) => {
-            let error = GetBlobError::from(error);
-
-            match &error {
-                GetBlobError::BlobNotFound => {
-                    tracing::debug!(?blob_id, "the requested blob ID does not exist")
-                }
-                GetBlobError::Internal(error) => tracing::error!(?error, "error retrieving blob"),
-                _ => (),
+            if let ClientErrorKind::RangeNotSatisfiable { blob_size } = error.kind() {
+                tracing::debug!(blob_size, "the requested range is not satisfiable");
+                return (
+                    StatusCode::RANGE_NOT_SATISFIABLE,
+                    [(CONTENT_RANGE, format!("bytes */{blob_size}"))],
+                )
+                    .into_response();
             }
+            get_blob_error_response(error, blob_id)
+        }
+    }
+}
 
-            error.to_response()
+/// Replies that the representation of the blob cached by the client is current.
+///
+/// Invalidated blobs must be removed from caches, so the blob is checked to still be available by
+/// reading its first byte, which is cheap compared to reading the whole blob.
+async fn not_modified<T: WalrusReadClient>(
+    client: &T,
+    blob_id: &BlobId,
+    response_headers: &BlobResponseHeaders<'_>,
+) -> Response {
+    tracing::debug!("checking that the blob is available for a conditional request");
+    match client
+        .read_blob_range(blob_id, ByteRange::Bounded { start: 0, end: 0 })
+        .await
+    {
+        // An empty blob has no first byte to read, but is available.
+        Ok(_) => (),
+        Err(error) if matches!(error.kind(), ClientErrorKind::RangeNotSatisfiable { .. }) => (),
+        Err(error) => return get_blob_error_response(error, blob_id),
+    }
+
+    tracing::debug!("the cached blob is not modified");
+    let mut headers = HeaderMap::new();
+    response_headers.populate_cache_headers(&mut headers, blob_id);
+    (StatusCode::NOT_MODIFIED, headers).into_response()
+}
+
+pub(super) fn get_blob_error_response(error: ClientError, blob_id: &BlobId) -> Response {
+    let error = GetBlobError::from(error);
+
+    match &error {
+        GetBlobError::BlobNotFound => {
+            tracing::debug!(?blob_id, "the requested blob ID does not exist")
+        }
+        GetBlobError::Internal(error) => tracing::error!(?error, "error retrieving blob"),
+        _ => (),
+    }
+
+    error.to_response()
+}
+
+/// Returns the strong entity tag of the blob, which is its quoted blob ID.
+fn blob_etag(blob_id: &BlobId) -> String {
+    format!("\"{blob_id}\"")
+}
+
+/// Returns true if the request is conditional and the representation of the blob cached by the
+/// client is current.
+///
+/// The content of a blob ID never changes, so the cached representation is current if one of the
+/// entity tags in the `If-None-Match` header matches the blob ID, or if the request has an
+/// `If-Modified-Since` header and no `If-None-Match` header, in which case the client cached the
+/// blob at some point in time.
+fn is_not_modified(request_headers: &HeaderMap, blob_id: &BlobId) -> bool {
+    if let Some(if_none_match) = request_headers.typed_get::<IfNoneMatch>() {
+        let etag: ETag = blob_etag(blob_id)
+            .parse()
+            .expect("the quoted blob ID is a valid entity tag");
+        return !if_none_match.precondition_passes(&etag);
+    }
+    request_headers.typed_get::<IfModifiedSince>().is_some()
+}
+
+/// Returns the byte range requested in the `Range` header, if the range applies to the blob.
+///
+/// Only a single range in bytes is supported; the header is ignored otherwise, and the whole blob
+/// is returned. The range is also ignored if the `If-Range` header refers to a different blob.
+pub(super) fn requested_range(request_headers: &HeaderMap, blob_id: &BlobId) -> Option<ByteRange> {
+    let range = request_headers.get(RANGE)?.to_str().ok()?;
+    if let Some(if_range) = request_headers.get(IF_RANGE) {
+        let etag = if_range
+            .to_str()
+            .ok()?
+            .trim_start_matches("W/")
+            .trim_matches('"');
+        if etag != blob_id.to_string() {
+            return None;
+        }
+    }
+    parse_byte_range(range)
+}
+
+/// Parses the value of a `Range` header consisting of a single byte range.
+fn parse_byte_range(value: &str) -> Option<ByteRange> {
+    let range = value.trim().strip_prefix("bytes=")?;
+    if range.contains(',') {
+        return None;
+    }
+    let (start, end) = range.split_once('-')?;
+    let (start, end) = (start.trim(), end.trim());
+
+    match (start.is_empty(), end.is_empty()) {
+        (true, false) => Some(ByteRange::Suffix(end.parse().ok()?)),
+        (false, true) => Some(ByteRange::From(start.parse().ok()?)),
+        (false, false) => {
+            let (start, end) = (start.parse().ok()?, end.parse().ok()?);
+            (start <= end).then_some(ByteRange::Bounded { start, end })
         }
+        (true, true) => None,
     }
 }
 
@@ -148,7 +526,7 @@ fn populate_response_headers(
 #[utoipa::path(
     get,
     path = BLOB_OBJECT_GET_ENDPOINT,
-    params(("blob_object_id" = ObjectIdSchema,)),
+    params(("blob_object_id" = ObjectIdSchema,), BlobQuery),
     responses(
         (
             status = 200,
@@ -161,22 +539,29 @@ fn populate_response_headers(
 )]
 pub(super) async fn get_blob_by_object_id<T: WalrusReadClient>(
     State((client, allowed_headers)): State<(Arc<T>, Arc<HashSet<String>>)>,
+    Extension(config): Extension<Arc<ResponseHeadersConfig>>,
     request_headers: HeaderMap,
+    Query(query): Query<BlobQuery>,
     Path(blob_object_id): Path<ObjectID>,
 ) -> Response {
+    let response_headers = match BlobResponseHeaders::new(
+        &request_headers,
+        query,
+        &config,
+        BLOB_OBJECT_GET_ENDPOINT,
+    ) {
+        Ok(response_headers) => response_headers,
+        Err(error) => return error.to_response(),
+    };
+
     tracing::debug!("starting to read blob with attribute");
     match client.get_blob_by_object_id(&blob_object_id).await {
         Ok(BlobWithAttribute { blob, attribute }) => {
-            // Get the blob data using the existing get_blob function
-            let mut response = get_blob(
-                request_headers.clone(),
-                State(client),
-                Path(BlobIdString(blob.blob_id)),
-            )
-            .await;
+            // Get the blob data in the same way as for the blob ID.
+            let mut response = read_blob(client.as_ref(), &blob.blob_id, &response_headers).await;
 
             // If the response was successful, add our additional metadata headers
-            if response.status() == StatusCode::OK {
+            if response.status().is_success() {
                 if let Some(attribute) = attribute {
                     populate_response_headers(response.headers_mut(), &attribute, &allowed_headers);
                 }
@@ -215,11 +600,21 @@ pub(crate) enum GetBlobError {
     #[rest_api_error(reason = "BLOB_NOT_FOUND", status = ApiStatusCode::NotFound)]
     BlobNotFound,
 
+    /// The requested content type is not a valid media type.
+    #[error("the requested content type is not a valid media type")]
+    #[rest_api_error(reason = "INVALID_CONTENT_TYPE", status = ApiStatusCode::InvalidArgument)]
+    InvalidContentType,
+
     /// The blob cannot be returned as has been blocked.
     #[error("the requested metadata is blocked")]
     #[rest_api_error(reason = "FORBIDDEN_BLOB", status = ApiStatusCode::UnavailableForLegalReasons)]
     Blocked,
 
+    /// The blob is not cached and all sources of blobs are unhealthy.
+    #[error("the blob cannot currently be read, please try again later")]
+    #[rest_api_error(reason = "SOURCES_UNAVAILABLE", status = ApiStatusCode::Unavailable)]
+    Unavailable,
+
     #[error(transparent)]
     #[rest_api_error(delegate)]
     Internal(#[from] anyhow::Error),
@@ -230,6 +625,7 @@ impl From<ClientError> for GetBlobError {
         match error.kind() {
             ClientErrorKind::BlobIdDoesNotExist => Self::BlobNotFound,
             ClientErrorKind::BlobIdBlocked(_) => Self::Blocked,
+            ClientErrorKind::Other(error) if error.is::<SourcesUnavailable>() => Self::Unavailable,
             _ => anyhow::anyhow!(error).into(),
         }
     }
@@ -239,7 +635,7 @@ impl From<ClientError> for GetBlobError {
 ///
 /// Store a (potentially deletable) blob on Walrus for 1 or more epochs. The associated on-Sui
 /// object can be sent to a specified Sui address.
-#[tracing::instrument(level = Level::ERROR, skip_all, fields(%epochs))]
+#[tracing::instrument(level = Level::ERROR, skip_all, fields(epochs = %query.epochs))]
 #[utoipa::path(
     put,
     path = BLOB_PUT_ENDPOINT,
@@ -257,22 +653,59 @@ impl From<ClientError> for GetBlobError {
 )]
 pub(super) async fn put_blob<T: WalrusWriteClient>(
     State(client): State<Arc<T>>,
-    Query(PublisherQuery {
-        encoding_type,
-        epochs,
-        deletable,
-        send_object_to,
-    }): Query<PublisherQuery>,
+    Query(query): Query<PublisherQuery>,
+    api_key_holder: Option<Extension<Arc<ApiKeyHolder>>>,
+    signed_url: Option<Extension<SignedUrlGrant>>,
+    progress: Option<Extension<UploadProgressHandle>>,
+    publish_jobs: Option<Extension<Arc<PublishJobs>>>,
     bearer_header: Option<TypedHeader<Authorization<Bearer>>>,
+    headers: HeaderMap,
     blob: Bytes,
 ) -> Response {
-    // Check if there is an authorization claim, and use it to check the size.
-    if let Some(TypedHeader(header)) = bearer_header {
+    // Check the size against the signed URL, and against the permissions of the API key or the
+    // authorization claim.
+    if let Some(Extension(grant)) = &signed_url {
+        if let Err(error) = grant.check_size(blob.len() as u64) {
+            return error.into_response();
+        }
+    }
+    let api_key_holder = api_key_holder.map(|Extension(holder)| holder);
+    if let Some(holder) = &api_key_holder {
+        if let Err(error) = holder
+            .check_size(blob.len() as u64)
+            .and_then(|()| holder.charge_quota(blob.len() as u64))
+        {
+            return error.into_response();
+        }
+    } else if let (None, Some(TypedHeader(header))) = (&signed_url, bearer_header) {
         if let Err(error) = check_blob_size(header, blob.len()) {
             return error.into_response();
         }
     }
 
+    // If requested and enabled, the blob is stored by a publish job instead of within the request.
+    if let Some(Extension(publish_jobs)) = publish_jobs.filter(|_| jobs::prefers_async(&headers)) {
+        return match publish_jobs
+            .submit(query, &blob, api_key_holder.clone())
+            .await
+        {
+            Ok(status) => jobs::job_accepted(status),
+            Err(error) => {
+                tracing::error!(?error, "error submitting a publish job");
+                if let Some(holder) = &api_key_holder {
+                    holder.refund_quota(blob.len() as u64);
+                }
+                error.into_response()
+            }
+        };
+    }
+
+    let PublisherQuery {
+        encoding_type,
+        epochs,
+        deletable,
+        send_object_to,
+    } = query;
     let post_store_action = if let Some(address) = send_object_to {
         PostStoreAction::TransferTo(address)
     } else {
@@ -280,17 +713,21 @@ pub(super) async fn put_blob<T: WalrusWriteClient>(
     };
     tracing::debug!(?post_store_action, "starting to store received blob");
 
-    match client
-        .write_blob(
-            &blob[..],
-            encoding_type,
-            epochs,
-            StoreWhen::NotStoredIgnoreResources,
-            BlobPersistence::from_deletable(deletable),
-            post_store_action,
-        )
-        .await
-    {
+    let store = client.write_blob(
+        &blob[..],
+        encoding_type,
+        epochs,
+        StoreWhen::NotStoredIgnoreResources,
+        BlobPersistence::from_deletable(deletable),
+        post_store_action,
+    );
+    let result = if let Some(Extension(progress)) = progress {
+        progress.follow(store).await
+    } else {
+        store.await
+    };
+
+    match result {
         Ok(result) => {
             if let BlobStoreResult::MarkedInvalid { .. } = result {
                 StoreBlobError::Internal(anyhow!(
@@ -303,6 +740,9 @@ pub(super) async fn put_blob<T: WalrusWriteClient>(
         }
         Err(error) => {
             tracing::error!(?error, "error storing blob");
+            if let Some(holder) = &api_key_holder {
+                holder.refund_quota(blob.len() as u64);
+            }
             StoreBlobError::from(error).into_response()
         }
     }
@@ -315,7 +755,7 @@ pub(super) async fn put_blob<T: WalrusWriteClient>(
 /// so.
 ///
 /// The function just decodes the token and checks that the size in the claim is not exceeded.
-fn check_blob_size(
+pub(super) fn check_blob_size(
     bearer_header: Authorization<Bearer>,
     blob_size: usize,
 ) -> Result<(), PublisherAuthError> {
@@ -376,13 +816,56 @@ impl From<ClientError> for StoreBlobError {
     }
 }
 
-/// Returns a `CorsLayer` for the blob store endpoint.
-pub(super) fn daemon_cors_layer() -> CorsLayer {
-    CorsLayer::new()
-        .allow_origin(Any)
-        .allow_methods(Any)
-        .max_age(Duration::from_secs(86400))
-        .allow_headers(Any)
+/// Returns a `CorsLayer` for the daemon, as configured in `config`.
+///
+/// Returns an error if one of the configured origins, methods, or headers is invalid.
+pub(super) fn daemon_cors_layer(config: &CorsConfig) -> anyhow::Result<CorsLayer> {
+    let allow_origin = match parse_cors_list::<HeaderValue>(&config.allowed_origins, "origin")? {
+        Some(origins) => AllowOrigin::list(origins),
+        None => AllowOrigin::any(),
+    };
+    let allow_methods = match parse_cors_list::<Method>(&config.allowed_methods, "method")? {
+        Some(methods) => AllowMethods::list(methods),
+        None => AllowMethods::any(),
+    };
+    let allow_headers = match parse_cors_list::<HeaderName>(&config.allowed_headers, "header")? {
+        Some(headers) => AllowHeaders::list(headers),
+        None => AllowHeaders::any(),
+    };
+
+    Ok(CorsLayer::new()
+        .allow_origin(allow_origin)
+        .allow_methods(allow_methods)
+        .allow_headers(allow_headers)
+        .expose_headers(ExposeHeaders::list(
+            tus::EXPOSED_HEADERS
+                .into_iter()
+                .chain([CERTIFIED_EPOCH_HEADER, ESTIMATED_EXPIRY_HEADER])
+                .chain(rate_limit::EXPOSED_HEADERS)
+                .map(HeaderName::from_static),
+        ))
+        .max_age(config.max_age))
+}
+
+/// Parses the values of a CORS allow-list, or returns `None` if the list allows any value.
+fn parse_cors_list<V>(values: &[String], kind: &str) -> anyhow::Result<Option<Vec<V>>>
+where
+    V: FromStr,
+    V::Err: Display,
+{
+    if values.iter().any(|value| value == "*") {
+        return Ok(None);
+    }
+    values
+        .iter()
+        .map(|value| {
+            value
+                .parse()
+                .map_err(|error| anyhow!("{error}"))
+                .with_context(|| format!("invalid CORS {kind} '{value}'"))
+        })
+        .collect::<anyhow::Result<_>>()
+        .map(Some)
 }
 
 #[tracing::instrument(level = Level::ERROR, skip_all)]
@@ -398,7 +881,7 @@ pub(super) async fn status() -> Response {
 }
 
 /// The query parameters for a publisher.
-#[derive(Debug, Deserialize, IntoParams)]
+#[derive(Debug, Clone, Serialize, Deserialize, IntoParams)]
 pub struct PublisherQuery {
     /// The encoding type to use for the blob.
     #[serde(default)]
@@ -421,3 +904,214 @@ pub struct PublisherQuery {
 pub(super) fn default_epochs() -> EpochCount {
     1
 }
+
+#[cfg(test)]
+mod tests {
+    use reqwest::header::{IF_MODIFIED_SINCE, IF_NONE_MATCH};
+    use walrus_test_utils::param_test;
+
+    use super::*;
+
+    param_test! {
+        test_parse_byte_range: [
+            bounded: ("bytes=0-499", Some(ByteRange::Bounded { start: 0, end: 499 })),
+            from: ("bytes=500-", Some(ByteRange::From(500))),
+            suffix: ("bytes=-500", Some(ByteRange::Suffix(500))),
+            single_byte: ("bytes=7-7", Some(ByteRange::Bounded { start: 7, end: 7 })),
+            reversed: ("bytes=10-5", None),
+            multiple_ranges: ("bytes=0-1,5-6", None),
+            other_unit: ("items=0-1", None),
+            empty: ("bytes=-", None),
+            invalid_number: ("bytes=a-5", None),
+        ]
+    }
+    fn test_parse_byte_range(value: &str, expected: Option<ByteRange>) {
+        assert_eq!(parse_byte_range(value), expected);
+    }
+
+    param_test! {
+        test_daemon_cors_layer: [
+            default: (CorsConfig::default(), true),
+            origins: (cors_config(&["https://example.com"], &["GET", "PUT"]), true),
+            wildcard_in_list: (cors_config(&["https://example.com", "*"], &["*"]), true),
+            invalid_origin: (cors_config(&["https://example.com\n"], &["GET"]), false),
+            invalid_method: (cors_config(&["*"], &["GET PUT"]), false),
+        ]
+    }
+    fn test_daemon_cors_layer(config: CorsConfig, is_valid: bool) {
+        assert_eq!(daemon_cors_layer(&config).is_ok(), is_valid);
+    }
+
+    fn cors_config(origins: &[&str], methods: &[&str]) -> CorsConfig {
+        CorsConfig {
+            allowed_origins: origins.iter().map(|origin| origin.to_string()).collect(),
+            allowed_methods: methods.iter().map(|method| method.to_string()).collect(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn ignores_range_if_it_refers_to_another_blob() {
+        let blob_id = BlobId([7; 32]);
+        let mut headers = HeaderMap::new();
+        headers.insert(RANGE, HeaderValue::from_static("bytes=0-9"));
+
+        assert_eq!(
+            requested_range(&headers, &blob_id),
+            Some(ByteRange::Bounded { start: 0, end: 9 })
+        );
+
+        headers.insert(
+            IF_RANGE,
+            HeaderValue::from_str(&format!("\"{blob_id}\"")).unwrap(),
+        );
+        assert_eq!(
+            requested_range(&headers, &blob_id),
+            Some(ByteRange::Bounded { start: 0, end: 9 })
+        );
+
+        headers.insert(IF_RANGE, HeaderValue::from_static("\"other\""));
+        assert_eq!(requested_range(&headers, &blob_id), None);
+    }
+
+    const DATE: &str = "Wed, 21 Oct 2015 07:28:00 GMT";
+
+    param_test! {
+        test_is_not_modified: [
+            unconditional: (None, None, false),
+            matching_etag: (Some("{etag}"), None, true),
+            weak_matching_etag: (Some("W/{etag}"), None, true),
+            etag_in_list: (Some("\"other\", {etag}"), None, true),
+            wildcard: (Some("*"), None, true),
+            other_etag: (Some("\"other\""), None, false),
+            modified_since: (None, Some(DATE), true),
+            invalid_date: (None, Some("yesterday"), false),
+            etag_takes_precedence: (Some("\"other\""), Some(DATE), false),
+        ]
+    }
+    fn test_is_not_modified(
+        if_none_match: Option<&str>,
+        if_modified_since: Option<&str>,
+        expected: bool,
+    ) {
+        let blob_id = BlobId([7; 32]);
+        let mut headers = HeaderMap::new();
+        if let Some(value) = if_none_match {
+            let value = value.replace("{etag}", &blob_etag(&blob_id));
+            headers.insert(IF_NONE_MATCH, HeaderValue::from_str(&value).unwrap());
+        }
+        if let Some(value) = if_modified_since {
+            headers.insert(IF_MODIFIED_SINCE, HeaderValue::from_str(value).unwrap());
+        }
+        assert_eq!(is_not_modified(&headers, &blob_id), expected);
+    }
+
+    #[test]
+    fn content_type_follows_precedence() {
+        fn response_headers<'a>(
+            request_headers: &'a HeaderMap,
+            config: &'a ResponseHeadersConfig,
+            content_type: Option<&str>,
+        ) -> Result<BlobResponseHeaders<'a>, GetBlobError> {
+            let query = BlobQuery {
+                content_type: content_type.map(str::to_owned),
+            };
+            BlobResponseHeaders::new(request_headers, query, config, BLOB_GET_ENDPOINT)
+        }
+
+        let config: ResponseHeadersConfig = serde_yaml::from_str(
+            r#"
+            routes:
+              /v1/blobs/{blob_id}:
+                default_content_type: application/octet-stream
+            "#,
+        )
+        .expect("the configuration is valid");
+        let png = b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR".as_slice();
+        let mut request_headers = HeaderMap::new();
+
+        let headers = response_headers(&request_headers, &config, None).unwrap();
+        assert_eq!(
+            headers
+                .content_type(Some(b"plain text".as_slice()))
+                .unwrap(),
+            "application/octet-stream"
+        );
+        assert_eq!(headers.content_type(Some(png)).unwrap(), "image/png");
+        assert_eq!(
+            headers.content_type(None).unwrap(),
+            "application/octet-stream"
+        );
+
+        request_headers.insert(CONTENT_TYPE, HeaderValue::from_static("text/plain"));
+        let headers = response_headers(&request_headers, &config, None).unwrap();
+        assert_eq!(headers.content_type(Some(png)).unwrap(), "text/plain");
+
+        let headers = response_headers(&request_headers, &config, Some("video/mp4")).unwrap();
+        assert_eq!(headers.content_type(Some(png)).unwrap(), "video/mp4");
+
+        assert!(matches!(
+            response_headers(&request_headers, &config, Some("not a media type")),
+            Err(GetBlobError::InvalidContentType)
+        ));
+    }
+
+    param_test! {
+        sandboxes_active_content_types: [
+            html: ("text/html", Some("attachment"), Some("sandbox")),
+            svg: ("image/svg+xml", Some("attachment"), Some("sandbox")),
+            png: ("image/png", None, None),
+        ]
+    }
+    fn sandboxes_active_content_types(
+        content_type: &str,
+        expected_content_disposition: Option<&str>,
+        expected_content_security_policy: Option<&str>,
+    ) {
+        let request_headers = HeaderMap::new();
+        let config = ResponseHeadersConfig::default();
+        let query = BlobQuery {
+            content_type: Some(content_type.to_owned()),
+        };
+        let response_headers =
+            BlobResponseHeaders::new(&request_headers, query, &config, BLOB_GET_ENDPOINT).unwrap();
+
+        let mut headers = HeaderMap::new();
+        response_headers.populate(&mut headers, &BlobId([7; 32]), None);
+
+        assert_eq!(headers[CONTENT_TYPE], content_type);
+        assert_eq!(headers[X_CONTENT_TYPE_OPTIONS], "nosniff");
+        assert_eq!(
+            headers
+                .get(CONTENT_DISPOSITION)
+                .map(|value| value.to_str().unwrap()),
+            expected_content_disposition
+        );
+        assert_eq!(
+            headers
+                .get(CONTENT_SECURITY_POLICY)
+                .map(|value| value.to_str().unwrap()),
+            expected_content_security_policy
+        );
+    }
+
+    #[test]
+    fn sandboxes_inline_active_content_in_an_opaque_origin() {
+        let request_headers = HeaderMap::new();
+        let config = ResponseHeadersConfig::default();
+        let query = BlobQuery {
+            content_type: Some("text/html".to_owned()),
+        };
+        let response_headers =
+            BlobResponseHeaders::new(&request_headers, query, &config, BLOB_GET_ENDPOINT)
+                .unwrap()
+                .with_inline_active_content();
+
+        let mut headers = HeaderMap::new();
+        response_headers.populate(&mut headers, &BlobId([7; 32]), None);
+
+        assert_eq!(headers[CONTENT_SECURITY_POLICY], SANDBOX_ALLOWING_SCRIPTS);
+        assert!(!SANDBOX_ALLOWING_SCRIPTS.contains("allow-same-origin"));
+        assert!(headers.get(CONTENT_DISPOSITION).is_none());
+    }
+}
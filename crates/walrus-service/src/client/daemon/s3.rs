@@ -0,0 +1,764 @@
+// Copyright (c) Walrus Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! A subset of the S3 API, which allows existing S3 tools and SDKs to store and read objects
+//! through the daemon.
+//!
+//! Each object is stored as a blob, and a local [`S3Index`] maps the bucket and key of the object
+//! to the blob. Requests use path-style addressing below [`S3_ENDPOINT`], e.g.,
+//! `PUT /s3/{bucket}/{key}`, and buckets exist implicitly. Multipart uploads and the copying of
+//! objects are not supported.
+//!
+//! Requests that modify the index must be signed with AWS Signature Version 4, using the identity
+//! of an API key of the publisher as access key ID and the API key as secret access key. Such
+//! requests are rejected if the publisher is not configured with API keys. The signatures of the
+//! individual chunks of `aws-chunked` payloads are not verified.
+
+use std::{collections::HashMap, fmt::Write as _, sync::Arc};
+
+use anyhow::anyhow;
+use axum::{
+    body::Bytes,
+    extract::{DefaultBodyLimit, OriginalUri, Path, Query, State},
+    http::{
+        header::{
+            ACCEPT_RANGES,
+            CONTENT_ENCODING,
+            CONTENT_LENGTH,
+            CONTENT_RANGE,
+            CONTENT_TYPE,
+            ETAG,
+            LAST_MODIFIED,
+        },
+        HeaderMap,
+        HeaderValue,
+        Method,
+        StatusCode,
+        Uri,
+    },
+    response::{IntoResponse, Response},
+    routing::get,
+    Router,
+};
+use chrono::SecondsFormat;
+use http_body::SizeHint;
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use tracing::Level;
+use walrus_core::{BlobId, EpochCount};
+use walrus_sdk::{
+    client::{byte_range::BlobRange, responses::BlobStoreResult},
+    error::{ClientError, ClientErrorKind},
+    store_when::StoreWhen,
+};
+use walrus_sui::client::BlobPersistence;
+
+use self::{
+    sigv4::{Authorization, SignatureError},
+    xml::{XmlWriter, S3_NAMESPACE},
+};
+use super::{
+    auth::{ApiKeyHolder, ApiKeys, PublisherAuthError},
+    routes::{requested_range, PublisherQuery},
+    WalrusReadClient,
+    WalrusWriteClient,
+};
+use crate::common::api::RestApiError as _;
+
+mod index;
+pub(crate) use index::{S3Index, S3Object};
+mod sigv4;
+mod xml;
+
+/// The path below which the S3-compatible API is served.
+pub const S3_ENDPOINT: &str = "/s3";
+
+/// The maximum number of objects returned when listing a bucket.
+const MAX_KEYS: usize = 1000;
+
+const X_AMZ_CONTENT_SHA256: &str = "x-amz-content-sha256";
+const X_AMZ_DECODED_CONTENT_LENGTH: &str = "x-amz-decoded-content-length";
+const X_AMZ_COPY_SOURCE: &str = "x-amz-copy-source";
+
+/// The state of the S3-compatible API.
+#[derive(Debug)]
+pub(crate) struct S3State<T> {
+    client: Arc<T>,
+    index: S3Index,
+    epochs: EpochCount,
+    /// The API keys with which requests modifying the index are signed, if any.
+    api_keys: Option<ApiKeys>,
+}
+
+impl<T> S3State<T> {
+    /// Creates the state of the S3-compatible API, which stores objects for `epochs` epochs.
+    ///
+    /// If `api_keys` is `None`, requests modifying the index are rejected.
+    pub(crate) fn new(
+        client: Arc<T>,
+        index: S3Index,
+        epochs: EpochCount,
+        api_keys: Option<ApiKeys>,
+    ) -> Self {
+        Self {
+            client,
+            index,
+            epochs,
+            api_keys,
+        }
+    }
+
+    /// Verifies the signature of the request and returns the holder of the API key with which it
+    /// is signed.
+    ///
+    /// The access key ID of the signature is the identity of the holder, and the secret access key
+    /// is the API key.
+    fn authenticate(
+        &self,
+        method: &Method,
+        uri: &Uri,
+        headers: &HeaderMap,
+        body: &[u8],
+    ) -> Result<Arc<ApiKeyHolder>, S3Error> {
+        let Some(api_keys) = &self.api_keys else {
+            tracing::debug!("rejecting an S3 request, as no API keys are configured");
+            return Err(S3Error::AccessDenied);
+        };
+        let authorization = Authorization::from_headers(headers).ok_or(S3Error::AccessDenied)?;
+        let (key, holder) = api_keys
+            .key_of_identity(authorization.access_key_id)
+            .ok_or(S3Error::InvalidAccessKeyId)?;
+
+        let payload_hash = match headers.get(X_AMZ_CONTENT_SHA256) {
+            Some(value) => value
+                .to_str()
+                .map_err(|_| S3Error::InvalidArgument("the payload digest is not valid"))?
+                .to_owned(),
+            None => format!("{:x}", Sha256::digest(body)),
+        };
+        authorization
+            .verify(key, method, uri, headers, &payload_hash, chrono::Utc::now())
+            .map_err(|error| match error {
+                SignatureError::Malformed => S3Error::AccessDenied,
+                SignatureError::RequestTimeTooSkewed => S3Error::RequestTimeTooSkewed,
+                SignatureError::DoesNotMatch => S3Error::SignatureDoesNotMatch,
+            })?;
+
+        tracing::info!(identity = holder.identity, "authenticated an S3 request");
+        Ok(holder.clone())
+    }
+
+    async fn object(&self, bucket: &str, key: &str) -> Result<S3Object, S3Error> {
+        self.index.get(bucket, key).await.ok_or(S3Error::NoSuchKey)
+    }
+}
+
+/// Returns the router of the S3-compatible API, which is meant to be nested at [`S3_ENDPOINT`].
+pub(super) fn router<T, S>(state: S3State<T>, max_body_limit: usize) -> Router<S>
+where
+    T: WalrusWriteClient + Send + Sync + 'static,
+    S: Clone + Send + Sync + 'static,
+{
+    Router::new()
+        .route(
+            "/{bucket}",
+            get(list_objects::<T>)
+                .head(head_bucket)
+                .put(create_bucket::<T>),
+        )
+        .route(
+            "/{bucket}/{*key}",
+            get(get_object::<T>)
+                .head(head_object::<T>)
+                .put(put_object::<T>)
+                .delete(delete_object::<T>),
+        )
+        .layer(DefaultBodyLimit::max(max_body_limit))
+        .with_state(Arc::new(state))
+}
+
+/// Stores the object in the body as a blob (`PutObject`).
+#[tracing::instrument(level = Level::ERROR, skip_all, fields(%bucket, %key))]
+async fn put_object<T: WalrusWriteClient>(
+    State(state): State<Arc<S3State<T>>>,
+    Path((bucket, key)): Path<(String, String)>,
+    Query(query): Query<HashMap<String, String>>,
+    OriginalUri(uri): OriginalUri,
+    method: Method,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<Response, S3Error> {
+    if query.contains_key("uploadId") {
+        return Err(S3Error::NotImplemented("multipart upload"));
+    }
+    if headers.contains_key(X_AMZ_COPY_SOURCE) {
+        return Err(S3Error::NotImplemented("copying objects"));
+    }
+    let holder = state.authenticate(&method, &uri, &headers, &body)?;
+    let data = decode_payload(&headers, body)?;
+    let size = data.len() as u64;
+
+    let query = PublisherQuery {
+        encoding_type: None,
+        epochs: state.epochs,
+        deletable: false,
+        send_object_to: None,
+    };
+    holder.check_upload(&query, SizeHint::with_exact(size))?;
+    // The permit is held until the object is stored.
+    let _upload_permit = holder.start_upload()?;
+    holder.charge_quota(size)?;
+
+    tracing::debug!(size, "starting to store an S3 object");
+    let blob_id = match store_blob(state.client.as_ref(), &data, state.epochs).await {
+        Ok(blob_id) => blob_id,
+        Err(error) => {
+            holder.refund_quota(size);
+            return Err(error);
+        }
+    };
+
+    let object = S3Object {
+        blob_id,
+        size,
+        content_type: headers
+            .get(CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_owned),
+        last_modified: chrono::Utc::now(),
+    };
+    state.index.insert(&bucket, &key, object).await?;
+    tracing::debug!(%blob_id, "stored an S3 object");
+
+    Ok([(ETAG, etag(&blob_id))].into_response())
+}
+
+async fn store_blob<T: WalrusWriteClient>(
+    client: &T,
+    data: &[u8],
+    epochs: EpochCount,
+) -> Result<BlobId, S3Error> {
+    let result = client
+        .write_blob(
+            data,
+            None,
+            epochs,
+            StoreWhen::NotStoredIgnoreResources,
+            BlobPersistence::from_deletable(false),
+            client.default_post_store_action(),
+        )
+        .await
+        .map_err(S3Error::from_store_error)?;
+
+    match result {
+        BlobStoreResult::NewlyCreated { .. } | BlobStoreResult::AlreadyCertified { .. } => {
+            Ok(result.blob_id().expect("stored blobs have a blob ID"))
+        }
+        _ => Err(S3Error::Internal(anyhow!(
+            "the blob of the object could not be stored: {result:?}"
+        ))),
+    }
+}
+
+/// Reads the object, or the range of it requested in the `Range` header (`GetObject`).
+#[tracing::instrument(level = Level::ERROR, skip_all, fields(%bucket, %key))]
+async fn get_object<T: WalrusReadClient>(
+    State(state): State<Arc<S3State<T>>>,
+    Path((bucket, key)): Path<(String, String)>,
+    headers: HeaderMap,
+) -> Result<Response, S3Error> {
+    let object = state.object(&bucket, &key).await?;
+    let mut response_headers = object_headers(&object);
+
+    let Some(range) = requested_range(&headers, &object.blob_id) else {
+        let data = state
+            .client
+            .read_blob(&object.blob_id)
+            .await
+            .map_err(S3Error::from_read_error)?;
+        return Ok((response_headers, data).into_response());
+    };
+
+    let BlobRange {
+        data,
+        range,
+        blob_size,
+    } = state
+        .client
+        .read_blob_range(&object.blob_id, range)
+        .await
+        .map_err(S3Error::from_read_error)?;
+    response_headers.insert(
+        CONTENT_RANGE,
+        HeaderValue::from_str(&format!(
+            "bytes {}-{}/{blob_size}",
+            range.start,
+            range.end - 1
+        ))
+        .expect("the content range only contains visible ASCII characters"),
+    );
+    Ok((StatusCode::PARTIAL_CONTENT, response_headers, data).into_response())
+}
+
+/// Returns the headers of the object, without its data (`HeadObject`).
+#[tracing::instrument(level = Level::ERROR, skip_all, fields(%bucket, %key))]
+async fn head_object<T: WalrusReadClient>(
+    State(state): State<Arc<S3State<T>>>,
+    Path((bucket, key)): Path<(String, String)>,
+) -> Result<Response, S3Error> {
+    let object = state.object(&bucket, &key).await?;
+    state
+        .client
+        .check_blob_id(&object.blob_id)
+        .map_err(S3Error::from_read_error)?;
+
+    let mut response_headers = object_headers(&object);
+    response_headers.insert(CONTENT_LENGTH, object.size.into());
+    Ok(response_headers.into_response())
+}
+
+/// Removes the object from the index (`DeleteObject`).
+///
+/// The blob of the object remains on Walrus until it expires.
+#[tracing::instrument(level = Level::ERROR, skip_all, fields(%bucket, %key))]
+async fn delete_object<T>(
+    State(state): State<Arc<S3State<T>>>,
+    Path((bucket, key)): Path<(String, String)>,
+    OriginalUri(uri): OriginalUri,
+    method: Method,
+    headers: HeaderMap,
+) -> Result<Response, S3Error> {
+    state.authenticate(&method, &uri, &headers, &[])?;
+    state.index.remove(&bucket, &key).await?;
+    Ok(StatusCode::NO_CONTENT.into_response())
+}
+
+/// Confirms that the bucket exists (`HeadBucket`), which is always the case, as buckets are
+/// implicit.
+async fn head_bucket() -> StatusCode {
+    StatusCode::OK
+}
+
+/// Creates the bucket (`CreateBucket`), which does nothing, as buckets are implicit.
+async fn create_bucket<T>(
+    State(state): State<Arc<S3State<T>>>,
+    OriginalUri(uri): OriginalUri,
+    method: Method,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<StatusCode, S3Error> {
+    state.authenticate(&method, &uri, &headers, &body)?;
+    Ok(StatusCode::OK)
+}
+
+/// The query parameters of `ListObjects` and `ListObjectsV2` requests.
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+struct ListObjectsQuery {
+    list_type: Option<u8>,
+    prefix: Option<String>,
+    delimiter: Option<String>,
+    max_keys: Option<usize>,
+    encoding_type: Option<String>,
+    continuation_token: Option<String>,
+    start_after: Option<String>,
+    marker: Option<String>,
+}
+
+/// Lists the objects in the bucket (`ListObjects` and `ListObjectsV2`).
+#[tracing::instrument(level = Level::ERROR, skip_all, fields(%bucket))]
+async fn list_objects<T>(
+    State(state): State<Arc<S3State<T>>>,
+    Path(bucket): Path<String>,
+    Query(query): Query<ListObjectsQuery>,
+) -> Result<Response, S3Error> {
+    let is_v2 = query.list_type == Some(2);
+    let url_encoding = match query.encoding_type.as_deref() {
+        None => false,
+        Some("url") => true,
+        Some(_) => return Err(S3Error::InvalidArgument("the encoding type must be 'url'")),
+    };
+    let encode = |value: &str| {
+        if url_encoding {
+            url_encode(value)
+        } else {
+            value.to_owned()
+        }
+    };
+
+    let prefix = query.prefix.as_deref().unwrap_or_default();
+    let delimiter = query.delimiter.as_deref().filter(|value| !value.is_empty());
+    let max_keys = query.max_keys.unwrap_or(MAX_KEYS).min(MAX_KEYS);
+    let start_after = if is_v2 {
+        query
+            .continuation_token
+            .as_deref()
+            .or(query.start_after.as_deref())
+    } else {
+        query.marker.as_deref()
+    };
+    let listing = state
+        .index
+        .list(&bucket, prefix, delimiter, start_after, max_keys)
+        .await;
+
+    let mut writer = XmlWriter::new("ListBucketResult", Some(S3_NAMESPACE));
+    writer
+        .element("Name", &bucket)
+        .element("Prefix", encode(prefix))
+        .element("MaxKeys", max_keys)
+        .element("IsTruncated", listing.is_truncated);
+    if let Some(delimiter) = delimiter {
+        writer.element("Delimiter", encode(delimiter));
+    }
+    if url_encoding {
+        writer.element("EncodingType", "url");
+    }
+    let next_entry = listing
+        .last_entry
+        .as_deref()
+        .filter(|_| listing.is_truncated);
+    if is_v2 {
+        writer.element(
+            "KeyCount",
+            listing.objects.len() + listing.common_prefixes.len(),
+        );
+        if let Some(token) = &query.continuation_token {
+            writer.element("ContinuationToken", token);
+        }
+        if let Some(start_after) = &query.start_after {
+            writer.element("StartAfter", encode(start_after));
+        }
+        if let Some(next_entry) = next_entry {
+            writer.element("NextContinuationToken", next_entry);
+        }
+    } else {
+        writer.element(
+            "Marker",
+            encode(query.marker.as_deref().unwrap_or_default()),
+        );
+        if let Some(next_entry) = next_entry {
+            writer.element("NextMarker", encode(next_entry));
+        }
+    }
+
+    for (key, object) in &listing.objects {
+        writer
+            .start("Contents")
+            .element("Key", encode(key))
+            .element(
+                "LastModified",
+                object
+                    .last_modified
+                    .to_rfc3339_opts(SecondsFormat::Millis, true),
+            )
+            .element("ETag", etag(&object.blob_id))
+            .element("Size", object.size)
+            .element("StorageClass", "STANDARD")
+            .end();
+    }
+    for common_prefix in &listing.common_prefixes {
+        writer
+            .start("CommonPrefixes")
+            .element("Prefix", encode(common_prefix))
+            .end();
+    }
+
+    Ok(xml_response(StatusCode::OK, writer.finish()))
+}
+
+/// Returns the headers describing the object in responses to `GetObject` and `HeadObject`.
+fn object_headers(object: &S3Object) -> HeaderMap {
+    let mut headers = HeaderMap::new();
+    let content_type = object
+        .content_type
+        .as_deref()
+        .and_then(|value| HeaderValue::from_str(value).ok())
+        .unwrap_or(HeaderValue::from_static("application/octet-stream"));
+    headers.insert(CONTENT_TYPE, content_type);
+    headers.insert(
+        ETAG,
+        HeaderValue::from_str(&etag(&object.blob_id))
+            .expect("the blob ID only contains visible ASCII characters"),
+    );
+    headers.insert(
+        LAST_MODIFIED,
+        HeaderValue::from_str(
+            &object
+                .last_modified
+                .format("%a, %d %b %Y %H:%M:%S GMT")
+                .to_string(),
+        )
+        .expect("the date only contains visible ASCII characters"),
+    );
+    headers.insert(ACCEPT_RANGES, HeaderValue::from_static("bytes"));
+    headers
+}
+
+/// Returns the entity tag of an object, which is its quoted blob ID.
+fn etag(blob_id: &BlobId) -> String {
+    format!("\"{blob_id}\"")
+}
+
+/// Returns the data of the object in the body of a `PutObject` request.
+///
+/// Bodies in the `aws-chunked` encoding, with which SDKs stream uploads, are decoded; the
+/// signatures and checksums of the chunks are not verified. Other bodies are checked against the
+/// SHA-256 digest in the `x-amz-content-sha256` header, if present.
+fn decode_payload(headers: &HeaderMap, body: Bytes) -> Result<Bytes, S3Error> {
+    let header = |name: &str| headers.get(name).and_then(|value| value.to_str().ok());
+    let content_sha256 = header(X_AMZ_CONTENT_SHA256);
+
+    let is_aws_chunked = content_sha256.is_some_and(|value| value.starts_with("STREAMING-"))
+        || header(CONTENT_ENCODING.as_str()).is_some_and(|value| {
+            value
+                .split(',')
+                .any(|encoding| encoding.trim() == "aws-chunked")
+        });
+    if is_aws_chunked {
+        let data = decode_aws_chunked(&body).ok_or(S3Error::InvalidArgument(
+            "the aws-chunked body is malformed",
+        ))?;
+        if let Some(length) = header(X_AMZ_DECODED_CONTENT_LENGTH) {
+            if length.parse::<usize>().ok() != Some(data.len()) {
+                return Err(S3Error::IncompleteBody);
+            }
+        }
+        return Ok(data.into());
+    }
+
+    if let Some(expected) = content_sha256.filter(|value| value.len() == 64) {
+        if !format!("{:x}", Sha256::digest(&body)).eq_ignore_ascii_case(expected) {
+            return Err(S3Error::ContentSha256Mismatch);
+        }
+    }
+    Ok(body)
+}
+
+/// Decodes a body in the `aws-chunked` encoding, in which each chunk is preceded by a line with
+/// its size in hexadecimal and optional extensions, and the last chunk is empty and followed by
+/// optional trailers.
+fn decode_aws_chunked(mut body: &[u8]) -> Option<Vec<u8>> {
+    let mut data = Vec::with_capacity(body.len());
+    loop {
+        let line_end = body.windows(2).position(|window| window == b"\r\n")?;
+        let line = std::str::from_utf8(&body[..line_end]).ok()?;
+        let size = usize::from_str_radix(line.split(';').next()?.trim(), 16).ok()?;
+        body = &body[line_end + 2..];
+
+        if size == 0 {
+            // The remainder only contains the trailers.
+            return Some(data);
+        }
+        data.extend_from_slice(body.get(..size)?);
+        body = body.get(size..)?.strip_prefix(b"\r\n")?;
+    }
+}
+
+/// Percent-encodes the value, as done for keys and prefixes in listings with the `url` encoding
+/// type.
+fn url_encode(value: &str) -> String {
+    let mut encoded = String::with_capacity(value.len());
+    for byte in value.bytes() {
+        if byte.is_ascii_alphanumeric() || b"-_.~/".contains(&byte) {
+            encoded.push(byte.into());
+        } else {
+            write!(encoded, "%{byte:02X}").expect("writing to a string does not fail");
+        }
+    }
+    encoded
+}
+
+fn xml_response(status: StatusCode, xml: String) -> Response {
+    (
+        status,
+        [(CONTENT_TYPE, HeaderValue::from_static("application/xml"))],
+        xml,
+    )
+        .into_response()
+}
+
+/// An error of the S3-compatible API, which is returned in the XML format of S3.
+#[derive(Debug, thiserror::Error)]
+pub(crate) enum S3Error {
+    /// The object does not exist, or its blob has expired.
+    #[error("the specified key does not exist")]
+    NoSuchKey,
+
+    /// The request is not signed with AWS Signature Version 4, or no API keys are configured.
+    #[error("access denied")]
+    AccessDenied,
+
+    /// The access key ID is not the identity of an API key of the publisher.
+    #[error("the access key ID is not valid")]
+    InvalidAccessKeyId,
+
+    /// The signature of the request does not match the request and the secret access key.
+    #[error("the request signature does not match")]
+    SignatureDoesNotMatch,
+
+    /// The request was signed too long before or after the time it was received.
+    #[error("the difference between the request time and the current time is too large")]
+    RequestTimeTooSkewed,
+
+    /// The upload is not allowed for the API key.
+    #[error(transparent)]
+    Rejected(#[from] PublisherAuthError),
+
+    /// An argument of the request is not valid.
+    #[error("{0}")]
+    InvalidArgument(&'static str),
+
+    /// The body does not match the SHA-256 digest in the request.
+    #[error("the body does not match the provided SHA-256 digest")]
+    ContentSha256Mismatch,
+
+    /// The decoded body is not of the length specified in the request.
+    #[error("the body does not have the specified decoded length")]
+    IncompleteBody,
+
+    /// The requested range does not contain any byte of the object.
+    #[error("the requested range is not satisfiable")]
+    InvalidRange { blob_size: u64 },
+
+    /// The blob of the object is blocked.
+    #[error("the blob of the object is blocked")]
+    Blocked,
+
+    /// The blob could not be stored to sufficient storage nodes before a timeout.
+    #[error("the service timed out while storing the object, please try again")]
+    ServiceUnavailable,
+
+    /// The operation is not supported by the S3-compatible API.
+    #[error("{0} is not supported")]
+    NotImplemented(&'static str),
+
+    /// An internal error.
+    #[error("an internal error occurred")]
+    Internal(#[from] anyhow::Error),
+}
+
+impl S3Error {
+    fn from_read_error(error: ClientError) -> Self {
+        match error.kind() {
+            ClientErrorKind::BlobIdDoesNotExist => Self::NoSuchKey,
+            ClientErrorKind::BlobIdBlocked(_) => Self::Blocked,
+            ClientErrorKind::RangeNotSatisfiable { blob_size } => Self::InvalidRange {
+                blob_size: *blob_size,
+            },
+            _ => Self::Internal(anyhow!(error)),
+        }
+    }
+
+    fn from_store_error(error: ClientError) -> Self {
+        match error.kind() {
+            ClientErrorKind::NotEnoughConfirmations(_, _) => Self::ServiceUnavailable,
+            ClientErrorKind::BlobIdBlocked(_) => Self::Blocked,
+            _ => Self::Internal(anyhow!(error)),
+        }
+    }
+
+    /// Returns the S3 error code.
+    fn code(&self) -> &'static str {
+        match self {
+            Self::NoSuchKey => "NoSuchKey",
+            Self::AccessDenied => "AccessDenied",
+            Self::InvalidAccessKeyId => "InvalidAccessKeyId",
+            Self::SignatureDoesNotMatch => "SignatureDoesNotMatch",
+            Self::RequestTimeTooSkewed => "RequestTimeTooSkewed",
+            Self::Rejected(error) => match error.status_code().http_code() {
+                StatusCode::PAYLOAD_TOO_LARGE => "EntityTooLarge",
+                StatusCode::TOO_MANY_REQUESTS => "SlowDown",
+                _ => "AccessDenied",
+            },
+            Self::InvalidArgument(_) => "InvalidArgument",
+            Self::ContentSha256Mismatch => "XAmzContentSHA256Mismatch",
+            Self::IncompleteBody => "IncompleteBody",
+            Self::InvalidRange { .. } => "InvalidRange",
+            Self::Blocked => "UnavailableForLegalReasons",
+            Self::ServiceUnavailable => "ServiceUnavailable",
+            Self::NotImplemented(_) => "NotImplemented",
+            Self::Internal(_) => "InternalError",
+        }
+    }
+
+    fn status(&self) -> StatusCode {
+        match self {
+            Self::NoSuchKey => StatusCode::NOT_FOUND,
+            Self::AccessDenied
+            | Self::InvalidAccessKeyId
+            | Self::SignatureDoesNotMatch
+            | Self::RequestTimeTooSkewed => StatusCode::FORBIDDEN,
+            Self::Rejected(error) => error.status_code().http_code(),
+            Self::InvalidArgument(_) | Self::ContentSha256Mismatch | Self::IncompleteBody => {
+                StatusCode::BAD_REQUEST
+            }
+            Self::InvalidRange { .. } => StatusCode::RANGE_NOT_SATISFIABLE,
+            Self::Blocked => StatusCode::UNAVAILABLE_FOR_LEGAL_REASONS,
+            Self::ServiceUnavailable => StatusCode::SERVICE_UNAVAILABLE,
+            Self::NotImplemented(_) => StatusCode::NOT_IMPLEMENTED,
+            Self::Internal(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+}
+
+impl IntoResponse for S3Error {
+    fn into_response(self) -> Response {
+        if let Self::Internal(error) = &self {
+            tracing::error!(?error, "error handling an S3 request");
+        }
+
+        let mut writer = XmlWriter::new("Error", None);
+        writer
+            .element("Code", self.code())
+            .element("Message", &self);
+        let mut response = xml_response(self.status(), writer.finish());
+        if let Self::InvalidRange { blob_size } = self {
+            response.headers_mut().insert(
+                CONTENT_RANGE,
+                HeaderValue::from_str(&format!("bytes */{blob_size}"))
+                    .expect("the content range only contains visible ASCII characters"),
+            );
+        }
+        response
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use walrus_test_utils::param_test;
+
+    use super::*;
+
+    #[test]
+    fn decodes_aws_chunked_bodies() {
+        let body = b"5;chunk-signature=abc\r\nhello\r\n6\r\n world\r\n0\r\n\
+            x-amz-checksum-crc32:AAAAAA==\r\n\r\n";
+        assert_eq!(
+            decode_aws_chunked(body).as_deref(),
+            Some(b"hello world".as_slice())
+        );
+        assert_eq!(decode_aws_chunked(b"5\r\nhel"), None);
+        assert_eq!(decode_aws_chunked(b"z\r\nhello\r\n0\r\n"), None);
+    }
+
+    #[test]
+    fn checks_the_digest_of_the_body() {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            X_AMZ_CONTENT_SHA256,
+            HeaderValue::from_str(&format!("{:x}", Sha256::digest(b"data"))).expect("valid"),
+        );
+        assert!(decode_payload(&headers, Bytes::from_static(b"data")).is_ok());
+        assert!(matches!(
+            decode_payload(&headers, Bytes::from_static(b"other")),
+            Err(S3Error::ContentSha256Mismatch)
+        ));
+    }
+
+    #[test]
+    fn url_encodes_keys() {
+        assert_eq!(url_encode("photos/a b+c.jpg"), "photos/a%20b%2Bc.jpg");
+        assert_eq!(url_encode("日"), "%E6%97%A5");
+    }
+}
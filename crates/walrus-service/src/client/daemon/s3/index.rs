@@ -0,0 +1,311 @@
+// Copyright (c) Walrus Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! The local index of the objects stored through the S3-compatible API.
+
+use std::{
+    collections::BTreeMap,
+    io,
+    ops::Bound,
+    path::{Path, PathBuf},
+};
+
+use anyhow::Context as _;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_with::{serde_as, DisplayFromStr};
+use tokio::sync::RwLock;
+use walrus_core::BlobId;
+
+/// An object stored through the S3-compatible API.
+#[serde_as]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct S3Object {
+    /// The ID of the blob holding the data of the object.
+    #[serde_as(as = "DisplayFromStr")]
+    pub blob_id: BlobId,
+    /// The size of the object in bytes.
+    pub size: u64,
+    /// The content type with which the object was stored, if any.
+    pub content_type: Option<String>,
+    /// The time at which the object was stored.
+    pub last_modified: DateTime<Utc>,
+}
+
+/// The objects in each bucket, keyed by the bucket name and the object key.
+type Buckets = BTreeMap<String, BTreeMap<String, S3Object>>;
+
+/// The objects stored through the S3-compatible API.
+///
+/// Walrus has no notion of buckets or keys, so the mapping from keys to blobs is kept in memory and
+/// written to a JSON file on every change. Buckets exist implicitly as long as they contain
+/// objects.
+#[derive(Debug)]
+pub(crate) struct S3Index {
+    path: PathBuf,
+    buckets: RwLock<Buckets>,
+}
+
+impl S3Index {
+    /// Opens the index stored in the file at `path`, or creates an empty index if the file does
+    /// not exist.
+    pub(crate) fn open(path: PathBuf) -> anyhow::Result<Self> {
+        let buckets = match std::fs::read(&path) {
+            Ok(bytes) => serde_json::from_slice(&bytes)
+                .with_context(|| format!("failed to parse the S3 index at {}", path.display()))?,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => Buckets::new(),
+            Err(error) => {
+                return Err(error)
+                    .with_context(|| format!("failed to read the S3 index at {}", path.display()))
+            }
+        };
+        Ok(Self {
+            path,
+            buckets: RwLock::new(buckets),
+        })
+    }
+
+    /// Returns the object with the key in the bucket, if any.
+    pub(crate) async fn get(&self, bucket: &str, key: &str) -> Option<S3Object> {
+        self.buckets.read().await.get(bucket)?.get(key).cloned()
+    }
+
+    /// Inserts the object with the key into the bucket, replacing any previous object with the
+    /// same key, and persists the index.
+    pub(crate) async fn insert(
+        &self,
+        bucket: &str,
+        key: &str,
+        object: S3Object,
+    ) -> anyhow::Result<()> {
+        let mut buckets = self.buckets.write().await;
+        let previous = buckets
+            .entry(bucket.to_owned())
+            .or_default()
+            .insert(key.to_owned(), object);
+
+        let result = persist(&self.path, &buckets).await;
+        if result.is_err() {
+            // Keep the index in memory consistent with the one on disk.
+            match previous {
+                Some(previous) => {
+                    if let Some(objects) = buckets.get_mut(bucket) {
+                        objects.insert(key.to_owned(), previous);
+                    }
+                }
+                None => remove_from(&mut buckets, bucket, key),
+            }
+        }
+        result
+    }
+
+    /// Removes the object with the key from the bucket, if any, and persists the index.
+    pub(crate) async fn remove(&self, bucket: &str, key: &str) -> anyhow::Result<()> {
+        let mut buckets = self.buckets.write().await;
+        let Some(previous) = buckets
+            .get(bucket)
+            .and_then(|objects| objects.get(key))
+            .cloned()
+        else {
+            return Ok(());
+        };
+        remove_from(&mut buckets, bucket, key);
+
+        let result = persist(&self.path, &buckets).await;
+        if result.is_err() {
+            buckets
+                .entry(bucket.to_owned())
+                .or_default()
+                .insert(key.to_owned(), previous);
+        }
+        result
+    }
+
+    /// Lists the objects in the bucket whose keys start with `prefix` and come after
+    /// `start_after`, in the order of their keys.
+    ///
+    /// If a `delimiter` is given, the keys that contain it after the prefix are grouped into a
+    /// common prefix, which ends with the first occurrence of the delimiter. At most `max_keys`
+    /// objects and common prefixes are returned.
+    pub(crate) async fn list(
+        &self,
+        bucket: &str,
+        prefix: &str,
+        delimiter: Option<&str>,
+        start_after: Option<&str>,
+        max_keys: usize,
+    ) -> Listing {
+        let buckets = self.buckets.read().await;
+        let mut listing = Listing::default();
+        let Some(objects) = buckets.get(bucket) else {
+            return listing;
+        };
+
+        let lower_bound = match start_after {
+            Some(start_after) if start_after >= prefix => Bound::Excluded(start_after),
+            _ => Bound::Included(prefix),
+        };
+        let mut entries = 0;
+        for (key, object) in objects
+            .range::<str, _>((lower_bound, Bound::Unbounded))
+            .take_while(|(key, _)| key.starts_with(prefix))
+        {
+            let common_prefix = delimiter.and_then(|delimiter| {
+                key[prefix.len()..]
+                    .find(delimiter)
+                    .map(|index| &key[..prefix.len() + index + delimiter.len()])
+            });
+
+            if let Some(common_prefix) = common_prefix {
+                // Skip the keys grouped into a common prefix that was already returned, possibly
+                // on a previous page.
+                if listing.common_prefixes.last().map(String::as_str) == Some(common_prefix)
+                    || start_after == Some(common_prefix)
+                {
+                    continue;
+                }
+            }
+
+            if entries == max_keys {
+                listing.is_truncated = entries > 0;
+                break;
+            }
+            entries += 1;
+            match common_prefix {
+                Some(common_prefix) => {
+                    listing.common_prefixes.push(common_prefix.to_owned());
+                    listing.last_entry = Some(common_prefix.to_owned());
+                }
+                None => {
+                    listing.objects.push((key.clone(), object.clone()));
+                    listing.last_entry = Some(key.clone());
+                }
+            }
+        }
+        listing
+    }
+}
+
+/// A page of the objects in a bucket.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub(crate) struct Listing {
+    /// The keys of the objects, with the objects.
+    pub objects: Vec<(String, S3Object)>,
+    /// The common prefixes of the keys that contain the delimiter.
+    pub common_prefixes: Vec<String>,
+    /// The key or common prefix that was listed last.
+    pub last_entry: Option<String>,
+    /// Whether there are more objects or common prefixes after the listed ones.
+    pub is_truncated: bool,
+}
+
+fn remove_from(buckets: &mut Buckets, bucket: &str, key: &str) {
+    if let Some(objects) = buckets.get_mut(bucket) {
+        objects.remove(key);
+        if objects.is_empty() {
+            buckets.remove(bucket);
+        }
+    }
+}
+
+/// Writes the index to a temporary file, which then atomically replaces the file at `path`.
+async fn persist(path: &Path, buckets: &Buckets) -> anyhow::Result<()> {
+    let bytes = serde_json::to_vec(buckets).context("failed to serialize the S3 index")?;
+    let mut temporary_path = path.as_os_str().to_owned();
+    temporary_path.push(".tmp");
+
+    tokio::fs::write(&temporary_path, bytes)
+        .await
+        .context("failed to write the S3 index")?;
+    tokio::fs::rename(&temporary_path, path)
+        .await
+        .context("failed to replace the S3 index")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn object(byte: u8) -> S3Object {
+        S3Object {
+            blob_id: BlobId([byte; 32]),
+            size: byte.into(),
+            content_type: None,
+            last_modified: DateTime::from_timestamp(1_700_000_000, 0).expect("valid timestamp"),
+        }
+    }
+
+    async fn index_with_keys(directory: &Path, keys: &[&str]) -> S3Index {
+        let index = S3Index::open(directory.join("index.json")).expect("the index can be opened");
+        for (byte, key) in keys.iter().enumerate() {
+            index
+                .insert("bucket", key, object(byte as u8))
+                .await
+                .expect("the index can be written");
+        }
+        index
+    }
+
+    fn keys(listing: &Listing) -> Vec<&str> {
+        listing
+            .objects
+            .iter()
+            .map(|(key, _)| key.as_str())
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn persists_objects() -> anyhow::Result<()> {
+        let directory = tempfile::tempdir()?;
+        let index = index_with_keys(directory.path(), &["a", "b"]).await;
+        index.remove("bucket", "a").await?;
+        index.insert("other", "c", object(7)).await?;
+
+        let reopened = S3Index::open(directory.path().join("index.json"))?;
+        assert_eq!(reopened.get("bucket", "a").await, None);
+        assert_eq!(reopened.get("bucket", "b").await, Some(object(1)));
+        assert_eq!(reopened.get("other", "c").await, Some(object(7)));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn lists_objects_in_pages() -> anyhow::Result<()> {
+        let directory = tempfile::tempdir()?;
+        let index = index_with_keys(directory.path(), &["a/1", "a/2", "a/3", "b"]).await;
+
+        let first = index.list("bucket", "a/", None, None, 2).await;
+        assert_eq!(keys(&first), ["a/1", "a/2"]);
+        assert!(first.is_truncated);
+
+        let second = index
+            .list("bucket", "a/", None, first.last_entry.as_deref(), 2)
+            .await;
+        assert_eq!(keys(&second), ["a/3"]);
+        assert!(!second.is_truncated);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn groups_keys_by_delimiter() -> anyhow::Result<()> {
+        let directory = tempfile::tempdir()?;
+        let index = index_with_keys(
+            directory.path(),
+            &["docs/a", "docs/b", "images/a", "images/b", "readme"],
+        )
+        .await;
+
+        let first = index.list("bucket", "", Some("/"), None, 2).await;
+        assert!(first.objects.is_empty());
+        assert_eq!(first.common_prefixes, ["docs/", "images/"]);
+        assert!(first.is_truncated);
+
+        let second = index
+            .list("bucket", "", Some("/"), first.last_entry.as_deref(), 2)
+            .await;
+        assert_eq!(keys(&second), ["readme"]);
+        assert!(second.common_prefixes.is_empty());
+        assert!(!second.is_truncated);
+        Ok(())
+    }
+}
@@ -0,0 +1,104 @@
+// Copyright (c) Walrus Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Writing of the XML documents returned by the S3-compatible API.
+
+use std::fmt::{Display, Write as _};
+
+/// The namespace of the elements of the S3 API.
+pub(super) const S3_NAMESPACE: &str = "http://s3.amazonaws.com/doc/2006-03-01/";
+
+/// Writes an XML document element by element.
+#[derive(Debug)]
+pub(super) struct XmlWriter {
+    xml: String,
+    open_elements: Vec<&'static str>,
+}
+
+impl XmlWriter {
+    /// Starts a document with the given root element, in the given namespace if any.
+    pub(super) fn new(root: &'static str, namespace: Option<&str>) -> Self {
+        let mut xml = String::from(r#"<?xml version="1.0" encoding="UTF-8"?>"#);
+        let namespace = namespace
+            .map(|namespace| format!(r#" xmlns="{}""#, escape(namespace)))
+            .unwrap_or_default();
+        write!(xml, "<{root}{namespace}>").expect("writing to a string does not fail");
+        Self {
+            xml,
+            open_elements: vec![root],
+        }
+    }
+
+    /// Writes an element with the value as text.
+    pub(super) fn element(&mut self, name: &'static str, value: impl Display) -> &mut Self {
+        write!(self.xml, "<{name}>{}</{name}>", escape(&value.to_string()))
+            .expect("writing to a string does not fail");
+        self
+    }
+
+    /// Opens an element, which contains the elements written until it is closed with
+    /// [`Self::end`].
+    pub(super) fn start(&mut self, name: &'static str) -> &mut Self {
+        write!(self.xml, "<{name}>").expect("writing to a string does not fail");
+        self.open_elements.push(name);
+        self
+    }
+
+    /// Closes the element opened last.
+    pub(super) fn end(&mut self) -> &mut Self {
+        let name = self
+            .open_elements
+            .pop()
+            .expect("only opened elements are closed");
+        write!(self.xml, "</{name}>").expect("writing to a string does not fail");
+        self
+    }
+
+    /// Closes all open elements and returns the document.
+    pub(super) fn finish(mut self) -> String {
+        while !self.open_elements.is_empty() {
+            self.end();
+        }
+        self.xml
+    }
+}
+
+/// Escapes the characters with a special meaning in XML text and attribute values.
+fn escape(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for character in value.chars() {
+        match character {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            _ => escaped.push(character),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn writes_nested_and_escaped_elements() {
+        let mut writer = XmlWriter::new("Result", Some(S3_NAMESPACE));
+        writer
+            .element("Name", "a<b>&\"c\"")
+            .start("Contents")
+            .element("Size", 42);
+        assert_eq!(
+            writer.finish(),
+            concat!(
+                r#"<?xml version="1.0" encoding="UTF-8"?>"#,
+                r#"<Result xmlns="http://s3.amazonaws.com/doc/2006-03-01/">"#,
+                "<Name>a&lt;b&gt;&amp;&quot;c&quot;</Name>",
+                "<Contents><Size>42</Size></Contents>",
+                "</Result>",
+            )
+        );
+    }
+}
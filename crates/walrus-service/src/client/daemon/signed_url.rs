@@ -0,0 +1,537 @@
+// Copyright (c) Walrus Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Signed URLs, which grant temporary access to the daemon without an API key.
+//!
+//! Similar to S3 presigned URLs, the operator signs a URL with the URL signing key of the daemon,
+//! and hands it out to a client, which can then store or read a blob until the URL expires. A
+//! signed URL carries the following query parameters:
+//!
+//! - `X-Walrus-Expires`: the Unix timestamp in seconds at which the URL expires;
+//! - `X-Walrus-Max-Size`: the maximum size in bytes of the body of the request, required for all
+//!   methods other than `GET` and `HEAD`;
+//! - `X-Walrus-Nonce`: a random value that makes the URL single-use, required for all methods
+//!   other than `GET` and `HEAD`;
+//! - `X-Walrus-Identity` (optional): the identity of the API key on whose behalf the URL is
+//!   signed, whose permissions, daily quota, and concurrent uploads apply to the request;
+//! - `X-Walrus-Signature`: the hex-encoded HMAC-SHA256 of the method, the path, and the query of
+//!   the URL without the signature, separated by newlines.
+//!
+//! As all other query parameters are signed, a signed URL to store a blob also fixes the number of
+//! epochs, the deletability, and the recipient of the blob object. A URL signed for `GET` also
+//! authorizes `HEAD` requests.
+//!
+//! The nonce of a URL is recorded by the publisher in the same cache that suppresses the replay of
+//! JWTs, so a URL signed to store a blob can store at most one blob of at most the maximum size.
+
+use std::{
+    fmt::{self, Debug},
+    sync::Arc,
+};
+
+use axum::{
+    body::HttpBody as _,
+    extract::{MatchedPath, Query, Request, State},
+    http::{Method, Uri},
+    middleware::Next,
+    response::{IntoResponse as _, Response},
+};
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use reqwest::Url;
+use serde::Deserialize;
+use sha2::Sha256;
+use walrus_proc_macros::RestApiError;
+use walrus_rest_client::api::errors::DAEMON_ERROR_DOMAIN as ERROR_DOMAIN;
+
+use super::{
+    routes::{BLOB_GET_ENDPOINT, BLOB_OBJECT_GET_ENDPOINT},
+    site::{SITE_FILE_ENDPOINT, SITE_INDEX_ENDPOINT, SITE_ROOT_ENDPOINT},
+};
+use crate::common::api::RestApiError;
+
+/// The query parameter with the expiry of the signed URL.
+const EXPIRES_PARAM: &str = "X-Walrus-Expires";
+/// The query parameter with the maximum size of the body of the request.
+const MAX_SIZE_PARAM: &str = "X-Walrus-Max-Size";
+/// The query parameter with the nonce that makes the signed URL single-use.
+const NONCE_PARAM: &str = "X-Walrus-Nonce";
+/// The query parameter with the identity of the API key on whose behalf the URL is signed.
+const IDENTITY_PARAM: &str = "X-Walrus-Identity";
+/// The query parameter with the signature of the URL.
+const SIGNATURE_PARAM: &str = "X-Walrus-Signature";
+/// The routes that require a signed URL if signed reads are enforced.
+const READ_ENDPOINTS: [&str; 5] = [
+    BLOB_GET_ENDPOINT,
+    BLOB_OBJECT_GET_ENDPOINT,
+    SITE_ROOT_ENDPOINT,
+    SITE_INDEX_ENDPOINT,
+    SITE_FILE_ENDPOINT,
+];
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// The query parameters of a signed URL.
+#[derive(Debug, Deserialize)]
+struct SignedUrlParams {
+    #[serde(rename = "X-Walrus-Expires")]
+    expires_at: Option<i64>,
+    #[serde(rename = "X-Walrus-Max-Size")]
+    max_size: Option<u64>,
+    #[serde(rename = "X-Walrus-Nonce")]
+    nonce: Option<String>,
+    #[serde(rename = "X-Walrus-Identity")]
+    identity: Option<String>,
+    #[serde(rename = "X-Walrus-Signature")]
+    signature: Option<String>,
+}
+
+/// The secret key with which URLs are signed and verified.
+#[derive(Clone)]
+pub(crate) struct UrlSigningKey(Arc<[u8]>);
+
+impl Debug for UrlSigningKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("UrlSigningKey(<redacted>)")
+    }
+}
+
+impl UrlSigningKey {
+    pub(crate) fn new(key: &[u8]) -> Self {
+        Self(key.into())
+    }
+
+    /// Returns the `url` signed for requests with the given `method` until `expires_at`, with a
+    /// body of at most `max_size` bytes, and on behalf of the API key with the given `identity`,
+    /// if specified.
+    ///
+    /// URLs for methods other than `GET` and `HEAD` are single-use, and must be signed with a
+    /// maximum size.
+    pub(crate) fn sign_url(
+        &self,
+        method: &Method,
+        mut url: Url,
+        expires_at: DateTime<Utc>,
+        max_size: Option<u64>,
+        identity: Option<&str>,
+    ) -> Result<Url, SignedUrlError> {
+        if !is_read(method) && max_size.is_none() {
+            return Err(SignedUrlError::Unbounded);
+        }
+        {
+            let mut query = url.query_pairs_mut();
+            query.append_pair(EXPIRES_PARAM, &expires_at.timestamp().to_string());
+            if let Some(max_size) = max_size {
+                query.append_pair(MAX_SIZE_PARAM, &max_size.to_string());
+            }
+            if !is_read(method) {
+                query.append_pair(NONCE_PARAM, &format!("{:032x}", rand::random::<u128>()));
+            }
+            if let Some(identity) = identity {
+                query.append_pair(IDENTITY_PARAM, identity);
+            }
+        }
+        let signature = self
+            .mac(method, url.path(), url.query().unwrap_or_default())
+            .finalize()
+            .into_bytes();
+        url.query_pairs_mut()
+            .append_pair(SIGNATURE_PARAM, &hex::encode(signature));
+        Ok(url)
+    }
+
+    /// Verifies the signature of the request with the given method and URI at time `now`, and
+    /// returns the access granted by the signed URL.
+    ///
+    /// Returns `Ok(None)` if the URI is not signed.
+    fn verify(
+        &self,
+        method: &Method,
+        uri: &Uri,
+        now: DateTime<Utc>,
+    ) -> Result<Option<SignedUrlGrant>, SignedUrlError> {
+        let Query(SignedUrlParams {
+            expires_at,
+            max_size,
+            nonce,
+            identity,
+            signature,
+        }) = Query::try_from_uri(uri).map_err(|_| SignedUrlError::Malformed)?;
+        let Some(signature) = signature else {
+            return Ok(None);
+        };
+
+        let signature = hex::decode(signature).map_err(|_| SignedUrlError::Malformed)?;
+        let query = uri.query().unwrap_or_default();
+        let signed_query = query
+            .split('&')
+            .filter(|pair| !pair.starts_with(&format!("{SIGNATURE_PARAM}=")))
+            .collect::<Vec<_>>()
+            .join("&");
+        // The MAC is verified in constant time.
+        self.mac(method, uri.path(), &signed_query)
+            .verify_slice(&signature)
+            .map_err(|_| SignedUrlError::InvalidSignature)?;
+
+        let expires_at = expires_at
+            .and_then(|timestamp| DateTime::from_timestamp(timestamp, 0))
+            .ok_or(SignedUrlError::Malformed)?;
+        if expires_at <= now {
+            return Err(SignedUrlError::Expired(expires_at));
+        }
+        if !is_read(method) && (max_size.is_none() || nonce.is_none()) {
+            return Err(SignedUrlError::Unbounded);
+        }
+        Ok(Some(SignedUrlGrant {
+            max_size,
+            nonce,
+            identity,
+            expires_at,
+        }))
+    }
+
+    fn mac(&self, method: &Method, path: &str, query: &str) -> HmacSha256 {
+        // A URL signed for `GET` also authorizes `HEAD` requests.
+        let method = if method == Method::HEAD {
+            &Method::GET
+        } else {
+            method
+        };
+        let mut mac = HmacSha256::new_from_slice(&self.0).expect("HMAC accepts keys of any length");
+        mac.update(format!("{method}\n{path}\n{query}").as_bytes());
+        mac
+    }
+}
+
+/// Returns true if the method only reads, and a signed URL for it may be used repeatedly.
+fn is_read(method: &Method) -> bool {
+    matches!(*method, Method::GET | Method::HEAD)
+}
+
+/// The configuration of the signed URLs accepted by the daemon.
+#[derive(Debug, Clone)]
+pub(crate) struct SignedUrls {
+    /// The key with which the URLs are signed.
+    pub key: UrlSigningKey,
+    /// Whether reading blobs and websites requires a signed URL.
+    pub require_for_reads: bool,
+}
+
+/// The access granted by a valid signed URL, which is added to the extensions of the request.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct SignedUrlGrant {
+    /// The maximum size in bytes of the body of the request.
+    pub max_size: Option<u64>,
+    /// The nonce that makes the URL single-use, set for all methods other than `GET` and `HEAD`.
+    pub nonce: Option<String>,
+    /// The identity of the API key on whose behalf the URL is signed.
+    pub identity: Option<String>,
+    /// The time at which the URL expires.
+    pub expires_at: DateTime<Utc>,
+}
+
+impl SignedUrlGrant {
+    /// Checks that a body of `size` bytes is allowed by the signed URL.
+    pub(crate) fn check_size(&self, size: u64) -> Result<(), SignedUrlError> {
+        match self.max_size {
+            Some(max_size) if size > max_size => Err(SignedUrlError::TooLarge(max_size)),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Verifies the signature of signed URLs and grants access to the holders of valid ones.
+///
+/// Requests with an invalid or expired signature are rejected. If signed reads are enforced,
+/// requests to read blobs or websites are also rejected without a signed URL.
+pub(super) async fn signed_url_layer(
+    State(signed_urls): State<Arc<SignedUrls>>,
+    matched_path: Option<MatchedPath>,
+    mut request: Request,
+    next: Next,
+) -> Response {
+    let grant = match signed_urls
+        .key
+        .verify(request.method(), request.uri(), Utc::now())
+    {
+        Ok(grant) => grant,
+        Err(error) => {
+            tracing::debug!(?error, "rejecting a request with an invalid signed URL");
+            return error.into_response();
+        }
+    };
+
+    if let Some(grant) = grant {
+        // The size of the body is checked again once it has been received.
+        let body_size_hint = request.body().size_hint().lower();
+        if let Err(error) = grant.check_size(body_size_hint) {
+            return error.into_response();
+        }
+        request.extensions_mut().insert(grant);
+    } else if signed_urls.require_for_reads
+        && is_read(request.method())
+        && matched_path.is_some_and(|path| READ_ENDPOINTS.contains(&path.as_str()))
+    {
+        return SignedUrlError::SignatureRequired.into_response();
+    }
+    next.run(request).await
+}
+
+#[derive(Debug, thiserror::Error, RestApiError)]
+#[rest_api_error(domain = ERROR_DOMAIN)]
+pub(crate) enum SignedUrlError {
+    /// The signature, expiry, or maximum size of the signed URL cannot be parsed.
+    #[error("the signed URL is malformed")]
+    #[rest_api_error(reason = "MALFORMED_SIGNED_URL", status = ApiStatusCode::InvalidArgument)]
+    Malformed,
+
+    /// The signature does not match the URL.
+    #[error("the signature of the URL is invalid")]
+    #[rest_api_error(reason = "INVALID_URL_SIGNATURE", status = ApiStatusCode::PermissionDenied)]
+    InvalidSignature,
+
+    /// The signed URL has expired.
+    #[error("the signed URL expired at {0}")]
+    #[rest_api_error(reason = "SIGNED_URL_EXPIRED", status = ApiStatusCode::PermissionDenied)]
+    Expired(DateTime<Utc>),
+
+    /// The body of the request is larger than allowed by the signed URL.
+    #[error("the request is larger than the maximum of {0} bytes allowed by the signed URL")]
+    #[rest_api_error(reason = "SIGNED_URL_SIZE_EXCEEDED", status = ApiStatusCode::PayloadTooLarge)]
+    TooLarge(u64),
+
+    /// A URL for a method other than `GET` and `HEAD` lacks a maximum size or a nonce.
+    #[error("signed URLs for methods other than GET and HEAD require a maximum size and a nonce")]
+    #[rest_api_error(reason = "UNBOUNDED_SIGNED_URL", status = ApiStatusCode::InvalidArgument)]
+    Unbounded,
+
+    /// The single-use signed URL has already been used.
+    #[error("the signed URL has already been used")]
+    #[rest_api_error(reason = "SIGNED_URL_ALREADY_USED", status = ApiStatusCode::PermissionDenied)]
+    AlreadyUsed,
+
+    /// The signed URL does not name the identity of an API key of the publisher, although the
+    /// publisher is configured with API keys.
+    #[error("the signed URL does not name the identity of an API key of the publisher")]
+    #[rest_api_error(
+        reason = "UNKNOWN_SIGNED_URL_IDENTITY",
+        status = ApiStatusCode::PermissionDenied
+    )]
+    UnknownIdentity,
+
+    /// The daemon only serves reads through signed URLs.
+    #[error("reading from this aggregator requires a signed URL")]
+    #[rest_api_error(reason = "SIGNATURE_REQUIRED", status = ApiStatusCode::PermissionDenied)]
+    SignatureRequired,
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{body::Body, http::StatusCode, middleware, routing::get, Router};
+    use chrono::TimeDelta;
+    use tower::ServiceExt as _;
+
+    use super::*;
+
+    const BASE_URL: &str = "https://aggregator.example.com";
+    const BLOB_PATH: &str = "/v1/blobs/M2rJRVJ6BIwt7CXcwGQkp5IEbCQOTn7uVQl2TZcLi-A";
+
+    fn key() -> UrlSigningKey {
+        UrlSigningKey::new(b"a secret key")
+    }
+
+    fn signed_uri(method: &Method, path_and_query: &str, max_size: Option<u64>) -> Uri {
+        let url = Url::parse(&format!("{BASE_URL}{path_and_query}")).unwrap();
+        let url = key()
+            .sign_url(
+                method,
+                url,
+                Utc::now() + TimeDelta::hours(1),
+                max_size,
+                None,
+            )
+            .unwrap();
+        url.as_str()
+            .strip_prefix(BASE_URL)
+            .unwrap()
+            .parse()
+            .unwrap()
+    }
+
+    #[test]
+    fn verifies_signed_urls() {
+        let uri = signed_uri(&Method::PUT, "/v1/blobs?epochs=5", Some(1024));
+        let grant = key()
+            .verify(&Method::PUT, &uri, Utc::now())
+            .unwrap()
+            .unwrap();
+        assert_eq!(grant.max_size, Some(1024));
+        assert!(grant.nonce.is_some());
+        assert_eq!(grant.identity, None);
+    }
+
+    #[test]
+    fn signs_urls_on_behalf_of_an_identity() {
+        let url = Url::parse(&format!("{BASE_URL}/v1/blobs")).unwrap();
+        let url = key()
+            .sign_url(
+                &Method::PUT,
+                url,
+                Utc::now() + TimeDelta::hours(1),
+                Some(1024),
+                Some("alice"),
+            )
+            .unwrap();
+        let uri: Uri = url
+            .as_str()
+            .strip_prefix(BASE_URL)
+            .unwrap()
+            .parse()
+            .unwrap();
+        let grant = key()
+            .verify(&Method::PUT, &uri, Utc::now())
+            .unwrap()
+            .unwrap();
+        assert_eq!(grant.identity.as_deref(), Some("alice"));
+
+        let uri: Uri = uri.to_string().replace("alice", "bob").parse().unwrap();
+        assert!(matches!(
+            key().verify(&Method::PUT, &uri, Utc::now()),
+            Err(SignedUrlError::InvalidSignature)
+        ));
+    }
+
+    #[test]
+    fn write_urls_are_single_use_and_bounded() {
+        let url = Url::parse(&format!("{BASE_URL}/v1/blobs")).unwrap();
+        let expires_at = Utc::now() + TimeDelta::hours(1);
+        assert!(matches!(
+            key().sign_url(&Method::PUT, url, expires_at, None, None),
+            Err(SignedUrlError::Unbounded)
+        ));
+
+        // Two URLs signed for the same request carry different nonces.
+        let first = signed_uri(&Method::PUT, "/v1/blobs", Some(1024));
+        let second = signed_uri(&Method::PUT, "/v1/blobs", Some(1024));
+        assert_ne!(
+            key().verify(&Method::PUT, &first, Utc::now()).unwrap(),
+            key().verify(&Method::PUT, &second, Utc::now()).unwrap()
+        );
+
+        // A URL without a nonce is rejected even if its signature is valid.
+        let query = format!(
+            "{EXPIRES_PARAM}={}&{MAX_SIZE_PARAM}=1024",
+            expires_at.timestamp()
+        );
+        let signature = key()
+            .mac(&Method::PUT, "/v1/blobs", &query)
+            .finalize()
+            .into_bytes();
+        let uri = format!(
+            "/v1/blobs?{query}&{SIGNATURE_PARAM}={}",
+            hex::encode(signature)
+        );
+        let uri: Uri = uri.parse().unwrap();
+        assert!(matches!(
+            key().verify(&Method::PUT, &uri, Utc::now()),
+            Err(SignedUrlError::Unbounded)
+        ));
+    }
+
+    #[test]
+    fn ignores_unsigned_urls() {
+        let uri: Uri = "/v1/blobs?epochs=5".parse().unwrap();
+        assert_eq!(key().verify(&Method::PUT, &uri, Utc::now()).unwrap(), None);
+    }
+
+    #[test]
+    fn get_urls_authorize_head_requests() {
+        let uri = signed_uri(&Method::GET, BLOB_PATH, None);
+        assert!(key().verify(&Method::HEAD, &uri, Utc::now()).is_ok());
+    }
+
+    #[test]
+    fn rejects_modified_urls() {
+        let uri = signed_uri(&Method::PUT, "/v1/blobs?epochs=5", Some(1024));
+        for (method, uri) in [
+            (Method::POST, uri.to_string()),
+            (
+                Method::PUT,
+                uri.to_string().replace("/v1/blobs", "/v1/blobz"),
+            ),
+            (
+                Method::PUT,
+                uri.to_string().replace("epochs=5", "epochs=53"),
+            ),
+            (
+                Method::PUT,
+                uri.to_string().replace("Max-Size=1024", "Max-Size=1048576"),
+            ),
+            (Method::PUT, format!("{uri}&deletable=true")),
+        ] {
+            let uri: Uri = uri.parse().unwrap();
+            assert!(matches!(
+                key().verify(&method, &uri, Utc::now()),
+                Err(SignedUrlError::InvalidSignature)
+            ));
+        }
+        assert!(matches!(
+            UrlSigningKey::new(b"another key").verify(&Method::PUT, &uri, Utc::now()),
+            Err(SignedUrlError::InvalidSignature)
+        ));
+    }
+
+    #[test]
+    fn rejects_expired_urls() {
+        let uri = signed_uri(&Method::GET, BLOB_PATH, None);
+        assert!(matches!(
+            key().verify(&Method::GET, &uri, Utc::now() + TimeDelta::hours(2)),
+            Err(SignedUrlError::Expired(_))
+        ));
+    }
+
+    #[test]
+    fn checks_the_size_against_the_grant() {
+        let grant = SignedUrlGrant {
+            max_size: Some(1024),
+            nonce: None,
+            identity: None,
+            expires_at: Utc::now(),
+        };
+        assert!(grant.check_size(1024).is_ok());
+        assert!(matches!(
+            grant.check_size(1025),
+            Err(SignedUrlError::TooLarge(1024))
+        ));
+    }
+
+    #[tokio::test]
+    async fn enforces_signed_reads() {
+        let signed_urls = Arc::new(SignedUrls {
+            key: key(),
+            require_for_reads: true,
+        });
+        let router = Router::new()
+            .route(BLOB_GET_ENDPOINT, get(|| async {}))
+            .route("/status", get(|| async {}))
+            .layer(middleware::from_fn_with_state(
+                signed_urls,
+                signed_url_layer,
+            ));
+
+        let signed = signed_uri(&Method::GET, BLOB_PATH, None).to_string();
+        for (uri, expected_status) in [
+            (BLOB_PATH.to_owned(), StatusCode::FORBIDDEN),
+            (signed.clone(), StatusCode::OK),
+            (
+                signed.replace("X-Walrus-Signature=", "X-Walrus-Signature=00"),
+                StatusCode::FORBIDDEN,
+            ),
+            ("/status".to_owned(), StatusCode::OK),
+        ] {
+            let request = axum::http::Request::get(uri).body(Body::empty()).unwrap();
+            let response = router.clone().oneshot(request).await.unwrap();
+            assert_eq!(response.status(), expected_status);
+        }
+    }
+}
@@ -0,0 +1,287 @@
+// Copyright (c) Walrus Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Serving static websites from manifest blobs.
+//!
+//! A manifest is a blob with a JSON object that maps the paths of the files of a website to the IDs
+//! of the blobs with their content, for example `{"index.html": "<blob ID>", "css/style.css":
+//! "<blob ID>"}`. The website is served below `/site/<manifest blob ID>/`: the path of a request is
+//! resolved through the manifest, a path that names a directory is resolved to its `index.html`,
+//! and the content type of a file is determined from its extension. Requests for paths that are not
+//! in the manifest are answered with the `404.html` of the website, if it has one.
+//!
+//! Websites are only served if the aggregator is started with `--serve-sites`. Since all websites
+//! share the origin of the aggregator, their HTML and script files are sandboxed so that scripts
+//! run in an opaque origin, see [`routes::SANDBOX_ALLOWING_SCRIPTS`].
+
+use std::{collections::HashMap, sync::Arc};
+
+use axum::{
+    extract::{Path, State},
+    http::{HeaderMap, StatusCode},
+    response::{Redirect, Response},
+    Extension,
+};
+use serde::Deserialize;
+use tracing::Level;
+use walrus_core::BlobId;
+use walrus_proc_macros::RestApiError;
+use walrus_rest_client::api::errors::DAEMON_ERROR_DOMAIN as ERROR_DOMAIN;
+
+use super::{
+    content_type,
+    routes::{self, BlobQuery, BlobResponseHeaders},
+    WalrusReadClient,
+};
+use crate::{
+    client::config::ResponseHeadersConfig,
+    common::api::{BlobIdString, RestApiError},
+};
+
+/// The path of the root of the website with the given manifest, which redirects to the index.
+pub const SITE_ROOT_ENDPOINT: &str = "/site/{manifest_id}";
+/// The path of the index of the website with the given manifest.
+pub const SITE_INDEX_ENDPOINT: &str = "/site/{manifest_id}/";
+/// The path of a file of the website with the given manifest.
+pub const SITE_FILE_ENDPOINT: &str = "/site/{manifest_id}/{*path}";
+
+/// The file served for directories.
+const INDEX_FILE: &str = "index.html";
+/// The file served, with the status `404 Not Found`, for paths that are not in the manifest.
+const NOT_FOUND_FILE: &str = "404.html";
+
+/// The manifest of a website, which maps the paths of its files to the IDs of their blobs.
+#[derive(Debug, Deserialize)]
+struct SiteManifest(HashMap<String, BlobIdString>);
+
+impl SiteManifest {
+    /// Parses the manifest, normalizing the paths so that they do not start with a slash.
+    fn parse(data: &[u8]) -> Result<Self, SiteError> {
+        let Self(files) = serde_json::from_slice(data).map_err(|error| {
+            tracing::debug!(?error, "the manifest of the website is invalid");
+            SiteError::InvalidManifest
+        })?;
+        Ok(Self(
+            files
+                .into_iter()
+                .map(|(path, blob_id)| (path.trim_start_matches('/').to_owned(), blob_id))
+                .collect(),
+        ))
+    }
+
+    /// Returns the path and the blob ID of the file that is served for the requested `path`.
+    ///
+    /// An empty path or a path ending with a slash names a directory, whose `index.html` is
+    /// served. Other paths name a file or, if there is no such file, a directory.
+    fn resolve(&self, path: &str) -> Option<(String, BlobId)> {
+        let path = path.trim_start_matches('/');
+        let candidates = if path.is_empty() || path.ends_with('/') {
+            vec![format!("{path}{INDEX_FILE}")]
+        } else {
+            vec![path.to_owned(), format!("{path}/{INDEX_FILE}")]
+        };
+        candidates.into_iter().find_map(|candidate| {
+            let BlobIdString(blob_id) = self.0.get(&candidate)?;
+            Some((candidate, *blob_id))
+        })
+    }
+}
+
+/// Redirects to the index of the website, so that relative links in its pages are resolved below
+/// the root of the website.
+pub(super) async fn redirect_to_site_index(
+    Path(BlobIdString(manifest_id)): Path<BlobIdString>,
+) -> Redirect {
+    Redirect::permanent(&format!("/site/{manifest_id}/"))
+}
+
+/// Serves the index of the website with the given manifest.
+#[tracing::instrument(level = Level::ERROR, skip_all, fields(%manifest_id))]
+pub(super) async fn get_site_index<T: WalrusReadClient>(
+    request_headers: HeaderMap,
+    State(client): State<Arc<T>>,
+    Extension(config): Extension<Arc<ResponseHeadersConfig>>,
+    Path(BlobIdString(manifest_id)): Path<BlobIdString>,
+) -> Response {
+    get_site_file_inner(client.as_ref(), &request_headers, &config, &manifest_id, "").await
+}
+
+/// Serves a file of the website with the given manifest.
+#[tracing::instrument(level = Level::ERROR, skip_all, fields(%manifest_id, %path))]
+pub(super) async fn get_site_file<T: WalrusReadClient>(
+    request_headers: HeaderMap,
+    State(client): State<Arc<T>>,
+    Extension(config): Extension<Arc<ResponseHeadersConfig>>,
+    Path((BlobIdString(manifest_id), path)): Path<(BlobIdString, String)>,
+) -> Response {
+    get_site_file_inner(
+        client.as_ref(),
+        &request_headers,
+        &config,
+        &manifest_id,
+        &path,
+    )
+    .await
+}
+
+async fn get_site_file_inner<T: WalrusReadClient>(
+    client: &T,
+    request_headers: &HeaderMap,
+    config: &ResponseHeadersConfig,
+    manifest_id: &BlobId,
+    path: &str,
+) -> Response {
+    tracing::debug!("reading the manifest of the website");
+    let manifest = match client.read_blob(manifest_id).await {
+        Ok(data) => data,
+        Err(error) => return routes::get_blob_error_response(error, manifest_id),
+    };
+    let manifest = match SiteManifest::parse(&manifest) {
+        Ok(manifest) => manifest,
+        Err(error) => return error.to_response(),
+    };
+
+    let (status, (file_path, blob_id)) = if let Some(file) = manifest.resolve(path) {
+        (StatusCode::OK, file)
+    } else if let Some(file) = manifest.resolve(NOT_FOUND_FILE) {
+        tracing::debug!("the path is not in the manifest, serving the not-found page");
+        (StatusCode::NOT_FOUND, file)
+    } else {
+        return SiteError::FileNotFound.to_response();
+    };
+    tracing::debug!(%file_path, %blob_id, "serving a file of the website");
+
+    let query = BlobQuery {
+        content_type: content_type::content_type_from_extension(&file_path).map(str::to_owned),
+    };
+    let response_headers =
+        match BlobResponseHeaders::new(request_headers, query, config, SITE_FILE_ENDPOINT) {
+            Ok(response_headers) => response_headers.with_inline_active_content(),
+            Err(error) => return error.to_response(),
+        };
+    let mut response = routes::read_blob(client, &blob_id, &response_headers).await;
+    if status == StatusCode::NOT_FOUND && response.status() == StatusCode::OK {
+        *response.status_mut() = status;
+    }
+    response
+}
+
+#[derive(Debug, thiserror::Error, RestApiError)]
+#[rest_api_error(domain = ERROR_DOMAIN)]
+pub(crate) enum SiteError {
+    /// The manifest blob is not a JSON object mapping paths to blob IDs.
+    #[error("the blob is not a valid website manifest")]
+    #[rest_api_error(reason = "INVALID_SITE_MANIFEST", status = ApiStatusCode::FailedPrecondition)]
+    InvalidManifest,
+
+    /// The requested path is not in the manifest of the website.
+    #[error("the requested file does not exist in the website")]
+    #[rest_api_error(reason = "SITE_FILE_NOT_FOUND", status = ApiStatusCode::NotFound)]
+    FileNotFound,
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{
+        body::Body,
+        http::{
+            self,
+            header::{CONTENT_SECURITY_POLICY, CONTENT_TYPE},
+        },
+        routing::get,
+        Router,
+    };
+    use tower::ServiceExt as _;
+    use walrus_test_utils::Result as TestResult;
+
+    use super::*;
+    use crate::client::daemon::test_utils::FakeClient;
+
+    const MANIFEST_ID: BlobId = BlobId([1; 32]);
+    const INDEX_ID: BlobId = BlobId([2; 32]);
+    const DOCS_INDEX_ID: BlobId = BlobId([3; 32]);
+    const STYLE_ID: BlobId = BlobId([4; 32]);
+    const NOT_FOUND_ID: BlobId = BlobId([5; 32]);
+
+    fn manifest() -> String {
+        format!(
+            r#"{{
+                "/index.html": "{INDEX_ID}",
+                "docs/index.html": "{DOCS_INDEX_ID}",
+                "css/style.css": "{STYLE_ID}",
+                "404.html": "{NOT_FOUND_ID}"
+            }}"#
+        )
+    }
+
+    #[test]
+    fn resolves_paths_through_the_manifest() -> TestResult {
+        let manifest = SiteManifest::parse(manifest().as_bytes())?;
+
+        for (path, expected) in [
+            ("", Some(("index.html", INDEX_ID))),
+            ("/index.html", Some(("index.html", INDEX_ID))),
+            ("docs", Some(("docs/index.html", DOCS_INDEX_ID))),
+            ("docs/", Some(("docs/index.html", DOCS_INDEX_ID))),
+            ("css/style.css", Some(("css/style.css", STYLE_ID))),
+            ("css/", None),
+            ("missing.html", None),
+        ] {
+            assert_eq!(
+                manifest.resolve(path),
+                expected.map(|(path, blob_id)| (path.to_owned(), blob_id)),
+                "{path}"
+            );
+        }
+        assert!(SiteManifest::parse(b"[\"index.html\"]").is_err());
+        Ok(())
+    }
+
+    async fn get_response(uri: &str) -> Response {
+        let client = FakeClient::with_blobs(HashMap::from([
+            (MANIFEST_ID, manifest().into_bytes()),
+            (INDEX_ID, b"<h1>index</h1>".to_vec()),
+            (DOCS_INDEX_ID, b"<h1>docs</h1>".to_vec()),
+            (STYLE_ID, b"h1 { color: red; }".to_vec()),
+            (NOT_FOUND_ID, b"<h1>not found</h1>".to_vec()),
+        ]));
+        Router::new()
+            .route(SITE_ROOT_ENDPOINT, get(redirect_to_site_index))
+            .route(SITE_INDEX_ENDPOINT, get(get_site_index))
+            .route(SITE_FILE_ENDPOINT, get(get_site_file))
+            .layer(Extension(Arc::new(ResponseHeadersConfig::default())))
+            .with_state(Arc::new(client))
+            .oneshot(http::Request::get(uri).body(Body::empty()).unwrap())
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn serves_websites_from_manifests() -> TestResult {
+        let response = get_response(&format!("/site/{MANIFEST_ID}")).await;
+        assert_eq!(response.status(), StatusCode::PERMANENT_REDIRECT);
+
+        let response = get_response(&format!("/site/{MANIFEST_ID}/")).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()[CONTENT_TYPE], "text/html; charset=utf-8");
+        assert_eq!(
+            response.headers()[CONTENT_SECURITY_POLICY],
+            routes::SANDBOX_ALLOWING_SCRIPTS
+        );
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await?;
+        assert_eq!(body, "<h1>index</h1>");
+
+        let response = get_response(&format!("/site/{MANIFEST_ID}/css/style.css")).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()[CONTENT_TYPE], "text/css; charset=utf-8");
+
+        let response = get_response(&format!("/site/{MANIFEST_ID}/missing")).await;
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await?;
+        assert_eq!(body, "<h1>not found</h1>");
+
+        let response = get_response(&format!("/site/{INDEX_ID}/")).await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        Ok(())
+    }
+}
@@ -0,0 +1,489 @@
+// Copyright (c) Walrus Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Reads of blobs from multiple sources.
+//!
+//! The aggregator reads blobs directly from the storage nodes of the committee, and can fall back
+//! to peer aggregators if the direct read fails, for example because its connectivity to the
+//! storage nodes is poor. Sources that fail repeatedly are considered unhealthy for an
+//! exponentially increasing time, during which they are only tried after the healthy sources. If
+//! all sources are unhealthy, the aggregator only serves the blobs in its cache, and answers other
+//! reads immediately with an error, until a source can be tried again.
+//!
+//! Peer aggregators are not trusted: the whole blob is read from a peer, even if only a range of it
+//! is requested, and it is only returned if the blob ID computed from its data matches the
+//! requested one.
+
+use std::{
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use chrono::{DateTime, Utc};
+use prometheus::{IntCounterVec, IntGaugeVec};
+use reqwest::{StatusCode, Url};
+use sui_types::base_types::ObjectID;
+use walrus_core::{
+    encoding::{EncodingConfig, EncodingConfigTrait as _},
+    BlobId,
+    EncodingType,
+    EpochCount,
+    SUPPORTED_ENCODING_TYPES,
+};
+use walrus_sdk::{
+    client::{
+        byte_range::{BlobRange, ByteRange},
+        responses::{BlobAvailability, BlobStoreResult},
+    },
+    error::{ClientError, ClientErrorKind, ClientResult},
+    store_when::StoreWhen,
+};
+use walrus_sui::{
+    client::{BlobPersistence, PostStoreAction},
+    types::move_structs::BlobWithAttribute,
+};
+use walrus_utils::metrics::Registry;
+
+use super::{routes::BLOB_GET_ENDPOINT, WalrusReadClient, WalrusWriteClient};
+
+/// The label of the committee in the metrics.
+const COMMITTEE_SOURCE: &str = "committee";
+const SUCCESS: &str = "success";
+const ERROR: &str = "error";
+
+/// The number of consecutive failures after which a source is considered unhealthy.
+const FAILURES_UNTIL_UNHEALTHY: u32 = 5;
+/// The time for which a source is first considered unhealthy, which doubles with every further
+/// failure.
+const MIN_UNHEALTHY_DURATION: Duration = Duration::from_secs(10);
+const MAX_UNHEALTHY_DURATION: Duration = Duration::from_secs(5 * 60);
+
+walrus_utils::metrics::define_metric_set! {
+    #[namespace = "walrus_aggregator_read_source"]
+    /// Metrics of the sources from which the aggregator reads blobs.
+    struct ReadSourceMetrics {
+        #[help = "The number of reads from each source, by outcome."]
+        reads_total: IntCounterVec["source", "outcome"],
+
+        #[help = "Whether each source is currently considered healthy (1) or not (0)."]
+        healthy: IntGaugeVec["source"],
+    }
+}
+
+/// The configuration of the sources from which the aggregator reads blobs.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub(crate) struct ReadSourcesConfig {
+    /// The URLs of the peer aggregators to fall back to, in order.
+    pub peer_aggregators: Vec<Url>,
+    /// The timeout of reads from peer aggregators.
+    pub peer_timeout: Duration,
+}
+
+/// The error returned for reads of blobs that are not cached while all sources are unhealthy.
+#[derive(Debug, thiserror::Error)]
+#[error("all sources of blobs are unhealthy, so only cached blobs are served")]
+pub(crate) struct SourcesUnavailable;
+
+/// A [`WalrusReadClient`] that reads blobs through the wrapped client and, if that fails, from
+/// peer aggregators.
+///
+/// All other operations, including the checks of the blocklist and the lookups of blob objects
+/// and expiries, use the wrapped client.
+#[derive(Debug)]
+pub(crate) struct MultiSourceClient<T> {
+    client: T,
+    peers: Vec<PeerAggregator>,
+    /// The encoding config with which the blobs read from peers are verified.
+    encoding_config: Arc<EncodingConfig>,
+    /// The health of the committee followed by the health of the peers.
+    health: Vec<Mutex<SourceHealth>>,
+    metrics: ReadSourceMetrics,
+}
+
+impl<T: WalrusReadClient + Send + Sync> MultiSourceClient<T> {
+    /// Wraps the client, which reads from the committee, adding the configured peer aggregators as
+    /// fallback sources.
+    ///
+    /// The blobs read from peers are verified against their blob IDs with the `encoding_config`.
+    pub(crate) fn new(
+        client: T,
+        config: ReadSourcesConfig,
+        encoding_config: EncodingConfig,
+        registry: &Registry,
+    ) -> anyhow::Result<Self> {
+        let http_client = reqwest::Client::builder()
+            .timeout(config.peer_timeout)
+            .build()?;
+        let peers: Vec<_> = config
+            .peer_aggregators
+            .into_iter()
+            .map(|url| PeerAggregator {
+                label: url.to_string(),
+                url,
+                client: http_client.clone(),
+            })
+            .collect();
+        if !peers.is_empty() {
+            tracing::info!(
+                peers = ?peers.iter().map(|peer| &peer.label).collect::<Vec<_>>(),
+                "configured peer aggregators as fallback sources"
+            );
+        }
+
+        let client = Self {
+            client,
+            health: (0..=peers.len()).map(|_| Mutex::default()).collect(),
+            peers,
+            encoding_config: Arc::new(encoding_config),
+            metrics: ReadSourceMetrics::new(registry),
+        };
+        for index in 0..client.health.len() {
+            walrus_utils::with_label!(client.metrics.healthy, client.label(index)).set(1);
+        }
+        Ok(client)
+    }
+
+    /// Reads the blob, or the range of it, from the first source that succeeds.
+    ///
+    /// The healthy sources are tried first, in their configured order. Errors that a source
+    /// returns for the blob itself, such as the blob not existing, are returned immediately.
+    async fn read(&self, blob_id: &BlobId, range: Option<ByteRange>) -> ClientResult<BlobRange> {
+        let now = Instant::now();
+        let (healthy, unhealthy): (Vec<_>, Vec<_>) =
+            (0..self.health.len()).partition(|&index| self.lock_health(index).is_healthy(now));
+        if healthy.is_empty() {
+            tracing::debug!(%blob_id, "not reading an uncached blob, as all sources are unhealthy");
+            return Err(ClientError::other(SourcesUnavailable));
+        }
+
+        let mut last_error = None;
+        for index in healthy.into_iter().chain(unhealthy) {
+            let result = self.read_from(index, blob_id, range).await;
+            let is_success = is_ok_or_blob_error(&result);
+            self.record_outcome(index, is_success);
+            match result {
+                Err(error) if !is_success => {
+                    tracing::warn!(
+                        ?error,
+                        %blob_id,
+                        source = self.label(index),
+                        "failed to read a blob from a source"
+                    );
+                    last_error = Some(error);
+                }
+                result => return result,
+            }
+        }
+        Err(last_error.expect("at least one source was tried"))
+    }
+
+    async fn read_from(
+        &self,
+        index: usize,
+        blob_id: &BlobId,
+        range: Option<ByteRange>,
+    ) -> ClientResult<BlobRange> {
+        match (index.checked_sub(1), range) {
+            (None, Some(range)) => self.client.read_blob_range(blob_id, range).await,
+            (None, None) => self.client.read_blob(blob_id).await.map(whole_blob),
+            (Some(peer), range) => {
+                let blob = self.peers[peer].read(blob_id).await?;
+                let blob = verify_blob(self.encoding_config.clone(), *blob_id, blob).await?;
+                match range {
+                    Some(range) => slice_range(whole_blob(blob), range),
+                    None => Ok(whole_blob(blob)),
+                }
+            }
+        }
+    }
+
+    fn record_outcome(&self, index: usize, is_success: bool) {
+        let outcome = if is_success { SUCCESS } else { ERROR };
+        walrus_utils::with_label!(self.metrics.reads_total, self.label(index), outcome).inc();
+
+        let mut health = self.lock_health(index);
+        let was_healthy = health.is_healthy(Instant::now());
+        if is_success {
+            health.record_success();
+        } else {
+            health.record_failure(Instant::now());
+        }
+        let is_healthy = health.unhealthy_until.is_none();
+        if was_healthy && !is_healthy {
+            tracing::warn!(
+                source = self.label(index),
+                failures = health.consecutive_failures,
+                "a source of blobs is unhealthy"
+            );
+        }
+        walrus_utils::with_label!(self.metrics.healthy, self.label(index)).set(is_healthy.into());
+    }
+
+    fn label(&self, index: usize) -> &str {
+        match index.checked_sub(1) {
+            None => COMMITTEE_SOURCE,
+            Some(peer) => &self.peers[peer].label,
+        }
+    }
+
+    fn lock_health(&self, index: usize) -> std::sync::MutexGuard<'_, SourceHealth> {
+        self.health[index]
+            .lock()
+            .expect("the lock is never held across a panic")
+    }
+}
+
+/// Returns whether the read succeeded, or failed because of the blob rather than the source.
+fn is_ok_or_blob_error(result: &ClientResult<BlobRange>) -> bool {
+    result.as_ref().map_or_else(
+        |error| {
+            matches!(
+                error.kind(),
+                ClientErrorKind::BlobIdDoesNotExist
+                    | ClientErrorKind::BlobIdBlocked(_)
+                    | ClientErrorKind::RangeNotSatisfiable { .. }
+            )
+        },
+        |_| true,
+    )
+}
+
+/// Returns the blob if the blob ID computed from its data matches `blob_id`.
+///
+/// As the encoding type of the blob is not known, the blob ID is computed for each supported
+/// encoding type.
+async fn verify_blob(
+    encoding_config: Arc<EncodingConfig>,
+    blob_id: BlobId,
+    blob: Vec<u8>,
+) -> ClientResult<Vec<u8>> {
+    tokio::task::spawn_blocking(move || {
+        let matches = SUPPORTED_ENCODING_TYPES.iter().any(|&encoding_type| {
+            encoding_config
+                .get_for_type(encoding_type)
+                .compute_metadata(&blob)
+                .is_ok_and(|metadata| *metadata.blob_id() == blob_id)
+        });
+        if matches {
+            Ok(blob)
+        } else {
+            Err(ClientError::other(PeerReadError::BlobIdMismatch))
+        }
+    })
+    .await
+    .map_err(ClientError::other)?
+}
+
+/// Returns the range containing the whole blob.
+fn whole_blob(blob: Vec<u8>) -> BlobRange {
+    let blob_size = blob.len() as u64;
+    BlobRange {
+        data: blob,
+        range: 0..blob_size,
+        blob_size,
+    }
+}
+
+impl<T: WalrusReadClient + Send + Sync> WalrusReadClient for MultiSourceClient<T> {
+    async fn read_blob(&self, blob_id: &BlobId) -> ClientResult<Vec<u8>> {
+        self.read(blob_id, None).await.map(|blob| blob.data)
+    }
+
+    async fn read_blob_range(&self, blob_id: &BlobId, range: ByteRange) -> ClientResult<BlobRange> {
+        self.read(blob_id, Some(range)).await
+    }
+
+    async fn get_blob_by_object_id(
+        &self,
+        blob_object_id: &ObjectID,
+    ) -> ClientResult<BlobWithAttribute> {
+        self.client.get_blob_by_object_id(blob_object_id).await
+    }
+
+    fn check_blob_id(&self, blob_id: &BlobId) -> ClientResult<()> {
+        self.client.check_blob_id(blob_id)
+    }
+
+    async fn estimated_blob_expiry(&self, blob_id: &BlobId) -> ClientResult<Option<DateTime<Utc>>> {
+        self.client.estimated_blob_expiry(blob_id).await
+    }
+
+    async fn blob_availability(&self, blob_id: &BlobId) -> ClientResult<BlobAvailability> {
+        self.client.blob_availability(blob_id).await
+    }
+}
+
+impl<T: WalrusWriteClient + Send + Sync> WalrusWriteClient for MultiSourceClient<T> {
+    async fn write_blob(
+        &self,
+        blob: &[u8],
+        encoding_type: Option<EncodingType>,
+        epochs_ahead: EpochCount,
+        store_when: StoreWhen,
+        persistence: BlobPersistence,
+        post_store: PostStoreAction,
+    ) -> ClientResult<BlobStoreResult> {
+        self.client
+            .write_blob(
+                blob,
+                encoding_type,
+                epochs_ahead,
+                store_when,
+                persistence,
+                post_store,
+            )
+            .await
+    }
+
+    fn default_post_store_action(&self) -> PostStoreAction {
+        self.client.default_post_store_action()
+    }
+}
+
+/// The health of a source, which is unhealthy for some time after repeated failures.
+#[derive(Debug, Default)]
+struct SourceHealth {
+    consecutive_failures: u32,
+    unhealthy_until: Option<Instant>,
+}
+
+impl SourceHealth {
+    fn is_healthy(&self, now: Instant) -> bool {
+        !matches!(self.unhealthy_until, Some(until) if until > now)
+    }
+
+    fn record_success(&mut self) {
+        *self = Self::default();
+    }
+
+    fn record_failure(&mut self, now: Instant) {
+        self.consecutive_failures = self.consecutive_failures.saturating_add(1);
+        if let Some(doublings) = self
+            .consecutive_failures
+            .checked_sub(FAILURES_UNTIL_UNHEALTHY)
+        {
+            let duration = MIN_UNHEALTHY_DURATION
+                .saturating_mul(1 << doublings.min(16))
+                .min(MAX_UNHEALTHY_DURATION);
+            self.unhealthy_until = Some(now + duration);
+        }
+    }
+}
+
+/// A peer aggregator, from which blobs are read over HTTP.
+///
+/// The data returned by peers must be verified against the blob ID.
+#[derive(Debug)]
+struct PeerAggregator {
+    label: String,
+    url: Url,
+    client: reqwest::Client,
+}
+
+impl PeerAggregator {
+    /// Reads the whole blob, without verifying it.
+    async fn read(&self, blob_id: &BlobId) -> ClientResult<Vec<u8>> {
+        let url = format!(
+            "{}{}",
+            self.url.as_str().trim_end_matches('/'),
+            BLOB_GET_ENDPOINT.replace("{blob_id}", &blob_id.to_string())
+        );
+        let response = self
+            .client
+            .get(url)
+            .send()
+            .await
+            .map_err(PeerReadError::Request)
+            .map_err(ClientError::other)?;
+        match response.status() {
+            StatusCode::OK => (),
+            StatusCode::NOT_FOUND => return Err(ClientErrorKind::BlobIdDoesNotExist.into()),
+            status => return Err(ClientError::other(PeerReadError::Status(status))),
+        }
+
+        Ok(response
+            .bytes()
+            .await
+            .map_err(PeerReadError::Request)
+            .map_err(ClientError::other)?
+            .to_vec())
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+enum PeerReadError {
+    #[error("the request to the peer aggregator failed: {0}")]
+    Request(reqwest::Error),
+    #[error("the peer aggregator responded with status {0}")]
+    Status(StatusCode),
+    #[error("the data returned by the peer aggregator does not match the blob ID")]
+    BlobIdMismatch,
+}
+
+fn slice_range(blob: BlobRange, range: ByteRange) -> ClientResult<BlobRange> {
+    let blob_size = blob.blob_size;
+    let range = range
+        .resolve(blob_size)
+        .ok_or(ClientErrorKind::RangeNotSatisfiable { blob_size })?;
+    let slice = usize::try_from(range.start).expect("the range is within the blob")
+        ..usize::try_from(range.end).expect("the range is within the blob");
+    Ok(BlobRange {
+        data: blob.data[slice].to_vec(),
+        range,
+        blob_size,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sources_become_unhealthy_after_repeated_failures() {
+        let now = Instant::now();
+        let mut health = SourceHealth::default();
+        for _ in 1..FAILURES_UNTIL_UNHEALTHY {
+            health.record_failure(now);
+            assert!(health.is_healthy(now));
+        }
+
+        health.record_failure(now);
+        assert!(!health.is_healthy(now));
+        assert!(health.is_healthy(now + MIN_UNHEALTHY_DURATION));
+
+        health.record_failure(now);
+        assert!(!health.is_healthy(now + MIN_UNHEALTHY_DURATION));
+        assert!(health.is_healthy(now + 2 * MIN_UNHEALTHY_DURATION));
+
+        health.record_success();
+        assert!(health.is_healthy(now));
+        assert_eq!(health.consecutive_failures, 0);
+    }
+
+    #[tokio::test]
+    async fn rejects_blobs_not_matching_the_blob_id() {
+        let encoding_config = Arc::new(walrus_core::test_utils::encoding_config());
+        let blob = b"the data of a blob".to_vec();
+        let blob_id = *encoding_config
+            .get_for_type(EncodingType::RS2)
+            .compute_metadata(&blob)
+            .expect("the blob can be encoded")
+            .blob_id();
+
+        let verified = verify_blob(encoding_config.clone(), blob_id, blob.clone()).await;
+        assert_eq!(verified.expect("the blob matches its ID"), blob);
+        let mut tampered = blob;
+        tampered[0] ^= 1;
+        let result = verify_blob(encoding_config, blob_id, tampered).await;
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn slices_ranges_from_whole_blobs() {
+        let blob = whole_blob((0..10).collect());
+        let range = slice_range(blob.clone(), ByteRange::Suffix(3)).expect("range is satisfiable");
+        assert_eq!(range.data, vec![7, 8, 9]);
+        assert_eq!(range.range, 7..10);
+        assert!(slice_range(blob, ByteRange::From(10)).is_err());
+    }
+}
@@ -0,0 +1,131 @@
+// Copyright (c) Walrus Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Test utilities for the daemon.
+
+use std::{
+    collections::HashMap,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use chrono::{DateTime, Utc};
+use sui_types::base_types::ObjectID;
+use walrus_core::{BlobId, EncodingType, EpochCount};
+use walrus_sdk::{
+    client::{
+        byte_range::{BlobRange, ByteRange},
+        responses::{BlobAvailability, BlobStoreResult, EventOrObjectId},
+    },
+    error::{ClientError, ClientErrorKind, ClientResult},
+    store_when::StoreWhen,
+};
+use walrus_sui::{
+    client::{BlobPersistence, PostStoreAction},
+    types::move_structs::BlobWithAttribute,
+};
+
+use super::{WalrusReadClient, WalrusWriteClient};
+
+/// A client that serves blobs from memory, counts the reads, and stores all blobs successfully.
+///
+/// Stored blobs are not served afterwards; the blob ID of a stored blob consists of bytes equal
+/// to the length of the blob.
+#[derive(Debug, Default)]
+pub(super) struct FakeClient {
+    /// The blobs served by the client.
+    blobs: HashMap<BlobId, Vec<u8>>,
+    /// The blob served for all blob IDs that are not in `blobs`, if any.
+    default_blob: Option<Vec<u8>>,
+    /// The blob ID that is blocked, if any.
+    pub blocked: Option<BlobId>,
+    reads: AtomicUsize,
+}
+
+impl FakeClient {
+    /// Creates a client that serves `blob` for all blob IDs.
+    pub fn serving(blob: &[u8]) -> Self {
+        Self {
+            default_blob: Some(blob.to_vec()),
+            ..Default::default()
+        }
+    }
+
+    /// Creates a client that serves the `blobs`.
+    pub fn with_blobs(blobs: HashMap<BlobId, Vec<u8>>) -> Self {
+        Self {
+            blobs,
+            ..Default::default()
+        }
+    }
+
+    /// Returns the number of blobs read through the client.
+    pub fn reads(&self) -> usize {
+        self.reads.load(Ordering::SeqCst)
+    }
+}
+
+impl WalrusReadClient for FakeClient {
+    async fn read_blob(&self, blob_id: &BlobId) -> ClientResult<Vec<u8>> {
+        self.reads.fetch_add(1, Ordering::SeqCst);
+        self.blobs
+            .get(blob_id)
+            .or(self.default_blob.as_ref())
+            .cloned()
+            .ok_or_else(|| ClientError::from(ClientErrorKind::BlobIdDoesNotExist))
+    }
+
+    async fn read_blob_range(
+        &self,
+        _blob_id: &BlobId,
+        _range: ByteRange,
+    ) -> ClientResult<BlobRange> {
+        unimplemented!("ranges are not read from the client in the tests of the daemon")
+    }
+
+    async fn get_blob_by_object_id(
+        &self,
+        _blob_object_id: &ObjectID,
+    ) -> ClientResult<BlobWithAttribute> {
+        unimplemented!("blobs are not read by object ID in the tests of the daemon")
+    }
+
+    fn check_blob_id(&self, blob_id: &BlobId) -> ClientResult<()> {
+        if self.blocked == Some(*blob_id) {
+            return Err(ClientError::from(ClientErrorKind::BlobIdBlocked(*blob_id)));
+        }
+        Ok(())
+    }
+
+    async fn estimated_blob_expiry(
+        &self,
+        _blob_id: &BlobId,
+    ) -> ClientResult<Option<DateTime<Utc>>> {
+        Ok(None)
+    }
+
+    async fn blob_availability(&self, _blob_id: &BlobId) -> ClientResult<BlobAvailability> {
+        unimplemented!("the availability of blobs is not requested in the tests of the daemon")
+    }
+}
+
+impl WalrusWriteClient for FakeClient {
+    async fn write_blob(
+        &self,
+        blob: &[u8],
+        _encoding_type: Option<EncodingType>,
+        _epochs_ahead: EpochCount,
+        _store_when: StoreWhen,
+        _persistence: BlobPersistence,
+        _post_store: PostStoreAction,
+    ) -> ClientResult<BlobStoreResult> {
+        Ok(BlobStoreResult::AlreadyCertified {
+            blob_id: BlobId([blob.len() as u8; 32]),
+            event_or_object: EventOrObjectId::Object(ObjectID::ZERO),
+            end_epoch: 10,
+        })
+    }
+
+    fn default_post_store_action(&self) -> PostStoreAction {
+        PostStoreAction::Keep
+    }
+}
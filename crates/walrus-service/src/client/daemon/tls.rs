@@ -0,0 +1,117 @@
+// Copyright (c) Walrus Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Serving the daemon over HTTPS.
+//!
+//! The certificate is either read from PEM files, which are reloaded periodically so that renewed
+//! certificates are picked up without a restart, or provisioned and renewed with ACME (for example,
+//! from Let's Encrypt) using the TLS-ALPN-01 challenge.
+
+use std::{net::SocketAddr, path::PathBuf, time::Duration};
+
+use axum::Router;
+use axum_server::{tls_rustls::RustlsConfig, Handle};
+use futures::StreamExt as _;
+use rustls_acme::caches::DirCache;
+
+/// The interval at which the certificate and key are reloaded from their files.
+const PEM_RELOAD_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/// The source of the TLS certificate of the daemon.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum TlsConfig {
+    /// The certificate chain and private key are read from PEM files.
+    Pem {
+        /// The path to the certificate chain.
+        certificate: PathBuf,
+        /// The path to the private key.
+        key: PathBuf,
+    },
+    /// The certificate is provisioned and renewed with ACME.
+    Acme(AcmeConfig),
+}
+
+/// The configuration of the provisioning of certificates with ACME.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct AcmeConfig {
+    /// The domains for which the certificate is issued.
+    pub domains: Vec<String>,
+    /// The email addresses of the contacts of the ACME account.
+    pub contacts: Vec<String>,
+    /// The directory in which the account key and the certificates are cached, if any.
+    ///
+    /// Without a cache, a new certificate is requested every time the daemon starts, which
+    /// quickly exceeds the rate limits of Let's Encrypt.
+    pub cache_dir: Option<PathBuf>,
+    /// Whether to use the staging environment of Let's Encrypt instead of the production one.
+    pub staging: bool,
+}
+
+/// Serves the `app` over HTTPS at the `address` until the process receives a Ctrl-C signal.
+pub(super) async fn serve(
+    address: SocketAddr,
+    tls_config: TlsConfig,
+    app: Router,
+) -> Result<(), std::io::Error> {
+    let handle = Handle::new();
+    tokio::spawn({
+        let handle = handle.clone();
+        async move {
+            let _ = tokio::signal::ctrl_c().await;
+            handle.graceful_shutdown(None);
+        }
+    });
+
+    match tls_config {
+        TlsConfig::Pem { certificate, key } => {
+            let rustls_config = RustlsConfig::from_pem_file(&certificate, &key).await?;
+            tokio::spawn(reload_pem_files(rustls_config.clone(), certificate, key));
+            axum_server::bind_rustls(address, rustls_config)
+                .handle(handle)
+                .serve(app.into_make_service_with_connect_info::<SocketAddr>())
+                .await
+        }
+        TlsConfig::Acme(acme_config) => {
+            let mut state = rustls_acme::AcmeConfig::new(acme_config.domains)
+                .contact(
+                    acme_config
+                        .contacts
+                        .iter()
+                        .map(|contact| format!("mailto:{contact}")),
+                )
+                .cache_option(acme_config.cache_dir.map(DirCache::new))
+                .directory_lets_encrypt(!acme_config.staging)
+                .state();
+            let acceptor = state.axum_acceptor(state.default_rustls_config());
+            tokio::spawn(async move {
+                while let Some(event) = state.next().await {
+                    match event {
+                        Ok(event) => tracing::info!(?event, "ACME certificate provisioning"),
+                        Err(error) => {
+                            tracing::warn!(?error, "failed to provision an ACME certificate")
+                        }
+                    }
+                }
+            });
+            axum_server::bind(address)
+                .acceptor(acceptor)
+                .handle(handle)
+                .serve(app.into_make_service_with_connect_info::<SocketAddr>())
+                .await
+        }
+    }
+}
+
+/// Periodically reloads the certificate and key, so that renewed certificates are used.
+async fn reload_pem_files(rustls_config: RustlsConfig, certificate: PathBuf, key: PathBuf) {
+    let mut interval = tokio::time::interval(PEM_RELOAD_INTERVAL);
+    // The first tick completes immediately, when the files were just loaded.
+    interval.tick().await;
+    loop {
+        interval.tick().await;
+        match rustls_config.reload_from_pem_file(&certificate, &key).await {
+            Ok(()) => tracing::debug!("reloaded the TLS certificate"),
+            Err(error) => tracing::warn!(?error, "failed to reload the TLS certificate"),
+        }
+    }
+}
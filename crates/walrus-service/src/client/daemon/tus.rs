@@ -0,0 +1,833 @@
+// Copyright (c) Walrus Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Resumable uploads with the [tus protocol](https://tus.io/protocols/resumable-upload).
+//!
+//! A client creates an upload of a given length at [`UPLOADS_ENDPOINT`], with the same query
+//! parameters and authentication as a regular store request, and then sends the data in one or
+//! more `PATCH` requests to the URL of the upload. The data is written to a file until the upload
+//! is complete, at which point the publisher stores it as a blob. The result of the store
+//! operation can be retrieved with a `GET` request to the URL of the upload.
+//!
+//! Uploads are subject to the same limits as regular store requests: their length cannot exceed
+//! the maximum body size of the publisher, and storing their blobs takes one of the permits for
+//! concurrent store requests. If no permit is available, the final `PATCH` request fails with
+//! `429 Too Many Requests` and can be repeated without data.
+//!
+//! The URL of an upload contains a random identifier, which serves as credential for all further
+//! requests to the upload. The `creation`, `termination`, and `expiration` extensions are
+//! supported.
+
+use std::{
+    collections::HashMap,
+    path::{Path as FsPath, PathBuf},
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use anyhow::Context as _;
+use axum::{
+    body::Body,
+    extract::{Path, Query, State},
+    http::{HeaderMap, HeaderName, HeaderValue, StatusCode},
+    response::{IntoResponse, Response},
+    routing::{get, post},
+    Extension,
+    Json,
+    Router,
+};
+use axum_extra::{
+    headers::{authorization::Bearer, Authorization},
+    TypedHeader,
+};
+use chrono::{DateTime, Utc};
+use futures::StreamExt as _;
+use http_body::SizeHint;
+use tokio::{
+    fs::{File, OpenOptions},
+    io::AsyncWriteExt as _,
+    sync::Semaphore,
+};
+use tracing::Level;
+use walrus_sdk::{client::responses::BlobStoreResult, store_when::StoreWhen};
+use walrus_sui::client::{BlobPersistence, PostStoreAction};
+
+use super::{
+    auth::{ApiKeyHolder, PublisherAuthError},
+    auth_layer,
+    routes::{check_blob_size, PublisherQuery, StoreBlobError},
+    signed_url::{SignedUrlError, SignedUrlGrant},
+    PublisherAuthState,
+    StoreLimits,
+    WalrusWriteClient,
+};
+use crate::common::api::RestApiError as _;
+
+/// The endpoint at which resumable uploads are created.
+pub const UPLOADS_ENDPOINT: &str = "/v1/uploads";
+/// The endpoint of a resumable upload.
+pub const UPLOAD_ENDPOINT: &str = "/v1/uploads/{upload_id}";
+
+/// The version of the tus protocol supported by the publisher.
+const TUS_VERSION: &str = "1.0.0";
+/// The extensions of the tus protocol supported by the publisher.
+const TUS_EXTENSIONS: &str = "creation,termination,expiration";
+/// The content type of the data sent in `PATCH` requests.
+const OFFSET_OCTET_STREAM: &str = "application/offset+octet-stream";
+
+/// The response headers that browsers need to expose to clients of the tus protocol.
+pub(super) const EXPOSED_HEADERS: [&str; 8] = [
+    TUS_RESUMABLE,
+    TUS_VERSION_HEADER,
+    TUS_EXTENSION,
+    TUS_MAX_SIZE,
+    UPLOAD_LENGTH,
+    UPLOAD_OFFSET,
+    UPLOAD_EXPIRES,
+    "location",
+];
+const TUS_RESUMABLE: &str = "tus-resumable";
+const TUS_VERSION_HEADER: &str = "tus-version";
+const TUS_EXTENSION: &str = "tus-extension";
+const TUS_MAX_SIZE: &str = "tus-max-size";
+const UPLOAD_LENGTH: &str = "upload-length";
+const UPLOAD_OFFSET: &str = "upload-offset";
+const UPLOAD_EXPIRES: &str = "upload-expires";
+
+/// The configuration of resumable uploads.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct ResumableUploadsConfig {
+    /// The directory in which the data of uploads is stored until they are complete.
+    pub directory: PathBuf,
+    /// The maximum length of an upload, in bytes.
+    ///
+    /// Uploads are further limited to the maximum body size of the publisher.
+    pub max_upload_size: u64,
+    /// The time after its creation at which an upload expires.
+    pub expiry: Duration,
+}
+
+/// The resumable uploads in progress, and the uploads whose data has been stored.
+#[derive(Debug)]
+pub(crate) struct ResumableUploads {
+    config: ResumableUploadsConfig,
+    uploads: Mutex<HashMap<String, UploadEntry>>,
+}
+
+#[derive(Debug, Clone)]
+struct UploadEntry {
+    expires_at: DateTime<Utc>,
+    upload: Arc<tokio::sync::Mutex<Upload>>,
+}
+
+#[derive(Debug)]
+struct Upload {
+    id: String,
+    length: u64,
+    offset: u64,
+    query: PublisherQuery,
+    api_key_holder: Option<Arc<ApiKeyHolder>>,
+    result: Option<BlobStoreResult>,
+}
+
+impl ResumableUploads {
+    /// Creates the resumable uploads, and removes the data of uploads that were in progress when
+    /// the publisher stopped, as they cannot be resumed.
+    pub(crate) fn new(config: ResumableUploadsConfig) -> anyhow::Result<Self> {
+        std::fs::create_dir_all(&config.directory).with_context(|| {
+            format!(
+                "failed to create the directory for resumable uploads at {}",
+                config.directory.display()
+            )
+        })?;
+        remove_upload_files(&config.directory).with_context(|| {
+            format!(
+                "failed to remove previous uploads from {}",
+                config.directory.display()
+            )
+        })?;
+        Ok(Self {
+            config,
+            uploads: Mutex::default(),
+        })
+    }
+
+    fn get(&self, id: &str) -> Result<UploadEntry, ResumableUploadError> {
+        let uploads = self.uploads.lock().expect("mutex should not be poisoned");
+        uploads
+            .get(id)
+            .filter(|entry| entry.expires_at > Utc::now())
+            .cloned()
+            .ok_or(ResumableUploadError::UploadNotFound)
+    }
+
+    fn remove(&self, id: &str) -> Option<UploadEntry> {
+        self.uploads
+            .lock()
+            .expect("mutex should not be poisoned")
+            .remove(id)
+    }
+
+    /// Removes the expired uploads, and the files with their data.
+    async fn remove_expired(&self) {
+        let now = Utc::now();
+        let expired: Vec<_> = {
+            let mut uploads = self.uploads.lock().expect("mutex should not be poisoned");
+            let expired = uploads
+                .iter()
+                .filter(|(_, entry)| entry.expires_at <= now)
+                .map(|(id, _)| id.clone())
+                .collect();
+            uploads.retain(|_, entry| entry.expires_at > now);
+            expired
+        };
+        for id in expired {
+            tracing::debug!(upload_id = id, "removing an expired upload");
+            self.remove_file(&id).await;
+        }
+    }
+
+    fn file_path(&self, id: &str) -> PathBuf {
+        self.config.directory.join(id)
+    }
+
+    async fn remove_file(&self, id: &str) {
+        match tokio::fs::remove_file(self.file_path(id)).await {
+            Ok(()) => (),
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => (),
+            Err(error) => tracing::warn!(?error, upload_id = id, "failed to remove an upload"),
+        }
+    }
+}
+
+/// Returns whether the name is the identifier of an upload, i.e., 32 lowercase hexadecimal
+/// digits.
+fn is_upload_id(name: &str) -> bool {
+    name.len() == 32
+        && name
+            .bytes()
+            .all(|byte| byte.is_ascii_digit() || (b'a'..=b'f').contains(&byte))
+}
+
+/// Removes the files of uploads from the directory, leaving other files untouched.
+fn remove_upload_files(directory: &FsPath) -> anyhow::Result<()> {
+    for entry in std::fs::read_dir(directory)? {
+        let entry = entry?;
+        let is_upload = entry.file_name().to_str().is_some_and(is_upload_id);
+        if is_upload && entry.file_type()?.is_file() {
+            std::fs::remove_file(entry.path())?;
+        }
+    }
+    Ok(())
+}
+
+/// The state of the handlers of resumable uploads.
+#[derive(Debug)]
+struct UploadsState<T> {
+    client: Arc<T>,
+    uploads: ResumableUploads,
+    /// The maximum length of an upload, in bytes.
+    max_upload_size: u64,
+    /// The permits for storing blobs, shared with the other endpoints that store blobs.
+    store_permits: Arc<Semaphore>,
+}
+
+/// Returns the router for resumable uploads.
+///
+/// The creation of uploads is authenticated in the same way as store requests, if `auth` is set,
+/// and uploads are subject to the `store_limits` of store requests.
+pub(super) fn router<T, S>(
+    client: Arc<T>,
+    uploads: ResumableUploads,
+    auth: Option<PublisherAuthState>,
+    store_limits: StoreLimits,
+) -> Router<S>
+where
+    T: WalrusWriteClient + Send + Sync + 'static,
+    S: Clone + Send + Sync + 'static,
+{
+    let create = match auth {
+        Some(auth) => post(create_upload::<T>)
+            .route_layer(axum::middleware::from_fn_with_state(auth, auth_layer)),
+        None => post(create_upload::<T>),
+    };
+    Router::new()
+        .route(UPLOADS_ENDPOINT, create.options(options::<T>))
+        .route(
+            UPLOAD_ENDPOINT,
+            get(get_upload_result::<T>)
+                .head(get_upload_offset::<T>)
+                .patch(append_to_upload::<T>)
+                .delete(terminate_upload::<T>),
+        )
+        .with_state(Arc::new(UploadsState {
+            client,
+            max_upload_size: uploads
+                .config
+                .max_upload_size
+                .min(store_limits.max_blob_size as u64),
+            uploads,
+            store_permits: store_limits.permits,
+        }))
+}
+
+/// Describes the supported version and extensions of the tus protocol.
+async fn options<T>(State(state): State<Arc<UploadsState<T>>>) -> Response {
+    (
+        StatusCode::NO_CONTENT,
+        [
+            (TUS_RESUMABLE, TUS_VERSION.to_owned()),
+            (TUS_VERSION_HEADER, TUS_VERSION.to_owned()),
+            (TUS_EXTENSION, TUS_EXTENSIONS.to_owned()),
+            (TUS_MAX_SIZE, state.max_upload_size.to_string()),
+        ],
+    )
+        .into_response()
+}
+
+/// Creates an upload of the length in the `Upload-Length` header.
+#[tracing::instrument(level = Level::ERROR, skip_all)]
+async fn create_upload<T: WalrusWriteClient>(
+    State(state): State<Arc<UploadsState<T>>>,
+    Query(query): Query<PublisherQuery>,
+    api_key_holder: Option<Extension<Arc<ApiKeyHolder>>>,
+    signed_url: Option<Extension<SignedUrlGrant>>,
+    bearer_header: Option<TypedHeader<Authorization<Bearer>>>,
+    headers: HeaderMap,
+) -> Result<Response, ResumableUploadError> {
+    check_tus_version(&headers)?;
+    let length = headers
+        .get(UPLOAD_LENGTH)
+        .and_then(|value| value.to_str().ok()?.parse::<u64>().ok())
+        .ok_or(ResumableUploadError::InvalidLength)?;
+    let max_upload_size = state.max_upload_size;
+    if length > max_upload_size {
+        return Err(ResumableUploadError::TooLarge(max_upload_size));
+    }
+
+    // Check the length against the signed URL, and against the permissions of the API key or the
+    // authorization claim.
+    if let Some(Extension(grant)) = &signed_url {
+        grant.check_size(length)?;
+    }
+    let api_key_holder = api_key_holder.map(|Extension(holder)| holder);
+    if let Some(holder) = &api_key_holder {
+        holder.check_upload(&query, SizeHint::with_exact(length))?;
+    } else if let (None, Some(TypedHeader(header))) = (&signed_url, bearer_header) {
+        check_blob_size(header, usize::try_from(length).unwrap_or(usize::MAX))?;
+    }
+
+    state.uploads.remove_expired().await;
+    let id = format!("{:032x}", rand::random::<u128>());
+    File::create(state.uploads.file_path(&id))
+        .await
+        .context("failed to create the file for the upload")?;
+
+    let expires_at = Utc::now()
+        + chrono::Duration::from_std(state.uploads.config.expiry)
+            .context("the expiry of uploads is too large")?;
+    let upload = Upload {
+        id: id.clone(),
+        length,
+        offset: 0,
+        query,
+        api_key_holder,
+        result: None,
+    };
+    state
+        .uploads
+        .uploads
+        .lock()
+        .expect("mutex should not be poisoned")
+        .insert(
+            id.clone(),
+            UploadEntry {
+                expires_at,
+                upload: Arc::new(tokio::sync::Mutex::new(upload)),
+            },
+        );
+    tracing::debug!(upload_id = id, length, "created a resumable upload");
+
+    Ok((
+        StatusCode::CREATED,
+        [
+            ("location", format!("{UPLOADS_ENDPOINT}/{id}")),
+            (UPLOAD_EXPIRES, http_date(expires_at)),
+            (TUS_RESUMABLE, TUS_VERSION.to_owned()),
+        ],
+    )
+        .into_response())
+}
+
+/// Returns the offset of the upload, up to which its data has been received.
+async fn get_upload_offset<T>(
+    State(state): State<Arc<UploadsState<T>>>,
+    Path(upload_id): Path<String>,
+) -> Result<Response, ResumableUploadError> {
+    let entry = state.uploads.get(&upload_id)?;
+    let upload = entry.upload.lock().await;
+    Ok((
+        StatusCode::OK,
+        [
+            (UPLOAD_OFFSET, upload.offset.to_string()),
+            (UPLOAD_LENGTH, upload.length.to_string()),
+            (UPLOAD_EXPIRES, http_date(entry.expires_at)),
+            ("cache-control", "no-store".to_owned()),
+            (TUS_RESUMABLE, TUS_VERSION.to_owned()),
+        ],
+    )
+        .into_response())
+}
+
+/// Appends the data in the body to the upload at the offset in the `Upload-Offset` header, and
+/// stores the blob once the upload is complete.
+///
+/// If storing the blob fails, the request can be repeated without data to retry.
+#[tracing::instrument(level = Level::ERROR, skip_all, fields(%upload_id))]
+async fn append_to_upload<T: WalrusWriteClient>(
+    State(state): State<Arc<UploadsState<T>>>,
+    Path(upload_id): Path<String>,
+    headers: HeaderMap,
+    body: Body,
+) -> Result<Response, ResumableUploadError> {
+    check_tus_version(&headers)?;
+    if headers
+        .get("content-type")
+        .and_then(|value| value.to_str().ok())
+        != Some(OFFSET_OCTET_STREAM)
+    {
+        return Err(ResumableUploadError::InvalidContentType);
+    }
+    let offset = headers
+        .get(UPLOAD_OFFSET)
+        .and_then(|value| value.to_str().ok()?.parse::<u64>().ok())
+        .ok_or(ResumableUploadError::InvalidOffset)?;
+
+    let entry = state.uploads.get(&upload_id)?;
+    let mut upload = entry
+        .upload
+        .try_lock()
+        .map_err(|_| ResumableUploadError::Locked)?;
+    if upload.result.is_some() || offset != upload.offset {
+        return Err(ResumableUploadError::OffsetMismatch(upload.offset));
+    }
+
+    let received = append_body(&state.uploads.file_path(&upload_id), &mut upload, body).await;
+    tracing::debug!(
+        offset = upload.offset,
+        "received data of a resumable upload"
+    );
+    received?;
+
+    if upload.offset == upload.length {
+        let result = store_upload(&state, &upload).await?;
+        upload.result = Some(result);
+    }
+
+    Ok((
+        StatusCode::NO_CONTENT,
+        [
+            (UPLOAD_OFFSET, upload.offset.to_string()),
+            (TUS_RESUMABLE, TUS_VERSION.to_owned()),
+        ],
+    )
+        .into_response())
+}
+
+/// Appends the body to the file of the upload, and advances its offset by the data written.
+///
+/// The offset is advanced even if receiving the body fails, so that the upload can be resumed
+/// from the data received so far.
+async fn append_body(
+    file_path: &FsPath,
+    upload: &mut Upload,
+    body: Body,
+) -> Result<(), ResumableUploadError> {
+    let mut file = OpenOptions::new()
+        .append(true)
+        .open(file_path)
+        .await
+        .context("failed to open the file of the upload")?;
+    let mut stream = body.into_data_stream();
+    while let Some(chunk) = stream.next().await {
+        let chunk = chunk.map_err(|error| {
+            tracing::debug!(?error, "failed to receive the data of a resumable upload");
+            ResumableUploadError::Interrupted
+        })?;
+        if upload.offset + chunk.len() as u64 > upload.length {
+            return Err(ResumableUploadError::ExceedsLength);
+        }
+        if let Err(error) = file.write_all(&chunk).await {
+            // Discard the partially written chunk, so that the file matches the offset.
+            let _ = file.set_len(upload.offset).await;
+            return Err(anyhow::Error::new(error)
+                .context("failed to write the data of the upload")
+                .into());
+        }
+        upload.offset += chunk.len() as u64;
+    }
+    file.flush()
+        .await
+        .context("failed to write the data of the upload")?;
+    Ok(())
+}
+
+/// Stores the data of the complete upload as a blob, and removes the file with the data.
+///
+/// Fails with [`ResumableUploadError::Overloaded`] if the publisher is already storing the maximum
+/// number of blobs concurrently.
+async fn store_upload<T: WalrusWriteClient>(
+    state: &UploadsState<T>,
+    upload: &Upload,
+) -> Result<BlobStoreResult, ResumableUploadError> {
+    let (client, uploads) = (state.client.as_ref(), &state.uploads);
+    // The permits are held until the blob is stored.
+    let _store_permit = state
+        .store_permits
+        .try_acquire()
+        .map_err(|_| ResumableUploadError::Overloaded)?;
+    let holder = upload.api_key_holder.as_deref();
+    let _upload_permit = match holder {
+        Some(holder) => {
+            let permit = holder.start_upload()?;
+            holder.check_size(upload.length)?;
+            holder.charge_quota(upload.length)?;
+            permit
+        }
+        None => None,
+    };
+
+    let blob = tokio::fs::read(uploads.file_path(&upload.id))
+        .await
+        .context("failed to read the data of the upload")?;
+    let PublisherQuery {
+        encoding_type,
+        epochs,
+        deletable,
+        send_object_to,
+    } = upload.query;
+    let post_store_action = match send_object_to {
+        Some(address) => PostStoreAction::TransferTo(address),
+        None => client.default_post_store_action(),
+    };
+
+    tracing::debug!(?post_store_action, "starting to store a resumable upload");
+    let result = client
+        .write_blob(
+            &blob,
+            encoding_type,
+            epochs,
+            StoreWhen::NotStoredIgnoreResources,
+            BlobPersistence::from_deletable(deletable),
+            post_store_action,
+        )
+        .await;
+
+    match result {
+        Ok(BlobStoreResult::MarkedInvalid { .. }) => Err(anyhow::anyhow!(
+            "the blob was marked invalid, which is likely a system error, please report it"
+        )
+        .into()),
+        Ok(result) => {
+            uploads.remove_file(&upload.id).await;
+            Ok(result)
+        }
+        Err(error) => {
+            tracing::error!(?error, "error storing a resumable upload");
+            if let Some(holder) = holder {
+                holder.refund_quota(upload.length);
+            }
+            Err(StoreBlobError::from(error).into())
+        }
+    }
+}
+
+/// Returns the result of storing the blob of a complete upload.
+async fn get_upload_result<T>(
+    State(state): State<Arc<UploadsState<T>>>,
+    Path(upload_id): Path<String>,
+) -> Result<Response, ResumableUploadError> {
+    let entry = state.uploads.get(&upload_id)?;
+    let upload = entry.upload.lock().await;
+    let result = upload
+        .result
+        .as_ref()
+        .ok_or(ResumableUploadError::Incomplete)?;
+    Ok(Json(result).into_response())
+}
+
+/// Terminates the upload and removes its data.
+async fn terminate_upload<T>(
+    State(state): State<Arc<UploadsState<T>>>,
+    Path(upload_id): Path<String>,
+    headers: HeaderMap,
+) -> Result<Response, ResumableUploadError> {
+    check_tus_version(&headers)?;
+    state
+        .uploads
+        .remove(&upload_id)
+        .ok_or(ResumableUploadError::UploadNotFound)?;
+    state.uploads.remove_file(&upload_id).await;
+    tracing::debug!(upload_id, "terminated a resumable upload");
+    Ok((StatusCode::NO_CONTENT, [(TUS_RESUMABLE, TUS_VERSION)]).into_response())
+}
+
+fn check_tus_version(headers: &HeaderMap) -> Result<(), ResumableUploadError> {
+    match headers.get(TUS_RESUMABLE) {
+        Some(version) if version == TUS_VERSION => Ok(()),
+        _ => Err(ResumableUploadError::UnsupportedVersion),
+    }
+}
+
+fn http_date(time: DateTime<Utc>) -> String {
+    time.format("%a, %d %b %Y %H:%M:%S GMT").to_string()
+}
+
+/// An error of a request of the tus protocol.
+#[derive(Debug, thiserror::Error)]
+pub(crate) enum ResumableUploadError {
+    /// The request does not use the supported version of the tus protocol.
+    #[error("the request must use version {TUS_VERSION} of the tus protocol")]
+    UnsupportedVersion,
+
+    /// The upload does not exist, or has expired or been terminated.
+    #[error("the upload does not exist")]
+    UploadNotFound,
+
+    /// The length of the upload is missing or invalid.
+    #[error("the Upload-Length header is missing or invalid")]
+    InvalidLength,
+
+    /// The length of the upload exceeds the maximum.
+    #[error("the upload is larger than the maximum of {0} bytes")]
+    TooLarge(u64),
+
+    /// The offset of the data is missing or invalid.
+    #[error("the Upload-Offset header is missing or invalid")]
+    InvalidOffset,
+
+    /// The offset of the data does not match the offset of the upload.
+    #[error("the offset does not match the offset of the upload, which is {0}")]
+    OffsetMismatch(u64),
+
+    /// The data is not sent with the content type of the tus protocol.
+    #[error("the content type must be {OFFSET_OCTET_STREAM}")]
+    InvalidContentType,
+
+    /// The data extends beyond the length of the upload.
+    #[error("the data extends beyond the length of the upload")]
+    ExceedsLength,
+
+    /// The request was interrupted before all data was received.
+    #[error("the request was interrupted")]
+    Interrupted,
+
+    /// Another request to the upload is in progress.
+    #[error("the upload is locked by another request")]
+    Locked,
+
+    /// The publisher is storing the maximum number of blobs concurrently.
+    #[error("the publisher is receiving too many requests; please try again later")]
+    Overloaded,
+
+    /// The blob of the upload has not been stored yet.
+    #[error("the upload is not complete")]
+    Incomplete,
+
+    /// The upload is not allowed for the client.
+    #[error(transparent)]
+    Unauthorized(#[from] PublisherAuthError),
+
+    /// The upload is not allowed by the signed URL.
+    #[error(transparent)]
+    SignedUrl(#[from] SignedUrlError),
+
+    /// Storing the blob of the upload failed.
+    #[error(transparent)]
+    Store(#[from] StoreBlobError),
+}
+
+impl From<anyhow::Error> for ResumableUploadError {
+    fn from(error: anyhow::Error) -> Self {
+        Self::Store(StoreBlobError::Internal(error))
+    }
+}
+
+impl ResumableUploadError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            Self::UnsupportedVersion => StatusCode::PRECONDITION_FAILED,
+            Self::UploadNotFound => StatusCode::NOT_FOUND,
+            Self::InvalidLength | Self::InvalidOffset | Self::ExceedsLength | Self::Interrupted => {
+                StatusCode::BAD_REQUEST
+            }
+            Self::TooLarge(_) => StatusCode::PAYLOAD_TOO_LARGE,
+            Self::OffsetMismatch(_) | Self::Incomplete => StatusCode::CONFLICT,
+            Self::InvalidContentType => StatusCode::UNSUPPORTED_MEDIA_TYPE,
+            Self::Locked => StatusCode::LOCKED,
+            Self::Overloaded => StatusCode::TOO_MANY_REQUESTS,
+            Self::Unauthorized(error) => error.status_code().http_code(),
+            Self::SignedUrl(error) => error.status_code().http_code(),
+            Self::Store(error) => error.status_code().http_code(),
+        }
+    }
+}
+
+impl IntoResponse for ResumableUploadError {
+    fn into_response(self) -> Response {
+        let mut response = match &self {
+            Self::Unauthorized(error) => error.to_response(),
+            Self::SignedUrl(error) => error.to_response(),
+            Self::Store(error) => error.to_response(),
+            Self::UnsupportedVersion => (
+                self.status_code(),
+                [(TUS_VERSION_HEADER, TUS_VERSION)],
+                self.to_string(),
+            )
+                .into_response(),
+            _ => (self.status_code(), self.to_string()).into_response(),
+        };
+        response.headers_mut().insert(
+            HeaderName::from_static(TUS_RESUMABLE),
+            HeaderValue::from_static(TUS_VERSION),
+        );
+        response
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::http::{self, header::LOCATION};
+    use tower::ServiceExt as _;
+
+    use super::*;
+    use crate::client::daemon::test_utils::FakeClient;
+
+    /// Returns the router for uploads of at most 1 KiB, of which the publisher accepts blobs of
+    /// at most `max_blob_size` bytes.
+    fn test_router(
+        directory: &FsPath,
+        max_blob_size: usize,
+        permits: Arc<Semaphore>,
+    ) -> anyhow::Result<Router> {
+        let uploads = ResumableUploads::new(ResumableUploadsConfig {
+            directory: directory.to_owned(),
+            max_upload_size: 1024,
+            expiry: Duration::from_secs(60),
+        })?;
+        let store_limits = StoreLimits {
+            max_blob_size,
+            permits,
+        };
+        Ok(router(
+            Arc::new(FakeClient::default()),
+            uploads,
+            None,
+            store_limits,
+        ))
+    }
+
+    fn tus_request(method: http::Method, uri: &str) -> http::request::Builder {
+        http::Request::builder()
+            .method(method)
+            .uri(uri)
+            .header(TUS_RESUMABLE, TUS_VERSION)
+    }
+
+    #[tokio::test]
+    async fn limits_uploads_to_the_max_blob_size() -> anyhow::Result<()> {
+        let directory = tempfile::tempdir()?;
+        let router = test_router(directory.path(), 100, Arc::new(Semaphore::new(1)))?;
+
+        let response = router
+            .clone()
+            .oneshot(tus_request(http::Method::OPTIONS, UPLOADS_ENDPOINT).body(Body::empty())?)
+            .await?;
+        assert_eq!(response.headers()[TUS_MAX_SIZE], "100");
+
+        let response = router
+            .oneshot(
+                tus_request(http::Method::POST, UPLOADS_ENDPOINT)
+                    .header(UPLOAD_LENGTH, "101")
+                    .body(Body::empty())?,
+            )
+            .await?;
+        assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn stores_uploads_only_with_a_store_permit() -> anyhow::Result<()> {
+        let directory = tempfile::tempdir()?;
+        let permits = Arc::new(Semaphore::new(1));
+        let router = test_router(directory.path(), 100, permits.clone())?;
+
+        let response = router
+            .clone()
+            .oneshot(
+                tus_request(http::Method::POST, UPLOADS_ENDPOINT)
+                    .header(UPLOAD_LENGTH, "4")
+                    .body(Body::empty())?,
+            )
+            .await?;
+        assert_eq!(response.status(), StatusCode::CREATED);
+        let location = response.headers()[LOCATION].to_str()?.to_owned();
+        let patch = |offset: &str, data: &'static [u8]| {
+            tus_request(http::Method::PATCH, &location)
+                .header("content-type", OFFSET_OCTET_STREAM)
+                .header(UPLOAD_OFFSET, offset)
+                .body(Body::from(data))
+        };
+
+        // All permits are taken by other store requests.
+        let other_request = permits.clone().acquire_owned().await?;
+        let response = router.clone().oneshot(patch("0", b"data")?).await?;
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+
+        // Once a permit is available, the upload can be completed without sending data again.
+        drop(other_request);
+        let response = router.clone().oneshot(patch("4", b"")?).await?;
+        assert_eq!(response.status(), StatusCode::NO_CONTENT);
+        let response = router
+            .oneshot(http::Request::get(&location).body(Body::empty())?)
+            .await?;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(permits.available_permits(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn removes_only_upload_files() -> anyhow::Result<()> {
+        let directory = tempfile::tempdir()?;
+        let upload_id = format!("{:032x}", rand::random::<u128>());
+        std::fs::write(directory.path().join(&upload_id), b"data")?;
+        std::fs::write(directory.path().join("other"), b"data")?;
+
+        let uploads = ResumableUploads::new(ResumableUploadsConfig {
+            directory: directory.path().to_owned(),
+            max_upload_size: 1024,
+            expiry: Duration::from_secs(60),
+        })?;
+        assert!(!directory.path().join(&upload_id).exists());
+        assert!(directory.path().join("other").exists());
+        assert!(matches!(
+            uploads.get(&upload_id),
+            Err(ResumableUploadError::UploadNotFound)
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn recognizes_upload_ids() {
+        assert!(is_upload_id("0123456789abcdef0123456789abcdef"));
+        assert!(!is_upload_id("0123456789ABCDEF0123456789ABCDEF"));
+        assert!(!is_upload_id("0123456789abcdef"));
+        assert!(!is_upload_id("../23456789abcdef0123456789abcdef"));
+    }
+
+    #[test]
+    fn formats_http_dates() {
+        let time = DateTime::from_timestamp(1_700_000_000, 0).expect("valid timestamp");
+        assert_eq!(http_date(time), "Tue, 14 Nov 2023 22:13:20 GMT");
+    }
+}
@@ -11,6 +11,7 @@ use std::{
     },
 };
 
+use chrono::{DateTime, Utc};
 use sui_sdk::{
     sui_client_config::SuiEnv,
     types::base_types::SuiAddress,
@@ -19,10 +20,11 @@ use sui_sdk::{
 use sui_types::base_types::ObjectID;
 use walrus_core::{BlobId, EncodingType, EpochCount};
 use walrus_sdk::{
+    blocklist::Blocklist,
     client::{
+        byte_range::{BlobRange, ByteRange},
         metrics::ClientMetrics,
-        refresh::CommitteesRefresherHandle,
-        responses::BlobStoreResult,
+        responses::{BlobAvailability, BlobStoreResult},
         Client,
     },
     config::ClientConfig,
@@ -58,12 +60,15 @@ pub struct ClientMultiplexer {
 }
 
 impl ClientMultiplexer {
+    /// Creates a new client multiplexer, whose clients all refuse to read or store the blobs on
+    /// the `blocklist`.
     pub async fn new(
         wallet: WalletContext,
         config: &ClientConfig,
         gas_budget: Option<u64>,
         prometheus_registry: &Registry,
         args: &PublisherArgs,
+        blocklist: Blocklist,
     ) -> anyhow::Result<Self> {
         let sui_env = wallet.config.get_active_env()?.clone();
         let contract_client = config.new_contract_client(wallet, gas_budget).await?;
@@ -72,17 +77,16 @@ impl ClientMultiplexer {
         let sui_client = contract_client.sui_client().clone();
         let sui_read_client = (*contract_client.read_client).clone();
 
-        // Start the refresher here, so that all the clients can share it.
+        // Start the refresher here, so that all the clients can share it, together with the
+        // connections to the storage nodes.
         let refresh_handle = config
             .refresh_config
             .build_refresher_and_run(sui_read_client.clone())
             .await?;
-        let read_client = Client::new_read_client(
-            config.clone(),
-            refresh_handle.clone(),
-            sui_read_client.clone(),
-        )
-        .await?;
+        let base_client = Client::new(config.clone(), refresh_handle)
+            .await?
+            .with_blocklist(blocklist);
+        let read_client = base_client.clone_with_client(sui_read_client);
 
         let refiller = Refiller::new(
             contract_client,
@@ -101,7 +105,7 @@ impl ClientMultiplexer {
                 args.sub_wallets_min_balance,
             ),
             &refiller,
-            refresh_handle.clone(),
+            &base_client,
         )
         .await?;
 
@@ -165,12 +169,28 @@ impl WalrusReadClient for ClientMultiplexer {
         WalrusReadClient::read_blob(&self.read_client, blob_id).await
     }
 
+    async fn read_blob_range(&self, blob_id: &BlobId, range: ByteRange) -> ClientResult<BlobRange> {
+        WalrusReadClient::read_blob_range(&self.read_client, blob_id, range).await
+    }
+
     async fn get_blob_by_object_id(
         &self,
         blob_object_id: &ObjectID,
     ) -> ClientResult<BlobWithAttribute> {
         self.read_client.get_blob_by_object_id(blob_object_id).await
     }
+
+    fn check_blob_id(&self, blob_id: &BlobId) -> ClientResult<()> {
+        self.read_client.check_blob_id(blob_id)
+    }
+
+    async fn estimated_blob_expiry(&self, blob_id: &BlobId) -> ClientResult<Option<DateTime<Utc>>> {
+        self.read_client.estimated_blob_expiry(blob_id).await
+    }
+
+    async fn blob_availability(&self, blob_id: &BlobId) -> ClientResult<BlobAvailability> {
+        self.read_client.blob_availability(blob_id).await
+    }
 }
 
 impl WalrusWriteClient for ClientMultiplexer {
@@ -234,11 +254,14 @@ pub struct WriteClientPool {
 
 impl WriteClientPool {
     /// Creates a new client pool with `n_client`, based on the given `config` and `sui_env`.
+    ///
+    /// The clients share the committees, the blocklist, and the connections to the storage nodes
+    /// of the `base_client`.
     pub async fn new(
         config: &ClientConfig,
         pool_config: WriteClientPoolConfig,
         refiller: &Refiller,
-        refresh_handle: CommitteesRefresherHandle,
+        base_client: &Client<()>,
     ) -> anyhow::Result<Self> {
         tracing::info!(%pool_config.n_clients, "creating write client pool");
 
@@ -249,8 +272,9 @@ impl WriteClientPool {
             pool_config.gas_budget,
             refiller,
             pool_config.min_balance,
+            base_client,
         )
-        .create_or_load_sub_clients(pool_config.n_clients, refresh_handle)
+        .create_or_load_sub_clients(pool_config.n_clients)
         .await?;
 
         Ok(Self {
@@ -290,6 +314,8 @@ struct SubClientLoader<'a> {
     refiller: &'a Refiller,
     /// The minimum balance the sub-wallets should have, below which they are refilled at startup.
     min_balance: u64,
+    /// The client whose state is shared by the sub clients.
+    base_client: &'a Client<()>,
 }
 
 impl<'a> SubClientLoader<'a> {
@@ -300,6 +326,7 @@ impl<'a> SubClientLoader<'a> {
         gas_budget: Option<u64>,
         refiller: &'a Refiller,
         min_balance: u64,
+        base_client: &'a Client<()>,
     ) -> Self {
         Self {
             config,
@@ -308,20 +335,18 @@ impl<'a> SubClientLoader<'a> {
             gas_budget,
             refiller,
             min_balance,
+            base_client,
         }
     }
 
     async fn create_or_load_sub_clients(
         &self,
         n_clients: usize,
-        refresh_handle: CommitteesRefresherHandle,
     ) -> anyhow::Result<Vec<Arc<Client<SuiContractClient>>>> {
         let mut clients = Vec::with_capacity(n_clients);
 
         for idx in 0..n_clients {
-            let client = self
-                .create_or_load_sub_client(idx, refresh_handle.clone())
-                .await?;
+            let client = self.create_or_load_sub_client(idx).await?;
             clients.push(Arc::new(client));
         }
 
@@ -332,7 +357,6 @@ impl<'a> SubClientLoader<'a> {
     async fn create_or_load_sub_client(
         &self,
         sub_wallet_idx: usize,
-        refresh_handle: CommitteesRefresherHandle,
     ) -> anyhow::Result<Client<SuiContractClient>> {
         let mut wallet = self.create_or_load_sub_wallet(sub_wallet_idx)?;
         self.top_up_if_necessary(&mut wallet, self.min_balance)
@@ -345,9 +369,7 @@ impl<'a> SubClientLoader<'a> {
         // Merge existing coins to avoid fragmentation.
         sui_client.merge_coins().await?;
 
-        let client =
-            Client::new_contract_client(self.config.clone(), refresh_handle, sui_client).await?;
-        Ok(client)
+        Ok(self.base_client.clone_with_client(sui_client))
     }
 
     /// Creates or loads a new wallet to use with the multiplexer.
@@ -14,7 +14,7 @@ use anyhow;
 use chrono::{DateTime, Utc};
 use futures::{stream, StreamExt as _};
 use serde::Serialize;
-use serde_with::{base64::Base64, serde_as, DisplayFromStr};
+use serde_with::{base64::Base64, serde_as, DisplayFromStr, DurationMilliSecondsWithFrac};
 use sui_types::base_types::{ObjectID, SuiAddress};
 use walrus_core::{
     bft,
@@ -40,13 +40,14 @@ use walrus_rest_client::api::{BlobStatus, ServiceHealthInfo};
 use walrus_sdk::{
     client::NodeCommunicationFactory,
     sui::{
-        client::ReadClient,
+        client::{CurrentEpochState, EpochPrices, ReadClient, SharedBlobFunding, UnstakeResult},
         types::{
             move_structs::{Blob, BlobAttribute, EpochState},
             Committee,
             NetworkAddress,
             StakedWal,
             StorageNode,
+            StorageResource,
         },
         utils::{price_for_encoded_length, storage_units_from_size, BYTES_PER_UNIT_SIZE},
     },
@@ -115,6 +116,79 @@ impl From<BlobIdDecimal> for BlobIdConversionOutput {
     }
 }
 
+/// The output of the `encoding-test-vectors verify` command.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct TestVectorVerificationOutput {
+    /// The number of test vectors that were verified successfully.
+    pub n_verified: usize,
+    /// The test vectors that failed verification.
+    pub failures: Vec<TestVectorFailure>,
+}
+
+/// A test vector that failed verification.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct TestVectorFailure {
+    /// The position of the test vector in the file.
+    pub index: usize,
+    /// The seed of the test vector.
+    pub seed: u64,
+    /// The length of the blob of the test vector.
+    pub blob_length: usize,
+    /// The reason for the failure.
+    pub error: String,
+}
+
+/// The output of the `bench encode` command.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct EncodingBenchmarkOutput {
+    /// The number of shards for which the blobs were encoded.
+    pub n_shards: NonZeroU16,
+    /// The encoding type with which the blobs were encoded.
+    pub encoding_type: EncodingType,
+    /// The number of times each operation was repeated.
+    pub iterations: u32,
+    /// The results for each blob size.
+    pub results: Vec<EncodingBenchmarkResult>,
+}
+
+/// The mean durations of the operations benchmarked by `bench encode` for one blob size.
+#[serde_as]
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct EncodingBenchmarkResult {
+    /// The size of the blob in bytes.
+    pub blob_size: u64,
+    /// The mean duration of encoding the blob into sliver pairs and computing its metadata.
+    #[serde_as(as = "DurationMilliSecondsWithFrac<f64>")]
+    pub encode: Duration,
+    /// The mean duration of computing the metadata, including the blob ID, of the blob.
+    #[serde_as(as = "DurationMilliSecondsWithFrac<f64>")]
+    pub compute_metadata: Duration,
+    /// The mean duration of decoding and verifying the blob from primary slivers.
+    #[serde_as(as = "DurationMilliSecondsWithFrac<f64>")]
+    pub decode: Duration,
+}
+
+impl EncodingBenchmarkResult {
+    /// Returns the number of bytes of the blob processed per second in the given `duration`.
+    pub fn bytes_per_second(&self, duration: Duration) -> u64 {
+        (self.blob_size as f64 / duration.as_secs_f64()) as u64
+    }
+}
+
+/// The output of the `sign-url` command.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct SignedUrlOutput {
+    /// The signed URL.
+    pub url: String,
+    /// The time at which the URL expires.
+    pub expires_at: DateTime<Utc>,
+}
+
 /// The output of the `store --dry-run` command.
 #[serde_as]
 #[derive(Debug, Clone, Serialize)]
@@ -597,6 +671,62 @@ pub struct StakeOutput {
     pub staked_wal: Vec<StakedWal>,
 }
 
+#[serde_with::skip_serializing_none]
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+/// The result of unstaking a single staked WAL object.
+pub struct UnstakedWal {
+    /// The object ID of the staked WAL.
+    pub staked_wal_id: ObjectID,
+    /// Whether the WAL, including its rewards, was withdrawn to the wallet.
+    pub withdrawn: bool,
+    /// The epoch from which the WAL can be withdrawn, if its withdrawal was requested.
+    pub withdraw_epoch: Option<Epoch>,
+}
+
+impl UnstakedWal {
+    /// Creates the result of unstaking the staked WAL with the given ID.
+    pub fn new(staked_wal_id: ObjectID, result: UnstakeResult) -> Self {
+        let (withdrawn, withdraw_epoch) = match result {
+            UnstakeResult::Withdrawn => (true, None),
+            UnstakeResult::WithdrawalRequested(withdraw_epoch) => (false, Some(withdraw_epoch)),
+        };
+        Self {
+            staked_wal_id,
+            withdrawn,
+            withdraw_epoch,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+/// The output of the `walrus unstake` command.
+pub struct UnstakeOutput {
+    /// The results of unstaking the staked WAL.
+    pub unstaked: Vec<UnstakedWal>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+/// Staked WAL along with the rewards it earned so far.
+pub struct StakedWalWithRewards {
+    /// The staked WAL.
+    pub staked_wal: StakedWal,
+    /// The rewards earned by the staked WAL so far, in FROST.
+    pub rewards: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+/// The output of the `walrus stake-status` command.
+pub struct StakeStatusOutput {
+    /// The current epoch.
+    pub current_epoch: Epoch,
+    /// The staked WAL of the owner, along with the rewards earned so far.
+    pub staked_wal: Vec<StakedWalWithRewards>,
+}
+
 #[derive(Debug, Clone, Serialize)]
 #[serde(rename_all = "camelCase")]
 /// The output of the `walrus generate-sui-wallet` command.
@@ -605,6 +735,18 @@ pub struct WalletOutput {
     pub wallet_address: SuiAddress,
 }
 
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+/// The output of the `walrus balance` command.
+pub struct BalanceOutput {
+    /// The address of the wallet.
+    pub address: SuiAddress,
+    /// The SUI balance of the wallet (in MIST).
+    pub sui_balance: u64,
+    /// The WAL balance of the wallet (in FROST).
+    pub wal_balance: u64,
+}
+
 #[derive(Debug, Clone, Serialize)]
 #[serde(rename_all = "camelCase")]
 /// The output of the `walrus get-wal` command.
@@ -639,6 +781,96 @@ pub struct FundSharedBlobOutput {
     pub amount: u64,
 }
 
+#[serde_as]
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+/// The output of the `walrus get-shared-blob-funding` command.
+pub struct SharedBlobFundingOutput {
+    /// The object ID of the shared blob.
+    pub shared_blob_object_id: ObjectID,
+    /// The blob ID of the shared blob.
+    #[serde_as(as = "DisplayFromStr")]
+    pub blob_id: BlobId,
+    /// The funds of the shared blob in FROST.
+    pub funds: u64,
+    /// The current epoch.
+    pub current_epoch: Epoch,
+    /// The epoch at which the blob expires if it is not extended.
+    pub end_epoch: Epoch,
+    /// The cost in FROST of extending the blob by one epoch, at the current storage price.
+    pub cost_per_epoch: u64,
+    /// The number of epochs by which the funds can extend the blob, at the current storage price.
+    pub funded_epochs: u64,
+    /// The number of epochs for which the blob is stored after the current one, including the
+    /// funded epochs.
+    pub remaining_epochs: u64,
+}
+
+impl From<SharedBlobFunding> for SharedBlobFundingOutput {
+    fn from(funding: SharedBlobFunding) -> Self {
+        Self {
+            shared_blob_object_id: funding.shared_blob.id,
+            blob_id: funding.shared_blob.blob.blob_id,
+            funds: funding.shared_blob.funds,
+            current_epoch: funding.current_epoch,
+            end_epoch: funding.shared_blob.blob.storage.end_epoch,
+            cost_per_epoch: funding.cost_per_epoch,
+            funded_epochs: funding.funded_epochs(),
+            remaining_epochs: funding.remaining_epochs(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+/// The output of the `walrus epoch` command.
+pub struct EpochOutput {
+    /// The current epoch.
+    pub epoch: Epoch,
+    /// Whether the epoch change to the current epoch is still in progress.
+    pub epoch_change_in_progress: bool,
+    /// The time at which the current epoch started, if the epoch change is done.
+    pub start: Option<DateTime<Utc>>,
+    /// The earliest time at which the change to the next epoch can start, if known.
+    pub estimated_end: Option<DateTime<Utc>>,
+    /// The duration of an epoch.
+    pub epoch_duration: Duration,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+/// The prices of an epoch, as output by the `walrus price-history` command.
+pub struct EpochPricesOutput {
+    /// The epoch.
+    pub epoch: Epoch,
+    /// The price in FROST for one unit of storage per epoch.
+    pub storage_price_per_unit_size: u64,
+    /// The price in FROST to write one unit of storage.
+    pub write_price_per_unit_size: u64,
+}
+
+impl From<EpochPrices> for EpochPricesOutput {
+    fn from(prices: EpochPrices) -> Self {
+        Self {
+            epoch: prices.epoch,
+            storage_price_per_unit_size: prices.storage_price_per_unit_size,
+            write_price_per_unit_size: prices.write_price_per_unit_size,
+        }
+    }
+}
+
+impl From<CurrentEpochState> for EpochOutput {
+    fn from(state: CurrentEpochState) -> Self {
+        Self {
+            epoch: state.epoch,
+            epoch_change_in_progress: matches!(state.state, EpochState::EpochChangeSync(_)),
+            start: state.start,
+            estimated_end: state.estimated_end,
+            epoch_duration: state.epoch_duration,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize)]
 #[serde(rename_all = "camelCase")]
 /// The output of the `walrus extend` command.
@@ -756,3 +988,187 @@ impl NodeHealthOutput {
         }
     }
 }
+
+/// The output of the `walrus storage` command.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct StorageResourcesOutput {
+    /// The current epoch.
+    pub(crate) current_epoch: Epoch,
+    /// The unused storage resources owned by the wallet, ordered by their end epoch.
+    pub(crate) resources: Vec<StorageResource>,
+    /// The total size of the storage resources.
+    pub(crate) total_size: u64,
+    /// How the storage resources can be used for an upcoming store, if requested.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) suggestion: Option<StorageSuggestion>,
+}
+
+impl StorageResourcesOutput {
+    pub(crate) fn new(
+        mut resources: Vec<StorageResource>,
+        current_epoch: Epoch,
+        upcoming_store: Option<(u64, Epoch)>,
+    ) -> Self {
+        resources.sort_by_key(|storage| (storage.end_epoch, storage.storage_size));
+        let suggestion = upcoming_store.map(|(encoded_size, end_epoch)| StorageSuggestion {
+            encoded_size,
+            end_epoch,
+            reuse: StorageReuse::find(&resources, encoded_size, current_epoch, end_epoch),
+        });
+        Self {
+            current_epoch,
+            total_size: resources.iter().map(|storage| storage.storage_size).sum(),
+            resources,
+            suggestion,
+        }
+    }
+}
+
+/// How the owned storage resources can be used for an upcoming store.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct StorageSuggestion {
+    /// The encoded size of the blob to store.
+    pub(crate) encoded_size: u64,
+    /// The epoch until which the blob is to be stored.
+    pub(crate) end_epoch: Epoch,
+    /// The storage resources that can be used.
+    pub(crate) reuse: StorageReuse,
+}
+
+/// The owned storage resources that can be used for a store.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum StorageReuse {
+    /// The storage resource can be used as is.
+    Reuse(ObjectID),
+    /// The storage resources cover the same epochs, and can be fused into a single resource of
+    /// their total size.
+    FuseAmount(Vec<ObjectID>),
+    /// The storage resources have the same size and consecutive epochs, and can be fused into a
+    /// single resource spanning all their epochs.
+    FusePeriods(Vec<ObjectID>),
+    /// None of the storage resources can be used, so new storage needs to be purchased.
+    None,
+}
+
+impl StorageReuse {
+    /// Finds the storage `resources` that can be used to store a blob of `encoded_size` from the
+    /// `current_epoch` until `end_epoch`.
+    ///
+    /// A single resource that is large enough is preferred over fusing several resources. A blob
+    /// can only be registered with a resource whose start epoch has been reached.
+    pub(crate) fn find(
+        resources: &[StorageResource],
+        encoded_size: u64,
+        current_epoch: Epoch,
+        end_epoch: Epoch,
+    ) -> Self {
+        let started = || {
+            resources.iter().filter(move |storage| {
+                storage.start_epoch <= current_epoch && storage.end_epoch > current_epoch
+            })
+        };
+
+        if let Some(storage) = started()
+            .filter(|storage| {
+                storage.storage_size >= encoded_size && storage.end_epoch >= end_epoch
+            })
+            .min_by_key(|storage| (storage.storage_size, storage.end_epoch))
+        {
+            return Self::Reuse(storage.id);
+        }
+
+        let mut same_epochs: HashMap<(Epoch, Epoch), Vec<&StorageResource>> = HashMap::new();
+        for storage in started().filter(|storage| storage.end_epoch >= end_epoch) {
+            same_epochs
+                .entry((storage.start_epoch, storage.end_epoch))
+                .or_default()
+                .push(storage);
+        }
+        let fuse_amount = same_epochs
+            .into_values()
+            .filter_map(|mut group| {
+                group.sort_by_key(|storage| std::cmp::Reverse(storage.storage_size));
+                let mut total = 0u64;
+                let n_required = group.iter().position(|storage| {
+                    total = total.saturating_add(storage.storage_size);
+                    total >= encoded_size
+                })? + 1;
+                Some(
+                    group[..n_required]
+                        .iter()
+                        .map(|storage| storage.id)
+                        .collect::<Vec<_>>(),
+                )
+            })
+            .min_by_key(Vec::len);
+        if let Some(storage_ids) = fuse_amount {
+            return Self::FuseAmount(storage_ids);
+        }
+
+        let by_size_and_start: HashMap<_, _> = resources
+            .iter()
+            .map(|storage| ((storage.storage_size, storage.start_epoch), storage))
+            .collect();
+        let mut candidates: Vec<_> = started()
+            .filter(|storage| storage.storage_size >= encoded_size)
+            .collect();
+        candidates.sort_by_key(|storage| storage.storage_size);
+        for first in candidates {
+            let mut storage_ids = vec![first.id];
+            let mut end = first.end_epoch;
+            while end < end_epoch {
+                let Some(next) = by_size_and_start.get(&(first.storage_size, end)) else {
+                    break;
+                };
+                storage_ids.push(next.id);
+                end = next.end_epoch;
+            }
+            if end >= end_epoch {
+                return Self::FusePeriods(storage_ids);
+            }
+        }
+        Self::None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn storage(start_epoch: Epoch, end_epoch: Epoch, storage_size: u64) -> StorageResource {
+        StorageResource {
+            id: ObjectID::random(),
+            start_epoch,
+            end_epoch,
+            storage_size,
+        }
+    }
+
+    #[test]
+    fn finds_reusable_and_fusable_storage() {
+        let small = storage(1, 10, 100);
+        let large = storage(1, 10, 1_000);
+        let next_period = storage(10, 20, 1_000);
+        let resources = [small.clone(), large.clone(), next_period.clone()];
+
+        assert_eq!(
+            StorageReuse::find(&resources, 50, 5, 10),
+            StorageReuse::Reuse(small.id)
+        );
+        assert_eq!(
+            StorageReuse::find(&resources, 1_050, 5, 10),
+            StorageReuse::FuseAmount(vec![large.id, small.id])
+        );
+        assert_eq!(
+            StorageReuse::find(&resources, 500, 5, 20),
+            StorageReuse::FusePeriods(vec![large.id, next_period.id])
+        );
+        assert_eq!(
+            StorageReuse::find(&resources, 500, 5, 21),
+            StorageReuse::None
+        );
+    }
+}
@@ -19,6 +19,7 @@ use std::{
 };
 
 use anyhow::{anyhow, Context as _, Result};
+use base64::{engine::general_purpose::URL_SAFE, Engine as _};
 use fastcrypto::{
     encoding::Base64,
     secp256r1::Secp256r1KeyPair,
@@ -48,16 +49,20 @@ use tracing_subscriber::{
 };
 use typed_store::DBMetrics;
 use uuid::Uuid;
-use walrus_core::{BlobId, PublicKey, ShardIndex};
+use walrus_core::{BlobId, Epoch, PublicKey, ShardIndex};
 use walrus_sdk::active_committees::ActiveCommittees;
 pub use walrus_sdk::utils::load_from_yaml;
 use walrus_sui::{
-    client::{retry_client::RetriableSuiClient, SuiReadClient},
+    client::{retry_client::RetriableSuiClient, ReadClient as _, SuiReadClient},
+    types::Committee,
     utils::SuiNetwork,
 };
 use walrus_utils::metrics::Registry;
 
-use crate::node::{config::MetricsPushConfig, events::event_processor::EventProcessorMetrics};
+use crate::node::{
+    config::{MetricsPushConfig, MetricsPushTarget},
+    events::event_processor::EventProcessorMetrics,
+};
 
 /// The maximum length of the storage node name. Keep in sync with `MAX_NODE_NAME_LENGTH` in
 /// `contracts/walrus/sources/staking/staking_pool.move`.
@@ -229,6 +234,20 @@ pub struct MetricsAndLoggingRuntime {
 impl MetricsAndLoggingRuntime {
     /// Start metrics and log collection in a new runtime
     pub fn start(metrics_address: SocketAddr) -> anyhow::Result<Self> {
+        Self::start_with_tracing(metrics_address, false)
+    }
+
+    /// Start metrics and log collection in a new runtime, and export spans to an OpenTelemetry
+    /// collector if `export_spans` is set.
+    ///
+    /// The telemetry subscriber reads the endpoint of the collector, the filter selecting the
+    /// exported spans, and the service name from the `OTLP_ENDPOINT`, `TRACE_FILTER`, and
+    /// `OTEL_SERVICE_NAME` environment variables. The W3C trace context of incoming HTTP requests
+    /// is continued by the exported spans.
+    pub fn start_with_tracing(
+        metrics_address: SocketAddr,
+        export_spans: bool,
+    ) -> anyhow::Result<Self> {
         let runtime = runtime::Builder::new_multi_thread()
             .thread_name("metrics-runtime")
             .worker_threads(2)
@@ -237,21 +256,31 @@ impl MetricsAndLoggingRuntime {
             .context("metrics runtime creation failed")?;
         let _guard = runtime.enter();
 
-        Self::new(metrics_address, Some(runtime))
+        Self::init(metrics_address, Some(runtime), export_spans)
     }
 
     /// Create a new runtime for metrics and logging.
-    pub fn new(mut metrics_address: SocketAddr, runtime: Option<Runtime>) -> anyhow::Result<Self> {
+    pub fn new(metrics_address: SocketAddr, runtime: Option<Runtime>) -> anyhow::Result<Self> {
+        Self::init(metrics_address, runtime, false)
+    }
+
+    fn init(
+        mut metrics_address: SocketAddr,
+        runtime: Option<Runtime>,
+        export_spans: bool,
+    ) -> anyhow::Result<Self> {
         metrics_address.set_ip(IpAddr::V4(Ipv4Addr::UNSPECIFIED));
         let registry_service = mysten_metrics::start_prometheus_server(metrics_address);
         let walrus_registry = registry_service.default_registry();
 
         // Initialize logging subscriber
-        let (telemetry_guards, tracing_handle) = telemetry_subscribers::TelemetryConfig::new()
+        let mut telemetry_config = telemetry_subscribers::TelemetryConfig::new()
             .with_env()
             .with_prom_registry(&walrus_registry)
-            .with_json()
-            .init();
+            .with_json();
+        // Spans may also be exported if requested through the `ENABLE_TRACING` variable.
+        telemetry_config.enable_otlp_tracing |= export_spans;
+        let (telemetry_guards, tracing_handle) = telemetry_config.init();
 
         // Initialize metrics to track db usage before we create any db instances.
         DBMetrics::init(&walrus_registry);
@@ -302,18 +331,38 @@ impl MetricPushRuntime {
             let mut interval = tokio::time::interval(mp_config.config.push_interval);
             interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
             let mut client = create_push_client();
-            tracing::info!("starting metrics push to '{}'", &mp_config.config.push_url);
+            let start_time = SystemTime::now();
+            tracing::info!(
+                push_target = ?mp_config.config.target,
+                "starting metrics push to '{}'",
+                &mp_config.config.push_url
+            );
             loop {
                 tokio::select! {
                     _ = interval.tick() => {
-                        if let Err(error) = push_metrics(
-                            mp_config.network_key_pair.as_ref(),
-                            &client,
-                            &mp_config.config.push_url,
-                            &registry,
-                            // clone because we serialize this with our metrics
-                            mp_config.config.labels.clone(),
-                        ).await {
+                        let result = match &mp_config.config.target {
+                            MetricsPushTarget::WalrusProxy => push_metrics(
+                                mp_config.network_key_pair.as_ref(),
+                                &client,
+                                &mp_config.config.push_url,
+                                &registry,
+                                // clone because we serialize this with our metrics
+                                mp_config.config.labels.clone(),
+                            ).await,
+                            MetricsPushTarget::Pushgateway { job } => push_metrics_to_pushgateway(
+                                &client,
+                                &mp_config.config,
+                                job,
+                                &registry,
+                            ).await,
+                            MetricsPushTarget::Otlp => push_metrics_to_otlp(
+                                &client,
+                                &mp_config.config,
+                                &registry,
+                                start_time,
+                            ).await,
+                        };
+                        if let Err(error) = result {
                             tracing::error!(?error, "unable to push metrics");
                             client = create_push_client();
                         }
@@ -428,6 +477,329 @@ async fn push_metrics(
     Ok(())
 }
 
+/// Pushes the metrics in the text exposition format to a Prometheus Pushgateway, replacing all
+/// metrics previously pushed to the same group.
+async fn push_metrics_to_pushgateway(
+    client: &reqwest::Client,
+    config: &MetricsPushConfig,
+    job: &str,
+    registry: &Registry,
+) -> Result<(), anyhow::Error> {
+    let url = pushgateway_group_url(&config.push_url, job, config.labels.as_ref())?;
+    tracing::debug!(%url, "pushing metrics to pushgateway");
+
+    let encoder = prometheus::TextEncoder::new();
+    let mut buf: Vec<u8> = vec![];
+    encoder.encode(&registry.gather(), &mut buf)?;
+
+    let request = client
+        .put(url)
+        .header(reqwest::header::CONTENT_TYPE, encoder.format_type())
+        .body(buf);
+    send_metrics_push_request(request, config.headers.as_ref()).await
+}
+
+/// Pushes the metrics as OTLP/HTTP JSON to an OpenTelemetry collector.
+async fn push_metrics_to_otlp(
+    client: &reqwest::Client,
+    config: &MetricsPushConfig,
+    registry: &Registry,
+    start_time: SystemTime,
+) -> Result<(), anyhow::Error> {
+    tracing::debug!(
+        push_url = config.push_url,
+        "pushing metrics to OTLP endpoint"
+    );
+    let payload = otlp::encode_metrics(
+        &registry.gather(),
+        config.labels.as_ref(),
+        start_time,
+        SystemTime::now(),
+    );
+    let request = client.post(&config.push_url).json(&payload);
+    send_metrics_push_request(request, config.headers.as_ref()).await
+}
+
+/// Returns the URL of the Pushgateway group identified by the job and the static labels.
+///
+/// Label values are pushed as path segments and are therefore percent-encoded. Values containing a
+/// slash are pushed in the `<label>@base64/<value>` form with a base64url-encoded value, as
+/// percent-encoded slashes are decoded before the Pushgateway splits the path. Empty values are not
+/// representable in the path and are skipped.
+fn pushgateway_group_url(
+    base_url: &str,
+    job: &str,
+    labels: Option<&HashMap<String, String>>,
+) -> Result<reqwest::Url, anyhow::Error> {
+    let mut url = reqwest::Url::parse(base_url).context("invalid pushgateway URL")?;
+    {
+        let mut segments = url
+            .path_segments_mut()
+            .map_err(|_| anyhow!("the pushgateway URL cannot be a base"))?;
+        segments
+            .pop_if_empty()
+            .push("metrics")
+            .extend(grouping_label_segments("job", job));
+        // Sort the labels to have a stable grouping key across pushes.
+        let mut labels: Vec<_> = labels
+            .into_iter()
+            .flatten()
+            .filter(|(_, value)| !value.is_empty())
+            .collect();
+        labels.sort();
+        for (name, value) in labels {
+            segments.extend(grouping_label_segments(name, value));
+        }
+    }
+    Ok(url)
+}
+
+/// Returns the path segments of the label in the grouping key of a Pushgateway URL.
+fn grouping_label_segments(name: &str, value: &str) -> [String; 2] {
+    if value.contains('/') {
+        [format!("{name}@base64"), URL_SAFE.encode(value)]
+    } else {
+        [name.to_owned(), value.to_owned()]
+    }
+}
+
+async fn send_metrics_push_request(
+    mut request: reqwest::RequestBuilder,
+    headers: Option<&HashMap<String, String>>,
+) -> Result<(), anyhow::Error> {
+    for (name, value) in headers.into_iter().flatten() {
+        request = request.header(name, value);
+    }
+    let response = request.send().await?;
+
+    if !response.status().is_success() {
+        let status = response.status();
+        let body = match response.text().await {
+            Ok(body) => body,
+            Err(error) => format!("couldn't decode response body; {error}"),
+        };
+        return Err(anyhow::anyhow!(
+            "metrics push failed: [{}]:{}",
+            status,
+            body
+        ));
+    }
+    tracing::debug!("successfully pushed metrics");
+    Ok(())
+}
+
+/// Conversion of Prometheus metric families to the JSON encoding of OTLP metrics.
+///
+/// See <https://opentelemetry.io/docs/specs/otlp/#json-protobuf-encoding>.
+mod otlp {
+    use std::{collections::HashMap, time::SystemTime};
+
+    use prometheus::proto::{LabelPair, Metric, MetricFamily, MetricType};
+    use serde_json::{json, Value};
+
+    /// The cumulative aggregation temporality, which matches the semantics of Prometheus.
+    const AGGREGATION_TEMPORALITY_CUMULATIVE: u8 = 2;
+
+    /// Encodes the metric families as an OTLP `ExportMetricsServiceRequest`.
+    pub(super) fn encode_metrics(
+        metric_families: &[MetricFamily],
+        resource_labels: Option<&HashMap<String, String>>,
+        start_time: SystemTime,
+        now: SystemTime,
+    ) -> Value {
+        let mut resource_labels: Vec<_> = resource_labels.into_iter().flatten().collect();
+        resource_labels.sort();
+        let resource_attributes: Vec<_> = resource_labels
+            .into_iter()
+            .map(|(key, value)| attribute(key, value))
+            .collect();
+        let timestamps = Timestamps {
+            start: unix_nanos(start_time),
+            now: unix_nanos(now),
+        };
+        let metrics: Vec<_> = metric_families
+            .iter()
+            .filter_map(|family| encode_family(family, &timestamps))
+            .collect();
+
+        json!({
+            "resourceMetrics": [{
+                "resource": { "attributes": resource_attributes },
+                "scopeMetrics": [{
+                    "scope": { "name": "walrus" },
+                    "metrics": metrics,
+                }],
+            }],
+        })
+    }
+
+    struct Timestamps {
+        start: String,
+        now: String,
+    }
+
+    fn encode_family(family: &MetricFamily, timestamps: &Timestamps) -> Option<Value> {
+        let metrics = family.get_metric();
+        if metrics.is_empty() {
+            return None;
+        }
+        let (data_key, data) = match family.get_field_type() {
+            MetricType::COUNTER => (
+                "sum",
+                json!({
+                    "dataPoints":
+                        number_points(metrics, timestamps, |m| m.get_counter().get_value()),
+                    "aggregationTemporality": AGGREGATION_TEMPORALITY_CUMULATIVE,
+                    "isMonotonic": true,
+                }),
+            ),
+            MetricType::GAUGE => (
+                "gauge",
+                json!({
+                    "dataPoints": number_points(metrics, timestamps, |m| m.get_gauge().get_value()),
+                }),
+            ),
+            MetricType::UNTYPED => (
+                "gauge",
+                json!({
+                    "dataPoints":
+                        number_points(metrics, timestamps, |m| m.get_untyped().get_value()),
+                }),
+            ),
+            MetricType::HISTOGRAM => (
+                "histogram",
+                json!({
+                    "dataPoints": metrics
+                        .iter()
+                        .map(|metric| histogram_point(metric, timestamps))
+                        .collect::<Vec<_>>(),
+                    "aggregationTemporality": AGGREGATION_TEMPORALITY_CUMULATIVE,
+                }),
+            ),
+            MetricType::SUMMARY => (
+                "summary",
+                json!({
+                    "dataPoints": metrics
+                        .iter()
+                        .map(|metric| summary_point(metric, timestamps))
+                        .collect::<Vec<_>>(),
+                }),
+            ),
+        };
+
+        let mut metric = json!({
+            "name": family.get_name(),
+            "description": family.get_help(),
+        });
+        metric[data_key] = data;
+        Some(metric)
+    }
+
+    fn number_points(
+        metrics: &[Metric],
+        timestamps: &Timestamps,
+        value: impl Fn(&Metric) -> f64,
+    ) -> Vec<Value> {
+        metrics
+            .iter()
+            .map(|metric| {
+                json!({
+                    "attributes": attributes(metric.get_label()),
+                    "startTimeUnixNano": timestamps.start,
+                    "timeUnixNano": timestamps.now,
+                    "asDouble": value(metric),
+                })
+            })
+            .collect()
+    }
+
+    fn histogram_point(metric: &Metric, timestamps: &Timestamps) -> Value {
+        let histogram = metric.get_histogram();
+        let (explicit_bounds, bucket_counts) =
+            bucket_counts(histogram.get_bucket(), histogram.get_sample_count());
+        json!({
+            "attributes": attributes(metric.get_label()),
+            "startTimeUnixNano": timestamps.start,
+            "timeUnixNano": timestamps.now,
+            "count": histogram.get_sample_count().to_string(),
+            "sum": histogram.get_sample_sum(),
+            "bucketCounts": bucket_counts,
+            "explicitBounds": explicit_bounds,
+        })
+    }
+
+    /// Converts the cumulative Prometheus buckets to the explicit bounds and the per-bucket
+    /// counts of OTLP, which contain an additional count for the implicit `+Inf` bucket.
+    ///
+    /// Counts are encoded as strings, as required for 64-bit integers in the JSON encoding.
+    pub(super) fn bucket_counts(
+        buckets: &[prometheus::proto::Bucket],
+        sample_count: u64,
+    ) -> (Vec<f64>, Vec<String>) {
+        let mut explicit_bounds = Vec::with_capacity(buckets.len());
+        let mut bucket_counts = Vec::with_capacity(buckets.len() + 1);
+        let mut previous_cumulative_count = 0;
+
+        for bucket in buckets
+            .iter()
+            .filter(|bucket| bucket.get_upper_bound().is_finite())
+        {
+            let cumulative_count = bucket.get_cumulative_count();
+            explicit_bounds.push(bucket.get_upper_bound());
+            bucket_counts.push(
+                cumulative_count
+                    .saturating_sub(previous_cumulative_count)
+                    .to_string(),
+            );
+            previous_cumulative_count = cumulative_count;
+        }
+        bucket_counts.push(
+            sample_count
+                .saturating_sub(previous_cumulative_count)
+                .to_string(),
+        );
+
+        (explicit_bounds, bucket_counts)
+    }
+
+    fn summary_point(metric: &Metric, timestamps: &Timestamps) -> Value {
+        let summary = metric.get_summary();
+        let quantile_values: Vec<_> = summary
+            .get_quantile()
+            .iter()
+            .map(|quantile| {
+                json!({ "quantile": quantile.get_quantile(), "value": quantile.get_value() })
+            })
+            .collect();
+        json!({
+            "attributes": attributes(metric.get_label()),
+            "startTimeUnixNano": timestamps.start,
+            "timeUnixNano": timestamps.now,
+            "count": summary.get_sample_count().to_string(),
+            "sum": summary.get_sample_sum(),
+            "quantileValues": quantile_values,
+        })
+    }
+
+    fn attributes(labels: &[LabelPair]) -> Vec<Value> {
+        labels
+            .iter()
+            .map(|label| attribute(label.get_name(), label.get_value()))
+            .collect()
+    }
+
+    fn attribute(key: &str, value: &str) -> Value {
+        json!({ "key": key, "value": { "stringValue": value } })
+    }
+
+    fn unix_nanos(time: SystemTime) -> String {
+        time.duration_since(SystemTime::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_nanos()
+            .to_string()
+    }
+}
+
 /// For a storage node, given the shard allocation in previous and current epoch, and currently
 /// existing shards, calculate the shards movement in respect to previous epoch (for shard sync)
 /// as well as to local existing shards (for shard removal and recovery).
@@ -513,6 +885,103 @@ impl ShardDiffCalculator {
     }
 }
 
+/// A preview of how the shards assigned to a storage node change in the next epoch.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ShardAssignmentPreview {
+    /// The ID of the storage node.
+    pub node_id: ObjectID,
+    /// The epoch for which the next committee has been selected.
+    pub next_epoch: Epoch,
+    /// The shards assigned to the node in the current epoch.
+    pub current_shards: Vec<ShardIndex>,
+    /// The shards assigned to the node in the next epoch.
+    pub next_shards: Vec<ShardIndex>,
+    /// The shards that the node gains in the next epoch and needs to sync.
+    pub gained_shards: Vec<ShardIndex>,
+    /// The shards that the node loses in the next epoch.
+    pub lost_shards: Vec<ShardIndex>,
+    /// The estimated number of bytes stored per shard.
+    ///
+    /// This is derived from the storage capacity currently reserved in the system and is therefore
+    /// an upper bound for the data actually stored.
+    pub estimated_bytes_per_shard: u64,
+}
+
+impl ShardAssignmentPreview {
+    /// Computes the preview for the node with ID `node_id`, given the current and next committees
+    /// and the storage capacity currently reserved in the system.
+    pub fn new(
+        node_id: ObjectID,
+        current_committee: &Committee,
+        next_committee: &Committee,
+        used_capacity_size: u64,
+    ) -> Self {
+        let shards_of_node = |committee: &Committee| -> Vec<ShardIndex> {
+            let mut shards = committee
+                .members()
+                .iter()
+                .find(|member| member.node_id == node_id)
+                .map(|member| member.shard_ids.clone())
+                .unwrap_or_default();
+            shards.sort_unstable();
+            shards
+        };
+        let current_shards = shards_of_node(current_committee);
+        let next_shards = shards_of_node(next_committee);
+
+        let gained_shards = next_shards
+            .iter()
+            .filter(|shard| !current_shards.contains(shard))
+            .copied()
+            .collect();
+        let lost_shards = current_shards
+            .iter()
+            .filter(|shard| !next_shards.contains(shard))
+            .copied()
+            .collect();
+
+        Self {
+            node_id,
+            next_epoch: next_committee.epoch,
+            current_shards,
+            next_shards,
+            gained_shards,
+            lost_shards,
+            estimated_bytes_per_shard: used_capacity_size
+                / u64::from(current_committee.n_shards().get()),
+        }
+    }
+
+    /// Fetches the committees and the system state from Sui and computes the preview for the node
+    /// with ID `node_id`.
+    ///
+    /// Fails if the committee for the next epoch has not been selected yet.
+    pub async fn fetch(read_client: &SuiReadClient, node_id: ObjectID) -> Result<Self> {
+        let (current_committee, next_committee, system_object) = futures::try_join!(
+            read_client.current_committee(),
+            read_client.next_committee(),
+            read_client.get_system_object(),
+        )?;
+        let next_committee = next_committee.context(
+            "the committee for the next epoch has not been selected yet; the preview is available \
+            once the voting for the next epoch has ended",
+        )?;
+
+        Ok(Self::new(
+            node_id,
+            &current_committee,
+            &next_committee,
+            system_object.used_capacity_size(),
+        ))
+    }
+
+    /// Returns the estimated number of bytes that the node needs to sync for the gained shards.
+    pub fn estimated_bytes_to_sync(&self) -> u64 {
+        self.estimated_bytes_per_shard * self.gained_shards.len() as u64
+    }
+}
+
 /// Generates a new Sui wallet for the specified network at the specified path and attempts to fund
 /// it through the faucet.
 pub async fn generate_sui_wallet(
@@ -727,8 +1196,13 @@ pub async fn collect_event_blobs_for_catchup(
         contract_config,
         exchange_objects: vec![],
         wallet_config: None,
+        rpc_urls: vec![],
         communication_config: Default::default(),
         refresh_config: Default::default(),
+        gas_sponsor: None,
+        gas_escalation: Default::default(),
+        signing_service: None,
+        offline_signer: None,
     };
 
     let walrus_client =
@@ -852,7 +1326,10 @@ mod tests {
 
     use std::num::NonZeroU16;
 
-    use walrus_sui::{test_utils, types::Committee};
+    use walrus_sui::{
+        test_utils,
+        types::{Committee, StorageNode},
+    };
     use walrus_test_utils::{assert_unordered_eq, param_test};
 
     use super::*;
@@ -1100,4 +1577,101 @@ mod tests {
                 .map(|s| ShardIndex(*s))
         );
     }
+
+    #[test]
+    fn shard_assignment_preview_reports_gained_and_lost_shards() {
+        let shard_indices = |ids: &[u16]| ids.iter().copied().map(ShardIndex).collect::<Vec<_>>();
+        let committee = |epoch, shards_0: &[u16], shards_1: &[u16], nodes: [&StorageNode; 2]| {
+            let mut node_0 = nodes[0].clone();
+            node_0.shard_ids = shard_indices(shards_0);
+            let mut node_1 = nodes[1].clone();
+            node_1.shard_ids = shard_indices(shards_1);
+            Committee::new(vec![node_0, node_1], epoch, NonZeroU16::new(6).unwrap())
+                .expect("failed to create committee")
+        };
+        let node_0 = test_utils::new_move_storage_node_for_testing();
+        let node_1 = test_utils::new_move_storage_node_for_testing();
+        let current_committee = committee(5, &[3, 1, 2], &[0, 4, 5], [&node_0, &node_1]);
+        let next_committee = committee(6, &[0, 1], &[2, 3, 4, 5], [&node_0, &node_1]);
+
+        let preview =
+            ShardAssignmentPreview::new(node_0.node_id, &current_committee, &next_committee, 6000);
+
+        assert_eq!(
+            preview,
+            ShardAssignmentPreview {
+                node_id: node_0.node_id,
+                next_epoch: 6,
+                current_shards: shard_indices(&[1, 2, 3]),
+                next_shards: shard_indices(&[0, 1]),
+                gained_shards: shard_indices(&[0]),
+                lost_shards: shard_indices(&[2, 3]),
+                estimated_bytes_per_shard: 1000,
+            }
+        );
+        assert_eq!(preview.estimated_bytes_to_sync(), 1000);
+    }
+
+    param_test! {
+        pushgateway_group_url_is_built_from_job_and_labels: [
+            no_labels: ("http://localhost:9091", None, "http://localhost:9091/metrics/job/walrus"),
+            trailing_slash: (
+                "http://localhost:9091/",
+                None,
+                "http://localhost:9091/metrics/job/walrus"
+            ),
+            with_path_prefix: (
+                "https://example.com/gateway",
+                None,
+                "https://example.com/gateway/metrics/job/walrus"
+            ),
+            sorted_labels: (
+                "http://localhost:9091",
+                Some(&[("name", "node-1"), ("host", "host-1")][..]),
+                "http://localhost:9091/metrics/job/walrus/host/host-1/name/node-1"
+            ),
+            escaped_and_empty_labels: (
+                "http://localhost:9091",
+                Some(&[("name", "a b"), ("host", "")][..]),
+                "http://localhost:9091/metrics/job/walrus/name/a%20b"
+            ),
+            label_with_slash: (
+                "http://localhost:9091",
+                Some(&[("name", "a/b c")][..]),
+                "http://localhost:9091/metrics/job/walrus/name@base64/YS9iIGM="
+            ),
+        ]
+    }
+    fn pushgateway_group_url_is_built_from_job_and_labels(
+        base_url: &str,
+        labels: Option<&[(&str, &str)]>,
+        expected: &str,
+    ) {
+        let labels: Option<HashMap<String, String>> = labels.map(|labels| {
+            labels
+                .iter()
+                .map(|(name, value)| (name.to_string(), value.to_string()))
+                .collect()
+        });
+        let url = pushgateway_group_url(base_url, "walrus", labels.as_ref()).unwrap();
+        assert_eq!(url.as_str(), expected);
+    }
+
+    #[test]
+    fn otlp_bucket_counts_are_not_cumulative() {
+        let buckets: Vec<_> = [(0.1, 2), (1.0, 5), (10.0, 5), (f64::INFINITY, 7)]
+            .into_iter()
+            .map(|(upper_bound, cumulative_count)| {
+                let mut bucket = prometheus::proto::Bucket::default();
+                bucket.set_upper_bound(upper_bound);
+                bucket.set_cumulative_count(cumulative_count);
+                bucket
+            })
+            .collect();
+
+        let (explicit_bounds, bucket_counts) = otlp::bucket_counts(&buckets, 7);
+
+        assert_eq!(explicit_bounds, [0.1, 1.0, 10.0]);
+        assert_eq!(bucket_counts, ["2", "3", "0", "2"]);
+    }
 }
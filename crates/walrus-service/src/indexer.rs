@@ -0,0 +1,36 @@
+// Copyright (c) Walrus Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Walrus blob lifecycle indexer.
+//!
+//! The indexer follows the Walrus contract events and writes the lifecycle of the blob objects into
+//! a PostgreSQL database, on top of which analytics and explorers can be built without processing
+//! the Sui events themselves. The schema is created by the migrations in `indexer-migrations` and
+//! consists of the following tables:
+//!
+//!   - `blob_object`: one row per Sui blob object, with its blob ID, size, encoding type, and
+//!     lifetime, and its current status. The status is one of `'registered'`, `'certified'`,
+//!     `'invalid'`, `'deleted'`, and `'expired'`. Since there are no events for the expiry of
+//!     blobs, blobs are marked as `'expired'` when the epoch in which they expire starts.
+//!   - `blob_lifecycle_event`: the log of all blob events, in the order in which they were emitted.
+//!   - `epoch_start`: the epochs and the time at which the indexer noticed their start.
+//!   - `indexer_cursor`: the position in the event stream from which the indexer resumes after a
+//!     restart.
+//!
+//! Each event is written in a single transaction, together with the cursor, so the database is
+//! consistent with a prefix of the event stream at all times.
+
+mod config;
+pub use config::IndexerConfig;
+
+#[cfg(feature = "indexer")]
+mod lifecycle;
+
+#[cfg(feature = "indexer")]
+mod metrics;
+
+#[cfg(feature = "indexer")]
+mod service;
+
+#[cfg(feature = "indexer")]
+pub use self::service::{run_indexer_database_migrations, start_indexer, VERSION};
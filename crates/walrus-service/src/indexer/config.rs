@@ -0,0 +1,53 @@
+// Copyright (c) Walrus Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Configuration for the blob lifecycle indexer.
+
+use std::{net::SocketAddr, path::PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{common::config::SuiReaderConfig, node::events::EventProcessorConfig};
+
+/// Configuration of a Walrus blob lifecycle indexer.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct IndexerConfig {
+    /// Directory in which to persist the event processor database.
+    #[serde(deserialize_with = "walrus_utils::config::resolve_home_dir")]
+    pub storage_path: PathBuf,
+    /// URL of the PostgreSQL database to which the blob lifecycle is written.
+    #[serde(default = "defaults::database_url_from_env_var")]
+    pub database_url: String,
+    /// Socket address on which the Prometheus server should export its metrics.
+    #[serde(default = "defaults::metrics_address")]
+    pub metrics_address: SocketAddr,
+    /// Sui config for the indexer.
+    pub sui: SuiReaderConfig,
+    /// Configuration for the event processor.
+    #[serde(default, skip_serializing_if = "defaults::is_default")]
+    pub event_processor_config: EventProcessorConfig,
+}
+
+/// Indexer-related default values.
+pub mod defaults {
+    use std::net::{Ipv4Addr, SocketAddr};
+
+    /// Default indexer metrics port.
+    pub const METRICS_PORT: u16 = 10186;
+
+    /// Returns the default metrics address.
+    pub fn metrics_address() -> SocketAddr {
+        (Ipv4Addr::LOCALHOST, METRICS_PORT).into()
+    }
+
+    /// Returns true iff the value is the default and we don't run in test mode.
+    pub fn is_default<T: PartialEq + Default>(t: &T) -> bool {
+        !cfg!(test) && t == &T::default()
+    }
+
+    /// Returns the database URL from the `DATABASE_URL` environment variable. Fails hard if it
+    /// can't find it to ensure there is always a database_url.
+    pub fn database_url_from_env_var() -> String {
+        std::env::var("DATABASE_URL").expect("missing DATABASE_URL env var")
+    }
+}
@@ -0,0 +1,134 @@
+// Copyright (c) Walrus Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! The blob lifecycle records written by the indexer.
+
+use sui_types::base_types::ObjectID;
+use walrus_core::{BlobId, Epoch};
+use walrus_sui::types::BlobEvent;
+
+/// The kind of a blob lifecycle event, as stored in the `kind` column of the
+/// `blob_lifecycle_event` table.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum BlobLifecycleKind {
+    /// A blob object was registered.
+    Registered,
+    /// A blob object was certified for the first time.
+    Certified,
+    /// The lifetime of a certified blob object was extended.
+    Extended,
+    /// A deletable blob object was deleted.
+    Deleted,
+    /// The blob ID was marked as invalid.
+    Invalid,
+    /// A blob on the deny list was deleted from the storage nodes.
+    DenyListDeleted,
+}
+
+impl BlobLifecycleKind {
+    /// Returns the name of the kind as stored in the database.
+    pub(crate) fn as_str(self) -> &'static str {
+        match self {
+            Self::Registered => "registered",
+            Self::Certified => "certified",
+            Self::Extended => "extended",
+            Self::Deleted => "deleted",
+            Self::Invalid => "invalid",
+            Self::DenyListDeleted => "deny_list_deleted",
+        }
+    }
+}
+
+/// A row of the `blob_lifecycle_event` table.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct BlobLifecycleRecord {
+    /// The kind of the event.
+    pub kind: BlobLifecycleKind,
+    /// The blob ID.
+    pub blob_id: BlobId,
+    /// The ID of the blob object, if the event refers to a single blob object.
+    pub object_id: Option<ObjectID>,
+    /// The epoch in which the event was emitted.
+    pub epoch: Epoch,
+    /// The end epoch (exclusive) of the blob object after the event, if known.
+    pub end_epoch: Option<Epoch>,
+}
+
+impl From<&BlobEvent> for BlobLifecycleRecord {
+    fn from(event: &BlobEvent) -> Self {
+        match event {
+            BlobEvent::Registered(registered) => Self {
+                kind: BlobLifecycleKind::Registered,
+                blob_id: registered.blob_id,
+                object_id: Some(registered.object_id),
+                epoch: registered.epoch,
+                end_epoch: Some(registered.end_epoch),
+            },
+            BlobEvent::Certified(certified) => Self {
+                kind: if certified.is_extension {
+                    BlobLifecycleKind::Extended
+                } else {
+                    BlobLifecycleKind::Certified
+                },
+                blob_id: certified.blob_id,
+                object_id: Some(certified.object_id),
+                epoch: certified.epoch,
+                end_epoch: Some(certified.end_epoch),
+            },
+            BlobEvent::Deleted(deleted) => Self {
+                kind: BlobLifecycleKind::Deleted,
+                blob_id: deleted.blob_id,
+                object_id: Some(deleted.object_id),
+                epoch: deleted.epoch,
+                end_epoch: Some(deleted.end_epoch),
+            },
+            BlobEvent::InvalidBlobID(invalid) => Self {
+                kind: BlobLifecycleKind::Invalid,
+                blob_id: invalid.blob_id,
+                object_id: None,
+                epoch: invalid.epoch,
+                end_epoch: None,
+            },
+            BlobEvent::DenyListBlobDeleted(deleted) => Self {
+                kind: BlobLifecycleKind::DenyListDeleted,
+                blob_id: deleted.blob_id,
+                object_id: None,
+                epoch: deleted.epoch,
+                end_epoch: None,
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use walrus_core::test_utils::random_blob_id;
+    use walrus_sui::{
+        test_utils::EventForTesting,
+        types::{BlobCertified, InvalidBlobId},
+    };
+
+    use super::*;
+
+    #[test]
+    fn distinguishes_extensions_from_certifications() {
+        let mut certified = BlobCertified::for_testing(random_blob_id());
+        let record = BlobLifecycleRecord::from(&BlobEvent::Certified(certified.clone()));
+        assert_eq!(record.kind, BlobLifecycleKind::Certified);
+        assert_eq!(record.object_id, Some(certified.object_id));
+        assert_eq!(record.end_epoch, Some(certified.end_epoch));
+
+        certified.is_extension = true;
+        let record = BlobLifecycleRecord::from(&BlobEvent::Certified(certified));
+        assert_eq!(record.kind.as_str(), "extended");
+    }
+
+    #[test]
+    fn invalid_blob_ids_refer_to_all_blob_objects() {
+        let invalid = InvalidBlobId::for_testing(random_blob_id());
+        let record = BlobLifecycleRecord::from(&BlobEvent::InvalidBlobID(invalid.clone()));
+        assert_eq!(record.kind, BlobLifecycleKind::Invalid);
+        assert_eq!(record.blob_id, invalid.blob_id);
+        assert_eq!(record.object_id, None);
+    }
+}
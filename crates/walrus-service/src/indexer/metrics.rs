@@ -0,0 +1,22 @@
+// Copyright (c) Walrus Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use prometheus::{IntCounter, IntCounterVec, IntGauge};
+
+walrus_utils::metrics::define_metric_set! {
+    #[namespace = "walrus"]
+    /// Metrics exported by the blob lifecycle indexer.
+    pub(crate) struct IndexerMetricSet {
+        #[help = "The count of all Sui stream events seen"]
+        indexer_events_seen: IntCounter[],
+
+        #[help = "The count of blob lifecycle events recorded, by kind"]
+        indexer_blob_events_recorded: IntCounterVec["kind"],
+
+        #[help = "The count of blob objects marked as expired"]
+        indexer_blobs_expired: IntCounter[],
+
+        #[help = "The latest epoch seen by the indexer"]
+        indexer_latest_epoch: IntGauge[],
+    }
+}
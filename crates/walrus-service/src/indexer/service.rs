@@ -0,0 +1,350 @@
+// Copyright (c) Walrus Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Indexer service implementation.
+
+use std::{pin::Pin, sync::Arc};
+
+use anyhow::{bail, Result};
+use diesel::{
+    result::Error,
+    sql_types::{Bool, Bytea, Int8, Nullable, Text},
+    Connection as _,
+    OptionalExtension as _,
+    QueryableByName,
+};
+use diesel_async::{
+    scoped_futures::ScopedFutureExt,
+    AsyncConnection as _,
+    AsyncPgConnection,
+    RunQueryDsl as _,
+};
+use diesel_migrations::{embed_migrations, EmbeddedMigrations, MigrationHarness};
+use futures::{stream, StreamExt};
+use sui_types::event::EventID;
+use tokio_util::sync::CancellationToken;
+use walrus_core::Epoch;
+use walrus_sui::types::{BlobEvent, ContractEvent, EpochChangeEvent, EpochChangeStart};
+
+use super::{config::IndexerConfig, lifecycle::BlobLifecycleRecord, metrics::IndexerMetricSet};
+use crate::{
+    common::utils::{self, version, MetricsAndLoggingRuntime},
+    node::{
+        events::{
+            event_processor::EventProcessor,
+            event_processor_runtime::EventProcessorRuntime,
+            CheckpointEventPosition,
+            EventStreamCursor,
+            EventStreamElement,
+            PositionedStreamEvent,
+        },
+        system_events::SystemEventProvider as _,
+        DatabaseConfig,
+    },
+};
+
+/// The version of the Walrus indexer.
+pub const VERSION: &str = version!();
+
+const MIGRATIONS: EmbeddedMigrations = embed_migrations!("indexer-migrations");
+
+/// Runs the database migrations for the indexer.
+pub fn run_indexer_database_migrations(config: &IndexerConfig) -> Result<()> {
+    let mut connection = diesel::PgConnection::establish(&config.database_url)?;
+    tracing::info!("running pending migrations");
+    let versions = connection
+        .run_pending_migrations(MIGRATIONS)
+        .map_err(|error| anyhow::anyhow!("failed to run pending migrations: {error}"))?;
+    tracing::info!(?versions, "migrations ran successfully");
+    Ok(())
+}
+
+/// Starts the indexer and writes the blob lifecycle into the database until the event stream ends.
+pub async fn start_indexer(
+    config: IndexerConfig,
+    metrics_runtime: &MetricsAndLoggingRuntime,
+) -> Result<()> {
+    tracing::info!(?config, version = VERSION, "starting the Walrus indexer");
+    utils::export_build_info(&metrics_runtime.registry, VERSION);
+
+    let event_processor = EventProcessorRuntime::start_async(
+        config.sui.clone(),
+        config.event_processor_config.clone(),
+        &config.storage_path,
+        &metrics_runtime.registry,
+        CancellationToken::new(),
+        &DatabaseConfig::default(),
+    )
+    .await?;
+
+    index_events(
+        event_processor,
+        &config.database_url,
+        IndexerMetricSet::new(&metrics_runtime.registry),
+    )
+    .await
+}
+
+async fn index_events(
+    event_processor: Arc<EventProcessor>,
+    database_url: &str,
+    metrics: IndexerMetricSet,
+) -> Result<()> {
+    let mut connection = AsyncPgConnection::establish(database_url).await?;
+    let cursor = get_indexer_cursor(&mut connection).await?;
+    tracing::info!(?cursor, "resuming the event stream");
+
+    let event_stream = Pin::from(event_processor.events(cursor).await?);
+    let mut indexed_element_stream = stream::iter(cursor.element_index..).zip(event_stream);
+    while let Some((
+        element_index,
+        PositionedStreamEvent {
+            element,
+            checkpoint_event_position,
+        },
+    )) = indexed_element_stream.next().await
+    {
+        metrics.indexer_events_seen.inc();
+        let EventStreamElement::ContractEvent(contract_event) = &element else {
+            // Checkpoint boundaries are not relevant for the indexer.
+            continue;
+        };
+        let event_id = element
+            .event_id()
+            .expect("contract events have an event ID");
+        record_event(
+            &mut connection,
+            contract_event,
+            event_id,
+            checkpoint_event_position,
+            element_index,
+            &metrics,
+        )
+        .await?;
+    }
+
+    bail!("the event stream of the indexer stopped")
+}
+
+#[derive(Debug, QueryableByName)]
+struct CursorRow {
+    #[diesel(sql_type = Int8)]
+    next_element_index: i64,
+}
+
+async fn get_indexer_cursor(connection: &mut AsyncPgConnection) -> Result<EventStreamCursor> {
+    let cursor: Option<CursorRow> =
+        diesel::sql_query("SELECT next_element_index FROM indexer_cursor")
+            .get_result(connection)
+            .await
+            .optional()?;
+    Ok(EventStreamCursor::new(
+        None,
+        cursor.map_or(0, |cursor| {
+            u64::try_from(cursor.next_element_index).expect("the element index is not negative")
+        }),
+    ))
+}
+
+/// Writes the contract event and the new cursor to the database in a single transaction.
+async fn record_event(
+    connection: &mut AsyncPgConnection,
+    contract_event: &ContractEvent,
+    event_id: EventID,
+    checkpoint_event_position: CheckpointEventPosition,
+    element_index: u64,
+    metrics: &IndexerMetricSet,
+) -> Result<(), Error> {
+    let element_index = to_i64(element_index);
+    let expired = connection
+        .transaction::<_, Error, _>(|connection| {
+            async move {
+                let mut expired = 0;
+                match contract_event {
+                    ContractEvent::BlobEvent(blob_event) => {
+                        record_blob_event(
+                            connection,
+                            blob_event,
+                            event_id,
+                            checkpoint_event_position,
+                            element_index,
+                        )
+                        .await?;
+                    }
+                    ContractEvent::EpochChangeEvent(EpochChangeEvent::EpochChangeStart(
+                        EpochChangeStart { epoch, .. },
+                    )) => {
+                        expired = record_epoch_start(connection, *epoch).await?;
+                    }
+                    _ => (),
+                }
+
+                diesel::sql_query(
+                    "
+                    INSERT INTO indexer_cursor (next_element_index) VALUES ($1)
+                    ON CONFLICT (id) DO UPDATE SET
+                        next_element_index = EXCLUDED.next_element_index,
+                        updated_at = NOW()",
+                )
+                .bind::<Int8, _>(element_index + 1)
+                .execute(connection)
+                .await?;
+                Ok(expired)
+            }
+            .scope_boxed()
+        })
+        .await?;
+
+    match contract_event {
+        ContractEvent::BlobEvent(blob_event) => metrics
+            .indexer_blob_events_recorded
+            .with_label_values(&[BlobLifecycleRecord::from(blob_event).kind.as_str()])
+            .inc(),
+        ContractEvent::EpochChangeEvent(EpochChangeEvent::EpochChangeStart(EpochChangeStart {
+            epoch,
+            ..
+        })) => {
+            metrics.indexer_blobs_expired.inc_by(expired as u64);
+            metrics.indexer_latest_epoch.set(i64::from(*epoch));
+        }
+        _ => (),
+    }
+    Ok(())
+}
+
+async fn record_blob_event(
+    connection: &mut AsyncPgConnection,
+    blob_event: &BlobEvent,
+    event_id: EventID,
+    checkpoint_event_position: CheckpointEventPosition,
+    element_index: i64,
+) -> Result<(), Error> {
+    let record = BlobLifecycleRecord::from(blob_event);
+    let blob_id = record.blob_id.0.to_vec();
+    let object_id = record.object_id.map(|object_id| object_id.to_vec());
+    let epoch = i64::from(record.epoch);
+    let end_epoch = record.end_epoch.map(i64::from);
+
+    diesel::sql_query(
+        "
+        INSERT INTO blob_lifecycle_event (
+            element_index,
+            checkpoint_sequence_number,
+            transaction_digest,
+            event_seq,
+            kind,
+            blob_id,
+            object_id,
+            epoch,
+            end_epoch
+        )
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+        ON CONFLICT (element_index) DO NOTHING",
+    )
+    .bind::<Int8, _>(element_index)
+    .bind::<Int8, _>(to_i64(checkpoint_event_position.checkpoint_sequence_number))
+    .bind::<Bytea, _>(event_id.tx_digest.into_inner().to_vec())
+    .bind::<Int8, _>(to_i64(event_id.event_seq))
+    .bind::<Text, _>(record.kind.as_str())
+    .bind::<Bytea, _>(&blob_id)
+    .bind::<Nullable<Bytea>, _>(&object_id)
+    .bind::<Int8, _>(epoch)
+    .bind::<Nullable<Int8>, _>(end_epoch)
+    .execute(connection)
+    .await?;
+
+    match blob_event {
+        BlobEvent::Registered(registered) => {
+            diesel::sql_query(
+                "
+                INSERT INTO blob_object (
+                    object_id,
+                    blob_id,
+                    size,
+                    encoding_type,
+                    deletable,
+                    registered_epoch,
+                    end_epoch,
+                    status
+                )
+                VALUES ($1, $2, $3, $4, $5, $6, $7, 'registered')
+                ON CONFLICT (object_id) DO NOTHING",
+            )
+            .bind::<Bytea, _>(registered.object_id.to_vec())
+            .bind::<Bytea, _>(&blob_id)
+            .bind::<Int8, _>(to_i64(registered.size))
+            .bind::<Text, _>(registered.encoding_type.to_string())
+            .bind::<Bool, _>(registered.deletable)
+            .bind::<Int8, _>(epoch)
+            .bind::<Int8, _>(i64::from(registered.end_epoch))
+            .execute(connection)
+            .await?;
+        }
+        BlobEvent::Certified(_) => {
+            diesel::sql_query(
+                "
+                UPDATE blob_object SET
+                    certified_epoch = COALESCE(certified_epoch, $2),
+                    end_epoch = GREATEST(end_epoch, $3),
+                    status = CASE WHEN status = 'invalid' THEN status ELSE 'certified' END,
+                    updated_at = NOW()
+                WHERE object_id = $1",
+            )
+            .bind::<Nullable<Bytea>, _>(&object_id)
+            .bind::<Int8, _>(epoch)
+            .bind::<Nullable<Int8>, _>(end_epoch)
+            .execute(connection)
+            .await?;
+        }
+        BlobEvent::Deleted(_) => {
+            diesel::sql_query(
+                "
+                UPDATE blob_object SET status = 'deleted', updated_at = NOW()
+                WHERE object_id = $1",
+            )
+            .bind::<Nullable<Bytea>, _>(&object_id)
+            .execute(connection)
+            .await?;
+        }
+        BlobEvent::InvalidBlobID(_) => {
+            diesel::sql_query(
+                "
+                UPDATE blob_object SET status = 'invalid', updated_at = NOW()
+                WHERE blob_id = $1 AND status <> 'deleted'",
+            )
+            .bind::<Bytea, _>(&blob_id)
+            .execute(connection)
+            .await?;
+        }
+        BlobEvent::DenyListBlobDeleted(_) => (),
+    }
+    tracing::debug!(?record, "recorded blob lifecycle event");
+    Ok(())
+}
+
+/// Records the start of the `epoch` and marks the blob objects that expire with it as expired.
+///
+/// Returns the number of blob objects marked as expired.
+async fn record_epoch_start(
+    connection: &mut AsyncPgConnection,
+    epoch: Epoch,
+) -> Result<usize, Error> {
+    diesel::sql_query("INSERT INTO epoch_start (epoch) VALUES ($1) ON CONFLICT DO NOTHING")
+        .bind::<Int8, _>(i64::from(epoch))
+        .execute(connection)
+        .await?;
+    let expired = diesel::sql_query(
+        "
+        UPDATE blob_object SET status = 'expired', updated_at = NOW()
+        WHERE status IN ('registered', 'certified') AND end_epoch <= $1",
+    )
+    .bind::<Int8, _>(i64::from(epoch))
+    .execute(connection)
+    .await?;
+    tracing::info!(epoch, expired, "a new Walrus epoch has begun");
+    Ok(expired)
+}
+
+fn to_i64(value: u64) -> i64 {
+    value.try_into().expect("the value fits into a BIGINT")
+}
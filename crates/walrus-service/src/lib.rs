@@ -20,6 +20,8 @@ pub mod testbed;
 
 pub mod backup;
 
+pub mod indexer;
+
 #[cfg(any(feature = "client", feature = "node"))]
 pub mod common;
 #[cfg(any(feature = "client", feature = "node"))]
@@ -11,13 +11,15 @@ use std::{
         atomic::{AtomicBool, AtomicU32, Ordering},
         Arc,
     },
+    time::Duration,
 };
 
 use anyhow::{anyhow, bail, Context};
 use blob_retirement_notifier::BlobRetirementNotifier;
+pub use blocklist_enforcer::BLOCKLIST_AUDIT_TARGET;
 use committee::{BeginCommitteeChangeError, EndCommitteeChangeError};
 use epoch_change_driver::EpochChangeDriver;
-use errors::{ListSymbolsError, Unavailable};
+use errors::{ListStoredBlobsError, ListSymbolsError, Unavailable};
 use events::{event_blob_writer::EventBlobWriter, CheckpointEventPosition};
 use fastcrypto::traits::KeyPair;
 use futures::{
@@ -41,7 +43,11 @@ use sui_macros::{fail_point_arg, fail_point_async};
 use sui_types::{base_types::ObjectID, event::EventID};
 use system_events::{CompletableHandle, EventHandle, EVENT_ID_FOR_CHECKPOINT_EVENTS};
 use thread_pool::{BoundedThreadPool, ThreadPoolBuilder};
-use tokio::{select, sync::watch, time::Instant};
+use tokio::{
+    select,
+    sync::{broadcast, watch, OnceCell},
+    time::Instant,
+};
 use tokio_metrics::TaskMonitor;
 use tokio_util::sync::CancellationToken;
 use tower::{Service, ServiceExt};
@@ -61,9 +67,11 @@ use walrus_core::{
     messages::{
         BlobPersistenceType,
         Confirmation,
+        EpochAccountingMsg,
         InvalidBlobIdAttestation,
         InvalidBlobIdMsg,
         ProtocolMessage,
+        SignedEpochAccountingReport,
         SignedMessage,
         SignedSyncShardRequest,
         StorageConfirmation,
@@ -87,12 +95,17 @@ use walrus_core::{
 };
 use walrus_rest_client::{
     api::{
+        BlobDataDeletionReason,
         BlobStatus,
+        EpochHandoverInfo,
+        NodeEvent,
         ServiceHealthInfo,
         ShardHealthInfo,
+        ShardOwnershipAudit,
         ShardStatus as ApiShardStatus,
         ShardStatusDetail,
         ShardStatusSummary,
+        StoredBlobsPage,
         StoredOnNodeStatus,
     },
     client::{RecoverySymbolsFilter, SymbolIdFilter},
@@ -119,13 +132,17 @@ use walrus_sdk::{
 use walrus_utils::metrics::{Registry, TaskMonitorFamily};
 
 use self::{
+    accounting::EpochAccountant,
     blob_sync::BlobSyncHandler,
+    blocklist_enforcer::BlocklistEnforcer,
     committee::{CommitteeService, NodeCommitteeService},
     config::StorageNodeConfig,
     contract_service::{SuiSystemContractService, SystemContractService},
+    epoch_handover::EpochHandover,
     errors::{
         BlobStatusError,
         ComputeStorageConfirmationError,
+        EpochAccountingError,
         InconsistencyProofError,
         IndexOutOfRange,
         InvalidEpochError,
@@ -146,12 +163,15 @@ use self::{
         EventStreamElement,
         PositionedStreamEvent,
     },
+    local_events::LocalEventSender,
     metrics::{NodeMetricSet, TelemetryLabel as _, STATUS_PENDING, STATUS_PERSISTED},
+    request_scheduler::{RequestClass, RequestScheduler},
     shard_sync::ShardSyncHandler,
     storage::{
         blob_info::{BlobInfoApi, CertifiedBlobInfoApi},
         ShardStatus,
         ShardStorage,
+        SliverChecksumStatus,
     },
     system_events::{EventManager, SuiSystemEventProvider},
 };
@@ -165,17 +185,25 @@ pub mod config;
 pub mod contract_service;
 pub mod dbtool;
 pub mod events;
+pub mod metadata_backfill;
 pub mod server;
 pub mod system_events;
 
 pub(crate) mod metrics;
 
+mod accounting;
 mod blob_retirement_notifier;
 mod blob_sync;
+mod blocklist_enforcer;
 mod consistency_check;
 mod epoch_change_driver;
+mod epoch_handover;
+mod garbage_collection;
+mod local_events;
 mod node_recovery;
 mod recovery_symbol_service;
+mod request_scheduler;
+mod shard_audit;
 mod shard_sync;
 mod start_epoch_change_finisher;
 mod thread_pool;
@@ -288,6 +316,32 @@ pub trait ServiceState {
         public_key: PublicKey,
         signed_request: SignedSyncShardRequest,
     ) -> impl Future<Output = Result<SyncShardResponse, SyncShardServiceError>> + Send;
+
+    /// Returns a receiver for the events about the locally stored data emitted from now on.
+    fn subscribe_to_local_events(&self) -> broadcast::Receiver<NodeEvent>;
+
+    /// Returns a signed report of the data served and stored by the node in the given epoch.
+    fn epoch_accounting_report(
+        &self,
+        epoch: Epoch,
+    ) -> impl Future<Output = Result<SignedEpochAccountingReport, EpochAccountingError>> + Send;
+
+    /// Returns the comparison of the shards in storage with the shards assigned to the node in
+    /// the current epoch.
+    fn shard_ownership_audit(&self) -> impl Future<Output = ShardOwnershipAudit> + Send;
+
+    /// Returns the state of the shard handover during the latest committee transition started
+    /// since the node started, if any.
+    fn epoch_handover(&self) -> Option<EpochHandoverInfo>;
+
+    /// Returns up to `limit` IDs of the blobs stored for the shard, in ascending order and
+    /// starting after the blob ID `cursor`, together with the cursor of the next page.
+    fn list_stored_blobs(
+        &self,
+        shard_index: ShardIndex,
+        cursor: Option<BlobId>,
+        limit: usize,
+    ) -> impl Future<Output = Result<StoredBlobsPage, ListStoredBlobsError>> + Send;
 }
 
 /// Builder to construct a [`StorageNode`].
@@ -492,6 +546,7 @@ pub struct StorageNode {
     node_recovery_handler: NodeRecoveryHandler,
     event_blob_writer_factory: Option<EventBlobWriterFactory>,
     config_synchronizer: Option<Arc<ConfigSynchronizer>>,
+    blocklist_enforcer: Option<BlocklistEnforcer>,
 }
 
 /// The internal state of a Walrus storage node.
@@ -508,12 +563,43 @@ pub struct StorageNodeInner {
     current_epoch: watch::Sender<Epoch>,
     is_shutting_down: AtomicBool,
     blocklist: Arc<Blocklist>,
+    delete_blocked_blob_data: bool,
+    delete_expired_blob_data: bool,
     node_capability: ObjectID,
     blob_retirement_notifier: Arc<BlobRetirementNotifier>,
     symbol_service: RecoverySymbolService,
     thread_pool: BoundedThreadPool,
     registry: Registry,
     latest_event_epoch: AtomicU32, // The epoch of the latest event processed by the node.
+    expired_blob_read_grace_epochs: Epoch,
+    epoch_handover: EpochHandover,
+    /// Attestations for blobs proven to be inconsistent in the given epoch.
+    ///
+    /// Concurrent proofs for the same blob wait for the verification of the first; once a proof
+    /// has been verified, the attestation is returned without verifying further proofs.
+    invalid_blob_attestations: moka::future::Cache<(Epoch, BlobId), InvalidBlobAttestationCell>,
+    local_events: LocalEventSender,
+    accountant: EpochAccountant,
+    request_scheduler: RequestScheduler,
+}
+
+type InvalidBlobAttestationCell = Arc<OnceCell<InvalidBlobIdAttestation>>;
+
+/// The maximum number of attestations of inconsistent blobs that are cached.
+const MAX_CACHED_INVALID_BLOB_ATTESTATIONS: u64 = 1_000;
+
+/// The interval at which the epoch accounting is written to the database.
+const EPOCH_ACCOUNTING_FLUSH_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Describes whether the data of a blob can be served on the read path.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum BlobReadability {
+    /// The blob is registered, or its storage period ended within the read grace period.
+    Readable,
+    /// There is no registered `Blob` object for the blob ID.
+    Unregistered,
+    /// The storage period of the blob ended at the contained epoch, before the read grace period.
+    Expired(Epoch),
 }
 
 /// Parameters for configuring and initializing a node.
@@ -589,11 +675,26 @@ impl StorageNode {
         };
         tracing::info!("successfully opened the node database");
 
+        if storage.was_shut_down_uncleanly() {
+            tracing::warn!("the node was not shut down cleanly, checking the database integrity");
+            let report = storage
+                .check_integrity_and_recover()
+                .await
+                .context("the database integrity check failed, refusing to serve its data")?;
+            tracing::warn!(%report, "recovered the database after an unclean shutdown");
+        }
+
         let thread_pool = ThreadPoolBuilder::default()
             .max_concurrent(config.thread_pool.max_concurrent_tasks)
             .metrics_registry(registry.clone())
             .build_bounded();
         let blocklist: Arc<Blocklist> = Arc::new(Blocklist::new(&config.blocklist_path)?);
+        let committees = committee_service.active_committees();
+        let last_confirmed_epoch = if committees.is_change_in_progress() {
+            committees.epoch().saturating_sub(1)
+        } else {
+            committees.epoch()
+        };
         let inner = Arc::new(StorageNodeInner {
             protocol_key_pair: config
                 .protocol_key_pair
@@ -609,6 +710,8 @@ impl StorageNode {
             start_time,
             is_shutting_down: false.into(),
             blocklist: blocklist.clone(),
+            delete_blocked_blob_data: config.delete_blocked_blob_data,
+            delete_expired_blob_data: config.delete_expired_blob_data,
             node_capability: node_capability.id,
             blob_retirement_notifier: Arc::new(BlobRetirementNotifier::new()),
             symbol_service: RecoverySymbolService::new(
@@ -621,6 +724,15 @@ impl StorageNode {
             encoding_config,
             registry: registry.clone(),
             latest_event_epoch: AtomicU32::new(0),
+            expired_blob_read_grace_epochs: config.expired_blob_read_grace_epochs,
+            epoch_handover: EpochHandover::new(last_confirmed_epoch),
+            invalid_blob_attestations: moka::future::Cache::builder()
+                .name("invalid_blob_attestations")
+                .max_capacity(MAX_CACHED_INVALID_BLOB_ATTESTATIONS)
+                .build(),
+            local_events: LocalEventSender::new(),
+            accountant: EpochAccountant::new(),
+            request_scheduler: RequestScheduler::new(&config.request_scheduler),
         });
 
         blocklist.start_refresh_task();
@@ -671,6 +783,10 @@ impl StorageNode {
             None
         };
 
+        let blocklist_enforcer = config
+            .delete_blocked_blob_data
+            .then(|| BlocklistEnforcer::new(inner.clone(), blob_sync_handler.clone()));
+
         Ok(StorageNode {
             inner,
             blob_sync_handler,
@@ -680,6 +796,7 @@ impl StorageNode {
             node_recovery_handler,
             event_blob_writer_factory,
             config_synchronizer,
+            blocklist_enforcer,
         })
     }
 
@@ -710,6 +827,13 @@ impl StorageNode {
             _ = cancel_token.cancelled() => {
                 self.inner.shut_down();
                 self.blob_sync_handler.cancel_all().await?;
+                self.inner.storage.persist_blob_id_filters().await;
+                if let Err(error) = self.inner.flush_epoch_accounting() {
+                    tracing::warn!(?error, "failed to write the epoch accounting on shutdown");
+                }
+                if let Err(error) = self.inner.storage.mark_clean_shutdown() {
+                    tracing::warn!(?error, "failed to record the clean shutdown of the storage");
+                }
             },
             blob_sync_result = self.blob_sync_handler.spawn_task_monitor() => {
                 match blob_sync_result {
@@ -736,6 +860,18 @@ impl StorageNode {
                     Err(e) => return Err(e.into()),
                 }
             }
+            () = async {
+                if let Some(enforcer) = self.blocklist_enforcer.as_ref() {
+                    enforcer.run().await
+                } else {
+                    std::future::pending().await
+                }
+            } => {
+                unreachable!("blocklist enforcer never completes");
+            }
+            () = self.inner.flush_epoch_accounting_periodically() => {
+                unreachable!("epoch accounting flush never completes");
+            }
         }
 
         Ok(())
@@ -1138,7 +1274,13 @@ impl StorageNode {
         let start = tokio::time::Instant::now();
         let histogram_set = self.inner.metrics.recover_blob_duration_seconds.clone();
 
+        self.inner.local_events.emit(NodeEvent::BlobCertified {
+            blob_id: event.blob_id,
+            epoch: event.epoch,
+        });
+
         if !self.inner.is_blob_certified(&event.blob_id)?
+            || self.inner.rejects_blob_data(&event.blob_id)
             || self.inner.storage.node_status()? == NodeStatus::RecoveryCatchUp
             || self
                 .inner
@@ -1213,6 +1355,10 @@ impl StorageNode {
             .cancel_sync_and_mark_event_complete(&event.blob_id)
             .await?;
         self.inner.storage.delete_blob_data(&event.blob_id).await?;
+        self.inner.local_events.emit(NodeEvent::BlobDataDeleted {
+            blob_id: event.blob_id,
+            reason: BlobDataDeletionReason::Invalid,
+        });
 
         event_handle.mark_as_complete();
         Ok(())
@@ -1262,6 +1408,7 @@ impl StorageNode {
                 "failed to schedule background blob info consistency check"
             );
         }
+        garbage_collection::schedule_background_garbage_collection(self.inner.clone(), event.epoch);
 
         // During epoch change, we need to lock the read access to shard map until all the new
         // shards are created.
@@ -1550,6 +1697,11 @@ impl StorageNode {
             ShardDiffCalculator::new(&committees, public_key, shard_map_lock.existing_shards());
 
         let shards_gained = shard_diff_calculator.gained_shards_from_prev_epoch();
+        self.inner.epoch_handover.begin(
+            event.epoch,
+            shard_diff_calculator.shards_to_lock().to_vec(),
+            shards_gained.to_vec(),
+        );
         self.create_new_shards_and_start_sync(
             shard_map_lock,
             shards_gained,
@@ -1619,6 +1771,10 @@ impl StorageNode {
 
     #[tracing::instrument(skip_all)]
     async fn process_epoch_change_done_event(&self, event: &EpochChangeDone) -> anyhow::Result<()> {
+        // The event is only emitted once the committee has confirmed that it is synced, which
+        // completes the handover of shards irrespective of the state of our committee service.
+        self.inner.epoch_handover.confirm(event.epoch);
+
         match self
             .inner
             .committee_service
@@ -1892,6 +2048,12 @@ impl StorageNodeInner {
         self.blocklist.is_blocked(blob_id)
     }
 
+    /// Returns true if the node does not store any data for the blob, as it is blocked and the
+    /// data of blocked blobs is deleted.
+    fn rejects_blob_data(&self, blob_id: &BlobId) -> bool {
+        self.delete_blocked_blob_data && self.is_blocked(blob_id)
+    }
+
     async fn get_shard_for_sliver_pair(
         &self,
         sliver_pair_index: SliverPairIndex,
@@ -2003,6 +2165,7 @@ impl StorageNodeInner {
             .await;
         let (metadata, sliver) = thread_pool::unwrap_or_resume_panic(result)?;
         let sliver_type = sliver.r#type();
+        let sliver_len = sliver.len();
 
         // Finally store the sliver in the appropriate shard storage.
         shard_storage
@@ -2011,10 +2174,121 @@ impl StorageNodeInner {
             .context("unable to store sliver")?;
 
         walrus_utils::with_label!(self.metrics.slivers_stored_total, sliver_type).inc();
+        self.accountant
+            .record_stored(self.current_epoch(), shard_storage.id(), sliver_len);
+        self.emit_event_if_blob_stored(metadata.blob_id()).await;
 
         Ok(true)
     }
 
+    /// Verifies a stored sliver that does not match the checksum stored alongside it against the
+    /// blob's metadata.
+    ///
+    /// If the sliver is valid, only its checksum was corrupted, and the sliver is stored again with
+    /// a new checksum. Otherwise, the sliver is corrupted and is not served.
+    async fn verify_sliver_with_checksum_mismatch(
+        &self,
+        shard_storage: &ShardStorage,
+        blob_id: &BlobId,
+        sliver: Sliver,
+    ) -> Result<Sliver, RetrieveSliverError> {
+        let sliver_type = sliver.r#type();
+        let metadata = self
+            .storage
+            .get_metadata(blob_id)
+            .context("database error when verifying sliver")?
+            .ok_or(RetrieveSliverError::Unavailable)?;
+
+        let encoding_config = self.encoding_config.clone();
+        let result = self
+            .thread_pool
+            .clone()
+            .oneshot(move || {
+                let verification = sliver.verify(&encoding_config, metadata.metadata());
+                (verification, sliver)
+            })
+            .await;
+        let (verification, sliver) = thread_pool::unwrap_or_resume_panic(result);
+
+        if let Err(error) = verification {
+            tracing::error!(
+                %blob_id,
+                shard_index = %shard_storage.id(),
+                %sliver_type,
+                ?error,
+                "stored sliver does not match its checksum and fails verification",
+            );
+            walrus_utils::with_label!(
+                self.metrics.sliver_checksum_mismatches_total,
+                sliver_type,
+                "corrupted"
+            )
+            .inc();
+            return Err(RetrieveSliverError::Unavailable);
+        }
+
+        tracing::warn!(
+            %blob_id,
+            shard_index = %shard_storage.id(),
+            %sliver_type,
+            "stored sliver does not match its checksum but is valid, storing a new checksum",
+        );
+        walrus_utils::with_label!(
+            self.metrics.sliver_checksum_mismatches_total,
+            sliver_type,
+            "valid"
+        )
+        .inc();
+        shard_storage
+            .put_sliver(*blob_id, sliver.clone())
+            .await
+            .context("unable to store sliver")?;
+        Ok(sliver)
+    }
+
+    /// Emits a [`NodeEvent::BlobStored`] event if the node stores the blob at all of its shards.
+    ///
+    /// Concurrently storing the last slivers of a blob may emit the event more than once.
+    pub(crate) async fn emit_event_if_blob_stored(&self, blob_id: &BlobId) {
+        if !self.local_events.has_subscribers() {
+            return;
+        }
+        match self.is_stored_at_all_shards_at_latest_epoch(blob_id).await {
+            Ok(true) => self
+                .local_events
+                .emit(NodeEvent::BlobStored { blob_id: *blob_id }),
+            Ok(false) => (),
+            Err(error) => tracing::warn!(
+                ?error,
+                walrus.blob_id = %blob_id,
+                "failed to check whether the blob is fully stored"
+            ),
+        }
+    }
+
+    /// Writes the bytes served and stored since the last flush to the epoch accounting in the
+    /// database.
+    fn flush_epoch_accounting(&self) -> Result<(), TypedStoreError> {
+        self.accountant
+            .flush(&self.storage, self.current_epoch(), |epoch| {
+                // The shard assignment is only known for the current and previous epoch.
+                self.owned_shards_at_epoch(epoch).unwrap_or_default()
+            })
+    }
+
+    async fn flush_epoch_accounting_periodically(&self) {
+        let mut interval = tokio::time::interval(EPOCH_ACCOUNTING_FLUSH_INTERVAL);
+        loop {
+            interval.tick().await;
+            if let Err(error) = self.flush_epoch_accounting() {
+                tracing::warn!(
+                    ?error,
+                    "failed to write the epoch accounting, retrying later"
+                );
+            }
+        }
+    }
+
     async fn create_storage_for_shards_in_background(
         self: &Arc<Self>,
         new_shards: Vec<ShardIndex>,
@@ -2040,6 +2314,34 @@ impl StorageNodeInner {
             .is_some_and(|blob_info| blob_info.is_registered(self.current_epoch())))
     }
 
+    /// Determines whether the blob's metadata and slivers can be served to readers.
+    ///
+    /// Blobs are readable while registered and for `expired_blob_read_grace_epochs` epochs after
+    /// the end of their storage period.
+    fn blob_readability(&self, blob_id: &BlobId) -> Result<BlobReadability, anyhow::Error> {
+        let Some(blob_info) = self
+            .storage
+            .get_blob_info(blob_id)
+            .context("could not retrieve blob info")?
+        else {
+            return Ok(BlobReadability::Unregistered);
+        };
+
+        let current_epoch = self.current_epoch();
+        if blob_info.is_registered(current_epoch) {
+            return Ok(BlobReadability::Readable);
+        }
+
+        let Some(end_epoch) = blob_info.latest_end_epoch() else {
+            return Ok(BlobReadability::Unregistered);
+        };
+        if current_epoch < end_epoch.saturating_add(self.expired_blob_read_grace_epochs) {
+            Ok(BlobReadability::Readable)
+        } else {
+            Ok(BlobReadability::Expired(end_epoch))
+        }
+    }
+
     fn is_blob_certified(&self, blob_id: &BlobId) -> Result<bool, anyhow::Error> {
         Ok(self
             .storage
@@ -2161,14 +2463,20 @@ impl ServiceState for StorageNode {
         self.inner.metadata_status(blob_id)
     }
 
-    fn retrieve_sliver(
+    async fn retrieve_sliver(
         &self,
         blob_id: &BlobId,
         sliver_pair_index: SliverPairIndex,
         sliver_type: SliverType,
-    ) -> impl Future<Output = Result<Sliver, RetrieveSliverError>> + Send {
-        self.inner
+    ) -> Result<Sliver, RetrieveSliverError> {
+        let sliver = self
+            .inner
             .retrieve_sliver(blob_id, sliver_pair_index, sliver_type)
+            .await?;
+        self.inner
+            .accountant
+            .record_served(self.inner.current_epoch(), sliver.len());
+        Ok(sliver)
     }
 
     fn store_sliver(
@@ -2200,23 +2508,36 @@ impl ServiceState for StorageNode {
             .verify_inconsistency_proof(blob_id, inconsistency_proof)
     }
 
-    fn retrieve_recovery_symbol(
+    async fn retrieve_recovery_symbol(
         &self,
         blob_id: &BlobId,
         symbol_id: SymbolId,
         sliver_type: Option<SliverType>,
-    ) -> impl Future<Output = Result<GeneralRecoverySymbol, RetrieveSymbolError>> + Send {
-        self.inner
+    ) -> Result<GeneralRecoverySymbol, RetrieveSymbolError> {
+        let symbol = self
+            .inner
             .retrieve_recovery_symbol(blob_id, symbol_id, sliver_type)
+            .await?;
+        self.inner
+            .accountant
+            .record_served(self.inner.current_epoch(), symbol.data().len());
+        Ok(symbol)
     }
 
-    fn retrieve_multiple_recovery_symbols(
+    async fn retrieve_multiple_recovery_symbols(
         &self,
         blob_id: &BlobId,
         filter: RecoverySymbolsFilter,
-    ) -> impl Future<Output = Result<Vec<GeneralRecoverySymbol>, ListSymbolsError>> + Send {
-        self.inner
+    ) -> Result<Vec<GeneralRecoverySymbol>, ListSymbolsError> {
+        let symbols = self
+            .inner
             .retrieve_multiple_recovery_symbols(blob_id, filter)
+            .await?;
+        self.inner.accountant.record_served(
+            self.inner.current_epoch(),
+            symbols.iter().map(|symbol| symbol.data().len()).sum(),
+        );
+        Ok(symbols)
     }
 
     fn blob_status(&self, blob_id: &BlobId) -> Result<BlobStatus, BlobStatusError> {
@@ -2246,6 +2567,35 @@ impl ServiceState for StorageNode {
     ) -> impl Future<Output = Result<SyncShardResponse, SyncShardServiceError>> + Send {
         self.inner.sync_shard(public_key, signed_request)
     }
+
+    fn subscribe_to_local_events(&self) -> broadcast::Receiver<NodeEvent> {
+        self.inner.subscribe_to_local_events()
+    }
+
+    fn epoch_accounting_report(
+        &self,
+        epoch: Epoch,
+    ) -> impl Future<Output = Result<SignedEpochAccountingReport, EpochAccountingError>> + Send
+    {
+        self.inner.epoch_accounting_report(epoch)
+    }
+
+    fn shard_ownership_audit(&self) -> impl Future<Output = ShardOwnershipAudit> + Send {
+        self.inner.shard_ownership_audit()
+    }
+
+    fn epoch_handover(&self) -> Option<EpochHandoverInfo> {
+        self.inner.epoch_handover()
+    }
+
+    fn list_stored_blobs(
+        &self,
+        shard_index: ShardIndex,
+        cursor: Option<BlobId>,
+        limit: usize,
+    ) -> impl Future<Output = Result<StoredBlobsPage, ListStoredBlobsError>> + Send {
+        self.inner.list_stored_blobs(shard_index, cursor, limit)
+    }
 }
 
 impl ServiceState for StorageNodeInner {
@@ -2267,10 +2617,14 @@ impl ServiceState for StorageNodeInner {
 
         ensure!(!self.is_blocked(blob_id), RetrieveMetadataError::Forbidden);
 
-        ensure!(
-            self.is_blob_registered(blob_id)?,
-            RetrieveMetadataError::Unavailable,
-        );
+        match self.blob_readability(blob_id)? {
+            BlobReadability::Readable => (),
+            BlobReadability::Unregistered => return Err(RetrieveMetadataError::Unavailable),
+            BlobReadability::Expired(end_epoch) => {
+                self.metrics.expired_blob_reads_rejected_total.inc();
+                return Err(RetrieveMetadataError::Expired(end_epoch));
+            }
+        }
 
         self.storage
             .get_metadata(blob_id)
@@ -2283,6 +2637,15 @@ impl ServiceState for StorageNodeInner {
         &self,
         metadata: UnverifiedBlobMetadataWithId,
     ) -> Result<bool, StoreMetadataError> {
+        ensure!(
+            !self.rejects_blob_data(metadata.blob_id()),
+            StoreMetadataError::Forbidden
+        );
+        let _permit = self
+            .request_scheduler
+            .acquire(RequestClass::UserWrite)
+            .await;
+
         let Some(blob_info) = self
             .storage
             .get_blob_info(metadata.blob_id())
@@ -2351,24 +2714,34 @@ impl ServiceState for StorageNodeInner {
         self.check_index(sliver_pair_index)?;
 
         ensure!(!self.is_blocked(blob_id), RetrieveSliverError::Forbidden);
-
-        ensure!(
-            self.is_blob_registered(blob_id)?,
-            RetrieveSliverError::Unavailable,
-        );
+        let _permit = self.request_scheduler.acquire(RequestClass::UserRead).await;
+
+        match self.blob_readability(blob_id)? {
+            BlobReadability::Readable => (),
+            BlobReadability::Unregistered => return Err(RetrieveSliverError::Unavailable),
+            BlobReadability::Expired(end_epoch) => {
+                self.metrics.expired_blob_reads_rejected_total.inc();
+                return Err(RetrieveSliverError::Expired(end_epoch));
+            }
+        }
 
         let shard_storage = self
             .get_shard_for_sliver_pair(sliver_pair_index, blob_id)
             .await?;
 
-        shard_storage
-            .get_sliver(blob_id, sliver_type)
+        let (sliver, checksum_status) = shard_storage
+            .get_sliver_with_checksum_status(blob_id, sliver_type)
             .context("unable to retrieve sliver")?
-            .ok_or(RetrieveSliverError::Unavailable)
-            .inspect(|sliver| {
-                walrus_utils::with_label!(self.metrics.slivers_retrieved_total, sliver.r#type())
-                    .inc();
-            })
+            .ok_or(RetrieveSliverError::Unavailable)?;
+        let sliver = if checksum_status == SliverChecksumStatus::Mismatch {
+            self.verify_sliver_with_checksum_mismatch(&shard_storage, blob_id, sliver)
+                .await?
+        } else {
+            sliver
+        };
+
+        walrus_utils::with_label!(self.metrics.slivers_retrieved_total, sliver.r#type()).inc();
+        Ok(sliver)
     }
 
     async fn store_sliver(
@@ -2379,6 +2752,14 @@ impl ServiceState for StorageNodeInner {
     ) -> Result<bool, StoreSliverError> {
         self.check_index(sliver_pair_index)?;
 
+        ensure!(
+            !self.rejects_blob_data(&blob_id),
+            StoreSliverError::Forbidden
+        );
+        let _permit = self
+            .request_scheduler
+            .acquire(RequestClass::UserWrite)
+            .await;
         ensure!(
             self.is_blob_registered(&blob_id)?,
             StoreSliverError::NotCurrentlyRegistered,
@@ -2456,12 +2837,39 @@ impl ServiceState for StorageNodeInner {
         blob_id: &BlobId,
         inconsistency_proof: InconsistencyProof,
     ) -> Result<InvalidBlobIdAttestation, InconsistencyProofError> {
-        let metadata = self.retrieve_metadata(blob_id)?;
+        let epoch = self.current_epoch();
+        let attestation_cell = self
+            .invalid_blob_attestations
+            .get_with((epoch, *blob_id), async { Arc::default() })
+            .await;
+        if let Some(attestation) = attestation_cell.get() {
+            self.metrics.invalid_blob_attestations_cached_total.inc();
+            return Ok(attestation.clone());
+        }
+
+        // If the verification of a concurrently submitted proof fails, the next waiting proof is
+        // verified instead.
+        let attestation = attestation_cell
+            .get_or_try_init(|| async {
+                let metadata = self.retrieve_metadata(blob_id)?;
+                let encoding_config = self.encoding_config.clone();
+                self.thread_pool
+                    .clone()
+                    .oneshot(move || {
+                        inconsistency_proof.verify(metadata.as_ref(), &encoding_config)
+                    })
+                    .map(thread_pool::unwrap_or_resume_panic)
+                    .await?;
+                self.metrics.inconsistency_proofs_verified_total.inc();
 
-        inconsistency_proof.verify(metadata.as_ref(), &self.encoding_config)?;
+                let message = InvalidBlobIdMsg::new(epoch, blob_id.to_owned());
+                Ok::<_, InconsistencyProofError>(
+                    sign_message(message, self.protocol_key_pair.clone()).await?,
+                )
+            })
+            .await?;
 
-        let message = InvalidBlobIdMsg::new(self.current_epoch(), blob_id.to_owned());
-        Ok(sign_message(message, self.protocol_key_pair.clone()).await?)
+        Ok(attestation.clone())
     }
 
     #[tracing::instrument(skip(self))]
@@ -2471,6 +2879,8 @@ impl ServiceState for StorageNodeInner {
         symbol_id: SymbolId,
         sliver_type: Option<SliverType>,
     ) -> Result<GeneralRecoverySymbol, RetrieveSymbolError> {
+        // Recovery symbols are served to other nodes recovering slivers.
+        let _permit = self.request_scheduler.acquire(RequestClass::Sync).await;
         let n_shards = self.n_shards();
 
         let primary_index = symbol_id.primary_sliver_index();
@@ -2665,9 +3075,93 @@ impl ServiceState for StorageNodeInner {
             .into());
         }
 
-        self.storage
+        let _permit = self.request_scheduler.acquire(RequestClass::Sync).await;
+        let response = self
+            .storage
             .handle_sync_shard_request(request, self.current_epoch())
+            .await?;
+        let SyncShardResponse::V1(slivers) = &response;
+        self.accountant.record_served(
+            self.current_epoch(),
+            slivers.iter().map(|(_, sliver)| sliver.len()).sum(),
+        );
+
+        Ok(response)
+    }
+
+    fn subscribe_to_local_events(&self) -> broadcast::Receiver<NodeEvent> {
+        self.local_events.subscribe()
+    }
+
+    async fn epoch_accounting_report(
+        &self,
+        epoch: Epoch,
+    ) -> Result<SignedEpochAccountingReport, EpochAccountingError> {
+        self.flush_epoch_accounting()?;
+        let report = self
+            .storage
+            .get_epoch_accounting(epoch)?
+            .ok_or(EpochAccountingError::NotFound(epoch))?;
+
+        let message = EpochAccountingMsg::new(self.current_epoch(), report);
+        Ok(sign_message(message, self.protocol_key_pair.clone()).await?)
+    }
+
+    async fn shard_ownership_audit(&self) -> ShardOwnershipAudit {
+        let shard_statuses = self.storage.list_shard_status().await;
+        let committees = self.committee_service.active_committees();
+        let public_key = self.public_key();
+
+        // Shards gained in the next epoch may already be synced, and shards lost in the current
+        // epoch change may still be transferred to their new owners.
+        let mut retained = committees
+            .next_committee()
+            .map(|committee| committee.shards_for_node_public_key(public_key).to_vec())
+            .unwrap_or_default();
+        if committees.is_change_in_progress() {
+            if let Some(previous) = committees.previous_committee() {
+                retained.extend_from_slice(previous.shards_for_node_public_key(public_key));
+            }
+        }
+
+        let current = committees.current_committee();
+        shard_audit::audit_shard_ownership(
+            current.epoch,
+            current.shards_for_node_public_key(public_key),
+            &retained,
+            shard_statuses,
+        )
+    }
+
+    fn epoch_handover(&self) -> Option<EpochHandoverInfo> {
+        self.epoch_handover.info()
+    }
+
+    async fn list_stored_blobs(
+        &self,
+        shard_index: ShardIndex,
+        cursor: Option<BlobId>,
+        limit: usize,
+    ) -> Result<StoredBlobsPage, ListStoredBlobsError> {
+        let shard_storage = self
+            .storage
+            .shard_storage(shard_index)
             .await
+            .ok_or(ShardNotAssigned(shard_index, self.current_epoch()))?;
+
+        // Fetch one more blob ID than requested to determine whether there is a next page.
+        let mut blob_ids = shard_storage.list_blob_ids(cursor, limit.saturating_add(1))?;
+        let next_cursor = if blob_ids.len() > limit {
+            blob_ids.truncate(limit);
+            blob_ids.last().copied()
+        } else {
+            None
+        };
+
+        Ok(StoredBlobsPage {
+            blob_ids,
+            next_cursor,
+        })
     }
 }
 
@@ -2693,11 +3187,12 @@ where
 
 #[cfg(test)]
 mod tests {
-    use std::{sync::OnceLock, time::Duration};
+    use std::{collections::HashSet, sync::OnceLock, time::Duration};
 
     use chrono::Utc;
     use config::ShardSyncConfig;
     use contract_service::MockSystemContractService;
+    use futures::TryStreamExt as _;
     use storage::{
         tests::{populated_storage, WhichSlivers, BLOB_ID, OTHER_SHARD_INDEX, SHARD_INDEX},
         ShardStatus,
@@ -2712,7 +3207,10 @@ mod tests {
         DEFAULT_ENCODING,
     };
     use walrus_proc_macros::walrus_simtest;
-    use walrus_rest_client::{api::errors::STORAGE_NODE_ERROR_DOMAIN, client::Client};
+    use walrus_rest_client::{
+        api::{errors::STORAGE_NODE_ERROR_DOMAIN, BatchedRecoverySymbolsRequest},
+        client::Client,
+    };
     use walrus_sui::{
         client::FixedSystemParameters,
         test_utils::{event_id_for_testing, EventForTesting},
@@ -3190,6 +3688,96 @@ mod tests {
         Ok(node)
     }
 
+    #[tokio::test]
+    async fn deletes_data_of_blocked_blobs() -> TestResult {
+        let (_ec, metadata, _idx, _rs) = generate_config_metadata_and_valid_recovery_symbols()?;
+        let blob_id = *metadata.blob_id();
+        let blocklist_file = tempfile::NamedTempFile::new()?;
+        serde_yaml::to_writer(blocklist_file.as_file(), &[blob_id.to_string()])?;
+
+        let node = StorageNodeHandle::builder()
+            .with_system_event_provider(vec![BlobRegistered::for_testing(blob_id).into()])
+            .with_shard_assignment(&[ShardIndex(0)])
+            .with_blocklist_file(Some(blocklist_file.path().to_path_buf()))
+            .with_node_started(true)
+            .build()
+            .await?;
+        tokio::time::sleep(Duration::from_millis(50)).await;
+
+        // Without deleting the data of blocked blobs, the metadata is stored but not served.
+        let inner = &node.storage_node.inner;
+        inner.store_metadata(metadata.into_unverified()).await?;
+        assert!(inner.storage.has_metadata(&blob_id)?);
+        assert!(matches!(
+            inner.retrieve_metadata(&blob_id),
+            Err(RetrieveMetadataError::Forbidden)
+        ));
+
+        let enforcer =
+            BlocklistEnforcer::new(inner.clone(), node.storage_node.blob_sync_handler.clone());
+        let mut deleted = HashSet::new();
+        enforcer.enforce(&mut deleted).await;
+
+        assert!(!inner.storage.has_metadata(&blob_id)?);
+        assert_eq!(deleted, HashSet::from([blob_id]));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn reports_and_deletes_expired_blobs() -> TestResult {
+        let end_epoch = BlobCertified::for_testing(BLOB_ID).end_epoch;
+        let node = StorageNodeHandle::builder()
+            .with_storage(
+                populated_storage(&[
+                    (SHARD_INDEX, vec![(BLOB_ID, WhichSlivers::Both)]),
+                    (OTHER_SHARD_INDEX, vec![(BLOB_ID, WhichSlivers::Both)]),
+                ])
+                .await?,
+            )
+            .with_system_event_provider(vec![
+                BlobRegistered::for_testing(BLOB_ID).into(),
+                BlobCertified::for_testing(BLOB_ID).into(),
+            ])
+            .with_node_started(true)
+            .build()
+            .await?;
+        tokio::time::sleep(Duration::from_millis(50)).await;
+
+        let inner = &node.storage_node.inner;
+        let mut events = inner.subscribe_to_local_events();
+
+        // The blob is still registered in the epoch before its end epoch.
+        let n_expired =
+            garbage_collection::process_expired_blobs(inner, end_epoch - 1, true).await?;
+        assert_eq!(n_expired, 0);
+        assert!(
+            inner
+                .is_stored_at_all_shards_at_latest_epoch(&BLOB_ID)
+                .await?
+        );
+
+        let n_expired = garbage_collection::process_expired_blobs(inner, end_epoch, true).await?;
+        assert_eq!(n_expired, 1);
+        assert!(
+            !inner
+                .is_stored_at_all_shards_at_latest_epoch(&BLOB_ID)
+                .await?
+        );
+
+        let received: Vec<_> = std::iter::from_fn(|| events.try_recv().ok()).collect();
+        assert!(received.contains(&NodeEvent::BlobExpired {
+            blob_id: BLOB_ID,
+            epoch: end_epoch,
+        }));
+        assert!(received.contains(&NodeEvent::BlobDataDeleted {
+            blob_id: BLOB_ID,
+            reason: BlobDataDeletionReason::Expired,
+        }));
+
+        Ok(())
+    }
+
     mod inconsistency_proof {
 
         use fastcrypto::traits::VerifyingKey;
@@ -3233,7 +3821,7 @@ mod tests {
 
             // Change metadata
             let mut metadata = metadata.metadata().to_owned();
-            metadata.mut_inner().hashes[0].primary_hash = Node::Digest([0; 32]);
+            metadata.hashes_mut()[0].primary_hash = Node::Digest([0; 32]);
             let blob_id = BlobId::from_sliver_pair_metadata(&metadata);
             let metadata = UnverifiedBlobMetadataWithId::new(blob_id, metadata);
 
@@ -3270,6 +3858,38 @@ mod tests {
 
             Ok(())
         }
+
+        #[tokio::test]
+        async fn verifies_repeated_proofs_for_a_blob_only_once() -> TestResult {
+            let (_encoding_config, metadata, index, recovery_symbols) =
+                generate_config_metadata_and_valid_recovery_symbols()?;
+
+            let mut metadata = metadata.metadata().to_owned();
+            metadata.hashes_mut()[0].primary_hash = Node::Digest([0; 32]);
+            let blob_id = BlobId::from_sliver_pair_metadata(&metadata);
+            let metadata = UnverifiedBlobMetadataWithId::new(blob_id, metadata);
+
+            let node = set_up_node_with_metadata(metadata).await?;
+
+            let mut attestations = vec![];
+            for _ in 0..3 {
+                let inconsistency_proof = InconsistencyProof::Primary(
+                    PrimaryInconsistencyProof::new(index, recovery_symbols.clone()),
+                );
+                attestations.push(
+                    node.as_ref()
+                        .verify_inconsistency_proof(&blob_id, inconsistency_proof)
+                        .await?,
+                );
+            }
+
+            assert!(attestations.windows(2).all(|pair| pair[0] == pair[1]));
+            let metrics = &node.as_ref().inner.metrics;
+            assert_eq!(metrics.inconsistency_proofs_verified_total.get(), 1);
+            assert_eq!(metrics.invalid_blob_attestations_cached_total.get(), 2);
+
+            Ok(())
+        }
     }
 
     #[derive(Debug)]
@@ -3581,6 +4201,65 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn streams_verified_sliver() -> TestResult {
+        let shards: &[&[u16]] = &[&[0, 1, 2, 3, 4, 5], &[6]];
+
+        let (cluster, _events, blob) =
+            cluster_with_partially_stored_blob(shards, BLOB, |_, _| true).await?;
+        let pair = blob.assigned_sliver_pair(ShardIndex(0));
+
+        let chunks: Vec<_> = cluster
+            .client(0)
+            .stream_verified_sliver::<Primary>(
+                pair.index(),
+                Arc::new(blob.metadata.clone()),
+                Arc::new(blob.config.clone()),
+            )
+            .try_collect()
+            .await?;
+
+        assert_eq!(chunks.concat(), pair.primary.symbols.data().as_slice());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn checks_each_symbol_of_recovery_symbols_batch() -> TestResult {
+        let shards: &[&[u16]] = &[&[0, 1, 2, 3, 4, 5], &[6]];
+
+        let (cluster, _events, blob) =
+            cluster_with_partially_stored_blob(shards, BLOB, |_, _| true).await?;
+        let target_sliver = blob
+            .assigned_sliver_pair(ShardIndex(6))
+            .index()
+            .to_sliver_index::<Primary>(blob.config.n_shards());
+        let filter = RecoverySymbolsFilter::recovers(target_sliver, SliverType::Primary);
+        let request = BatchedRecoverySymbolsRequest::new(
+            *blob.blob_id(),
+            &filter,
+            target_sliver,
+            SliverType::Primary,
+        );
+
+        let results = cluster
+            .client(0)
+            .list_and_check_recovery_symbols_batch(
+                vec![(request, Arc::new(blob.metadata.clone()))],
+                Arc::new(blob.config.clone()),
+            )
+            .await?;
+
+        let [symbols] = results.as_slice() else {
+            panic!("expected a single list of symbols, got {}", results.len());
+        };
+        let symbols = symbols.as_ref().expect("the request specifies symbols");
+        assert!(!symbols.is_empty());
+        assert!(symbols.iter().all(|symbol| symbol.is_verified()));
+
+        Ok(())
+    }
+
     async_param_test! {
         recovers_sliver_from_other_nodes_on_certified_blob_event -> TestResult: [
             primary: (SliverType::Primary),
@@ -3673,6 +4352,50 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn rejects_reads_for_expired_blob() -> TestResult {
+        let shards: &[&[u16]] = &[&[1], &[0, 2, 3, 4]];
+
+        let (cluster, events) = cluster_at_epoch1_without_blobs(shards, None).await?;
+        let node = &cluster.nodes[0];
+
+        // Register a blob whose storage period already ended.
+        events.send(
+            BlobRegistered {
+                end_epoch: 1,
+                ..BlobRegistered::for_testing(BLOB_ID)
+            }
+            .into(),
+        )?;
+
+        retry_until_success_or_timeout(TIMEOUT, || async {
+            node.storage_node
+                .inner
+                .storage
+                .get_blob_info(&BLOB_ID)?
+                .ok_or(anyhow!("blob info not updated"))
+        })
+        .await?;
+
+        let inner = &node.storage_node.inner;
+        assert!(matches!(
+            inner.retrieve_metadata(&BLOB_ID),
+            Err(RetrieveMetadataError::Expired(1))
+        ));
+        assert!(matches!(
+            inner
+                .retrieve_sliver(&BLOB_ID, SliverPairIndex(0), SliverType::Primary)
+                .await,
+            Err(RetrieveSliverError::Expired(1))
+        ));
+        assert!(matches!(
+            inner.retrieve_metadata(&OTHER_BLOB_ID),
+            Err(RetrieveMetadataError::Unavailable)
+        ));
+
+        Ok(())
+    }
+
     // Tests that a panic thrown by a blob sync task is propagated to the node runtime.
     #[tokio::test]
     async fn blob_sync_panic_thrown() {
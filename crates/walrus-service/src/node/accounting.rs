@@ -0,0 +1,240 @@
+// Copyright (c) Walrus Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Per-epoch accounting of the sliver data served and stored by the node.
+//!
+//! The served and stored bytes are accumulated in memory and periodically merged into the
+//! reports in the node's database, from which operators can retrieve signed reports to reconcile
+//! rewards and plan capacity.
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::Mutex,
+};
+
+use typed_store::TypedStoreError;
+use walrus_core::{
+    messages::{EpochAccountingReport, ShardOccupancy},
+    Epoch,
+    ShardIndex,
+};
+
+use super::storage::Storage;
+
+/// Accumulates the bytes served and stored by the node until they are written to the database.
+#[derive(Debug, Default)]
+pub(crate) struct EpochAccountant {
+    pending: Mutex<HashMap<Epoch, PendingAccounting>>,
+    /// Held for the duration of a flush, so that concurrent flushes do not read and overwrite the
+    /// same report in the database, losing the bytes merged by one of them.
+    flush_lock: Mutex<()>,
+}
+
+#[derive(Debug, Default)]
+struct PendingAccounting {
+    bytes_served: u64,
+    bytes_stored: BTreeMap<ShardIndex, u64>,
+}
+
+impl EpochAccountant {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records that `n_bytes` of sliver data were served in `epoch`.
+    pub fn record_served(&self, epoch: Epoch, n_bytes: usize) {
+        let mut pending = self.pending.lock().expect("mutex should not be poisoned");
+        let accounting = pending.entry(epoch).or_default();
+        accounting.bytes_served += to_u64(n_bytes);
+    }
+
+    /// Records that `n_bytes` of sliver data were stored for `shard` in `epoch`.
+    pub fn record_stored(&self, epoch: Epoch, shard: ShardIndex, n_bytes: usize) {
+        let mut pending = self.pending.lock().expect("mutex should not be poisoned");
+        let accounting = pending.entry(epoch).or_default();
+        *accounting.bytes_stored.entry(shard).or_default() += to_u64(n_bytes);
+    }
+
+    /// Merges the accumulated bytes into the reports in `storage`.
+    ///
+    /// A report is written for `current_epoch` even if nothing was recorded for it. The shards
+    /// returned by `owned_shards` for an epoch are included in the report of the epoch even if no
+    /// data was stored for them.
+    ///
+    /// On failure, the accumulated bytes that were not written are retained for the next flush.
+    /// Concurrent flushes are serialized; bytes recorded during a flush are retained for the next
+    /// one.
+    pub fn flush<F>(
+        &self,
+        storage: &Storage,
+        current_epoch: Epoch,
+        owned_shards: F,
+    ) -> Result<(), TypedStoreError>
+    where
+        F: Fn(Epoch) -> Vec<ShardIndex>,
+    {
+        let _flush_guard = self
+            .flush_lock
+            .lock()
+            .expect("mutex should not be poisoned");
+        let mut pending =
+            std::mem::take(&mut *self.pending.lock().expect("mutex should not be poisoned"));
+        pending.entry(current_epoch).or_default();
+
+        let mut epochs: Vec<_> = pending.keys().copied().collect();
+        epochs.sort_unstable();
+
+        for epoch in epochs {
+            let accounting = pending.remove(&epoch).expect("epoch is pending");
+            let result = storage.get_epoch_accounting(epoch).and_then(|report| {
+                let report = accounting.merge_into(
+                    report.unwrap_or_else(|| EpochAccountingReport {
+                        epoch,
+                        ..Default::default()
+                    }),
+                    owned_shards(epoch),
+                );
+                storage.put_epoch_accounting(&report)
+            });
+
+            if let Err(error) = result {
+                pending.insert(epoch, accounting);
+                self.restore(pending);
+                return Err(error);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Adds the accumulated bytes that could not be written back to the pending bytes.
+    fn restore(&self, unwritten: HashMap<Epoch, PendingAccounting>) {
+        let mut pending = self.pending.lock().expect("mutex should not be poisoned");
+        for (epoch, accounting) in unwritten {
+            let current = pending.entry(epoch).or_default();
+            current.bytes_served += accounting.bytes_served;
+            for (shard, bytes_stored) in accounting.bytes_stored {
+                *current.bytes_stored.entry(shard).or_default() += bytes_stored;
+            }
+        }
+    }
+}
+
+impl PendingAccounting {
+    fn merge_into(
+        self,
+        mut report: EpochAccountingReport,
+        owned_shards: Vec<ShardIndex>,
+    ) -> EpochAccountingReport {
+        let mut shards: BTreeMap<_, _> = report
+            .shards
+            .iter()
+            .map(|occupancy| (occupancy.shard, occupancy.bytes_stored))
+            .collect();
+        for shard in owned_shards {
+            shards.entry(shard).or_default();
+        }
+        for (shard, bytes_stored) in self.bytes_stored {
+            *shards.entry(shard).or_default() += bytes_stored;
+        }
+
+        report.bytes_served += self.bytes_served;
+        report.bytes_stored = shards.values().sum();
+        report.shards = shards
+            .into_iter()
+            .map(|(shard, bytes_stored)| ShardOccupancy {
+                shard,
+                bytes_stored,
+            })
+            .collect();
+        report
+    }
+}
+
+fn to_u64(n_bytes: usize) -> u64 {
+    u64::try_from(n_bytes).expect("usize fits into a u64")
+}
+
+#[cfg(test)]
+mod tests {
+    use walrus_test_utils::Result as TestResult;
+
+    use super::*;
+    use crate::node::storage::tests::empty_storage;
+
+    #[tokio::test]
+    async fn merges_recorded_bytes_into_stored_reports() -> TestResult {
+        let storage = empty_storage().await;
+        let storage = storage.as_ref();
+        let accountant = EpochAccountant::new();
+        let owned_shards = |_: Epoch| vec![ShardIndex(1), ShardIndex(2)];
+
+        accountant.record_served(3, 10);
+        accountant.record_stored(3, ShardIndex(1), 5);
+        accountant.flush(storage, 3, owned_shards)?;
+
+        accountant.record_served(3, 1);
+        accountant.record_stored(3, ShardIndex(1), 2);
+        accountant.record_stored(4, ShardIndex(2), 7);
+        accountant.flush(storage, 4, owned_shards)?;
+
+        assert_eq!(
+            storage.get_epoch_accounting(3)?,
+            Some(EpochAccountingReport {
+                epoch: 3,
+                bytes_served: 11,
+                bytes_stored: 7,
+                shards: vec![
+                    ShardOccupancy {
+                        shard: ShardIndex(1),
+                        bytes_stored: 7,
+                    },
+                    ShardOccupancy {
+                        shard: ShardIndex(2),
+                        bytes_stored: 0,
+                    },
+                ],
+            })
+        );
+        assert_eq!(
+            storage
+                .get_epoch_accounting(4)?
+                .map(|report| report.bytes_stored),
+            Some(7)
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn concurrent_flushes_do_not_lose_bytes() -> TestResult {
+        const N_THREADS: usize = 4;
+        const N_RECORDS: usize = 100;
+
+        let storage = empty_storage().await;
+        let storage = storage.as_ref();
+        let accountant = EpochAccountant::new();
+
+        std::thread::scope(|scope| {
+            for _ in 0..N_THREADS {
+                scope.spawn(|| {
+                    for _ in 0..N_RECORDS {
+                        accountant.record_served(1, 1);
+                        accountant
+                            .flush(storage, 1, |_| vec![])
+                            .expect("flushing to the database succeeds");
+                    }
+                });
+            }
+        });
+
+        assert_eq!(
+            storage
+                .get_epoch_accounting(1)?
+                .map(|report| report.bytes_served),
+            Some(u64::try_from(N_THREADS * N_RECORDS)?)
+        );
+
+        Ok(())
+    }
+}
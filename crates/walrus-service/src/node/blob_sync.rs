@@ -344,6 +344,7 @@ impl BlobSyncHandler {
                 let decrement_guard = GaugeGuard::acquire(&in_progress_gauge);
 
                 synchronizer.run(permits.sliver_pairs).await;
+                self.node.emit_event_if_blob_stored(&blob_id).await;
 
                 decrement_guard
             } => {
@@ -0,0 +1,134 @@
+// Copyright (c) Walrus Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Deletion of the locally stored data of blobs on the node's blocklist.
+//!
+//! Changes to the blocklist and the resulting deletions are logged with the target
+//! [`BLOCKLIST_AUDIT_TARGET`], so that they can be routed to a separate audit log.
+
+use std::{collections::HashSet, sync::Arc, time::Duration};
+
+use tokio::time;
+use walrus_core::BlobId;
+use walrus_rest_client::api::{BlobDataDeletionReason, NodeEvent};
+
+use super::{
+    blob_sync::BlobSyncHandler,
+    storage::blob_info::CertifiedBlobInfoApi,
+    StorageNodeInner,
+};
+
+/// The tracing target of the audit log entries for the blocklist.
+pub const BLOCKLIST_AUDIT_TARGET: &str = "walrus::blocklist_audit";
+
+/// The interval at which the blocklist is checked for changes.
+const ENFORCEMENT_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Deletes the locally stored data of blobs when they are added to the blocklist, and recovers
+/// it when they are removed again.
+#[derive(Debug)]
+pub(crate) struct BlocklistEnforcer {
+    node: Arc<StorageNodeInner>,
+    blob_sync_handler: Arc<BlobSyncHandler>,
+}
+
+impl BlocklistEnforcer {
+    pub fn new(node: Arc<StorageNodeInner>, blob_sync_handler: Arc<BlobSyncHandler>) -> Self {
+        Self {
+            node,
+            blob_sync_handler,
+        }
+    }
+
+    /// Periodically enforces the blocklist on the locally stored data.
+    ///
+    /// On the first iteration, the data of all blocked blobs is deleted, which covers blobs that
+    /// were added to the blocklist while the node was not running.
+    pub async fn run(&self) {
+        let mut deleted = HashSet::new();
+        let mut interval = time::interval(ENFORCEMENT_INTERVAL);
+        loop {
+            interval.tick().await;
+            self.enforce(&mut deleted).await;
+        }
+    }
+
+    /// Deletes the data of blocked blobs and starts the recovery of blobs in `deleted` that are
+    /// no longer blocked; `deleted` tracks the blobs whose data has been deleted.
+    ///
+    /// The data of blobs in `deleted` is deleted again if their metadata was stored in the
+    /// meantime, for example, through shard sync.
+    pub async fn enforce(&self, deleted: &mut HashSet<BlobId>) {
+        let blocked = self.node.blocklist.blocked_blob_ids();
+
+        let unblocked: Vec<_> = deleted.difference(&blocked).copied().collect();
+        for blob_id in unblocked {
+            deleted.remove(&blob_id);
+            tracing::info!(
+                target: BLOCKLIST_AUDIT_TARGET,
+                walrus.blob_id = %blob_id,
+                "blob was removed from the blocklist, the node stores and serves its data again"
+            );
+            if let Err(error) = self.start_recovery(blob_id).await {
+                tracing::warn!(
+                    ?error,
+                    walrus.blob_id = %blob_id,
+                    "failed to start the recovery of a blob removed from the blocklist"
+                );
+            }
+        }
+
+        for blob_id in blocked {
+            if deleted.contains(&blob_id)
+                && !self.node.storage.has_metadata(&blob_id).unwrap_or(true)
+            {
+                continue;
+            }
+            match self.delete_blob_data(&blob_id).await {
+                Ok(()) => {
+                    tracing::info!(
+                        target: BLOCKLIST_AUDIT_TARGET,
+                        walrus.blob_id = %blob_id,
+                        "deleted the locally stored data of a blocked blob"
+                    );
+                    self.node.metrics.blocked_blob_data_deleted_total.inc();
+                    self.node.local_events.emit(NodeEvent::BlobDataDeleted {
+                        blob_id,
+                        reason: BlobDataDeletionReason::Blocked,
+                    });
+                    deleted.insert(blob_id);
+                }
+                Err(error) => tracing::warn!(
+                    ?error,
+                    walrus.blob_id = %blob_id,
+                    "failed to delete the data of a blocked blob, retrying later"
+                ),
+            }
+        }
+    }
+
+    async fn delete_blob_data(&self, blob_id: &BlobId) -> anyhow::Result<()> {
+        self.blob_sync_handler
+            .cancel_sync_and_mark_event_complete(blob_id)
+            .await?;
+        self.node.storage.delete_blob_data(blob_id).await?;
+        Ok(())
+    }
+
+    async fn start_recovery(&self, blob_id: BlobId) -> anyhow::Result<()> {
+        let current_epoch = self.node.current_epoch();
+        let Some(certified_epoch) = self
+            .node
+            .storage
+            .get_blob_info(&blob_id)?
+            .filter(|blob_info| blob_info.is_certified(current_epoch))
+            .and_then(|blob_info| blob_info.initial_certified_epoch())
+        else {
+            return Ok(());
+        };
+        self.blob_sync_handler
+            .start_sync(blob_id, certified_epoch, None)
+            .await?;
+        Ok(())
+    }
+}
@@ -33,6 +33,7 @@ use self::node_service::NodeService;
 mod committee_service;
 mod node_service;
 mod request_futures;
+mod symbol_batcher;
 
 pub(crate) use self::{
     committee_service::NodeCommitteeService,
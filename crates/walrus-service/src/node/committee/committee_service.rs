@@ -47,6 +47,7 @@ use super::{
         LegacyRecoverSliver,
         RecoverSliver,
     },
+    symbol_batcher::RecoverySymbolBatcher,
     BeginCommitteeChangeError,
     CommitteeLookupService,
     CommitteeService,
@@ -386,6 +387,8 @@ pub(super) struct NodeCommitteeServiceInner<T> {
     pub encoding_config: Arc<EncodingConfig>,
     /// Shared randomness.
     pub rng: SyncMutex<StdRng>,
+    /// Combines requests for recovery symbols to the same node.
+    pub symbol_batcher: RecoverySymbolBatcher,
     /// The identity of the local storage node within and across committees.
     local_identity: Option<PublicKey>,
     /// Function used to construct new services.
@@ -416,6 +419,10 @@ where
         .await?;
 
         let this = Self {
+            symbol_batcher: RecoverySymbolBatcher::new(
+                config.symbol_request_batch_window,
+                config.max_symbol_requests_per_batch,
+            ),
             committee_tracker: watch::Sender::new(committee_tracker),
             services: SyncMutex::new(services),
             service_factory: TokioMutex::new(service_factory),
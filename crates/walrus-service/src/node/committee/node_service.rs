@@ -39,8 +39,10 @@ use walrus_core::{
     SliverIndex,
     SliverPairIndex,
     SliverType,
+    SymbolId,
 };
 use walrus_rest_client::{
+    api::{ApiFeature, BatchedRecoverySymbolsRequest},
     client::{Client, RecoverySymbolsFilter},
     error::{ClientBuildError, NodeError},
 };
@@ -75,10 +77,41 @@ pub(crate) enum Request {
     },
     ListVerifiedRecoverySymbols {
         filter: RecoverySymbolsFilter,
+        /// The IDs of the symbols matched by the filter, used to request the symbols individually
+        /// from nodes that do not support listing symbols.
+        symbol_ids: Vec<SymbolId>,
         metadata: Arc<VerifiedBlobMetadataWithId>,
         target_index: SliverIndex,
         target_type: SliverType,
     },
+    /// Lists the recovery symbols of multiple blobs, with a single request to nodes supporting
+    /// [`ApiFeature::BatchRecoverySymbols`].
+    ListVerifiedRecoverySymbolsBatch(Vec<RecoverySymbolsRequest>),
+}
+
+/// A request for the recovery symbols of a single blob, which is sent either on its own as a
+/// [`Request::ListVerifiedRecoverySymbols`] or as part of a
+/// [`Request::ListVerifiedRecoverySymbolsBatch`].
+#[derive(Debug, Clone)]
+pub(crate) struct RecoverySymbolsRequest {
+    pub filter: RecoverySymbolsFilter,
+    /// The IDs of the symbols matched by the filter.
+    pub symbol_ids: Vec<SymbolId>,
+    pub metadata: Arc<VerifiedBlobMetadataWithId>,
+    pub target_index: SliverIndex,
+    pub target_type: SliverType,
+}
+
+impl From<RecoverySymbolsRequest> for Request {
+    fn from(request: RecoverySymbolsRequest) -> Self {
+        Request::ListVerifiedRecoverySymbols {
+            filter: request.filter,
+            symbol_ids: request.symbol_ids,
+            metadata: request.metadata,
+            target_index: request.target_index,
+            target_type: request.target_type,
+        }
+    }
 }
 
 /// Responses to [`Request`]s sent to a node service.
@@ -92,6 +125,7 @@ pub(crate) enum Response {
     InvalidBlobAttestation(InvalidBlobIdAttestation),
     ShardSlivers(Vec<(BlobId, Sliver)>),
     VerifiedRecoverySymbols(Vec<GeneralRecoverySymbol>),
+    VerifiedRecoverySymbolsBatch(Vec<Result<Vec<GeneralRecoverySymbol>, NodeError>>),
 }
 
 impl Response {
@@ -141,6 +175,10 @@ impl_response_conversion!(
     Vec<GeneralRecoverySymbol>,
     Response::VerifiedRecoverySymbols
 );
+impl_response_conversion!(
+    Vec<Result<Vec<GeneralRecoverySymbol>, NodeError>>,
+    Response::VerifiedRecoverySymbolsBatch
+);
 impl_response_conversion!(InvalidBlobIdAttestation, Response::InvalidBlobAttestation);
 impl_response_conversion!(Vec<(BlobId, Sliver)>, Response::ShardSlivers);
 
@@ -157,14 +195,14 @@ pub(crate) enum NodeServiceError {
 /// used for communication with the committee.
 pub(crate) trait NodeService
 where
-    Self: Send + Clone,
+    Self: Send + Clone + 'static,
     Self: Service<Request, Response = Response, Error = NodeServiceError, Future: Send>,
 {
 }
 
 impl<T> NodeService for T
 where
-    T: Send + Clone,
+    T: Send + Clone + 'static,
     T: Service<Request, Response = Response, Error = NodeServiceError, Future: Send>,
 {
 }
@@ -274,19 +312,27 @@ impl Service<Request> for RemoteStorageNode {
 
                 Request::ListVerifiedRecoverySymbols {
                     filter,
+                    symbol_ids,
                     metadata,
                     target_index,
                     target_type,
-                } => client
-                    .list_and_verify_recovery_symbols(
-                        filter,
-                        metadata.clone(),
-                        encoding_config.clone(),
-                        target_index,
-                        target_type,
-                    )
-                    .await
-                    .map(Response::VerifiedRecoverySymbols)?,
+                } => list_verified_recovery_symbols(
+                    &client,
+                    filter,
+                    &symbol_ids,
+                    metadata,
+                    encoding_config,
+                    target_index,
+                    target_type,
+                )
+                .await
+                .map(Response::VerifiedRecoverySymbols)?,
+
+                Request::ListVerifiedRecoverySymbolsBatch(requests) => {
+                    list_verified_recovery_symbols_batch(&client, requests, encoding_config)
+                        .await
+                        .map(Response::VerifiedRecoverySymbolsBatch)?
+                }
             };
             Ok(response)
         }
@@ -294,6 +340,122 @@ impl Service<Request> for RemoteStorageNode {
     }
 }
 
+/// Lists the recovery symbols from the node, falling back to requesting the symbols individually
+/// if the node's advertised API version does not support listing symbols.
+///
+/// If the version of the node is not yet known, the symbols are listed optimistically, and
+/// requested individually only if the node turns out to not support the endpoint.
+async fn list_verified_recovery_symbols(
+    client: &Client,
+    filter: RecoverySymbolsFilter,
+    symbol_ids: &[SymbolId],
+    metadata: Arc<VerifiedBlobMetadataWithId>,
+    encoding_config: Arc<EncodingConfig>,
+    target_index: SliverIndex,
+    target_type: SliverType,
+) -> Result<Vec<GeneralRecoverySymbol>, NodeError> {
+    let supports_listing = |client: &Client| {
+        client
+            .api_version()
+            .is_none_or(|version| version.supports(ApiFeature::ListRecoverySymbols))
+    };
+
+    if supports_listing(client) {
+        match client
+            .list_and_verify_recovery_symbols(
+                filter.clone(),
+                metadata.clone(),
+                encoding_config.clone(),
+                target_index,
+                target_type,
+            )
+            .await
+        {
+            Err(error) if error.is_status_not_found() && !supports_listing(client) => {
+                tracing::debug!(
+                    api_version = ?client.api_version(),
+                    "node does not support listing recovery symbols, requesting them individually"
+                );
+            }
+            result => return result,
+        }
+    }
+
+    client
+        .get_and_verify_recovery_symbols(
+            symbol_ids,
+            filter,
+            metadata,
+            encoding_config,
+            target_index,
+            target_type,
+        )
+        .await
+}
+
+/// Lists the recovery symbols of multiple blobs from the node with a single request, falling back
+/// to listing the symbols of each blob separately if the node's advertised API version does not
+/// support batches.
+///
+/// As with [`list_verified_recovery_symbols`], a batch is attempted optimistically if the version
+/// of the node is not yet known.
+async fn list_verified_recovery_symbols_batch(
+    client: &Client,
+    requests: Vec<RecoverySymbolsRequest>,
+    encoding_config: Arc<EncodingConfig>,
+) -> Result<Vec<Result<Vec<GeneralRecoverySymbol>, NodeError>>, NodeError> {
+    let supports_batches = |client: &Client| {
+        client
+            .api_version()
+            .is_none_or(|version| version.supports(ApiFeature::BatchRecoverySymbols))
+    };
+
+    if supports_batches(client) {
+        let batch = requests
+            .iter()
+            .map(|request| {
+                let batched_request = BatchedRecoverySymbolsRequest::new(
+                    *request.metadata.blob_id(),
+                    &request.filter,
+                    request.target_index,
+                    request.target_type,
+                );
+                (batched_request, request.metadata.clone())
+            })
+            .collect();
+
+        match client
+            .list_and_verify_recovery_symbols_batch(batch, encoding_config.clone())
+            .await
+        {
+            Err(error) if error.is_status_not_found() && !supports_batches(client) => {
+                tracing::debug!(
+                    api_version = ?client.api_version(),
+                    "node does not support batches of recovery symbols, listing them per blob"
+                );
+            }
+            result => return result,
+        }
+    }
+
+    let results = requests.into_iter().map(|request| {
+        let encoding_config = encoding_config.clone();
+        async move {
+            list_verified_recovery_symbols(
+                client,
+                request.filter,
+                &request.symbol_ids,
+                request.metadata,
+                encoding_config,
+                request.target_index,
+                request.target_type,
+            )
+            .await
+        }
+    });
+    Ok(futures::future::join_all(results).await)
+}
+
 // TODO(jsmith): Define a LocalStorageNode that can be used within process.
 // Such a service would need to hold only a `Weak` to `StorageNodeInner`, so as to avoid a memory
 // leak due to cyclic Arcs.
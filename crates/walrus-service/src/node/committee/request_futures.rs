@@ -55,7 +55,7 @@ use walrus_utils::backoff::ExponentialBackoffState;
 
 use super::{
     committee_service::NodeCommitteeServiceInner,
-    node_service::{NodeService, NodeServiceError, Request, Response},
+    node_service::{NodeService, NodeServiceError, RecoverySymbolsRequest, Request, Response},
 };
 
 pub(super) struct GetAndVerifyMetadata<'a, T> {
@@ -803,24 +803,30 @@ impl<'a, T: NodeService> CollectRecoverySymbols<'a, T> {
             let filter = if symbols_to_request.len() == node_info.shard_ids.len() {
                 RecoverySymbolsFilter::recovers(self.target_index(), self.target_sliver_type())
             } else {
-                RecoverySymbolsFilter::ids(symbols_to_request).expect("symbols list is non-empty")
+                RecoverySymbolsFilter::ids(symbols_to_request.clone())
+                    .expect("symbols list is non-empty")
             };
             let filter = filter.require_proof_from_axis(self.target_sliver_type().orthogonal());
 
-            let request = Request::ListVerifiedRecoverySymbols {
+            let request = RecoverySymbolsRequest {
                 filter,
+                symbol_ids: symbols_to_request,
                 metadata: self.metadata.clone(),
                 target_index: self.target_index(),
                 target_type: self.target_sliver_type(),
             };
 
-            let request = time::timeout(
-                self.shared.config.sliver_request_timeout,
-                client.oneshot(request).map_ok(|symbol| symbol.into_value()),
-            )
-            .map(log_and_discard_timeout_or_error)
-            .map(move |symbol| (symbols_count, symbol))
-            .boxed();
+            let request = self
+                .shared
+                .symbol_batcher
+                .request(
+                    node_info.public_key.clone(),
+                    client,
+                    request,
+                    self.shared.config.sliver_request_timeout,
+                )
+                .map(move |symbol| (symbols_count, symbol))
+                .boxed();
 
             self.pending_requests.push(request);
             self.tracker.increase_pending(symbols_count);
@@ -1480,7 +1486,7 @@ async fn wait_for_write_committee_change<F>(
     }
 }
 
-fn log_and_discard_timeout_or_error<T>(
+pub(super) fn log_and_discard_timeout_or_error<T>(
     result: Result<Result<T, NodeServiceError>, Elapsed>,
 ) -> Option<T> {
     match result {
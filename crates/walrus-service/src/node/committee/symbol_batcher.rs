@@ -0,0 +1,317 @@
+// Copyright (c) Walrus Foundation
+// SPDX-License-Identifier: Apache-2.0
+#![allow(clippy::mutable_key_type)]
+
+//! Batching of requests for recovery symbols that are sent to the same storage node.
+//!
+//! When many blobs are recovered concurrently, for example during shard recovery, each blob
+//! requests a few symbols from every node in the committee. The [`RecoverySymbolBatcher`] collects
+//! the requests to a node for a short window and sends them as a single
+//! [`Request::ListVerifiedRecoverySymbolsBatch`], which reduces the per-request overhead on both
+//! nodes.
+//!
+//! The first request to a node starts a batch, which is sent once the window elapses unless it was
+//! already sent because it reached the maximum batch size. Batches are sent by spawned tasks, so
+//! cancelling any of the requests, including the one that started the batch, does not affect the
+//! others. Each request receives its own result, so a failure to recover the symbols of one blob
+//! does not affect the other requests in the batch.
+
+use std::{
+    collections::HashMap,
+    num::NonZeroUsize,
+    sync::{Arc, Mutex as SyncMutex},
+    time::Duration,
+};
+
+use futures::TryFutureExt as _;
+use tokio::{sync::oneshot, time};
+use tower::ServiceExt as _;
+use tracing::Instrument as _;
+use walrus_core::{encoding::GeneralRecoverySymbol, PublicKey};
+use walrus_rest_client::error::NodeError;
+
+use super::{
+    node_service::{NodeService, RecoverySymbolsRequest, Request},
+    request_futures::log_and_discard_timeout_or_error,
+};
+
+type SymbolsSender = oneshot::Sender<Option<Vec<GeneralRecoverySymbol>>>;
+
+/// Combines concurrent requests for recovery symbols to the same node into batches.
+#[derive(Debug)]
+pub(super) struct RecoverySymbolBatcher {
+    window: Duration,
+    max_batch_size: usize,
+    state: Arc<SyncMutex<BatcherState>>,
+}
+
+#[derive(Debug, Default)]
+struct BatcherState {
+    next_batch_id: u64,
+    pending: HashMap<PublicKey, PendingBatch>,
+}
+
+#[derive(Debug)]
+struct PendingBatch {
+    id: u64,
+    requests: Vec<RecoverySymbolsRequest>,
+    senders: Vec<SymbolsSender>,
+}
+
+impl RecoverySymbolBatcher {
+    /// Creates a new batcher that collects requests for `window` and sends at most
+    /// `max_batch_size` requests in a batch.
+    ///
+    /// A window of zero disables batching.
+    pub fn new(window: Duration, max_batch_size: NonZeroUsize) -> Self {
+        Self {
+            window,
+            max_batch_size: max_batch_size.get(),
+            state: Arc::default(),
+        }
+    }
+
+    /// Requests the recovery symbols from the node identified by `node`, possibly as part of a
+    /// batch with other requests to the same node.
+    ///
+    /// Returns `None` if the symbols could not be retrieved within `timeout` of sending the
+    /// request.
+    pub async fn request<T: NodeService>(
+        &self,
+        node: PublicKey,
+        service: T,
+        request: RecoverySymbolsRequest,
+        timeout: Duration,
+    ) -> Option<Vec<GeneralRecoverySymbol>> {
+        if self.window.is_zero() || self.max_batch_size == 1 {
+            return send_single(service, request, timeout).await;
+        }
+
+        let (sender, receiver) = oneshot::channel();
+        let (batch_id, starts_batch, full_batch) = {
+            let mut state = self.state.lock().expect("mutex should not be poisoned");
+
+            if let Some(batch) = state.pending.get_mut(&node) {
+                batch.requests.push(request);
+                batch.senders.push(sender);
+                let batch_id = batch.id;
+                let full_batch = (batch.requests.len() >= self.max_batch_size)
+                    .then(|| state.pending.remove(&node).expect("batch is pending"));
+                (batch_id, false, full_batch)
+            } else {
+                let batch_id = state.next_batch_id;
+                state.next_batch_id += 1;
+                state.pending.insert(
+                    node.clone(),
+                    PendingBatch {
+                        id: batch_id,
+                        requests: vec![request],
+                        senders: vec![sender],
+                    },
+                );
+                (batch_id, true, None)
+            }
+        };
+
+        if let Some(batch) = full_batch {
+            tracing::trace!(batch_id, "sending full batch of recovery symbol requests");
+            tokio::spawn(send_batch(service, batch, timeout).in_current_span());
+        } else if starts_batch {
+            let state = self.state.clone();
+            let window = self.window;
+            tokio::spawn(
+                async move {
+                    time::sleep(window).await;
+
+                    let batch = state
+                        .lock()
+                        .expect("mutex should not be poisoned")
+                        .remove_pending(&node, batch_id);
+                    // The batch was already sent if it reached the maximum size.
+                    if let Some(batch) = batch {
+                        tracing::trace!(
+                            batch_id,
+                            batch_size = batch.requests.len(),
+                            "sending batch of recovery symbol requests after the window elapsed"
+                        );
+                        send_batch(service, batch, timeout).await;
+                    }
+                }
+                .in_current_span(),
+            );
+        }
+
+        // The sender is dropped without a value if the batch failed.
+        receiver.await.ok().flatten()
+    }
+}
+
+impl BatcherState {
+    /// Removes the pending batch to `node` if it is the batch with the specified ID.
+    fn remove_pending(&mut self, node: &PublicKey, batch_id: u64) -> Option<PendingBatch> {
+        if self.pending.get(node)?.id == batch_id {
+            self.pending.remove(node)
+        } else {
+            None
+        }
+    }
+}
+
+async fn send_single<T: NodeService>(
+    service: T,
+    request: RecoverySymbolsRequest,
+    timeout: Duration,
+) -> Option<Vec<GeneralRecoverySymbol>> {
+    let result = time::timeout(
+        timeout,
+        service
+            .oneshot(request.into())
+            .map_ok(|symbols| symbols.into_value()),
+    )
+    .await;
+    log_and_discard_timeout_or_error(result)
+}
+
+/// Sends the batch to the node and distributes the results to the requests in the batch.
+async fn send_batch<T: NodeService>(service: T, batch: PendingBatch, timeout: Duration) {
+    let PendingBatch {
+        mut requests,
+        senders,
+        ..
+    } = batch;
+
+    if requests.len() == 1 {
+        let request = requests.pop().expect("batch has a single request");
+        let symbols = send_single(service, request, timeout).await;
+        if let Some(sender) = senders.into_iter().next() {
+            let _ = sender.send(symbols);
+        }
+        return;
+    }
+
+    let result = time::timeout(
+        timeout,
+        service
+            .oneshot(Request::ListVerifiedRecoverySymbolsBatch(requests))
+            .map_ok(|results| {
+                results.into_value::<Vec<Result<Vec<GeneralRecoverySymbol>, NodeError>>>()
+            }),
+    )
+    .await;
+
+    let Some(results) = log_and_discard_timeout_or_error(result) else {
+        return;
+    };
+    for (sender, result) in senders.into_iter().zip(results) {
+        let symbols = result
+            .inspect_err(|error| tracing::debug!(%error, "failed to list recovery symbols"))
+            .ok();
+        // The requester may have stopped waiting for the symbols.
+        let _ = sender.send(symbols);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    };
+
+    use tower::ServiceExt as _;
+    use walrus_core::{
+        keys::ProtocolKeyPair,
+        metadata::VerifiedBlobMetadataWithId,
+        test_utils,
+        SliverIndex,
+        SliverType,
+    };
+    use walrus_rest_client::client::RecoverySymbolsFilter;
+
+    use super::*;
+    use crate::node::committee::node_service::Response;
+
+    fn recovery_symbols_request(
+        metadata: Arc<VerifiedBlobMetadataWithId>,
+    ) -> RecoverySymbolsRequest {
+        RecoverySymbolsRequest {
+            filter: RecoverySymbolsFilter::recovers(SliverIndex(0), SliverType::Primary),
+            symbol_ids: vec![],
+            metadata,
+            target_index: SliverIndex(0),
+            target_type: SliverType::Primary,
+        }
+    }
+
+    /// Returns a service answering batches with empty lists of symbols, and counting the batches.
+    fn batch_service(batches_received: Arc<AtomicUsize>) -> impl NodeService {
+        tower::service_fn(move |request| {
+            let response = match request {
+                Request::ListVerifiedRecoverySymbolsBatch(requests) => {
+                    batches_received.fetch_add(1, Ordering::SeqCst);
+                    Response::VerifiedRecoverySymbolsBatch(
+                        requests.iter().map(|_| Ok(vec![])).collect(),
+                    )
+                }
+                request => panic!("unexpected request: {request:?}"),
+            };
+            std::future::ready(Ok(response))
+        })
+        .boxed_clone()
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn combines_concurrent_requests_to_the_same_node() {
+        let batches_received = Arc::new(AtomicUsize::new(0));
+        let service = batch_service(batches_received.clone());
+
+        let batcher =
+            RecoverySymbolBatcher::new(Duration::from_millis(10), NonZeroUsize::new(3).unwrap());
+        let node = ProtocolKeyPair::generate().public().clone();
+        let metadata = Arc::new(test_utils::verified_blob_metadata());
+
+        let results = futures::future::join_all((0..5).map(|_| {
+            batcher.request(
+                node.clone(),
+                service.clone(),
+                recovery_symbols_request(metadata.clone()),
+                Duration::from_secs(1),
+            )
+        }))
+        .await;
+
+        assert!(results
+            .iter()
+            .all(|symbols| symbols.as_ref().is_some_and(Vec::is_empty)));
+        // One full batch of three requests, and a batch of the remaining two after the window.
+        assert_eq!(batches_received.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn sends_batch_if_the_request_starting_it_is_cancelled() {
+        let batches_received = Arc::new(AtomicUsize::new(0));
+        let service = batch_service(batches_received.clone());
+
+        let batcher =
+            RecoverySymbolBatcher::new(Duration::from_millis(10), NonZeroUsize::new(3).unwrap());
+        let node = ProtocolKeyPair::generate().public().clone();
+        let metadata = Arc::new(test_utils::verified_blob_metadata());
+        let request = || {
+            Box::pin(batcher.request(
+                node.clone(),
+                service.clone(),
+                recovery_symbols_request(metadata.clone()),
+                Duration::from_secs(1),
+            ))
+        };
+
+        let mut leader = request();
+        let mut follower = request();
+        assert!(futures::poll!(&mut leader).is_pending());
+        assert!(futures::poll!(&mut follower).is_pending());
+        drop(leader);
+
+        assert_eq!(follower.await, Some(vec![]));
+        assert_eq!(batches_received.load(Ordering::SeqCst), 1);
+    }
+}
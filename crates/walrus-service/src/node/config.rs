@@ -7,7 +7,7 @@ use std::{
     collections::HashMap,
     fmt::Display,
     net::{IpAddr, SocketAddr},
-    num::NonZeroUsize,
+    num::{NonZeroU64, NonZeroUsize},
     path::{Path, PathBuf},
     str::FromStr as _,
     time::Duration,
@@ -22,6 +22,7 @@ use serde_with::{
     ser::SerializeAsWrap,
     serde_as,
     DeserializeAs,
+    DurationMilliSeconds,
     DurationSeconds,
     SerializeAs,
 };
@@ -81,6 +82,19 @@ pub struct StorageNodeConfig {
     /// File path to the blocklist.
     #[serde(default, skip_serializing_if = "defaults::is_none")]
     pub blocklist_path: Option<PathBuf>,
+    /// Whether to delete the locally stored data of blobs on the blocklist.
+    ///
+    /// If set, the node deletes the metadata and slivers of a blob once it is added to the
+    /// blocklist and does not store any data for it while it remains blocked. If the blob is later
+    /// removed from the blocklist, the node attempts to recover its data from other nodes.
+    #[serde(default, skip_serializing_if = "defaults::is_default")]
+    pub delete_blocked_blob_data: bool,
+    /// Whether to delete the locally stored data of blobs once they expire.
+    ///
+    /// If set, the node deletes the metadata and slivers of the blobs whose latest registration
+    /// ends at the start of an epoch when it enters that epoch.
+    #[serde(default, skip_serializing_if = "defaults::is_default")]
+    pub delete_expired_blob_data: bool,
     /// Optional "config" to tune storage database.
     #[serde(default, skip_serializing_if = "defaults::is_default")]
     pub db_config: DatabaseConfig,
@@ -171,6 +185,15 @@ pub struct StorageNodeConfig {
     /// Configuration for the blocking thread pool.
     #[serde(default, skip_serializing_if = "defaults::is_default")]
     pub thread_pool: ThreadPoolConfig,
+    /// Configuration for the scheduling of requests according to their priority class.
+    #[serde(default, skip_serializing_if = "defaults::is_default")]
+    pub request_scheduler: RequestSchedulerConfig,
+    /// The number of epochs after the end of a blob's storage period during which the node
+    /// continues to serve the blob's metadata and slivers, if they are still stored.
+    ///
+    /// After this grace period, reads are rejected with a "blob expired" error.
+    #[serde(default, skip_serializing_if = "defaults::is_default")]
+    pub expired_blob_read_grace_epochs: Epoch,
 }
 
 impl Default for StorageNodeConfig {
@@ -178,6 +201,8 @@ impl Default for StorageNodeConfig {
         Self {
             storage_path: PathBuf::from("/opt/walrus/db"),
             blocklist_path: Default::default(),
+            delete_blocked_blob_data: false,
+            delete_expired_blob_data: false,
             db_config: Default::default(),
             protocol_key_pair: PathOrInPlace::from_path("/opt/walrus/config/protocol.key"),
             next_protocol_key_pair: None,
@@ -209,6 +234,8 @@ impl Default for StorageNodeConfig {
             num_uncertified_blob_threshold: None,
             balance_check: Default::default(),
             thread_pool: Default::default(),
+            request_scheduler: Default::default(),
+            expired_blob_read_grace_epochs: 0,
         }
     }
 }
@@ -449,6 +476,39 @@ pub struct MetricsPushConfig {
     /// Static labels to provide to the push process.
     #[serde(default, skip_serializing_if = "defaults::is_none")]
     pub labels: Option<HashMap<String, String>>,
+    /// The kind of endpoint located at `push_url`, which determines the push protocol.
+    #[serde(default, skip_serializing_if = "defaults::is_default")]
+    pub target: MetricsPushTarget,
+    /// Additional HTTP headers sent with each push, e.g., for authenticating with a hosted
+    /// Pushgateway or OTLP collector.
+    ///
+    /// Ignored when pushing to a walrus-proxy, which authenticates nodes by their network key.
+    #[serde(default, skip_serializing_if = "defaults::is_none")]
+    pub headers: Option<HashMap<String, String>>,
+}
+
+/// The kind of endpoint to which metrics are pushed.
+#[derive(Debug, Default, Clone, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum MetricsPushTarget {
+    /// A walrus-proxy, which receives snappy-compressed protobuf metric families signed with the
+    /// node's network key.
+    #[default]
+    WalrusProxy,
+    /// A Prometheus Pushgateway.
+    ///
+    /// Metrics are pushed in the text exposition format to the group identified by the job name
+    /// and the static labels; `push_url` is the base URL of the Pushgateway.
+    Pushgateway {
+        /// The job name used for the grouping key.
+        #[serde(default = "defaults::pushgateway_job")]
+        job: String,
+    },
+    /// An OpenTelemetry collector accepting OTLP over HTTP with JSON encoding.
+    ///
+    /// `push_url` is the full URL of the metrics endpoint, usually ending in `/v1/metrics`. The
+    /// static labels are attached as resource attributes.
+    Otlp,
 }
 
 /// Identifies a role to attach to metrics.
@@ -473,6 +533,8 @@ impl MetricsPushConfig {
             push_interval: defaults::push_interval(),
             push_url: url,
             labels: None,
+            target: MetricsPushTarget::default(),
+            headers: None,
         }
     }
 
@@ -583,6 +645,16 @@ pub struct CommitteeServiceConfig {
     /// Use the experimental batch recovery service endpoint.
     // TODO: Remove (WAL-594).
     pub experimental_batch_symbol_recovery: bool,
+    /// The time for which requests for recovery symbols to the same node are collected, before
+    /// they are sent as a single batch.
+    ///
+    /// A window of zero disables batching.
+    #[serde_as(as = "DurationMilliSeconds<u64>")]
+    #[serde(rename = "symbol_request_batch_window_millis")]
+    pub symbol_request_batch_window: Duration,
+    /// The maximum number of requests for recovery symbols in a batch; a batch is sent as soon as
+    /// it is full.
+    pub max_symbol_requests_per_batch: NonZeroUsize,
 }
 
 impl Default for CommitteeServiceConfig {
@@ -596,6 +668,8 @@ impl Default for CommitteeServiceConfig {
             max_concurrent_metadata_requests: NonZeroUsize::new(1).unwrap(),
             node_connect_timeout: Duration::from_secs(1),
             experimental_batch_symbol_recovery: true,
+            symbol_request_batch_window: Duration::from_millis(10),
+            max_symbol_requests_per_batch: NonZeroUsize::new(64).unwrap(),
         }
     }
 }
@@ -728,6 +802,11 @@ pub mod defaults {
         duration == &push_interval()
     }
 
+    /// The default job name when pushing metrics to a Prometheus Pushgateway.
+    pub fn pushgateway_job() -> String {
+        "walrus-node".to_owned()
+    }
+
     /// The default interval between config monitoring checks
     pub fn config_synchronizer_interval() -> Duration {
         Duration::from_secs(CONFIG_SYNCHRONIZER_INTERVAL_SECS)
@@ -953,6 +1032,138 @@ pub struct RestServerConfig {
     /// Configuration for incoming HTTP/2 connections.
     #[serde(flatten, skip_serializing_if = "defaults::is_default")]
     pub http2_config: Http2Config,
+    /// Limits on the rate at which a single peer can submit inconsistency proofs.
+    #[serde(skip_serializing_if = "defaults::is_default")]
+    pub inconsistency_proof_rate_limit: PeerRateLimitConfig,
+    /// Whether to serve the stream of events about the data stored by the node.
+    ///
+    /// The stream is intended for operators monitoring or indexing their own node.
+    #[serde(skip_serializing_if = "defaults::is_default")]
+    pub enable_event_stream: bool,
+    /// The socket address on which to serve the admin API, such as the signed per-epoch storage
+    /// accounting reports. The admin API is not served if unset.
+    ///
+    /// The admin API is served over plain HTTP on a listener separate from the public REST API,
+    /// and is intended for the operator of the node. The address should therefore not be
+    /// reachable by third parties, for example by binding it to a loopback interface.
+    #[serde(skip_serializing_if = "defaults::is_default")]
+    pub admin_api_address: Option<SocketAddr>,
+    /// Configuration of the log of slow requests, which is served by the admin API.
+    #[serde(skip_serializing_if = "defaults::is_default")]
+    pub slow_request_log: SlowRequestLogConfig,
+    /// Limits on the bandwidth used to serve data to clients, which are enforced separately from
+    /// those on ingesting and syncing data.
+    #[serde(skip_serializing_if = "defaults::is_default")]
+    pub bandwidth_limits: BandwidthLimitsConfig,
+    /// Configuration of the results of write requests that are retained for their idempotency
+    /// keys.
+    #[serde(skip_serializing_if = "defaults::is_default")]
+    pub idempotency: IdempotencyConfig,
+}
+
+/// Bandwidth limits of the REST server, which are enforced separately for each kind of traffic.
+///
+/// Traffic of a kind without a limit is not restricted.
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BandwidthLimitsConfig {
+    /// The limit on the slivers and metadata served to clients.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub serving: Option<BandwidthLimitConfig>,
+    /// The limit on the slivers and metadata uploaded by clients, and on the slivers and recovery
+    /// symbols served to other storage nodes during shard syncs and recovery.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ingest_and_sync: Option<BandwidthLimitConfig>,
+}
+
+/// Configuration of a leaky-bucket bandwidth limit.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BandwidthLimitConfig {
+    /// The sustained bandwidth, in bytes per second.
+    pub bytes_per_second: NonZeroU64,
+    /// The number of bytes that can be transferred in a burst above the sustained bandwidth.
+    #[serde(default, skip_serializing_if = "defaults::is_default")]
+    pub burst_bytes: u64,
+}
+
+/// Configuration of the log of requests that exceeded a latency threshold.
+#[serde_as]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SlowRequestLogConfig {
+    /// The duration after which a request is logged as slow.
+    #[serde_as(as = "DurationMilliSeconds<u64>")]
+    #[serde(rename = "threshold_millis")]
+    pub threshold: Duration,
+    /// Thresholds for individual routes, such as `/v1/blobs/{blob_id}/metadata`, which take
+    /// precedence over `threshold`.
+    #[serde_as(as = "HashMap<_, DurationMilliSeconds<u64>>")]
+    #[serde(
+        rename = "route_thresholds_millis",
+        skip_serializing_if = "HashMap::is_empty"
+    )]
+    pub route_thresholds: HashMap<String, Duration>,
+    /// The maximum number of slow requests that are retained, after which the oldest are
+    /// dropped.
+    pub capacity: usize,
+}
+
+impl Default for SlowRequestLogConfig {
+    fn default() -> Self {
+        Self {
+            threshold: Duration::from_secs(1),
+            route_thresholds: HashMap::new(),
+            capacity: 1000,
+        }
+    }
+}
+
+/// Configuration of the results of write requests that are retained for their idempotency keys.
+///
+/// A retried write with the same key as a retained result is not processed again, but answered
+/// with the retained result.
+#[serde_as]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct IdempotencyConfig {
+    /// The maximum number of results that are retained, after which the least recently used are
+    /// dropped.
+    pub max_entries: u64,
+    /// The duration for which a result is retained after the write completed.
+    #[serde_as(as = "DurationSeconds<u64>")]
+    #[serde(rename = "retention_secs")]
+    pub retention: Duration,
+}
+
+impl Default for IdempotencyConfig {
+    fn default() -> Self {
+        Self {
+            max_entries: 100_000,
+            retention: Duration::from_secs(600),
+        }
+    }
+}
+
+/// Configuration of a token-bucket rate limit applied separately to each peer.
+#[serde_as]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PeerRateLimitConfig {
+    /// The maximum number of requests that a peer can make in a burst.
+    pub max_burst: u32,
+    /// The interval after which a peer can make an additional request, up to `max_burst`.
+    #[serde_as(as = "DurationMilliSeconds<u64>")]
+    #[serde(rename = "replenish_interval_millis")]
+    pub replenish_interval: Duration,
+}
+
+impl Default for PeerRateLimitConfig {
+    fn default() -> Self {
+        Self {
+            max_burst: 10,
+            replenish_interval: Duration::from_secs(1),
+        }
+    }
 }
 
 /// Configuration of the HTTP/2 connections established by the REST API.
@@ -1029,6 +1240,62 @@ impl Default for ThreadPoolConfig {
     }
 }
 
+/// Configuration for the scheduling of the node's work according to its priority class.
+///
+/// Work is only started while fewer than `max_concurrent_requests` requests are in progress in
+/// total, and while fewer than the `max_concurrent` requests of its class are in progress. Waiting
+/// requests are started in proportion to the `weight` of their class. Limiting the concurrency of
+/// the background classes below `max_concurrent_requests` reserves capacity for user requests.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RequestSchedulerConfig {
+    /// The maximum number of requests in progress across all classes.
+    pub max_concurrent_requests: usize,
+    /// The scheduling of reads of slivers and metadata by clients.
+    pub user_reads: RequestClassConfig,
+    /// The scheduling of writes of slivers and metadata by clients.
+    pub user_writes: RequestClassConfig,
+    /// The scheduling of serving shard syncs and recovery symbols to other nodes.
+    pub sync: RequestClassConfig,
+    /// The scheduling of background consistency checks of the stored data.
+    pub scrubbing: RequestClassConfig,
+}
+
+impl Default for RequestSchedulerConfig {
+    fn default() -> Self {
+        Self {
+            max_concurrent_requests: 512,
+            user_reads: RequestClassConfig {
+                weight: 8,
+                max_concurrent: 512,
+            },
+            user_writes: RequestClassConfig {
+                weight: 4,
+                max_concurrent: 384,
+            },
+            sync: RequestClassConfig {
+                weight: 2,
+                max_concurrent: 128,
+            },
+            scrubbing: RequestClassConfig {
+                weight: 1,
+                max_concurrent: 2,
+            },
+        }
+    }
+}
+
+/// Configuration of the scheduling of a single class of requests.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RequestClassConfig {
+    /// The relative share of the capacity allocated to the class when requests are waiting.
+    ///
+    /// A weight of zero is treated as a weight of one.
+    pub weight: u32,
+    /// The maximum number of requests of the class in progress.
+    pub max_concurrent: usize,
+}
+
 #[cfg(test)]
 mod tests {
     use std::{io::Write as _, str::FromStr};
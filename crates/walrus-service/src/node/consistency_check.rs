@@ -16,6 +16,7 @@ use typed_store::TypedStoreError;
 use walrus_core::Epoch;
 
 use super::{
+    request_scheduler::RequestClass,
     storage::blob_info::{
         BlobInfoIter,
         BlobInfoIterator,
@@ -50,6 +51,10 @@ pub(super) async fn schedule_background_consistency_check(
             .certified_per_object_blob_info_iter_before_epoch(epoch);
         let _ = tx.send(());
 
+        // The scan runs at the lowest priority; the snapshot is taken before waiting for a permit.
+        let _permit = tokio::runtime::Handle::current()
+            .block_on(node.request_scheduler.acquire(RequestClass::Scrubbing));
+
         // Right now, the computing the two digests are sequential, given that scanning blob info
         // table is quick. We may consider parallelizing them in the future.
         compose_certified_blob_list_digest(node.clone(), blob_info_iterator, epoch);
@@ -0,0 +1,225 @@
+// Copyright (c) Walrus Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Tracking of the handover of shards between committees during an epoch change.
+//!
+//! When an epoch change starts, the node locks the shards that it hands over to other nodes and
+//! starts syncing the shards that it receives. The handover then progresses through the phases of
+//! [`EpochHandoverPhase`]:
+//!
+//! 1. [`Syncing`][EpochHandoverPhase::Syncing]: The received shards are being synced. Writes for
+//!    these shards are accepted once their storage has been created; until then, they wait on the
+//!    lock of the shard map held during the epoch change.
+//! 2. [`AwaitingConfirmation`][EpochHandoverPhase::AwaitingConfirmation]: The node holds all of
+//!    its shards and has signalled that its sync is done.
+//! 3. [`Confirmed`][EpochHandoverPhase::Confirmed]: The `EpochChangeDone` event for the epoch has
+//!    been observed, i.e., the committee has confirmed that it is synced.
+//!
+//! Throughout the handover, the outgoing shards are read only but continue to serve reads. Their
+//! storage is only removed once the transition in which they were handed over has been confirmed.
+
+use std::sync::Mutex;
+
+use tokio::sync::watch;
+use walrus_core::{Epoch, ShardIndex};
+use walrus_rest_client::api::{EpochHandoverInfo, EpochHandoverPhase};
+
+/// Tracks the state of the node's shard handover across committee transitions.
+#[derive(Debug)]
+pub(crate) struct EpochHandover {
+    /// The handover of the latest transition started by this node, if any.
+    latest: Mutex<Option<EpochHandoverInfo>>,
+    /// The latest epoch for which the transition has been confirmed.
+    confirmed_epoch: watch::Sender<Epoch>,
+}
+
+impl EpochHandover {
+    /// Creates a new instance, where the transition to `confirmed_epoch` and all prior
+    /// transitions are considered confirmed.
+    pub fn new(confirmed_epoch: Epoch) -> Self {
+        Self {
+            latest: Mutex::new(None),
+            confirmed_epoch: watch::Sender::new(confirmed_epoch),
+        }
+    }
+
+    /// Starts the handover for the transition to `epoch`.
+    pub fn begin(
+        &self,
+        epoch: Epoch,
+        outgoing_shards: Vec<ShardIndex>,
+        incoming_shards: Vec<ShardIndex>,
+    ) {
+        let phase = if epoch <= *self.confirmed_epoch.borrow() {
+            // The event is re-processed, for example after a restart.
+            EpochHandoverPhase::Confirmed
+        } else if incoming_shards.is_empty() {
+            EpochHandoverPhase::AwaitingConfirmation
+        } else {
+            EpochHandoverPhase::Syncing
+        };
+        tracing::info!(
+            walrus.epoch = epoch,
+            ?phase,
+            ?outgoing_shards,
+            ?incoming_shards,
+            "starting shard handover"
+        );
+
+        *self.latest.lock().expect("mutex should not be poisoned") = Some(EpochHandoverInfo {
+            epoch,
+            phase,
+            outgoing_shards,
+            incoming_shards,
+        });
+    }
+
+    /// Records that the node has synced all shards it received in the transition to `epoch`.
+    pub fn mark_synced(&self, epoch: Epoch) {
+        self.update_phase(epoch, |phase| {
+            if phase == EpochHandoverPhase::Syncing {
+                EpochHandoverPhase::AwaitingConfirmation
+            } else {
+                phase
+            }
+        });
+    }
+
+    /// Records that the committee confirmed the transition to `epoch`.
+    ///
+    /// This also confirms the transitions to all prior epochs.
+    pub fn confirm(&self, epoch: Epoch) {
+        self.confirmed_epoch.send_if_modified(|confirmed_epoch| {
+            let is_newer = epoch > *confirmed_epoch;
+            if is_newer {
+                *confirmed_epoch = epoch;
+            }
+            is_newer
+        });
+
+        let mut latest = self.latest.lock().expect("mutex should not be poisoned");
+        if let Some(info) = latest.as_mut().filter(|info| info.epoch <= epoch) {
+            info.phase = EpochHandoverPhase::Confirmed;
+        }
+    }
+
+    /// Returns the state of the handover of the latest transition, if any was started since the
+    /// node started.
+    pub fn info(&self) -> Option<EpochHandoverInfo> {
+        self.latest
+            .lock()
+            .expect("mutex should not be poisoned")
+            .clone()
+    }
+
+    /// Waits until the transition to `epoch` has been confirmed.
+    pub async fn wait_for_confirmation(&self, epoch: Epoch) {
+        let mut receiver = self.confirmed_epoch.subscribe();
+        if *receiver.borrow() < epoch {
+            tracing::info!(
+                walrus.epoch = epoch,
+                "waiting for the confirmation of the shard handover"
+            );
+        }
+        receiver
+            .wait_for(|confirmed_epoch| *confirmed_epoch >= epoch)
+            .await
+            .expect("the sender is owned by self and therefore not dropped");
+    }
+
+    fn update_phase(
+        &self,
+        epoch: Epoch,
+        update: impl FnOnce(EpochHandoverPhase) -> EpochHandoverPhase,
+    ) {
+        let mut latest = self.latest.lock().expect("mutex should not be poisoned");
+        if let Some(info) = latest.as_mut().filter(|info| info.epoch == epoch) {
+            info.phase = update(info.phase);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use tokio::time;
+
+    use super::*;
+
+    fn shards(ids: &[u16]) -> Vec<ShardIndex> {
+        ids.iter().copied().map(ShardIndex).collect()
+    }
+
+    fn phase(handover: &EpochHandover) -> EpochHandoverPhase {
+        handover.info().expect("handover was started").phase
+    }
+
+    #[test]
+    fn progresses_through_all_phases_when_receiving_shards() {
+        let handover = EpochHandover::new(1);
+        assert_eq!(handover.info(), None);
+
+        handover.begin(2, shards(&[0]), shards(&[1, 2]));
+        assert_eq!(phase(&handover), EpochHandoverPhase::Syncing);
+
+        handover.mark_synced(2);
+        assert_eq!(phase(&handover), EpochHandoverPhase::AwaitingConfirmation);
+
+        handover.confirm(2);
+        assert_eq!(
+            handover.info(),
+            Some(EpochHandoverInfo {
+                epoch: 2,
+                phase: EpochHandoverPhase::Confirmed,
+                outgoing_shards: shards(&[0]),
+                incoming_shards: shards(&[1, 2]),
+            })
+        );
+    }
+
+    #[test]
+    fn awaits_confirmation_when_not_receiving_shards() {
+        let handover = EpochHandover::new(1);
+
+        handover.begin(2, shards(&[0]), vec![]);
+
+        assert_eq!(phase(&handover), EpochHandoverPhase::AwaitingConfirmation);
+    }
+
+    #[test]
+    fn ignores_updates_for_other_epochs() {
+        let handover = EpochHandover::new(1);
+        handover.begin(3, vec![], shards(&[1]));
+
+        handover.mark_synced(2);
+        handover.confirm(2);
+
+        assert_eq!(phase(&handover), EpochHandoverPhase::Syncing);
+    }
+
+    #[test]
+    fn handover_for_confirmed_epoch_is_confirmed() {
+        let handover = EpochHandover::new(3);
+
+        handover.begin(3, vec![], shards(&[1]));
+
+        assert_eq!(phase(&handover), EpochHandoverPhase::Confirmed);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn waits_for_confirmation_of_epoch() {
+        let handover = EpochHandover::new(1);
+        handover.wait_for_confirmation(1).await;
+
+        let mut wait_for_confirmation = std::pin::pin!(handover.wait_for_confirmation(2));
+        time::timeout(Duration::from_secs(60), &mut wait_for_confirmation)
+            .await
+            .expect_err("must not complete before the epoch is confirmed");
+
+        handover.confirm(3);
+        time::timeout(Duration::from_secs(1), wait_for_confirmation)
+            .await
+            .expect("must complete once a later epoch is confirmed");
+    }
+}
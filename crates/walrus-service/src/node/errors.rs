@@ -120,6 +120,12 @@ pub enum RetrieveMetadataError {
     #[rest_api_error(reason = "FORBIDDEN_BLOB", status = ApiStatusCode::UnavailableForLegalReasons)]
     Forbidden,
 
+    /// The metadata is no longer served, as the storage period of the associated blob ended at
+    /// the contained epoch.
+    #[error("the blob expired at epoch {0}")]
+    #[rest_api_error(reason = "BLOB_EXPIRED", status = ApiStatusCode::NotFound)]
+    Expired(Epoch),
+
     #[error(transparent)]
     #[rest_api_error(delegate)]
     Internal(#[from] InternalError),
@@ -139,6 +145,12 @@ pub enum RetrieveSliverError {
     #[rest_api_error(reason = "FORBIDDEN_BLOB", status = ApiStatusCode::UnavailableForLegalReasons)]
     Forbidden,
 
+    /// The sliver is no longer served, as the storage period of the associated blob ended at the
+    /// contained epoch.
+    #[error("the blob expired at epoch {0}")]
+    #[rest_api_error(reason = "BLOB_EXPIRED", status = ApiStatusCode::NotFound)]
+    Expired(Epoch),
+
     /// The index of the identified sliver is out of range for the system.
     #[error("the requested sliver index is out of range: {0}")]
     #[rest_api_error(delegate)]
@@ -191,6 +203,11 @@ pub enum StoreMetadataError {
     #[rest_api_error(reason = "INVALID_BLOB", status = ApiStatusCode::FailedPrecondition)]
     InvalidBlob(EventID),
 
+    /// The metadata is not stored, as the associated blob has been blocked on this storage node.
+    #[error("the blob for this metadata is blocked")]
+    #[rest_api_error(reason = "FORBIDDEN_BLOB", status = ApiStatusCode::UnavailableForLegalReasons)]
+    Forbidden,
+
     #[error("unsupported encoding type {0}, supported types are: {SUPPORTED_ENCODING_TYPES:?}")]
     #[rest_api_error(reason = "UNSUPPORTED_ENCODING_TYPE", status = ApiStatusCode::InvalidArgument)]
     UnsupportedEncodingType(EncodingType),
@@ -237,6 +254,10 @@ pub enum ListSymbolsError {
     )]
     NoSymbolsSpecified,
 
+    #[error("at most {max} blobs can be specified in a single batch")]
+    #[rest_api_error(reason = "BATCH_TOO_LARGE", status = ApiStatusCode::InvalidArgument)]
+    BatchTooLarge { max: usize },
+
     #[error(transparent)]
     #[rest_api_error(delegate)]
     Last(#[from] RetrieveSymbolError),
@@ -264,6 +285,11 @@ pub enum StoreSliverError {
     #[rest_api_error(reason = "METADATA_NOT_FOUND", status = ApiStatusCode::FailedPrecondition)]
     MissingMetadata,
 
+    /// The sliver is not stored, as the associated blob has been blocked on this storage node.
+    #[error("the blob for this sliver is blocked")]
+    #[rest_api_error(reason = "FORBIDDEN_BLOB", status = ApiStatusCode::UnavailableForLegalReasons)]
+    Forbidden,
+
     /// The provided sliver failed verification against the previously uploaded metadata
     /// for that blob ID.
     #[error("the provided sliver is invalid: {0}")]
@@ -296,6 +322,11 @@ pub enum InconsistencyProofError {
     #[rest_api_error(reason = "INVALID_PROOF", status = ApiStatusCode::InvalidArgument)]
     InvalidProof(#[from] InconsistencyVerificationError),
 
+    /// The peer submitted too many inconsistency proofs in a short period of time.
+    #[error("too many inconsistency proofs submitted, retry later")]
+    #[rest_api_error(reason = "TOO_MANY_PROOFS", status = ApiStatusCode::ResourceExhausted)]
+    RateLimited,
+
     #[error(transparent)]
     #[rest_api_error(delegate)]
     Internal(#[from] InternalError),
@@ -306,6 +337,7 @@ impl From<RetrieveMetadataError> for InconsistencyProofError {
         match value {
             RetrieveMetadataError::Unavailable => Self::MissingMetadata,
             RetrieveMetadataError::Forbidden => Self::MissingMetadata,
+            RetrieveMetadataError::Expired(_) => Self::MissingMetadata,
             RetrieveMetadataError::Internal(error) => Self::Internal(error),
         }
     }
@@ -369,6 +401,43 @@ impl From<TypedStoreError> for SyncShardServiceError {
     }
 }
 
+#[derive(Debug, thiserror::Error, RestApiError)]
+#[rest_api_error(domain = ERROR_DOMAIN)]
+pub enum EpochAccountingError {
+    /// The node has not recorded any accounting for the requested epoch.
+    #[error("no accounting is recorded for epoch {0}")]
+    #[rest_api_error(reason = "ACCOUNTING_NOT_FOUND", status = ApiStatusCode::NotFound)]
+    NotFound(Epoch),
+
+    #[error(transparent)]
+    #[rest_api_error(delegate)]
+    Internal(#[from] InternalError),
+}
+
+impl From<TypedStoreError> for EpochAccountingError {
+    fn from(value: TypedStoreError) -> Self {
+        Self::Internal(anyhow!(value))
+    }
+}
+
+#[derive(Debug, thiserror::Error, RestApiError)]
+#[rest_api_error(domain = ERROR_DOMAIN)]
+pub enum ListStoredBlobsError {
+    #[error(transparent)]
+    #[rest_api_error(delegate)]
+    ShardNotAssigned(#[from] ShardNotAssigned),
+
+    #[error(transparent)]
+    #[rest_api_error(delegate)]
+    Internal(#[from] InternalError),
+}
+
+impl From<TypedStoreError> for ListStoredBlobsError {
+    fn from(value: TypedStoreError) -> Self {
+        Self::Internal(anyhow!(value))
+    }
+}
+
 #[derive(Debug, thiserror::Error)]
 pub enum SyncShardClientError {
     #[error("The destination node does not have a valid client to talk to the source node")]
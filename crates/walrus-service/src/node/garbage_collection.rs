@@ -0,0 +1,77 @@
+// Copyright (c) Walrus Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Processing of the blobs that expire at the start of an epoch.
+//!
+//! When the node enters a new epoch, the blobs whose latest registration ends at that epoch are
+//! reported as [`NodeEvent::BlobExpired`] to the subscribers of the node's event stream and, if
+//! enabled in the node config, their locally stored data is deleted. Blobs expiring in epochs
+//! that the node skips, for example while it is catching up, are not processed.
+
+use std::sync::Arc;
+
+use anyhow::Context;
+use walrus_core::Epoch;
+use walrus_rest_client::api::{BlobDataDeletionReason, NodeEvent};
+
+use super::{request_scheduler::RequestClass, StorageNodeInner};
+
+/// Schedules a background task processing the blobs that expire at the start of `epoch`.
+pub(super) fn schedule_background_garbage_collection(node: Arc<StorageNodeInner>, epoch: Epoch) {
+    tokio::spawn(async move {
+        let delete_data = node.delete_expired_blob_data;
+        if let Err(error) = process_expired_blobs(&node, epoch, delete_data).await {
+            tracing::warn!(?error, %epoch, "failed to process the blobs expired in the epoch");
+        }
+    });
+}
+
+/// Emits a [`NodeEvent::BlobExpired`] event for each blob whose latest registration ends at
+/// `epoch` and, if `delete_data` is set, deletes the metadata and slivers of these blobs.
+///
+/// Returns the number of expired blobs. The blob info table is only scanned if the data is
+/// deleted or if there are subscribers to the events.
+pub(super) async fn process_expired_blobs(
+    node: &Arc<StorageNodeInner>,
+    epoch: Epoch,
+    delete_data: bool,
+) -> anyhow::Result<usize> {
+    if !delete_data && !node.local_events.has_subscribers() {
+        return Ok(0);
+    }
+
+    let _permit = node
+        .request_scheduler
+        .acquire(RequestClass::Scrubbing)
+        .await;
+    let scan_node = node.clone();
+    let expired_blob_ids =
+        tokio::task::spawn_blocking(move || scan_node.storage.blob_ids_expiring_at_epoch(epoch))
+            .await
+            .context("the scan of the blob info table panicked")??;
+
+    for blob_id in &expired_blob_ids {
+        node.local_events.emit(NodeEvent::BlobExpired {
+            blob_id: *blob_id,
+            epoch,
+        });
+        if delete_data {
+            node.storage
+                .delete_blob_data(blob_id)
+                .await
+                .context("failed to delete the data of an expired blob")?;
+            node.local_events.emit(NodeEvent::BlobDataDeleted {
+                blob_id: *blob_id,
+                reason: BlobDataDeletionReason::Expired,
+            });
+        }
+    }
+
+    tracing::info!(
+        %epoch,
+        n_expired_blobs = expired_blob_ids.len(),
+        delete_data,
+        "processed the blobs expired in the epoch"
+    );
+    Ok(expired_blob_ids.len())
+}
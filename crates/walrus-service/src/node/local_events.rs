@@ -0,0 +1,46 @@
+// Copyright (c) Walrus Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Broadcasting of events about the data stored locally by the node.
+//!
+//! The events are streamed to operators through the node's event stream, see
+//! [`NodeEvent`] for the emitted events. Events are only buffered for active subscribers, and
+//! subscribers that fall behind by more than the capacity of the channel miss events.
+
+use tokio::sync::broadcast;
+use walrus_rest_client::api::NodeEvent;
+
+/// The number of events buffered for each subscriber.
+const LOCAL_EVENTS_CHANNEL_CAPACITY: usize = 1024;
+
+/// Broadcasts [`NodeEvent`]s to all subscribers of the node's event stream.
+#[derive(Debug, Clone)]
+pub(crate) struct LocalEventSender {
+    sender: broadcast::Sender<NodeEvent>,
+}
+
+impl LocalEventSender {
+    pub fn new() -> Self {
+        Self {
+            sender: broadcast::Sender::new(LOCAL_EVENTS_CHANNEL_CAPACITY),
+        }
+    }
+
+    /// Sends the event to all current subscribers.
+    pub fn emit(&self, event: NodeEvent) {
+        // An error only indicates that there are currently no subscribers.
+        let _ = self.sender.send(event);
+    }
+
+    /// Returns true if there is at least one subscriber.
+    ///
+    /// Can be used to skip the construction of events that are expensive to compute.
+    pub fn has_subscribers(&self) -> bool {
+        self.sender.receiver_count() > 0
+    }
+
+    /// Returns a receiver for all events emitted from now on.
+    pub fn subscribe(&self) -> broadcast::Receiver<NodeEvent> {
+        self.sender.subscribe()
+    }
+}
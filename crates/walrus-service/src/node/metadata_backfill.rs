@@ -0,0 +1,378 @@
+// Copyright (c) Walrus Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Reconstruction of missing or corrupted blob metadata.
+//!
+//! Partial deletions can leave certified blobs without metadata in the node's database, or with
+//! metadata that cannot be decoded or does not match the blob ID. [`backfill_metadata`] finds
+//! these blobs and restores their metadata without resyncing the shards: if the node stores enough
+//! primary slivers of a blob, the blob is decoded and the metadata is recomputed from it, otherwise
+//! the metadata is retrieved from the other members of the committee. In both cases, the metadata
+//! is verified against the blob ID before it is stored.
+
+use std::{
+    collections::{HashMap, HashSet},
+    fmt,
+};
+
+use anyhow::Context as _;
+use serde::Serialize;
+use typed_store::{rocks::MetricConf, TypedStoreError};
+use walrus_core::{
+    encoding::{EncodingConfig, EncodingConfigTrait as _, Primary},
+    metadata::{UnverifiedBlobMetadataWithId, VerifiedBlobMetadataWithId},
+    BlobId,
+    EncodingType,
+    Epoch,
+};
+use walrus_rest_client::client::Client;
+use walrus_sui::{
+    client::ReadClient,
+    types::{move_structs::Blob, Committee},
+};
+use walrus_utils::metrics::Registry;
+
+use super::{
+    config::StorageNodeConfig,
+    storage::{blob_info::PerObjectBlobInfoApi as _, Storage},
+};
+
+/// Options of [`backfill_metadata`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MetadataBackfillOptions {
+    /// Only report the blobs whose metadata needs to be restored, without restoring it.
+    pub dry_run: bool,
+    /// Only reconstruct metadata from the slivers stored by the node, without contacting other
+    /// storage nodes.
+    pub local_only: bool,
+}
+
+/// The blobs whose metadata was found to be missing or corrupted, and how it was restored.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MetadataBackfillReport {
+    /// The number of certified blobs whose metadata was checked.
+    pub checked_count: u64,
+    /// Blobs whose metadata is missing or corrupted.
+    pub damaged: Vec<BlobId>,
+    /// Blobs whose metadata was reconstructed from the slivers stored by the node.
+    pub restored_from_slivers: Vec<BlobId>,
+    /// Blobs whose metadata was retrieved from other storage nodes.
+    pub restored_from_peers: Vec<BlobId>,
+    /// Blobs whose metadata could not be restored.
+    pub unrecoverable: Vec<BlobId>,
+}
+
+impl fmt::Display for MetadataBackfillReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "checked {} blobs, {} with missing or corrupted metadata: {} restored from local \
+            slivers, {} restored from other storage nodes, {} unrecoverable",
+            self.checked_count,
+            self.damaged.len(),
+            self.restored_from_slivers.len(),
+            self.restored_from_peers.len(),
+            self.unrecoverable.len(),
+        )
+    }
+}
+
+/// Restores the missing or corrupted metadata of the certified blobs in the database of the node
+/// with the provided configuration.
+///
+/// The database is opened by this function, so the node must not be running.
+pub async fn backfill_metadata(
+    config: &StorageNodeConfig,
+    options: MetadataBackfillOptions,
+) -> anyhow::Result<MetadataBackfillReport> {
+    let read_client = config
+        .sui
+        .as_ref()
+        .context("the configuration does not contain a `sui` section")?
+        .new_read_client()
+        .await
+        .context("unable to connect to the Sui RPC node")?;
+    let committee = read_client
+        .current_committee()
+        .await
+        .context("unable to retrieve the current committee")?;
+
+    typed_store::metrics::DBMetrics::init(&prometheus::Registry::default());
+    let storage = Storage::open(
+        config.storage_path.as_path(),
+        config.db_config.clone(),
+        MetricConf::new("storage"),
+        Registry::default(),
+    )?;
+
+    let result = backfill_storage(&storage, config, &read_client, &committee, options).await;
+
+    // Leave the marker of an unclean shutdown for the node's integrity check if it was present
+    // before opening the database.
+    if !storage.was_shut_down_uncleanly() {
+        if let Err(error) = storage.mark_clean_shutdown() {
+            tracing::warn!(?error, "failed to mark the database as closed cleanly");
+        }
+    }
+    result
+}
+
+async fn backfill_storage(
+    storage: &Storage,
+    config: &StorageNodeConfig,
+    read_client: &impl ReadClient,
+    committee: &Committee,
+    options: MetadataBackfillOptions,
+) -> anyhow::Result<MetadataBackfillReport> {
+    let encoding_config = EncodingConfig::new(committee.n_shards());
+    let mut report = MetadataBackfillReport::default();
+    let certified_before = committee.epoch + 1;
+    for item in storage.certified_blob_info_iter_before_epoch(certified_before) {
+        let (blob_id, _) = item?;
+        report.checked_count += 1;
+        if !has_valid_metadata(storage, &blob_id, &encoding_config)? {
+            report.damaged.push(blob_id);
+        }
+    }
+    tracing::info!(
+        checked_count = report.checked_count,
+        damaged_count = report.damaged.len(),
+        "checked the metadata of certified blobs"
+    );
+    if options.dry_run || report.damaged.is_empty() {
+        return Ok(report);
+    }
+
+    let blob_objects = blob_objects(storage, read_client, &report.damaged, certified_before)
+        .await
+        .context("unable to retrieve the blob objects of the damaged blobs")?;
+    let peers = if options.local_only {
+        vec![]
+    } else {
+        peer_clients(config, committee)
+    };
+
+    for blob_id in report.damaged.clone() {
+        let from_slivers = match blob_objects.get(&blob_id) {
+            Some(blob) => {
+                reconstruct_from_slivers(
+                    storage,
+                    &encoding_config,
+                    &blob_id,
+                    blob.encoding_type,
+                    blob.size,
+                )
+                .await?
+            }
+            None => None,
+        };
+
+        let metadata = if let Some(metadata) = from_slivers {
+            report.restored_from_slivers.push(blob_id);
+            metadata
+        } else if let Some(metadata) = fetch_from_peers(&peers, &blob_id, &encoding_config).await {
+            report.restored_from_peers.push(blob_id);
+            metadata
+        } else {
+            tracing::warn!(walrus.blob_id = %blob_id, "unable to restore the metadata of the blob");
+            report.unrecoverable.push(blob_id);
+            continue;
+        };
+
+        storage.put_verified_metadata(&metadata).await?;
+        tracing::info!(walrus.blob_id = %blob_id, "restored the metadata of the blob");
+    }
+
+    Ok(report)
+}
+
+/// Returns true if the metadata of the blob is stored, can be decoded, and matches the blob ID.
+fn has_valid_metadata(
+    storage: &Storage,
+    blob_id: &BlobId,
+    encoding_config: &EncodingConfig,
+) -> Result<bool, TypedStoreError> {
+    let metadata = match storage.get_metadata(blob_id) {
+        Ok(Some(metadata)) => metadata,
+        Ok(None) => return Ok(false),
+        Err(TypedStoreError::SerializationError(error)) => {
+            tracing::debug!(walrus.blob_id = %blob_id, %error, "unable to decode the metadata");
+            return Ok(false);
+        }
+        Err(error) => return Err(error),
+    };
+
+    let metadata = UnverifiedBlobMetadataWithId::new(*blob_id, metadata.metadata().clone());
+    Ok(metadata.verify(encoding_config).is_ok())
+}
+
+/// Returns the on-chain objects of the specified blobs, which provide their size and encoding.
+async fn blob_objects(
+    storage: &Storage,
+    read_client: &impl ReadClient,
+    blob_ids: &[BlobId],
+    certified_before: Epoch,
+) -> anyhow::Result<HashMap<BlobId, Blob>> {
+    let mut remaining: HashSet<_> = blob_ids.iter().copied().collect();
+    let mut objects = HashMap::new();
+
+    for item in storage.certified_per_object_blob_info_iter_before_epoch(certified_before) {
+        let (object_id, blob_info) = item?;
+        if !remaining.remove(&blob_info.blob_id()) {
+            continue;
+        }
+        match read_client.get_blob_by_object_id(&object_id).await {
+            Ok(blob) => {
+                objects.insert(blob_info.blob_id(), blob.blob);
+            }
+            Err(error) => {
+                // Another object may exist for the blob.
+                tracing::debug!(%object_id, %error, "unable to retrieve the blob object");
+                remaining.insert(blob_info.blob_id());
+            }
+        }
+        if remaining.is_empty() {
+            break;
+        }
+    }
+
+    Ok(objects)
+}
+
+/// Decodes the blob from the primary slivers stored in all shards of the node and recomputes its
+/// metadata.
+///
+/// Returns `None` if the node does not store enough primary slivers of the blob, or if the decoded
+/// blob does not match the blob ID.
+async fn reconstruct_from_slivers(
+    storage: &Storage,
+    encoding_config: &EncodingConfig,
+    blob_id: &BlobId,
+    encoding_type: EncodingType,
+    blob_size: u64,
+) -> Result<Option<VerifiedBlobMetadataWithId>, TypedStoreError> {
+    let config = encoding_config.get_for_type(encoding_type);
+
+    let mut slivers = vec![];
+    for shard in storage.existing_shard_storages().await {
+        if let Some(sliver) = shard.get_primary_sliver(blob_id)? {
+            slivers.push(sliver);
+        }
+    }
+    if slivers.len() < usize::from(config.n_source_symbols::<Primary>().get()) {
+        tracing::debug!(
+            walrus.blob_id = %blob_id,
+            n_slivers = slivers.len(),
+            "not enough primary slivers are stored to decode the blob"
+        );
+        return Ok(None);
+    }
+
+    let mut decoder = match config.get_blob_decoder::<Primary>(blob_size) {
+        Ok(decoder) => decoder,
+        Err(error) => {
+            tracing::warn!(walrus.blob_id = %blob_id, %error, "unable to create a blob decoder");
+            return Ok(None);
+        }
+    };
+    match decoder.decode_and_verify(blob_id, slivers) {
+        Ok(Some((_blob, metadata))) => Ok(Some(metadata)),
+        Ok(None) => Ok(None),
+        Err(error) => {
+            tracing::warn!(
+                walrus.blob_id = %blob_id,
+                %error,
+                "the slivers stored by the node do not decode to the blob"
+            );
+            Ok(None)
+        }
+    }
+}
+
+/// Returns clients for the other members of the committee.
+///
+/// The protocol key pair of the node must be loaded.
+fn peer_clients(config: &StorageNodeConfig, committee: &Committee) -> Vec<Client> {
+    let own_public_key = config.protocol_key_pair().public();
+    committee
+        .members()
+        .iter()
+        .filter(|member| &member.public_key != own_public_key)
+        .filter_map(|member| {
+            Client::builder()
+                .authenticate_with_public_key(member.network_public_key.clone())
+                .build(&member.network_address.0)
+                .inspect_err(|error| {
+                    tracing::debug!(node = %member.name, %error, "unable to create a client");
+                })
+                .ok()
+        })
+        .collect()
+}
+
+/// Retrieves the metadata from the first peer that returns metadata matching the blob ID.
+async fn fetch_from_peers(
+    peers: &[Client],
+    blob_id: &BlobId,
+    encoding_config: &EncodingConfig,
+) -> Option<VerifiedBlobMetadataWithId> {
+    for peer in peers {
+        match peer.get_and_verify_metadata(blob_id, encoding_config).await {
+            Ok(metadata) => return Some(metadata),
+            Err(error) => {
+                tracing::debug!(walrus.blob_id = %blob_id, %error, "a peer did not return metadata")
+            }
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use walrus_core::{test_utils, ShardIndex, Sliver, DEFAULT_ENCODING};
+    use walrus_sui::{test_utils::EventForTesting as _, types::BlobCertified};
+    use walrus_test_utils::Result as TestResult;
+
+    use super::*;
+    use crate::test_utils::empty_storage_with_shards;
+
+    #[tokio::test]
+    async fn reconstructs_metadata_from_stored_primary_slivers() -> TestResult {
+        let encoding_config = test_utils::encoding_config();
+        let n_shards = encoding_config.n_shards();
+        let blob = walrus_test_utils::random_data(314);
+        let (sliver_pairs, metadata) = encoding_config
+            .get_for_type(DEFAULT_ENCODING)
+            .encode_with_metadata(&blob)?;
+        let blob_id = *metadata.blob_id();
+
+        let shards: Vec<_> = (0..n_shards.get()).map(ShardIndex).collect();
+        let storage = empty_storage_with_shards(&shards).await;
+        let storage = storage.as_ref();
+        storage.update_blob_info(0, &BlobCertified::for_testing(blob_id).into())?;
+
+        for pair in sliver_pairs {
+            let shard = pair.index().to_shard_index(n_shards, &blob_id);
+            storage
+                .shard_storage(shard)
+                .await
+                .expect("shard is present")
+                .put_sliver(blob_id, Sliver::Primary(pair.primary))
+                .await?;
+        }
+        assert!(!has_valid_metadata(storage, &blob_id, &encoding_config)?);
+
+        let reconstructed = reconstruct_from_slivers(
+            storage,
+            &encoding_config,
+            &blob_id,
+            DEFAULT_ENCODING,
+            blob.len().try_into()?,
+        )
+        .await?;
+
+        assert_eq!(reconstructed, Some(metadata));
+        Ok(())
+    }
+}
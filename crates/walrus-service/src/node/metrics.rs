@@ -83,6 +83,22 @@ walrus_utils::metrics::define_metric_set! {
         #[help = "Total number of sliver instances returned"]
         slivers_retrieved_total: IntCounterVec["sliver_type"],
 
+        #[help = "The total number of stored slivers that did not match their checksum when read, \
+                  by whether they passed the full verification against the metadata"]
+        sliver_checksum_mismatches_total: IntCounterVec["sliver_type", "outcome"],
+
+        #[help = "The total number of metadata or sliver reads rejected due to blob expiry"]
+        expired_blob_reads_rejected_total: IntCounter[],
+
+        #[help = "The total number of blocked blobs whose local data was deleted"]
+        blocked_blob_data_deleted_total: IntCounter[],
+
+        #[help = "The total number of inconsistency proofs verified"]
+        inconsistency_proofs_verified_total: IntCounter[],
+
+        #[help = "The total number of invalid blob attestations served from the cache"]
+        invalid_blob_attestations_cached_total: IntCounter[],
+
         #[help = "The number of Walrus events processed"]
         event_cursor_progress: U64GaugeVec["state"],
 
@@ -258,6 +274,7 @@ impl TelemetryLabel for ClientErrorKind {
             ClientErrorKind::CertificationFailed(_) => "certification-failed",
             ClientErrorKind::NotEnoughConfirmations(_, _) => "not-enough-confirmations",
             ClientErrorKind::NotEnoughSlivers => "not-enough-slivers",
+            ClientErrorKind::NotEnoughValidSlivers(_) => "not-enough-valid-slivers",
             ClientErrorKind::BlobIdDoesNotExist => "blob-id-does-not-exist",
             ClientErrorKind::NoMetadataReceived => "no-metadata-received",
             ClientErrorKind::NoValidStatusReceived => "no-valid-status-received",
@@ -271,8 +288,13 @@ impl TelemetryLabel for ClientErrorKind {
             ClientErrorKind::CommitteeChangeNotified => "committee-change-notified",
             ClientErrorKind::StakeBelowThreshold(_) => "stake-below-threshold",
             ClientErrorKind::FailedToLoadCerts(_) => "failed-to-load-certs",
+            ClientErrorKind::FailedToLoadRootCertificates { .. } => {
+                "failed-to-load-root-certificates"
+            }
             ClientErrorKind::Other(_) => "unknown",
             ClientErrorKind::StoreBlobInternal(_) => "store-blob-internal",
+            ClientErrorKind::RangeNotSatisfiable { .. } => "range-not-satisfiable",
+            ClientErrorKind::InvalidQuilt(_) => "invalid-quilt",
         }
     }
 }
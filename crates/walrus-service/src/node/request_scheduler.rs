@@ -0,0 +1,334 @@
+// Copyright (c) Walrus Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Scheduling of the node's work according to its priority class.
+//!
+//! Each [`RequestClass`] has its own concurrency limit and queue of waiting requests, and all
+//! classes share a limit on the total number of requests in progress. When capacity becomes
+//! available, waiting requests are started using stride scheduling, such that each class with
+//! waiting requests receives a share of the capacity proportional to its weight. Ties are broken
+//! in favour of the class with the higher priority.
+
+use std::{
+    collections::VecDeque,
+    fmt,
+    sync::{Arc, Mutex},
+};
+
+use tokio::sync::oneshot;
+
+use super::config::{RequestClassConfig, RequestSchedulerConfig};
+
+/// The stride of a class with weight one; the stride of a class is this value divided by its
+/// weight.
+const BASE_STRIDE: u64 = 1 << 32;
+
+/// The priority class of work performed by the node, from highest to lowest priority.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum RequestClass {
+    /// Reads of slivers and metadata by clients.
+    UserRead = 0,
+    /// Writes of slivers and metadata by clients.
+    UserWrite = 1,
+    /// Serving shard syncs and recovery symbols to other nodes.
+    ///
+    /// The node's own syncs are not scheduled, as they depend on other nodes serving their
+    /// requests in this class.
+    Sync = 2,
+    /// Background consistency checks of the stored data.
+    Scrubbing = 3,
+}
+
+impl RequestClass {
+    const ALL: [Self; 4] = [Self::UserRead, Self::UserWrite, Self::Sync, Self::Scrubbing];
+}
+
+/// Schedules work with weighted queues and separate concurrency limits for each [`RequestClass`].
+#[derive(Debug, Clone)]
+pub(crate) struct RequestScheduler {
+    state: Arc<Mutex<SchedulerState>>,
+}
+
+impl RequestScheduler {
+    pub fn new(config: &RequestSchedulerConfig) -> Self {
+        let class_state = |config: &RequestClassConfig| ClassState {
+            stride: BASE_STRIDE / u64::from(config.weight.max(1)),
+            max_concurrent: config.max_concurrent,
+            in_progress: 0,
+            pass: 0,
+            waiting: VecDeque::new(),
+        };
+
+        Self {
+            state: Arc::new(Mutex::new(SchedulerState {
+                max_concurrent: config.max_concurrent_requests,
+                in_progress: 0,
+                virtual_time: 0,
+                classes: [
+                    class_state(&config.user_reads),
+                    class_state(&config.user_writes),
+                    class_state(&config.sync),
+                    class_state(&config.scrubbing),
+                ],
+            })),
+        }
+    }
+
+    /// Waits until a request of the specified class can be started, and returns a permit that
+    /// must be held while the request is in progress.
+    pub async fn acquire(&self, class: RequestClass) -> RequestPermit {
+        let receiver = {
+            let mut state = self.state.lock().expect("mutex should not be poisoned");
+            state.catch_up(class);
+
+            if state.class(class).waiting.is_empty() && state.has_capacity(class) {
+                state.start(class);
+                return RequestPermit::new(self.state.clone(), class);
+            }
+
+            let (sender, receiver) = oneshot::channel();
+            state.class_mut(class).waiting.push_back(sender);
+            receiver
+        };
+
+        receiver
+            .await
+            .expect("waiting requests are only removed when sending a permit")
+    }
+
+    /// Returns the number of requests of the class that are currently in progress.
+    #[cfg(test)]
+    fn in_progress(&self, class: RequestClass) -> usize {
+        self.state
+            .lock()
+            .expect("mutex should not be poisoned")
+            .class(class)
+            .in_progress
+    }
+}
+
+/// A permit for a request in progress, which allows other requests to start when dropped.
+#[must_use]
+pub(crate) struct RequestPermit {
+    state: Option<Arc<Mutex<SchedulerState>>>,
+    class: RequestClass,
+}
+
+impl RequestPermit {
+    fn new(state: Arc<Mutex<SchedulerState>>, class: RequestClass) -> Self {
+        Self {
+            state: Some(state),
+            class,
+        }
+    }
+}
+
+impl fmt::Debug for RequestPermit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RequestPermit")
+            .field("class", &self.class)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Drop for RequestPermit {
+    fn drop(&mut self) {
+        let Some(state_mutex) = self.state.take() else {
+            return;
+        };
+
+        // Permits of requests that were cancelled while waiting are dropped outside of the lock.
+        let _cancelled = {
+            let mut state = state_mutex.lock().expect("mutex should not be poisoned");
+            state.finish(self.class);
+            state.start_waiting(&state_mutex)
+        };
+    }
+}
+
+#[derive(Debug)]
+struct SchedulerState {
+    max_concurrent: usize,
+    in_progress: usize,
+    /// The pass of the class that was most recently started.
+    virtual_time: u64,
+    classes: [ClassState; RequestClass::ALL.len()],
+}
+
+#[derive(Debug)]
+struct ClassState {
+    stride: u64,
+    max_concurrent: usize,
+    in_progress: usize,
+    /// The virtual time at which the next request of the class is started.
+    pass: u64,
+    waiting: VecDeque<oneshot::Sender<RequestPermit>>,
+}
+
+impl SchedulerState {
+    fn class(&self, class: RequestClass) -> &ClassState {
+        &self.classes[class as usize]
+    }
+
+    fn class_mut(&mut self, class: RequestClass) -> &mut ClassState {
+        &mut self.classes[class as usize]
+    }
+
+    fn has_capacity(&self, class: RequestClass) -> bool {
+        let class = self.class(class);
+        self.in_progress < self.max_concurrent && class.in_progress < class.max_concurrent
+    }
+
+    /// Prevents a class that had no waiting requests from accumulating credit while it was idle.
+    fn catch_up(&mut self, class: RequestClass) {
+        let virtual_time = self.virtual_time;
+        let class = self.class_mut(class);
+        if class.waiting.is_empty() {
+            class.pass = class.pass.max(virtual_time);
+        }
+    }
+
+    fn start(&mut self, class: RequestClass) {
+        self.in_progress += 1;
+        let class = &mut self.classes[class as usize];
+        class.in_progress += 1;
+        self.virtual_time = class.pass;
+        class.pass += class.stride;
+    }
+
+    fn finish(&mut self, class: RequestClass) {
+        self.in_progress -= 1;
+        self.class_mut(class).in_progress -= 1;
+    }
+
+    /// Starts waiting requests while there is capacity, and returns the permits of requests that
+    /// were cancelled while waiting.
+    fn start_waiting(&mut self, state_mutex: &Arc<Mutex<SchedulerState>>) -> Vec<RequestPermit> {
+        let mut cancelled = vec![];
+        while let Some(class) = self.next_class() {
+            let sender = self
+                .class_mut(class)
+                .waiting
+                .pop_front()
+                .expect("the class has waiting requests");
+            self.start(class);
+            if let Err(permit) = sender.send(RequestPermit::new(state_mutex.clone(), class)) {
+                cancelled.push(permit);
+            }
+        }
+        cancelled
+    }
+
+    /// Returns the class with waiting requests and capacity that has the lowest pass.
+    fn next_class(&self) -> Option<RequestClass> {
+        RequestClass::ALL
+            .into_iter()
+            .filter(|&class| !self.class(class).waiting.is_empty() && self.has_capacity(class))
+            .min_by_key(|&class| self.class(class).pass)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use tokio::{task::JoinSet, time};
+
+    use super::*;
+
+    fn config(max_concurrent_requests: usize, weights: [u32; 4]) -> RequestSchedulerConfig {
+        let class = |weight| RequestClassConfig {
+            weight,
+            max_concurrent: max_concurrent_requests,
+        };
+        RequestSchedulerConfig {
+            max_concurrent_requests,
+            user_reads: class(weights[0]),
+            user_writes: class(weights[1]),
+            sync: class(weights[2]),
+            scrubbing: class(weights[3]),
+        }
+    }
+
+    #[tokio::test]
+    async fn limits_the_concurrency_of_each_class() {
+        let mut config = config(2, [1, 1, 1, 1]);
+        config.sync.max_concurrent = 1;
+        let scheduler = RequestScheduler::new(&config);
+
+        let _sync_permit = scheduler.acquire(RequestClass::Sync).await;
+        let waiting_sync = time::timeout(
+            Duration::from_millis(10),
+            scheduler.acquire(RequestClass::Sync),
+        )
+        .await;
+        assert!(waiting_sync.is_err());
+
+        let _read_permit = scheduler.acquire(RequestClass::UserRead).await;
+        assert_eq!(scheduler.in_progress(RequestClass::Sync), 1);
+        assert_eq!(scheduler.in_progress(RequestClass::UserRead), 1);
+    }
+
+    #[tokio::test]
+    async fn starts_waiting_requests_in_proportion_to_their_weight() {
+        let scheduler = RequestScheduler::new(&config(1, [3, 1, 1, 1]));
+        let blocking_permit = scheduler.acquire(RequestClass::Scrubbing).await;
+
+        let (sender, mut receiver) = tokio::sync::mpsc::unbounded_channel();
+        let mut requests = JoinSet::new();
+        for class in [RequestClass::Sync, RequestClass::UserRead] {
+            for _ in 0..8 {
+                let scheduler = scheduler.clone();
+                let sender = sender.clone();
+                requests.spawn(async move {
+                    let _permit = scheduler.acquire(class).await;
+                    sender.send(class).expect("the receiver is not dropped");
+                });
+            }
+        }
+        // Wait for all requests to be queued.
+        while scheduler.state.lock().unwrap().classes[..3]
+            .iter()
+            .map(|class| class.waiting.len())
+            .sum::<usize>()
+            < 16
+        {
+            tokio::task::yield_now().await;
+        }
+
+        drop(blocking_permit);
+        requests.join_all().await;
+        drop(sender);
+
+        let mut order = vec![];
+        while let Some(class) = receiver.recv().await {
+            order.push(class);
+        }
+        let n_reads_among_first_eight = order[..8]
+            .iter()
+            .filter(|&&class| class == RequestClass::UserRead)
+            .count();
+        assert_eq!(n_reads_among_first_eight, 6);
+    }
+
+    #[tokio::test]
+    async fn skips_requests_cancelled_while_waiting() {
+        let scheduler = RequestScheduler::new(&config(1, [1, 1, 1, 1]));
+        let permit = scheduler.acquire(RequestClass::UserWrite).await;
+
+        let cancelled = time::timeout(
+            Duration::from_millis(10),
+            scheduler.acquire(RequestClass::UserWrite),
+        )
+        .await;
+        assert!(cancelled.is_err());
+        drop(permit);
+
+        let _permit = time::timeout(
+            Duration::from_secs(1),
+            scheduler.acquire(RequestClass::UserWrite),
+        )
+        .await
+        .expect("the cancelled request does not hold the capacity");
+    }
+}
@@ -3,13 +3,16 @@
 
 //! Server for the Walrus service.
 
-use std::{net::SocketAddr, ops::Deref, sync::Arc, time::Duration};
+use std::{future::Future, net::SocketAddr, ops::Deref, sync::Arc, time::Duration};
 
 use anyhow::{anyhow, Context};
 use axum::{
     extract::DefaultBodyLimit,
+    http::HeaderValue,
     middleware,
+    response::Response,
     routing::{get, post, put},
+    Extension,
     Router,
 };
 use axum_server::{tls_rustls::RustlsConfig, Handle};
@@ -29,19 +32,40 @@ use tracing::Instrument as _;
 use utoipa::OpenApi as _;
 use utoipa_redoc::{Redoc, Servable as _};
 use walrus_core::{encoding, keys::NetworkKeyPair};
+use walrus_rest_client::api::{ApiVersion, API_VERSION_HEADER};
 use walrus_utils::metrics::Registry;
 
-use self::telemetry::MetricsMiddlewareState;
-use super::config::{defaults, Http2Config, PathOrInPlace, StorageNodeConfig, TlsConfig};
+use self::{
+    bandwidth::BandwidthLimiters,
+    idempotency::IdempotencyCache,
+    rate_limit::PeerRateLimiter,
+    slow_log::SlowRequestLog,
+    telemetry::MetricsMiddlewareState,
+};
+use super::config::{
+    defaults,
+    BandwidthLimitsConfig,
+    Http2Config,
+    IdempotencyConfig,
+    PathOrInPlace,
+    PeerRateLimitConfig,
+    SlowRequestLogConfig,
+    StorageNodeConfig,
+    TlsConfig,
+};
 use crate::{
     common::telemetry::{self, MakeHttpSpan},
     node::ServiceState,
 };
 
+mod bandwidth;
 mod extract;
+mod idempotency;
 mod openapi;
+mod rate_limit;
 mod responses;
 mod routes;
+pub(crate) mod slow_log;
 
 /// Additional space to be added to the maximum body size accepted by the server.
 ///
@@ -70,6 +94,26 @@ pub struct RestApiConfig {
 
     /// Configuration of HTTP/2 connections.
     pub http2_config: Http2Config,
+
+    /// Limits on the rate at which a single peer can submit inconsistency proofs.
+    pub inconsistency_proof_rate_limit: PeerRateLimitConfig,
+
+    /// Whether to serve the stream of events about the data stored by the node.
+    pub enable_event_stream: bool,
+
+    /// The socket address on which to serve the admin API, if any.
+    ///
+    /// The admin API is served over plain HTTP, separately from the public API.
+    pub admin_api_address: Option<SocketAddr>,
+
+    /// Configuration of the log of slow requests.
+    pub slow_request_log: SlowRequestLogConfig,
+
+    /// Limits on the bandwidth used for serving, and for ingesting and syncing data.
+    pub bandwidth_limits: BandwidthLimitsConfig,
+
+    /// Configuration of the results retained for the idempotency keys of write requests.
+    pub idempotency: IdempotencyConfig,
 }
 
 impl From<&StorageNodeConfig> for RestApiConfig {
@@ -108,6 +152,15 @@ impl From<&StorageNodeConfig> for RestApiConfig {
             tls_certificate,
             graceful_shutdown_period,
             http2_config: config.rest_server.http2_config.clone(),
+            inconsistency_proof_rate_limit: config
+                .rest_server
+                .inconsistency_proof_rate_limit
+                .clone(),
+            enable_event_stream: config.rest_server.enable_event_stream,
+            admin_api_address: config.rest_server.admin_api_address,
+            slow_request_log: config.rest_server.slow_request_log.clone(),
+            bandwidth_limits: config.rest_server.bandwidth_limits.clone(),
+            idempotency: config.rest_server.idempotency.clone(),
         }
     }
 }
@@ -150,8 +203,12 @@ pub struct RestApiServer<S> {
     state: Arc<S>,
     config: RestApiConfig,
     metrics: MetricsMiddlewareState,
+    slow_request_log: Arc<SlowRequestLog>,
+    bandwidth_limiters: Arc<BandwidthLimiters>,
+    idempotency_cache: Arc<IdempotencyCache>,
     cancel_token: CancellationToken,
     handle: Mutex<Option<Handle>>,
+    admin_handle: Mutex<Option<Handle>>,
 }
 
 impl<S> RestApiServer<S>
@@ -168,8 +225,12 @@ where
         Self {
             state,
             metrics: MetricsMiddlewareState::new(registry),
+            slow_request_log: Arc::new(SlowRequestLog::new(config.slow_request_log.clone())),
+            bandwidth_limiters: Arc::new(BandwidthLimiters::new(&config.bandwidth_limits)),
+            idempotency_cache: Arc::new(IdempotencyCache::new(&config.idempotency)),
             cancel_token,
             handle: Default::default(),
+            admin_handle: Default::default(),
             config,
         }
     }
@@ -186,6 +247,14 @@ where
                 self.metrics.clone(),
                 telemetry::metrics_middleware,
             ))
+            .layer(middleware::from_fn_with_state(
+                self.slow_request_log.clone(),
+                slow_log::slow_request_log_middleware,
+            ))
+            .layer(middleware::from_fn_with_state(
+                self.bandwidth_limiters.clone(),
+                bandwidth::bandwidth_limit_middleware,
+            ))
             .layer(
                 TraceLayer::new_for_http()
                     .make_span_with(MakeHttpSpan::new())
@@ -195,7 +264,8 @@ where
                     .on_failure(())
                     .on_response(MakeHttpSpan::new()),
             )
-            .layer(Self::cors_layer());
+            .layer(Self::cors_layer())
+            .layer(middleware::map_response(advertise_api_version));
 
         let app = self
             .define_routes()
@@ -203,6 +273,9 @@ where
             .layer(request_layers)
             .into_make_service_with_connect_info::<SocketAddr>();
 
+        // The admin API is bound before the handle of the public API is set, so that the server is
+        // only reported as ready once both are listening.
+        let admin_server = self.admin_api_server().await;
         let handle = self.init_handle().await;
 
         let server = if let Some(tls_config) = self.configure_tls().await? {
@@ -223,10 +296,49 @@ where
             .in_current_span(),
         );
 
-        server
-            .inspect(|_| tracing::info!("server run has completed"))
-            .await
-            .map_err(|error| anyhow!(error))
+        let server = server.inspect(|_| tracing::info!("server run has completed"));
+        match admin_server {
+            Some(admin_server) => tokio::try_join!(server, admin_server).map(|_| ()),
+            None => server.await,
+        }
+        .map_err(|error| anyhow!(error))
+    }
+
+    /// Returns the server for the admin API if an address is configured for it.
+    ///
+    /// The admin API is served over plain HTTP, on a listener separate from the public API.
+    async fn admin_api_server(
+        &self,
+    ) -> Option<impl Future<Output = std::io::Result<()>> + Send + 'static> {
+        let admin_api_address = self.config.admin_api_address?;
+
+        let app = self
+            .define_admin_routes()
+            .with_state(self.state.clone())
+            .layer(
+                TraceLayer::new_for_http()
+                    .make_span_with(MakeHttpSpan::new())
+                    .on_response(MakeHttpSpan::new()),
+            )
+            .into_make_service_with_connect_info::<SocketAddr>();
+
+        let handle = Handle::new();
+        *self.admin_handle.lock().await = Some(handle.clone());
+        tokio::spawn(
+            Self::handle_shutdown_signal(
+                handle.clone(),
+                self.cancel_token.clone(),
+                self.config.graceful_shutdown_period,
+            )
+            .in_current_span(),
+        );
+
+        Some(
+            axum_server::bind(admin_api_address)
+                .handle(handle)
+                .serve(app)
+                .inspect(|_| tracing::info!("admin API server run has completed")),
+        )
     }
 
     fn configure_server<A>(&self, mut server: axum_server::Server<A>) -> axum_server::Server<A> {
@@ -329,17 +441,23 @@ where
             .expect("we only break if the handle is some")
             .listening()
             .await;
+
+        if let Some(admin_handle) = self.admin_handle.lock().await.as_ref() {
+            let _ = admin_handle.listening().await;
+        }
     }
 
     fn define_routes(&self) -> Router<Arc<S>> {
-        Router::new()
+        let router = Router::new()
             .merge(Redoc::with_url(
                 routes::API_DOCS_ENDPOINT,
                 RestApiDoc::openapi(),
             ))
             .route(
                 routes::METADATA_ENDPOINT,
-                get(routes::get_metadata).put(routes::put_metadata),
+                get(routes::get_metadata)
+                    .put(routes::put_metadata)
+                    .layer(Extension(self.idempotency_cache.clone())),
             )
             .route(
                 routes::METADATA_STATUS_ENDPOINT,
@@ -355,7 +473,8 @@ where
                         .expect("running on 64bit arch (see hardware requirements)")
                             + HEADROOM,
                     ))
-                    .get(routes::get_sliver),
+                    .get(routes::get_sliver)
+                    .layer(Extension(self.idempotency_cache.clone())),
             )
             .route(
                 routes::SLIVER_STATUS_ENDPOINT,
@@ -384,13 +503,51 @@ where
                 routes::RECOVERY_SYMBOL_LIST_ENDPOINT,
                 get(routes::list_recovery_symbols),
             )
+            .route(
+                routes::RECOVERY_SYMBOL_BATCH_ENDPOINT,
+                post(routes::list_recovery_symbols_batch),
+            )
             .route(
                 routes::INCONSISTENCY_PROOF_ENDPOINT,
-                post(routes::inconsistency_proof),
+                post(routes::inconsistency_proof).layer(Extension(Arc::new(PeerRateLimiter::new(
+                    &self.config.inconsistency_proof_rate_limit,
+                )))),
             )
             .route(routes::BLOB_STATUS_ENDPOINT, get(routes::get_blob_status))
             .route(routes::HEALTH_ENDPOINT, get(routes::health_info))
-            .route(routes::SYNC_SHARD_ENDPOINT, post(routes::sync_shard))
+            .route(routes::CAPABILITIES_ENDPOINT, get(routes::get_capabilities))
+            .route(
+                routes::STORED_BLOBS_ENDPOINT,
+                get(routes::list_stored_blobs),
+            )
+            .route(routes::SYNC_SHARD_ENDPOINT, post(routes::sync_shard));
+
+        if self.config.enable_event_stream {
+            router.route(routes::EVENTS_ENDPOINT, get(routes::stream_events))
+        } else {
+            router
+        }
+    }
+
+    fn define_admin_routes(&self) -> Router<Arc<S>> {
+        Router::new()
+            .route(
+                routes::EPOCH_ACCOUNTING_ENDPOINT,
+                get(routes::get_epoch_accounting_report),
+            )
+            .route(
+                routes::SLOW_REQUESTS_ENDPOINT,
+                get(routes::get_slow_requests).layer(Extension(self.slow_request_log.clone())),
+            )
+            .route(
+                routes::SHARD_OWNERSHIP_AUDIT_ENDPOINT,
+                get(routes::get_shard_ownership_audit),
+            )
+            .route(
+                routes::EPOCH_HANDOVER_ENDPOINT,
+                get(routes::get_epoch_handover),
+            )
+            .layer(middleware::map_response(advertise_api_version))
     }
 
     /// Returns the CORS leayer for the server.
@@ -402,6 +559,14 @@ where
     }
 }
 
+/// Adds the API version implemented by the node to the headers of the response.
+async fn advertise_api_version(mut response: Response) -> Response {
+    response
+        .headers_mut()
+        .insert(API_VERSION_HEADER, HeaderValue::from(ApiVersion::CURRENT.0));
+    response
+}
+
 fn create_self_signed_certificate(
     key_pair: &NetworkKeyPair,
     public_server_name: String,
@@ -442,7 +607,7 @@ mod tests {
     use fastcrypto::traits::KeyPair;
     use p256::pkcs8::LineEnding;
     use rcgen::{BasicConstraints, Certificate as RcGenCertificate, CertifiedKey, IsCa};
-    use tokio::{task::JoinHandle, time::Duration};
+    use tokio::{sync::broadcast, task::JoinHandle, time::Duration};
     use tokio_util::sync::CancellationToken;
     use walrus_core::{
         encoding::{EncodingAxis, GeneralRecoverySymbol, Primary, Secondary},
@@ -455,6 +620,7 @@ mod tests {
         messages::{
             BlobPersistenceType,
             InvalidBlobIdAttestation,
+            SignedEpochAccountingReport,
             SignedMessage,
             StorageConfirmation,
             SyncShardMsg,
@@ -462,9 +628,11 @@ mod tests {
         },
         metadata::{UnverifiedBlobMetadataWithId, VerifiedBlobMetadataWithId},
         BlobId,
+        Epoch,
         InconsistencyProof,
         PublicKey,
         RecoverySymbol,
+        ShardIndex,
         Sliver,
         SliverIndex,
         SliverPairIndex,
@@ -473,13 +641,22 @@ mod tests {
     };
     use walrus_rest_client::{
         api::{
+            ApiFeature,
             BlobStatus,
             DeletableCounts,
+            EpochHandoverInfo,
+            EpochHandoverPhase,
+            IdempotencyKey,
+            NodeEvent,
             ServiceHealthInfo,
+            ShardOwnershipAudit,
             ShardStatusSummary,
+            StoredBlobsPage,
             StoredOnNodeStatus,
+            IDEMPOTENCY_KEY_HEADER,
         },
         client::{Client, ClientBuilder, RecoverySymbolsFilter},
+        error::NodeErrorKind,
     };
     use walrus_sui::test_utils::event_id_for_testing;
     use walrus_test_utils::{async_param_test, Result as TestResult, WithTempDir};
@@ -488,9 +665,10 @@ mod tests {
     use crate::{
         node::{
             config::StorageNodeConfig,
-            errors::ListSymbolsError,
+            errors::{ListStoredBlobsError, ListSymbolsError, ShardNotAssigned},
             BlobStatusError,
             ComputeStorageConfirmationError,
+            EpochAccountingError,
             InconsistencyProofError,
             RetrieveMetadataError,
             RetrieveSliverError,
@@ -703,6 +881,73 @@ mod tests {
         ) -> Result<SyncShardResponse, SyncShardServiceError> {
             Ok(SyncShardResponse::V1(vec![]))
         }
+
+        /// Returns a receiver with a single event, after which the stream is closed.
+        fn subscribe_to_local_events(&self) -> broadcast::Receiver<NodeEvent> {
+            let (sender, receiver) = broadcast::channel(1);
+            sender
+                .send(NodeEvent::BlobStored {
+                    blob_id: BlobId([7; 32]),
+                })
+                .expect("the receiver is not dropped");
+            receiver
+        }
+
+        /// Returns a report only for epoch 0, and otherwise an error.
+        async fn epoch_accounting_report(
+            &self,
+            epoch: Epoch,
+        ) -> Result<SignedEpochAccountingReport, EpochAccountingError> {
+            if epoch == 0 {
+                Ok(walrus_core::test_utils::random_signed_message())
+            } else {
+                Err(EpochAccountingError::NotFound(epoch))
+            }
+        }
+
+        async fn shard_ownership_audit(&self) -> ShardOwnershipAudit {
+            ShardOwnershipAudit {
+                epoch: 1,
+                assigned: vec![ShardIndex(0)],
+                missing: vec![ShardIndex(0)],
+                ..Default::default()
+            }
+        }
+
+        fn epoch_handover(&self) -> Option<EpochHandoverInfo> {
+            Some(EpochHandoverInfo {
+                epoch: 1,
+                phase: EpochHandoverPhase::Syncing,
+                outgoing_shards: vec![],
+                incoming_shards: vec![ShardIndex(0)],
+            })
+        }
+
+        /// Stores three blobs for shard 0 and returns an error for any other shard.
+        async fn list_stored_blobs(
+            &self,
+            shard_index: ShardIndex,
+            cursor: Option<BlobId>,
+            limit: usize,
+        ) -> Result<StoredBlobsPage, ListStoredBlobsError> {
+            if shard_index != ShardIndex(0) {
+                return Err(ShardNotAssigned(shard_index, 0).into());
+            }
+            let mut blob_ids: Vec<_> = (1..=3)
+                .map(|i| BlobId([i; 32]))
+                .filter(|blob_id| cursor.is_none_or(|cursor| *blob_id > cursor))
+                .collect();
+            let next_cursor = if blob_ids.len() > limit {
+                blob_ids.truncate(limit);
+                blob_ids.last().copied()
+            } else {
+                None
+            };
+            Ok(StoredBlobsPage {
+                blob_ids,
+                next_cursor,
+            })
+        }
     }
 
     async fn start_rest_api_with_config(
@@ -896,6 +1141,34 @@ mod tests {
         assert_eq!(res.status(), StatusCode::CREATED);
     }
 
+    async_param_test! {
+        store_metadata_with_idempotency_key: [
+            valid: ("0123456789abcdef-_", StatusCode::CREATED),
+            invalid_character: ("key with spaces", StatusCode::BAD_REQUEST),
+            too_long: (&"a".repeat(IdempotencyKey::MAX_LENGTH + 1), StatusCode::BAD_REQUEST),
+        ]
+    }
+    async fn store_metadata_with_idempotency_key(key: &str, expected_status: StatusCode) {
+        let (config, _handle) = start_rest_api_with_test_config().await;
+
+        let metadata_with_blob_id = walrus_core::test_utils::unverified_blob_metadata();
+        let blob_id = metadata_with_blob_id.blob_id().to_string();
+        let path = routes::METADATA_ENDPOINT.replace("{blob_id}", &blob_id);
+        let url = format!("https://{}{path}", config.as_ref().rest_api_address);
+
+        let client = storage_node_client(config.as_ref()).into_inner();
+        for _ in 0..2 {
+            let res = client
+                .put(&url)
+                .header(IDEMPOTENCY_KEY_HEADER, key)
+                .body(bcs::to_bytes(metadata_with_blob_id.metadata()).unwrap())
+                .send()
+                .await
+                .unwrap();
+            assert_eq!(res.status(), expected_status);
+        }
+    }
+
     #[tokio::test]
     async fn retrieve_sliver() {
         let (config, _handle) = start_rest_api_with_test_config().await;
@@ -1097,8 +1370,199 @@ mod tests {
         assert_eq!(err.http_status_code(), Some(StatusCode::NOT_FOUND));
     }
 
+    #[tokio::test]
+    async fn streams_local_events_if_enabled() {
+        let mut config = test_utils::storage_node_config();
+        config.as_mut().rest_server.enable_event_stream = true;
+        let url = format!(
+            "https://{}{}",
+            config.as_ref().rest_api_address,
+            routes::EVENTS_ENDPOINT
+        );
+        let _handle = start_rest_api_with_config(config.as_ref()).await;
+        let client = storage_node_client(config.as_ref()).into_inner();
+        let body = client.get(&url).send().await.unwrap().text().await.unwrap();
+
+        let data = body
+            .strip_prefix("data: ")
+            .expect("the body contains a single event")
+            .trim_end();
+        let event: NodeEvent = serde_json::from_str(data).unwrap();
+        assert_eq!(
+            event,
+            NodeEvent::BlobStored {
+                blob_id: BlobId([7; 32])
+            }
+        );
+    }
+
+    /// Returns a test config with the admin API enabled, and the URL of the admin API.
+    fn storage_node_config_with_admin_api() -> (WithTempDir<StorageNodeConfig>, String) {
+        let mut config = test_utils::storage_node_config();
+        let admin_api_address = test_utils::unused_socket_address(false);
+        config.as_mut().rest_server.admin_api_address = Some(admin_api_address);
+        (config, format!("http://{admin_api_address}"))
+    }
+
+    async_param_test! {
+        serves_epoch_accounting_through_admin_api -> TestResult: [
+            known_epoch: (0, StatusCode::OK),
+            unknown_epoch: (1, StatusCode::NOT_FOUND),
+        ]
+    }
+    async fn serves_epoch_accounting_through_admin_api(
+        epoch: Epoch,
+        expected_status: StatusCode,
+    ) -> TestResult {
+        let (config, admin_url) = storage_node_config_with_admin_api();
+        let path = routes::EPOCH_ACCOUNTING_ENDPOINT.replace("{epoch}", &epoch.to_string());
+        let _handle = start_rest_api_with_config(config.as_ref()).await;
+
+        let response = reqwest::get(format!("{admin_url}{path}")).await?;
+
+        assert_eq!(response.status(), expected_status);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn does_not_serve_admin_api_on_public_api() -> TestResult {
+        let (config, _) = storage_node_config_with_admin_api();
+        let _handle = start_rest_api_with_config(config.as_ref()).await;
+        let client = storage_node_client(config.as_ref()).into_inner();
+
+        let response = client
+            .get(format!(
+                "https://{}{}",
+                config.as_ref().rest_api_address,
+                routes::EPOCH_ACCOUNTING_ENDPOINT.replace("{epoch}", "0")
+            ))
+            .send()
+            .await?;
+
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn serves_slow_requests_through_admin_api() -> TestResult {
+        let (mut config, admin_url) = storage_node_config_with_admin_api();
+        config.as_mut().rest_server.slow_request_log.threshold = Duration::ZERO;
+        let _handle = start_rest_api_with_config(config.as_ref()).await;
+        let client = storage_node_client(config.as_ref());
+
+        let blob_id = blob_id_for_valid_response();
+        client.get_metadata(&blob_id).await?;
+        let response =
+            reqwest::get(format!("{admin_url}{}", routes::SLOW_REQUESTS_ENDPOINT)).await?;
+
+        assert_eq!(response.status(), StatusCode::OK);
+        let body: serde_json::Value = serde_json::from_str(&response.text().await?)?;
+        let slow_request = &body["success"]["data"][0];
+        assert_eq!(slow_request["route"], routes::METADATA_ENDPOINT);
+        assert_eq!(
+            slow_request["blobIdPrefix"],
+            blob_id.to_string()[..8].to_owned()
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn serves_shard_ownership_audit_through_admin_api() -> TestResult {
+        let (config, admin_url) = storage_node_config_with_admin_api();
+        let _handle = start_rest_api_with_config(config.as_ref()).await;
+
+        let response = reqwest::get(format!(
+            "{admin_url}{}",
+            routes::SHARD_OWNERSHIP_AUDIT_ENDPOINT
+        ))
+        .await?;
+
+        assert_eq!(response.status(), StatusCode::OK);
+        let body: serde_json::Value = serde_json::from_str(&response.text().await?)?;
+        let audit: ShardOwnershipAudit = serde_json::from_value(body["success"]["data"].clone())?;
+        assert_eq!(audit.missing, vec![ShardIndex(0)]);
+        assert!(audit.extraneous.is_empty());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn serves_epoch_handover_through_admin_api() -> TestResult {
+        let (config, admin_url) = storage_node_config_with_admin_api();
+        let _handle = start_rest_api_with_config(config.as_ref()).await;
+
+        let response =
+            reqwest::get(format!("{admin_url}{}", routes::EPOCH_HANDOVER_ENDPOINT)).await?;
+
+        assert_eq!(response.status(), StatusCode::OK);
+        let body: serde_json::Value = serde_json::from_str(&response.text().await?)?;
+        let handover: EpochHandoverInfo = serde_json::from_value(body["success"]["data"].clone())?;
+        assert_eq!(handover.phase, EpochHandoverPhase::Syncing);
+        assert_eq!(handover.incoming_shards, vec![ShardIndex(0)]);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn lists_stored_blobs_in_pages() -> TestResult {
+        let (config, _handle) = start_rest_api_with_test_config().await;
+        let client = storage_node_client(config.as_ref());
+
+        let first_page = client
+            .list_stored_blobs(ShardIndex(0), None, Some(2))
+            .await?;
+        assert_eq!(first_page.blob_ids, [BlobId([1; 32]), BlobId([2; 32])]);
+        assert_eq!(first_page.next_cursor, Some(BlobId([2; 32])));
+
+        let last_page = client
+            .list_stored_blobs(ShardIndex(0), first_page.next_cursor.as_ref(), Some(2))
+            .await?;
+        assert_eq!(last_page.blob_ids, [BlobId([3; 32])]);
+        assert_eq!(last_page.next_cursor, None);
+
+        let error = client
+            .list_stored_blobs(ShardIndex(1), None, None)
+            .await
+            .expect_err("shard 1 is not stored");
+        assert_eq!(error.error_kind(), NodeErrorKind::ShardNotOwned);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn advertises_api_version_on_success_and_error() {
+        let (config, _handle) = start_rest_api_with_test_config().await;
+        let client = storage_node_client(config.as_ref());
+        assert_eq!(client.api_version(), None);
+
+        let blob_id = walrus_core::test_utils::random_blob_id();
+        let _ = client
+            .get_recovery_symbol_legacy::<Primary>(&blob_id, SliverPairIndex(1), SliverPairIndex(0))
+            .await
+            .expect_err("pair-id 1 triggers a not found response");
+        assert_eq!(client.api_version(), Some(ApiVersion::CURRENT));
+
+        client
+            .get_server_health_info(false)
+            .await
+            .expect("request should succeed");
+        assert_eq!(client.api_version(), Some(ApiVersion::CURRENT));
+    }
+
+    #[tokio::test]
+    async fn reports_capabilities() -> TestResult {
+        let (config, _handle) = start_rest_api_with_test_config().await;
+        let client = storage_node_client(config.as_ref());
+
+        let capabilities = client.get_capabilities().await?;
+
+        assert_eq!(capabilities.api_version, ApiVersion::CURRENT);
+        assert!(capabilities.supports(ApiFeature::BatchRecoverySymbols));
+        assert!(capabilities.supports(ApiFeature::Capabilities));
+        assert!(capabilities.supports(ApiFeature::MetadataV2));
+        assert!(capabilities.max_recovery_symbols_batch_size.is_some());
+        Ok(())
+    }
+
     mod tls {
-        use walrus_rest_client::error::NodeError;
+        use walrus_rest_client::{client::CertificatePin, error::NodeError};
 
         use super::*;
 
@@ -1257,6 +1721,55 @@ mod tests {
 
             Ok(())
         }
+
+        async_param_test! {
+            client_checks_certificate_pins -> TestResult: [
+                matching_spki: (true, true),
+                matching_certificate: (true, false),
+                mismatching_spki: (false, true),
+                mismatching_certificate: (false, false),
+            ]
+        }
+        async fn client_checks_certificate_pins(matches: bool, pin_spki: bool) -> TestResult {
+            let mut config = test_utils::storage_node_config();
+            let network_key_pair = config.as_ref().network_key_pair().clone();
+            let rest_api_address = config.as_ref().rest_api_address;
+
+            let (certified_key_pair, issuer_cert) = create_non_self_signed_certificate(
+                &network_key_pair,
+                rest_api_address.ip().to_string(),
+            )?;
+            let pinned_cert = if matches {
+                certified_key_pair.cert.der().clone()
+            } else {
+                issuer_cert.der().clone()
+            };
+            let pin = if pin_spki {
+                CertificatePin::for_public_key_of(&pinned_cert).expect("certificate is valid")
+            } else {
+                CertificatePin::for_certificate(&pinned_cert)
+            };
+
+            configure_certificates_from_disk(certified_key_pair, &mut config)?;
+            start_rest_api_with_config(config.as_ref()).await;
+
+            let client = default_client_builder()
+                .add_root_certificate(issuer_cert.der())
+                .authenticate_with_public_key(network_key_pair.public().clone())
+                .pin_certificate(pin)
+                .build(&rest_api_address.to_string())
+                .expect("must be able to construct client in tests");
+
+            let result = try_tls_request(client).await;
+            if matches {
+                result?;
+            } else {
+                let err = result.expect_err("must fail since the certificate is not pinned");
+                assert!(err.is_connect(), "should fail to connect");
+            }
+
+            Ok(())
+        }
     }
 
     async_param_test! {
@@ -0,0 +1,268 @@
+// Copyright (c) Walrus Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Limits on the bandwidth used by the REST server.
+//!
+//! Requests are classified by their route into serving data to clients, and ingesting or syncing
+//! data. Each class has its own leaky bucket, which is drained by the bodies of requests and
+//! responses of that class. A body whose bucket overflows is paused until the bucket has leaked
+//! enough, so that heavily read blobs cannot exhaust the bandwidth used by committee syncs.
+
+use std::{
+    future::Future as _,
+    pin::Pin,
+    sync::{Arc, Mutex},
+    task::{ready, Context, Poll},
+    time::Duration,
+};
+
+use axum::{
+    body::{Body, Bytes},
+    extract::{MatchedPath, Request, State},
+    http::Method,
+    middleware::Next,
+    response::Response,
+};
+use http_body::{Frame, SizeHint};
+use tokio::time::{self, Instant, Sleep};
+
+use super::routes;
+use crate::node::config::{BandwidthLimitConfig, BandwidthLimitsConfig};
+
+/// The kind of traffic of a request, each of which has a separate bandwidth limit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TrafficClass {
+    /// Slivers and metadata served to clients.
+    Serving,
+    /// Slivers and metadata uploaded by clients, and data served to other storage nodes.
+    IngestAndSync,
+}
+
+impl TrafficClass {
+    /// Returns the class of requests with the given method to the given route, if the class is
+    /// subject to bandwidth limits.
+    fn of(method: &Method, route: &str) -> Option<Self> {
+        match route {
+            routes::METADATA_ENDPOINT | routes::SLIVER_ENDPOINT => {
+                if method == Method::PUT {
+                    Some(Self::IngestAndSync)
+                } else {
+                    Some(Self::Serving)
+                }
+            }
+            routes::SYNC_SHARD_ENDPOINT
+            | routes::RECOVERY_ENDPOINT
+            | routes::RECOVERY_SYMBOL_ENDPOINT
+            | routes::RECOVERY_SYMBOL_LIST_ENDPOINT
+            | routes::RECOVERY_SYMBOL_BATCH_ENDPOINT => Some(Self::IngestAndSync),
+            _ => None,
+        }
+    }
+}
+
+/// The bandwidth limits of the classes of traffic.
+#[derive(Debug, Default)]
+pub(crate) struct BandwidthLimiters {
+    serving: Option<Arc<LeakyBucket>>,
+    ingest_and_sync: Option<Arc<LeakyBucket>>,
+}
+
+impl BandwidthLimiters {
+    pub fn new(config: &BandwidthLimitsConfig) -> Self {
+        let bucket = |config: &BandwidthLimitConfig| Arc::new(LeakyBucket::new(config));
+        Self {
+            serving: config.serving.as_ref().map(bucket),
+            ingest_and_sync: config.ingest_and_sync.as_ref().map(bucket),
+        }
+    }
+
+    fn bucket(&self, class: TrafficClass) -> Option<&Arc<LeakyBucket>> {
+        match class {
+            TrafficClass::Serving => self.serving.as_ref(),
+            TrafficClass::IngestAndSync => self.ingest_and_sync.as_ref(),
+        }
+    }
+}
+
+/// A leaky bucket, which lets bytes through at a sustained rate, and in bursts of up to a fixed
+/// size while the bucket is not full.
+#[derive(Debug)]
+struct LeakyBucket {
+    bytes_per_second: f64,
+    /// The time it takes the bucket to leak a full burst.
+    burst_tolerance: Duration,
+    /// The time at which the bucket is empty, if all bytes added so far are let through at the
+    /// sustained rate.
+    empty_at: Mutex<Instant>,
+}
+
+impl LeakyBucket {
+    fn new(config: &BandwidthLimitConfig) -> Self {
+        // Converting to floating point loses precision only for absurdly high limits.
+        #[allow(clippy::cast_precision_loss)]
+        let bytes_per_second = config.bytes_per_second.get() as f64;
+        #[allow(clippy::cast_precision_loss)]
+        let burst_tolerance = Duration::from_secs_f64(config.burst_bytes as f64 / bytes_per_second);
+
+        Self {
+            bytes_per_second,
+            burst_tolerance,
+            empty_at: Mutex::new(Instant::now()),
+        }
+    }
+
+    /// Adds `n_bytes` to the bucket, and returns the time to wait until they fit within the burst
+    /// size and may be sent.
+    fn add(&self, n_bytes: usize) -> Duration {
+        #[allow(clippy::cast_precision_loss)]
+        let leak_duration = Duration::from_secs_f64(n_bytes as f64 / self.bytes_per_second);
+
+        let now = Instant::now();
+        let mut empty_at = self.empty_at.lock().expect("mutex should not be poisoned");
+        *empty_at = (*empty_at).max(now) + leak_duration;
+
+        empty_at
+            .saturating_duration_since(now)
+            .saturating_sub(self.burst_tolerance)
+    }
+}
+
+/// Middleware that limits the bandwidth used by the bodies of requests and responses according
+/// to their [`TrafficClass`].
+pub(crate) async fn bandwidth_limit_middleware(
+    State(limiters): State<Arc<BandwidthLimiters>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let bucket = request
+        .extensions()
+        .get::<MatchedPath>()
+        .and_then(|route| TrafficClass::of(request.method(), route.as_str()))
+        .and_then(|class| limiters.bucket(class))
+        .cloned();
+
+    let Some(bucket) = bucket else {
+        return next.run(request).await;
+    };
+
+    let request = request.map(|body| Body::new(ThrottledBody::new(body, bucket.clone())));
+    next.run(request)
+        .await
+        .map(|body| Body::new(ThrottledBody::new(body, bucket)))
+}
+
+/// A body whose data frames are delayed until they are let through by a [`LeakyBucket`].
+struct ThrottledBody {
+    inner: Body,
+    bucket: Arc<LeakyBucket>,
+    /// A data frame that is held back, and the delay until it may be returned.
+    delayed: Option<(Pin<Box<Sleep>>, Frame<Bytes>)>,
+}
+
+impl ThrottledBody {
+    fn new(inner: Body, bucket: Arc<LeakyBucket>) -> Self {
+        Self {
+            inner,
+            bucket,
+            delayed: None,
+        }
+    }
+}
+
+impl http_body::Body for ThrottledBody {
+    type Data = Bytes;
+    type Error = axum::Error;
+
+    fn poll_frame(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Frame<Self::Data>, Self::Error>>> {
+        let this = self.get_mut();
+
+        if let Some((delay, _)) = this.delayed.as_mut() {
+            ready!(delay.as_mut().poll(cx));
+            let (_, frame) = this.delayed.take().expect("frame is delayed");
+            return Poll::Ready(Some(Ok(frame)));
+        }
+
+        let frame = match ready!(Pin::new(&mut this.inner).poll_frame(cx)) {
+            Some(Ok(frame)) => frame,
+            other => return Poll::Ready(other),
+        };
+        let Some(data) = frame.data_ref() else {
+            return Poll::Ready(Some(Ok(frame)));
+        };
+
+        let delay = this.bucket.add(data.len());
+        if delay.is_zero() {
+            return Poll::Ready(Some(Ok(frame)));
+        }
+
+        let mut sleep = Box::pin(time::sleep(delay));
+        // Register the waker with the sleep, which is still pending.
+        if sleep.as_mut().poll(cx).is_ready() {
+            return Poll::Ready(Some(Ok(frame)));
+        }
+        this.delayed = Some((sleep, frame));
+        Poll::Pending
+    }
+
+    fn is_end_stream(&self) -> bool {
+        self.delayed.is_none() && self.inner.is_end_stream()
+    }
+
+    fn size_hint(&self) -> SizeHint {
+        let Some((_, frame)) = &self.delayed else {
+            return self.inner.size_hint();
+        };
+        let delayed_bytes = frame.data_ref().map_or(0, |data| data.len() as u64);
+        let inner = self.inner.size_hint();
+
+        let mut hint = SizeHint::new();
+        hint.set_lower(inner.lower() + delayed_bytes);
+        if let Some(upper) = inner.upper() {
+            hint.set_upper(upper + delayed_bytes);
+        }
+        hint
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::num::NonZeroU64;
+
+    use http_body_util::BodyExt as _;
+
+    use super::*;
+
+    fn bucket(bytes_per_second: u64, burst_bytes: u64) -> Arc<LeakyBucket> {
+        Arc::new(LeakyBucket::new(&BandwidthLimitConfig {
+            bytes_per_second: NonZeroU64::new(bytes_per_second).unwrap(),
+            burst_bytes,
+        }))
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn lets_bursts_through_and_then_limits_the_rate() {
+        let bucket = bucket(1000, 500);
+
+        assert_eq!(bucket.add(500), Duration::ZERO);
+        assert_eq!(bucket.add(100), Duration::from_millis(100));
+
+        time::advance(Duration::from_secs(1)).await;
+        assert_eq!(bucket.add(500), Duration::ZERO);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn delays_body_frames_according_to_the_bucket() {
+        let chunks: Vec<Result<_, axum::Error>> = (0..4).map(|_| Ok(vec![0u8; 1000])).collect();
+        let body = Body::from_stream(futures::stream::iter(chunks));
+        let throttled = ThrottledBody::new(body, bucket(1000, 0));
+
+        let start = Instant::now();
+        let collected = throttled.collect().await.expect("body is valid");
+
+        assert_eq!(collected.to_bytes().len(), 4000);
+        assert_eq!(start.elapsed(), Duration::from_secs(4));
+    }
+}
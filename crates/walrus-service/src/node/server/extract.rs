@@ -13,7 +13,11 @@ use reqwest::header::AUTHORIZATION;
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use walrus_core::PublicKey;
 use walrus_proc_macros::RestApiError;
-use walrus_rest_client::api::errors::STORAGE_NODE_ERROR_DOMAIN as ERROR_DOMAIN;
+use walrus_rest_client::api::{
+    errors::STORAGE_NODE_ERROR_DOMAIN as ERROR_DOMAIN,
+    IdempotencyKey,
+    IDEMPOTENCY_KEY_HEADER,
+};
 
 use crate::common::api::RestApiError;
 
@@ -138,3 +142,27 @@ where
         ))
     }
 }
+
+/// The idempotency key attached to a write request, if any.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IdempotencyKeyHeader(pub Option<IdempotencyKey>);
+
+impl<S> FromRequestParts<S> for IdempotencyKeyHeader
+where
+    S: Send + Sync,
+{
+    type Rejection = (StatusCode, &'static str);
+
+    async fn from_request_parts(parts: &mut Parts, _: &S) -> Result<Self, Self::Rejection> {
+        let Some(header_value) = parts.headers.get(IDEMPOTENCY_KEY_HEADER) else {
+            return Ok(IdempotencyKeyHeader(None));
+        };
+
+        header_value
+            .to_str()
+            .ok()
+            .and_then(|key| key.parse().ok())
+            .map(|key| IdempotencyKeyHeader(Some(key)))
+            .ok_or((StatusCode::BAD_REQUEST, "Invalid Idempotency-Key header"))
+    }
+}
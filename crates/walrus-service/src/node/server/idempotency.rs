@@ -0,0 +1,153 @@
+// Copyright (c) Walrus Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Deduplication of retried write requests by their idempotency keys.
+
+use std::future::Future;
+
+use axum::http::StatusCode;
+use moka::future::Cache;
+use walrus_rest_client::api::IdempotencyKey;
+
+use crate::node::config::IdempotencyConfig;
+
+/// The status code and message of a successful write.
+pub(crate) type WriteResult = (StatusCode, &'static str);
+
+/// The results of successful writes, indexed by their idempotency keys and request targets.
+#[derive(Debug)]
+pub(crate) struct IdempotencyCache {
+    results: Cache<(IdempotencyKey, String), WriteResult>,
+}
+
+impl IdempotencyCache {
+    pub fn new(config: &IdempotencyConfig) -> Self {
+        Self {
+            results: Cache::builder()
+                .max_capacity(config.max_entries)
+                .time_to_live(config.retention)
+                .build(),
+        }
+    }
+
+    /// Performs the write and returns its result, unless a write with the same key and target has
+    /// already succeeded or is in progress.
+    ///
+    /// In that case, `write` is dropped without being run and the result of the other write is
+    /// returned, after waiting for it to complete. Failed writes are not retained, so that they
+    /// can be retried. Writes without a key are always performed.
+    pub async fn run<E>(
+        &self,
+        key: Option<IdempotencyKey>,
+        target: &str,
+        write: impl Future<Output = Result<WriteResult, E>>,
+    ) -> Result<WriteResult, E> {
+        let Some(key) = key else {
+            return write.await;
+        };
+
+        let mut write = Some(write);
+        let mut error = None;
+        let result = self
+            .results
+            .try_get_with((key, target.to_owned()), async {
+                let write = write.take().expect("the cache runs the write at most once");
+                write.await.map_err(|write_error| error = Some(write_error))
+            })
+            .await;
+
+        match (result, error, write) {
+            (Ok(result), _, _) => Ok(result),
+            (Err(_), Some(error), _) => Err(error),
+            // A concurrent write with the same key failed, so this one is performed anew.
+            (Err(_), None, Some(write)) => write.await,
+            (Err(_), None, None) => unreachable!("a failed write records its error"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use super::*;
+
+    const TARGET: &str = "/v1/blobs/blob/metadata";
+
+    fn cache() -> IdempotencyCache {
+        IdempotencyCache::new(&IdempotencyConfig::default())
+    }
+
+    async fn count_write(
+        n_writes: &AtomicUsize,
+        result: Result<WriteResult, &'static str>,
+    ) -> Result<WriteResult, &'static str> {
+        n_writes.fetch_add(1, Ordering::SeqCst);
+        result
+    }
+
+    #[tokio::test]
+    async fn performs_write_with_the_same_key_once() {
+        let cache = cache();
+        let key = IdempotencyKey::random();
+        let n_writes = AtomicUsize::new(0);
+
+        for _ in 0..2 {
+            let result = cache
+                .run(
+                    Some(key.clone()),
+                    TARGET,
+                    count_write(&n_writes, Ok((StatusCode::CREATED, "stored"))),
+                )
+                .await;
+            assert_eq!(result, Ok((StatusCode::CREATED, "stored")));
+        }
+        assert_eq!(n_writes.load(Ordering::SeqCst), 1);
+
+        let other_target_result = cache
+            .run(
+                Some(key),
+                "/v1/blobs/other/metadata",
+                count_write(&n_writes, Ok((StatusCode::OK, "already stored"))),
+            )
+            .await;
+        assert_eq!(other_target_result, Ok((StatusCode::OK, "already stored")));
+        assert_eq!(n_writes.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn performs_writes_without_key_or_after_failure() {
+        let cache = cache();
+        let key = IdempotencyKey::random();
+        let n_writes = AtomicUsize::new(0);
+
+        for _ in 0..2 {
+            let result = cache
+                .run(
+                    None,
+                    TARGET,
+                    count_write(&n_writes, Ok((StatusCode::OK, "ok"))),
+                )
+                .await;
+            assert!(result.is_ok());
+        }
+        let failed = cache
+            .run(
+                Some(key.clone()),
+                TARGET,
+                count_write(&n_writes, Err("failed")),
+            )
+            .await;
+        assert_eq!(failed, Err("failed"));
+        let retried = cache
+            .run(
+                Some(key),
+                TARGET,
+                count_write(&n_writes, Ok((StatusCode::OK, "ok"))),
+            )
+            .await;
+        assert!(retried.is_ok());
+
+        assert_eq!(n_writes.load(Ordering::SeqCst), 4);
+    }
+}
@@ -0,0 +1,113 @@
+// Copyright (c) Walrus Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Rate limiting of requests on a per-peer basis.
+
+use std::{collections::HashMap, net::IpAddr, sync::Mutex, time::Duration};
+
+use tokio::time::Instant;
+
+use crate::node::config::PeerRateLimitConfig;
+
+/// The number of tracked peers above which the buckets of peers with a full budget are dropped.
+const MAX_TRACKED_PEERS: usize = 10_000;
+
+/// A token-bucket rate limiter that tracks a separate budget for each peer.
+#[derive(Debug)]
+pub(crate) struct PeerRateLimiter {
+    max_burst: u32,
+    replenish_interval: Duration,
+    buckets: Mutex<HashMap<IpAddr, TokenBucket>>,
+}
+
+impl PeerRateLimiter {
+    pub fn new(config: &PeerRateLimitConfig) -> Self {
+        Self {
+            max_burst: config.max_burst,
+            replenish_interval: config.replenish_interval,
+            buckets: Mutex::default(),
+        }
+    }
+
+    /// Consumes a request from the budget of the peer and returns true, or returns false if the
+    /// peer has exhausted its budget.
+    pub fn try_acquire(&self, peer: IpAddr) -> bool {
+        let now = Instant::now();
+        let mut buckets = self.buckets.lock().expect("mutex should not be poisoned");
+
+        if buckets.len() >= MAX_TRACKED_PEERS && !buckets.contains_key(&peer) {
+            // Peers with a full budget are indistinguishable from untracked peers.
+            buckets.retain(|_, bucket| !self.replenish(bucket, now));
+        }
+
+        let bucket = buckets.entry(peer).or_insert(TokenBucket {
+            tokens: self.max_burst,
+            last_replenished: now,
+        });
+        self.replenish(bucket, now);
+
+        if bucket.tokens == 0 {
+            return false;
+        }
+        bucket.tokens -= 1;
+        true
+    }
+
+    /// Adds the tokens accrued since the bucket was last replenished, and returns true if the
+    /// bucket is full.
+    fn replenish(&self, bucket: &mut TokenBucket, now: Instant) -> bool {
+        let elapsed = now.saturating_duration_since(bucket.last_replenished);
+        let n_accrued = elapsed.as_nanos() / self.replenish_interval.as_nanos().max(1);
+        let n_missing = self.max_burst - bucket.tokens;
+
+        if n_accrued >= u128::from(n_missing) {
+            bucket.tokens = self.max_burst;
+            bucket.last_replenished = now;
+            true
+        } else {
+            let n_accrued = u32::try_from(n_accrued).expect("less than n_missing");
+            bucket.tokens += n_accrued;
+            bucket.last_replenished += self.replenish_interval * n_accrued;
+            false
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct TokenBucket {
+    tokens: u32,
+    last_replenished: Instant,
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::Ipv4Addr;
+
+    use tokio::time;
+
+    use super::*;
+
+    const PEER_A: IpAddr = IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1));
+    const PEER_B: IpAddr = IpAddr::V4(Ipv4Addr::new(10, 0, 0, 2));
+
+    #[tokio::test(start_paused = true)]
+    async fn limits_each_peer_separately_and_replenishes_over_time() {
+        let limiter = PeerRateLimiter::new(&PeerRateLimitConfig {
+            max_burst: 2,
+            replenish_interval: Duration::from_secs(1),
+        });
+
+        assert!(limiter.try_acquire(PEER_A));
+        assert!(limiter.try_acquire(PEER_A));
+        assert!(!limiter.try_acquire(PEER_A));
+        assert!(limiter.try_acquire(PEER_B));
+
+        time::advance(Duration::from_millis(1500)).await;
+        assert!(limiter.try_acquire(PEER_A));
+        assert!(!limiter.try_acquire(PEER_A));
+
+        time::advance(Duration::from_millis(500)).await;
+        assert!(limiter.try_acquire(PEER_A));
+        assert!(!limiter.try_acquire(PEER_A));
+    }
+}
@@ -1,31 +1,41 @@
 // Copyright (c) Walrus Foundation
 // SPDX-License-Identifier: Apache-2.0
 
-use std::{num::NonZeroU16, sync::Arc};
+use std::{net::SocketAddr, num::NonZeroU16, sync::Arc};
 
 use axum::{
-    extract::{Path, Query, State},
-    http::StatusCode,
-    response::{IntoResponse, Response},
+    extract::{ConnectInfo, Path, Query, State},
+    http::{StatusCode, Uri},
+    response::{
+        sse::{Event, KeepAlive, Sse},
+        IntoResponse,
+        Response,
+    },
+    Extension,
 };
 use axum_extra::extract::Query as ExtraQuery;
+use futures::{future, stream, Stream};
 use serde::Deserialize;
 use serde_with::{serde_as, DisplayFromStr, OneOrMany};
 use sui_types::base_types::ObjectID;
+use tokio::sync::broadcast::error::RecvError;
 use tracing::Level;
 use walrus_core::{
     encoding::{GeneralRecoverySymbol, Primary as PrimaryEncoding, Secondary as SecondaryEncoding},
     messages::{
         BlobPersistenceType,
         InvalidBlobIdAttestation,
+        SignedEpochAccountingReport,
         SignedMessage,
         SignedSyncShardRequest,
         StorageConfirmation,
     },
     metadata::{BlobMetadata, UnverifiedBlobMetadataWithId, VerifiedBlobMetadataWithId},
     BlobId,
+    Epoch,
     InconsistencyProof,
     RecoverySymbol,
+    ShardIndex,
     Sliver,
     SliverIndex,
     SliverPairIndex,
@@ -33,22 +43,37 @@ use walrus_core::{
     SymbolId,
 };
 use walrus_rest_client::{
-    api::{BlobStatus, ServiceHealthInfo, StoredOnNodeStatus},
+    api::{
+        ApiVersion,
+        BatchedRecoverySymbolsRequest,
+        BlobStatus,
+        EpochHandoverInfo,
+        NodeCapabilities,
+        ServiceHealthInfo,
+        ShardOwnershipAudit,
+        StoredBlobsPage,
+        StoredOnNodeStatus,
+        SLIVER_CHECKSUM_HEADER,
+    },
     client::RecoverySymbolsFilter,
 };
 use walrus_sui::ObjectIdSchema;
 
 use super::{
-    extract::{Authorization, Bcs},
+    extract::{Authorization, Bcs, IdempotencyKeyHeader},
+    idempotency::IdempotencyCache,
     openapi::{self},
+    rate_limit::PeerRateLimiter,
     responses::OrRejection,
+    slow_log::{SlowRequest, SlowRequestLog},
 };
 use crate::{
     common::api::{ApiSuccess, BlobIdString},
     node::{
-        errors::{IndexOutOfRange, ListSymbolsError},
+        errors::{IndexOutOfRange, ListStoredBlobsError, ListSymbolsError},
         BlobStatusError,
         ComputeStorageConfirmationError,
+        EpochAccountingError,
         InconsistencyProofError,
         RetrieveMetadataError,
         RetrieveSliverError,
@@ -83,13 +108,36 @@ pub const RECOVERY_ENDPOINT: &str =
 pub const RECOVERY_SYMBOL_ENDPOINT: &str = "/v1/blobs/{blob_id}/recoverySymbols/{symbol_id}";
 /// The path to get multiple recovery symbols.
 pub const RECOVERY_SYMBOL_LIST_ENDPOINT: &str = "/v1/blobs/{blob_id}/recoverySymbols";
+/// The path to get the recovery symbols of multiple blobs.
+pub const RECOVERY_SYMBOL_BATCH_ENDPOINT: &str = "/v1/recoverySymbols/batch";
 /// The path to push inconsistency proofs.
 pub const INCONSISTENCY_PROOF_ENDPOINT: &str =
     "/v1/blobs/{blob_id}/inconsistencyProof/{sliver_type}";
 /// The path to get the status of a blob.
 pub const BLOB_STATUS_ENDPOINT: &str = "/v1/blobs/{blob_id}/status";
 pub const HEALTH_ENDPOINT: &str = "/v1/health";
+/// The path to get the API version and the features supported by the node.
+pub const CAPABILITIES_ENDPOINT: &str = "/v1/capabilities";
 pub const SYNC_SHARD_ENDPOINT: &str = "/v1/migrate/sync_shard";
+/// The path to stream the events about the data stored by the node.
+pub const EVENTS_ENDPOINT: &str = "/v1/events";
+/// The path to get the signed storage accounting of the node for an epoch.
+pub const EPOCH_ACCOUNTING_ENDPOINT: &str = "/v1/admin/accounting/{epoch}";
+/// The path to get the most recent requests that exceeded their latency threshold.
+pub const SLOW_REQUESTS_ENDPOINT: &str = "/v1/admin/slow-requests";
+/// The path to compare the shards in storage with the shards assigned to the node on chain.
+pub const SHARD_OWNERSHIP_AUDIT_ENDPOINT: &str = "/v1/admin/shard-audit";
+/// The path to get the state of the shard handover during the latest committee transition.
+pub const EPOCH_HANDOVER_ENDPOINT: &str = "/v1/admin/epoch-handover";
+/// The path to list the blobs stored for a shard.
+pub const STORED_BLOBS_ENDPOINT: &str = "/v1/shards/{shard_index}/blobs";
+
+/// The maximum number of blobs whose recovery symbols can be requested in a single batch.
+const MAX_RECOVERY_SYMBOL_BATCH_SIZE: usize = 256;
+/// The number of blob IDs returned in a page of stored blobs if no limit is specified.
+const DEFAULT_STORED_BLOBS_PAGE_SIZE: usize = 100;
+/// The maximum number of blob IDs that can be requested in a single page of stored blobs.
+const MAX_STORED_BLOBS_PAGE_SIZE: usize = 1000;
 
 /// Convenience trait to apply bounds on the ServiceState.
 trait SyncServiceState: ServiceState + Send + Sync + 'static {}
@@ -162,17 +210,26 @@ pub async fn get_metadata_status<S: SyncServiceState>(
 )]
 pub async fn put_metadata<S: SyncServiceState>(
     State(state): State<Arc<S>>,
+    Extension(idempotency_cache): Extension<Arc<IdempotencyCache>>,
+    IdempotencyKeyHeader(idempotency_key): IdempotencyKeyHeader,
+    uri: Uri,
     Path(BlobIdString(blob_id)): Path<BlobIdString>,
     Bcs(metadata): Bcs<BlobMetadata>,
 ) -> Result<ApiSuccess<&'static str>, StoreMetadataError> {
-    let (code, message) = if state
-        .store_metadata(UnverifiedBlobMetadataWithId::new(blob_id, metadata))
-        .await?
-    {
-        (StatusCode::CREATED, "metadata successfully stored")
-    } else {
-        (StatusCode::OK, "metadata already stored")
+    let store_metadata = async {
+        let result = if state
+            .store_metadata(UnverifiedBlobMetadataWithId::new(blob_id, metadata))
+            .await?
+        {
+            (StatusCode::CREATED, "metadata successfully stored")
+        } else {
+            (StatusCode::OK, "metadata already stored")
+        };
+        Ok::<_, StoreMetadataError>(result)
     };
+    let (code, message) = idempotency_cache
+        .run(idempotency_key, uri.path(), store_metadata)
+        .await?;
 
     Ok(ApiSuccess::new(code, message))
 }
@@ -196,7 +253,17 @@ pub async fn put_metadata<S: SyncServiceState>(
         ("sliver_type" = SliverType, ),
     ),
     responses(
-        (status = 200, description = "BCS encoded primary or secondary sliver", body = [u8]),
+        (
+            status = 200,
+            description = "BCS encoded primary or secondary sliver",
+            body = [u8],
+            headers(
+                (
+                    "x-walrus-sliver-checksum" = String,
+                    description = "The xxHash64 checksum of the sliver, as 16 hexadecimal digits"
+                ),
+            ),
+        ),
         RetrieveSliverError,
     ),
     tag = openapi::GROUP_READING_BLOBS
@@ -215,9 +282,10 @@ pub async fn get_sliver<S: SyncServiceState>(
         .await?;
 
     debug_assert_eq!(sliver.r#type(), sliver_type, "invalid sliver type fetched");
+    let checksum_header = [(SLIVER_CHECKSUM_HEADER, sliver.checksum().to_string())];
     match sliver {
-        Sliver::Primary(inner) => Ok(Bcs(inner).into_response()),
-        Sliver::Secondary(inner) => Ok(Bcs(inner).into_response()),
+        Sliver::Primary(inner) => Ok((checksum_header, Bcs(inner)).into_response()),
+        Sliver::Secondary(inner) => Ok((checksum_header, Bcs(inner)).into_response()),
     }
 }
 
@@ -246,6 +314,9 @@ pub async fn get_sliver<S: SyncServiceState>(
 )]
 pub async fn put_sliver<S: SyncServiceState>(
     State(state): State<Arc<S>>,
+    Extension(idempotency_cache): Extension<Arc<IdempotencyCache>>,
+    IdempotencyKeyHeader(idempotency_key): IdempotencyKeyHeader,
+    uri: Uri,
     Path((blob_id, sliver_pair_index, sliver_type)): Path<(
         BlobIdString,
         SliverPairIndex,
@@ -254,17 +325,24 @@ pub async fn put_sliver<S: SyncServiceState>(
     body: axum::body::Bytes,
 ) -> Result<ApiSuccess<&'static str>, OrRejection<StoreSliverError>> {
     let blob_id = blob_id.0;
-    let sliver = match sliver_type {
-        SliverType::Primary => Sliver::Primary(Bcs::from_bytes(&body)?.0),
-        SliverType::Secondary => Sliver::Secondary(Bcs::from_bytes(&body)?.0),
-    };
+    let store_sliver = async {
+        let sliver = match sliver_type {
+            SliverType::Primary => Sliver::Primary(Bcs::from_bytes(&body)?.0),
+            SliverType::Secondary => Sliver::Secondary(Bcs::from_bytes(&body)?.0),
+        };
 
-    state
-        .store_sliver(blob_id, sliver_pair_index, sliver)
+        state
+            .store_sliver(blob_id, sliver_pair_index, sliver)
+            .await?;
+
+        // TODO(WAL-253): Change to CREATED.
+        Ok::<_, OrRejection<StoreSliverError>>((StatusCode::OK, "sliver stored successfully"))
+    };
+    let (code, message) = idempotency_cache
+        .run(idempotency_key, uri.path(), store_sliver)
         .await?;
 
-    // TODO(WAL-253): Change to CREATED.
-    Ok(ApiSuccess::ok("sliver stored successfully"))
+    Ok(ApiSuccess::new(code, message))
 }
 
 /// Check if the blob slivers are present.
@@ -580,6 +658,57 @@ pub async fn list_recovery_symbols<S: SyncServiceState>(
     Ok(Bcs(symbols))
 }
 
+/// Get the recovery symbols of multiple blobs.
+///
+/// Accepts a BCS-encoded list of requests, each specifying a blob and the recovery symbols of that
+/// blob to return, and returns a BCS-encoded list with the matching recovery symbols for each of
+/// the requests, in the same order. The list for a request is empty if no matching symbols could
+/// be retrieved.
+#[tracing::instrument(skip_all, err(level = Level::DEBUG), fields(n_requests = requests.len()))]
+#[utoipa::path(
+    post,
+    path = RECOVERY_SYMBOL_BATCH_ENDPOINT,
+    request_body(content = [u8], description = "BCS-encoded list of requests"),
+    responses(
+        (status = 200, description = "BCS-encoded lists of recovery symbols", body = [u8]),
+        ListSymbolsError,
+    ),
+    tag = openapi::GROUP_RECOVERY
+)]
+pub async fn list_recovery_symbols_batch<S: SyncServiceState>(
+    State(state): State<Arc<S>>,
+    Bcs(requests): Bcs<Vec<BatchedRecoverySymbolsRequest>>,
+) -> Result<Bcs<Vec<Vec<GeneralRecoverySymbol>>>, ListSymbolsError> {
+    if requests.len() > MAX_RECOVERY_SYMBOL_BATCH_SIZE {
+        return Err(ListSymbolsError::BatchTooLarge {
+            max: MAX_RECOVERY_SYMBOL_BATCH_SIZE,
+        });
+    }
+
+    let symbols = future::join_all(requests.into_iter().map(|request| {
+        let state = state.clone();
+        async move {
+            let Some(filter) = request.filter() else {
+                return vec![];
+            };
+            state
+                .retrieve_multiple_recovery_symbols(&request.blob_id, filter)
+                .await
+                .inspect_err(|error| {
+                    tracing::debug!(
+                        ?error,
+                        walrus.blob_id = %request.blob_id,
+                        "failed to retrieve the recovery symbols of a blob in a batch"
+                    )
+                })
+                .unwrap_or_default()
+        }
+    }))
+    .await;
+
+    Ok(Bcs(symbols))
+}
+
 /// Verify blob inconsistency.
 ///
 /// Accepts an inconsistency proof from other storage nodes, verifies it, and returns an attestation
@@ -601,9 +730,15 @@ pub async fn list_recovery_symbols<S: SyncServiceState>(
 )]
 pub async fn inconsistency_proof<S: SyncServiceState>(
     State(state): State<Arc<S>>,
+    ConnectInfo(peer): ConnectInfo<SocketAddr>,
+    Extension(rate_limiter): Extension<Arc<PeerRateLimiter>>,
     Path((blob_id, sliver_type)): Path<(BlobIdString, SliverType)>,
     body: axum::body::Bytes,
 ) -> Result<ApiSuccess<InvalidBlobIdAttestation>, OrRejection<InconsistencyProofError>> {
+    if !rate_limiter.try_acquire(peer.ip()) {
+        return Err(InconsistencyProofError::RateLimited.into());
+    }
+
     let blob_id = blob_id.0;
     let inconsistency_proof = match sliver_type {
         SliverType::Primary => InconsistencyProof::Primary(Bcs::from_bytes(&body)?.0),
@@ -667,6 +802,202 @@ pub async fn health_info<S: SyncServiceState>(
     ApiSuccess::ok(state.health_info(query.detailed).await)
 }
 
+/// Get the capabilities of the node.
+///
+/// Returns the version of the API implemented by the node, the features that it supports, and its
+/// limits on the sizes of batched and paginated requests.
+#[tracing::instrument(skip_all)]
+#[utoipa::path(
+    get,
+    path = CAPABILITIES_ENDPOINT,
+    responses(
+        (status = 200, description = "The capabilities of the node",
+        body = ApiSuccess<NodeCapabilities>),
+    ),
+    tag = openapi::GROUP_STATUS
+)]
+pub async fn get_capabilities() -> ApiSuccess<NodeCapabilities> {
+    ApiSuccess::ok(NodeCapabilities {
+        api_version: ApiVersion::CURRENT,
+        features: ApiVersion::CURRENT.features(),
+        max_recovery_symbols_batch_size: Some(MAX_RECOVERY_SYMBOL_BATCH_SIZE as u32),
+        max_stored_blobs_page_size: Some(MAX_STORED_BLOBS_PAGE_SIZE as u32),
+    })
+}
+
+/// Stream the node's events.
+///
+/// Streams events about the data stored by this node as server-sent events, each containing a
+/// JSON-encoded event, such as blobs being stored, certified, or deleted, and the progress of shard
+/// syncs. Only events emitted after subscribing are streamed. If the subscriber falls behind,
+/// missed events are reported by a `lagged` event containing the number of missed events.
+#[tracing::instrument(skip_all)]
+#[utoipa::path(
+    get,
+    path = EVENTS_ENDPOINT,
+    responses(
+        (status = 200, description = "Stream of JSON-encoded node events",
+        content_type = "text/event-stream", body = String),
+    ),
+    tag = openapi::GROUP_STATUS
+)]
+pub async fn stream_events<S: SyncServiceState>(
+    State(state): State<Arc<S>>,
+) -> Sse<impl Stream<Item = Result<Event, axum::Error>>> {
+    let events = stream::unfold(
+        state.subscribe_to_local_events(),
+        |mut receiver| async move {
+            let event = match receiver.recv().await {
+                Ok(event) => Event::default().json_data(event),
+                Err(RecvError::Lagged(n_missed)) => {
+                    Ok(Event::default().event("lagged").data(n_missed.to_string()))
+                }
+                Err(RecvError::Closed) => return None,
+            };
+            Some((event, receiver))
+        },
+    );
+
+    Sse::new(events).keep_alive(KeepAlive::default())
+}
+
+/// Get the storage accounting for an epoch.
+///
+/// Returns a report, signed by the node's protocol key, of the sliver bytes served and stored by
+/// the node in the specified epoch and of the bytes stored for each of the shards it owned. Only
+/// served on the listener of the node's admin API.
+#[tracing::instrument(skip_all, fields(epoch = %epoch), err(level = Level::DEBUG))]
+#[utoipa::path(
+    get,
+    path = EPOCH_ACCOUNTING_ENDPOINT,
+    params(("epoch" = Epoch,)),
+    responses(
+        (status = 200, description = "Signed epoch accounting report",
+        body = ApiSuccess<SignedMessage::<u8>>),
+        EpochAccountingError,
+    ),
+    tag = openapi::GROUP_STATUS
+)]
+pub async fn get_epoch_accounting_report<S: SyncServiceState>(
+    State(state): State<Arc<S>>,
+    Path(epoch): Path<Epoch>,
+) -> Result<ApiSuccess<SignedEpochAccountingReport>, EpochAccountingError> {
+    Ok(ApiSuccess::ok(state.epoch_accounting_report(epoch).await?))
+}
+
+/// Get the slow requests.
+///
+/// Returns the most recent requests that exceeded the latency threshold of their route, from the
+/// most recent to the oldest. Only served on the listener of the node's admin API.
+#[tracing::instrument(skip_all)]
+#[utoipa::path(
+    get,
+    path = SLOW_REQUESTS_ENDPOINT,
+    responses(
+        (status = 200, description = "The most recent slow requests",
+        body = ApiSuccess<Vec<SlowRequest>>),
+    ),
+    tag = openapi::GROUP_STATUS
+)]
+pub async fn get_slow_requests(
+    Extension(slow_request_log): Extension<Arc<SlowRequestLog>>,
+) -> ApiSuccess<Vec<SlowRequest>> {
+    ApiSuccess::ok(slow_request_log.entries())
+}
+
+/// Audit the shards in storage.
+///
+/// Compares the shards present in the node's storage with the shards assigned to it in the
+/// current epoch. Reports the shards that are no longer needed and can be safely deleted, and the
+/// assigned shards that are missing or still need to be synced. Only served on the listener of
+/// the node's admin API.
+#[tracing::instrument(skip_all)]
+#[utoipa::path(
+    get,
+    path = SHARD_OWNERSHIP_AUDIT_ENDPOINT,
+    responses(
+        (status = 200, description = "The audit of the shards in storage",
+        body = ApiSuccess<ShardOwnershipAudit>),
+    ),
+    tag = openapi::GROUP_STATUS
+)]
+pub async fn get_shard_ownership_audit<S: SyncServiceState>(
+    State(state): State<Arc<S>>,
+) -> ApiSuccess<ShardOwnershipAudit> {
+    ApiSuccess::ok(state.shard_ownership_audit().await)
+}
+
+/// Get the state of the shard handover.
+///
+/// Returns the shards handed over and received by the node in the latest committee transition
+/// since the node started, and the phase of the handover, or `null` if no transition was started.
+/// Only served on the listener of the node's admin API.
+#[tracing::instrument(skip_all)]
+#[utoipa::path(
+    get,
+    path = EPOCH_HANDOVER_ENDPOINT,
+    responses(
+        (status = 200, description = "The state of the shard handover, or `null` if none started",
+        body = ApiSuccess<EpochHandoverInfo>),
+    ),
+    tag = openapi::GROUP_STATUS
+)]
+pub async fn get_epoch_handover<S: SyncServiceState>(
+    State(state): State<Arc<S>>,
+) -> ApiSuccess<Option<EpochHandoverInfo>> {
+    ApiSuccess::ok(state.epoch_handover())
+}
+
+/// Specifies the page of stored blobs to be returned.
+#[serde_as]
+#[derive(Debug, Clone, Deserialize, utoipa::IntoParams)]
+#[serde(rename_all = "camelCase")]
+#[into_params(style = Form, parameter_in = Query)]
+pub struct ListStoredBlobsQuery {
+    /// The blob ID after which to start the page, as returned as the cursor of the previous page.
+    #[serde_as(as = "Option<DisplayFromStr>")]
+    #[serde(default)]
+    #[param(value_type = Option<String>)]
+    cursor: Option<BlobId>,
+    /// The maximum number of blob IDs to return, at most 1000. Defaults to 100.
+    limit: Option<usize>,
+}
+
+/// List the blobs stored for a shard.
+///
+/// Returns the IDs of the blobs for which the node stores slivers of the specified shard, in
+/// ascending order. The results are paginated; the cursor returned with a page can be used to
+/// request the next page, and is absent for the last page.
+#[tracing::instrument(skip_all, err(level = Level::DEBUG), fields(
+    walrus.shard_index = %shard_index
+))]
+#[utoipa::path(
+    get,
+    path = STORED_BLOBS_ENDPOINT,
+    params(("shard_index" = u16,), ListStoredBlobsQuery),
+    responses(
+        (status = 200, description = "A page of the IDs of the stored blobs",
+        body = ApiSuccess<StoredBlobsPage>),
+        ListStoredBlobsError,
+    ),
+    tag = openapi::GROUP_STATUS
+)]
+pub async fn list_stored_blobs<S: SyncServiceState>(
+    State(state): State<Arc<S>>,
+    Path(shard_index): Path<ShardIndex>,
+    Query(query): Query<ListStoredBlobsQuery>,
+) -> Result<ApiSuccess<StoredBlobsPage>, ListStoredBlobsError> {
+    let limit = query
+        .limit
+        .unwrap_or(DEFAULT_STORED_BLOBS_PAGE_SIZE)
+        .clamp(1, MAX_STORED_BLOBS_PAGE_SIZE);
+    let page = state
+        .list_stored_blobs(shard_index, query.cursor, limit)
+        .await?;
+
+    Ok(ApiSuccess::ok(page))
+}
+
 #[tracing::instrument(skip_all)]
 #[utoipa::path(
     post,
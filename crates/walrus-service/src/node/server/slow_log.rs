@@ -0,0 +1,245 @@
+// Copyright (c) Walrus Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Log of the requests that exceeded the latency threshold of their route.
+//!
+//! Slow requests are logged with the target [`SLOW_REQUEST_LOG_TARGET`] and the most recent ones
+//! are retained in memory, from where they can be retrieved through the admin API.
+
+use std::{
+    cell::Cell,
+    collections::VecDeque,
+    net::{IpAddr, SocketAddr},
+    str::FromStr as _,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant, SystemTime},
+};
+
+use axum::{
+    extract::{ConnectInfo, MatchedPath, Request, State},
+    middleware::Next,
+    response::Response,
+};
+use serde::Serialize;
+use walrus_core::BlobId;
+
+use crate::{common::telemetry::UNMATCHED_ROUTE, node::config::SlowRequestLogConfig};
+
+/// The tracing target of the log entries for slow requests.
+pub const SLOW_REQUEST_LOG_TARGET: &str = "walrus::slow_requests";
+
+/// The number of characters of the blob ID that are recorded for a slow request.
+const BLOB_ID_PREFIX_LENGTH: usize = 8;
+
+tokio::task_local! {
+    /// The time spent on database operations by the request handled on the current task.
+    static DB_TIME: Cell<Duration>;
+}
+
+/// Adds `duration` to the database time of the request handled on the current task, if any.
+pub(crate) fn record_db_time(duration: Duration) {
+    // Database operations outside of requests are not attributed to any request.
+    let _ = DB_TIME.try_with(|db_time| db_time.set(db_time.get() + duration));
+}
+
+/// A request that exceeded the latency threshold of its route.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct SlowRequest {
+    /// The time at which the request was received, in milliseconds since the Unix epoch.
+    pub received_at_millis: u64,
+    /// The HTTP method of the request.
+    pub method: String,
+    /// The route of the request, such as `/v1/blobs/{blob_id}/metadata`.
+    pub route: String,
+    /// The HTTP status code of the response.
+    pub status: u16,
+    /// The prefix of the blob ID in the request path, if any.
+    pub blob_id_prefix: Option<String>,
+    /// The IP address of the peer that made the request.
+    #[schema(value_type = Option<String>)]
+    pub peer: Option<IpAddr>,
+    /// The time until the response was available, in milliseconds.
+    pub duration_millis: u64,
+    /// The time spent on database operations while handling the request, in milliseconds.
+    ///
+    /// Only includes operations that completed on the task handling the request.
+    pub db_time_millis: u64,
+}
+
+/// A bounded log of the most recent slow requests.
+#[derive(Debug)]
+pub(crate) struct SlowRequestLog {
+    config: SlowRequestLogConfig,
+    entries: Mutex<VecDeque<SlowRequest>>,
+}
+
+impl SlowRequestLog {
+    pub fn new(config: SlowRequestLogConfig) -> Self {
+        Self {
+            entries: Mutex::default(),
+            config,
+        }
+    }
+
+    /// Returns the retained slow requests, from the most recent to the oldest.
+    pub fn entries(&self) -> Vec<SlowRequest> {
+        self.entries
+            .lock()
+            .expect("mutex should not be poisoned")
+            .iter()
+            .rev()
+            .cloned()
+            .collect()
+    }
+
+    fn threshold(&self, route: &str) -> Duration {
+        self.config
+            .route_thresholds
+            .get(route)
+            .copied()
+            .unwrap_or(self.config.threshold)
+    }
+
+    fn record(&self, entry: SlowRequest) {
+        if self.config.capacity == 0 {
+            return;
+        }
+        let mut entries = self.entries.lock().expect("mutex should not be poisoned");
+        if entries.len() == self.config.capacity {
+            entries.pop_front();
+        }
+        entries.push_back(entry);
+    }
+}
+
+/// Middleware that records requests that exceed the latency threshold of their route in the
+/// [`SlowRequestLog`].
+pub(crate) async fn slow_request_log_middleware(
+    State(log): State<Arc<SlowRequestLog>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let received_at = SystemTime::now();
+    let start = Instant::now();
+
+    let method = request.method().to_string();
+    let route = request
+        .extensions()
+        .get::<MatchedPath>()
+        .map_or(UNMATCHED_ROUTE, MatchedPath::as_str)
+        .to_owned();
+    let peer = request
+        .extensions()
+        .get::<ConnectInfo<SocketAddr>>()
+        .map(|ConnectInfo(address)| address.ip());
+    let blob_id_prefix = blob_id_prefix(request.uri().path());
+
+    let (response, db_time) = DB_TIME
+        .scope(Cell::new(Duration::ZERO), async {
+            let response = next.run(request).await;
+            (response, DB_TIME.with(Cell::get))
+        })
+        .await;
+
+    let duration = start.elapsed();
+    if duration < log.threshold(&route) {
+        return response;
+    }
+
+    let entry = SlowRequest {
+        received_at_millis: to_millis(
+            received_at
+                .duration_since(SystemTime::UNIX_EPOCH)
+                .unwrap_or_default(),
+        ),
+        method,
+        route,
+        status: response.status().as_u16(),
+        blob_id_prefix,
+        peer,
+        duration_millis: to_millis(duration),
+        db_time_millis: to_millis(db_time),
+    };
+    tracing::info!(
+        target: SLOW_REQUEST_LOG_TARGET,
+        http.request.method = %entry.method,
+        http.route = %entry.route,
+        http.response.status_code = entry.status,
+        walrus.blob_id_prefix = ?entry.blob_id_prefix,
+        client.address = ?entry.peer,
+        duration_millis = entry.duration_millis,
+        db_time_millis = entry.db_time_millis,
+        "request exceeded the latency threshold"
+    );
+    log.record(entry);
+
+    response
+}
+
+/// Returns the prefix of the first segment of `path` that is a blob ID.
+fn blob_id_prefix(path: &str) -> Option<String> {
+    path.split('/')
+        .find(|segment| BlobId::from_str(segment).is_ok())
+        .map(|blob_id| blob_id.chars().take(BLOB_ID_PREFIX_LENGTH).collect())
+}
+
+fn to_millis(duration: Duration) -> u64 {
+    u64::try_from(duration.as_millis()).unwrap_or(u64::MAX)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn slow_request(route: &str) -> SlowRequest {
+        SlowRequest {
+            received_at_millis: 0,
+            method: "GET".to_owned(),
+            route: route.to_owned(),
+            status: 200,
+            blob_id_prefix: None,
+            peer: None,
+            duration_millis: 2000,
+            db_time_millis: 0,
+        }
+    }
+
+    #[test]
+    fn retains_the_most_recent_requests_up_to_the_capacity() {
+        let log = SlowRequestLog::new(SlowRequestLogConfig {
+            capacity: 2,
+            ..Default::default()
+        });
+
+        for route in ["/first", "/second", "/third"] {
+            log.record(slow_request(route));
+        }
+
+        assert_eq!(
+            log.entries(),
+            vec![slow_request("/third"), slow_request("/second")]
+        );
+    }
+
+    #[test]
+    fn route_thresholds_take_precedence() {
+        let log = SlowRequestLog::new(SlowRequestLogConfig {
+            threshold: Duration::from_secs(1),
+            route_thresholds: [("/v1/health".to_owned(), Duration::from_millis(100))].into(),
+            ..Default::default()
+        });
+
+        assert_eq!(log.threshold("/v1/health"), Duration::from_millis(100));
+        assert_eq!(log.threshold("/v1/blobs/{blob_id}"), Duration::from_secs(1));
+    }
+
+    #[test]
+    fn extracts_the_blob_id_prefix_from_the_path() {
+        let blob_id = BlobId([7; 32]).to_string();
+        let path = format!("/v1/blobs/{blob_id}/slivers/1/primary");
+
+        assert_eq!(blob_id_prefix(&path), Some(blob_id[..8].to_owned()));
+        assert_eq!(blob_id_prefix("/v1/health"), None);
+    }
+}
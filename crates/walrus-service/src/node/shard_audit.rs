@@ -0,0 +1,91 @@
+// Copyright (c) Walrus Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Audit of the shards in the node's storage against the shards assigned to it on chain.
+
+use std::collections::{BTreeSet, HashMap};
+
+use walrus_core::{Epoch, ShardIndex};
+use walrus_rest_client::api::{
+    ShardHealthInfo,
+    ShardOwnershipAudit,
+    ShardStatus as ApiShardStatus,
+};
+
+use super::{api_status_from_shard_status, storage::ShardStatus};
+
+/// Compares the shards present in storage with the shards assigned to the node in `epoch`.
+///
+/// Shards in `retained` are needed for an ongoing epoch change, for example because they are
+/// being transferred to or from the node, and are never reported as extraneous. A present shard
+/// that is locked to move is also kept, as other nodes may still sync it from this node.
+pub(super) fn audit_shard_ownership(
+    epoch: Epoch,
+    assigned: &[ShardIndex],
+    retained: &[ShardIndex],
+    present: HashMap<ShardIndex, Option<ShardStatus>>,
+) -> ShardOwnershipAudit {
+    let assigned: BTreeSet<_> = assigned.iter().copied().collect();
+
+    let mut audit = ShardOwnershipAudit {
+        epoch,
+        assigned: assigned.iter().copied().collect(),
+        ..Default::default()
+    };
+
+    for shard in &assigned {
+        if present.get(shard).copied().flatten() != Some(ShardStatus::Active) {
+            audit.missing.push(*shard);
+        }
+    }
+
+    for (shard, status) in present {
+        if !assigned.contains(&shard)
+            && !retained.contains(&shard)
+            && status != Some(ShardStatus::LockedToMove)
+        {
+            audit.extraneous.push(shard);
+        }
+        audit.present.push(ShardHealthInfo {
+            shard,
+            status: status.map_or(ApiShardStatus::Unknown, api_status_from_shard_status),
+        });
+    }
+
+    audit.present.sort_by_key(|info| info.shard);
+    audit.extraneous.sort_unstable();
+    audit
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reports_extraneous_and_missing_shards() {
+        let present = HashMap::from([
+            (ShardIndex(0), Some(ShardStatus::Active)),
+            (ShardIndex(1), Some(ShardStatus::ActiveSync)),
+            (ShardIndex(3), Some(ShardStatus::Active)),
+            (ShardIndex(4), Some(ShardStatus::LockedToMove)),
+            (ShardIndex(5), Some(ShardStatus::Active)),
+        ]);
+
+        let audit = audit_shard_ownership(
+            7,
+            &[ShardIndex(2), ShardIndex(1), ShardIndex(0)],
+            &[ShardIndex(5)],
+            present,
+        );
+
+        assert_eq!(audit.epoch, 7);
+        assert_eq!(
+            audit.assigned,
+            vec![ShardIndex(0), ShardIndex(1), ShardIndex(2)]
+        );
+        assert_eq!(audit.missing, vec![ShardIndex(1), ShardIndex(2)]);
+        assert_eq!(audit.extraneous, vec![ShardIndex(3)]);
+        assert_eq!(audit.present.len(), 5);
+        assert_eq!(audit.present[4].status, ApiShardStatus::Ready);
+    }
+}
@@ -14,7 +14,7 @@ use tokio::{
     time::Instant,
 };
 use walrus_core::{BlobId, Epoch, ShardIndex};
-use walrus_rest_client::error::ServiceError;
+use walrus_rest_client::{api::NodeEvent, error::ServiceError};
 use walrus_utils::backoff::{BackoffStrategy, ExponentialBackoff};
 
 use super::{
@@ -348,6 +348,11 @@ impl ShardSyncHandler {
         let shard_sync_task = tokio::spawn(async move {
             let shard_index = shard_storage.id();
             let mut last_progress_time = Instant::now();
+            let local_events = &shard_sync_handler_clone.node.local_events;
+            local_events.emit(NodeEvent::ShardSyncStarted {
+                shard: shard_index,
+                epoch: current_epoch,
+            });
 
             let mut backoff = ExponentialBackoff::new_with_seed(
                 shard_sync_handler_clone.config.shard_sync_retry_min_backoff,
@@ -419,6 +424,18 @@ impl ShardSyncHandler {
                 }
             }
 
+            local_events.emit(if shard_sync_success {
+                NodeEvent::ShardSyncCompleted {
+                    shard: shard_index,
+                    epoch: current_epoch,
+                }
+            } else {
+                NodeEvent::ShardSyncFailed {
+                    shard: shard_index,
+                    epoch: current_epoch,
+                }
+            });
+
             // Remove the task from the shard_sync_in_progress map upon completion.
             let epoch_sync_done = if shard_sync_success {
                 let mut shard_sync_map =
@@ -430,6 +447,10 @@ impl ShardSyncHandler {
             };
 
             if epoch_sync_done {
+                shard_sync_handler_clone
+                    .node
+                    .epoch_handover
+                    .mark_synced(current_epoch);
                 shard_sync_handler_clone
                     .node
                     .contract_service
@@ -36,7 +36,8 @@ impl StartEpochChangeFinisher {
     ///
     /// This includes the following:
     /// - Sending epoch sync done if there is no newly scheduled shard syncs.
-    /// - Removing no longer owned storage for shards.
+    /// - Removing no longer owned storage for shards, once the transition in which they were
+    ///   handed over has been confirmed.
     /// - Marking the event as completed.
     pub fn start_finish_epoch_change_tasks(
         &self,
@@ -69,6 +70,15 @@ impl StartEpochChangeFinisher {
                 if !ongoing_shard_sync {
                     self_clone.epoch_sync_done(&committees, &event_clone).await;
                 }
+                // The shards to remove were handed over in a previous transition. Continue serving
+                // reads from them until the new owners have confirmed that they are synced.
+                if !shards.is_empty() {
+                    self_clone
+                        .node
+                        .epoch_handover
+                        .wait_for_confirmation(event_clone.epoch.saturating_sub(1))
+                        .await;
+                }
                 self_clone
                     .remove_storage_for_shards(event_clone.clone(), &shards.clone())
                     .await?;
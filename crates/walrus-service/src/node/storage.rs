@@ -6,7 +6,7 @@ use std::{
     collections::{hash_map::Entry, HashMap},
     fmt::Debug,
     ops::Bound::{Excluded, Included},
-    path::Path,
+    path::{Path, PathBuf},
     sync::Arc,
     time::Instant,
 };
@@ -27,7 +27,7 @@ use typed_store::{
     TypedStoreError,
 };
 use walrus_core::{
-    messages::{SyncShardRequest, SyncShardResponse},
+    messages::{EpochAccountingReport, SyncShardRequest, SyncShardResponse},
     metadata::{BlobMetadata, VerifiedBlobMetadataWithId},
     BlobId,
     Epoch,
@@ -39,6 +39,7 @@ use walrus_utils::metrics::Registry;
 use self::{
     blob_info::{BlobInfo, BlobInfoApi, BlobInfoTable},
     constants::{
+        epoch_accounting_cf_name,
         metadata_cf_name,
         node_status_cf_name,
         pending_recover_slivers_column_family_name,
@@ -55,6 +56,10 @@ use crate::utils;
 pub(crate) mod blob_info;
 pub(crate) mod constants;
 
+mod blob_id_filter;
+pub use blob_id_filter::BlobIdFilterConfig;
+use blob_id_filter::{BlobIdFilterStore, BLOB_ID_FILTERS_DIRECTORY};
+
 mod database_config;
 pub use database_config::DatabaseConfig;
 
@@ -62,6 +67,10 @@ mod event_cursor_table;
 pub(super) use event_cursor_table::EventProgress;
 
 mod event_sequencer;
+
+mod integrity;
+pub(crate) use integrity::IntegrityReport;
+
 mod metrics;
 mod shard;
 
@@ -75,6 +84,7 @@ pub(crate) use shard::{
     SecondarySliverData,
     ShardStatus,
     ShardStorage,
+    SliverChecksumStatus,
 };
 
 pub(crate) fn metadata_options(db_config: &DatabaseConfig) -> Options {
@@ -85,6 +95,10 @@ pub(crate) fn node_status_options(db_config: &DatabaseConfig) -> Options {
     db_config.node_status().to_options()
 }
 
+pub(crate) fn epoch_accounting_options(db_config: &DatabaseConfig) -> Options {
+    db_config.epoch_accounting().to_options()
+}
+
 /// The status of the node.
 //
 //     Standby <--> RecoveryCatchUp --> RecoveryInProgress
@@ -145,10 +159,14 @@ pub struct Storage {
     metadata: DBMap<BlobId, BlobMetadata>,
     blob_info: BlobInfoTable,
     event_cursor: EventCursorTable,
+    epoch_accounting: DBMap<Epoch, EpochAccountingReport>,
     shards: Arc<RwLock<HashMap<ShardIndex, Arc<ShardStorage>>>>,
     config: DatabaseConfig,
     metrics: Arc<CommonDatabaseMetrics>,
     metrics_registry: Registry,
+    blob_id_filter_store: Option<Arc<BlobIdFilterStore>>,
+    path: PathBuf,
+    unclean_shutdown: bool,
 }
 
 /// An opaque lock object that can be required to later access the shards map.
@@ -221,6 +239,8 @@ impl Storage {
         let metadata_cf_name = metadata_cf_name();
         let blob_info_column_families = BlobInfoTable::options(&db_config);
         let (event_cursor_cf_name, event_cursor_options) = EventCursorTable::options(&db_config);
+        let epoch_accounting_cf_name = epoch_accounting_cf_name();
+        let epoch_accounting_options = epoch_accounting_options(&db_config);
 
         let expected_column_families: Vec<_> = shard_column_families
             .iter_mut()
@@ -229,6 +249,7 @@ impl Storage {
                 (node_status_cf_name, node_status_options),
                 (metadata_cf_name, metadata_options),
                 (event_cursor_cf_name, event_cursor_options),
+                (epoch_accounting_cf_name, epoch_accounting_options),
             ])
             .chain(blob_info_column_families)
             .collect::<Vec<_>>();
@@ -239,6 +260,7 @@ impl Storage {
             metrics_config,
             &expected_column_families,
         )?;
+        let unclean_shutdown = integrity::mark_running(path)?;
 
         let node_status = DBMap::reopen(
             &database,
@@ -258,13 +280,32 @@ impl Storage {
         )?;
 
         let event_cursor = EventCursorTable::reopen(&database)?;
+        let epoch_accounting = DBMap::reopen(
+            &database,
+            Some(epoch_accounting_cf_name),
+            &ReadWriteOptions::default(),
+            false,
+        )?;
         let blob_info = BlobInfoTable::reopen(&database)?;
+        let blob_id_filter_store = db_config.blob_id_filter.clone().map(|config| {
+            Arc::new(BlobIdFilterStore::new(
+                path.join(BLOB_ID_FILTERS_DIRECTORY),
+                config,
+            ))
+        });
         let shards = Arc::new(RwLock::new(
             existing_shards_ids
                 .into_iter()
                 .map(|id| {
-                    ShardStorage::create_or_reopen(id, &database, &db_config, None, &registry)
-                        .map(|shard| (id, Arc::new(shard)))
+                    ShardStorage::create_or_reopen(
+                        id,
+                        &database,
+                        &db_config,
+                        None,
+                        blob_id_filter_store.as_ref(),
+                        &registry,
+                    )
+                    .map(|shard| (id, Arc::new(shard)))
                 })
                 .collect::<Result<_, _>>()?,
         ));
@@ -275,6 +316,7 @@ impl Storage {
             metadata,
             blob_info,
             event_cursor,
+            epoch_accounting,
             shards,
             config: db_config,
             metrics: Arc::new(CommonDatabaseMetrics::new_with_id(
@@ -282,9 +324,28 @@ impl Storage {
                 "storage".to_owned(),
             )),
             metrics_registry: registry,
+            blob_id_filter_store,
+            path: path.to_owned(),
+            unclean_shutdown,
         })
     }
 
+    /// Writes the filters over the stored blob IDs of all shards to disk, so that they are not
+    /// rebuilt when the storage is opened again.
+    ///
+    /// Must only be called once no further slivers are stored.
+    pub(crate) async fn persist_blob_id_filters(&self) {
+        for shard_storage in self.shards.read().await.values() {
+            if let Err(error) = shard_storage.persist_blob_id_filters() {
+                tracing::warn!(
+                    ?error,
+                    walrus.shard_index = %shard_storage.id(),
+                    "failed to persist the blob ID filters of the shard"
+                );
+            }
+        }
+    }
+
     pub(crate) fn node_status(&self) -> Result<NodeStatus, TypedStoreError> {
         self.node_status
             .get(&())
@@ -295,6 +356,22 @@ impl Storage {
         self.node_status.insert(&(), &status)
     }
 
+    /// Returns the storage accounting recorded for the specified epoch, if any.
+    pub(crate) fn get_epoch_accounting(
+        &self,
+        epoch: Epoch,
+    ) -> Result<Option<EpochAccountingReport>, TypedStoreError> {
+        self.epoch_accounting.get(&epoch)
+    }
+
+    /// Stores the storage accounting for the epoch of the report, replacing any previous report.
+    pub(crate) fn put_epoch_accounting(
+        &self,
+        report: &EpochAccountingReport,
+    ) -> Result<(), TypedStoreError> {
+        self.epoch_accounting.insert(&report.epoch, report)
+    }
+
     /// Returns lock write access to the shards map, and returns the underlying shard map.
     pub(crate) async fn lock_shards(&self) -> StorageShardLock {
         let shards_guard = self.shards.clone().write_owned().await;
@@ -337,6 +414,7 @@ impl Storage {
                         &self.database,
                         &self.config,
                         Some(ShardStatus::None),
+                        self.blob_id_filter_store.as_ref(),
                         &self.metrics_registry,
                     )
                     .inspect_err(|error| {
@@ -421,7 +499,7 @@ impl Storage {
         metadata: &VerifiedBlobMetadataWithId,
     ) -> Result<(), TypedStoreError> {
         self.metadata
-            .insert(metadata.blob_id(), metadata.metadata())
+            .insert(metadata.blob_id(), &metadata.metadata().to_v1())
     }
 
     /// Store the metadata without updating blob info. This is only used during storing metadata for
@@ -435,6 +513,9 @@ impl Storage {
     }
 
     /// Store the verified metadata.
+    ///
+    /// The metadata is stored as [`BlobMetadata::V1`], so that the database can still be read by
+    /// earlier versions of the node, see [`BlobMetadata::to_v1`].
     #[tracing::instrument(skip_all)]
     pub async fn put_verified_metadata(
         &self,
@@ -457,8 +538,9 @@ impl Storage {
             ..Default::default()
         };
 
+        let metadata = metadata.to_v1();
         let mut batch = self.metadata.batch();
-        batch.insert_batch(&self.metadata, [(blob_id, metadata)])?;
+        batch.insert_batch(&self.metadata, [(blob_id, &*metadata)])?;
         self.blob_info
             .set_metadata_stored(&mut batch, blob_id, true)?;
 
@@ -718,6 +800,14 @@ impl Storage {
             .certified_per_object_blob_info_iter_before_epoch(epoch, std::ops::Bound::Unbounded)
     }
 
+    /// Returns the IDs of the blobs whose latest registration ends at `epoch`.
+    pub(crate) fn blob_ids_expiring_at_epoch(
+        &self,
+        epoch: Epoch,
+    ) -> Result<Vec<BlobId>, TypedStoreError> {
+        self.blob_info.blob_ids_expiring_at_epoch(epoch)
+    }
+
     /// Returns the current event cursor.
     pub(crate) fn get_event_cursor_progress(&self) -> Result<EventProgress, TypedStoreError> {
         self.event_cursor.get_event_cursor_progress()
@@ -881,6 +971,25 @@ pub(crate) mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn stores_metadata_without_encoding_params() -> TestResult {
+        let storage = empty_storage().await;
+        let storage = storage.as_ref();
+        let metadata = walrus_core::test_utils::verified_blob_metadata();
+        let blob_id = metadata.blob_id();
+
+        storage.update_blob_info(0, &BlobCertified::for_testing(*blob_id).into())?;
+
+        storage
+            .put_verified_metadata(&metadata.clone().with_encoding_params())
+            .await?;
+        let retrieved = storage.get_metadata(blob_id)?;
+
+        assert_eq!(retrieved, Some(metadata));
+
+        Ok(())
+    }
+
     #[tokio::test]
     async fn stores_and_deletes_metadata() -> TestResult {
         let storage = empty_storage().await;
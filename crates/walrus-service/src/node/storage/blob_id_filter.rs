@@ -0,0 +1,574 @@
+// Copyright (c) Walrus Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Bloom filters over the IDs of the blobs whose slivers are stored in a shard.
+//!
+//! The filters allow answering most lookups of slivers that are not stored without accessing the
+//! database. Filters have no false negatives, as blob IDs are inserted before the corresponding
+//! sliver is written to the database. As blob IDs cannot be removed from a filter, the IDs of
+//! deleted slivers remain in the filter until it is rebuilt.
+//!
+//! The filters are written to disk when the node shuts down and loaded again when the shard
+//! storage is opened. A persisted filter is removed as soon as it is loaded, so that a filter
+//! that is missing updates, for example, after a crash, is never loaded. Missing filters are
+//! rebuilt from the database on a background thread, so that opening the storage does not scan
+//! the slivers of the shard; until a filter is built, all lookups access the database.
+
+use std::{
+    f64::consts::LN_2,
+    fs,
+    io,
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+        Mutex,
+        OnceLock,
+    },
+    thread,
+};
+
+use serde::{Deserialize, Serialize};
+use typed_store::TypedStoreError;
+use walrus_core::{BlobId, ShardIndex, SliverType};
+
+/// The name of the directory, within the database directory, in which filters are persisted.
+pub(super) const BLOB_ID_FILTERS_DIRECTORY: &str = "blob_id_filters";
+
+/// The result of reading the IDs of the blobs for which slivers are stored.
+pub(crate) type StoredBlobIds = Result<Vec<BlobId>, TypedStoreError>;
+
+/// Configuration of the bloom filters over the IDs of the blobs stored in each shard.
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
+#[serde(default)]
+pub struct BlobIdFilterConfig {
+    /// The false-positive rate targeted by the filters.
+    pub false_positive_rate: f64,
+    /// The minimum number of blob IDs for which each filter is sized.
+    ///
+    /// Filters are sized for twice the number of stored blob IDs when they are built, but for at
+    /// least this number of blob IDs.
+    pub min_capacity: u64,
+}
+
+impl Default for BlobIdFilterConfig {
+    fn default() -> Self {
+        Self {
+            false_positive_rate: 0.01,
+            min_capacity: 100_000,
+        }
+    }
+}
+
+/// A bloom filter over blob IDs, which supports concurrent inserts and lookups.
+#[derive(Debug)]
+pub(crate) struct BlobIdFilter {
+    bits: Box<[AtomicU64]>,
+    n_hashes: u32,
+    capacity: u64,
+    n_inserted: AtomicU64,
+}
+
+impl BlobIdFilter {
+    /// Creates an empty filter that has approximately the specified false-positive rate when
+    /// containing `capacity` blob IDs.
+    pub fn with_capacity(capacity: u64, false_positive_rate: f64) -> Self {
+        let capacity = capacity.max(1);
+        let false_positive_rate = false_positive_rate.clamp(f64::MIN_POSITIVE, 0.5);
+        let n_bits = (-(capacity as f64) * false_positive_rate.ln() / (LN_2 * LN_2)).ceil();
+        let n_words = (n_bits as u64).div_ceil(64).max(1);
+        let n_hashes = ((n_words * 64) as f64 / capacity as f64 * LN_2).round() as u32;
+
+        Self {
+            bits: (0..n_words).map(|_| AtomicU64::new(0)).collect(),
+            n_hashes: n_hashes.clamp(1, 32),
+            capacity,
+            n_inserted: AtomicU64::new(0),
+        }
+    }
+
+    /// Inserts the blob ID into the filter.
+    pub fn insert(&self, blob_id: &BlobId) {
+        for (word, mask) in self.bit_positions(blob_id) {
+            self.bits[word].fetch_or(mask, Ordering::Relaxed);
+        }
+        self.n_inserted.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Returns false if the blob ID was definitely not inserted into the filter.
+    pub fn may_contain(&self, blob_id: &BlobId) -> bool {
+        self.bit_positions(blob_id)
+            .all(|(word, mask)| self.bits[word].load(Ordering::Relaxed) & mask != 0)
+    }
+
+    /// Returns true if more blob IDs were inserted than the filter was sized for, in which case
+    /// its false-positive rate exceeds the configured rate.
+    pub fn is_saturated(&self) -> bool {
+        self.n_inserted.load(Ordering::Relaxed) > self.capacity
+    }
+
+    /// Returns the word index and bit mask of each of the bits corresponding to the blob ID.
+    ///
+    /// Blob IDs are the output of a cryptographic hash function, therefore the bit positions are
+    /// derived directly from their bytes using double hashing.
+    fn bit_positions(&self, blob_id: &BlobId) -> impl Iterator<Item = (usize, u64)> {
+        let n_bits = self.bits.len() as u64 * 64;
+        let hash_1 = u64::from_le_bytes(blob_id.0[..8].try_into().expect("slice has 8 bytes"));
+        let hash_2 = u64::from_le_bytes(blob_id.0[8..16].try_into().expect("slice has 8 bytes"));
+
+        (0..u64::from(self.n_hashes)).map(move |i| {
+            let position = hash_1.wrapping_add(i.wrapping_mul(hash_2)) % n_bits;
+            ((position / 64) as usize, 1 << (position % 64))
+        })
+    }
+}
+
+/// The on-disk representation of a [`BlobIdFilter`].
+#[derive(Debug, Serialize, Deserialize)]
+enum PersistedBlobIdFilter {
+    V1 {
+        n_hashes: u32,
+        capacity: u64,
+        n_inserted: u64,
+        bits: Vec<u64>,
+    },
+}
+
+impl From<&BlobIdFilter> for PersistedBlobIdFilter {
+    fn from(filter: &BlobIdFilter) -> Self {
+        Self::V1 {
+            n_hashes: filter.n_hashes,
+            capacity: filter.capacity,
+            n_inserted: filter.n_inserted.load(Ordering::Relaxed),
+            bits: filter
+                .bits
+                .iter()
+                .map(|word| word.load(Ordering::Relaxed))
+                .collect(),
+        }
+    }
+}
+
+impl From<PersistedBlobIdFilter> for BlobIdFilter {
+    fn from(persisted: PersistedBlobIdFilter) -> Self {
+        let PersistedBlobIdFilter::V1 {
+            n_hashes,
+            capacity,
+            n_inserted,
+            bits,
+        } = persisted;
+        Self {
+            bits: bits.into_iter().map(AtomicU64::new).collect(),
+            n_hashes,
+            capacity,
+            n_inserted: AtomicU64::new(n_inserted),
+        }
+    }
+}
+
+/// A [`BlobIdFilter`] that may still be being built.
+///
+/// Blob IDs inserted before the filter is built are buffered and inserted once it is built, so
+/// that the built filter has no false negatives.
+#[derive(Debug, Default)]
+struct LazyBlobIdFilter {
+    filter: OnceLock<BlobIdFilter>,
+    pending: Mutex<Vec<BlobId>>,
+}
+
+impl LazyBlobIdFilter {
+    fn built(filter: BlobIdFilter) -> Self {
+        Self {
+            filter: OnceLock::from(filter),
+            pending: Mutex::default(),
+        }
+    }
+
+    fn get(&self) -> Option<&BlobIdFilter> {
+        self.filter.get()
+    }
+
+    fn insert(&self, blob_id: &BlobId) {
+        if let Some(filter) = self.filter.get() {
+            filter.insert(blob_id);
+            return;
+        }
+        let mut pending = self.pending.lock().expect("mutex should not be poisoned");
+        // The filter may have been built while waiting for the lock.
+        match self.filter.get() {
+            Some(filter) => filter.insert(blob_id),
+            None => pending.push(*blob_id),
+        }
+    }
+
+    /// Returns true if the filter is not built yet or may contain the blob ID.
+    fn may_contain(&self, blob_id: &BlobId) -> bool {
+        self.filter
+            .get()
+            .is_none_or(|filter| filter.may_contain(blob_id))
+    }
+
+    /// Sets the filter, after inserting the blob IDs buffered while it was built.
+    fn set(&self, filter: BlobIdFilter) {
+        let mut pending = self.pending.lock().expect("mutex should not be poisoned");
+        pending
+            .drain(..)
+            .for_each(|blob_id| filter.insert(&blob_id));
+        let _ = self.filter.set(filter);
+    }
+}
+
+/// The filters over the blob IDs of the primary and secondary slivers stored in a shard.
+#[derive(Debug)]
+pub(crate) struct ShardBlobIdFilters {
+    shard: ShardIndex,
+    primary: LazyBlobIdFilter,
+    secondary: LazyBlobIdFilter,
+    store: Arc<BlobIdFilterStore>,
+    /// Whether the filters are persisted; inserts after persisting remove the persisted filters.
+    is_persisted: Mutex<bool>,
+}
+
+impl ShardBlobIdFilters {
+    /// Records that a sliver of the specified type is stored for the blob.
+    pub fn insert(&self, blob_id: &BlobId, sliver_type: SliverType) {
+        self.get(sliver_type).insert(blob_id);
+
+        let mut is_persisted = self
+            .is_persisted
+            .lock()
+            .expect("mutex should not be poisoned");
+        if *is_persisted {
+            self.store.remove(self.shard);
+            *is_persisted = false;
+        }
+    }
+
+    /// Returns false if no sliver of the specified type is stored for the blob.
+    ///
+    /// Always returns true while the filter is being built.
+    pub fn may_contain(&self, blob_id: &BlobId, sliver_type: SliverType) -> bool {
+        self.get(sliver_type).may_contain(blob_id)
+    }
+
+    /// Returns true if both filters are built.
+    pub fn is_built(&self) -> bool {
+        self.primary.get().is_some() && self.secondary.get().is_some()
+    }
+
+    /// Writes the filters to disk, if they are built.
+    pub fn persist(&self) -> io::Result<()> {
+        let mut is_persisted = self
+            .is_persisted
+            .lock()
+            .expect("mutex should not be poisoned");
+        let (Some(primary), Some(secondary)) = (self.primary.get(), self.secondary.get()) else {
+            tracing::debug!(
+                walrus.shard_index = %self.shard,
+                "not persisting blob ID filters that are still being built"
+            );
+            return Ok(());
+        };
+        self.store.persist(self.shard, primary, secondary)?;
+        *is_persisted = true;
+        Ok(())
+    }
+
+    #[cfg(test)]
+    fn wait_until_built(&self) {
+        let start = std::time::Instant::now();
+        while !self.is_built() {
+            assert!(
+                start.elapsed() < std::time::Duration::from_secs(10),
+                "the filters are not built within 10 seconds"
+            );
+            thread::sleep(std::time::Duration::from_millis(10));
+        }
+    }
+
+    fn get(&self, sliver_type: SliverType) -> &LazyBlobIdFilter {
+        match sliver_type {
+            SliverType::Primary => &self.primary,
+            SliverType::Secondary => &self.secondary,
+        }
+    }
+}
+
+/// Loads, builds, and persists the [`ShardBlobIdFilters`] of the shards.
+#[derive(Debug)]
+pub(crate) struct BlobIdFilterStore {
+    directory: PathBuf,
+    config: BlobIdFilterConfig,
+}
+
+impl BlobIdFilterStore {
+    /// Creates a new store persisting filters in the specified directory.
+    pub fn new(directory: PathBuf, config: BlobIdFilterConfig) -> Self {
+        Self { directory, config }
+    }
+
+    /// Loads the persisted filters of the shard, and rebuilds the missing filters from the stored
+    /// blob IDs on a background thread.
+    ///
+    /// `stored_blob_ids` reads the IDs of the blobs for which slivers of the given type are
+    /// stored.
+    pub fn load_or_build(
+        self: &Arc<Self>,
+        shard: ShardIndex,
+        stored_blob_ids: impl Fn(SliverType) -> StoredBlobIds + Send + 'static,
+    ) -> Arc<ShardBlobIdFilters> {
+        let load = |sliver_type| {
+            self.load(shard, sliver_type)
+                .map(LazyBlobIdFilter::built)
+                .unwrap_or_default()
+        };
+        let filters = Arc::new(ShardBlobIdFilters {
+            shard,
+            primary: load(SliverType::Primary),
+            secondary: load(SliverType::Secondary),
+            store: self.clone(),
+            is_persisted: Mutex::new(false),
+        });
+
+        if !filters.is_built() {
+            let store = self.clone();
+            let filters = filters.clone();
+            let spawn_result = thread::Builder::new()
+                .name(format!("blob-id-filter-{}", shard.0))
+                .spawn(move || store.build_missing(&filters, stored_blob_ids));
+            if let Err(error) = spawn_result {
+                tracing::warn!(
+                    ?error,
+                    walrus.shard_index = %shard,
+                    "failed to spawn the thread building the blob ID filters"
+                );
+            }
+        }
+        filters
+    }
+
+    /// Builds the filters that were not loaded from disk.
+    ///
+    /// If building a filter fails, the filter remains unbuilt and lookups access the database.
+    fn build_missing(
+        &self,
+        filters: &ShardBlobIdFilters,
+        stored_blob_ids: impl Fn(SliverType) -> StoredBlobIds,
+    ) {
+        for sliver_type in [SliverType::Primary, SliverType::Secondary] {
+            let lazy_filter = filters.get(sliver_type);
+            if lazy_filter.get().is_some() {
+                continue;
+            }
+            match stored_blob_ids(sliver_type) {
+                Ok(blob_ids) => lazy_filter.set(self.build(&blob_ids)),
+                Err(error) => tracing::warn!(
+                    ?error,
+                    walrus.shard_index = %filters.shard,
+                    %sliver_type,
+                    "failed to build the blob ID filter"
+                ),
+            }
+        }
+        tracing::debug!(walrus.shard_index = %filters.shard, "built the blob ID filters");
+    }
+
+    fn persist(
+        &self,
+        shard: ShardIndex,
+        primary: &BlobIdFilter,
+        secondary: &BlobIdFilter,
+    ) -> io::Result<()> {
+        fs::create_dir_all(&self.directory)?;
+        for (sliver_type, filter) in [
+            (SliverType::Primary, primary),
+            (SliverType::Secondary, secondary),
+        ] {
+            let persisted = PersistedBlobIdFilter::from(filter);
+            let bytes = bcs::to_bytes(&persisted).map_err(io::Error::other)?;
+
+            // Write to a temporary file first, to never leave a partially written filter behind.
+            let path = self.path(shard, sliver_type);
+            let temporary_path = path.with_extension("tmp");
+            fs::write(&temporary_path, bytes)?;
+            fs::rename(temporary_path, path)?;
+        }
+        Ok(())
+    }
+
+    fn remove(&self, shard: ShardIndex) {
+        for sliver_type in [SliverType::Primary, SliverType::Secondary] {
+            let path = self.path(shard, sliver_type);
+            if let Err(error) = fs::remove_file(&path) {
+                if error.kind() != io::ErrorKind::NotFound {
+                    tracing::warn!(
+                        ?error,
+                        ?path,
+                        "failed to remove the persisted blob ID filter"
+                    );
+                }
+            }
+        }
+    }
+
+    fn load(&self, shard: ShardIndex, sliver_type: SliverType) -> Option<BlobIdFilter> {
+        let path = self.path(shard, sliver_type);
+        let bytes = match fs::read(&path) {
+            Ok(bytes) => bytes,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => return None,
+            Err(error) => {
+                tracing::warn!(?error, ?path, "failed to read the persisted blob ID filter");
+                return None;
+            }
+        };
+
+        // The filter is only valid until the next write to the shard.
+        if let Err(error) = fs::remove_file(&path) {
+            tracing::warn!(
+                ?error,
+                ?path,
+                "failed to remove the persisted blob ID filter"
+            );
+            return None;
+        }
+
+        match bcs::from_bytes::<PersistedBlobIdFilter>(&bytes) {
+            Ok(persisted) => {
+                let filter = BlobIdFilter::from(persisted);
+                (!filter.is_saturated()).then_some(filter)
+            }
+            Err(error) => {
+                tracing::warn!(
+                    ?error,
+                    ?path,
+                    "failed to decode the persisted blob ID filter"
+                );
+                None
+            }
+        }
+    }
+
+    fn build(&self, blob_ids: &[BlobId]) -> BlobIdFilter {
+        let capacity = (2 * blob_ids.len() as u64).max(self.config.min_capacity);
+        let filter = BlobIdFilter::with_capacity(capacity, self.config.false_positive_rate);
+        for blob_id in blob_ids {
+            filter.insert(blob_id);
+        }
+        filter
+    }
+
+    fn path(&self, shard: ShardIndex, sliver_type: SliverType) -> PathBuf {
+        self.directory
+            .join(format!("{}-{sliver_type}.bloom", shard.0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use walrus_core::test_utils::random_blob_id;
+    use walrus_test_utils::Result as TestResult;
+
+    use super::*;
+
+    #[test]
+    fn filter_has_no_false_negatives_and_few_false_positives() {
+        let filter = BlobIdFilter::with_capacity(1000, 0.01);
+        let inserted: Vec<_> = (0..1000).map(|_| random_blob_id()).collect();
+        inserted.iter().for_each(|blob_id| filter.insert(blob_id));
+
+        assert!(inserted.iter().all(|blob_id| filter.may_contain(blob_id)));
+        let n_false_positives = (0..10_000)
+            .filter(|_| filter.may_contain(&random_blob_id()))
+            .count();
+        assert!(
+            n_false_positives < 300,
+            "{n_false_positives} false positives"
+        );
+        assert!(!filter.is_saturated());
+    }
+
+    #[test]
+    fn persisted_filters_are_loaded_once() -> TestResult {
+        let directory = tempfile::tempdir()?;
+        let store = Arc::new(BlobIdFilterStore::new(
+            directory.path().to_owned(),
+            Default::default(),
+        ));
+        let shard = ShardIndex(3);
+        let blob_id = random_blob_id();
+        let no_stored_blob_ids = |_: SliverType| Ok(vec![]);
+
+        let filters = store.load_or_build(shard, no_stored_blob_ids);
+        filters.wait_until_built();
+        assert!(!filters.may_contain(&blob_id, SliverType::Primary));
+        filters.insert(&blob_id, SliverType::Primary);
+        filters.persist()?;
+
+        // The persisted filters are loaded without building them.
+        let filters = store.load_or_build(shard, no_stored_blob_ids);
+        assert!(filters.is_built());
+        assert!(filters.may_contain(&blob_id, SliverType::Primary));
+        assert!(!filters.may_contain(&blob_id, SliverType::Secondary));
+
+        // The persisted filters were removed when they were loaded.
+        let filters = store.load_or_build(shard, no_stored_blob_ids);
+        filters.wait_until_built();
+        assert!(!filters.may_contain(&blob_id, SliverType::Primary));
+
+        Ok(())
+    }
+
+    #[test]
+    fn inserting_after_persisting_removes_persisted_filters() -> TestResult {
+        let directory = tempfile::tempdir()?;
+        let store = Arc::new(BlobIdFilterStore::new(
+            directory.path().to_owned(),
+            Default::default(),
+        ));
+        let shard = ShardIndex(3);
+        let blob_id = random_blob_id();
+        let no_stored_blob_ids = |_: SliverType| Ok(vec![]);
+
+        let filters = store.load_or_build(shard, no_stored_blob_ids);
+        filters.wait_until_built();
+        filters.persist()?;
+        filters.insert(&blob_id, SliverType::Secondary);
+
+        let stored_blob_ids = move |_: SliverType| Ok(vec![blob_id]);
+        let filters = store.load_or_build(shard, stored_blob_ids);
+        filters.wait_until_built();
+        assert!(filters.may_contain(&blob_id, SliverType::Secondary));
+
+        Ok(())
+    }
+
+    #[test]
+    fn blob_ids_inserted_while_building_are_kept() -> TestResult {
+        let directory = tempfile::tempdir()?;
+        let store = Arc::new(BlobIdFilterStore::new(
+            directory.path().to_owned(),
+            Default::default(),
+        ));
+        let stored_blob_id = random_blob_id();
+        let inserted_blob_id = random_blob_id();
+        let (sender, receiver) = std::sync::mpsc::channel::<()>();
+        let receiver = Mutex::new(receiver);
+
+        // The build only starts reading the stored blob IDs once a blob ID has been inserted.
+        let stored_blob_ids = move |_: SliverType| {
+            let _ = receiver.lock().expect("not poisoned").recv();
+            Ok(vec![stored_blob_id])
+        };
+        let filters = store.load_or_build(ShardIndex(3), stored_blob_ids);
+        assert!(filters.may_contain(&inserted_blob_id, SliverType::Primary));
+        filters.insert(&inserted_blob_id, SliverType::Primary);
+        drop(sender);
+        filters.wait_until_built();
+
+        assert!(filters.may_contain(&stored_blob_id, SliverType::Primary));
+        assert!(filters.may_contain(&inserted_blob_id, SliverType::Primary));
+        assert!(!filters.may_contain(&inserted_blob_id, SliverType::Secondary));
+
+        Ok(())
+    }
+}
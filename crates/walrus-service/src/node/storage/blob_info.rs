@@ -150,6 +150,11 @@ impl BlobInfoTable {
         batch.write()
     }
 
+    /// Returns the index of the latest event that was applied to the blob info.
+    pub fn latest_handled_event_index(&self) -> Result<Option<u64>, TypedStoreError> {
+        self.latest_handled_event_index.lock().unwrap().get(&())
+    }
+
     fn has_event_been_handled(latest_handled_index: Option<u64>, event_index: u64) -> bool {
         latest_handled_index.is_some_and(|i| event_index <= i)
     }
@@ -203,6 +208,20 @@ impl BlobInfoTable {
         )
     }
 
+    /// Returns the IDs of the valid blobs whose latest registration ends at `epoch`, and which are
+    /// therefore no longer registered from `epoch` on.
+    #[tracing::instrument(skip_all)]
+    pub fn blob_ids_expiring_at_epoch(&self, epoch: Epoch) -> Result<Vec<BlobId>, TypedStoreError> {
+        let mut blob_ids = vec![];
+        for item in self.aggregate_blob_info.safe_iter() {
+            let (blob_id, blob_info) = item?;
+            if blob_info.latest_end_epoch() == Some(epoch) && !blob_info.is_registered(epoch) {
+                blob_ids.push(blob_id);
+            }
+        }
+        Ok(blob_ids)
+    }
+
     /// Returns the blob info for `blob_id`.
     pub fn get(&self, blob_id: &BlobId) -> Result<Option<BlobInfo>, TypedStoreError> {
         self.aggregate_blob_info.get(blob_id)
@@ -385,6 +404,12 @@ pub(crate) trait BlobInfoApi: CertifiedBlobInfoApi {
     /// Returns true iff there exists at least one non-expired deletable or permanent `Blob` object.
     fn is_registered(&self, current_epoch: Epoch) -> bool;
 
+    /// Returns the latest end epoch among the (possibly already expired) deletable or permanent
+    /// `Blob` objects that have not been deleted.
+    ///
+    /// Returns `None` if there are no such objects or the blob is invalid.
+    fn latest_end_epoch(&self) -> Option<Epoch>;
+
     /// Returns the event through which this blob was marked invalid.
     ///
     /// Returns `None` if it isn't invalid.
@@ -1057,6 +1082,24 @@ impl BlobInfoApi for BlobInfoV1 {
         exists_registered_permanent_blob || maybe_exists_registered_deletable_blob
     }
 
+    fn latest_end_epoch(&self) -> Option<Epoch> {
+        let Self::Valid(ValidBlobInfoV1 {
+            count_deletable_total,
+            permanent_total,
+            latest_seen_deletable_registered_epoch,
+            ..
+        }) = self
+        else {
+            return None;
+        };
+
+        let permanent_end_epoch = permanent_total.as_ref().map(|p| p.end_epoch);
+        let deletable_end_epoch =
+            latest_seen_deletable_registered_epoch.filter(|_| *count_deletable_total > 0);
+
+        permanent_end_epoch.max(deletable_end_epoch)
+    }
+
     fn invalidation_event(&self) -> Option<EventID> {
         if let Self::Invalid { event, .. } = self {
             Some(*event)
@@ -1984,4 +2027,45 @@ mod tests {
             BlobStatus::Nonexistent,
         ));
     }
+
+    param_test! {
+        test_latest_end_epoch: [
+            no_objects: (ValidBlobInfoV1::default(), None),
+            permanent_only: (
+                ValidBlobInfoV1 {
+                    permanent_total: Some(PermanentBlobInfoV1::new_fixed_for_testing(1, 3, 0)),
+                    ..Default::default()
+                },
+                Some(3),
+            ),
+            deletable_only: (
+                ValidBlobInfoV1 {
+                    count_deletable_total: 1,
+                    latest_seen_deletable_registered_epoch: Some(4),
+                    ..Default::default()
+                },
+                Some(4),
+            ),
+            deleted_deletable: (
+                ValidBlobInfoV1 {
+                    count_deletable_total: 0,
+                    latest_seen_deletable_registered_epoch: Some(4),
+                    ..Default::default()
+                },
+                None,
+            ),
+            permanent_and_deletable: (
+                ValidBlobInfoV1 {
+                    permanent_total: Some(PermanentBlobInfoV1::new_fixed_for_testing(1, 5, 0)),
+                    count_deletable_total: 2,
+                    latest_seen_deletable_registered_epoch: Some(4),
+                    ..Default::default()
+                },
+                Some(5),
+            ),
+        ]
+    }
+    fn test_latest_end_epoch(blob_info: ValidBlobInfoV1, expected: Option<Epoch>) {
+        assert_eq!(BlobInfoV1::Valid(blob_info).latest_end_epoch(), expected);
+    }
 }
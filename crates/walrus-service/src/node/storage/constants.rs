@@ -10,6 +10,7 @@ const NODE_STATUS_COLUMN_FAMILY_NAME: &str = "node_status";
 const METADATA_COLUMN_FAMILY_NAME: &str = "metadata";
 const EVENT_INDEX_COLUMN_FAMILY_NAME: &str = "latest_handled_event_index";
 const EVENT_CURSOR_COLUMN_FAMILY_NAME: &str = "event_cursor";
+const EPOCH_ACCOUNTING_COLUMN_FAMILY_NAME: &str = "epoch_accounting";
 const EVENT_CURSOR_KEY: [u8; 6] = *b"cursor";
 
 // Base name for shard-related column families
@@ -55,6 +56,11 @@ pub fn event_cursor_cf_name() -> &'static str {
     EVENT_CURSOR_COLUMN_FAMILY_NAME
 }
 
+/// Returns the name of the epoch accounting column family.
+pub fn epoch_accounting_cf_name() -> &'static str {
+    EPOCH_ACCOUNTING_COLUMN_FAMILY_NAME
+}
+
 pub fn event_cursor_key() -> &'static [u8; 6] {
     &EVENT_CURSOR_KEY
 }
@@ -116,6 +122,7 @@ mod tests {
         assert_eq!(per_object_blob_info_cf_name(), "per_object_blob_info");
         assert_eq!(node_status_cf_name(), "node_status");
         assert_eq!(event_index_cf_name(), "latest_handled_event_index");
+        assert_eq!(epoch_accounting_cf_name(), "epoch_accounting");
 
         let shard = ShardIndex(900);
         assert_eq!(base_column_family_name(shard), "shard-900");
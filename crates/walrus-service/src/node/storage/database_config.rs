@@ -1,9 +1,11 @@
 // Copyright (c) Walrus Foundation
 // SPDX-License-Identifier: Apache-2.0
 
-use rocksdb::{BlockBasedOptions, Cache, DBCompressionType, Options};
+use rocksdb::{BlockBasedOptions, Cache, DBCompressionType, DBRecoveryMode, Options};
 use serde::{Deserialize, Serialize};
 
+use super::blob_id_filter::BlobIdFilterConfig;
+
 /// Options for configuring a column family.
 #[derive(Debug, Clone, Default, Deserialize, Serialize, PartialEq)]
 #[serde(default)]
@@ -223,6 +225,9 @@ impl Default for GlobalDatabaseOptions {
 impl From<&GlobalDatabaseOptions> for Options {
     fn from(value: &GlobalDatabaseOptions) -> Self {
         let mut options = Options::default();
+        // After a crash, recover all consistent writes from the write-ahead log and discard
+        // everything from the first incomplete record, such that no batch is partially applied.
+        options.set_wal_recovery_mode(DBRecoveryMode::PointInTime);
 
         if let Some(max_files) = value.max_open_files {
             options.set_max_open_files(max_files);
@@ -238,8 +243,8 @@ impl From<&GlobalDatabaseOptions> for Options {
 /// `optimized_for_blobs` options are applied to sliver and metadata tables.
 ///
 /// Options for all individual tables can be set as well through the `node_status`, `metadata`,
-/// `blob_info`, `per_object_blob_info`, `event_cursor`, `shard`, `shard_status`,
-/// `shard_sync_progress`, and `pending_recover_slivers` fields.
+/// `blob_info`, `per_object_blob_info`, `event_cursor`, `epoch_accounting`, `shard`,
+/// `shard_status`, `shard_sync_progress`, and `pending_recover_slivers` fields.
 ///
 /// **Warning:** Note that the configuration is currently not properly hierarchical. For example, if
 /// the `metadata` options are defined, they are *not* merged with the `optimized_for_blobs` or
@@ -265,6 +270,8 @@ pub struct DatabaseConfig {
     pub(super) per_object_blob_info: Option<DatabaseTableOptions>,
     /// Event cursor database options.
     pub(super) event_cursor: Option<DatabaseTableOptions>,
+    /// Epoch accounting database options.
+    pub(super) epoch_accounting: Option<DatabaseTableOptions>,
     /// Shard database options.
     pub(super) shard: Option<DatabaseTableOptions>,
     /// Shard status database options.
@@ -291,6 +298,18 @@ pub struct DatabaseConfig {
     pub(super) event_store: Option<DatabaseTableOptions>,
     /// Init state store database options.
     pub(super) init_state: Option<DatabaseTableOptions>,
+    /// Bloom filters over the IDs of the blobs stored in each shard, which avoid database
+    /// accesses when looking up slivers that are not stored.
+    ///
+    /// Disabled if `None`.
+    pub(super) blob_id_filter: Option<BlobIdFilterConfig>,
+    /// Whether to store the checksum of each sliver together with the sliver, which allows
+    /// detecting slivers that were corrupted on disk.
+    ///
+    /// Slivers stored with their checksum cannot be read by node versions that predate the
+    /// checksums, so this should only be enabled once rolling back to such a version is no
+    /// longer needed. Disabled by default.
+    pub(super) store_sliver_checksums: bool,
 }
 
 impl DatabaseConfig {
@@ -339,6 +358,14 @@ impl DatabaseConfig {
             .unwrap_or_else(|| self.standard.clone())
     }
 
+    /// Returns the epoch accounting database option.
+    pub fn epoch_accounting(&self) -> DatabaseTableOptions {
+        self.epoch_accounting
+            .clone()
+            .map(|options| options.inherit_from(self.standard.clone()))
+            .unwrap_or_else(|| self.standard.clone())
+    }
+
     /// Returns the shard database option.
     pub fn shard(&self) -> DatabaseTableOptions {
         self.shard
@@ -446,6 +473,7 @@ impl Default for DatabaseConfig {
             blob_info: Some(DatabaseTableOptions::blob_info()),
             per_object_blob_info: Some(DatabaseTableOptions::blob_info()),
             event_cursor: None,
+            epoch_accounting: None,
             shard: None,
             shard_status: None,
             shard_sync_progress: None,
@@ -459,6 +487,8 @@ impl Default for DatabaseConfig {
             committee_store: None,
             event_store: None,
             init_state: None,
+            blob_id_filter: None,
+            store_sliver_checksums: false,
         }
     }
 }
@@ -0,0 +1,232 @@
+// Copyright (c) Walrus Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Detection of unclean shutdowns and recovery of the storage before the node serves data again.
+//!
+//! While the storage is open, a marker file exists in the database directory, which is removed
+//! when the node shuts down cleanly. If the marker is found when opening the storage, the node
+//! previously stopped in the middle of its work. RocksDB then replays its write-ahead log up to
+//! the last consistent point, which discards partially written batches, and the node checks and
+//! repairs the state that spans several writes with [`Storage::check_integrity_and_recover`].
+
+use std::{fmt, io, path::Path};
+
+use typed_store::TypedStoreError;
+use walrus_core::ShardIndex;
+
+use super::{ShardStatus, Storage};
+
+/// The name of the file that marks the storage as in use.
+pub(super) const RUNNING_MARKER_FILE: &str = "RUNNING";
+
+/// Creates the marker file in the database directory at `path`.
+///
+/// Returns true if the marker already existed, i.e., the storage was not closed cleanly.
+pub(super) fn mark_running(path: &Path) -> io::Result<bool> {
+    let marker = path.join(RUNNING_MARKER_FILE);
+    let existed = marker.try_exists()?;
+    std::fs::write(marker, [])?;
+    Ok(existed)
+}
+
+/// The state of the storage found after an unclean shutdown, and the repairs that were made.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub(crate) struct IntegrityReport {
+    /// The number of events that were completely processed.
+    pub processed_event_count: u64,
+    /// The index of the latest event that was applied to the blob info.
+    pub latest_applied_event_index: Option<u64>,
+    /// Shards whose interrupted sync or recovery is resumed.
+    pub resumed_shards: Vec<(ShardIndex, ShardStatus)>,
+    /// Active shards whose leftover sync progress was removed.
+    pub discarded_sync_progress: Vec<ShardIndex>,
+}
+
+impl IntegrityReport {
+    /// Returns the number of events that were applied to the blob info but not marked as
+    /// processed, and are therefore processed again.
+    ///
+    /// The blob info skips events that it already applied, so reprocessing them is safe.
+    pub fn events_to_reapply(&self) -> u64 {
+        self.latest_applied_event_index.map_or(0, |index| {
+            (index + 1).saturating_sub(self.processed_event_count)
+        })
+    }
+}
+
+impl fmt::Display for IntegrityReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} events processed, {} applied events are processed again",
+            self.processed_event_count,
+            self.events_to_reapply()
+        )?;
+        for (shard, status) in &self.resumed_shards {
+            write!(f, "; {shard} resumes from status {status}")?;
+        }
+        for shard in &self.discarded_sync_progress {
+            write!(f, "; discarded stale sync progress of active {shard}")?;
+        }
+        Ok(())
+    }
+}
+
+impl Storage {
+    /// Returns true if the storage was not closed cleanly before it was opened.
+    pub(crate) fn was_shut_down_uncleanly(&self) -> bool {
+        self.unclean_shutdown
+    }
+
+    /// Removes the marker file, recording that the storage was closed cleanly.
+    ///
+    /// Must only be called once the node no longer writes to the storage.
+    pub(crate) fn mark_clean_shutdown(&self) -> io::Result<()> {
+        match std::fs::remove_file(self.path.join(RUNNING_MARKER_FILE)) {
+            Err(error) if error.kind() != io::ErrorKind::NotFound => Err(error),
+            _ => Ok(()),
+        }
+    }
+
+    /// Verifies that the node's bookkeeping tables can be read, reconciles the event cursor with
+    /// the events applied to the blob info, and rolls back state left behind by interrupted
+    /// operations.
+    ///
+    /// Returns an error if any of the tables is corrupted, in which case the node must not serve
+    /// data from the storage.
+    pub(crate) async fn check_integrity_and_recover(
+        &self,
+    ) -> Result<IntegrityReport, TypedStoreError> {
+        self.node_status()?;
+        let mut report = IntegrityReport {
+            processed_event_count: self.event_cursor.get_sequentially_processed_event_count()?,
+            latest_applied_event_index: self.blob_info.latest_handled_event_index()?,
+            ..Default::default()
+        };
+
+        for (shard, shard_storage) in self.shards.read().await.iter() {
+            let status = shard_storage.status()?;
+            let has_sync_progress = shard_storage.has_sync_progress()?;
+            match status {
+                ShardStatus::ActiveSync | ShardStatus::ActiveRecover => {
+                    report.resumed_shards.push((*shard, status));
+                }
+                // The progress is removed together with setting the shard to active, so any
+                // progress left for an active shard is stale.
+                ShardStatus::Active if has_sync_progress => {
+                    shard_storage.clear_sync_progress()?;
+                    report.discarded_sync_progress.push(*shard);
+                }
+                _ => {}
+            }
+        }
+        report
+            .resumed_shards
+            .sort_unstable_by_key(|(shard, _)| *shard);
+        report.discarded_sync_progress.sort_unstable();
+
+        Ok(report)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use typed_store::rocks::MetricConf;
+    use walrus_core::SliverType;
+    use walrus_test_utils::{Result as TestResult, WithTempDir};
+    use walrus_utils::metrics::Registry;
+
+    use super::*;
+    use crate::node::storage::{
+        tests::{empty_storage, BLOB_ID, SHARD_INDEX},
+        DatabaseConfig,
+    };
+
+    fn reopen(storage: WithTempDir<Storage>) -> TestResult<WithTempDir<Storage>> {
+        let WithTempDir { inner, temp_dir } = storage;
+        drop(inner);
+        let inner = Storage::open(
+            temp_dir.path(),
+            DatabaseConfig::default(),
+            MetricConf::default(),
+            Registry::default(),
+        )?;
+        Ok(WithTempDir { inner, temp_dir })
+    }
+
+    #[tokio::test]
+    async fn detects_unclean_shutdown_when_reopened() -> TestResult {
+        let storage = empty_storage().await;
+        assert!(!storage.as_ref().was_shut_down_uncleanly());
+
+        let storage = reopen(storage)?;
+        assert!(storage.as_ref().was_shut_down_uncleanly());
+
+        storage.as_ref().mark_clean_shutdown()?;
+        let storage = reopen(storage)?;
+        assert!(!storage.as_ref().was_shut_down_uncleanly());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn resumes_interrupted_shard_sync() -> TestResult {
+        let storage = empty_storage().await;
+        let storage = storage.as_ref();
+        let shard = storage
+            .shard_storage(SHARD_INDEX)
+            .await
+            .expect("shard is present");
+
+        shard.record_start_shard_sync()?;
+        shard.update_sync_progress_in_test(BLOB_ID, SliverType::Primary)?;
+
+        let report = storage.check_integrity_and_recover().await?;
+
+        assert_eq!(
+            report.resumed_shards,
+            vec![(SHARD_INDEX, ShardStatus::ActiveSync)]
+        );
+        assert!(report.discarded_sync_progress.is_empty());
+        assert!(shard.has_sync_progress()?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn counts_applied_events_that_are_processed_again() {
+        let report = |processed_event_count, latest_applied_event_index| IntegrityReport {
+            processed_event_count,
+            latest_applied_event_index,
+            ..Default::default()
+        };
+
+        assert_eq!(report(0, None).events_to_reapply(), 0);
+        assert_eq!(report(5, Some(4)).events_to_reapply(), 0);
+        assert_eq!(report(5, Some(7)).events_to_reapply(), 3);
+        assert_eq!(report(8, Some(7)).events_to_reapply(), 0);
+    }
+
+    #[tokio::test]
+    async fn discards_sync_progress_of_active_shards() -> TestResult {
+        let storage = empty_storage().await;
+        let storage = storage.as_ref();
+        let shard = storage
+            .shard_storage(SHARD_INDEX)
+            .await
+            .expect("shard is present");
+
+        shard.record_start_shard_sync()?;
+        shard.update_sync_progress_in_test(BLOB_ID, SliverType::Primary)?;
+        shard.set_active_status()?;
+
+        let report = storage.check_integrity_and_recover().await?;
+
+        assert_eq!(report.discarded_sync_progress, vec![SHARD_INDEX]);
+        assert!(report.resumed_shards.is_empty());
+        assert!(!shard.has_sync_progress()?);
+        assert_eq!(report.events_to_reapply(), 0);
+
+        Ok(())
+    }
+}
@@ -6,6 +6,8 @@ use std::time::Duration;
 use prometheus::HistogramVec;
 use typed_store::TypedStoreError;
 
+use crate::node::server::slow_log;
+
 walrus_utils::metrics::define_metric_set! {
     #[namespace = "db_client"]
     /// Metrics exported by database operations.
@@ -27,7 +29,10 @@ walrus_utils::metrics::define_metric_set! {
 }
 
 impl CommonDatabaseMetrics {
+    /// Observes the duration of an operation, which is also attributed to the request being
+    /// handled on the current task, if any.
     pub fn observe_operation_duration(&self, labels: Labels, duration: Duration) {
+        slow_log::record_db_time(duration);
         walrus_utils::with_label!(
             self.operation_duration_seconds,
             labels.collection_name,
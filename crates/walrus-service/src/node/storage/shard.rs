@@ -31,7 +31,15 @@ use typed_store::{
 };
 use walrus_core::{
     by_axis::ByAxis,
-    encoding::{EncodingAxis, Primary, PrimarySliver, Secondary, SecondarySliver},
+    encoding::{
+        EncodingAxis,
+        Primary,
+        PrimarySliver,
+        Secondary,
+        SecondarySliver,
+        SliverChecksum,
+        SliverData,
+    },
     BlobId,
     Epoch,
     InconsistencyProof,
@@ -39,9 +47,11 @@ use walrus_core::{
     Sliver,
     SliverType,
 };
+use walrus_rest_client::api::NodeEvent;
 use walrus_utils::metrics::Registry;
 
 use super::{
+    blob_id_filter::{BlobIdFilterStore, ShardBlobIdFilters},
     blob_info::{BlobInfo, BlobInfoIterator},
     constants,
     metrics::{CommonDatabaseMetrics, Labels, OperationType},
@@ -174,10 +184,55 @@ pub(crate) enum ShardLastSyncStatus {
     Recovery,
 }
 
+/// The result of comparing a sliver read from the database with the checksum stored alongside it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum SliverChecksumStatus {
+    /// The sliver matches its checksum.
+    Valid,
+    /// The sliver does not match its checksum; either of them was corrupted after being stored.
+    Mismatch,
+    /// The sliver was stored without a checksum.
+    Missing,
+}
+
+impl SliverChecksumStatus {
+    fn of<T: EncodingAxis>(sliver: &SliverData<T>, checksum: Option<SliverChecksum>) -> Self {
+        match checksum {
+            Some(checksum) if sliver.checksum() == checksum => Self::Valid,
+            Some(_) => Self::Mismatch,
+            None => Self::Missing,
+        }
+    }
+}
+
+// Important: the sliver data enums are committed to database. Do not modify the existing
+// variants. Only add new variants at the end.
+
 /// Primary sliver data stored in the database.
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum PrimarySliverData {
     V1(PrimarySliver),
+    /// The sliver with the checksum computed when it was stored.
+    V2(PrimarySliver, SliverChecksum),
+}
+
+impl PrimarySliverData {
+    /// Returns the sliver stored together with its checksum.
+    pub(crate) fn with_checksum(sliver: PrimarySliver) -> Self {
+        let checksum = sliver.checksum();
+        Self::V2(sliver, checksum)
+    }
+
+    /// Returns the sliver and the result of comparing it with its stored checksum.
+    pub(crate) fn into_checked(self) -> (PrimarySliver, SliverChecksumStatus) {
+        match self {
+            Self::V1(sliver) => (sliver, SliverChecksumStatus::Missing),
+            Self::V2(sliver, checksum) => {
+                let status = SliverChecksumStatus::of(&sliver, Some(checksum));
+                (sliver, status)
+            }
+        }
+    }
 }
 
 impl From<PrimarySliver> for PrimarySliverData {
@@ -189,7 +244,7 @@ impl From<PrimarySliver> for PrimarySliverData {
 impl From<PrimarySliverData> for PrimarySliver {
     fn from(data: PrimarySliverData) -> Self {
         match data {
-            PrimarySliverData::V1(sliver) => sliver,
+            PrimarySliverData::V1(sliver) | PrimarySliverData::V2(sliver, _) => sliver,
         }
     }
 }
@@ -198,6 +253,27 @@ impl From<PrimarySliverData> for PrimarySliver {
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum SecondarySliverData {
     V1(SecondarySliver),
+    /// The sliver with the checksum computed when it was stored.
+    V2(SecondarySliver, SliverChecksum),
+}
+
+impl SecondarySliverData {
+    /// Returns the sliver stored together with its checksum.
+    pub(crate) fn with_checksum(sliver: SecondarySliver) -> Self {
+        let checksum = sliver.checksum();
+        Self::V2(sliver, checksum)
+    }
+
+    /// Returns the sliver and the result of comparing it with its stored checksum.
+    pub(crate) fn into_checked(self) -> (SecondarySliver, SliverChecksumStatus) {
+        match self {
+            Self::V1(sliver) => (sliver, SliverChecksumStatus::Missing),
+            Self::V2(sliver, checksum) => {
+                let status = SliverChecksumStatus::of(&sliver, Some(checksum));
+                (sliver, status)
+            }
+        }
+    }
 }
 
 impl From<SecondarySliver> for SecondarySliverData {
@@ -209,7 +285,7 @@ impl From<SecondarySliver> for SecondarySliverData {
 impl From<SecondarySliverData> for SecondarySliver {
     fn from(data: SecondarySliverData) -> Self {
         match data {
-            SecondarySliverData::V1(sliver) => sliver,
+            SecondarySliverData::V1(sliver) | SecondarySliverData::V2(sliver, _) => sliver,
         }
     }
 }
@@ -224,6 +300,10 @@ pub struct ShardStorage {
     pending_recover_slivers: DBMap<(SliverType, BlobId), ()>,
     metrics: ShardMetrics,
     cf_names: Arc<ShardColumnFamilyNames>,
+    /// Filters over the IDs of the blobs whose slivers are stored, if enabled.
+    blob_id_filters: Option<Arc<ShardBlobIdFilters>>,
+    /// Whether slivers are stored together with their checksums.
+    store_sliver_checksums: bool,
 }
 
 macro_rules! reopen_cf {
@@ -247,6 +327,7 @@ impl ShardStorage {
         database: &Arc<RocksDB>,
         db_config: &DatabaseConfig,
         initial_shard_status: Option<ShardStatus>,
+        blob_id_filter_store: Option<&Arc<BlobIdFilterStore>>,
         registry: &Registry,
     ) -> Result<Self, TypedStoreError> {
         let start = Instant::now();
@@ -265,6 +346,7 @@ impl ShardStorage {
             database,
             db_config,
             initial_shard_status,
+            blob_id_filter_store,
             metrics.clone(),
         );
 
@@ -281,6 +363,7 @@ impl ShardStorage {
         database: &Arc<RocksDB>,
         db_config: &DatabaseConfig,
         initial_shard_status: Option<ShardStatus>,
+        blob_id_filter_store: Option<&Arc<BlobIdFilterStore>>,
         metrics: ShardMetrics,
     ) -> Result<Self, TypedStoreError> {
         let cf_names = ShardColumnFamilyNames::new(id);
@@ -334,6 +417,21 @@ impl ShardStorage {
             shard_status.insert(&(), &status)?;
         }
 
+        let blob_id_filters = blob_id_filter_store.map(|store| {
+            let primary_slivers = primary_slivers.clone();
+            let secondary_slivers = secondary_slivers.clone();
+            store.load_or_build(id, move |sliver_type| match sliver_type {
+                SliverType::Primary => primary_slivers
+                    .safe_iter()
+                    .map(|entry| entry.map(|(blob_id, _)| blob_id))
+                    .collect(),
+                SliverType::Secondary => secondary_slivers
+                    .safe_iter()
+                    .map(|entry| entry.map(|(blob_id, _)| blob_id))
+                    .collect(),
+            })
+        });
+
         Ok(Self {
             id,
             shard_status,
@@ -343,6 +441,8 @@ impl ShardStorage {
             pending_recover_slivers,
             metrics,
             cf_names: Arc::new(cf_names),
+            blob_id_filters,
+            store_sliver_checksums: db_config.store_sliver_checksums,
         })
     }
 
@@ -361,22 +461,19 @@ impl ShardStorage {
             ..Default::default()
         };
 
+        self.record_in_blob_id_filter(&blob_id, sliver.r#type());
         let response = match sliver {
             Sliver::Primary(primary) => {
                 let table = self.primary_slivers.clone();
+                let data = self.primary_sliver_data(primary);
 
-                tokio::task::spawn_blocking(move || {
-                    table.insert(&blob_id, &PrimarySliverData::from(primary))
-                })
-                .await
+                tokio::task::spawn_blocking(move || table.insert(&blob_id, &data)).await
             }
             Sliver::Secondary(secondary) => {
                 let table = self.secondary_slivers.clone();
+                let data = self.secondary_sliver_data(secondary);
 
-                tokio::task::spawn_blocking(move || {
-                    table.insert(&blob_id, &SecondarySliverData::from(secondary))
-                })
-                .await
+                tokio::task::spawn_blocking(move || table.insert(&blob_id, &data)).await
             }
         };
         let response = utils::unwrap_or_resume_unwind(response);
@@ -387,10 +484,53 @@ impl ShardStorage {
         response
     }
 
+    /// Returns the database record of the primary sliver, which includes its checksum if enabled
+    /// in the database config.
+    fn primary_sliver_data(&self, sliver: PrimarySliver) -> PrimarySliverData {
+        if self.store_sliver_checksums {
+            PrimarySliverData::with_checksum(sliver)
+        } else {
+            sliver.into()
+        }
+    }
+
+    /// Returns the database record of the secondary sliver, which includes its checksum if
+    /// enabled in the database config.
+    fn secondary_sliver_data(&self, sliver: SecondarySliver) -> SecondarySliverData {
+        if self.store_sliver_checksums {
+            SecondarySliverData::with_checksum(sliver)
+        } else {
+            sliver.into()
+        }
+    }
+
     pub(crate) fn id(&self) -> ShardIndex {
         self.id
     }
 
+    /// Records that a sliver of the blob is stored; must be called before storing the sliver.
+    fn record_in_blob_id_filter(&self, blob_id: &BlobId, sliver_type: SliverType) {
+        if let Some(filters) = &self.blob_id_filters {
+            filters.insert(blob_id, sliver_type);
+        }
+    }
+
+    /// Returns false if no sliver of the specified type is stored for the blob, without accessing
+    /// the database.
+    fn may_store_sliver(&self, blob_id: &BlobId, sliver_type: SliverType) -> bool {
+        self.blob_id_filters
+            .as_ref()
+            .is_none_or(|filters| filters.may_contain(blob_id, sliver_type))
+    }
+
+    /// Writes the filters over the stored blob IDs to disk, if enabled.
+    pub(crate) fn persist_blob_id_filters(&self) -> std::io::Result<()> {
+        match &self.blob_id_filters {
+            Some(filters) => filters.persist(),
+            None => Ok(()),
+        }
+    }
+
     /// Returns the sliver of the specified type that is stored for that Blob ID, if any.
     #[tracing::instrument(skip_all, fields(walrus.shard_index = %self.id), err)]
     pub(crate) fn get_sliver(
@@ -408,12 +548,44 @@ impl ShardStorage {
         }
     }
 
-    /// Retrieves the stored primary sliver for the given blob ID.
+    /// Returns the sliver of the specified type that is stored for that Blob ID, if any, together
+    /// with the result of comparing it with the checksum stored alongside it.
     #[tracing::instrument(skip_all, fields(walrus.shard_index = %self.id), err)]
+    pub(crate) fn get_sliver_with_checksum_status(
+        &self,
+        blob_id: &BlobId,
+        sliver_type: SliverType,
+    ) -> Result<Option<(Sliver, SliverChecksumStatus)>, TypedStoreError> {
+        Ok(match sliver_type {
+            SliverType::Primary => self.get_primary_sliver_data(blob_id)?.map(|data| {
+                let (sliver, status) = data.into_checked();
+                (Sliver::Primary(sliver), status)
+            }),
+            SliverType::Secondary => self.get_secondary_sliver_data(blob_id)?.map(|data| {
+                let (sliver, status) = data.into_checked();
+                (Sliver::Secondary(sliver), status)
+            }),
+        })
+    }
+
+    /// Retrieves the stored primary sliver for the given blob ID.
     pub(crate) fn get_primary_sliver(
         &self,
         blob_id: &BlobId,
     ) -> Result<Option<PrimarySliver>, TypedStoreError> {
+        self.get_primary_sliver_data(blob_id)
+            .map(|data| data.map(PrimarySliver::from))
+    }
+
+    #[tracing::instrument(skip_all, fields(walrus.shard_index = %self.id), err)]
+    fn get_primary_sliver_data(
+        &self,
+        blob_id: &BlobId,
+    ) -> Result<Option<PrimarySliverData>, TypedStoreError> {
+        if !self.may_store_sliver(blob_id, SliverType::Primary) {
+            return Ok(None);
+        }
+
         let start = Instant::now();
         let labels = Labels {
             collection_name: &self.cf_names.primary_slivers,
@@ -422,10 +594,7 @@ impl ShardStorage {
             ..Labels::default()
         };
 
-        let response = self
-            .primary_slivers
-            .get(blob_id)
-            .map(|s| s.map(|s| s.into()));
+        let response = self.primary_slivers.get(blob_id);
 
         self.metrics
             .observe_operation_duration(labels.with_response(response.as_ref()), start.elapsed());
@@ -434,11 +603,23 @@ impl ShardStorage {
     }
 
     /// Retrieves the stored secondary sliver for the given blob ID.
-    #[tracing::instrument(skip_all, fields(walrus.shard_index = %self.id), err)]
     pub(crate) fn get_secondary_sliver(
         &self,
         blob_id: &BlobId,
     ) -> Result<Option<SecondarySliver>, TypedStoreError> {
+        self.get_secondary_sliver_data(blob_id)
+            .map(|data| data.map(SecondarySliver::from))
+    }
+
+    #[tracing::instrument(skip_all, fields(walrus.shard_index = %self.id), err)]
+    fn get_secondary_sliver_data(
+        &self,
+        blob_id: &BlobId,
+    ) -> Result<Option<SecondarySliverData>, TypedStoreError> {
+        if !self.may_store_sliver(blob_id, SliverType::Secondary) {
+            return Ok(None);
+        }
+
         let start = Instant::now();
         let labels = Labels {
             collection_name: &self.cf_names.secondary_slivers,
@@ -447,10 +628,7 @@ impl ShardStorage {
             ..Labels::default()
         };
 
-        let response = self
-            .secondary_slivers
-            .get(blob_id)
-            .map(|s| s.map(|s| s.into()));
+        let response = self.secondary_slivers.get(blob_id);
 
         self.metrics
             .observe_operation_duration(labels.with_response(response.as_ref()), start.elapsed());
@@ -479,6 +657,10 @@ impl ShardStorage {
         blob_id: &BlobId,
         type_: SliverType,
     ) -> Result<bool, TypedStoreError> {
+        if !self.may_store_sliver(blob_id, type_) {
+            return Ok(false);
+        }
+
         let start = Instant::now();
         let labels = Labels {
             collection_name: self.cf_names.slivers(type_),
@@ -498,6 +680,21 @@ impl ShardStorage {
         response
     }
 
+    /// Returns up to `limit` IDs of the blobs for which the shard stores a primary sliver, in
+    /// ascending order, starting after the blob ID `after`, if provided.
+    #[tracing::instrument(skip_all, fields(walrus.shard_index = %self.id), err)]
+    pub(crate) fn list_blob_ids(
+        &self,
+        after: Option<BlobId>,
+        limit: usize,
+    ) -> Result<Vec<BlobId>, TypedStoreError> {
+        self.primary_slivers
+            .safe_range_iter((after.map_or(Unbounded, Excluded), Unbounded))
+            .take(limit)
+            .map(|result| result.map(|(blob_id, _)| blob_id))
+            .collect()
+    }
+
     /// Deletes the sliver pair for the given [`BlobId`].
     #[tracing::instrument(skip_all, fields(walrus.shard_index = %self.id), err)]
     pub(crate) fn delete_sliver_pair(
@@ -544,6 +741,18 @@ impl ShardStorage {
         batch.write()
     }
 
+    /// Returns true if progress of a shard sync is recorded for the shard.
+    ///
+    /// The recorded progress is decoded, such that an error is returned if it is corrupted.
+    pub(crate) fn has_sync_progress(&self) -> Result<bool, TypedStoreError> {
+        Ok(self.shard_sync_progress.get(&())?.is_some())
+    }
+
+    /// Deletes the recorded progress of the shard sync.
+    pub(crate) fn clear_sync_progress(&self) -> Result<(), TypedStoreError> {
+        self.shard_sync_progress.remove(&())
+    }
+
     pub(crate) fn set_active_status(&self) -> Result<(), TypedStoreError> {
         self.shard_status.insert(&(), &ShardStatus::Active)
     }
@@ -598,18 +807,12 @@ impl ShardStorage {
             ByAxis::Primary(slivers) => slivers_to_fetch
                 .iter()
                 .zip(slivers)
-                .filter_map(|(&blob_id, sliver)| {
-                    let PrimarySliverData::V1(sliver) = sliver?;
-                    Some((blob_id, Sliver::Primary(sliver)))
-                })
+                .filter_map(|(&blob_id, sliver)| Some((blob_id, Sliver::Primary(sliver?.into()))))
                 .collect(),
             ByAxis::Secondary(slivers) => slivers_to_fetch
                 .iter()
                 .zip(slivers)
-                .filter_map(|(&blob_id, sliver)| {
-                    let SecondarySliverData::V1(sliver) = sliver?;
-                    Some((blob_id, Sliver::Secondary(sliver)))
-                })
+                .filter_map(|(&blob_id, sliver)| Some((blob_id, Sliver::Secondary(sliver?.into()))))
                 .collect(),
         };
 
@@ -855,6 +1058,11 @@ impl ShardStorage {
                     )?;
                 }
                 batch.write()?;
+                node.accountant.record_stored(
+                    node.current_epoch(),
+                    self.id,
+                    fetched_slivers.iter().map(|(_, sliver)| sliver.len()).sum(),
+                );
 
                 walrus_utils::with_label!(
                     node.metrics.sync_shard_sync_sliver_total,
@@ -863,6 +1071,16 @@ impl ShardStorage {
                 )
                 .inc_by(fetched_slivers.len() as u64);
 
+                if let Some(last_synced_blob_id) = last_synced_blob_id {
+                    node.local_events.emit(NodeEvent::ShardSyncProgressed {
+                        shard: self.id,
+                        epoch,
+                        sliver_type,
+                        synced_slivers: fetched_slivers.len(),
+                        last_synced_blob_id,
+                    });
+                }
+
                 if last_synced_blob_id.is_none() {
                     break;
                 }
@@ -916,19 +1134,20 @@ impl ShardStorage {
                 debug_assert!(node.storage.has_metadata(blob_id)?);
             }
 
+            self.record_in_blob_id_filter(blob_id, sliver.r#type());
             match sliver {
                 Sliver::Primary(primary) => {
                     assert_eq!(sliver_type, SliverType::Primary);
                     batch.insert_batch(
                         &self.primary_slivers,
-                        [(blob_id, &PrimarySliverData::from(primary.clone()))],
+                        [(blob_id, &self.primary_sliver_data(primary.clone()))],
                     )?;
                 }
                 Sliver::Secondary(secondary) => {
                     assert_eq!(sliver_type, SliverType::Secondary);
                     batch.insert_batch(
                         &self.secondary_slivers,
-                        [(blob_id, &SecondarySliverData::from(secondary.clone()))],
+                        [(blob_id, &self.secondary_sliver_data(secondary.clone()))],
                     )?;
                 }
             }
@@ -1311,6 +1530,18 @@ impl ShardStorage {
         self.shard_status.insert(&(), &status)
     }
 
+    #[cfg(test)]
+    pub(crate) fn update_sync_progress_in_test(
+        &self,
+        last_synced_blob_id: BlobId,
+        sliver_type: SliverType,
+    ) -> Result<(), TypedStoreError> {
+        self.shard_sync_progress.insert(
+            &(),
+            &ShardSyncProgress::new(last_synced_blob_id, sliver_type),
+        )
+    }
+
     #[cfg(test)]
     pub(crate) fn check_and_record_missing_blobs_in_test(
         &self,
@@ -1494,6 +1725,51 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn reports_the_checksum_status_of_stored_slivers() -> TestResult {
+        let storage = empty_storage().await;
+        let shard = storage
+            .as_ref()
+            .shard_storage(SHARD_INDEX)
+            .await
+            .expect("shard should exist");
+        let sliver = get_sliver(SliverType::Primary, 1);
+        let primary = sliver.clone().to_raw::<Primary>()?;
+
+        shard.put_sliver(BLOB_ID, sliver.clone()).await?;
+        assert_eq!(
+            shard.get_sliver_with_checksum_status(&BLOB_ID, SliverType::Primary)?,
+            Some((sliver.clone(), SliverChecksumStatus::Missing))
+        );
+
+        let shard_with_checksums = ShardStorage {
+            store_sliver_checksums: true,
+            ..shard.as_ref().clone()
+        };
+        shard_with_checksums
+            .put_sliver(BLOB_ID, sliver.clone())
+            .await?;
+        assert_eq!(
+            shard.get_sliver_with_checksum_status(&BLOB_ID, SliverType::Primary)?,
+            Some((sliver.clone(), SliverChecksumStatus::Valid))
+        );
+
+        let wrong_checksum = SliverChecksum(primary.checksum().0 ^ 1);
+        shard
+            .primary_slivers
+            .insert(&BLOB_ID, &PrimarySliverData::V2(primary, wrong_checksum))?;
+        assert_eq!(
+            shard.get_sliver_with_checksum_status(&BLOB_ID, SliverType::Primary)?,
+            Some((sliver.clone(), SliverChecksumStatus::Mismatch))
+        );
+        assert_eq!(
+            shard.get_sliver(&BLOB_ID, SliverType::Primary)?,
+            Some(sliver)
+        );
+
+        Ok(())
+    }
+
     #[tokio::test]
     async fn stores_and_deletes_slivers() -> TestResult {
         let storage = empty_storage().await;
@@ -1521,6 +1797,29 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn lists_stored_blob_ids_in_pages() -> TestResult {
+        let storage = empty_storage().await;
+        let shard = storage
+            .as_ref()
+            .shard_storage(SHARD_INDEX)
+            .await
+            .expect("shard should exist");
+
+        let blob_ids: Vec<_> = (1..=3).map(|i| BlobId([i; 32])).collect();
+        for blob_id in blob_ids.iter().rev() {
+            shard
+                .put_sliver(*blob_id, get_sliver(SliverType::Primary, 1))
+                .await?;
+        }
+
+        assert_eq!(shard.list_blob_ids(None, 2)?, blob_ids[..2]);
+        assert_eq!(shard.list_blob_ids(Some(blob_ids[1]), 2)?, blob_ids[2..]);
+        assert!(shard.list_blob_ids(Some(blob_ids[2]), 2)?.is_empty());
+
+        Ok(())
+    }
+
     #[tokio::test]
     async fn delete_on_empty_slivers_does_not_error() -> TestResult {
         let storage = empty_storage().await;
@@ -2553,8 +2553,13 @@ pub mod test_cluster {
             contract_config,
             exchange_objects: vec![],
             wallet_config: None,
+            rpc_urls: vec![],
             communication_config,
             refresh_config: Default::default(),
+            gas_sponsor: None,
+            gas_escalation: Default::default(),
+            signing_service: None,
+            offline_signer: None,
         };
 
         let client = admin_contract_client
@@ -2639,6 +2644,8 @@ pub fn storage_node_config() -> WithTempDir<StorageNodeConfig> {
             db_config: Default::default(),
             rest_server: Default::default(),
             blocklist_path: None,
+            delete_blocked_blob_data: false,
+            delete_expired_blob_data: false,
             sui: None,
             blob_recovery: Default::default(),
             tls: Default::default(),
@@ -2666,6 +2673,8 @@ pub fn storage_node_config() -> WithTempDir<StorageNodeConfig> {
             num_uncertified_blob_threshold: Some(3),
             balance_check: Default::default(),
             thread_pool: Default::default(),
+            request_scheduler: Default::default(),
+            expired_blob_read_grace_epochs: 0,
         },
         temp_dir,
     }
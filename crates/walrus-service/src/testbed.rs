@@ -562,8 +562,13 @@ pub async fn create_client_config(
         contract_config,
         exchange_objects,
         wallet_config: Some(WalletConfig::from_path(wallet_path)),
+        rpc_urls: vec![],
         communication_config: Default::default(),
         refresh_config: Default::default(),
+        gas_sponsor: None,
+        gas_escalation: Default::default(),
+        signing_service: None,
+        offline_signer: None,
     };
 
     Ok(client_config)
@@ -737,6 +742,8 @@ pub async fn create_storage_node_configs(
             name: node.name.clone(),
             storage_path,
             blocklist_path: None,
+            delete_blocked_blob_data: false,
+            delete_expired_blob_data: false,
             protocol_key_pair,
             next_protocol_key_pair: None,
             network_key_pair: node.network_keypair.into(),
@@ -770,6 +777,8 @@ pub async fn create_storage_node_configs(
             num_uncertified_blob_threshold: Some(10),
             balance_check: Default::default(),
             thread_pool: Default::default(),
+            request_scheduler: Default::default(),
+            expired_blob_read_grace_epochs: 0,
         });
     }
 
@@ -171,13 +171,13 @@ impl WriteClient {
         let n_shards = self.client.as_ref().encoding_config().n_shards();
 
         // Make primary sliver 0 inconsistent.
-        metadata.mut_inner().hashes[0].primary_hash = Node::Digest([0; 32]);
+        metadata.hashes_mut()[0].primary_hash = Node::Digest([0; 32]);
 
         // Make second sliver inconsistent if enough committee members
         if n_members >= 7 {
             // Sliver `n_shards/2` will be held by a different node if the shards are assigned
             // sequentially.
-            metadata.mut_inner().hashes[(n_shards.get() / 2) as usize].primary_hash =
+            metadata.hashes_mut()[(n_shards.get() / 2) as usize].primary_hash =
                 Node::Digest([0; 32]);
         }
 
@@ -5,7 +5,7 @@
 
 use core::fmt;
 use std::{
-    collections::HashMap,
+    collections::{HashMap, HashSet},
     future::Future,
     path::PathBuf,
     str::FromStr,
@@ -15,7 +15,8 @@ use std::{
 
 use anyhow::{anyhow, Context, Result};
 use contract_config::ContractConfig;
-use futures::future::BoxFuture;
+use fastcrypto::traits::Signer;
+use futures::{future::BoxFuture, stream, StreamExt as _, TryStreamExt as _};
 use move_package::BuildConfig as MoveBuildConfig;
 use retry_client::RetriableSuiClient;
 use sui_package_management::LockCommand;
@@ -23,18 +24,30 @@ use sui_sdk::{
     rpc_types::{
         get_new_package_obj_from_response,
         Coin,
+        ObjectChange,
         SuiExecutionStatus,
         SuiTransactionBlockEffectsAPI,
         SuiTransactionBlockResponse,
+        SuiTransactionBlockResponseOptions,
     },
     types::base_types::{ObjectID, ObjectRef},
     wallet_context::WalletContext,
 };
 use sui_types::{
     base_types::SuiAddress,
+    crypto::Signature,
+    digests::TransactionDigest,
     event::EventID,
+    gas_coin::GasCoin,
+    object::Owner,
     programmable_transaction_builder::ProgrammableTransactionBuilder,
-    transaction::{Argument, ProgrammableTransaction, TransactionData, TransactionKind},
+    transaction::{
+        Argument,
+        ProgrammableTransaction,
+        Transaction,
+        TransactionData,
+        TransactionKind,
+    },
     TypeTag,
 };
 use tokio::sync::Mutex;
@@ -67,6 +80,7 @@ use crate::{
             EpochState,
             SharedBlob,
             StorageNode,
+            WalExchange,
         },
         BlobEvent,
         Committee,
@@ -80,14 +94,20 @@ use crate::{
     utils::get_created_sui_object_ids_by_type,
 };
 
+mod read_cache;
+
 mod read_client;
 pub use read_client::{
     CoinType,
     CommitteesAndState,
+    CurrentEpochState,
+    EpochPrices,
     FixedSystemParameters,
     ReadClient,
+    SharedBlobFunding,
     Subsidies,
     SuiReadClient,
+    MAX_SUPPORTED_CONTRACT_VERSION,
 };
 pub mod retry_client;
 pub mod rpc_config;
@@ -97,14 +117,36 @@ use crate::types::move_structs::EventBlob;
 
 pub mod contract_config;
 
+mod gas_coins;
+use gas_coins::{GasCoinCache, GasCoinSelection};
+
+mod gas_escalation;
+pub use gas_escalation::GasEscalationConfig;
+
+mod gas_sponsor;
+use gas_sponsor::GasSponsor;
+pub use gas_sponsor::GasSponsorConfig;
+
 mod metrics;
 pub use metrics::SuiClientMetricSet;
 
+mod signer;
+pub use signer::{
+    OfflineSigner,
+    OfflineSignerConfig,
+    SigningServiceConfig,
+    SigningServiceSigner,
+    TransactionSigner,
+};
+
 // Keep in sync with the corresponding value in
 // `contracts/walrus/sources/staking/staked_wal.move`
 /// The minimum threshold for staking.
 pub const MIN_STAKING_THRESHOLD: u64 = 1_000_000_000; // 1 WAL
 
+/// The maximum number of blob attributes read concurrently when listing blobs.
+const MAX_CONCURRENT_ATTRIBUTE_READS: usize = 16;
+
 #[derive(Debug, thiserror::Error)]
 /// Error returned by the [`SuiContractClient`] and the [`SuiReadClient`].
 pub enum SuiClientError {
@@ -187,6 +229,47 @@ pub enum SuiClientError {
         FROST for staking"
     )]
     StakeBelowThreshold(u64),
+    /// The staked WAL cannot be withdrawn before its withdraw epoch.
+    #[error(
+        "the staked WAL can be withdrawn from epoch {withdraw_epoch} on (current epoch: \
+        {current_epoch})"
+    )]
+    StakeNotWithdrawableYet {
+        /// The epoch from which the staked WAL can be withdrawn.
+        withdraw_epoch: Epoch,
+        /// The current epoch.
+        current_epoch: Epoch,
+    },
+    /// The gas station rejected the request to sponsor a transaction.
+    #[error("the gas station rejected the request to sponsor the transaction: {0}")]
+    GasStationError(String),
+    /// The transaction failed due to gas even after increasing its gas price and budget.
+    #[error(
+        "the transaction failed even after increasing the gas budget to {gas_budget} MIST and the \
+        gas price to {gas_price} MIST: {source}"
+    )]
+    GasEscalationExhausted {
+        /// The gas budget of the last attempt.
+        gas_budget: u64,
+        /// The gas price of the last attempt.
+        gas_price: u64,
+        /// The error of the last attempt.
+        source: Box<SuiClientError>,
+    },
+    /// The version of a Walrus object on chain is newer than the versions supported by this
+    /// binary.
+    #[error(
+        "the on-chain Walrus {object} object has version {version}, but this binary only supports \
+        versions up to {max_supported_version}; please upgrade walrus to the latest version"
+    )]
+    UnsupportedContractVersion {
+        /// The name of the Walrus object.
+        object: &'static str,
+        /// The version of the object on chain.
+        version: u64,
+        /// The highest version supported by this binary.
+        max_supported_version: u64,
+    },
 }
 
 impl SuiClientError {
@@ -236,6 +319,8 @@ pub struct CertifyAndExtendBlobResult {
     pub blob_object_id: ObjectID,
     /// The result of the post store action.
     pub post_store_action_result: PostStoreActionResult,
+    /// The digest of the transaction that certified or extended the blob.
+    pub transaction_digest: TransactionDigest,
 }
 
 impl CertifyAndExtendBlobResult {
@@ -251,6 +336,17 @@ impl CertifyAndExtendBlobResult {
     }
 }
 
+/// The expected costs and effects of a transaction, obtained with a dry run before submitting it.
+#[derive(Debug, Clone)]
+pub struct TransactionPreview {
+    /// The expected gas cost of the transaction in MIST, net of storage rebates.
+    pub gas_cost: u64,
+    /// The expected amount of WAL spent by the transaction in FROST.
+    pub wal_cost: u64,
+    /// The objects expected to be created, mutated, or deleted by the transaction.
+    pub object_changes: Vec<ObjectChange>,
+}
+
 /// Metadata for a blob object on Sui.
 #[derive(Debug, Clone)]
 pub struct BlobObjectMetadata {
@@ -409,6 +505,15 @@ impl PostStoreActionResult {
     }
 }
 
+/// Result of unstaking a [`StakedWal`] object.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UnstakeResult {
+    /// The staked WAL, including its rewards, was withdrawn to the wallet.
+    Withdrawn,
+    /// The withdrawal was requested; the WAL can be withdrawn from the given epoch on.
+    WithdrawalRequested(Epoch),
+}
+
 /// Enum to select between different pool operations that require authorization.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum PoolOperationWithAuthorization {
@@ -513,6 +618,37 @@ impl SuiContractClient {
         })
     }
 
+    /// Has the gas of the transactions of the client paid by the sponsor configured in `config`.
+    ///
+    /// Transactions that spend SUI other than for gas, such as exchanging SUI for WAL, are still
+    /// paid by the wallet of the client.
+    pub fn with_gas_sponsor(mut self, config: &GasSponsorConfig) -> SuiClientResult<Self> {
+        tracing::info!("the gas of transactions is paid by a sponsor");
+        self.inner.get_mut().gas_sponsor = Some(GasSponsor::from_config(config)?);
+        Ok(self)
+    }
+
+    /// Signs the transactions of the client with the external `signer` instead of the keystore of
+    /// the wallet.
+    ///
+    /// The address of the signer becomes the active address of the client.
+    pub fn with_signer(mut self, signer: Arc<dyn TransactionSigner>) -> Self {
+        let address = signer.address();
+        tracing::info!(%address, "transactions are signed by an external signer");
+        let inner = self.inner.get_mut();
+        inner.wallet.config.active_address = Some(address);
+        inner.signer = Some(signer);
+        self.wallet_address = address;
+        self
+    }
+
+    /// Resubmits transactions that fail due to their gas price or budget, or due to congestion,
+    /// with an increased gas price and budget as configured.
+    pub fn with_gas_escalation(mut self, config: GasEscalationConfig) -> Self {
+        self.inner.get_mut().gas_escalation = Some(config);
+        self
+    }
+
     /// Returns the contained [`SuiReadClient`].
     pub fn read_client(&self) -> &SuiReadClient {
         &self.read_client
@@ -590,6 +726,55 @@ impl SuiContractClient {
         .await
     }
 
+    /// Registers the blobs with the provided storage resources, and purchases storage for the next
+    /// `epochs_ahead` Walrus epochs to register the blobs in `blob_metadata_list`.
+    ///
+    /// This combines the [`register_blobs`][Self::register_blobs] and
+    /// [`reserve_and_register_blobs`][Self::reserve_and_register_blobs] functions in one atomic
+    /// transaction. Returns the blobs registered with the provided storage resources and the blobs
+    /// registered with newly purchased storage, in this order.
+    ///
+    /// The blobs are certified in a separate transaction, with
+    /// [`certify_and_extend_blobs`][Self::certify_and_extend_blobs], as their certificates can
+    /// only be obtained after the registration is executed.
+    pub async fn register_blobs_with_owned_and_new_storage(
+        &self,
+        blob_metadata_and_storage: Vec<(BlobObjectMetadata, StorageResource)>,
+        epochs_ahead: EpochCount,
+        blob_metadata_list: Vec<BlobObjectMetadata>,
+        persistence: BlobPersistence,
+    ) -> SuiClientResult<(Vec<Blob>, Vec<Blob>)> {
+        self.retry_on_wrong_version(|| async {
+            self.inner
+                .lock()
+                .await
+                .register_blobs_with_owned_and_new_storage(
+                    blob_metadata_and_storage.clone(),
+                    epochs_ahead,
+                    blob_metadata_list.clone(),
+                    persistence,
+                )
+                .await
+        })
+        .await
+    }
+
+    /// Previews the costs and effects of
+    /// [`reserve_and_register_blobs`][Self::reserve_and_register_blobs] with a dry run, without
+    /// submitting the transaction.
+    pub async fn preview_reserve_and_register_blobs(
+        &self,
+        epochs_ahead: EpochCount,
+        blob_metadata_list: Vec<BlobObjectMetadata>,
+        persistence: BlobPersistence,
+    ) -> SuiClientResult<TransactionPreview> {
+        self.inner
+            .lock()
+            .await
+            .preview_reserve_and_register_blobs(epochs_ahead, blob_metadata_list, persistence)
+            .await
+    }
+
     /// Certifies the specified blob on Sui, given a certificate that confirms its storage and
     /// returns the certified blob.
     ///
@@ -721,6 +906,55 @@ impl SuiContractClient {
         .await
     }
 
+    /// Unstakes the [`StakedWal`] object with the given ID.
+    ///
+    /// The WAL is withdrawn directly if the stake can be withdrawn early, if the node is neither
+    /// in the current nor in the next committee, or if the withdraw epoch of an earlier withdrawal
+    /// request has been reached. Otherwise, the withdrawal is requested, and the WAL can be
+    /// withdrawn by unstaking again from the returned epoch on.
+    pub async fn unstake(&self, staked_wal_id: ObjectID) -> SuiClientResult<UnstakeResult> {
+        let staked_wal: StakedWal = self.sui_client().get_sui_object(staked_wal_id).await?;
+        let current_epoch = self.read_client.current_epoch().await?;
+        if let Some(withdraw_epoch) = staked_wal.withdraw_epoch() {
+            ensure!(
+                withdraw_epoch <= current_epoch,
+                SuiClientError::StakeNotWithdrawableYet {
+                    withdraw_epoch,
+                    current_epoch,
+                }
+            );
+            self.withdraw_stake(staked_wal_id).await?;
+            return Ok(UnstakeResult::Withdrawn);
+        }
+
+        let contains_node = |committee: &Committee| {
+            committee
+                .members()
+                .iter()
+                .any(|node| node.node_id == staked_wal.node_id)
+        };
+        let in_current_committee = contains_node(&self.read_client.current_committee().await?);
+        let in_next_committee = self
+            .read_client
+            .next_committee()
+            .await?
+            .is_some_and(|committee| contains_node(&committee));
+        // Keep in sync with `can_withdraw_early` in `staked_wal.move`.
+        let can_withdraw_early = staked_wal.activation_epoch == current_epoch + 2
+            || (staked_wal.activation_epoch == current_epoch + 1 && !in_next_committee);
+        if can_withdraw_early || (!in_current_committee && !in_next_committee) {
+            self.withdraw_stake(staked_wal_id).await?;
+            return Ok(UnstakeResult::Withdrawn);
+        }
+
+        self.request_withdraw_stake(staked_wal_id).await?;
+        let staked_wal: StakedWal = self.sui_client().get_sui_object(staked_wal_id).await?;
+        let withdraw_epoch = staked_wal
+            .withdraw_epoch()
+            .ok_or_else(|| anyhow!("the staked WAL is not withdrawing after the request"))?;
+        Ok(UnstakeResult::WithdrawalRequested(withdraw_epoch))
+    }
+
     /// Call to end voting and finalize the next epoch parameters.
     ///
     /// Can be called once the voting period is over.
@@ -873,6 +1107,29 @@ impl SuiContractClient {
             .await
     }
 
+    /// Exchanges as much SUI for WAL as needed for the WAL balance of the wallet to reach
+    /// `min_wal_balance`, using the exchange with the given ID.
+    ///
+    /// Returns the amount of SUI exchanged in MIST, which is zero if the WAL balance is already
+    /// sufficient.
+    pub async fn exchange_sui_for_missing_wal(
+        &self,
+        exchange_id: ObjectID,
+        min_wal_balance: u64,
+    ) -> SuiClientResult<u64> {
+        let wal_balance = self.balance(CoinType::Wal).await?;
+        if wal_balance >= min_wal_balance {
+            tracing::debug!(wal_balance, min_wal_balance, "WAL balance is sufficient");
+            return Ok(0);
+        }
+        let exchange: WalExchange = self.sui_client().get_sui_object(exchange_id).await?;
+        let amount = exchange
+            .exchange_rate
+            .wal_to_sui_rounded_up(min_wal_balance - wal_balance);
+        self.exchange_sui_for_wal(exchange_id, amount).await?;
+        Ok(amount)
+    }
+
     /// Creates a new [`contracts::subsidies::Subsidies`] object,
     /// funds it with the specified amount,
     /// and returns the object ID and the admin cap ID.
@@ -912,6 +1169,41 @@ impl SuiContractClient {
             .collect())
     }
 
+    /// Returns the list of [`Blob`] objects owned by the wallet currently in use, along with their
+    /// attributes.
+    ///
+    /// If `owner` is `None`, the current wallet address is used. The attributes are read with one
+    /// request per blob object.
+    pub async fn owned_blobs_with_attributes(
+        &self,
+        owner: Option<SuiAddress>,
+        selection_policy: ExpirySelectionPolicy,
+    ) -> SuiClientResult<Vec<BlobWithAttribute>> {
+        let blobs = self.owned_blobs(owner, selection_policy).await?;
+        stream::iter(blobs)
+            .map(|blob| async move {
+                let attribute = self.read_client.get_blob_attribute(&blob.id).await?;
+                Ok(BlobWithAttribute { blob, attribute })
+            })
+            .buffered(MAX_CONCURRENT_ATTRIBUTE_READS)
+            .try_collect()
+            .await
+    }
+
+    /// Returns the list of [`StakedWal`] objects owned by the wallet currently in use.
+    ///
+    /// If `owner` is `None`, the current wallet address is used.
+    pub async fn owned_staked_wal(
+        &self,
+        owner: Option<SuiAddress>,
+    ) -> SuiClientResult<Vec<StakedWal>> {
+        Ok(self
+            .read_client
+            .get_owned_objects::<StakedWal>(owner.unwrap_or(self.wallet_address), &[])
+            .await?
+            .collect())
+    }
+
     /// Returns the list of [`StorageResource`] objects owned by the wallet currently in use.
     pub async fn owned_storage(
         &self,
@@ -920,8 +1212,9 @@ impl SuiContractClient {
         let current_epoch = self.read_client.current_committee().await?.epoch;
         Ok(self
             .read_client
-            .get_owned_objects::<StorageResource>(self.wallet_address, &[])
+            .owned_storage_resources(self.wallet_address)
             .await?
+            .into_iter()
             .filter(|storage| selection_policy.matches(storage.end_epoch, current_epoch))
             .collect())
     }
@@ -968,6 +1261,19 @@ impl SuiContractClient {
         self.inner.lock().await.merge_coins().await
     }
 
+    /// Splits `count` SUI coins of `amount` each off the gas coin of the wallet, and returns the
+    /// IDs of the new coins.
+    ///
+    /// This allows transactions to be submitted in parallel from the same wallet, e.g., by multiple
+    /// processes, without them competing for the same gas coin.
+    pub async fn split_gas_coin(
+        &self,
+        count: usize,
+        amount: u64,
+    ) -> SuiClientResult<Vec<ObjectID>> {
+        self.inner.lock().await.split_gas_coin(count, amount).await
+    }
+
     /// Sends the `amount` gas to the provided `address`.
     pub async fn send_sui(&self, amount: u64, address: SuiAddress) -> SuiClientResult<()> {
         self.inner.lock().await.send_sui(amount, address).await
@@ -1290,6 +1596,14 @@ struct SuiContractClientInner {
     /// The gas budget used by the client. If not set, the client will use a dry run to estimate
     /// the required gas budget.
     gas_budget: Option<u64>,
+    /// The sponsor that pays for the gas of the transactions, if any.
+    gas_sponsor: Option<GasSponsor>,
+    /// The SUI coins of the wallet from which gas coins are selected.
+    gas_coins: GasCoinCache,
+    /// The escalation of the gas price and budget of transactions that fail due to gas, if any.
+    gas_escalation: Option<GasEscalationConfig>,
+    /// The external signer of the transactions, if they are not signed with the wallet's keys.
+    signer: Option<Arc<dyn TransactionSigner>>,
 }
 
 impl SuiContractClientInner {
@@ -1303,6 +1617,10 @@ impl SuiContractClientInner {
             wallet,
             read_client,
             gas_budget,
+            gas_sponsor: None,
+            gas_coins: GasCoinCache::default(),
+            gas_escalation: None,
+            signer: None,
         })
     }
 
@@ -1530,13 +1848,42 @@ impl SuiContractClientInner {
         blob_metadata_list: Vec<BlobObjectMetadata>,
         persistence: BlobPersistence,
     ) -> SuiClientResult<Vec<Blob>> {
+        self.register_blobs_with_owned_and_new_storage(
+            vec![],
+            epochs_ahead,
+            blob_metadata_list,
+            persistence,
+        )
+        .await
+        .map(|(_, new_storage_blobs)| new_storage_blobs)
+    }
+
+    /// Registers the blobs with the provided storage resources, and purchases storage for the next
+    /// `epochs_ahead` Walrus epochs to register the blobs in `blob_metadata_list`, all in one
+    /// atomic transaction.
+    ///
+    /// Returns the blobs registered with the provided storage resources and the blobs registered
+    /// with newly purchased storage, in this order.
+    #[tracing::instrument(level = Level::DEBUG, skip_all)]
+    pub async fn register_blobs_with_owned_and_new_storage(
+        &mut self,
+        blob_metadata_and_storage: Vec<(BlobObjectMetadata, StorageResource)>,
+        epochs_ahead: EpochCount,
+        blob_metadata_list: Vec<BlobObjectMetadata>,
+        persistence: BlobPersistence,
+    ) -> SuiClientResult<(Vec<Blob>, Vec<Blob>)> {
+        let owned_storage_ids: HashSet<_> = blob_metadata_and_storage
+            .iter()
+            .map(|(_, storage)| storage.id)
+            .collect();
         let subsidies_package_id = self.read_client.get_subsidies_package_id();
-        match subsidies_package_id {
+        let blobs = match subsidies_package_id {
             Some(pkg_id) => {
                 match self
                     .reserve_and_register_blobs_with_subsidies(
                         epochs_ahead,
                         blob_metadata_list.clone(),
+                        blob_metadata_and_storage.clone(),
                         persistence,
                         pkg_id,
                     )
@@ -1553,6 +1900,7 @@ impl SuiContractClientInner {
                         self.reserve_and_register_blobs_without_subsidies(
                             epochs_ahead,
                             blob_metadata_list.clone(),
+                            blob_metadata_and_storage.clone(),
                             persistence,
                         )
                         .await
@@ -1564,11 +1912,50 @@ impl SuiContractClientInner {
                 self.reserve_and_register_blobs_without_subsidies(
                     epochs_ahead,
                     blob_metadata_list,
+                    blob_metadata_and_storage,
                     persistence,
                 )
                 .await
             }
+        }?;
+        Ok(blobs
+            .into_iter()
+            .partition(|blob| owned_storage_ids.contains(&blob.storage.id)))
+    }
+
+    /// Previews the costs and effects of reserving storage and registering the blobs with a dry
+    /// run, without submitting the transaction.
+    pub async fn preview_reserve_and_register_blobs(
+        &mut self,
+        epochs_ahead: EpochCount,
+        blob_metadata_list: Vec<BlobObjectMetadata>,
+        persistence: BlobPersistence,
+    ) -> SuiClientResult<TransactionPreview> {
+        let subsidies_package_id = self.read_client.get_subsidies_package_id();
+        let mut pt_builder = self.transaction_builder()?;
+        for blob_metadata in blob_metadata_list.into_iter() {
+            let storage_arg = match subsidies_package_id {
+                Some(pkg_id) => {
+                    pt_builder
+                        .reserve_space_with_subsidies(
+                            blob_metadata.encoded_size,
+                            epochs_ahead,
+                            pkg_id,
+                        )
+                        .await?
+                }
+                None => {
+                    pt_builder
+                        .reserve_space_without_subsidies(blob_metadata.encoded_size, epochs_ahead)
+                        .await?
+                }
+            };
+            pt_builder
+                .register_blob(storage_arg.into(), blob_metadata, persistence)
+                .await?;
         }
+        let (ptb, _sui_cost) = pt_builder.finish().await?;
+        self.preview_ptb(ptb).await
     }
 
     /// reserve and register blobs with subsidies
@@ -1576,6 +1963,7 @@ impl SuiContractClientInner {
         &mut self,
         epochs_ahead: EpochCount,
         blob_metadata_list: Vec<BlobObjectMetadata>,
+        blob_metadata_and_storage: Vec<(BlobObjectMetadata, StorageResource)>,
         persistence: BlobPersistence,
         subsidies_package_id: ObjectID,
     ) -> SuiClientResult<Vec<Blob>> {
@@ -1583,6 +1971,7 @@ impl SuiContractClientInner {
         self.reserve_and_register_blobs_impl(
             epochs_ahead,
             blob_metadata_list,
+            blob_metadata_and_storage,
             persistence,
             |builder, encoded_size, epochs| {
                 Box::pin(async move {
@@ -1600,12 +1989,14 @@ impl SuiContractClientInner {
         &mut self,
         epochs_ahead: EpochCount,
         blob_metadata_list: Vec<BlobObjectMetadata>,
+        blob_metadata_and_storage: Vec<(BlobObjectMetadata, StorageResource)>,
         persistence: BlobPersistence,
     ) -> SuiClientResult<Vec<Blob>> {
         // Use helper for implementing with the non-subsidies approach
         self.reserve_and_register_blobs_impl(
             epochs_ahead,
             blob_metadata_list,
+            blob_metadata_and_storage,
             persistence,
             |builder, encoded_size, epochs| {
                 Box::pin(async move {
@@ -1619,10 +2010,14 @@ impl SuiContractClientInner {
     }
 
     /// Common implementation for reserving and registering blobs
+    ///
+    /// The blobs in `blob_metadata_and_storage` are registered with the provided storage resources
+    /// in the same transaction.
     async fn reserve_and_register_blobs_impl<F>(
         &mut self,
         epochs_ahead: EpochCount,
         blob_metadata_list: Vec<BlobObjectMetadata>,
+        blob_metadata_and_storage: Vec<(BlobObjectMetadata, StorageResource)>,
         persistence: BlobPersistence,
         reserve_space_fn: F,
     ) -> SuiClientResult<Vec<Blob>>
@@ -1634,18 +2029,24 @@ impl SuiContractClientInner {
             ) -> BoxFuture<'a, SuiClientResult<Argument>>
             + Send,
     {
-        if blob_metadata_list.is_empty() {
+        if blob_metadata_list.is_empty() && blob_metadata_and_storage.is_empty() {
             tracing::debug!("no blobs to register");
             return Ok(vec![]);
         }
 
-        let expected_num_blobs = blob_metadata_list.len();
+        let expected_num_blobs = blob_metadata_list.len() + blob_metadata_and_storage.len();
         tracing::debug!(
             num_blobs = expected_num_blobs,
+            num_blobs_with_owned_storage = blob_metadata_and_storage.len(),
             "starting to reserve and register blobs"
         );
 
         let mut pt_builder = self.transaction_builder()?;
+        for (blob_metadata, storage) in blob_metadata_and_storage.into_iter() {
+            pt_builder
+                .register_blob(storage.id.into(), blob_metadata, persistence)
+                .await?;
+        }
         for blob_metadata in blob_metadata_list.into_iter() {
             let storage_arg =
                 reserve_space_fn(&mut pt_builder, blob_metadata.encoded_size, epochs_ahead).await?;
@@ -2236,13 +2637,13 @@ impl SuiContractClientInner {
         method: &str,
     ) -> SuiClientResult<SuiTransactionBlockResponse> {
         // Get the current gas price from the network
-        let gas_price = self.wallet.get_reference_gas_price().await?;
+        let mut gas_price = self.wallet.get_reference_gas_price().await?;
         let wallet_address = self.wallet.active_address()?;
 
         tracing::debug!(?programmable_transaction, "sending PTB");
 
         // Estimate the gas budget unless explicitly set.
-        let gas_budget = if let Some(budget) = self.gas_budget {
+        let mut gas_budget = if let Some(budget) = self.gas_budget {
             budget
         } else {
             let tx_kind =
@@ -2253,26 +2654,146 @@ impl SuiContractClientInner {
                 .await?
         };
 
-        let min_gas_coin_balance =
-            minimum_gas_coin_balance.max(gas_budget + additional_gas_coin_balance);
+        // An explicitly set gas budget is never exceeded, so such transactions are not escalated.
+        // Neither are transactions whose signer does not allow signing them again.
+        let escalation = self
+            .gas_escalation
+            .clone()
+            .filter(|_| self.gas_budget.is_none() && self.allows_resigning());
+        let Some(escalation) = escalation else {
+            return self
+                .sign_and_send_ptb_with_gas(
+                    programmable_transaction,
+                    gas_budget,
+                    gas_price,
+                    additional_gas_coin_balance,
+                    minimum_gas_coin_balance,
+                    method,
+                )
+                .await;
+        };
 
-        // Construct the transaction with gas coins that meet the minimum balance requirement
-        let transaction = TransactionData::new_programmable(
-            wallet_address,
-            self.get_compatible_gas_coins(min_gas_coin_balance).await?,
-            programmable_transaction,
-            gas_budget,
-            gas_price,
-        );
+        let mut retries = 0;
+        loop {
+            let error = match self
+                .sign_and_send_ptb_with_gas(
+                    programmable_transaction.clone(),
+                    gas_budget,
+                    gas_price,
+                    additional_gas_coin_balance,
+                    minimum_gas_coin_balance,
+                    method,
+                )
+                .await
+            {
+                Err(error) if gas_escalation::is_gas_error(&error) => error,
+                result => return result,
+            };
+            if escalation.max_retries == 0 {
+                return Err(error);
+            }
 
-        // Sign the transaction with the wallet's keys
-        let signed_transaction = self.wallet.sign_transaction(&transaction);
+            let reference_gas_price = self.wallet.get_reference_gas_price().await?;
+            let next_attempt = (retries < escalation.max_retries)
+                .then(|| escalation.escalate(gas_budget, gas_price, reference_gas_price))
+                .flatten();
+            let Some((next_gas_budget, next_gas_price)) = next_attempt else {
+                return Err(SuiClientError::GasEscalationExhausted {
+                    gas_budget,
+                    gas_price,
+                    source: Box::new(error),
+                });
+            };
+            tracing::warn!(
+                %error,
+                gas_budget = next_gas_budget,
+                gas_price = next_gas_price,
+                "the transaction failed due to gas; resubmitting with an increased gas price and \
+                budget"
+            );
+            retries += 1;
+            gas_budget = next_gas_budget;
+            gas_price = next_gas_price;
+        }
+    }
 
-        // Execute the transaction and wait for response
-        let response = self
-            .sui_client()
-            .execute_transaction(signed_transaction, method)
-            .await?;
+    /// Signs and sends a programmable transaction with the given gas budget and gas price.
+    async fn sign_and_send_ptb_with_gas(
+        &mut self,
+        programmable_transaction: ProgrammableTransaction,
+        gas_budget: u64,
+        gas_price: u64,
+        additional_gas_coin_balance: u64,
+        minimum_gas_coin_balance: u64,
+        method: &str,
+    ) -> SuiClientResult<SuiTransactionBlockResponse> {
+        let wallet_address = self.wallet.active_address()?;
+
+        // Only transactions that use the gas coin exclusively for gas can be sponsored.
+        let response = if self.gas_sponsor.is_some()
+            && additional_gas_coin_balance == 0
+            && minimum_gas_coin_balance == 0
+        {
+            self.sign_and_send_sponsored_ptb(
+                programmable_transaction,
+                gas_budget,
+                gas_price,
+                method,
+            )
+            .await?
+        } else {
+            let min_gas_coin_balance =
+                minimum_gas_coin_balance.max(gas_budget + additional_gas_coin_balance);
+            let mut locked_gas_coins = HashSet::new();
+            let mut attempt = 0;
+
+            loop {
+                attempt += 1;
+                // Construct the transaction with gas coins that meet the minimum balance
+                // requirement
+                let gas_payment = self
+                    .get_compatible_gas_coins(min_gas_coin_balance, &locked_gas_coins)
+                    .await?;
+                let gas_coin_ids: Vec<_> = gas_payment.iter().map(|coin| coin.0).collect();
+                let transaction = TransactionData::new_programmable(
+                    wallet_address,
+                    gas_payment,
+                    programmable_transaction.clone(),
+                    gas_budget,
+                    gas_price,
+                );
+
+                // Sign the transaction with the wallet's keys
+                let signed_transaction = self.sign_transaction(&transaction).await?;
+
+                // Execute the transaction and wait for response
+                match self
+                    .sui_client()
+                    .execute_transaction(signed_transaction, method)
+                    .await
+                {
+                    Ok(response) => break response,
+                    // The gas coins may be used by a concurrent transaction from the same wallet;
+                    // retry with other coins.
+                    Err(error)
+                        if gas_coins::is_object_lock_error(&error)
+                            && attempt < gas_coins::MAX_GAS_COIN_LOCK_ATTEMPTS
+                            && self.allows_resigning() =>
+                    {
+                        tracing::warn!(
+                            %error,
+                            ?gas_coin_ids,
+                            "gas coins are locked by a concurrent transaction; retrying with \
+                            other coins"
+                        );
+                        locked_gas_coins.extend(gas_coin_ids);
+                        // Other cached coins may have been used by the concurrent transaction.
+                        self.gas_coins.clear();
+                    }
+                    Err(error) => return Err(error.into()),
+                }
+            }
+        };
 
         // Check transaction execution status from effects
         match response
@@ -2294,22 +2815,281 @@ impl SuiContractClientInner {
         }
     }
 
+    /// Signs and sends a programmable transaction whose gas is paid by the sponsor.
+    async fn sign_and_send_sponsored_ptb(
+        &self,
+        programmable_transaction: ProgrammableTransaction,
+        gas_budget: u64,
+        gas_price: u64,
+        method: &str,
+    ) -> SuiClientResult<SuiTransactionBlockResponse> {
+        let wallet_address = self.wallet.active_address()?;
+        match self
+            .gas_sponsor
+            .as_ref()
+            .expect("the sponsor is set for sponsored transactions")
+        {
+            GasSponsor::Key {
+                address,
+                keypair,
+                lock,
+            } => {
+                // Other clients with the same sponsor must not select the same gas coins until
+                // this transaction has been executed.
+                let _guard = lock.lock().await;
+                let gas_coins = self
+                    .read_client
+                    .get_coins_with_total_balance(*address, CoinType::Sui, gas_budget, vec![])
+                    .await?
+                    .iter()
+                    .map(Coin::object_ref)
+                    .collect();
+                let transaction = TransactionData::new_programmable_allow_sponsor(
+                    wallet_address,
+                    gas_coins,
+                    programmable_transaction,
+                    gas_budget,
+                    gas_price,
+                    *address,
+                );
+                // Both the sender and the sponsor sign the transaction.
+                let sender_signed = self.sign_transaction(&transaction).await?;
+                let sponsor_signed = Transaction::from_data_and_signer(
+                    transaction.clone(),
+                    vec![keypair as &dyn Signer<Signature>],
+                );
+                let signed_transaction = Transaction::from_generic_sig_data(
+                    transaction,
+                    [sender_signed, sponsor_signed]
+                        .iter()
+                        .flat_map(|signed| signed.data().tx_signatures().to_vec())
+                        .collect(),
+                );
+                Ok(self
+                    .sui_client()
+                    .execute_transaction(signed_transaction, method)
+                    .await?)
+            }
+            GasSponsor::Station(station) => {
+                let reservation = station.reserve_gas(gas_budget).await?;
+                tracing::debug!(
+                    reservation_id = reservation.reservation_id,
+                    sponsor = %reservation.sponsor_address,
+                    "reserved gas at the gas station"
+                );
+                let transaction = TransactionData::new_programmable_allow_sponsor(
+                    wallet_address,
+                    reservation.gas_coins(),
+                    programmable_transaction,
+                    gas_budget,
+                    gas_price,
+                    reservation.sponsor_address,
+                );
+                let sender_signed = self.sign_transaction(&transaction).await?;
+                let digest = station
+                    .execute_transaction(
+                        reservation.reservation_id,
+                        &transaction,
+                        &sender_signed.data().tx_signatures()[0],
+                    )
+                    .await?;
+                // The gas station only returns the effects of the transaction, so the full
+                // response is read from the full node.
+                Ok(self
+                    .sui_client()
+                    .get_transaction_with_options(
+                        digest,
+                        SuiTransactionBlockResponseOptions::new()
+                            .with_effects()
+                            .with_input()
+                            .with_events()
+                            .with_object_changes()
+                            .with_balance_changes(),
+                    )
+                    .await?)
+            }
+        }
+    }
+
+    /// Dry-runs the programmable transaction to preview its costs and effects.
+    async fn preview_ptb(
+        &mut self,
+        programmable_transaction: ProgrammableTransaction,
+    ) -> SuiClientResult<TransactionPreview> {
+        let gas_price = self.wallet.get_reference_gas_price().await?;
+        let sender = self.wallet.active_address()?;
+        let response = self
+            .sui_client()
+            .dry_run_transaction_kind(
+                sender,
+                TransactionKind::ProgrammableTransaction(programmable_transaction),
+                gas_price,
+            )
+            .await?;
+        if let SuiExecutionStatus::Failure { error } = response.effects.status() {
+            return Err(SuiClientError::TransactionExecutionError(
+                error.as_str().into(),
+            ));
+        }
+
+        let wal_type = TypeTag::from_str(self.read_client.wal_coin_type())?;
+        let wal_balance_change: i128 = response
+            .balance_changes
+            .iter()
+            .filter(|change| {
+                change.owner == Owner::AddressOwner(sender) && change.coin_type == wal_type
+            })
+            .map(|change| change.amount)
+            .sum();
+        Ok(TransactionPreview {
+            gas_cost: response
+                .effects
+                .gas_cost_summary()
+                .net_gas_usage()
+                .max(0)
+                .try_into()
+                .expect("the value is non-negative"),
+            wal_cost: (-wal_balance_change).max(0).try_into().unwrap_or(u64::MAX),
+            object_changes: response.object_changes,
+        })
+    }
+
+    /// Returns true if failed transactions may be signed again with other gas parameters.
+    ///
+    /// See [`TransactionSigner::allows_resigning`].
+    fn allows_resigning(&self) -> bool {
+        self.signer
+            .as_ref()
+            .is_none_or(|signer| signer.allows_resigning())
+    }
+
+    /// Signs the transaction with the external signer, if any, or else with the wallet's keys.
+    async fn sign_transaction(
+        &self,
+        transaction: &TransactionData,
+    ) -> SuiClientResult<Transaction> {
+        match &self.signer {
+            Some(signer) => {
+                let signature = signer
+                    .sign_transaction(transaction)
+                    .await
+                    .context("the external signer failed to sign the transaction")?;
+                Ok(Transaction::from_generic_sig_data(
+                    transaction.clone(),
+                    vec![signature],
+                ))
+            }
+            None => Ok(self.wallet.sign_transaction(transaction)),
+        }
+    }
+
+    /// Selects gas coins with a total balance of at least `min_balance`, ignoring the `excluded`
+    /// ones.
+    ///
+    /// If the SUI of the wallet is spread across too many coins to pay for the transaction, the
+    /// coins are merged first.
     async fn get_compatible_gas_coins(
         &mut self,
         min_balance: u64,
+        excluded: &HashSet<ObjectID>,
     ) -> SuiClientResult<Vec<ObjectRef>> {
-        Ok(self
-            .read_client
-            .get_coins_with_total_balance(
-                self.wallet.active_address()?,
-                CoinType::Sui,
-                min_balance,
-                vec![],
+        let address = self.wallet.active_address()?;
+        for _ in 0..gas_coins::MAX_GAS_COIN_MERGES {
+            if let GasCoinSelection::Selected(coins) = self.gas_coins.take(min_balance, excluded) {
+                return Ok(coins.iter().map(Coin::object_ref).collect());
+            }
+
+            // The cached coins are insufficient; fetch coins covering several transactions.
+            let fetch_balance = min_balance.saturating_mul(gas_coins::GAS_COIN_FETCH_FACTOR);
+            let coins = self
+                .sui_client()
+                .get_coins_covering(address, None, fetch_balance.into(), excluded)
+                .await;
+            self.gas_coins.replace(coins);
+            match self.gas_coins.take(min_balance, excluded) {
+                GasCoinSelection::Selected(coins) => {
+                    return Ok(coins.iter().map(Coin::object_ref).collect());
+                }
+                GasCoinSelection::Insufficient => break,
+                GasCoinSelection::Fragmented => {
+                    tracing::info!(
+                        min_balance,
+                        "the SUI of the wallet is spread across too many coins; merging coins"
+                    );
+                    let coins = self.gas_coins.take_coins_to_merge(excluded);
+                    self.merge_gas_coins(coins).await?;
+                }
+            }
+        }
+        Err(SuiClientError::NoCompatibleGasCoins(Some(
+            min_balance.into(),
+        )))
+    }
+
+    /// Merges the SUI `coins` of the wallet by using them as gas payment of a transaction that
+    /// sends the gas coin to the wallet itself.
+    async fn merge_gas_coins(&mut self, coins: Vec<Coin>) -> SuiClientResult<()> {
+        let address = self.wallet.active_address()?;
+        let gas_price = self.wallet.get_reference_gas_price().await?;
+        let total_balance = coins.iter().map(|coin| coin.balance).sum::<u64>();
+
+        let mut pt_builder = ProgrammableTransactionBuilder::new();
+        pt_builder.pay_all_sui(address);
+        let programmable_transaction = pt_builder.finish();
+        let gas_budget = if let Some(budget) = self.gas_budget {
+            budget
+        } else {
+            self.read_client
+                .sui_client()
+                .estimate_gas_budget(
+                    address,
+                    TransactionKind::ProgrammableTransaction(programmable_transaction.clone()),
+                    gas_price,
+                )
+                .await?
+        }
+        .min(total_balance);
+
+        let transaction = TransactionData::new_programmable(
+            address,
+            coins.iter().map(Coin::object_ref).collect(),
+            programmable_transaction,
+            gas_budget,
+            gas_price,
+        );
+        let signed_transaction = self.sign_transaction(&transaction).await?;
+        self.sui_client()
+            .execute_transaction(signed_transaction, "merge_gas_coins")
+            .await?;
+        Ok(())
+    }
+
+    /// Splits `count` SUI coins of `amount` each off the gas coin of the wallet, and returns the
+    /// IDs of the new coins.
+    pub async fn split_gas_coin(
+        &mut self,
+        count: usize,
+        amount: u64,
+    ) -> SuiClientResult<Vec<ObjectID>> {
+        tracing::debug!(count, amount, "splitting the gas coin");
+        let address = self.wallet.active_address()?;
+        let mut pt_builder = ProgrammableTransactionBuilder::new();
+        pt_builder.pay_sui(vec![address; count], vec![amount; count])?;
+
+        let total_amount = amount
+            .checked_mul(count as u64)
+            .context("the total amount of the split coins overflows")?;
+        let response = self
+            .sign_and_send_ptb_with_additional_gas_coin_balance(
+                pt_builder.finish(),
+                total_amount,
+                "split_gas_coin",
             )
-            .await?
-            .iter()
-            .map(Coin::object_ref)
-            .collect())
+            .await?;
+        Ok(get_created_sui_object_ids_by_type(
+            &response,
+            &GasCoin::type_(),
+        )?)
     }
 
     /// Merges the WAL and SUI coins owned by the wallet of the contract client.
@@ -2771,6 +3551,7 @@ impl SuiContractClientInner {
             .map(|(blob_params, r)| CertifyAndExtendBlobResult {
                 blob_object_id: blob_params.blob.id,
                 post_store_action_result: r,
+                transaction_digest: res.digest,
             })
             .collect();
 
@@ -2964,6 +3745,10 @@ impl ReadClient for SuiContractClient {
             .await
     }
 
+    async fn buyer_subsidy_rate(&self) -> SuiClientResult<u16> {
+        self.read_client.buyer_subsidy_rate().await
+    }
+
     async fn event_stream(
         &self,
         polling_interval: Duration,
@@ -3051,6 +3836,10 @@ impl ReadClient for SuiContractClient {
     async fn system_object_version(&self) -> SuiClientResult<u64> {
         self.read_client.system_object_version().await
     }
+
+    fn clear_cache(&self) {
+        self.read_client.clear_cache();
+    }
 }
 
 impl fmt::Debug for SuiContractClient {
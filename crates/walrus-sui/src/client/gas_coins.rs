@@ -0,0 +1,251 @@
+// Copyright (c) Walrus Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Selection of the gas coins of the transactions of a
+//! [`SuiContractClient`][super::SuiContractClient].
+//!
+//! Gas coins are selected largest first, so that a transaction uses as few coins as possible even
+//! if the SUI of the wallet is spread across many coins. Since Sui merges all the coins of the gas
+//! payment into the first one, the selection additionally includes a few dust coins, which
+//! gradually consolidates a fragmented wallet without dedicated transactions.
+//!
+//! The coins of the wallet are not listed for each transaction. They are fetched page by page
+//! until they cover a multiple of the required balance, and kept in a [`GasCoinCache`] from which
+//! subsequent transactions select their gas coins until the cached coins are insufficient.
+
+use std::collections::HashSet;
+
+use sui_sdk::rpc_types::Coin;
+use sui_types::base_types::ObjectID;
+
+// Keep in sync with `max_gas_payment_objects` in the Sui protocol config.
+/// The maximum number of coins that can be used as gas payment of a transaction.
+pub(crate) const MAX_GAS_PAYMENT_COINS: usize = 256;
+/// SUI coins with a balance below this value are considered dust.
+const DUST_COIN_BALANCE: u64 = 10_000_000; // 0.01 SUI
+/// The maximum number of dust coins merged into the gas coin as part of a single transaction.
+const MAX_DUST_COINS_PER_TRANSACTION: usize = 32;
+/// The maximum number of merge transactions before giving up on selecting the gas coins.
+pub(crate) const MAX_GAS_COIN_MERGES: usize = 4;
+/// The maximum number of attempts to execute a transaction when its gas coins are locked by
+/// concurrent transactions.
+pub(crate) const MAX_GAS_COIN_LOCK_ATTEMPTS: usize = 3;
+/// The multiple of the balance required by a transaction that is fetched into the cache, so that
+/// the following transactions can select their gas coins from the cache.
+pub(crate) const GAS_COIN_FETCH_FACTOR: u64 = 8;
+
+/// The result of selecting the gas coins for a transaction.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum GasCoinSelection {
+    /// The coins to use as gas payment, largest first.
+    Selected(Vec<Coin>),
+    /// The coins hold enough SUI, but more than [`MAX_GAS_PAYMENT_COINS`] coins would be needed;
+    /// the coins need to be merged before they can be used.
+    Fragmented,
+    /// The total balance of the coins is insufficient.
+    Insufficient,
+}
+
+/// Selects gas coins with a total balance of at least `min_balance`, ignoring the `excluded` ones.
+pub(crate) fn select_gas_coins(
+    mut coins: Vec<Coin>,
+    min_balance: u64,
+    excluded: &HashSet<ObjectID>,
+) -> GasCoinSelection {
+    coins.retain(|coin| !excluded.contains(&coin.coin_object_id));
+    sort_largest_first(&mut coins);
+
+    let mut total = 0u64;
+    let Some(n_required) = coins.iter().position(|coin| {
+        total = total.saturating_add(coin.balance);
+        total >= min_balance
+    }) else {
+        return GasCoinSelection::Insufficient;
+    };
+    let n_required = n_required + 1;
+    if n_required > MAX_GAS_PAYMENT_COINS {
+        return GasCoinSelection::Fragmented;
+    }
+
+    // Merge the smallest remaining dust coins into the gas coin along the way.
+    let n_dust = coins[n_required..]
+        .iter()
+        .rev()
+        .take_while(|coin| coin.balance < DUST_COIN_BALANCE)
+        .take(MAX_DUST_COINS_PER_TRANSACTION.min(MAX_GAS_PAYMENT_COINS - n_required))
+        .count();
+    let dust = coins.split_off(coins.len() - n_dust);
+    coins.truncate(n_required);
+    coins.extend(dust.into_iter().rev());
+    GasCoinSelection::Selected(coins)
+}
+
+/// Returns the largest coins, at most [`MAX_GAS_PAYMENT_COINS`], ignoring the `excluded` ones.
+///
+/// Used as gas payment of a transaction, the returned coins are merged into a single coin.
+pub(crate) fn coins_to_merge(mut coins: Vec<Coin>, excluded: &HashSet<ObjectID>) -> Vec<Coin> {
+    coins.retain(|coin| !excluded.contains(&coin.coin_object_id));
+    sort_largest_first(&mut coins);
+    coins.truncate(MAX_GAS_PAYMENT_COINS);
+    coins
+}
+
+/// The SUI coins of the wallet that have not been used since they were fetched.
+///
+/// Coins selected as gas payment are removed from the cache, as the transaction modifies or
+/// deletes them. The cache must be cleared if coins may have been used elsewhere, for example if a
+/// transaction fails because its gas coins are locked.
+#[derive(Debug, Default)]
+pub(crate) struct GasCoinCache {
+    coins: Vec<Coin>,
+}
+
+impl GasCoinCache {
+    /// Replaces the cached coins with the freshly fetched `coins`.
+    pub(crate) fn replace(&mut self, coins: Vec<Coin>) {
+        self.coins = coins;
+    }
+
+    /// Removes all coins from the cache.
+    pub(crate) fn clear(&mut self) {
+        self.coins.clear();
+    }
+
+    /// Selects gas coins from the cache with [`select_gas_coins`], and removes the selected coins
+    /// from the cache.
+    pub(crate) fn take(
+        &mut self,
+        min_balance: u64,
+        excluded: &HashSet<ObjectID>,
+    ) -> GasCoinSelection {
+        let selection = select_gas_coins(self.coins.clone(), min_balance, excluded);
+        if let GasCoinSelection::Selected(selected) = &selection {
+            let selected: HashSet<_> = selected.iter().map(|coin| coin.coin_object_id).collect();
+            self.coins
+                .retain(|coin| !selected.contains(&coin.coin_object_id));
+        }
+        selection
+    }
+
+    /// Removes all coins from the cache, and returns the largest ones to merge, see
+    /// [`coins_to_merge`].
+    pub(crate) fn take_coins_to_merge(&mut self, excluded: &HashSet<ObjectID>) -> Vec<Coin> {
+        coins_to_merge(std::mem::take(&mut self.coins), excluded)
+    }
+}
+
+fn sort_largest_first(coins: &mut [Coin]) {
+    coins.sort_by(|a, b| b.balance.cmp(&a.balance));
+}
+
+/// Returns true if the error indicates that an object used by the transaction is locked by or
+/// was consumed in a concurrent transaction.
+///
+/// For gas coins, such errors are resolved by retrying the transaction with other coins.
+pub(crate) fn is_object_lock_error(error: &anyhow::Error) -> bool {
+    let error = format!("{error:?}");
+    [
+        "ObjectLockConflict",
+        "ObjectVersionUnavailableForConsumption",
+        "ObjectsDoubleUsed",
+    ]
+    .iter()
+    .any(|pattern| error.contains(pattern))
+}
+
+#[cfg(test)]
+mod tests {
+    use sui_types::{
+        base_types::{ObjectDigest, SequenceNumber},
+        digests::TransactionDigest,
+    };
+
+    use super::*;
+
+    fn coins(balances: &[u64]) -> Vec<Coin> {
+        balances
+            .iter()
+            .map(|balance| Coin {
+                coin_type: "0x2::sui::SUI".to_owned(),
+                coin_object_id: ObjectID::random(),
+                version: SequenceNumber::new(),
+                digest: ObjectDigest::random(),
+                balance: *balance,
+                previous_transaction: TransactionDigest::random(),
+            })
+            .collect()
+    }
+
+    fn balances(selection: GasCoinSelection) -> Vec<u64> {
+        match selection {
+            GasCoinSelection::Selected(coins) => coins.iter().map(|coin| coin.balance).collect(),
+            other => panic!("expected selected coins, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn selects_largest_coins_and_merges_dust() {
+        let selection = select_gas_coins(
+            coins(&[1, 5_000_000_000, 3, 20_000_000_000, 2, 30_000_000]),
+            21_000_000_000,
+            &HashSet::new(),
+        );
+        assert_eq!(
+            balances(selection),
+            vec![20_000_000_000, 5_000_000_000, 1, 2, 3]
+        );
+    }
+
+    #[test]
+    fn skips_excluded_coins() {
+        let coins = coins(&[20_000_000_000, 15_000_000_000, 10_000_000_000]);
+        let excluded = HashSet::from([coins[0].coin_object_id]);
+        let selection = select_gas_coins(coins, 20_000_000_000, &excluded);
+        assert_eq!(balances(selection), vec![15_000_000_000, 10_000_000_000]);
+    }
+
+    #[test]
+    fn detects_insufficient_and_fragmented_coins() {
+        assert_eq!(
+            select_gas_coins(coins(&[1_000, 2_000]), 5_000, &HashSet::new()),
+            GasCoinSelection::Insufficient
+        );
+        assert_eq!(
+            select_gas_coins(
+                coins(&[1_000; MAX_GAS_PAYMENT_COINS + 10]),
+                1_000 * (MAX_GAS_PAYMENT_COINS as u64 + 1),
+                &HashSet::new()
+            ),
+            GasCoinSelection::Fragmented
+        );
+        assert_eq!(
+            coins_to_merge(coins(&[1_000; MAX_GAS_PAYMENT_COINS + 10]), &HashSet::new()).len(),
+            MAX_GAS_PAYMENT_COINS
+        );
+    }
+
+    #[test]
+    fn reuses_cached_coins_until_insufficient() {
+        let mut cache = GasCoinCache::default();
+        cache.replace(coins(&[20_000_000_000, 15_000_000_000, 10_000_000_000]));
+
+        // Each selection removes the selected coins from the cache.
+        for expected in [20_000_000_000, 15_000_000_000, 10_000_000_000] {
+            assert_eq!(
+                balances(cache.take(10_000_000_000, &HashSet::new())),
+                vec![expected]
+            );
+        }
+        assert_eq!(
+            cache.take(10_000_000_000, &HashSet::new()),
+            GasCoinSelection::Insufficient
+        );
+
+        cache.replace(coins(&[20_000_000_000]));
+        cache.clear();
+        assert_eq!(
+            cache.take(1, &HashSet::new()),
+            GasCoinSelection::Insufficient
+        );
+    }
+}
@@ -0,0 +1,137 @@
+// Copyright (c) Walrus Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Escalation of the gas price and budget of transactions that fail due to gas.
+//!
+//! Transactions can fail because the gas price changed at an epoch change, because the gas budget
+//! estimated with a dry run is insufficient when the transaction is executed, or because the shared
+//! objects they use are congested. Such transactions are resubmitted with an increased gas price
+//! and budget, until the configured maximum gas budget is reached. Transactions of clients with an
+//! explicitly set gas budget are not escalated, as their budget is never exceeded.
+
+use serde::{Deserialize, Serialize};
+
+use super::SuiClientError;
+use crate::types::move_errors::MoveExecutionError;
+
+/// The configuration of the escalation of the gas price and budget of failed transactions.
+///
+/// The escalation does not apply if the gas budget of the client is set explicitly.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(default)]
+pub struct GasEscalationConfig {
+    /// The maximum gas budget in MIST up to which the gas budget of a transaction is increased.
+    pub max_gas_budget: u64,
+    /// The maximum number of times a transaction is resubmitted with an increased gas price.
+    ///
+    /// Setting this to 0 disables the escalation.
+    pub max_retries: u32,
+    /// The percentage by which the gas price is increased on every resubmission.
+    pub gas_price_increase_percent: u64,
+}
+
+impl Default for GasEscalationConfig {
+    fn default() -> Self {
+        Self {
+            max_gas_budget: 5_000_000_000, // 5 SUI
+            max_retries: 3,
+            gas_price_increase_percent: 50,
+        }
+    }
+}
+
+impl GasEscalationConfig {
+    /// Returns the gas budget and gas price with which a transaction that failed due to gas is
+    /// resubmitted, or `None` if the gas budget is already at the maximum.
+    ///
+    /// The gas price is increased by [`Self::gas_price_increase_percent`] and is at least the
+    /// current `reference_gas_price`. The gas budget is increased proportionally to the gas price,
+    /// and additionally by the same percentage to cover the case in which the budget was
+    /// insufficient.
+    pub(crate) fn escalate(
+        &self,
+        gas_budget: u64,
+        gas_price: u64,
+        reference_gas_price: u64,
+    ) -> Option<(u64, u64)> {
+        if gas_budget >= self.max_gas_budget {
+            return None;
+        }
+        let increase = |value: u64| {
+            u64::try_from(
+                u128::from(value) * u128::from(100 + self.gas_price_increase_percent) / 100,
+            )
+            .unwrap_or(u64::MAX)
+        };
+        let new_gas_price = increase(gas_price).max(reference_gas_price).max(1);
+        let new_gas_budget = u64::try_from(
+            u128::from(increase(gas_budget)) * u128::from(new_gas_price)
+                / u128::from(gas_price.max(1)),
+        )
+        .unwrap_or(u64::MAX)
+        .min(self.max_gas_budget);
+        Some((new_gas_budget, new_gas_price))
+    }
+}
+
+/// Returns true if the transaction failed due to its gas price or budget, or due to congestion,
+/// and may succeed if resubmitted with a higher gas price and budget.
+pub(crate) fn is_gas_error(error: &SuiClientError) -> bool {
+    const GAS_ERRORS: [&str; 3] = ["InsufficientGas", "GasPriceUnderRGP", "GasBudgetTooLow"];
+    match error {
+        SuiClientError::SharedObjectCongestion(_) => true,
+        SuiClientError::TransactionExecutionError(MoveExecutionError::NotParsable(error)) => {
+            GAS_ERRORS.iter().any(|pattern| error.contains(pattern))
+        }
+        SuiClientError::Internal(error) => {
+            let error = format!("{error:?}");
+            GAS_ERRORS.iter().any(|pattern| error.contains(pattern))
+        }
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn escalates_gas_price_and_budget_up_to_the_maximum() {
+        let config = GasEscalationConfig {
+            max_gas_budget: 1_000_000,
+            max_retries: 3,
+            gas_price_increase_percent: 50,
+        };
+
+        assert_eq!(
+            config.escalate(100_000, 1_000, 1_000),
+            Some((225_000, 1_500))
+        );
+        // The gas price is at least the reference gas price, and the budget follows.
+        assert_eq!(
+            config.escalate(100_000, 1_000, 2_000),
+            Some((300_000, 2_000))
+        );
+        // The budget is capped at the maximum, after which no further escalation happens.
+        assert_eq!(
+            config.escalate(800_000, 1_000, 1_000),
+            Some((1_000_000, 1_500))
+        );
+        assert_eq!(config.escalate(1_000_000, 1_500, 1_000), None);
+    }
+
+    #[test]
+    fn recognizes_gas_errors() {
+        assert!(is_gas_error(&SuiClientError::SharedObjectCongestion(
+            vec![]
+        )));
+        assert!(is_gas_error(&SuiClientError::TransactionExecutionError(
+            "InsufficientGas".into()
+        )));
+        assert!(is_gas_error(&SuiClientError::Internal(anyhow::anyhow!(
+            "Transaction validator signing failed due to issues with transaction inputs: \
+            GasPriceUnderRGP {{ gas_price: 750, reference_gas_price: 1000 }}"
+        ))));
+        assert!(!is_gas_error(&SuiClientError::NoCompatibleWalCoins));
+    }
+}
@@ -0,0 +1,290 @@
+// Copyright (c) Walrus Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Sponsorship of the gas of the transactions of a [`SuiContractClient`][super::SuiContractClient].
+//!
+//! A sponsor pays for the gas of the transactions of the client, so that the wallet of the client
+//! does not need to hold SUI. The sponsor is either a key available to the client, whose SUI coins
+//! are used as gas payment, or a gas station that implements the API of the Sui gas pool
+//! (`/v1/reserve_gas` and `/v1/execute_tx`).
+//!
+//! The transactions paid by a sponsor key are serialized across all contract clients of the
+//! process, as concurrent transactions would otherwise select the same gas coins of the sponsor. A
+//! sponsor key must therefore not be used by several processes at the same time; a gas station
+//! reserves distinct gas coins for each transaction instead.
+
+use std::{
+    collections::HashMap,
+    path::PathBuf,
+    sync::{Arc, Mutex, OnceLock},
+    time::Duration,
+};
+
+use anyhow::{anyhow, Context};
+use fastcrypto::encoding::{Base64, Encoding as _};
+use serde::{Deserialize, Serialize};
+use sui_sdk::rpc_types::{
+    SuiObjectRef,
+    SuiTransactionBlockEffects,
+    SuiTransactionBlockEffectsAPI as _,
+};
+use sui_types::{
+    base_types::{ObjectRef, SuiAddress},
+    crypto::SuiKeyPair,
+    digests::TransactionDigest,
+    signature::GenericSignature,
+    transaction::TransactionData,
+};
+use walrus_utils::config::resolve_home_dir;
+
+use super::{SuiClientError, SuiClientResult};
+
+/// The duration for which gas coins are reserved at the gas station.
+const GAS_RESERVATION_DURATION: Duration = Duration::from_secs(60);
+
+/// The lock serializing the transactions paid by a sponsor key.
+pub(crate) type SponsorKeyLock = Arc<tokio::sync::Mutex<()>>;
+
+/// The locks of the sponsor keys used in this process, by address of the sponsor.
+static SPONSOR_KEY_LOCKS: OnceLock<Mutex<HashMap<SuiAddress, SponsorKeyLock>>> = OnceLock::new();
+
+/// Returns the lock serializing the transactions paid by the sponsor with the given address, which
+/// is shared by all contract clients of the process with that sponsor.
+fn sponsor_key_lock(address: SuiAddress) -> SponsorKeyLock {
+    SPONSOR_KEY_LOCKS
+        .get_or_init(Default::default)
+        .lock()
+        .expect("mutex should not be poisoned")
+        .entry(address)
+        .or_default()
+        .clone()
+}
+
+/// The configuration of the sponsor that pays for the gas of transactions.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum GasSponsorConfig {
+    /// A gas station implementing the API of the Sui gas pool.
+    Endpoint {
+        /// The URL of the gas station.
+        url: reqwest::Url,
+        /// The bearer token with which the requests to the gas station are authenticated.
+        #[serde(default)]
+        auth_token: Option<String>,
+    },
+    /// A key of the sponsor, which signs the transactions of the client.
+    Key {
+        /// The path to a file containing the Bech32-encoded private key of the sponsor, starting
+        /// with `suiprivkey`.
+        #[serde(deserialize_with = "resolve_home_dir")]
+        key_file: PathBuf,
+    },
+}
+
+/// The sponsor that pays for the gas of the transactions of a contract client.
+pub(crate) enum GasSponsor {
+    /// A gas station that reserves gas coins, and co-signs and executes the transactions.
+    Station(GasStation),
+    /// A key of the sponsor, whose SUI coins are used as gas payment.
+    Key {
+        address: SuiAddress,
+        keypair: SuiKeyPair,
+        /// Held from the selection of the gas coins until the transaction is executed.
+        lock: SponsorKeyLock,
+    },
+}
+
+impl GasSponsor {
+    /// Creates the sponsor from its configuration, reading the key of the sponsor if necessary.
+    pub(crate) fn from_config(config: &GasSponsorConfig) -> anyhow::Result<Self> {
+        match config {
+            GasSponsorConfig::Endpoint { url, auth_token } => Ok(Self::Station(GasStation {
+                client: reqwest::Client::new(),
+                url: url.clone(),
+                auth_token: auth_token.clone(),
+            })),
+            GasSponsorConfig::Key { key_file } => {
+                let key = std::fs::read_to_string(key_file).with_context(|| {
+                    format!(
+                        "failed to read the key of the gas sponsor from {}",
+                        key_file.display()
+                    )
+                })?;
+                let keypair = SuiKeyPair::decode(key.trim())
+                    .map_err(|error| anyhow!("invalid key of the gas sponsor: {error}"))?;
+                let address = SuiAddress::from(&keypair.public());
+                Ok(Self::Key {
+                    address,
+                    keypair,
+                    lock: sponsor_key_lock(address),
+                })
+            }
+        }
+    }
+}
+
+/// The client of a gas station implementing the API of the Sui gas pool.
+pub(crate) struct GasStation {
+    client: reqwest::Client,
+    url: reqwest::Url,
+    auth_token: Option<String>,
+}
+
+/// The gas coins reserved for a transaction at the gas station.
+#[derive(Debug, Deserialize)]
+pub(crate) struct GasReservation {
+    /// The address of the sponsor, which owns the gas coins.
+    pub sponsor_address: SuiAddress,
+    /// The ID of the reservation, which is passed along with the transaction.
+    pub reservation_id: u64,
+    gas_coins: Vec<SuiObjectRef>,
+}
+
+impl GasReservation {
+    /// Returns the references of the reserved gas coins.
+    pub(crate) fn gas_coins(&self) -> Vec<ObjectRef> {
+        self.gas_coins
+            .iter()
+            .map(SuiObjectRef::to_object_ref)
+            .collect()
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct ReserveGasRequest {
+    gas_budget: u64,
+    reserve_duration_secs: u64,
+}
+
+#[derive(Debug, Deserialize)]
+struct ReserveGasResponse {
+    result: Option<GasReservation>,
+    error: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct ExecuteTxRequest {
+    reservation_id: u64,
+    tx_bytes: String,
+    user_sig: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct ExecuteTxResponse {
+    effects: Option<SuiTransactionBlockEffects>,
+    error: Option<String>,
+}
+
+impl GasStation {
+    /// Reserves gas coins with a total balance of at least `gas_budget`.
+    pub(crate) async fn reserve_gas(&self, gas_budget: u64) -> SuiClientResult<GasReservation> {
+        let response: ReserveGasResponse = self
+            .post(
+                "v1/reserve_gas",
+                &ReserveGasRequest {
+                    gas_budget,
+                    reserve_duration_secs: GAS_RESERVATION_DURATION.as_secs(),
+                },
+            )
+            .await?;
+        response
+            .result
+            .ok_or_else(|| gas_station_error(response.error))
+    }
+
+    /// Has the gas station co-sign and execute the `transaction` with the gas coins of the
+    /// reservation, and returns the digest of the executed transaction.
+    pub(crate) async fn execute_transaction(
+        &self,
+        reservation_id: u64,
+        transaction: &TransactionData,
+        sender_signature: &GenericSignature,
+    ) -> SuiClientResult<TransactionDigest> {
+        let tx_bytes = bcs::to_bytes(transaction).context("failed to serialize the transaction")?;
+        let response: ExecuteTxResponse = self
+            .post(
+                "v1/execute_tx",
+                &ExecuteTxRequest {
+                    reservation_id,
+                    tx_bytes: Base64::encode(tx_bytes),
+                    user_sig: Base64::encode(sender_signature.as_ref()),
+                },
+            )
+            .await?;
+        response
+            .effects
+            .map(|effects| *effects.transaction_digest())
+            .ok_or_else(|| gas_station_error(response.error))
+    }
+
+    async fn post<Req: Serialize, Res: for<'de> Deserialize<'de>>(
+        &self,
+        path: &str,
+        request: &Req,
+    ) -> SuiClientResult<Res> {
+        let url = self
+            .url
+            .join(path)
+            .context("invalid URL of the gas station")?;
+        let mut builder = self.client.post(url).json(request);
+        if let Some(auth_token) = &self.auth_token {
+            builder = builder.bearer_auth(auth_token);
+        }
+        let response = builder
+            .send()
+            .await
+            .context("failed to send the request to the gas station")?;
+        Ok(response
+            .json()
+            .await
+            .context("failed to parse the response of the gas station")?)
+    }
+}
+
+fn gas_station_error(error: Option<String>) -> SuiClientError {
+    SuiClientError::GasStationError(
+        error.unwrap_or_else(|| "the response of the gas station is empty".to_owned()),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_gas_sponsor_config() -> anyhow::Result<()> {
+        let config: GasSponsorConfig = serde_json::from_str(
+            r#"{"endpoint": {"url": "https://gas.example.com", "auth_token": "secret"}}"#,
+        )?;
+        assert_eq!(
+            config,
+            GasSponsorConfig::Endpoint {
+                url: "https://gas.example.com".parse()?,
+                auth_token: Some("secret".to_owned()),
+            }
+        );
+
+        let config: GasSponsorConfig =
+            serde_json::from_str(r#"{"key": {"key_file": "/etc/walrus/sponsor.key"}}"#)?;
+        assert_eq!(
+            config,
+            GasSponsorConfig::Key {
+                key_file: "/etc/walrus/sponsor.key".into(),
+            }
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn clients_share_the_lock_of_a_sponsor_key() {
+        let address = SuiAddress::random_for_testing_only();
+        assert!(Arc::ptr_eq(
+            &sponsor_key_lock(address),
+            &sponsor_key_lock(address)
+        ));
+        assert!(!Arc::ptr_eq(
+            &sponsor_key_lock(address),
+            &sponsor_key_lock(SuiAddress::random_for_testing_only())
+        ));
+    }
+}
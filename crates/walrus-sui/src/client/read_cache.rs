@@ -0,0 +1,143 @@
+// Copyright (c) Walrus Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Caching of the Walrus objects read by a [`SuiReadClient`][super::SuiReadClient].
+//!
+//! The system and staking objects, and the committees derived from them, change at most a few
+//! times per epoch. Caching them for a short time avoids reading them from chain, including the
+//! objects of all the committee members, every time the prices, the epoch, or a committee are
+//! needed.
+
+use std::{
+    collections::HashMap,
+    sync::{Mutex, MutexGuard},
+    time::Duration,
+};
+
+use tokio::time::Instant;
+use walrus_core::Epoch;
+
+use crate::types::{Committee, StakingObject, SystemObject};
+
+/// The cache of the Walrus objects that change at most a few times per epoch.
+///
+/// Cached values expire after the time to live of the cache; a time to live of zero disables the
+/// cache.
+#[derive(Debug)]
+pub(crate) struct ReadCache {
+    ttl: Duration,
+    system_object: Mutex<Option<(SystemObject, Instant)>>,
+    staking_object: Mutex<Option<(StakingObject, Instant)>>,
+    committees: Mutex<HashMap<Epoch, (Committee, Instant)>>,
+}
+
+impl ReadCache {
+    /// Creates a new empty cache whose values expire after `ttl`.
+    pub(crate) fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            system_object: Mutex::new(None),
+            staking_object: Mutex::new(None),
+            committees: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Returns the time to live of the cached values.
+    pub(crate) fn ttl(&self) -> Duration {
+        self.ttl
+    }
+
+    /// Returns the cached system object, if it has not expired.
+    pub(crate) fn system_object(&self) -> Option<SystemObject> {
+        self.fresh(lock(&self.system_object).as_ref())
+    }
+
+    /// Caches the system object.
+    pub(crate) fn set_system_object(&self, system_object: SystemObject) {
+        if self.is_enabled() {
+            *lock(&self.system_object) = Some((system_object, Instant::now()));
+        }
+    }
+
+    /// Returns the cached staking object, if it has not expired.
+    pub(crate) fn staking_object(&self) -> Option<StakingObject> {
+        self.fresh(lock(&self.staking_object).as_ref())
+    }
+
+    /// Caches the staking object.
+    pub(crate) fn set_staking_object(&self, staking_object: StakingObject) {
+        if self.is_enabled() {
+            *lock(&self.staking_object) = Some((staking_object, Instant::now()));
+        }
+    }
+
+    /// Returns the cached committee of the `epoch`, if it has not expired.
+    pub(crate) fn committee(&self, epoch: Epoch) -> Option<Committee> {
+        self.fresh(lock(&self.committees).get(&epoch))
+    }
+
+    /// Caches the committee of its epoch, and drops the expired committees.
+    pub(crate) fn set_committee(&self, committee: Committee) {
+        if self.is_enabled() {
+            let mut committees = lock(&self.committees);
+            committees.retain(|_, (_, cached_at)| cached_at.elapsed() < self.ttl);
+            committees.insert(committee.epoch, (committee, Instant::now()));
+        }
+    }
+
+    /// Drops all cached values, such that they are read from chain on the next access.
+    pub(crate) fn clear(&self) {
+        *lock(&self.system_object) = None;
+        *lock(&self.staking_object) = None;
+        lock(&self.committees).clear();
+    }
+
+    fn is_enabled(&self) -> bool {
+        !self.ttl.is_zero()
+    }
+
+    fn fresh<T: Clone>(&self, entry: Option<&(T, Instant)>) -> Option<T> {
+        entry
+            .filter(|(_, cached_at)| cached_at.elapsed() < self.ttl)
+            .map(|(value, _)| value.clone())
+    }
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().expect("mutex should not be poisoned")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::num::NonZeroU16;
+
+    use super::*;
+
+    fn committee(epoch: Epoch) -> Committee {
+        Committee::new(vec![], epoch, NonZeroU16::new(10).expect("nonzero"))
+            .expect("committees without members are valid")
+    }
+
+    #[test]
+    fn caches_committees_until_cleared() {
+        let cache = ReadCache::new(Duration::from_secs(60));
+        cache.set_committee(committee(0));
+        assert_eq!(cache.committee(0), Some(committee(0)));
+        assert_eq!(cache.committee(1), None);
+
+        cache.clear();
+        assert_eq!(cache.committee(0), None);
+    }
+
+    #[test]
+    fn does_not_return_expired_values() {
+        let cache = ReadCache::new(Duration::from_millis(1));
+        cache.set_committee(committee(0));
+        std::thread::sleep(Duration::from_millis(10));
+        assert_eq!(cache.committee(0), None);
+
+        let disabled = ReadCache::new(Duration::ZERO);
+        disabled.set_committee(committee(0));
+        assert_eq!(disabled.committee(0), None);
+    }
+}
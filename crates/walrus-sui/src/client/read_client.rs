@@ -25,11 +25,14 @@ use sui_sdk::{
         SuiObjectDataFilter,
         SuiObjectDataOptions,
         SuiObjectResponseQuery,
+        SuiTransactionBlockEffectsAPI as _,
+        SuiTransactionBlockResponseOptions,
     },
     types::base_types::ObjectID,
 };
 use sui_types::{
-    base_types::{ObjectRef, SequenceNumber, SuiAddress},
+    base_types::{ObjectRef, SequenceNumber, SuiAddress, TransactionDigest},
+    dynamic_field::derive_dynamic_field_id,
     event::EventID,
     object::Owner,
     transaction::ObjectArg,
@@ -44,6 +47,7 @@ use walrus_utils::backoff::ExponentialBackoffConfig;
 
 use super::{
     contract_config::ContractConfig,
+    read_cache::ReadCache,
     retry_client::{RetriableSuiClient, MULTI_GET_OBJ_LIMIT},
     SuiClientError,
     SuiClientResult,
@@ -52,33 +56,66 @@ use crate::{
     contracts::{self, AssociatedContractStruct, TypeOriginMap},
     types::{
         move_structs::{
+            self,
             Blob,
             BlobAttribute,
             BlobWithAttribute,
             EpochState,
             EventBlob,
             NodeMetadata,
+            PoolExchangeRate,
+            SharedBlob,
             StakingInnerV1,
             StakingObjectForDeserialization,
             StakingPool,
+            StorageResource,
+            SuiDynamicField,
             SystemObjectForDeserialization,
             SystemStateInnerV1,
             SystemStateInnerV1Enum,
             SystemStateInnerV1Testnet,
         },
         BlobEvent,
+        BlobLifecycleEvent,
         Committee,
         ContractEvent,
+        EpochChangeStart,
+        StakedWal,
         StakingObject,
         StorageNode,
         StorageNodeCap,
         SystemObject,
     },
-    utils::{get_sui_object_from_object_response, handle_pagination},
+    utils::{get_sui_object_from_object_response, handle_pagination, price_for_encoded_length},
 };
 
 const EVENT_MODULE: &str = "events";
 
+// Keep in sync with `VERSION` in `contracts/walrus/sources/system.move` and
+// `contracts/walrus/sources/staking.move`.
+/// The highest version of the Walrus system and staking objects supported by this binary.
+pub const MAX_SUPPORTED_CONTRACT_VERSION: u64 = 1;
+
+/// Returns an error if the `version` of the Walrus `object` on chain is newer than the versions
+/// supported by this binary.
+///
+/// Newer versions are introduced by contract upgrades, after which the calls of this binary would
+/// fail with Move aborts.
+pub(crate) fn ensure_supported_contract_version(
+    object: &'static str,
+    version: u64,
+) -> SuiClientResult<()> {
+    ensure!(
+        version <= MAX_SUPPORTED_CONTRACT_VERSION,
+        SuiClientError::UnsupportedContractVersion {
+            object,
+            version,
+            max_supported_version: MAX_SUPPORTED_CONTRACT_VERSION,
+        }
+    );
+    Ok(())
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 /// The type of coin.
 pub enum CoinType {
@@ -118,6 +155,109 @@ pub struct FixedSystemParameters {
     pub epoch_zero_end: DateTime<Utc>,
 }
 
+/// The current epoch, along with its start and the estimated time at which it ends.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CurrentEpochState {
+    /// The current epoch.
+    pub epoch: Epoch,
+    /// The state of the current epoch.
+    pub state: EpochState,
+    /// The time at which the current epoch started.
+    ///
+    /// This is `None` while the epoch change to the current epoch is in progress.
+    pub start: Option<DateTime<Utc>>,
+    /// The earliest time at which the change to the next epoch can start.
+    ///
+    /// This is `None` while the epoch change to the current epoch is in progress. The actual epoch
+    /// change may happen later, as it needs to be initiated on chain.
+    pub estimated_end: Option<DateTime<Utc>>,
+    /// The duration of an epoch for epochs 1 onwards.
+    pub epoch_duration: Duration,
+}
+
+impl CurrentEpochState {
+    /// Creates the state of the current `epoch` from its `state` and the fixed system parameters.
+    pub fn new(epoch: Epoch, state: EpochState, fixed_params: &FixedSystemParameters) -> Self {
+        let start = match state {
+            EpochState::EpochChangeDone(start) | EpochState::NextParamsSelected(start) => {
+                Some(start)
+            }
+            EpochState::EpochChangeSync(_) => None,
+        };
+        let estimated_end = if epoch == 0 {
+            Some(fixed_params.epoch_zero_end)
+        } else {
+            start.and_then(|start| {
+                chrono::Duration::from_std(fixed_params.epoch_duration)
+                    .ok()
+                    .and_then(|duration| start.checked_add_signed(duration))
+            })
+        };
+        Self {
+            epoch,
+            state,
+            start,
+            estimated_end,
+            epoch_duration: fixed_params.epoch_duration,
+        }
+    }
+
+    /// Returns the time remaining until the estimated end of the epoch, if known.
+    ///
+    /// Returns a zero duration if the estimated end has already passed.
+    pub fn time_until_estimated_end(&self) -> Option<Duration> {
+        self.estimated_end
+            .map(|end| (end - Utc::now()).to_std().unwrap_or_default())
+    }
+}
+
+/// The storage and write prices of an epoch.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EpochPrices {
+    /// The epoch.
+    pub epoch: Epoch,
+    /// The price for one unit of storage per epoch.
+    pub storage_price_per_unit_size: u64,
+    /// The price to write one unit of storage.
+    pub write_price_per_unit_size: u64,
+}
+
+/// The funding of a shared blob, which anyone can extend with its funds.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SharedBlobFunding {
+    /// The shared blob.
+    pub shared_blob: SharedBlob,
+    /// The current epoch.
+    pub current_epoch: Epoch,
+    /// The cost in FROST of extending the lifetime of the blob by one epoch, at the current storage
+    /// price.
+    pub cost_per_epoch: u64,
+}
+
+impl SharedBlobFunding {
+    /// Returns the number of epochs by which the lifetime of the blob can be extended with its
+    /// funds, at the current storage price.
+    pub fn funded_epochs(&self) -> u64 {
+        self.shared_blob
+            .funds
+            .checked_div(self.cost_per_epoch)
+            .unwrap_or(u64::MAX)
+    }
+
+    /// Returns the number of epochs for which the blob is stored after the current one, including
+    /// the epochs that are funded but not yet paid for by extending the blob.
+    pub fn remaining_epochs(&self) -> u64 {
+        u64::from(
+            self.shared_blob
+                .blob
+                .storage
+                .end_epoch
+                .saturating_sub(self.current_epoch),
+        )
+        .saturating_add(self.funded_epochs())
+    }
+}
+
 /// Trait to read system state information and events from chain.
 pub trait ReadClient: Send + Sync {
     /// Returns the price for one unit of storage per epoch.
@@ -131,6 +271,11 @@ pub trait ReadClient: Send + Sync {
         &self,
     ) -> impl Future<Output = SuiClientResult<(u64, u64)>> + Send;
 
+    /// Returns the rate in basis points at which the storage purchased by buyers is subsidized.
+    ///
+    /// The rate is 0 if no subsidies object is configured or if its subsidy pool is empty.
+    fn buyer_subsidy_rate(&self) -> impl Future<Output = SuiClientResult<u16>> + Send;
+
     /// Returns a stream of new blob events.
     ///
     /// The `polling_interval` defines how often the connected full node is polled for events.
@@ -234,6 +379,10 @@ pub trait ReadClient: Send + Sync {
 
     /// Returns the version of the system object.
     fn system_object_version(&self) -> impl Future<Output = SuiClientResult<u64>> + Send;
+
+    /// Drops the cached system and staking objects and committees, if any, such that they are read
+    /// from chain on the next access.
+    fn clear_cache(&self);
 }
 
 /// The mutability of a shared object.
@@ -295,13 +444,23 @@ pub struct SuiReadClient {
     staking_obj_initial_version: OnceCell<SequenceNumber>,
     subsidies: Arc<RwLock<Option<Subsidies>>>,
     wal_type: String,
+    cache: Arc<ReadCache>,
+    /// The epoch in which the versions of the Walrus objects were last checked successfully.
+    contract_versions_checked_epoch: Arc<RwLock<Option<Epoch>>>,
 }
 
 const MAX_POLLING_INTERVAL: Duration = Duration::from_secs(5);
+const MAX_SUBSCRIPTION_BACKOFF: Duration = Duration::from_secs(60);
 const EVENT_CHANNEL_CAPACITY: usize = 1024;
 
 impl SuiReadClient {
+    /// The interval at which the epoch is polled while waiting for an epoch change.
+    pub const EPOCH_POLLING_INTERVAL: Duration = Duration::from_secs(10);
+
     /// Constructor for `SuiReadClient`.
+    ///
+    /// Returns an error if the versions of the Walrus system or staking objects on chain are not
+    /// supported by this binary.
     pub async fn new(
         sui_client: RetriableSuiClient,
         contract_config: &ContractConfig,
@@ -309,6 +468,10 @@ impl SuiReadClient {
         let walrus_package_id = sui_client
             .get_system_package_id_from_system_object(contract_config.system_object)
             .await?;
+        let staking_object: StakingObjectForDeserialization = sui_client
+            .get_sui_object(contract_config.staking_object)
+            .await?;
+        ensure_supported_contract_version("staking", staking_object.version)?;
         let type_origin_map = sui_client
             .type_origin_map_for_package(walrus_package_id)
             .await?;
@@ -335,9 +498,26 @@ impl SuiReadClient {
             staking_obj_initial_version: OnceCell::new(),
             subsidies: Arc::new(RwLock::new(subsidies)),
             wal_type,
+            cache: Arc::new(ReadCache::new(Duration::ZERO)),
+            contract_versions_checked_epoch: Arc::new(RwLock::new(None)),
         })
     }
 
+    /// Caches the system and staking objects and the committees for the duration `ttl`.
+    ///
+    /// These change at most a few times per epoch, so caching them saves RPC calls if the prices,
+    /// the epoch, or the committees are read frequently. The cache can be cleared with
+    /// [`ReadClient::clear_cache`]. By default, nothing is cached.
+    pub fn with_cache_ttl(mut self, ttl: Duration) -> Self {
+        self.cache = Arc::new(ReadCache::new(ttl));
+        self
+    }
+
+    /// Returns the duration for which the system and staking objects and the committees are cached.
+    pub fn cache_ttl(&self) -> Duration {
+        self.cache.ttl()
+    }
+
     /// Constructs a new `SuiReadClient` around a [`RetriableSuiClient`] constructed for the
     /// provided fullnode's RPC address.
     pub async fn new_for_rpc<S: AsRef<str>>(
@@ -349,6 +529,173 @@ impl SuiReadClient {
         Self::new(client, contract_config).await
     }
 
+    /// Constructs a new `SuiReadClient` around a [`RetriableSuiClient`] that fails over between
+    /// the provided fullnodes' RPC addresses.
+    ///
+    /// Unlike the [`SuiContractClient`][crate::client::SuiContractClient], the read client does not
+    /// require a Sui wallet.
+    pub async fn new_for_rpc_urls<S: AsRef<str>>(
+        rpc_addresses: &[S],
+        contract_config: &ContractConfig,
+        backoff_config: ExponentialBackoffConfig,
+    ) -> SuiClientResult<Self> {
+        let client = RetriableSuiClient::new_for_rpc_urls(rpc_addresses, backoff_config).await?;
+        Self::new(client, contract_config).await
+    }
+
+    /// Returns the current epoch, along with its start and estimated end.
+    pub async fn current_epoch_state(&self) -> SuiClientResult<CurrentEpochState> {
+        let staking_object = self.get_staking_object().await?.inner;
+        let fixed_params = self.fixed_system_parameters().await?;
+        Ok(CurrentEpochState::new(
+            staking_object.epoch,
+            staking_object.epoch_state,
+            &fixed_params,
+        ))
+    }
+
+    /// Waits until the current epoch is at least `epoch`, and returns the state of the then
+    /// current epoch.
+    ///
+    /// The client sleeps until the estimated end of the current epoch, and then polls the staking
+    /// object every [`Self::EPOCH_POLLING_INTERVAL`] until the epoch changed. The cached objects
+    /// are dropped before every read, so the epoch is always read from chain.
+    pub async fn wait_for_epoch(&self, epoch: Epoch) -> SuiClientResult<CurrentEpochState> {
+        loop {
+            self.clear_cache();
+            let state = self.current_epoch_state().await?;
+            if state.epoch >= epoch {
+                return Ok(state);
+            }
+            let sleep_duration = state
+                .time_until_estimated_end()
+                .unwrap_or_default()
+                .max(Self::EPOCH_POLLING_INTERVAL);
+            tracing::debug!(
+                current_epoch = state.epoch,
+                target_epoch = epoch,
+                ?sleep_duration,
+                "waiting for the epoch change"
+            );
+            tokio::time::sleep(sleep_duration).await;
+        }
+    }
+
+    /// Returns the storage and write prices of up to `max_epochs` of the most recent epochs, oldest
+    /// first.
+    ///
+    /// The prices of an epoch are set when the epoch starts. They are read from the versions of the
+    /// system state written by the transactions that emitted the `EpochChangeStart` events, so the
+    /// history is limited to the events and object versions retained by the full node.
+    pub async fn price_history(&self, max_epochs: usize) -> SuiClientResult<Vec<EpochPrices>> {
+        let system_object = self.get_system_object().await?;
+        let system_state_id = derive_dynamic_field_id(
+            self.system_object_id,
+            &TypeTag::U64,
+            &bcs::to_bytes(&system_object.version).expect("u64 is serializable"),
+        )
+        .map_err(|error| SuiClientError::Internal(error.into()))?;
+        let event_type = contracts::events::EpochChangeStart
+            .to_move_struct_tag_with_type_map(&self.type_origin_map(), &[])?;
+
+        let mut history = Vec::with_capacity(max_epochs);
+        let mut cursor = None;
+        while history.len() < max_epochs {
+            let page = self
+                .sui_client
+                .query_events(
+                    EventFilter::MoveEventType(event_type.clone()),
+                    cursor,
+                    Some(max_epochs - history.len()),
+                    true,
+                )
+                .await?;
+            for event in page.data {
+                let epoch_change = EpochChangeStart::try_from(event)
+                    .context("failed to parse the epoch change event")?;
+                let Some(version) = self
+                    .system_state_version_written_by(
+                        epoch_change.event_id.tx_digest,
+                        system_state_id,
+                    )
+                    .await?
+                else {
+                    tracing::warn!(
+                        epoch = epoch_change.epoch,
+                        "the epoch change did not write the system state; skipping the epoch"
+                    );
+                    continue;
+                };
+                let (storage_price_per_unit_size, write_price_per_unit_size) =
+                    self.prices_at_version(system_state_id, version).await?;
+                history.push(EpochPrices {
+                    epoch: epoch_change.epoch,
+                    storage_price_per_unit_size,
+                    write_price_per_unit_size,
+                });
+            }
+            if !page.has_next_page {
+                break;
+            }
+            cursor = page.next_cursor;
+        }
+        history.reverse();
+        Ok(history)
+    }
+
+    async fn system_state_version_written_by(
+        &self,
+        tx_digest: TransactionDigest,
+        system_state_id: ObjectID,
+    ) -> SuiClientResult<Option<SequenceNumber>> {
+        let response = self
+            .sui_client
+            .get_transaction_with_options(
+                tx_digest,
+                SuiTransactionBlockResponseOptions::new().with_effects(),
+            )
+            .await?;
+        Ok(response.effects.and_then(|effects| {
+            effects
+                .mutated()
+                .iter()
+                .find(|object| object.object_id() == system_state_id)
+                .map(|object| object.version())
+        }))
+    }
+
+    /// Returns the storage and write prices at the given version of the system state.
+    async fn prices_at_version(
+        &self,
+        system_state_id: ObjectID,
+        version: SequenceNumber,
+    ) -> SuiClientResult<(u64, u64)> {
+        if let Ok(field) = self
+            .sui_client
+            .get_past_sui_object::<SuiDynamicField<u64, SystemStateInnerV1>>(
+                system_state_id,
+                version,
+            )
+            .await
+        {
+            return Ok((
+                field.value.storage_price_per_unit_size,
+                field.value.write_price_per_unit_size,
+            ));
+        }
+        let field = self
+            .sui_client
+            .get_past_sui_object::<SuiDynamicField<u64, SystemStateInnerV1Testnet>>(
+                system_state_id,
+                version,
+            )
+            .await?;
+        Ok((
+            field.value.storage_price_per_unit_size,
+            field.value.write_price_per_unit_size,
+        ))
+    }
+
     /// Gets the [`RetriableSuiClient`] from the associated read client.
     pub fn sui_client(&self) -> &RetriableSuiClient {
         &self.sui_client
@@ -503,6 +850,54 @@ impl SuiReadClient {
         self.sui_client.get_sui_object(node_id).await
     }
 
+    /// Returns the rewards in FROST earned by the `staked_wal` so far.
+    ///
+    /// Mirrors `calculate_rewards` of the staking pool: the rewards are computed from the exchange
+    /// rates of the pool at the activation epoch and at the withdraw epoch of the staked WAL, or
+    /// at the current epoch if no withdrawal has been requested.
+    pub async fn get_staking_rewards(&self, staked_wal: &StakedWal) -> SuiClientResult<u64> {
+        let end_epoch = match staked_wal.withdraw_epoch() {
+            Some(withdraw_epoch) => withdraw_epoch,
+            None => self.current_epoch().await?,
+        };
+        if end_epoch <= staked_wal.activation_epoch {
+            return Ok(0);
+        }
+        let pool = self.get_staking_pool(staked_wal.node_id).await?;
+        let shares = self
+            .pool_exchange_rate_at_epoch(&pool, staked_wal.activation_epoch)
+            .await?
+            .convert_to_share_amount(staked_wal.principal);
+        let wal_amount = self
+            .pool_exchange_rate_at_epoch(&pool, end_epoch)
+            .await?
+            .convert_to_wal_amount(shares);
+        Ok(wal_amount.saturating_sub(staked_wal.principal))
+    }
+
+    /// Returns the exchange rate of the staking pool at the `epoch`, which is the latest exchange
+    /// rate recorded at or before the epoch.
+    async fn pool_exchange_rate_at_epoch(
+        &self,
+        pool: &StakingPool,
+        epoch: Epoch,
+    ) -> SuiClientResult<PoolExchangeRate> {
+        for epoch in (pool.activation_epoch()..=epoch).rev() {
+            if let Ok(exchange_rate) = self
+                .sui_client
+                .get_dynamic_field::<Epoch, PoolExchangeRate>(
+                    pool.exchange_rates(),
+                    TypeTag::U32,
+                    epoch,
+                )
+                .await
+            {
+                return Ok(exchange_rate);
+            }
+        }
+        Ok(PoolExchangeRate::Flat)
+    }
+
     fn walrus_package_id(&self) -> RwLockReadGuard<ObjectID> {
         self.walrus_package_id
             .read()
@@ -551,6 +946,92 @@ impl SuiReadClient {
             .expect("balances should fit into a u64"))
     }
 
+    /// Subscribes to the events marking the lifecycle of blobs, i.e., their registration,
+    /// certification, and blob IDs being marked as invalid.
+    ///
+    /// If a `cursor` is provided, the stream only contains events emitted after the event with the
+    /// provided [`EventID`]. The connected full node is polled for new events every
+    /// `polling_interval`.
+    ///
+    /// Unlike [`ReadClient::event_stream`], the stream does not end on errors of the full node: the
+    /// subscription is re-established after a backoff and resumes after the last event received.
+    pub fn subscribe_events(
+        &self,
+        polling_interval: Duration,
+        cursor: Option<EventID>,
+    ) -> SuiClientResult<impl Stream<Item = BlobLifecycleEvent>> {
+        let (tx_event, rx_event) = mpsc::channel::<BlobLifecycleEvent>(EVENT_CHANNEL_CAPACITY);
+        let event_api = self.sui_client.event_api().clone();
+        let walrus_package_id = self.walrus_package_id.clone();
+        let module = Identifier::new(EVENT_MODULE)?;
+
+        tokio::spawn(async move {
+            let mut cursor = cursor;
+            let mut reconnect_backoff = polling_interval;
+            loop {
+                // Read the package ID on every connection, as it changes with package upgrades.
+                let event_filter = EventFilter::MoveEventModule {
+                    package: *walrus_package_id
+                        .read()
+                        .expect("lock should not be poisoned"),
+                    module: module.clone(),
+                };
+                let (tx_contract_event, mut rx_contract_event) =
+                    mpsc::channel::<ContractEvent>(EVENT_CHANNEL_CAPACITY);
+                let poller = tokio::spawn(poll_for_events(
+                    tx_contract_event,
+                    polling_interval,
+                    event_api.clone(),
+                    event_filter,
+                    cursor,
+                ));
+
+                loop {
+                    let event = tokio::select! {
+                        event = rx_contract_event.recv() => event,
+                        _ = tx_event.closed() => {
+                            poller.abort();
+                            return;
+                        }
+                    };
+                    let Some(event) = event else {
+                        break;
+                    };
+                    cursor = Some(event.event_id());
+                    reconnect_backoff = polling_interval;
+                    if let Ok(event) = BlobLifecycleEvent::try_from(event) {
+                        if tx_event.send(event).await.is_err() {
+                            poller.abort();
+                            return;
+                        }
+                    }
+                }
+
+                match poller.await {
+                    Ok(Err(error)) => tracing::warn!(
+                        ?error,
+                        event_cursor = ?cursor,
+                        backoff = ?reconnect_backoff,
+                        "the event subscription failed; reconnecting after backoff"
+                    ),
+                    Err(error) => tracing::warn!(
+                        ?error,
+                        event_cursor = ?cursor,
+                        backoff = ?reconnect_backoff,
+                        "the event subscription panicked; reconnecting after backoff"
+                    ),
+                    Ok(Ok(())) => (),
+                }
+                tokio::time::sleep(reconnect_backoff).await;
+                reconnect_backoff = reconnect_backoff
+                    .saturating_mul(2)
+                    .min(MAX_SUBSCRIPTION_BACKOFF)
+                    .max(polling_interval);
+            }
+        });
+        Ok(ReceiverStream::new(rx_event))
+    }
+
     /// Returns a vector of coins of provided `coin_type` whose total balance is at least `balance`.
     ///
     /// Returns a [`SuiClientError::NoCompatibleGasCoins`] or
@@ -600,6 +1081,20 @@ impl SuiReadClient {
         }
     }
 
+    /// Returns the [`StorageResource`] objects owned by `owner`.
+    ///
+    /// Storage resources that are used by a blob are wrapped in the blob object, so the returned
+    /// resources are the ones that are not used yet.
+    pub async fn owned_storage_resources(
+        &self,
+        owner: SuiAddress,
+    ) -> SuiClientResult<Vec<StorageResource>> {
+        Ok(self
+            .get_owned_objects::<StorageResource>(owner, &[])
+            .await?
+            .collect())
+    }
+
     /// Get all the owned objects of the specified type for the specified owner.
     ///
     /// If some of the returned objects cannot be converted to the expected type, they are ignored.
@@ -691,7 +1186,12 @@ impl SuiReadClient {
     }
 
     /// Returns the system object.
+    ///
+    /// The system object is cached if the client is configured with a cache.
     pub async fn get_system_object(&self) -> SuiClientResult<SystemObject> {
+        if let Some(system_object) = self.cache.system_object() {
+            return Ok(system_object);
+        }
         let SystemObjectForDeserialization {
             id,
             version,
@@ -723,17 +1223,24 @@ impl SuiReadClient {
                 .await?;
             SystemStateInnerV1Enum::V1Testnet(inner)
         };
-        Ok(SystemObject {
+        let system_object = SystemObject {
             id,
             version,
             package_id,
             new_package_id,
             inner,
-        })
+        };
+        self.cache.set_system_object(system_object.clone());
+        Ok(system_object)
     }
 
     /// Returns the staking object.
+    ///
+    /// The staking object is cached if the client is configured with a cache.
     pub async fn get_staking_object(&self) -> SuiClientResult<StakingObject> {
+        if let Some(staking_object) = self.cache.staking_object() {
+            return Ok(staking_object);
+        }
         let StakingObjectForDeserialization {
             id,
             version,
@@ -758,9 +1265,40 @@ impl SuiReadClient {
             new_package_id,
             inner,
         };
+        self.check_contract_versions_once_per_epoch(&staking_object)
+            .await?;
+        self.cache.set_staking_object(staking_object.clone());
         Ok(staking_object)
     }
 
+    /// Checks the versions of the Walrus system and staking objects against the versions
+    /// supported by this binary, unless they were already checked in the epoch of the
+    /// `staking_object`.
+    ///
+    /// The versions are checked when the client is created, and this re-checks them after epoch
+    /// changes, at which contract upgrades are usually migrated.
+    async fn check_contract_versions_once_per_epoch(
+        &self,
+        staking_object: &StakingObject,
+    ) -> SuiClientResult<()> {
+        let epoch = staking_object.epoch();
+        let checked_epoch = *self
+            .contract_versions_checked_epoch
+            .read()
+            .expect("lock should not be poisoned");
+        if checked_epoch == Some(epoch) {
+            return Ok(());
+        }
+        ensure_supported_contract_version("staking", staking_object.version)?;
+        let system_object = self.system_object_for_deserialization().await?;
+        ensure_supported_contract_version("system", system_object.version)?;
+        *self
+            .contract_versions_checked_epoch
+            .write()
+            .expect("lock should not be poisoned") = Some(epoch);
+        Ok(())
+    }
+
     /// Sets a subsidies object to be used by the client.
     pub async fn set_subsidies_object(&self, subsidies_object_id: ObjectID) -> SuiClientResult<()> {
         let subsidies_package_id = self
@@ -791,6 +1329,9 @@ impl SuiReadClient {
         n_shards: NonZeroU16,
         shard_assignment: &[(ObjectID, Vec<u16>)],
     ) -> SuiClientResult<Committee> {
+        if let Some(committee) = self.cache.committee(epoch) {
+            return Ok(committee);
+        }
         let mut node_object_responses = vec![];
         for obj_id_batch in shard_assignment.chunks(MULTI_GET_OBJ_LIMIT) {
             node_object_responses.extend(
@@ -820,7 +1361,10 @@ impl SuiReadClient {
                 Ok::<StorageNode, anyhow::Error>(storage_node)
             })
             .collect::<Result<Vec<_>>>()?;
-        Committee::new(nodes, epoch, n_shards).map_err(|err| SuiClientError::Internal(err.into()))
+        let committee = Committee::new(nodes, epoch, n_shards)
+            .map_err(|err| SuiClientError::Internal(err.into()))?;
+        self.cache.set_committee(committee.clone());
+        Ok(committee)
     }
 
     /// Queries the full note and gets the requested committee from the staking object.
@@ -853,6 +1397,27 @@ impl SuiReadClient {
         self.sui_client.backoff_config()
     }
 
+    /// Returns the shared blob with the given object ID, along with the cost of extending it.
+    ///
+    /// The [`SharedBlobFunding`] indicates for how many epochs the funds of the shared blob can
+    /// extend its lifetime at the current storage price.
+    pub async fn get_shared_blob_funding(
+        &self,
+        shared_blob_obj_id: ObjectID,
+    ) -> SuiClientResult<SharedBlobFunding> {
+        let shared_blob: SharedBlob = self.sui_client.get_sui_object(shared_blob_obj_id).await?;
+        let storage_price = self.storage_price_per_unit_size().await?;
+        Ok(SharedBlobFunding {
+            cost_per_epoch: price_for_encoded_length(
+                shared_blob.blob.storage.storage_size,
+                storage_price,
+                1,
+            ),
+            current_epoch: self.current_epoch().await?,
+            shared_blob,
+        })
+    }
+
     /// Returns the node metadata for the given metadata ID.
     pub async fn get_node_metadata(&self, metadata_id: ObjectID) -> SuiClientResult<NodeMetadata> {
         let type_map = self.type_origin_map().clone();
@@ -898,6 +1463,18 @@ impl ReadClient for SuiReadClient {
         ))
     }
 
+    async fn buyer_subsidy_rate(&self) -> SuiClientResult<u16> {
+        let Some(subsidies_object_id) = self.get_subsidies_object_id() else {
+            return Ok(0);
+        };
+        let subsidies: move_structs::Subsidies =
+            self.sui_client.get_sui_object(subsidies_object_id).await?;
+        if subsidies.subsidy_pool == 0 {
+            return Ok(0);
+        }
+        Ok(subsidies.buyer_subsidy_rate)
+    }
+
     async fn event_stream(
         &self,
         polling_interval: Duration,
@@ -1097,7 +1674,10 @@ impl ReadClient for SuiReadClient {
             .sui_client
             .get_system_package_id_from_system_object(self.system_object_id)
             .await?;
-        self.refresh_package_id_with_id(walrus_package_id).await
+        self.refresh_package_id_with_id(walrus_package_id).await?;
+        // The cached objects may have been modified by the upgrade.
+        self.cache.clear();
+        Ok(())
     }
 
     async fn refresh_subsidies_package_id(&self) -> SuiClientResult<()> {
@@ -1127,6 +1707,11 @@ impl ReadClient for SuiReadClient {
     async fn system_object_version(&self) -> SuiClientResult<u64> {
         Ok(self.system_object_for_deserialization().await?.version)
     }
+
+    fn clear_cache(&self) {
+        tracing::debug!("clearing the cached system and staking objects and committees");
+        self.cache.clear();
+    }
 }
 
 impl fmt::Debug for SuiReadClient {
@@ -1236,3 +1821,53 @@ where
     tracing::debug!("channel was closed by receiver");
     return Ok(());
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rejects_newer_contract_versions() {
+        assert!(
+            ensure_supported_contract_version("system", MAX_SUPPORTED_CONTRACT_VERSION).is_ok()
+        );
+        let error =
+            ensure_supported_contract_version("staking", MAX_SUPPORTED_CONTRACT_VERSION + 1)
+                .expect_err("newer versions are not supported");
+        assert!(matches!(
+            error,
+            SuiClientError::UnsupportedContractVersion {
+                object: "staking",
+                ..
+            }
+        ));
+        assert!(error.to_string().contains("please upgrade walrus"));
+    }
+
+    #[test]
+    fn estimates_the_end_of_the_current_epoch() {
+        let epoch_zero_end = DateTime::from_timestamp(1_000_000, 0).expect("valid timestamp");
+        let fixed_params = FixedSystemParameters {
+            n_shards: NonZeroU16::new(10).expect("nonzero"),
+            max_epochs_ahead: 53,
+            epoch_duration: Duration::from_secs(3600),
+            epoch_zero_end,
+        };
+
+        let genesis = CurrentEpochState::new(0, EpochState::EpochChangeSync(0), &fixed_params);
+        assert_eq!(genesis.start, None);
+        assert_eq!(genesis.estimated_end, Some(epoch_zero_end));
+
+        let start = epoch_zero_end + chrono::Duration::hours(5);
+        let state = CurrentEpochState::new(5, EpochState::EpochChangeDone(start), &fixed_params);
+        assert_eq!(state.start, Some(start));
+        assert_eq!(
+            state.estimated_end,
+            Some(start + chrono::Duration::hours(1))
+        );
+        assert_eq!(state.time_until_estimated_end(), Some(Duration::ZERO));
+
+        let syncing = CurrentEpochState::new(5, EpochState::EpochChangeSync(3), &fixed_params);
+        assert_eq!(syncing.estimated_end, None);
+    }
+}
@@ -6,14 +6,18 @@
 //! Wraps the [`SuiClient`] to introduce retries.
 
 use std::{
-    collections::BTreeMap,
+    collections::{BTreeMap, HashSet},
     fmt::Debug,
     future::Future,
     str::FromStr,
-    sync::{atomic::AtomicUsize, Arc},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
     time::Duration,
 };
 
+use anyhow::Context as _;
 use futures::{
     future::{self},
     stream,
@@ -37,6 +41,8 @@ use sui_sdk::{
         Balance,
         Coin,
         DryRunTransactionBlockResponse,
+        EventFilter,
+        EventPage,
         ObjectsPage,
         SuiCommittee,
         SuiMoveNormalizedModule,
@@ -57,8 +63,9 @@ use sui_storage::blob::Blob;
 #[cfg(msim)]
 use sui_types::transaction::TransactionDataAPI;
 use sui_types::{
-    base_types::{ObjectID, SuiAddress, TransactionDigest},
+    base_types::{ObjectID, SequenceNumber, SuiAddress, TransactionDigest},
     dynamic_field::derive_dynamic_field_id,
+    event::EventID,
     full_checkpoint_content::CheckpointData,
     messages_checkpoint::CertifiedCheckpointSummary,
     object::Object,
@@ -74,7 +81,12 @@ use url::ParseError;
 use walrus_core::ensure;
 use walrus_utils::backoff::{BackoffStrategy, ExponentialBackoff, ExponentialBackoffConfig};
 
-use super::{rpc_config::RpcFallbackConfig, SuiClientError, SuiClientResult};
+use super::{
+    read_client::ensure_supported_contract_version,
+    rpc_config::RpcFallbackConfig,
+    SuiClientError,
+    SuiClientResult,
+};
 use crate::{
     client::SuiClientMetricSet,
     contracts::{self, AssociatedContractStruct, TypeOriginMap},
@@ -102,6 +114,9 @@ const MAX_GAS_BUDGET: u64 = 50_000_000_000;
 /// The maximum number of objects to get in a single RPC call.
 pub(crate) const MULTI_GET_OBJ_LIMIT: usize = 50;
 
+/// The latency of an RPC call above which the client fails over to the next RPC endpoint.
+const RPC_LATENCY_FAILOVER_THRESHOLD: Duration = Duration::from_secs(10);
+
 /// Trait to test if an error is produced by a temporary RPC failure and can be retried.
 pub trait RetriableRpcError: Debug {
     /// Returns `true` if the error is a retriable network error.
@@ -349,6 +364,80 @@ impl<T> FailoverWrapper<T> {
         &self.inner[index % self.client_count()].1
     }
 
+    fn current_index(&self) -> usize {
+        self.current_index.load(Ordering::Relaxed)
+    }
+
+    /// Returns the current instance.
+    fn current(&self) -> &T {
+        &self.inner[self.current_index() % self.client_count()].0
+    }
+
+    /// Records the outcome of a call issued to the instance at `index`, and fails over to the next
+    /// instance if the call failed or took longer than [`RPC_LATENCY_FAILOVER_THRESHOLD`].
+    fn record_call(&self, index: usize, failed: bool, latency: Duration) {
+        if self.client_count() < 2 || !failed && latency <= RPC_LATENCY_FAILOVER_THRESHOLD {
+            return;
+        }
+        let next_index = (index + 1) % self.client_count();
+        // Only fail over once if several concurrent calls to the same instance fail.
+        if self
+            .current_index
+            .compare_exchange(index, next_index, Ordering::Relaxed, Ordering::Relaxed)
+            .is_ok()
+        {
+            tracing::warn!(
+                current_client = self.get_name(index),
+                next_client = self.get_name(next_index),
+                failed,
+                ?latency,
+                "failing over to the next client"
+            );
+        }
+    }
+
+    /// Retries the given function with the backoff `strategy` while it returns retriable errors.
+    ///
+    /// The function issues its call to the [current][Self::current] instance. If a call fails with
+    /// a retriable error or has a high latency, the next instance becomes the current one, so that
+    /// the retries and subsequent calls use it.
+    async fn retry_with_failover<S, F, R, E, Fut>(
+        &self,
+        strategy: S,
+        mut func: F,
+        metrics: Option<Arc<SuiClientMetricSet>>,
+        method: &str,
+    ) -> Result<R, E>
+    where
+        S: BackoffStrategy,
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<R, E>>,
+        E: RetriableRpcError + ToErrorType,
+    {
+        retry_rpc_errors(
+            strategy,
+            || {
+                let index = self.current_index();
+                let start = Instant::now();
+                let call = func();
+                async move {
+                    let result = call.await;
+                    self.record_call(
+                        index,
+                        result
+                            .as_ref()
+                            .is_err_and(|error| error.is_retriable_rpc_error()),
+                        start.elapsed(),
+                    );
+                    result
+                }
+            },
+            metrics,
+            method,
+        )
+        .await
+    }
+
     /// Executes an operation on the current inner instance, falling back to the next one
     /// if it fails.
     async fn with_failover<F, Fut, R>(&self, operation: F) -> Result<R, RetriableClientError>
@@ -416,10 +505,13 @@ impl<T> FailoverWrapper<T> {
 /// This retriable client wraps functions from the [`CoinReadApi`][sui_sdk::apis::CoinReadApi] and
 /// the [`ReadApi`][sui_sdk::apis::ReadApi] of the [`SuiClient`], and
 /// additionally provides some convenience methods.
+///
+/// If created for multiple RPC endpoints, the client fails over to the next endpoint when calls
+/// fail or have a high latency.
 #[allow(missing_debug_implementations)]
 #[derive(Clone)]
 pub struct RetriableSuiClient {
-    sui_client: SuiClient,
+    sui_clients: FailoverWrapper<SuiClient>,
     backoff_config: ExponentialBackoffConfig,
     metrics: Option<Arc<SuiClientMetricSet>>,
 }
@@ -432,8 +524,16 @@ impl RetriableSuiClient {
     /// make a call to the RPC server in [`WalletContext::get_client`], which may fail without any
     /// retries. `new_from_wallet` will handle this case correctly.
     pub fn new(sui_client: SuiClient, backoff_config: ExponentialBackoffConfig) -> Self {
+        Self::new_with_endpoints(vec![(sui_client, "default".to_owned())], backoff_config)
+    }
+
+    fn new_with_endpoints(
+        clients: Vec<(SuiClient, String)>,
+        backoff_config: ExponentialBackoffConfig,
+    ) -> Self {
         RetriableSuiClient {
-            sui_client,
+            sui_clients: FailoverWrapper::new(clients)
+                .expect("at least one Sui client is required"),
             backoff_config,
             metrics: None,
         }
@@ -450,13 +550,93 @@ impl RetriableSuiClient {
         &self.backoff_config
     }
 
+    /// Returns the name of the RPC endpoint that is currently used, which is its URL if the client
+    /// was created for an RPC address.
+    pub fn current_rpc_endpoint(&self) -> &str {
+        self.sui_clients.get_current_client_name()
+    }
+
+    /// Returns the [`SuiClient`] of the RPC endpoint that is currently used.
+    fn sui_client(&self) -> &SuiClient {
+        self.sui_clients.current()
+    }
+
     /// Creates a new retriable client from an RCP address.
     pub async fn new_for_rpc<S: AsRef<str>>(
         rpc_address: S,
         backoff_config: ExponentialBackoffConfig,
     ) -> SuiClientResult<Self> {
+        let rpc_address = rpc_address.as_ref();
         let client = SuiClientBuilder::default().build(rpc_address).await?;
-        Ok(Self::new(client, backoff_config))
+        Ok(Self::new_with_endpoints(
+            vec![(client, rpc_address.to_owned())],
+            backoff_config,
+        ))
+    }
+
+    /// Creates a new retriable client that fails over between the provided RPC addresses.
+    ///
+    /// The health of all RPC nodes is checked, and unreachable ones are discarded. The remaining
+    /// nodes are used in order of their latency, starting with the fastest one.
+    pub async fn new_for_rpc_urls<S: AsRef<str>>(
+        rpc_addresses: &[S],
+        backoff_config: ExponentialBackoffConfig,
+    ) -> SuiClientResult<Self> {
+        ensure!(
+            !rpc_addresses.is_empty(),
+            "at least one Sui RPC URL is required"
+        );
+        let health_checks = rpc_addresses.iter().map(|rpc_address| async move {
+            let rpc_address = rpc_address.as_ref();
+            let start = Instant::now();
+            let client = SuiClientBuilder::default().build(rpc_address).await?;
+            client
+                .read_api()
+                .get_latest_checkpoint_sequence_number()
+                .await?;
+            Ok::<_, sui_sdk::error::Error>((client, rpc_address.to_owned(), start.elapsed()))
+        });
+
+        let mut healthy_clients = vec![];
+        let mut last_error = None;
+        for (result, rpc_address) in future::join_all(health_checks)
+            .await
+            .into_iter()
+            .zip(rpc_addresses)
+        {
+            match result {
+                Ok(healthy_client) => healthy_clients.push(healthy_client),
+                Err(error) => {
+                    tracing::warn!(
+                        %error,
+                        rpc_address = rpc_address.as_ref(),
+                        "Sui RPC node failed the health check"
+                    );
+                    last_error = Some(error);
+                }
+            }
+        }
+        if healthy_clients.is_empty() {
+            return Err(last_error
+                .expect("there is at least one RPC address")
+                .into());
+        }
+
+        healthy_clients.sort_by_key(|(_, _, latency)| *latency);
+        tracing::info!(
+            rpc_addresses = ?healthy_clients
+                .iter()
+                .map(|(_, rpc_address, latency)| format!("{rpc_address} ({latency:?})"))
+                .collect::<Vec<_>>(),
+            "using the healthy Sui RPC nodes in order of latency"
+        );
+        Ok(Self::new_with_endpoints(
+            healthy_clients
+                .into_iter()
+                .map(|(client, rpc_address, _)| (client, rpc_address))
+                .collect(),
+            backoff_config,
+        ))
     }
 
     /// Creates a new retriable client from a wallet context.
@@ -511,6 +691,32 @@ impl RetriableSuiClient {
         .await
     }
 
+    /// Returns coins of the given type owned by the address, ignoring the `exclude`d ones, until
+    /// their total balance is at least `amount` or all coins have been returned.
+    ///
+    /// The coins are fetched page by page, and no further pages are fetched once the balance is
+    /// covered. Pages of coins that cannot be fetched after retries end the listing early, so the
+    /// returned coins may be incomplete.
+    #[tracing::instrument(level = Level::DEBUG, skip_all)]
+    pub async fn get_coins_covering(
+        &self,
+        address: SuiAddress,
+        coin_type: Option<String>,
+        amount: u128,
+        exclude: &HashSet<ObjectID>,
+    ) -> Vec<Coin> {
+        let mut total = 0u128;
+        self.get_coins_stream_retry(address, coin_type)
+            .filter(|coin: &Coin| future::ready(!exclude.contains(&coin.coin_object_id)))
+            .take_while(|coin: &Coin| {
+                let ready = future::ready(total < amount);
+                total += u128::from(coin.balance);
+                ready
+            })
+            .collect()
+            .await
+    }
+
     /// Returns a list of coins for the given address, or an error upon failure.
     ///
     /// This is a reimplementation of the [`sui_sdk::apis::CoinReadApi::select_coins`] method, but
@@ -561,22 +767,21 @@ impl RetriableSuiClient {
                 if let Some(item) = data.pop() {
                     Some((item, (data, cursor, has_next_page, coin_type)))
                 } else if has_next_page {
-                    let page = retry_rpc_errors(
-                        self.get_strategy(),
-                        || async {
-                            self.sui_client
-                                .coin_read_api()
-                                .get_coins(owner, coin_type.clone(), cursor.clone(), Some(100))
-                                .await
-                        },
-                        self.metrics.clone(),
-                        "get_coins",
-                    )
-                    .await
-                    .inspect_err(
-                        |error| tracing::warn!(%error, "failed to get coins after retries"),
-                    )
-                    .ok()?;
+                    let page = self
+                        .retry_with_failover(
+                            || async {
+                                self.sui_client()
+                                    .coin_read_api()
+                                    .get_coins(owner, coin_type.clone(), cursor.clone(), Some(100))
+                                    .await
+                            },
+                            "get_coins",
+                        )
+                        .await
+                        .inspect_err(
+                            |error| tracing::warn!(%error, "failed to get coins after retries"),
+                        )
+                        .ok()?;
 
                     let mut data = page.data;
                     data.reverse();
@@ -602,15 +807,13 @@ impl RetriableSuiClient {
         owner: SuiAddress,
         coin_type: Option<String>,
     ) -> SuiRpcResult<Balance> {
-        retry_rpc_errors(
-            self.get_strategy(),
+        self.retry_with_failover(
             || async {
-                self.sui_client
+                self.sui_client()
                     .coin_read_api()
                     .get_balance(owner, coin_type.clone())
                     .await
             },
-            self.metrics.clone(),
             "get_balance",
         )
         .await
@@ -627,15 +830,13 @@ impl RetriableSuiClient {
         cursor: Option<ObjectID>,
         limit: Option<usize>,
     ) -> SuiRpcResult<ObjectsPage> {
-        retry_rpc_errors(
-            self.get_strategy(),
+        self.retry_with_failover(
             || async {
-                self.sui_client
+                self.sui_client()
                     .read_api()
                     .get_owned_objects(address, query.clone(), cursor, limit)
                     .await
             },
-            self.metrics.clone(),
             "get_owned_objects",
         )
         .await
@@ -650,15 +851,13 @@ impl RetriableSuiClient {
         object_id: ObjectID,
         options: SuiObjectDataOptions,
     ) -> SuiRpcResult<SuiObjectResponse> {
-        retry_rpc_errors(
-            self.get_strategy(),
+        self.retry_with_failover(
             || async {
-                self.sui_client
+                self.sui_client()
                     .read_api()
                     .get_object_with_options(object_id, options.clone())
                     .await
             },
-            self.metrics.clone(),
             "get_object",
         )
         .await
@@ -672,20 +871,80 @@ impl RetriableSuiClient {
         digest: TransactionDigest,
         options: SuiTransactionBlockResponseOptions,
     ) -> SuiRpcResult<SuiTransactionBlockResponse> {
-        retry_rpc_errors(
-            self.get_strategy(),
+        self.retry_with_failover(
             || async {
-                self.sui_client
+                self.sui_client()
                     .read_api()
                     .get_transaction_with_options(digest, options.clone())
                     .await
             },
-            self.metrics.clone(),
             "get_transaction",
         )
         .await
     }
 
+    /// Returns a page of the events matching the `filter`.
+    ///
+    /// Calls [`sui_sdk::apis::EventApi::query_events`] internally.
+    #[tracing::instrument(level = Level::DEBUG, skip_all)]
+    pub async fn query_events(
+        &self,
+        filter: EventFilter,
+        cursor: Option<EventID>,
+        limit: Option<usize>,
+        descending_order: bool,
+    ) -> SuiRpcResult<EventPage> {
+        self.retry_with_failover(
+            || async {
+                self.sui_client()
+                    .event_api()
+                    .query_events(filter.clone(), cursor, limit, descending_order)
+                    .await
+            },
+            "query_events",
+        )
+        .await
+    }
+
+    /// Returns the version `version` of the object with ID `object_id`, converted to `U`.
+    ///
+    /// Calls [`sui_sdk::apis::ReadApi::try_get_parsed_past_object`] internally. Full nodes may
+    /// prune old object versions, in which case an error is returned.
+    #[tracing::instrument(level = Level::DEBUG, skip_all)]
+    pub async fn get_past_sui_object<U>(
+        &self,
+        object_id: ObjectID,
+        version: SequenceNumber,
+    ) -> SuiClientResult<U>
+    where
+        U: AssociatedContractStruct,
+    {
+        let response = self
+            .retry_with_failover(
+                || async {
+                    self.sui_client()
+                        .read_api()
+                        .try_get_parsed_past_object(
+                            object_id,
+                            version,
+                            SuiObjectDataOptions::new().with_bcs().with_type(),
+                        )
+                        .await
+                },
+                "get_past_object",
+            )
+            .await?;
+        let object_data = response
+            .into_object()
+            .with_context(|| format!("version {version} of object {object_id} is not available"))?;
+        Ok(U::try_from_object_data(&object_data).with_context(|| {
+            format!(
+                "could not convert object to expected type {}",
+                U::CONTRACT_STRUCT
+            )
+        })?)
+    }
+
     /// Return a list of [SuiObjectResponse] from the given vector of [ObjectID]s.
     ///
     /// Calls [`sui_sdk::apis::ReadApi::multi_get_object_with_options`] internally.
@@ -695,15 +954,13 @@ impl RetriableSuiClient {
         object_ids: Vec<ObjectID>,
         options: SuiObjectDataOptions,
     ) -> SuiRpcResult<Vec<SuiObjectResponse>> {
-        retry_rpc_errors(
-            self.get_strategy(),
+        self.retry_with_failover(
             || async {
-                self.sui_client
+                self.sui_client()
                     .read_api()
                     .multi_get_object_with_options(object_ids.clone(), options.clone())
                     .await
             },
-            self.metrics.clone(),
             "multi_get_object",
         )
         .await
@@ -717,15 +974,13 @@ impl RetriableSuiClient {
         &self,
         package_id: ObjectID,
     ) -> SuiRpcResult<BTreeMap<String, SuiMoveNormalizedModule>> {
-        retry_rpc_errors(
-            self.get_strategy(),
+        self.retry_with_failover(
             || async {
-                self.sui_client
+                self.sui_client()
                     .read_api()
                     .get_normalized_move_modules_by_package(package_id)
                     .await
             },
-            self.metrics.clone(),
             "get_normalized_move_modules_by_package",
         )
         .await
@@ -738,15 +993,13 @@ impl RetriableSuiClient {
         &self,
         epoch: Option<BigInt<u64>>,
     ) -> SuiRpcResult<SuiCommittee> {
-        retry_rpc_errors(
-            self.get_strategy(),
+        self.retry_with_failover(
             || async {
-                self.sui_client
+                self.sui_client()
                     .governance_api()
                     .get_committee_info(epoch)
                     .await
             },
-            self.metrics.clone(),
             "get_committee_info",
         )
         .await
@@ -756,10 +1009,8 @@ impl RetriableSuiClient {
     ///
     /// Calls [`sui_sdk::apis::ReadApi::get_reference_gas_price`] internally.
     pub async fn get_reference_gas_price(&self) -> SuiRpcResult<u64> {
-        retry_rpc_errors(
-            self.get_strategy(),
-            || async { self.sui_client.read_api().get_reference_gas_price().await },
-            self.metrics.clone(),
+        self.retry_with_failover(
+            || async { self.sui_client().read_api().get_reference_gas_price().await },
             "get_reference_gas_price",
         )
         .await
@@ -772,15 +1023,13 @@ impl RetriableSuiClient {
         &self,
         transaction: TransactionData,
     ) -> SuiRpcResult<DryRunTransactionBlockResponse> {
-        retry_rpc_errors(
-            self.get_strategy(),
+        self.retry_with_failover(
             || async {
-                self.sui_client
+                self.sui_client()
                     .read_api()
                     .dry_run_transaction_block(transaction.clone())
                     .await
             },
-            self.metrics.clone(),
             "dry_run_transaction_block",
         )
         .await
@@ -791,7 +1040,7 @@ impl RetriableSuiClient {
     /// Internally calls the [`SuiClient::event_api`] function. Note that no retries are
     /// implemented for this function.
     pub fn event_api(&self) -> &EventApi {
-        self.sui_client.event_api()
+        self.sui_client().event_api()
     }
 
     /// Returns a reference to the [`GovernanceApi`].
@@ -799,7 +1048,7 @@ impl RetriableSuiClient {
     /// Internally calls the [`SuiClient::governance_api`] function. Note that no retries are
     /// implemented for this function.
     pub fn governance_api(&self) -> &GovernanceApi {
-        self.sui_client.governance_api()
+        self.sui_client().governance_api()
     }
 
     /// Returns a [`SuiObjectResponse`] based on the provided [`ObjectID`].
@@ -832,10 +1081,8 @@ impl RetriableSuiClient {
     ///
     /// Calls [`sui_sdk::apis::ReadApi::get_chain_identifier`] internally.
     pub async fn get_chain_identifier(&self) -> SuiRpcResult<String> {
-        retry_rpc_errors(
-            self.get_strategy(),
-            || async { self.sui_client.read_api().get_chain_identifier().await },
-            self.metrics.clone(),
+        self.retry_with_failover(
+            || async { self.sui_client().read_api().get_chain_identifier().await },
             "get_chain_identifier",
         )
         .await
@@ -924,6 +1171,8 @@ impl RetriableSuiClient {
     }
 
     /// Checks if the Walrus system object exist on chain and returns the Walrus package ID.
+    ///
+    /// Returns an error if the version of the system object is not supported by this binary.
     pub(crate) async fn get_system_package_id_from_system_object(
         &self,
         system_object_id: ObjectID,
@@ -931,6 +1180,7 @@ impl RetriableSuiClient {
         let system_object = self
             .get_sui_object::<SystemObjectForDeserialization>(system_object_id)
             .await?;
+        ensure_supported_contract_version("system", system_object.version)?;
 
         let pkg_id = system_object.package_id;
         Ok(pkg_id)
@@ -1055,17 +1305,9 @@ impl RetriableSuiClient {
         kind: TransactionKind,
         gas_price: u64,
     ) -> SuiClientResult<u64> {
-        let dry_run_tx_data = self
-            .sui_client
-            .transaction_builder()
-            .tx_data_for_dry_run(signer, kind, MAX_GAS_BUDGET, gas_price, None, None)
-            .await;
         let effects = self
-            .dry_run_transaction_block(dry_run_tx_data)
-            .await
-            .inspect_err(|error| {
-                tracing::debug!(%error, "transaction dry run failed");
-            })?
+            .dry_run_transaction_kind(signer, kind, gas_price)
+            .await?
             .effects;
         let gas_cost_summary = effects.gas_cost_summary();
 
@@ -1075,6 +1317,27 @@ impl RetriableSuiClient {
         Ok(computation_cost_with_overhead.max(gas_usage_with_overhead.max(0) as u64))
     }
 
+    /// Calls a dry run with the transaction data of the `kind` of transaction and the maximum gas
+    /// budget.
+    pub(crate) async fn dry_run_transaction_kind(
+        &self,
+        signer: SuiAddress,
+        kind: TransactionKind,
+        gas_price: u64,
+    ) -> SuiClientResult<DryRunTransactionBlockResponse> {
+        let dry_run_tx_data = self
+            .sui_client()
+            .transaction_builder()
+            .tx_data_for_dry_run(signer, kind, MAX_GAS_BUDGET, gas_price, None, None)
+            .await;
+        Ok(self
+            .dry_run_transaction_block(dry_run_tx_data)
+            .await
+            .inspect_err(|error| {
+                tracing::debug!(%error, "transaction dry run failed");
+            })?)
+    }
+
     /// Executes a transaction.
     #[tracing::instrument(err, skip(self))]
     pub(crate) async fn execute_transaction(
@@ -1083,15 +1346,14 @@ impl RetriableSuiClient {
         method: &str,
     ) -> anyhow::Result<SuiTransactionBlockResponse> {
         // Retry here must use the exact same transaction to avoid locked objects.
-        retry_rpc_errors(
-            self.get_strategy(),
+        self.retry_with_failover(
             || async {
                 #[cfg(msim)]
                 {
                     maybe_return_injected_error_in_stake_pool_transaction(&transaction)?;
                 }
                 Ok(self
-                    .sui_client
+                    .sui_client()
                     .quorum_driver_api()
                     .execute_transaction_block(
                         transaction.clone(),
@@ -1105,12 +1367,24 @@ impl RetriableSuiClient {
                     )
                     .await?)
             },
-            self.metrics.clone(),
             method,
         )
         .await
     }
 
+    /// Retries the given function while it returns retriable errors, and fails over to the next
+    /// RPC endpoint when a call fails with a retriable error or has a high latency.
+    async fn retry_with_failover<F, T, E, Fut>(&self, func: F, method: &str) -> Result<T, E>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T, E>>,
+        E: RetriableRpcError + ToErrorType,
+    {
+        self.sui_clients
+            .retry_with_failover(self.get_strategy(), func, self.metrics.clone(), method)
+            .await
+    }
+
     /// Gets a backoff strategy, seeded from the internal RNG.
     fn get_strategy(&self) -> ExponentialBackoff<StdRng> {
         self.backoff_config.get_strategy(ThreadRng::default().gen())
@@ -1802,6 +2076,41 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn retry_with_failover_uses_the_next_client_after_an_error() {
+        let failing_client = MockClient::new(true);
+        let succeeding_client = MockClient::new(false);
+        let failing_calls = failing_client.call_count.clone();
+        let succeeding_calls = succeeding_client.call_count.clone();
+        let wrapper = FailoverWrapper::new(vec![
+            (failing_client, "first".to_string()),
+            (succeeding_client, "second".to_string()),
+        ])
+        .unwrap();
+        let backoff_config =
+            ExponentialBackoffConfig::new(Duration::from_millis(1), Duration::from_millis(1), None);
+
+        for _ in 0..2 {
+            let result = wrapper
+                .retry_with_failover(
+                    backoff_config.get_strategy(0),
+                    || wrapper.current().operation(),
+                    None,
+                    "operation",
+                )
+                .await;
+            assert!(matches!(result, Ok(ref s) if s == "success"));
+        }
+
+        // The first client is only called once, the second one becomes the current client.
+        assert_eq!(failing_calls.load(std::sync::atomic::Ordering::SeqCst), 1);
+        assert_eq!(
+            succeeding_calls.load(std::sync::atomic::Ordering::SeqCst),
+            2
+        );
+        assert_eq!(wrapper.get_current_client_name(), "second");
+    }
+
     #[tokio::test]
     async fn test_failover_wrapper_all_fail() {
         // Create mock clients - both fail.
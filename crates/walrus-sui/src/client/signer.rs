@@ -0,0 +1,434 @@
+// Copyright (c) Walrus Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Signing of the transactions of a [`SuiContractClient`][super::SuiContractClient] outside of the
+//! keystore of its wallet.
+//!
+//! Implementations of [`TransactionSigner`] can sign with keys that cannot be exported into a
+//! keystore, such as keys held by a hardware wallet or a key management service. The
+//! [`SigningServiceSigner`] delegates the signing to a remote signing service, and the
+//! [`OfflineSigner`] exports the transactions to be signed offline, for example by the signers of a
+//! Sui multisig address.
+
+use std::{
+    fmt::Debug,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use anyhow::Context;
+use fastcrypto::{
+    encoding::{Base64, Encoding as _},
+    traits::ToFromBytes as _,
+};
+use futures::{future::BoxFuture, FutureExt as _};
+use serde::{Deserialize, Serialize};
+use serde_with::{serde_as, DurationSeconds};
+use sui_types::{
+    base_types::SuiAddress,
+    signature::GenericSignature,
+    transaction::TransactionData,
+};
+use tokio::time::Instant;
+
+/// A signer of the transactions of a contract client.
+pub trait TransactionSigner: Debug + Send + Sync {
+    /// Returns the address whose transactions the signer signs.
+    fn address(&self) -> SuiAddress;
+
+    /// Signs the intent message of the `transaction` with the key of [`Self::address`].
+    fn sign_transaction<'a>(
+        &'a self,
+        transaction: &'a TransactionData,
+    ) -> BoxFuture<'a, anyhow::Result<GenericSignature>>;
+
+    /// Returns true if transactions that fail due to gas or locked gas coins may be rebuilt and
+    /// signed again.
+    ///
+    /// Signers that require manual steps for every signature return false, such that every
+    /// transaction is signed at most once.
+    fn allows_resigning(&self) -> bool {
+        true
+    }
+}
+
+/// The configuration of a remote signing service that signs the transactions of the client.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct SigningServiceConfig {
+    /// The URL to which the transactions to sign are posted.
+    pub url: reqwest::Url,
+    /// The address whose transactions the signing service signs.
+    pub address: SuiAddress,
+    /// The bearer token with which the requests to the signing service are authenticated.
+    #[serde(default)]
+    pub auth_token: Option<String>,
+}
+
+/// A [`TransactionSigner`] that delegates the signing to a remote signing service.
+///
+/// The transaction is posted as `{"address": ..., "tx_bytes": ...}`, with the BCS-encoded
+/// transaction data in Base64, and the service responds with `{"signature": ...}`, containing the
+/// Base64-encoded serialized signature.
+pub struct SigningServiceSigner {
+    client: reqwest::Client,
+    config: SigningServiceConfig,
+}
+
+impl SigningServiceSigner {
+    /// Creates a new signer for the signing service configured in `config`.
+    pub fn new(config: SigningServiceConfig) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            config,
+        }
+    }
+
+    async fn sign(&self, transaction: &TransactionData) -> anyhow::Result<GenericSignature> {
+        let tx_bytes = bcs::to_bytes(transaction).context("failed to serialize the transaction")?;
+        let mut builder = self
+            .client
+            .post(self.config.url.clone())
+            .json(&SignRequest {
+                address: self.config.address,
+                tx_bytes: Base64::encode(tx_bytes),
+            });
+        if let Some(auth_token) = &self.config.auth_token {
+            builder = builder.bearer_auth(auth_token);
+        }
+        let response: SignResponse = builder
+            .send()
+            .await
+            .context("failed to send the transaction to the signing service")?
+            .error_for_status()
+            .context("the signing service rejected the transaction")?
+            .json()
+            .await
+            .context("failed to parse the response of the signing service")?;
+
+        let signature = Base64::decode(&response.signature)
+            .context("the signature of the signing service is not valid Base64")?;
+        GenericSignature::from_bytes(&signature)
+            .context("the signing service returned an invalid signature")
+    }
+}
+
+impl Debug for SigningServiceSigner {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SigningServiceSigner")
+            .field("url", &self.config.url)
+            .field("address", &self.config.address)
+            .finish_non_exhaustive()
+    }
+}
+
+impl TransactionSigner for SigningServiceSigner {
+    fn address(&self) -> SuiAddress {
+        self.config.address
+    }
+
+    fn sign_transaction<'a>(
+        &'a self,
+        transaction: &'a TransactionData,
+    ) -> BoxFuture<'a, anyhow::Result<GenericSignature>> {
+        self.sign(transaction).boxed()
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct SignRequest {
+    address: SuiAddress,
+    tx_bytes: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct SignResponse {
+    signature: String,
+}
+
+/// The configuration of the offline signing of the transactions of the client.
+#[serde_as]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct OfflineSignerConfig {
+    /// The address whose transactions are signed offline, for example a Sui multisig address.
+    pub address: SuiAddress,
+    /// The directory to which the unsigned transactions are written, and from which their
+    /// signatures are read.
+    #[serde(deserialize_with = "walrus_utils::config::resolve_home_dir")]
+    pub exchange_dir: PathBuf,
+    /// How long to wait for the signature of a transaction, one hour by default.
+    #[serde_as(as = "DurationSeconds<u64>")]
+    #[serde(
+        rename = "signature_timeout_secs",
+        default = "OfflineSignerConfig::default_signature_timeout"
+    )]
+    pub signature_timeout: Duration,
+}
+
+impl OfflineSignerConfig {
+    fn default_signature_timeout() -> Duration {
+        Duration::from_secs(60 * 60)
+    }
+}
+
+/// A [`TransactionSigner`] for transactions that are signed offline.
+///
+/// The Base64-encoded BCS bytes of every transaction to sign are written to the file
+/// `<DIGEST>.tx` in the exchange directory, where `<DIGEST>` is the digest of the transaction. The
+/// signer then waits for the Base64-encoded serialized signature of the transaction in the file
+/// `<DIGEST>.sig`. For a multisig address, this is the signature combined from the partial
+/// signatures of a quorum of its signers, e.g., with `sui keytool multi-sig-combine-partial-sig`.
+///
+/// Signing fails if the signature is not provided within the configured
+/// [`signature_timeout`][OfflineSignerConfig::signature_timeout]. As every signature requires
+/// manual steps, transactions signed offline are not resubmitted with an increased gas price or
+/// with other gas coins if they fail.
+#[derive(Debug)]
+pub struct OfflineSigner {
+    config: OfflineSignerConfig,
+}
+
+impl OfflineSigner {
+    /// The interval at which the exchange directory is checked for the signature.
+    const POLLING_INTERVAL: Duration = Duration::from_secs(1);
+
+    /// Creates a new signer for the offline signing configured in `config`.
+    pub fn new(config: OfflineSignerConfig) -> Self {
+        Self { config }
+    }
+
+    async fn sign(&self, transaction: &TransactionData) -> anyhow::Result<GenericSignature> {
+        let tx_bytes = bcs::to_bytes(transaction).context("failed to serialize the transaction")?;
+        let digest = transaction.digest();
+        let tx_path = self.config.exchange_dir.join(format!("{digest}.tx"));
+        let signature_path = self.config.exchange_dir.join(format!("{digest}.sig"));
+
+        tokio::fs::create_dir_all(&self.config.exchange_dir)
+            .await
+            .context("failed to create the exchange directory of the offline signer")?;
+        tokio::fs::write(&tx_path, Base64::encode(tx_bytes))
+            .await
+            .context("failed to write the transaction to sign")?;
+        tracing::info!(
+            address = %self.config.address,
+            tx_path = %tx_path.display(),
+            signature_path = %signature_path.display(),
+            "waiting for the transaction to be signed offline"
+        );
+
+        let signature = self.wait_for_signature(&signature_path).await?;
+        for path in [&tx_path, &signature_path] {
+            if let Err(error) = tokio::fs::remove_file(path).await {
+                tracing::debug!(?error, path = %path.display(), "failed to remove exchange file");
+            }
+        }
+        Ok(signature)
+    }
+
+    async fn wait_for_signature(&self, signature_path: &Path) -> anyhow::Result<GenericSignature> {
+        let deadline = Instant::now() + self.config.signature_timeout;
+        let encoded = loop {
+            match tokio::fs::read_to_string(signature_path).await {
+                Ok(encoded) if !encoded.trim().is_empty() => break encoded,
+                Ok(_) => (),
+                Err(error) if error.kind() == std::io::ErrorKind::NotFound => (),
+                Err(error) => return Err(error).context("failed to read the signature"),
+            }
+            anyhow::ensure!(
+                Instant::now() < deadline,
+                "the transaction was not signed within {:?}; expected its signature in '{}'",
+                self.config.signature_timeout,
+                signature_path.display()
+            );
+            tokio::time::sleep(Self::POLLING_INTERVAL).await;
+        };
+        let signature =
+            Base64::decode(encoded.trim()).context("the offline signature is not valid Base64")?;
+        GenericSignature::from_bytes(&signature).context("the offline signature is invalid")
+    }
+}
+
+impl TransactionSigner for OfflineSigner {
+    fn address(&self) -> SuiAddress {
+        self.config.address
+    }
+
+    fn sign_transaction<'a>(
+        &'a self,
+        transaction: &'a TransactionData,
+    ) -> BoxFuture<'a, anyhow::Result<GenericSignature>> {
+        self.sign(transaction).boxed()
+    }
+
+    fn allows_resigning(&self) -> bool {
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{
+        http::{HeaderMap, StatusCode},
+        routing::post,
+        Router,
+    };
+    use fastcrypto::ed25519::Ed25519KeyPair;
+    use sui_types::{
+        base_types::random_object_ref,
+        crypto::{get_key_pair, Signature, Signer},
+        transaction::{Transaction, TransactionDataAPI as _},
+    };
+
+    use super::*;
+
+    const AUTH_TOKEN: &str = "secret";
+
+    /// Returns a transaction and the signature of its sender on it.
+    fn signed_transaction() -> (TransactionData, GenericSignature) {
+        let (sender, keypair): (SuiAddress, Ed25519KeyPair) = get_key_pair();
+        let transaction = TransactionData::new_transfer_sui(
+            SuiAddress::random_for_testing_only(),
+            sender,
+            Some(1),
+            random_object_ref(),
+            1_000_000,
+            1_000,
+        );
+        let signed = Transaction::from_data_and_signer(
+            transaction.clone(),
+            vec![&keypair as &dyn Signer<Signature>],
+        );
+        let signature = signed.data().tx_signatures()[0].clone();
+        (transaction, signature)
+    }
+
+    /// Serves a signing service that returns `signature` for requests authenticated with
+    /// [`AUTH_TOKEN`] to sign `transaction`, and returns its URL.
+    async fn serve_signing_service(
+        transaction: &TransactionData,
+        signature: &GenericSignature,
+    ) -> anyhow::Result<reqwest::Url> {
+        let expected_tx_bytes = Base64::encode(bcs::to_bytes(transaction)?);
+        let response = serde_json::json!({ "signature": Base64::encode(signature.as_bytes()) });
+        let sign = move |headers: HeaderMap, body: String| async move {
+            let authorized = headers
+                .get("authorization")
+                .is_some_and(|value| value == format!("Bearer {AUTH_TOKEN}").as_str());
+            if !authorized {
+                return (StatusCode::UNAUTHORIZED, String::new());
+            }
+            let request: serde_json::Value = serde_json::from_str(&body).expect("valid JSON");
+            if request["tx_bytes"] != expected_tx_bytes {
+                return (StatusCode::BAD_REQUEST, String::new());
+            }
+            (StatusCode::OK, response.to_string())
+        };
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+        let address = listener.local_addr()?;
+        tokio::spawn(async move {
+            axum::serve(listener, Router::new().route("/sign", post(sign))).await
+        });
+        Ok(format!("http://{address}/sign").parse()?)
+    }
+
+    #[tokio::test]
+    async fn signing_service_signs_transactions() -> anyhow::Result<()> {
+        let (transaction, signature) = signed_transaction();
+        let url = serve_signing_service(&transaction, &signature).await?;
+        let signer = SigningServiceSigner::new(SigningServiceConfig {
+            url,
+            address: transaction.sender(),
+            auth_token: Some(AUTH_TOKEN.to_owned()),
+        });
+
+        assert_eq!(signer.address(), transaction.sender());
+        assert_eq!(signer.sign_transaction(&transaction).await?, signature);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn signing_service_rejections_are_errors() -> anyhow::Result<()> {
+        let (transaction, signature) = signed_transaction();
+        let url = serve_signing_service(&transaction, &signature).await?;
+        let signer = SigningServiceSigner::new(SigningServiceConfig {
+            url,
+            address: transaction.sender(),
+            auth_token: None,
+        });
+
+        let error = signer
+            .sign_transaction(&transaction)
+            .await
+            .expect_err("the request is not authenticated");
+        assert!(error.to_string().contains("rejected the transaction"));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn offline_signer_reads_the_signature_file() -> anyhow::Result<()> {
+        let (transaction, signature) = signed_transaction();
+        let exchange_dir = tempfile::tempdir()?;
+        let signer = OfflineSigner::new(OfflineSignerConfig {
+            address: transaction.sender(),
+            exchange_dir: exchange_dir.path().to_owned(),
+            signature_timeout: Duration::from_secs(30),
+        });
+        assert!(!signer.allows_resigning());
+
+        // Wait for the exported transaction and sign it, like the signers of the address would.
+        let digest = transaction.digest();
+        let tx_path = exchange_dir.path().join(format!("{digest}.tx"));
+        let signature_path = exchange_dir.path().join(format!("{digest}.sig"));
+        let expected_tx = Base64::encode(bcs::to_bytes(&transaction)?);
+        let offline_signing = async {
+            while tokio::fs::read_to_string(&tx_path).await.ok().as_ref() != Some(&expected_tx) {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+            tokio::fs::write(&signature_path, Base64::encode(signature.as_bytes())).await?;
+            anyhow::Ok(())
+        };
+
+        let (signed, ()) =
+            tokio::try_join!(signer.sign_transaction(&transaction), offline_signing)?;
+        assert_eq!(signed, signature);
+        assert!(!tx_path.exists());
+        assert!(!signature_path.exists());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn offline_signing_times_out_without_a_signature() -> anyhow::Result<()> {
+        let (transaction, _) = signed_transaction();
+        let exchange_dir = tempfile::tempdir()?;
+        let signer = OfflineSigner::new(OfflineSignerConfig {
+            address: transaction.sender(),
+            exchange_dir: exchange_dir.path().to_owned(),
+            signature_timeout: Duration::ZERO,
+        });
+
+        let error = signer
+            .sign_transaction(&transaction)
+            .await
+            .expect_err("no signature is provided");
+        assert!(error.to_string().contains("was not signed within"));
+        Ok(())
+    }
+
+    #[test]
+    fn parses_signing_service_config() -> anyhow::Result<()> {
+        let address = SuiAddress::random_for_testing_only();
+        let config: SigningServiceConfig = serde_json::from_value(serde_json::json!({
+            "url": "https://signer.example.com/sign",
+            "address": address,
+        }))?;
+        assert_eq!(
+            config,
+            SigningServiceConfig {
+                url: "https://signer.example.com/sign".parse()?,
+                address,
+                auth_token: None,
+            }
+        );
+        Ok(())
+    }
+}
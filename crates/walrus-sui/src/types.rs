@@ -9,6 +9,7 @@ use std::{
     fmt::Display,
     net::SocketAddr,
     num::{NonZeroU16, ParseIntError},
+    ops::Range,
 };
 
 use sui_types::base_types::ObjectID;
@@ -19,6 +20,7 @@ pub use events::{
     BlobCertified,
     BlobDeleted,
     BlobEvent,
+    BlobLifecycleEvent,
     BlobRegistered,
     ContractEvent,
     ContractUpgradedEvent,
@@ -50,8 +52,11 @@ pub mod move_errors;
 use serde::{Deserialize, Serialize};
 use walrus_core::{
     bft,
+    encoding::{shards_for_byte_range, DataTooLargeError},
     ensure,
     messages::ProofOfPossession,
+    BlobId,
+    EncodingType,
     Epoch,
     NetworkPublicKey,
     PublicKey,
@@ -366,6 +371,26 @@ impl Committee {
             .map(|index| &self.members[index])
     }
 
+    /// Returns the shards storing the primary slivers that contain the bytes in `range` of the
+    /// blob with ID `blob_id` and size `blob_size`, together with the members holding them.
+    ///
+    /// See [`shards_for_byte_range`] for details. Shards that are not held by any member are
+    /// omitted.
+    pub fn nodes_for_byte_range(
+        &self,
+        blob_id: &BlobId,
+        encoding_type: EncodingType,
+        blob_size: u64,
+        range: Range<u64>,
+    ) -> Result<Vec<(ShardIndex, &StorageNode)>, DataTooLargeError> {
+        let shards =
+            shards_for_byte_range(self.n_shards, blob_id, encoding_type, blob_size, range)?;
+        Ok(shards
+            .into_iter()
+            .filter_map(|(_, shard)| Some((shard, self.find_by_shard(shard)?)))
+            .collect())
+    }
+
     /// Returns the network address and network public key of the committee members.
     pub fn network_addresses_and_pks(
         &self,
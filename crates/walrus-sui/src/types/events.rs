@@ -369,6 +369,54 @@ impl TryFrom<SuiEvent> for BlobEvent {
     }
 }
 
+/// Events marking the lifecycle of a blob from its registration to its certification, or to its
+/// blob ID being marked as invalid.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum BlobLifecycleEvent {
+    /// A registration event.
+    Registered(BlobRegistered),
+    /// A certification event.
+    Certified(BlobCertified),
+    /// An invalid blob ID event.
+    InvalidBlobId(InvalidBlobId),
+}
+
+impl BlobLifecycleEvent {
+    /// Returns the blob ID contained in the wrapped event.
+    pub fn blob_id(&self) -> BlobId {
+        match self {
+            BlobLifecycleEvent::Registered(event) => event.blob_id,
+            BlobLifecycleEvent::Certified(event) => event.blob_id,
+            BlobLifecycleEvent::InvalidBlobId(event) => event.blob_id,
+        }
+    }
+
+    /// Returns the event ID of the wrapped event.
+    pub fn event_id(&self) -> EventID {
+        match self {
+            BlobLifecycleEvent::Registered(event) => event.event_id,
+            BlobLifecycleEvent::Certified(event) => event.event_id,
+            BlobLifecycleEvent::InvalidBlobId(event) => event.event_id,
+        }
+    }
+}
+
+impl TryFrom<ContractEvent> for BlobLifecycleEvent {
+    type Error = ContractEvent;
+
+    /// Converts the contract event, returning it unchanged if it is not a blob lifecycle event.
+    fn try_from(value: ContractEvent) -> Result<Self, Self::Error> {
+        match value {
+            ContractEvent::BlobEvent(BlobEvent::Registered(event)) => Ok(Self::Registered(event)),
+            ContractEvent::BlobEvent(BlobEvent::Certified(event)) => Ok(Self::Certified(event)),
+            ContractEvent::BlobEvent(BlobEvent::InvalidBlobID(event)) => {
+                Ok(Self::InvalidBlobId(event))
+            }
+            other => Err(other),
+        }
+    }
+}
+
 /// Sui event that epoch parameters have been selected.
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct EpochParametersSelected {
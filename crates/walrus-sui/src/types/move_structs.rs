@@ -464,6 +464,56 @@ impl AssociatedContractStruct for StakingPool {
     const CONTRACT_STRUCT: StructTag<'static> = contracts::staking_pool::StakingPool;
 }
 
+impl StakingPool {
+    /// Returns the epoch when the pool is / will be activated.
+    pub(crate) fn activation_epoch(&self) -> Epoch {
+        self.activation_epoch
+    }
+
+    /// Returns the ID of the table of the exchange rates of the pool, indexed by epoch.
+    pub(crate) fn exchange_rates(&self) -> ObjectID {
+        self.exchange_rates
+    }
+}
+
+/// Sui type for the exchange rate between WAL and the shares of a staking pool.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Deserialize)]
+pub enum PoolExchangeRate {
+    /// One to one exchange rate.
+    Flat,
+    /// Exchange rate of `wal_amount` WAL for `share_amount` shares.
+    Variable {
+        /// Amount of staked WAL tokens and rewards.
+        wal_amount: u128,
+        /// Amount of total shares in the pool.
+        share_amount: u128,
+    },
+}
+
+impl PoolExchangeRate {
+    /// Converts an `amount` of shares to the corresponding amount of WAL.
+    pub fn convert_to_wal_amount(&self, amount: u64) -> u64 {
+        match self {
+            Self::Flat => amount,
+            Self::Variable {
+                wal_amount,
+                share_amount,
+            } => u64::try_from(u128::from(amount) * wal_amount / share_amount).unwrap_or(u64::MAX),
+        }
+    }
+
+    /// Converts an `amount` of WAL to the corresponding amount of shares.
+    pub fn convert_to_share_amount(&self, amount: u64) -> u64 {
+        match self {
+            Self::Flat => amount,
+            Self::Variable {
+                wal_amount,
+                share_amount,
+            } => u64::try_from(u128::from(amount) * share_amount / wal_amount).unwrap_or(u64::MAX),
+        }
+    }
+}
+
 /// Holds information about a future epoch, namely how much
 /// storage needs to be reclaimed and the rewards to be distributed.
 #[derive(Debug, PartialEq, Eq, Clone, Deserialize)]
@@ -728,6 +778,22 @@ impl SystemObject {
         }
     }
 
+    /// Returns the total storage capacity of the Walrus instance.
+    pub fn total_capacity_size(&self) -> u64 {
+        match &self.inner {
+            SystemStateInnerV1Enum::V1(inner) => inner.total_capacity_size,
+            SystemStateInnerV1Enum::V1Testnet(inner) => inner.total_capacity_size,
+        }
+    }
+
+    /// Returns the storage capacity of the Walrus instance that is currently reserved.
+    pub fn used_capacity_size(&self) -> u64 {
+        match &self.inner {
+            SystemStateInnerV1Enum::V1(inner) => inner.used_capacity_size,
+            SystemStateInnerV1Enum::V1Testnet(inner) => inner.used_capacity_size,
+        }
+    }
+
     /// Returns the latest certified event blob.
     pub fn latest_certified_event_blob(&self) -> Option<EventBlob> {
         match &self.inner {
@@ -874,6 +940,16 @@ impl AssociatedContractStruct for StakedWal {
     const CONTRACT_STRUCT: StructTag<'static> = contracts::staked_wal::StakedWal;
 }
 
+impl StakedWal {
+    /// Returns the epoch from which the WAL can be withdrawn, if a withdrawal has been requested.
+    pub fn withdraw_epoch(&self) -> Option<Epoch> {
+        match self.state {
+            StakedWalState::Staked => None,
+            StakedWalState::Withdrawing(withdraw_epoch, _) => Some(withdraw_epoch),
+        }
+    }
+}
+
 impl Display for StakedWal {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         writeln!(f, "StakedWal:")?;
@@ -911,6 +987,12 @@ impl ExchangeRate {
     pub fn sui_to_wal(&self, amount: u64) -> u64 {
         amount * self.wal / self.sui
     }
+
+    /// Returns the smallest amount of SUI that is converted to at least `amount` WAL.
+    pub fn wal_to_sui_rounded_up(&self, amount: u64) -> u64 {
+        u64::try_from((u128::from(amount) * u128::from(self.sui)).div_ceil(u128::from(self.wal)))
+            .unwrap_or(u64::MAX)
+    }
 }
 
 /// Sui type for a subsidies object.
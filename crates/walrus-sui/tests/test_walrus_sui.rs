@@ -8,11 +8,15 @@ use std::{num::NonZeroU16, sync::Arc, time::Duration};
 use anyhow::{bail, Result};
 use fastcrypto::{
     bls12381::min_pk::{BLS12381AggregateSignature, BLS12381KeyPair, BLS12381PrivateKey},
+    ed25519::Ed25519KeyPair,
     traits::ToFromBytes,
 };
 use rand::{rngs::StdRng, SeedableRng};
 use sui_sdk::wallet_context::WalletContext;
-use sui_types::{base_types::SuiAddress, crypto::Signer};
+use sui_types::{
+    base_types::SuiAddress,
+    crypto::{get_key_pair, Signer, SuiKeyPair},
+};
 use tokio_stream::StreamExt;
 use walrus_core::{
     encoding::{EncodingConfig, EncodingConfigTrait as _},
@@ -35,6 +39,7 @@ use walrus_sui::{
         BlobObjectMetadata,
         BlobPersistence,
         CoinType,
+        GasSponsorConfig,
         PostStoreAction,
         ReadClient,
         SuiContractClient,
@@ -504,6 +509,60 @@ async fn test_collect_commission() -> anyhow::Result<()> {
     Ok(())
 }
 
+#[tokio::test]
+#[ignore = "ignore integration tests by default"]
+async fn test_sponsored_transactions() -> anyhow::Result<()> {
+    _ = tracing_subscriber::fmt::try_init();
+    let (sui_cluster_handle, admin_client, _) = initialize_contract_and_wallet().await?;
+
+    // Fund a new sponsor key and write it to a file.
+    let (sponsor_address, sponsor_keypair): (SuiAddress, Ed25519KeyPair) = get_key_pair();
+    admin_client
+        .as_ref()
+        .send_sui(10_000_000_000, sponsor_address)
+        .await?;
+    let key_dir = tempfile::tempdir()?;
+    let key_file = key_dir.path().join("sponsor.key");
+    let encoded_key = SuiKeyPair::Ed25519(sponsor_keypair)
+        .encode()
+        .map_err(|error| anyhow::anyhow!("failed to encode the sponsor key: {error}"))?;
+    std::fs::write(&key_file, encoded_key)?;
+    let sponsor = GasSponsorConfig::Key { key_file };
+
+    // Two clients whose transactions are paid by the same sponsor.
+    let mut clients = vec![];
+    for _ in 0..2 {
+        let client = new_contract_client_on_sui_test_cluster(
+            sui_cluster_handle.clone(),
+            admin_client.as_ref(),
+        )
+        .await?
+        .and_then(|client| client.with_gas_sponsor(&sponsor))?;
+        admin_client
+            .as_ref()
+            .send_wal(1_000_000_000, client.as_ref().address())
+            .await?;
+        clients.push(client);
+    }
+    let sponsor_balance = sui_balance(admin_client.as_ref(), sponsor_address).await?;
+    let mut client_balances = vec![];
+    for client in &clients {
+        client_balances.push(client.as_ref().balance(CoinType::Sui).await?);
+    }
+
+    // The concurrent transactions of the clients must not select the same coins of the sponsor.
+    let reservations = clients
+        .iter()
+        .map(|client| client.as_ref().reserve_space(1_000_000, 1));
+    futures::future::try_join_all(reservations).await?;
+
+    for (client, balance) in clients.iter().zip(client_balances) {
+        assert_eq!(client.as_ref().balance(CoinType::Sui).await?, balance);
+    }
+    assert!(sui_balance(admin_client.as_ref(), sponsor_address).await? < sponsor_balance);
+    Ok(())
+}
+
 async_param_test! {
     #[ignore = "ignore integration tests by default"]
     test_automatic_wal_coin_squashing -> anyhow::Result<()> : [
@@ -609,6 +668,15 @@ async fn test_automatic_wal_coin_squashing(
 
 // Helper functions
 
+/// Returns the total SUI balance of the `address`.
+async fn sui_balance(client: &SuiContractClient, address: SuiAddress) -> anyhow::Result<u128> {
+    Ok(client
+        .sui_client()
+        .get_balance(address, None)
+        .await?
+        .total_balance)
+}
+
 fn sign_with_default_committee(msg: &[u8]) -> BLS12381AggregateSignature {
     default_protocol_keypair().as_ref().sign(msg).into()
 }
@@ -241,6 +241,40 @@ where
     }
 }
 
+/// Repeatedly calls the provided function with the provided backoff strategy until it returns
+/// successfully or returns an error for which `should_retry` is false.
+pub async fn retry_while<S, F, T, E, Fut, P>(
+    mut strategy: S,
+    mut func: F,
+    should_retry: P,
+) -> Result<T, E>
+where
+    S: BackoffStrategy,
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, E>>,
+    P: Fn(&E) -> bool,
+{
+    loop {
+        let error = match func().await {
+            Ok(value) => return Ok(value),
+            Err(error) => error,
+        };
+
+        if !should_retry(&error) {
+            tracing::debug!("attempt failed with an error that is not retried");
+            return Err(error);
+        }
+
+        if let Some(delay) = strategy.next_delay() {
+            tracing::debug!(?delay, "attempt failed, waiting before retrying");
+            tokio::time::sleep(delay).await;
+        } else {
+            tracing::debug!("last attempt failed, returning last failure value");
+            return Err(error);
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use std::time::Duration;
@@ -290,4 +324,28 @@ mod tests {
         }
         assert_eq!(retries, actual);
     }
+
+    #[tokio::test]
+    async fn retry_while_stops_on_errors_that_are_not_retried() {
+        let strategy = ExponentialBackoff::new_with_seed(
+            Duration::from_millis(1),
+            Duration::from_millis(5),
+            Some(5),
+            42,
+        );
+        let mut attempts = 0;
+
+        let result: Result<(), u32> = super::retry_while(
+            strategy,
+            || {
+                attempts += 1;
+                std::future::ready(Err(attempts))
+            },
+            |error| *error < 3,
+        )
+        .await;
+
+        assert_eq!(result, Err(3));
+        assert_eq!(attempts, 3);
+    }
 }